@@ -0,0 +1,27 @@
+// Criterion benchmarks of one full poll cycle per backend: `CallValidator::sense` with
+// every subsystem due, then `CallTracker::update`
+//
+//   cargo bench --bench poll_cycle
+//
+// `platform` polls the audio and network backends of the machine it runs on; where they
+// cannot be reached (no sound server in a CI container) the cycle still runs and times
+// the error paths, so `mock` is the group to track across builds.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn platform(c: &mut Criterion) {
+    let mut cycle = rust_audio_validator::platform_cycle();
+    let mut group = c.benchmark_group("platform");
+    group.bench_function(std::env::consts::OS, |b| b.iter(&mut cycle));
+    group.finish();
+}
+
+fn mock(c: &mut Criterion) {
+    let mut cycle = rust_audio_validator::mock_cycle();
+    let mut group = c.benchmark_group("mock");
+    group.bench_function("scripted_call", |b| b.iter(&mut cycle));
+    group.finish();
+}
+
+criterion_group!(benches, platform, mock);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+// Build script: only does work when optional features need generated code
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/validator.proto");
+
+        // Use the vendored protoc so builds don't depend on a system install
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path()
+                .expect("vendored protoc not available for this host");
+            std::env::set_var("PROTOC", protoc);
+        }
+
+        tonic_build::compile_protos("proto/validator.proto")
+            .expect("failed to compile proto/validator.proto");
+    }
+}
@@ -0,0 +1,514 @@
+// `aggregate` subcommand: one combined state per user across several machines
+// For users with a desktop and a laptop where either might host the call. Each monitor
+// labels its lines with --origin-user (and --origin-machine, the host name by default)
+// and sends them here:
+//
+//   VALIDATOR_WEBHOOK_SECRET=... rust-audio-validator aggregate [--listen 127.0.0.1:9000]
+//       [--http 0.0.0.0:9001] [--handoff-secs 30] [--stale-secs 180]
+//
+// `--listen` takes socket sinks ({"sink": "socket", "address": "<aggregator>:9000"}), which
+// send the state every tick; a socket that disconnects takes its machine's call with it.
+// Socket lines are not signed, so `--listen` only binds loopback (a local monitor, or a
+// tunnel). `--http` takes webhook sinks (POST, signed with VALIDATOR_WEBHOOK_SECRET) and
+// answers GET /state from loopback with the combined state; it binds other addresses only
+// with a secret set, and then refuses unsigned POSTs. Each machine's call is
+// its latest `state`, `call_started` or `call_ended`. Per user the combined state reports
+// the call on whichever machine hosts it (the most confident one when several do) and
+// keeps reporting it for --handoff-secs after it ends there, so moving the call to the
+// other machine does not read as a new call. A machine not heard from for --stale-secs
+// (crashed, asleep, off the network) is taken out of its call, as if it had sent
+// `call_ended`; webhook sinks need a snapshot keepalive shorter than that. Lines without an origin count as user
+// "default" on the sending address. An `aggregate_state` line goes to stdout whenever a
+// user's call or machines change.
+
+use crate::browser_bridge;
+use crate::output::{utc_timestamp, Envelope, EventType, Origin};
+use crate::webhook;
+use crate::CallInfo;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const USAGE: &str =
+    "Usage: rust-audio-validator aggregate [--listen <loopback addr>] [--http <addr>] [--handoff-secs 30] [--stale-secs 180]";
+
+/// Longest socket line accepted, in bytes
+const MAX_LINE: u64 = 256 * 1024;
+
+/// How long a user's call outlives its machine's `call_ended`, waiting for another machine
+const DEFAULT_HANDOFF_SECS: u64 = 30;
+/// How long a machine may stay silent before its call counts as over
+const DEFAULT_STALE_SECS: u64 = 180;
+/// User of lines that carry no origin
+const DEFAULT_USER: &str = "default";
+
+/// How a machine's lines arrive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    Socket,
+    Webhook,
+}
+
+/// Combined state of every user heard from
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AggregateState {
+    pub users: Vec<UserState>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserState {
+    pub user: String,
+    /// The call on whichever machine hosts it, also while a handoff is pending
+    pub active_call: Option<HostedCall>,
+    /// RFC3339 in UTC: when the user's call started, across handoffs between machines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_since_utc: Option<String>,
+    /// The call ended on its machine and no other machine has picked it up yet
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pending_handoff: bool,
+    pub machines: Vec<MachineStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HostedCall {
+    pub machine: String,
+    #[serde(flatten)]
+    pub call: CallInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MachineStatus {
+    pub machine: String,
+    pub transport: Transport,
+    pub in_call: bool,
+    /// RFC3339 in UTC
+    pub last_seen_utc: String,
+}
+
+#[derive(Debug)]
+struct Machine {
+    transport: Transport,
+    call: Option<CallInfo>,
+    last_seen: SystemTime,
+}
+
+#[derive(Debug, Default)]
+struct User {
+    machines: BTreeMap<String, Machine>,
+    /// The call last hosted, with its machine
+    hosted: Option<(String, CallInfo)>,
+    call_since: Option<SystemTime>,
+    /// When the hosting machine's call ended, while no other machine has one
+    ended_at: Option<SystemTime>,
+}
+
+/// Merges machines' lines per user
+pub struct Aggregator {
+    users: BTreeMap<String, User>,
+    handoff: Duration,
+    stale: Duration,
+}
+
+impl Aggregator {
+    pub fn new(handoff: Duration, stale: Duration) -> Self {
+        Aggregator { users: BTreeMap::new(), handoff, stale }
+    }
+
+    /// Apply one NDJSON line from `origin`
+    pub fn apply(&mut self, origin: &Origin, transport: Transport, line: &Value, now: SystemTime) {
+        let payload = line.get("payload").cloned().unwrap_or(Value::Null);
+        let call = match line.get("event_type").and_then(Value::as_str) {
+            Some("state") => Some(serde_json::from_value::<Option<CallInfo>>(
+                payload.get("active_call").cloned().unwrap_or(Value::Null),
+            )),
+            Some("call_started") => Some(serde_json::from_value::<CallInfo>(payload).map(Some)),
+            Some("call_ended") => Some(Ok(None)),
+            _ => None,
+        };
+
+        let user = self.users.entry(origin.user.clone()).or_default();
+        let machine = user.machines.entry(origin.machine.clone()).or_insert(Machine {
+            transport,
+            call: None,
+            last_seen: now,
+        });
+        machine.transport = transport;
+        machine.last_seen = now;
+        if let Some(Ok(call)) = call {
+            machine.call = call;
+        }
+        self.settle(&origin.user, now);
+    }
+
+    /// `origin`'s connection closed; whatever call it had is over
+    pub fn disconnect(&mut self, origin: &Origin, now: SystemTime) {
+        let Some(user) = self.users.get_mut(&origin.user) else { return };
+        if let Some(machine) = user.machines.get_mut(&origin.machine) {
+            machine.call = None;
+        }
+        self.settle(&origin.user, now);
+    }
+
+    /// End the calls of machines gone quiet, and calls whose handoff window passed
+    pub fn expire(&mut self, now: SystemTime) {
+        let stale = self.stale;
+        for user in self.users.values_mut() {
+            for machine in user.machines.values_mut() {
+                if now.duration_since(machine.last_seen).unwrap_or_default() >= stale {
+                    machine.call = None;
+                }
+            }
+        }
+        let users: Vec<String> = self.users.keys().cloned().collect();
+        for user in users {
+            self.settle(&user, now);
+        }
+    }
+
+    fn settle(&mut self, name: &str, now: SystemTime) {
+        let handoff = self.handoff;
+        let Some(user) = self.users.get_mut(name) else { return };
+
+        let hosting = user
+            .machines
+            .iter()
+            .filter_map(|(machine, state)| state.call.as_ref().map(|call| (machine, call)))
+            .max_by(|a, b| a.1.confidence.partial_cmp(&b.1.confidence).unwrap_or(std::cmp::Ordering::Equal));
+
+        match hosting {
+            Some((machine, call)) => {
+                if user.call_since.is_none() {
+                    let started = chrono::DateTime::parse_from_rfc3339(&call.started_at_utc)
+                        .map(SystemTime::from)
+                        .unwrap_or(now);
+                    user.call_since = Some(started);
+                }
+                user.hosted = Some((machine.clone(), call.clone()));
+                user.ended_at = None;
+            }
+            None if user.hosted.is_some() => {
+                let ended_at = *user.ended_at.get_or_insert(now);
+                if now.duration_since(ended_at).unwrap_or_default() >= handoff {
+                    user.hosted = None;
+                    user.call_since = None;
+                    user.ended_at = None;
+                }
+            }
+            None => {}
+        }
+    }
+
+    pub fn state(&self) -> AggregateState {
+        let users = self
+            .users
+            .iter()
+            .map(|(name, user)| UserState {
+                user: name.clone(),
+                active_call: user
+                    .hosted
+                    .as_ref()
+                    .map(|(machine, call)| HostedCall { machine: machine.clone(), call: call.clone() }),
+                call_since_utc: user.call_since.map(utc_timestamp),
+                pending_handoff: user.ended_at.is_some(),
+                machines: user
+                    .machines
+                    .iter()
+                    .map(|(machine, state)| MachineStatus {
+                        machine: machine.clone(),
+                        transport: state.transport,
+                        in_call: state.call.is_some(),
+                        last_seen_utc: utc_timestamp(state.last_seen),
+                    })
+                    .collect(),
+            })
+            .collect();
+        AggregateState { users }
+    }
+}
+
+/// User, hosting machine and app, call start, pending handoff, machines in a call
+type UserSummary = (String, Option<(String, String)>, Option<String>, bool, Vec<(String, bool)>);
+
+impl AggregateState {
+    /// What a consumer acts on: per user the hosting machine, app and start, and which
+    /// machines are in a call; not confidences, durations or last-seen times
+    fn summary(&self) -> Vec<UserSummary> {
+        self.users
+            .iter()
+            .map(|user| {
+                (
+                    user.user.clone(),
+                    user.active_call.as_ref().map(|hosted| (hosted.machine.clone(), hosted.call.app.clone())),
+                    user.call_since_utc.clone(),
+                    user.pending_handoff,
+                    user.machines.iter().map(|machine| (machine.machine.clone(), machine.in_call)).collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// What the listener threads hand to the merge loop
+enum Input {
+    Line { peer: SocketAddr, transport: Transport, line: Value },
+    /// A socket closed; these origins sent on it
+    Closed(Vec<Origin>),
+}
+
+/// `origin` of `line`, or user "default" on `peer`'s address
+fn origin_of(line: &Value, peer: SocketAddr) -> Origin {
+    line.get("origin")
+        .and_then(|origin| serde_json::from_value::<Origin>(origin.clone()).ok())
+        .unwrap_or_else(|| Origin { user: DEFAULT_USER.to_string(), machine: peer.ip().to_string() })
+}
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+
+    let parse_addr = |name: &str| -> Result<Option<SocketAddr>, String> {
+        option(name)
+            .map(|addr| addr.parse().map_err(|e| format!("invalid {} address '{}': {}", name, addr, e)))
+            .transpose()
+    };
+    let (listen, http) = match (parse_addr("--listen"), parse_addr("--http")) {
+        (Ok(listen), Ok(http)) if listen.is_some() || http.is_some() => (listen, http),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("[aggregate] {}", e);
+            return 2;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+    let handoff = match option("--handoff-secs").map(|secs| secs.parse::<u64>()) {
+        Some(Ok(secs)) => Duration::from_secs(secs),
+        Some(Err(_)) => {
+            eprintln!("[aggregate] Invalid --handoff-secs, expected whole seconds");
+            return 2;
+        }
+        None => Duration::from_secs(DEFAULT_HANDOFF_SECS),
+    };
+    let stale = match option("--stale-secs").map(|secs| secs.parse::<u64>()) {
+        Some(Ok(secs)) if secs > 0 => Duration::from_secs(secs),
+        Some(_) => {
+            eprintln!("[aggregate] Invalid --stale-secs, expected whole seconds above 0");
+            return 2;
+        }
+        None => Duration::from_secs(DEFAULT_STALE_SECS),
+    };
+    let secret = std::env::var("VALIDATOR_WEBHOOK_SECRET").ok().filter(|secret| !secret.is_empty());
+    if listen.is_some_and(|addr| !addr.ip().is_loopback()) {
+        eprintln!("[aggregate] Socket sinks cannot sign their lines; --listen only binds loopback addresses");
+        return 2;
+    }
+    if http.is_some_and(|addr| !addr.ip().is_loopback()) && secret.is_none() {
+        eprintln!("[aggregate] Set VALIDATOR_WEBHOOK_SECRET to take webhooks on a non-loopback --http address");
+        return 2;
+    }
+
+    let (inputs, received) = mpsc::channel();
+    let published = Arc::new(Mutex::new(String::from("{\"users\":[]}")));
+
+    if let Some(addr) = listen {
+        match TcpListener::bind(addr) {
+            Ok(listener) => {
+                let inputs = inputs.clone();
+                thread::spawn(move || serve_sockets(listener, inputs));
+                eprintln!("[aggregate] Socket sinks on {}", addr);
+            }
+            Err(e) => {
+                eprintln!("[aggregate] Cannot listen on {}: {}", addr, e);
+                return 1;
+            }
+        }
+    }
+    if let Some(addr) = http {
+        match TcpListener::bind(addr) {
+            Ok(listener) => {
+                let inputs = inputs.clone();
+                let published = Arc::clone(&published);
+                thread::spawn(move || serve_http(listener, inputs, published, secret));
+                eprintln!("[aggregate] Webhooks and GET /state on {}", addr);
+            }
+            Err(e) => {
+                eprintln!("[aggregate] Cannot listen on {}: {}", addr, e);
+                return 1;
+            }
+        }
+    }
+    drop(inputs);
+
+    let mut aggregator = Aggregator::new(handoff, stale);
+    let mut last_summary = None;
+    loop {
+        let now = SystemTime::now();
+        match received.recv_timeout(Duration::from_secs(1)) {
+            Ok(Input::Line { peer, transport, line }) => {
+                aggregator.apply(&origin_of(&line, peer), transport, &line, now)
+            }
+            Ok(Input::Closed(origins)) => origins.iter().for_each(|origin| aggregator.disconnect(origin, now)),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return 1,
+        }
+        aggregator.expire(now);
+
+        let state = aggregator.state();
+        if let Ok(json) = serde_json::to_string(&state) {
+            *published.lock().unwrap() = json;
+        }
+        let summary = state.summary();
+        if last_summary.as_ref() != Some(&summary) {
+            if let Ok(line) = Envelope::new(EventType::AggregateState, &state).to_json_line() {
+                println!("{}", line);
+            }
+            last_summary = Some(summary);
+        }
+    }
+}
+
+/// One thread per socket sink connection, reading NDJSON lines
+fn serve_sockets(listener: TcpListener, inputs: Sender<Input>) {
+    let inputs = Mutex::new(inputs);
+    browser_bridge::serve(listener, move |stream| {
+        let inputs = inputs.lock().unwrap().clone();
+        let Ok(peer) = stream.peer_addr() else { return };
+        let mut reader = BufReader::new(stream);
+        let mut origins = BTreeSet::new();
+        loop {
+            let mut line = Vec::new();
+            match reader.by_ref().take(MAX_LINE).read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                // A line longer than MAX_LINE ends the connection
+                Ok(_) if !line.ends_with(b"\n") => break,
+                Ok(_) => {}
+            }
+            let Ok(line) = serde_json::from_slice::<Value>(&line) else { continue };
+            let origin = origin_of(&line, peer);
+            origins.insert((origin.user, origin.machine));
+            if inputs.send(Input::Line { peer, transport: Transport::Socket, line }).is_err() {
+                return;
+            }
+        }
+        let origins = origins.into_iter().map(|(user, machine)| Origin { user, machine }).collect();
+        let _ = inputs.send(Input::Closed(origins));
+    });
+}
+
+/// Webhook POSTs in, GET /state out
+fn serve_http(listener: TcpListener, inputs: Sender<Input>, published: Arc<Mutex<String>>, secret: Option<String>) {
+    let inputs = Mutex::new(inputs);
+    browser_bridge::serve(listener, move |stream| {
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+        let (Ok(peer), Ok(mut writer)) = (stream.peer_addr(), stream.try_clone()) else { return };
+
+        let (status, body) = match browser_bridge::read_request(&stream) {
+            Ok(request) if request.method == "GET" && request.path == "/state" => {
+                if peer.ip().is_loopback() {
+                    ("200 OK", published.lock().unwrap().clone())
+                } else {
+                    ("403 Forbidden", String::new())
+                }
+            }
+            Ok(request) if request.method == "POST" => {
+                let signed = secret.as_deref().map_or(true, |secret| {
+                    let header = webhook::SIGNATURE_HEADER.to_lowercase();
+                    request.headers.get(&header).is_some_and(|signature| {
+                        webhook::verify(secret, &request.body, signature)
+                    })
+                });
+                match serde_json::from_slice::<Value>(&request.body) {
+                    _ if !signed => ("401 Unauthorized", String::new()),
+                    Ok(line) => {
+                        let _ = inputs.lock().unwrap().send(Input::Line { peer, transport: Transport::Webhook, line });
+                        ("204 No Content", String::new())
+                    }
+                    Err(_) => ("400 Bad Request", String::new()),
+                }
+            }
+            Ok(_) => ("404 Not Found", String::new()),
+            Err(_) => ("400 Bad Request", String::new()),
+        };
+
+        let content_type = if body.is_empty() { "" } else { "Content-Type: application/json\r\n" };
+        let _ = write!(
+            writer,
+            "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn started(app: &str, confidence: f32) -> Value {
+        json!({
+            "event_type": "call_started",
+            "payload": {
+                "app": app, "process_id": 42, "window_title": "", "has_mic": true, "has_audio": true,
+                "has_webrtc": true, "confidence": confidence, "started_at_utc": "2025-01-01T09:00:00Z"
+            }
+        })
+    }
+
+    #[test]
+    fn test_call_follows_the_user_across_machines() {
+        let desktop = Origin { user: "alice".to_string(), machine: "desktop".to_string() };
+        let laptop = Origin { user: "alice".to_string(), machine: "laptop".to_string() };
+        let at = |secs: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(1_735_722_000 + secs);
+        let mut aggregator = Aggregator::new(Duration::from_secs(30), Duration::from_secs(180));
+        let hosting = |aggregator: &Aggregator| {
+            let user = aggregator.state().users.remove(0);
+            (user.active_call.map(|hosted| hosted.machine), user.call_since_utc, user.pending_handoff)
+        };
+
+        aggregator.apply(&desktop, Transport::Socket, &started("Zoom", 0.8), at(0));
+        let since = Some("2025-01-01T09:00:00Z".to_string());
+        assert_eq!(hosting(&aggregator), (Some("desktop".to_string()), since.clone(), false));
+
+        // Joined on both: the more confident machine hosts it
+        aggregator.apply(&laptop, Transport::Webhook, &started("Zoom", 0.9), at(60));
+        assert_eq!(hosting(&aggregator).0.as_deref(), Some("laptop"));
+
+        // Left on the laptop: the desktop still hosts it; then its socket dropped
+        aggregator.apply(&laptop, Transport::Webhook, &json!({"event_type": "call_ended", "payload": {}}), at(120));
+        assert_eq!(hosting(&aggregator), (Some("desktop".to_string()), since.clone(), false));
+        aggregator.disconnect(&desktop, at(125));
+        assert_eq!(hosting(&aggregator), (Some("desktop".to_string()), since.clone(), true));
+
+        // Picked up on the laptop within the handoff window: still the call from 09:00
+        let call = started("Zoom", 0.7)["payload"].clone();
+        aggregator.apply(&laptop, Transport::Socket, &json!({"event_type": "state", "payload": {"active_call": call}}), at(140));
+        assert_eq!(hosting(&aggregator), (Some("laptop".to_string()), since, false));
+
+        // Gone everywhere for longer than the handoff window: over
+        aggregator.disconnect(&laptop, at(200));
+        aggregator.expire(at(229));
+        assert!(hosting(&aggregator).0.is_some());
+        aggregator.expire(at(230));
+        assert_eq!(hosting(&aggregator), (None, None, false));
+
+        // A webhook machine that crashed mid-call never sends call_ended: silent too long
+        aggregator.apply(&laptop, Transport::Webhook, &started("Zoom", 0.9), at(300));
+        aggregator.expire(at(479));
+        assert!(!hosting(&aggregator).2);
+        aggregator.expire(at(480));
+        assert!(hosting(&aggregator).2);
+        aggregator.expire(at(510));
+        assert_eq!(hosting(&aggregator), (None, None, false));
+    }
+}
@@ -0,0 +1,230 @@
+// ALSA-only fallback for Linux systems without a sound server
+// Kiosk and minimal builds often run without PulseAudio or PipeWire, where every Pulse
+// query fails. When no Pulse server socket answers, the Linux backend switches to this
+// one: volume and mute from `amixer`, the sound cards from /proc/asound/cards, and the
+// apps from the running PCM substreams (/proc/asound/card*/pcm*/sub*/status), whose
+// owner_pid names the process playing or recording. ALSA has no per-app volume or meters.
+
+use super::{AudioAppSession, AudioInfo, DeviceUsage, MicAppSession, OutputDevice, OutputFormFactor};
+use crate::error::ValidatorError;
+use std::fs;
+use std::os::unix::net::UnixStream;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Simple mixer controls tried in order; USB cards often have no Master
+const PLAYBACK_CONTROLS: &[&str] = &["Master", "PCM", "Speaker", "Headphone"];
+const CAPTURE_CONTROLS: &[&str] = &["Capture", "Mic"];
+
+/// How long the Pulse reachability check is trusted
+const PULSE_RECHECK: Duration = Duration::from_secs(5);
+
+static PULSE_CHECK: Mutex<Option<(Instant, bool)>> = Mutex::new(None);
+
+/// Whether the Linux backend should read ALSA directly: no Pulse server answers and
+/// the kernel has sound cards
+pub fn is_fallback() -> bool {
+    let mut check = PULSE_CHECK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let reachable = match *check {
+        Some((at, reachable)) if at.elapsed() < PULSE_RECHECK => reachable,
+        _ => {
+            let reachable = pulse_server_reachable();
+            *check = Some((Instant::now(), reachable));
+            reachable
+        }
+    };
+    !reachable && !cards().is_empty()
+}
+
+/// Probe for a Pulse server again on the next check
+pub fn recheck() {
+    PULSE_CHECK.clear_poison();
+    *PULSE_CHECK.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+// The native socket of PulseAudio or pipewire-pulse: $PULSE_SERVER, the user's runtime
+// directory, then a system-wide daemon
+fn pulse_server_reachable() -> bool {
+    if let Ok(server) = std::env::var("PULSE_SERVER") {
+        return match server.strip_prefix("unix:") {
+            Some(path) => UnixStream::connect(path).is_ok(),
+            // tcp: and remote servers are left to libpulse
+            None => true,
+        };
+    }
+    let user_socket = std::env::var("XDG_RUNTIME_DIR").ok().map(|dir| format!("{}/pulse/native", dir));
+    user_socket
+        .into_iter()
+        .chain(std::iter::once("/run/pulse/native".to_string()))
+        .any(|path| UnixStream::connect(path).is_ok())
+}
+
+pub fn output_volume() -> Result<AudioInfo, ValidatorError> {
+    mixer_level(PLAYBACK_CONTROLS)
+}
+
+pub fn capture_volume() -> Result<AudioInfo, ValidatorError> {
+    mixer_level(CAPTURE_CONTROLS)
+}
+
+/// The first card, which is ALSA's default without an asoundrc
+pub fn device() -> Result<OutputDevice, ValidatorError> {
+    let name = cards()
+        .into_iter()
+        .next()
+        .ok_or_else(|| ValidatorError::BackendUnavailable("ALSA sound card".to_string()))?;
+    Ok(OutputDevice {
+        form_factor: OutputFormFactor::from_hints(&[&name]),
+        is_virtual: super::is_virtual_device(&[&name]),
+        name,
+    })
+}
+
+pub fn apps_playing() -> Result<Vec<AudioAppSession>, ValidatorError> {
+    let device = device()?;
+    let mut sessions: Vec<AudioAppSession> = Vec::new();
+    for process_id in running_streams(false) {
+        if sessions.iter().any(|session| session.process_id == process_id) {
+            continue;
+        }
+        let name = process_name(process_id);
+        sessions.push(AudioAppSession {
+            window_title: crate::platform::linux::get_window_title(process_id).unwrap_or_else(|_| name.clone()),
+            name,
+            volume: 100.0,
+            is_active: true,
+            peak_level: 0.0,
+            is_muted: false,
+            process_id,
+            output_device: Some(device.clone()),
+        });
+    }
+    Ok(sessions)
+}
+
+pub fn apps_recording() -> Result<Vec<MicAppSession>, ValidatorError> {
+    let mut pids = running_streams(true);
+    pids.sort_unstable();
+    pids.dedup();
+    Ok(pids
+        .into_iter()
+        .map(|process_id| MicAppSession { name: process_name(process_id), process_id, device: None })
+        .collect())
+}
+
+pub fn capture_devices() -> Result<Vec<DeviceUsage>, ValidatorError> {
+    let device = device()?;
+    Ok(vec![DeviceUsage {
+        name: device.name,
+        form_factor: device.form_factor,
+        is_virtual: device.is_virtual,
+        is_default: true,
+        apps: apps_recording()?.into_iter().map(|session| session.name).collect(),
+    }])
+}
+
+fn process_name(process_id: u32) -> String {
+    <() as crate::platform::PlatformUtils>::get_process_name(process_id).unwrap_or_else(|_| format!("Process_{}", process_id))
+}
+
+/// Card descriptions from /proc/asound/cards
+pub fn cards() -> Vec<String> {
+    parse_cards(&fs::read_to_string("/proc/asound/cards").unwrap_or_default())
+}
+
+// " 0 [PCH            ]: HDA-Intel - HDA Intel PCH" (a second, indented line follows)
+fn parse_cards(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|line| line.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+        .filter_map(|line| line.split_once(" - ").map(|(_, name)| name.trim().to_string()))
+        .collect()
+}
+
+// Owner pids of the running playback or capture substreams
+fn running_streams(capture: bool) -> Vec<u32> {
+    let suffix = if capture { 'c' } else { 'p' };
+    let mut pids = Vec::new();
+    let Ok(cards) = fs::read_dir("/proc/asound") else { return pids };
+    for card in cards.flatten().filter(|entry| entry.file_name().to_string_lossy().starts_with("card")) {
+        let Ok(pcms) = fs::read_dir(card.path()) else { continue };
+        for pcm in pcms.flatten() {
+            let name = pcm.file_name().to_string_lossy().to_string();
+            if !name.starts_with("pcm") || !name.ends_with(suffix) {
+                continue;
+            }
+            let Ok(substreams) = fs::read_dir(pcm.path()) else { continue };
+            for substream in substreams.flatten() {
+                let status = fs::read_to_string(substream.path().join("status")).unwrap_or_default();
+                pids.extend(running_owner(&status));
+            }
+        }
+    }
+    pids
+}
+
+// "state: RUNNING" and "owner_pid   : 1234"; a closed substream reads "closed"
+fn running_owner(status: &str) -> Option<u32> {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.split_once(':').filter(|(key, _)| key.trim() == name).map(|(_, value)| value.trim()))
+    };
+    matches!(field("state")?, "RUNNING" | "DRAINING").then_some(())?;
+    field("owner_pid")?.parse().ok().filter(|&pid| pid > 0)
+}
+
+fn mixer_level(controls: &[&str]) -> Result<AudioInfo, ValidatorError> {
+    for control in controls {
+        // -M: mapped volume, the percentage alsamixer shows
+        let output = crate::subprocess::output(Command::new("amixer").args(["-M", "get", control]))
+            .map_err(|e| ValidatorError::spawn("amixer", e))?;
+        // amixer fails for controls the card does not have
+        if !output.status.success() {
+            continue;
+        }
+        if let Some(info) = parse_amixer(&String::from_utf8_lossy(&output.stdout)) {
+            return Ok(info);
+        }
+    }
+    Err(ValidatorError::NotFound(format!("ALSA mixer control {}", controls.join("/"))))
+}
+
+// "  Front Left: Playback 60 [69%] [-20.25dB] [on]"; muted when every channel is [off]
+fn parse_amixer(text: &str) -> Option<AudioInfo> {
+    let mut volume: Option<f32> = None;
+    let mut any_on = false;
+    let mut any_switch = false;
+    for line in text.lines().filter(|line| line.contains('%')) {
+        for field in line.split('[').filter_map(|field| field.split_once(']').map(|(value, _)| value)) {
+            if let Some(percent) = field.strip_suffix('%').and_then(|percent| percent.parse::<f32>().ok()) {
+                volume = Some(volume.map_or(percent, |volume| volume.max(percent)));
+            } else if field == "on" || field == "off" {
+                any_switch = true;
+                any_on |= field == "on";
+            }
+        }
+    }
+    let volume = volume?;
+    Some(AudioInfo { volume, is_muted: (any_switch && !any_on) || volume == 0.0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alsa_mixer_cards_and_streams() {
+        let amixer = "Simple mixer control 'Master',0\n  Capabilities: pvolume pswitch\n  Playback channels: Front Left - Front Right\n  Front Left: Playback 60 [69%] [-20.25dB] [off]\n  Front Right: Playback 60 [71%] [-20.25dB] [off]\n";
+        let info = parse_amixer(amixer).expect("volume parsed");
+        assert_eq!(info.volume, 71.0);
+        assert!(info.is_muted);
+
+        let cards = " 0 [PCH            ]: HDA-Intel - HDA Intel PCH\n                      HDA Intel PCH at 0xf7f10000 irq 32\n 1 [Device         ]: USB-Audio - USB Audio Device\n";
+        assert_eq!(parse_cards(cards), vec!["HDA Intel PCH".to_string(), "USB Audio Device".to_string()]);
+
+        assert_eq!(running_owner("state: RUNNING\nowner_pid   : 1234\ntrigger_time: 1.0\n"), Some(1234));
+        assert_eq!(running_owner("state: PREPARED\nowner_pid   : 1234\n"), None);
+        assert_eq!(running_owner("closed\n"), None);
+    }
+}
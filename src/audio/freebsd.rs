@@ -0,0 +1,253 @@
+// FreeBSD audio backend using OSS and sndio
+// Volumes come from the OSS mixer (/dev/mixer ioctls), or from sndioctl when sndiod is
+// running, which also reports mute and a level per client. OSS has no per-app sessions:
+// the apps come from the open channels in /dev/sndstat, which carry the pid and command
+// of the process that opened them (listed with hw.snd.verbose=2 or higher).
+
+use super::{AudioAppSession, AudioBackend, AudioInfo, MicAppSession, OutputDevice, OutputFormFactor, SystemAudio};
+use crate::error::ValidatorError;
+use std::fs::File;
+use std::os::fd::AsRawFd;
+use std::process::Command;
+
+/// OSS mixer channels (sys/soundcard.h)
+const SOUND_MIXER_VOLUME: u32 = 0;
+const SOUND_MIXER_MIC: u32 = 7;
+
+/// MIXER_READ(channel): _IOR('M', channel, int)
+const fn mixer_read(channel: u32) -> libc::c_ulong {
+    0x4004_4d00 | channel as libc::c_ulong
+}
+
+impl AudioBackend for SystemAudio {
+    fn get_microphone_volume_and_mute(&self) -> Result<AudioInfo, ValidatorError> {
+        match sndio_controls() {
+            Some(controls) if controls.input_level.is_some() => Ok(AudioInfo {
+                volume: controls.input_level.unwrap_or(0.0),
+                is_muted: controls.input_muted,
+            }),
+            _ => oss_level(SOUND_MIXER_MIC),
+        }
+    }
+
+    fn get_microphone_device_name(&self) -> Result<String, ValidatorError> {
+        Ok(default_device()?.name)
+    }
+
+    fn get_apps_using_microphone(&self) -> Result<Vec<MicAppSession>, ValidatorError> {
+        Ok(open_channels()?
+            .into_iter()
+            .filter(|channel| channel.is_recording)
+            .map(|channel| MicAppSession { name: channel.command, process_id: channel.pid, device: None })
+            .collect())
+    }
+
+    fn get_audio_output_volume_and_mute(&self) -> Result<AudioInfo, ValidatorError> {
+        match sndio_controls() {
+            Some(controls) if controls.output_level.is_some() => Ok(AudioInfo {
+                volume: controls.output_level.unwrap_or(0.0),
+                is_muted: controls.output_muted,
+            }),
+            _ => oss_level(SOUND_MIXER_VOLUME),
+        }
+    }
+
+    fn get_audio_output_device_name(&self) -> Result<String, ValidatorError> {
+        Ok(default_device()?.name)
+    }
+
+    fn get_audio_output_device(&self) -> Result<OutputDevice, ValidatorError> {
+        default_device()
+    }
+
+    fn get_audio_output_peak_level(&self) -> Result<f32, ValidatorError> {
+        // Neither OSS nor sndio meters the output
+        Ok(0.0)
+    }
+
+    fn get_apps_playing_audio(&self) -> Result<Vec<AudioAppSession>, ValidatorError> {
+        let clients = sndio_controls().map(|controls| controls.clients).unwrap_or_default();
+        let output_device = default_device().ok();
+
+        let mut sessions: Vec<AudioAppSession> = Vec::new();
+        for channel in open_channels()?.into_iter().filter(|channel| !channel.is_recording) {
+            if sessions.iter().any(|session| session.process_id == channel.pid) {
+                continue;
+            }
+            // sndiod is the process holding the device; its clients are listed by sndioctl
+            if channel.command == "sndiod" {
+                continue;
+            }
+            sessions.push(session(channel.command, channel.pid, 100.0, output_device.clone()));
+        }
+        // sndio clients have a name but no pid; the pipeline resolves them by name
+        for (name, level) in clients {
+            if !sessions.iter().any(|session| session.name == name) {
+                sessions.push(session(name, 0, level, output_device.clone()));
+            }
+        }
+        Ok(sessions)
+    }
+}
+
+fn session(name: String, process_id: u32, volume: f32, output_device: Option<OutputDevice>) -> AudioAppSession {
+    let window_title = match process_id {
+        0 => name.clone(),
+        pid => crate::platform::freebsd::get_window_title(pid).unwrap_or_else(|_| name.clone()),
+    };
+    AudioAppSession {
+        name,
+        volume,
+        is_active: true,
+        peak_level: 0.0,
+        is_muted: volume <= 0.0,
+        process_id,
+        window_title,
+        output_device,
+    }
+}
+
+// Volume of an OSS mixer channel; OSS has no mute, a channel at zero counts as muted
+fn oss_level(channel: u32) -> Result<AudioInfo, ValidatorError> {
+    let mixer = File::open("/dev/mixer").map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ValidatorError::BackendUnavailable("OSS mixer (/dev/mixer)".to_string()),
+        _ => e.into(),
+    })?;
+
+    let mut value: libc::c_int = 0;
+    let result = unsafe { libc::ioctl(mixer.as_raw_fd(), mixer_read(channel), &mut value) };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    // Left channel in the low byte, right in the next, 0-100 each
+    let volume = (value & 0x7f).max((value >> 8) & 0x7f) as f32;
+    Ok(AudioInfo { volume, is_muted: volume == 0.0 })
+}
+
+/// What `sndioctl` reports while sndiod runs
+#[derive(Debug, Default, PartialEq)]
+struct SndioControls {
+    /// 0.0-100.0
+    output_level: Option<f32>,
+    output_muted: bool,
+    input_level: Option<f32>,
+    input_muted: bool,
+    /// Connected clients with their level, 0.0-100.0
+    clients: Vec<(String, f32)>,
+}
+
+fn sndio_controls() -> Option<SndioControls> {
+    let output = crate::subprocess::output(&mut Command::new("sndioctl")).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_sndioctl(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// "output.level=0.502", "output.mute=0", "app/firefox0.level=1.000"
+fn parse_sndioctl(text: &str) -> SndioControls {
+    let mut controls = SndioControls::default();
+    for line in text.lines() {
+        let Some((control, value)) = line.trim().split_once('=') else { continue };
+        let level = value.parse::<f32>().ok().map(|level| level * 100.0);
+        match control {
+            "output.level" => controls.output_level = level,
+            "output.mute" => controls.output_muted = value == "1",
+            "input.level" => controls.input_level = level,
+            "input.mute" => controls.input_muted = value == "1",
+            _ => {
+                let client = control.strip_prefix("app/").and_then(|client| client.strip_suffix(".level"));
+                if let (Some(client), Some(level)) = (client, level) {
+                    // sndiod numbers the instances of an app: firefox0, firefox1
+                    let name = client.trim_end_matches(|c: char| c.is_ascii_digit()).to_string();
+                    controls.clients.push((name, level));
+                }
+            }
+        }
+    }
+    controls
+}
+
+/// A pcm channel opened by a process
+#[derive(Debug, Clone, PartialEq)]
+struct OpenChannel {
+    pid: u32,
+    command: String,
+    is_recording: bool,
+}
+
+fn read_sndstat() -> Result<String, ValidatorError> {
+    std::fs::read_to_string("/dev/sndstat").map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ValidatorError::BackendUnavailable("sound(4) (/dev/sndstat)".to_string()),
+        _ => e.into(),
+    })
+}
+
+fn open_channels() -> Result<Vec<OpenChannel>, ValidatorError> {
+    Ok(parse_channels(&read_sndstat()?))
+}
+
+// "[pcm0:play:dsp0.p0]: spd 48000, fmt 0x00200010, flags 0x2000010c, 0x00000001, pid 1234 (firefox)"
+// Virtual channels read "[pcm0:virtual_play:dsp0.vp0]" and "[pcm0:virtual_record:dsp0.vr0]"
+fn parse_channels(sndstat: &str) -> Vec<OpenChannel> {
+    sndstat
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (name, rest) = line.strip_prefix('[')?.split_once("]:")?;
+            let direction = name.split(':').nth(1)?;
+            let (_, owner) = rest.rsplit_once("pid ")?;
+            let (pid, command) = owner.split_once(' ')?;
+            let pid = pid.trim_end_matches(',').parse::<u32>().ok().filter(|&pid| pid > 0)?;
+            let command = command.trim().trim_start_matches('(').trim_end_matches(')').to_string();
+            Some(OpenChannel { pid, command, is_recording: direction.contains("rec") })
+        })
+        .collect()
+}
+
+fn default_device() -> Result<OutputDevice, ValidatorError> {
+    let sndstat = read_sndstat()?;
+    let name = parse_default_device(&sndstat).ok_or_else(|| ValidatorError::NotFound("default pcm device".to_string()))?;
+    Ok(OutputDevice {
+        form_factor: OutputFormFactor::from_hints(&[&name]),
+        is_virtual: super::is_virtual_device(&[&name]),
+        name,
+    })
+}
+
+// "pcm0: <Realtek ALC892 (Rear Analog)> (play/rec) default"
+fn parse_default_device(sndstat: &str) -> Option<String> {
+    let devices: Vec<&str> = sndstat.lines().filter(|line| line.starts_with("pcm")).collect();
+    let line = devices.iter().find(|line| line.trim_end().ends_with("default")).or(devices.first())?;
+    let (_, description) = line.split_once('<')?;
+    Some(description.split_once('>')?.0.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sndstat_channels_and_sndioctl() {
+        let sndstat = "Installed devices:\n\
+            pcm0: <Realtek ALC892 (Rear Analog)> (play/rec)\n\
+            pcm1: <USB audio> (play/rec) default\n\
+            \t[pcm1:play:dsp1.p0]: spd 48000, fmt 0x00200010, flags 0x2000010c, 0x00000001, pid 1234 (chrome)\n\
+            \t[pcm1:record:dsp1.r0]: spd 48000, fmt 0x00200010, flags 0x2000010c, 0x00000001, pid 1234 (chrome)\n\
+            \t[pcm1:play:dsp1.p1]: spd 48000, fmt 0x00200010, flags 0x00000000, 0x00000000, pid -1 ()\n";
+        assert_eq!(parse_default_device(sndstat).as_deref(), Some("USB audio"));
+        assert_eq!(
+            parse_channels(sndstat),
+            vec![
+                OpenChannel { pid: 1234, command: "chrome".to_string(), is_recording: false },
+                OpenChannel { pid: 1234, command: "chrome".to_string(), is_recording: true },
+            ]
+        );
+
+        let controls = parse_sndioctl("input.level=1.000\ninput.mute=0\noutput.level=0.500\noutput.mute=1\napp/zoom0.level=1.000\n");
+        assert_eq!(controls.output_level, Some(50.0));
+        assert!(controls.output_muted);
+        assert_eq!(controls.clients, vec![("zoom".to_string(), 100.0)]);
+    }
+}
@@ -1,52 +1,108 @@
 // Linux audio backend using PulseAudio
-// This implementation provides audio monitoring for Linux systems with PulseAudio
+// This implementation provides audio monitoring for Linux systems with PulseAudio, and
+// falls back to ALSA (alsa.rs) where no sound server runs
 
-use super::{AudioAppSession, AudioBackend, AudioInfo};
+use super::alsa;
+use super::{is_virtual_device, AudioAppSession, AudioBackend, AudioInfo, CaptureSession, DeviceUsage, MicAppSession, OutputDevice, OutputFormFactor, SystemAudio};
+use crate::error::ValidatorError;
 use libpulse_binding as pulse;
 use libpulse_binding::callbacks::ListResult;
+use libpulse_binding::context::introspect::Introspector;
 use libpulse_binding::context::{Context, FlagSet as ContextFlagSet};
 use libpulse_binding::mainloop::threaded::Mainloop;
+use libpulse_binding::def::BufferAttr;
 use libpulse_binding::proplist::Proplist;
+use libpulse_binding::sample::{Format, Spec};
+use libpulse_binding::stream::{FlagSet as StreamFlagSet, PeekResult, Stream};
 use libpulse_binding::volume::{ChannelVolumes, Volume};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::ops::Deref;
 use std::process::Command;
 
 // Implement the AudioBackend trait for Linux
-impl AudioBackend for () {
-    fn get_microphone_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+// Without a reachable Pulse server (kiosk builds) every query goes to ALSA directly
+impl AudioBackend for SystemAudio {
+    fn get_microphone_volume_and_mute(&self) -> std::result::Result<AudioInfo, ValidatorError> {
+        if alsa::is_fallback() {
+            return alsa::capture_volume();
+        }
         get_microphone_volume_and_mute_impl()
     }
 
-    fn get_microphone_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_microphone_device_name(&self) -> std::result::Result<String, ValidatorError> {
+        if alsa::is_fallback() {
+            return Ok(alsa::device()?.name);
+        }
         get_microphone_device_name_impl()
     }
 
-    fn get_apps_using_microphone() -> std::result::Result<Vec<String>, Box<dyn std::error::Error>> {
+    fn get_apps_using_microphone(&self) -> std::result::Result<Vec<MicAppSession>, ValidatorError> {
+        if alsa::is_fallback() {
+            return alsa::apps_recording();
+        }
         get_apps_using_microphone_impl()
     }
 
-    fn get_audio_output_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+    fn get_microphone_devices(&self) -> std::result::Result<Vec<DeviceUsage>, ValidatorError> {
+        if alsa::is_fallback() {
+            return alsa::capture_devices();
+        }
+        get_microphone_devices_impl()
+    }
+
+    fn get_microphone_sessions(&self) -> std::result::Result<Vec<CaptureSession>, ValidatorError> {
+        if alsa::is_fallback() {
+            return Ok(Vec::new());
+        }
+        get_microphone_sessions_impl()
+    }
+
+    fn get_audio_output_volume_and_mute(&self) -> std::result::Result<AudioInfo, ValidatorError> {
+        if alsa::is_fallback() {
+            return alsa::output_volume();
+        }
         get_audio_output_volume_and_mute_impl()
     }
 
-    fn get_audio_output_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_audio_output_device_name(&self) -> std::result::Result<String, ValidatorError> {
+        if alsa::is_fallback() {
+            return Ok(alsa::device()?.name);
+        }
         get_audio_output_device_name_impl()
     }
 
-    fn get_audio_output_peak_level() -> std::result::Result<f32, Box<dyn std::error::Error>> {
+    fn get_audio_output_device(&self) -> std::result::Result<OutputDevice, ValidatorError> {
+        if alsa::is_fallback() {
+            return alsa::device();
+        }
+        get_audio_output_device_impl()
+    }
+
+    fn get_audio_output_peak_level(&self) -> std::result::Result<f32, ValidatorError> {
+        if alsa::is_fallback() {
+            return Ok(0.0);
+        }
         get_audio_output_peak_level_impl()
     }
 
-    fn get_apps_playing_audio() -> std::result::Result<Vec<AudioAppSession>, Box<dyn std::error::Error>> {
+    // Which of Pulse and ALSA to read is decided afresh
+    fn restart(&mut self) {
+        alsa::recheck();
+    }
+
+    fn get_apps_playing_audio(&self) -> std::result::Result<Vec<AudioAppSession>, ValidatorError> {
+        if alsa::is_fallback() {
+            return alsa::apps_playing();
+        }
         get_apps_playing_audio_impl()
     }
 }
 
 // Helper function to create PulseAudio context
-fn create_pulse_context() -> std::result::Result<(Mainloop, Context), Box<dyn std::error::Error>> {
+fn create_pulse_context() -> std::result::Result<(Mainloop, Context), ValidatorError> {
     let mut proplist = Proplist::new().ok_or("Failed to create proplist")?;
     proplist.set_str(pulse::proplist::properties::APPLICATION_NAME, "rust-audio-validator")
         .map_err(|_| "Failed to set app name")?;
@@ -56,7 +112,7 @@ fn create_pulse_context() -> std::result::Result<(Mainloop, Context), Box<dyn st
         .ok_or("Failed to create context")?;
 
     context.connect(None, ContextFlagSet::NOFLAGS, None)
-        .map_err(|e| format!("Failed to connect to PulseAudio: {:?}", e))?;
+        .map_err(|e| ValidatorError::BackendUnavailable(format!("PulseAudio ({:?})", e)))?;
 
     mainloop.lock();
     mainloop.start().map_err(|e| format!("Failed to start mainloop: {:?}", e))?;
@@ -67,7 +123,7 @@ fn create_pulse_context() -> std::result::Result<(Mainloop, Context), Box<dyn st
             pulse::context::State::Ready => break,
             pulse::context::State::Failed | pulse::context::State::Terminated => {
                 mainloop.unlock();
-                return Err("PulseAudio context failed".into());
+                return Err(ValidatorError::BackendUnavailable("PulseAudio".to_string()));
             }
             _ => {
                 mainloop.unlock();
@@ -82,7 +138,7 @@ fn create_pulse_context() -> std::result::Result<(Mainloop, Context), Box<dyn st
 }
 
 // Microphone volume and mute status
-fn get_microphone_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+fn get_microphone_volume_and_mute_impl() -> std::result::Result<AudioInfo, ValidatorError> {
     let (mainloop, context) = match create_pulse_context() {
         Ok(ctx) => ctx,
         Err(_) => {
@@ -133,11 +189,11 @@ fn get_microphone_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box<d
     mainloop.stop();
     mainloop.unlock();
 
-    result.lock().unwrap().take().ok_or("Failed to get microphone info".into())
+    result.lock().unwrap().take().ok_or_else(|| ValidatorError::Timeout("PulseAudio source info".to_string()))
 }
 
 // Microphone device name
-fn get_microphone_device_name_impl() -> std::result::Result<String, Box<dyn std::error::Error>> {
+fn get_microphone_device_name_impl() -> std::result::Result<String, ValidatorError> {
     let (mainloop, context) = match create_pulse_context() {
         Ok(ctx) => ctx,
         Err(_) => return Ok("Default Microphone".to_string()),
@@ -183,26 +239,56 @@ fn get_microphone_device_name_impl() -> std::result::Result<String, Box<dyn std:
 }
 
 // Get applications using microphone
-fn get_apps_using_microphone_impl() -> std::result::Result<Vec<String>, Box<dyn std::error::Error>> {
-    let (mainloop, context) = match create_pulse_context() {
-        Ok(ctx) => ctx,
-        Err(_) => return Ok(Vec::new()),
-    };
+fn get_apps_using_microphone_impl() -> std::result::Result<Vec<MicAppSession>, ValidatorError> {
+    Ok(capturing_apps()?.into_iter().map(|(session, _)| session).collect())
+}
+
+// Every source with the apps recording from it: the default one, any other in use
+fn get_microphone_devices_impl() -> std::result::Result<Vec<DeviceUsage>, ValidatorError> {
+    let capturing = capturing_apps()?;
+    let default_source = pactl_default("Default Source:");
+
+    Ok(list_devices("sources")
+        .into_iter()
+        .map(|source| DeviceUsage {
+            is_default: default_source.as_deref() == Some(source.name.as_str()),
+            apps: capturing
+                .iter()
+                .filter(|(_, index)| *index == source.index)
+                .map(|(session, _)| session.name.clone())
+                .collect(),
+            name: source.device.name,
+            form_factor: source.device.form_factor,
+            is_virtual: source.device.is_virtual,
+        })
+        .filter(|usage| usage.is_default || !usage.apps.is_empty())
+        .collect())
+}
+
+// Apps with a source output, with the index of the source they record from
+fn capturing_apps() -> std::result::Result<Vec<(MicAppSession, u32)>, ValidatorError> {
+    let (mainloop, context) = create_pulse_context()?;
 
     let result = Arc::new(Mutex::new(Vec::new()));
     let result_clone = Arc::clone(&result);
 
     mainloop.lock();
     let introspect = context.introspect();
+    let portal_clients = request_portal_clients(&introspect);
 
     introspect.get_source_output_info_list(move |list_result| {
         if let ListResult::Item(output_info) = list_result {
             // Get application name from properties
             if let Some(props) = output_info.proplist.as_ref() {
-                if let Some(app_name) = props.get_str(pulse::proplist::properties::APPLICATION_PROCESS_BINARY) {
-                    result_clone.lock().unwrap().push(app_name);
-                } else if let Some(app_name) = props.get_str(pulse::proplist::properties::APPLICATION_NAME) {
-                    result_clone.lock().unwrap().push(app_name);
+                let app_name = props
+                    .get_str(pulse::proplist::properties::APPLICATION_PROCESS_BINARY)
+                    .or_else(|| props.get_str(pulse::proplist::properties::APPLICATION_NAME));
+                if let Some(app_name) = app_name {
+                    let process_id = props
+                        .get_str(pulse::proplist::properties::APPLICATION_PROCESS_ID)
+                        .and_then(|pid| pid.parse().ok())
+                        .unwrap_or(0);
+                    result_clone.lock().unwrap().push((app_name, process_id, output_info.source, output_info.client));
                 }
             }
         }
@@ -216,11 +302,140 @@ fn get_apps_using_microphone_impl() -> std::result::Result<Vec<String>, Box<dyn
     mainloop.stop();
     mainloop.unlock();
 
-    Ok(result.lock().unwrap().clone())
+    let portal_clients = portal_clients.lock().unwrap();
+    let capturing = result.lock().unwrap().clone();
+    Ok(capturing
+        .into_iter()
+        .map(|(app_name, process_id, source, client)| {
+            // Sandboxed apps report their pid inside the sandbox; the portal knows the host pid
+            let session = match client.and_then(|client| portal_clients.get(&client)) {
+                Some(portal) => MicAppSession { name: portal.app_name.clone(), process_id: portal.process_id, device: None },
+                None => MicAppSession { name: app_name, process_id, device: None },
+            };
+            (session, source)
+        })
+        .collect())
+}
+
+// Recording apps with the input level of the source they record from
+// Pulse has no meter per source output, so each source in use is metered once with a
+// peak-detect stream (what pavucontrol's level bars use) and every app on it shares it
+fn get_microphone_sessions_impl() -> std::result::Result<Vec<CaptureSession>, ValidatorError> {
+    let capturing = capturing_apps()?;
+    let mut sources: Vec<u32> = capturing.iter().map(|(_, source)| *source).collect();
+    sources.sort_unstable();
+    sources.dedup();
+    if sources.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let peaks = source_peaks(&sources)?;
+    Ok(capturing
+        .into_iter()
+        .filter(|(session, _)| session.process_id != 0)
+        .map(|(session, source)| CaptureSession {
+            process_id: session.process_id,
+            peak_level: peaks.get(&source).copied(),
+            device_level: true,
+        })
+        .collect())
+}
+
+/// Peak-detect streams deliver this many readings per second
+const PEAK_RATE: u32 = 25;
+/// How long each source is listened to
+const PEAK_WINDOW: std::time::Duration = std::time::Duration::from_millis(150);
+
+// Highest input level of each source over PEAK_WINDOW
+fn source_peaks(sources: &[u32]) -> std::result::Result<HashMap<u32, f32>, ValidatorError> {
+    let (mainloop, mut context) = create_pulse_context()?;
+    let spec = Spec { format: Format::F32le, channels: 1, rate: PEAK_RATE };
+    // One reading per fragment, delivered as soon as it is taken
+    let attr = BufferAttr {
+        maxlength: u32::MAX,
+        tlength: u32::MAX,
+        prebuf: u32::MAX,
+        minreq: u32::MAX,
+        fragsize: std::mem::size_of::<f32>() as u32,
+    };
+
+    mainloop.lock();
+    let mut streams = Vec::new();
+    for &source in sources {
+        let Some(mut stream) = Stream::new(&mut context, "peak", &spec, None) else { continue };
+        let flags = StreamFlagSet::PEAK_DETECT | StreamFlagSet::ADJUST_LATENCY | StreamFlagSet::DONT_MOVE;
+        if stream.connect_record(Some(&source.to_string()), Some(&attr), flags).is_ok() {
+            streams.push((source, stream));
+        }
+    }
+    mainloop.unlock();
+
+    std::thread::sleep(PEAK_WINDOW);
+
+    mainloop.lock();
+    let mut peaks = HashMap::new();
+    for (source, mut stream) in streams {
+        let mut peak = 0.0f32;
+        loop {
+            let reading = match stream.peek() {
+                Ok(PeekResult::Data(data)) => data
+                    .chunks_exact(4)
+                    .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).abs())
+                    .fold(0.0, f32::max),
+                Ok(PeekResult::Hole(_)) => 0.0,
+                _ => break,
+            };
+            peak = peak.max(reading);
+            if stream.discard().is_err() {
+                break;
+            }
+        }
+        let _ = stream.disconnect();
+        peaks.insert(source, peak.min(1.0));
+    }
+    mainloop.stop();
+    mainloop.unlock();
+
+    Ok(peaks)
+}
+
+/// A client connected through the PipeWire portal, as the host sees it
+#[derive(Debug, Clone)]
+struct PortalClient {
+    /// Process name for the Flatpak app ID ("chrome" for com.google.Chrome)
+    app_name: String,
+    /// Host pid, 0 if unknown; application.process.id is the pid inside the sandbox
+    process_id: u32,
+}
+
+// Queue a client list request on a locked mainloop; portal clients by client index
+// once the mainloop has run
+fn request_portal_clients(introspect: &Introspector) -> Arc<Mutex<HashMap<u32, PortalClient>>> {
+    let clients = Arc::new(Mutex::new(HashMap::new()));
+    let clients_clone = Arc::clone(&clients);
+
+    introspect.get_client_info_list(move |list_result| {
+        if let ListResult::Item(client_info) = list_result {
+            if let Some(props) = client_info.proplist.as_ref() {
+                if let Some(app_id) = props.get_str("pipewire.access.portal.app_id") {
+                    let process_id = props
+                        .get_str("pipewire.sec.pid")
+                        .and_then(|pid| pid.parse().ok())
+                        .unwrap_or(0);
+                    clients_clone.lock().unwrap().insert(client_info.index, PortalClient {
+                        app_name: crate::sandbox::app_name(&app_id),
+                        process_id,
+                    });
+                }
+            }
+        }
+    });
+
+    clients
 }
 
 // Audio output volume and mute status
-fn get_audio_output_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+fn get_audio_output_volume_and_mute_impl() -> std::result::Result<AudioInfo, ValidatorError> {
     let (mainloop, context) = match create_pulse_context() {
         Ok(ctx) => ctx,
         Err(_) => {
@@ -269,11 +484,11 @@ fn get_audio_output_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box
     mainloop.stop();
     mainloop.unlock();
 
-    result.lock().unwrap().take().ok_or("Failed to get audio output info".into())
+    result.lock().unwrap().take().ok_or_else(|| ValidatorError::Timeout("PulseAudio sink info".to_string()))
 }
 
 // Audio output device name
-fn get_audio_output_device_name_impl() -> std::result::Result<String, Box<dyn std::error::Error>> {
+fn get_audio_output_device_name_impl() -> std::result::Result<String, ValidatorError> {
     let (mainloop, context) = match create_pulse_context() {
         Ok(ctx) => ctx,
         Err(_) => return Ok("Default Speakers".to_string()),
@@ -318,13 +533,96 @@ fn get_audio_output_device_name_impl() -> std::result::Result<String, Box<dyn st
     Ok(result.lock().unwrap().take().unwrap_or_else(|| "Default Speakers".to_string()))
 }
 
+// Default output device with its form factor
+// Falls back to the sink description alone when pactl is unavailable
+fn get_audio_output_device_impl() -> std::result::Result<OutputDevice, ValidatorError> {
+    if let Some(default_sink) = pactl_default("Default Sink:") {
+        if let Some(sink) = list_devices("sinks").into_iter().find(|sink| sink.name == default_sink) {
+            return Ok(sink.device);
+        }
+    }
+
+    Ok(OutputDevice {
+        name: get_audio_output_device_name_impl()?,
+        form_factor: OutputFormFactor::Unknown,
+        is_virtual: false,
+    })
+}
+
+/// One sink or source from `pactl list`
+struct PactlDevice {
+    index: u32,
+    name: String,
+    device: OutputDevice,
+}
+
+// pactl with untranslated output, so the field names below match in any locale
+fn pactl(args: &[&str]) -> Command {
+    let mut command = Command::new("pactl");
+    command.env("LC_ALL", "C").args(args);
+    command
+}
+
+// "Default Sink:" or "Default Source:" from `pactl info`
+fn pactl_default(field: &str) -> Option<String> {
+    let output = crate::subprocess::output(&mut pactl(&["info"])).ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix(field).map(|name| name.trim().to_string()))
+}
+
+// `kind` is "sinks" or "sources"
+fn list_devices(kind: &str) -> Vec<PactlDevice> {
+    let header = if kind == "sinks" { "Sink #" } else { "Source #" };
+    crate::subprocess::output(&mut pactl(&["list", kind]))
+        .map(|output| parse_devices(&String::from_utf8_lossy(&output.stdout), header))
+        .unwrap_or_default()
+}
+
+// Devices with their form factor from `device.form_factor`, `device.bus`, the active port
+// ("analog-output-headphones", "hdmi-output-0", "headset-output") and the description
+fn parse_devices(text: &str, header: &str) -> Vec<PactlDevice> {
+    let mut devices = Vec::new();
+    for block in text.split(header).skip(1) {
+        let Some(index) = block.lines().next().and_then(|line| line.trim().parse().ok()) else {
+            continue;
+        };
+        let field = |prefix: &str| {
+            block
+                .lines()
+                .find_map(|line| line.trim().strip_prefix(prefix))
+                .map(|value| value.trim().trim_matches('"').to_string())
+                .unwrap_or_default()
+        };
+
+        let name = field("Name:");
+        let description = field("Description:");
+        let form_factor = OutputFormFactor::from_hints(&[
+            &field("device.form_factor ="),
+            &field("device.bus ="),
+            &field("Active Port:"),
+            &description,
+        ]);
+        // Null sinks (module-null-sink, PipeWire support.null-audio-sink) have no hardware
+        // behind them; neither do monitors of other sinks nor OBS's monitoring outputs
+        let is_virtual = field("device.class =") == "abstract"
+            || name.ends_with(".monitor")
+            || is_virtual_device(&[&name, &description, &field("factory.name =")]);
+        devices.push(PactlDevice {
+            index,
+            name,
+            device: OutputDevice { name: description, form_factor, is_virtual },
+        });
+    }
+    devices
+}
+
 // Audio output peak level
 // Uses PulseAudio pactl to get real-time peak levels
-fn get_audio_output_peak_level_impl() -> std::result::Result<f32, Box<dyn std::error::Error>> {
+fn get_audio_output_peak_level_impl() -> std::result::Result<f32, ValidatorError> {
     // Method 1: Use pactl to get sink volume and check if audio is playing
-    let pactl_output = Command::new("pactl")
-        .args(&["list", "sinks"])
-        .output();
+    let pactl_output = crate::subprocess::output(Command::new("pactl")
+        .args(&["list", "sinks"]));
 
     if let Ok(output) = pactl_output {
         let pactl_str = String::from_utf8_lossy(&output.stdout);
@@ -360,9 +658,8 @@ fn get_audio_output_peak_level_impl() -> std::result::Result<f32, Box<dyn std::e
     }
 
     // Method 2: Check for active sink inputs (apps playing audio)
-    let sink_inputs = Command::new("pactl")
-        .args(&["list", "sink-inputs"])
-        .output();
+    let sink_inputs = crate::subprocess::output(Command::new("pactl")
+        .args(&["list", "sink-inputs"]));
 
     if let Ok(output) = sink_inputs {
         let sink_str = String::from_utf8_lossy(&output.stdout);
@@ -380,9 +677,8 @@ fn get_audio_output_peak_level_impl() -> std::result::Result<f32, Box<dyn std::e
     }
 
     // Method 3: Fallback - check if pulseaudio is actively processing
-    let ps_output = Command::new("ps")
-        .args(&["aux"])
-        .output();
+    let ps_output = crate::subprocess::output(Command::new("ps")
+        .args(&["aux"]));
 
     if let Ok(output) = ps_output {
         let ps_str = String::from_utf8_lossy(&output.stdout);
@@ -407,17 +703,16 @@ fn get_audio_output_peak_level_impl() -> std::result::Result<f32, Box<dyn std::e
 }
 
 // Get applications playing audio
-fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, Box<dyn std::error::Error>> {
-    let (mainloop, context) = match create_pulse_context() {
-        Ok(ctx) => ctx,
-        Err(_) => return Ok(Vec::new()),
-    };
+fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, ValidatorError> {
+    let (mainloop, context) = create_pulse_context()?;
 
+    // Sessions with the index of the sink they play to
     let result = Arc::new(Mutex::new(Vec::new()));
     let result_clone = Arc::clone(&result);
 
     mainloop.lock();
     let introspect = context.introspect();
+    let portal_clients = request_portal_clients(&introspect);
 
     introspect.get_sink_input_info_list(move |list_result| {
         if let ListResult::Item(input_info) = list_result {
@@ -449,14 +744,16 @@ fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, Bo
             let volume_avg = input_info.volume.avg().0 as f32 / Volume::NORMAL.0 as f32 * 100.0;
             let is_corked = input_info.corked;
 
-            result_clone.lock().unwrap().push(AudioAppSession {
+            result_clone.lock().unwrap().push((AudioAppSession {
                 name: app_name,
                 volume: volume_avg,
                 is_active: !is_corked,
                 peak_level: 0.0,  // Would need sink monitor for accurate peak
+                is_muted: input_info.mute,
                 process_id,
                 window_title,
-            });
+                output_device: None,
+            }, input_info.sink, input_info.client));
         }
     });
 
@@ -468,34 +765,40 @@ fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, Bo
     mainloop.stop();
     mainloop.unlock();
 
-    Ok(result.lock().unwrap().clone())
-}
-
-// Public convenience functions
-pub fn get_microphone_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
-    get_microphone_volume_and_mute_impl()
-}
-
-pub fn get_microphone_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
-    get_microphone_device_name_impl()
-}
-
-pub fn get_apps_using_microphone() -> std::result::Result<Vec<String>, Box<dyn std::error::Error>> {
-    get_apps_using_microphone_impl()
-}
-
-pub fn get_audio_output_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
-    get_audio_output_volume_and_mute_impl()
-}
-
-pub fn get_audio_output_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
-    get_audio_output_device_name_impl()
-}
-
-pub fn get_audio_output_peak_level() -> std::result::Result<f32, Box<dyn std::error::Error>> {
-    get_audio_output_peak_level_impl()
-}
+    let playing = result.lock().unwrap().clone();
+    let sinks: HashMap<u32, OutputDevice> = if playing.is_empty() {
+        HashMap::new()
+    } else {
+        list_devices("sinks").into_iter().map(|sink| (sink.index, sink.device)).collect()
+    };
+    let portal_clients = portal_clients.lock().unwrap();
+    let mut sessions: Vec<AudioAppSession> = playing
+        .into_iter()
+        .map(|(session, sink, client)| {
+            let mut session = AudioAppSession {
+                output_device: sinks.get(&sink).cloned(),
+                ..session
+            };
+            // Flatpak clients: name the app by its ID and use the host pid for the title lookup
+            if let Some(portal) = client.and_then(|client| portal_clients.get(&client)) {
+                if session.window_title == session.name {
+                    session.window_title = portal.app_name.clone();
+                }
+                session.name = portal.app_name.clone();
+                session.process_id = portal.process_id;
+            }
+            session
+        })
+        .collect();
+
+    // Most clients don't set window.name; ask the display server instead
+    for session in sessions.iter_mut() {
+        if session.process_id != 0 && session.window_title == session.name {
+            if let Ok(title) = crate::platform::linux::get_window_title(session.process_id) {
+                session.window_title = title;
+            }
+        }
+    }
 
-pub fn get_apps_playing_audio() -> std::result::Result<Vec<AudioAppSession>, Box<dyn std::error::Error>> {
-    get_apps_playing_audio_impl()
+    Ok(sessions)
 }
@@ -1,51 +1,71 @@
 // macOS audio backend using system utilities and process monitoring
 // This implementation provides robust audio monitoring for macOS
 
-use super::{AudioAppSession, AudioBackend, AudioInfo};
+use super::{is_virtual_device, AudioAppSession, AudioBackend, AudioInfo, MicAppSession, OutputDevice, OutputFormFactor, SystemAudio};
+use crate::error::ValidatorError;
 use std::process::Command;
 use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// system_profiler takes about a second, far too slow to run on every tick
+const DEVICE_CACHE_TTL: Duration = Duration::from_secs(10);
+static DEFAULT_OUTPUT: Mutex<Option<(Instant, OutputDevice)>> = Mutex::new(None);
 
 // Implement the AudioBackend trait for macOS
-impl AudioBackend for () {
-    fn get_microphone_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+impl AudioBackend for SystemAudio {
+    fn get_microphone_volume_and_mute(&self) -> std::result::Result<AudioInfo, ValidatorError> {
         get_microphone_volume_and_mute_impl()
     }
 
-    fn get_microphone_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_microphone_device_name(&self) -> std::result::Result<String, ValidatorError> {
         get_microphone_device_name_impl()
     }
 
-    fn get_apps_using_microphone() -> std::result::Result<Vec<String>, Box<dyn std::error::Error>> {
+    fn get_apps_using_microphone(&self) -> std::result::Result<Vec<MicAppSession>, ValidatorError> {
         get_apps_using_microphone_impl()
     }
 
-    fn get_audio_output_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+    fn get_apps_using_camera(&self) -> std::result::Result<Vec<String>, ValidatorError> {
+        get_apps_using_camera_impl()
+    }
+
+    fn get_audio_output_volume_and_mute(&self) -> std::result::Result<AudioInfo, ValidatorError> {
         get_audio_output_volume_and_mute_impl()
     }
 
-    fn get_audio_output_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_audio_output_device_name(&self) -> std::result::Result<String, ValidatorError> {
         get_audio_output_device_name_impl()
     }
 
-    fn get_audio_output_peak_level() -> std::result::Result<f32, Box<dyn std::error::Error>> {
+    fn get_audio_output_device(&self) -> std::result::Result<OutputDevice, ValidatorError> {
+        default_output_device().ok_or_else(|| ValidatorError::NotFound("default output device".to_string()))
+    }
+
+    fn get_audio_output_peak_level(&self) -> std::result::Result<f32, ValidatorError> {
         get_audio_output_peak_level_impl()
     }
 
-    fn get_apps_playing_audio() -> std::result::Result<Vec<AudioAppSession>, Box<dyn std::error::Error>> {
+    // The default output device is read again
+    fn restart(&mut self) {
+        DEFAULT_OUTPUT.clear_poison();
+        *DEFAULT_OUTPUT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+
+    fn get_apps_playing_audio(&self) -> std::result::Result<Vec<AudioAppSession>, ValidatorError> {
         get_apps_playing_audio_impl()
     }
 }
 
 // Get microphone volume and mute status using osascript
-fn get_microphone_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+fn get_microphone_volume_and_mute_impl() -> std::result::Result<AudioInfo, ValidatorError> {
     // macOS doesn't provide easy system-wide mic volume access
     // Use osascript to query Audio MIDI Setup or default to reasonable values
     // For a production implementation, use Core Audio APIs directly
 
     // Check if input device is available and get volume via system_profiler
-    let output = Command::new("system_profiler")
-        .arg("SPAudioDataType")
-        .output();
+    let output = crate::subprocess::output(Command::new("system_profiler")
+        .arg("SPAudioDataType"));
 
     match output {
         Ok(_) => {
@@ -67,11 +87,10 @@ fn get_microphone_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box<d
 }
 
 // Get microphone device name
-fn get_microphone_device_name_impl() -> std::result::Result<String, Box<dyn std::error::Error>> {
+fn get_microphone_device_name_impl() -> std::result::Result<String, ValidatorError> {
     // Use system_profiler to get default input device
-    let output = Command::new("system_profiler")
-        .arg("SPAudioDataType")
-        .output();
+    let output = crate::subprocess::output(Command::new("system_profiler")
+        .arg("SPAudioDataType"));
 
     match output {
         Ok(output) => {
@@ -95,39 +114,19 @@ fn get_microphone_device_name_impl() -> std::result::Result<String, Box<dyn std:
 
 // Get applications using microphone
 // Uses multiple detection methods for robust mic usage detection
-fn get_apps_using_microphone_impl() -> std::result::Result<Vec<String>, Box<dyn std::error::Error>> {
+fn get_apps_using_microphone_impl() -> std::result::Result<Vec<MicAppSession>, ValidatorError> {
     let mut apps = Vec::new();
     let mut seen = HashSet::new();
 
-    // Method 1: Use log command to check for mic usage permissions
-    // This shows which apps have recently used the microphone
-    let log_output = Command::new("log")
-        .args(&["show", "--predicate", "subsystem == 'com.apple.TCC' and eventMessage contains 'Microphone'", "--style", "syslog", "--last", "5s"])
-        .output();
-
-    if let Ok(output) = log_output {
-        let log_str = String::from_utf8_lossy(&output.stdout);
-
-        for line in log_str.lines() {
-            // Parse log entries for app identifiers
-            if line.contains("ALLOW") || line.contains("kTCCServiceMicrophone") {
-                // Extract app name from log format
-                if let Some(app_start) = line.find("identifier=") {
-                    if let Some(app_part) = line[app_start..].split_whitespace().next() {
-                        let app_name = app_part.replace("identifier=", "").replace(",", "");
-                        if !app_name.is_empty() && seen.insert(app_name.clone()) {
-                            apps.push(app_name);
-                        }
-                    }
-                }
-            }
-        }
+    // Exact attribution from Core Audio process objects (macOS 14.2+)
+    match super::macos_capture::microphone_clients() {
+        Ok(clients) => return Ok(capture_client_sessions(&clients)),
+        Err(e) => log::debug!("Core Audio process objects unavailable, using heuristics: {}", e),
     }
 
-    // Method 2: Check processes with open audio input devices
-    let lsof_output = Command::new("lsof")
-        .args(&["-c", "AppleCameraAssistant", "-c", "coreaudiod"])
-        .output();
+    // Fallback 1: Check processes with open audio input devices
+    let lsof_output = crate::subprocess::output(Command::new("lsof")
+        .args(&["-c", "AppleCameraAssistant", "-c", "coreaudiod"]));
 
     if let Ok(output) = lsof_output {
         let lsof_str = String::from_utf8_lossy(&output.stdout);
@@ -138,13 +137,13 @@ fn get_apps_using_microphone_impl() -> std::result::Result<Vec<String>, Box<dyn
             let meeting_apps = get_active_meeting_apps();
             for app in meeting_apps {
                 if seen.insert(app.clone()) {
-                    apps.push(app);
+                    apps.push(MicAppSession::named(app));
                 }
             }
         }
     }
 
-    // Method 3: Check known meeting apps that are running and likely using mic
+    // Fallback 2: Check known meeting apps that are running and likely using mic
     let running_apps = get_running_processes();
     let known_mic_apps = vec![
         ("Google Chrome", vec!["meet.google.com", "teams.microsoft.com", "slack.com"]),
@@ -162,7 +161,7 @@ fn get_apps_using_microphone_impl() -> std::result::Result<Vec<String>, Box<dyn
             if is_app_active(app_name) {
                 let normalized_name = app_name.to_string();
                 if seen.insert(normalized_name.clone()) {
-                    apps.push(normalized_name);
+                    apps.push(MicAppSession::named(normalized_name));
                 }
             }
         }
@@ -171,6 +170,43 @@ fn get_apps_using_microphone_impl() -> std::result::Result<Vec<String>, Box<dyn
     Ok(apps)
 }
 
+// Get applications using the camera
+// CoreMediaIO only reports that a camera is running, so it is attributed to the apps
+// capturing audio at the same time - in a video call that is the call app.
+fn get_apps_using_camera_impl() -> std::result::Result<Vec<String>, ValidatorError> {
+    if !super::macos_capture::camera_in_use() {
+        return Ok(Vec::new());
+    }
+
+    let clients = super::macos_capture::microphone_clients()?;
+    Ok(capture_client_sessions(&clients).into_iter().map(|session| session.name).collect())
+}
+
+// One session per capture client, named by bundle identifier or by process name for
+// bare executables
+fn capture_client_sessions(clients: &[super::macos_capture::CaptureClient]) -> Vec<MicAppSession> {
+    let mut sessions: Vec<MicAppSession> = Vec::new();
+    let mut running_processes: Option<HashMap<String, u32>> = None;
+
+    for client in clients {
+        let name = if client.bundle_id.is_empty() {
+            let processes = running_processes.get_or_insert_with(get_running_processes);
+            match processes.iter().find(|(_, &pid)| pid == client.process_id) {
+                Some((name, _)) => name.clone(),
+                None => continue,
+            }
+        } else {
+            client.bundle_id.clone()
+        };
+
+        if !sessions.iter().any(|session| session.process_id == client.process_id) {
+            sessions.push(MicAppSession { name, process_id: client.process_id, device: None });
+        }
+    }
+
+    sessions
+}
+
 // Get active meeting applications
 fn get_active_meeting_apps() -> Vec<String> {
     let mut apps = Vec::new();
@@ -183,7 +219,7 @@ fn get_active_meeting_apps() -> Vec<String> {
         end tell
     "#;
 
-    if let Ok(output) = Command::new("osascript").arg("-e").arg(script).output() {
+    if let Ok(output) = crate::subprocess::output(Command::new("osascript").arg("-e").arg(script)) {
         if output.status.success() {
             let apps_str = String::from_utf8_lossy(&output.stdout);
             let meeting_keywords = vec!["Chrome", "Safari", "Firefox", "zoom", "Teams", "Slack", "WhatsApp", "Meet"];
@@ -218,7 +254,7 @@ fn is_app_active(app_name: &str) -> bool {
         app_name, app_name
     );
 
-    if let Ok(output) = Command::new("osascript").arg("-e").arg(&script).output() {
+    if let Ok(output) = crate::subprocess::output(Command::new("osascript").arg("-e").arg(&script)) {
         if output.status.success() {
             let result = String::from_utf8_lossy(&output.stdout).trim().to_lowercase();
             return result == "true";
@@ -226,7 +262,7 @@ fn is_app_active(app_name: &str) -> bool {
     }
 
     // Fallback: check if process exists
-    if let Ok(output) = Command::new("pgrep").arg("-i").arg(app_name).output() {
+    if let Ok(output) = crate::subprocess::output(Command::new("pgrep").arg("-i").arg(app_name)) {
         return output.status.success() && !output.stdout.is_empty();
     }
 
@@ -237,7 +273,7 @@ fn is_app_active(app_name: &str) -> bool {
 fn get_running_processes() -> HashMap<String, u32> {
     let mut processes = HashMap::new();
 
-    if let Ok(output) = Command::new("ps").args(&["-ax", "-o", "pid,comm"]).output() {
+    if let Ok(output) = crate::subprocess::output(Command::new("ps").args(&["-ax", "-o", "pid,comm"])) {
         if output.status.success() {
             let ps_str = String::from_utf8_lossy(&output.stdout);
 
@@ -259,11 +295,10 @@ fn get_running_processes() -> HashMap<String, u32> {
 }
 
 // Get audio output volume and mute status
-fn get_audio_output_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+fn get_audio_output_volume_and_mute_impl() -> std::result::Result<AudioInfo, ValidatorError> {
     // Use osascript to get system volume
-    let output = Command::new("osascript")
-        .args(&["-e", "output volume of (get volume settings)"])
-        .output();
+    let output = crate::subprocess::output(Command::new("osascript")
+        .args(&["-e", "output volume of (get volume settings)"]));
 
     match output {
         Ok(output) => {
@@ -271,9 +306,8 @@ fn get_audio_output_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box
 
             if let Ok(volume) = output_str.parse::<f32>() {
                 // Check mute status
-                let mute_output = Command::new("osascript")
-                    .args(&["-e", "output muted of (get volume settings)"])
-                    .output();
+                let mute_output = crate::subprocess::output(Command::new("osascript")
+                    .args(&["-e", "output muted of (get volume settings)"]));
 
                 let is_muted = if let Ok(mute_out) = mute_output {
                     let mute_str = String::from_utf8_lossy(&mute_out.stdout).trim().to_string();
@@ -303,37 +337,69 @@ fn get_audio_output_volume_and_mute_impl() -> std::result::Result<AudioInfo, Box
 }
 
 // Get audio output device name
-fn get_audio_output_device_name_impl() -> std::result::Result<String, Box<dyn std::error::Error>> {
-    // Use system_profiler to get default output device
-    let output = Command::new("system_profiler")
-        .arg("SPAudioDataType")
-        .output();
+fn get_audio_output_device_name_impl() -> std::result::Result<String, ValidatorError> {
+    Ok(default_output_device()
+        .map(|device| device.name)
+        .unwrap_or_else(|| "Default Speakers".to_string()))
+}
 
-    match output {
-        Ok(output) => {
-            let output_str = String::from_utf8_lossy(&output.stdout);
+// Default output device from system_profiler, cached for DEVICE_CACHE_TTL
+fn default_output_device() -> Option<OutputDevice> {
+    let mut cache = DEFAULT_OUTPUT.lock().unwrap();
+    if let Some((at, device)) = cache.as_ref() {
+        if at.elapsed() < DEVICE_CACHE_TTL {
+            return Some(device.clone());
+        }
+    }
 
-            // Look for default output device
-            for line in output_str.lines() {
-                if line.contains("Default Output Device: Yes") {
-                    return Ok("Built-in Output".to_string());
-                }
-            }
+    let output = crate::subprocess::output(Command::new("system_profiler")
+        .arg("SPAudioDataType")).ok()?;
+    let device = parse_default_output(&String::from_utf8_lossy(&output.stdout))?;
+    *cache = Some((Instant::now(), device.clone()));
+    Some(device)
+}
 
-            Ok("Default Speakers".to_string())
+// The device block marked "Default Output Device: Yes"; its form factor comes from the
+// transport (Built-in, Bluetooth, HDMI, DisplayPort, USB) and the output source
+// ("MacBook Pro Speakers", "External Headphones")
+fn parse_default_output(text: &str) -> Option<OutputDevice> {
+    let mut name = "";
+    let mut transport = "";
+    let mut source = "";
+    let mut is_default = false;
+
+    // BlackHole, Soundflower and Loopback report the Virtual transport
+    let finish = |name: &str, transport: &str, source: &str| OutputDevice {
+        name: name.to_string(),
+        form_factor: OutputFormFactor::from_hints(&[transport, source, name]),
+        is_virtual: transport == "Virtual" || is_virtual_device(&[name]),
+    };
+
+    for line in text.lines().map(str::trim) {
+        if let Some(header) = line.strip_suffix(':').filter(|header| !header.contains(": ")) {
+            if is_default {
+                return Some(finish(name, transport, source));
+            }
+            (name, transport, source) = (header, "", "");
+        } else if let Some(value) = line.strip_prefix("Transport:") {
+            transport = value.trim();
+        } else if let Some(value) = line.strip_prefix("Output Source:") {
+            source = value.trim();
+        } else if line == "Default Output Device: Yes" {
+            is_default = true;
         }
-        Err(_) => Ok("Default Speakers".to_string()),
     }
+
+    is_default.then(|| finish(name, transport, source))
 }
 
 // Get audio output peak level
 // Estimates peak level based on active audio sessions
-fn get_audio_output_peak_level_impl() -> std::result::Result<f32, Box<dyn std::error::Error>> {
+fn get_audio_output_peak_level_impl() -> std::result::Result<f32, ValidatorError> {
     // Check if any audio is currently playing using coreaudiod activity
     // Method 1: Check if coreaudiod is actively processing audio
-    let top_output = Command::new("top")
-        .args(&["-l", "1", "-n", "1", "-stats", "pid,cpu,command"])
-        .output();
+    let top_output = crate::subprocess::output(Command::new("top")
+        .args(&["-l", "1", "-n", "1", "-stats", "pid,cpu,command"]));
 
     if let Ok(output) = top_output {
         let top_str = String::from_utf8_lossy(&output.stdout);
@@ -368,14 +434,13 @@ fn get_audio_output_peak_level_impl() -> std::result::Result<f32, Box<dyn std::e
 
 // Get applications playing audio
 // Uses multiple methods to detect audio-playing applications
-fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, Box<dyn std::error::Error>> {
+fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, ValidatorError> {
     let mut apps = Vec::new();
     let mut seen_pids = HashSet::new();
 
     // Method 1: Get processes with audio output using lsof for CoreAudio
-    let lsof_output = Command::new("lsof")
-        .args(&["-c", "coreaudiod"])
-        .output();
+    let lsof_output = crate::subprocess::output(Command::new("lsof")
+        .args(&["-c", "coreaudiod"]));
 
     let mut audio_active = false;
     if let Ok(output) = lsof_output {
@@ -422,17 +487,18 @@ fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, Bo
                     volume: 75.0,
                     is_active,
                     peak_level,
+                    is_muted: false,
                     process_id: pid,
                     window_title: window_title.clone(),
+                    output_device: None,
                 });
             }
         }
     }
 
     // Method 3: Use pmset to detect if audio is preventing sleep
-    let pmset_output = Command::new("pmset")
-        .args(&["-g", "assertions"])
-        .output();
+    let pmset_output = crate::subprocess::output(Command::new("pmset")
+        .args(&["-g", "assertions"]));
 
     if let Ok(output) = pmset_output {
         let pmset_str = String::from_utf8_lossy(&output.stdout);
@@ -457,8 +523,10 @@ fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, Bo
                                         volume: 75.0,
                                         is_active: true,
                                         peak_level: 0.2,
+                                        is_muted: false,
                                         process_id: pid,
                                         window_title,
+                                        output_device: None,
                                     });
                                 }
                             }
@@ -469,6 +537,15 @@ fn get_apps_playing_audio_impl() -> std::result::Result<Vec<AudioAppSession>, Bo
         }
     }
 
+    // Apps play to the system default output unless they pick a device themselves,
+    // which Core Audio does not report per process
+    if !apps.is_empty() {
+        let device = default_output_device();
+        for app in apps.iter_mut() {
+            app.output_device = device.clone();
+        }
+    }
+
     Ok(apps)
 }
 
@@ -512,32 +589,3 @@ fn estimate_app_audio_level(app_name: &str, window_title: &str) -> f32 {
     // Default for active audio
     0.2
 }
-
-// Public convenience functions
-pub fn get_microphone_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
-    get_microphone_volume_and_mute_impl()
-}
-
-pub fn get_microphone_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
-    get_microphone_device_name_impl()
-}
-
-pub fn get_apps_using_microphone() -> std::result::Result<Vec<String>, Box<dyn std::error::Error>> {
-    get_apps_using_microphone_impl()
-}
-
-pub fn get_audio_output_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
-    get_audio_output_volume_and_mute_impl()
-}
-
-pub fn get_audio_output_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
-    get_audio_output_device_name_impl()
-}
-
-pub fn get_audio_output_peak_level() -> std::result::Result<f32, Box<dyn std::error::Error>> {
-    get_audio_output_peak_level_impl()
-}
-
-pub fn get_apps_playing_audio() -> std::result::Result<Vec<AudioAppSession>, Box<dyn std::error::Error>> {
-    get_apps_playing_audio_impl()
-}
@@ -0,0 +1,187 @@
+// macOS capture attribution from Core Audio process objects
+// Since macOS 14.2 the HAL exposes one AudioObject per client process with its pid,
+// bundle id and whether it is running input - the same data behind Control Center's
+// orange "microphone in use" indicator. Camera use comes from CoreMediaIO, which only
+// reports whether a device is running somewhere, not for whom.
+
+use core_foundation::base::TCFType;
+use core_foundation::string::{CFString, CFStringRef};
+use coreaudio::sys::{AudioObjectGetPropertyData, AudioObjectGetPropertyDataSize, AudioObjectID, AudioObjectPropertyAddress};
+use crate::error::ValidatorError;
+use std::ffi::c_void;
+use std::mem::size_of;
+use std::ptr;
+
+const fn four_cc(code: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*code)
+}
+
+const SYSTEM_OBJECT: u32 = 1;
+const SCOPE_GLOBAL: u32 = four_cc(b"glob");
+const ELEMENT_MAIN: u32 = 0;
+
+// AudioHardware.h (macOS 14.2+)
+const PROCESS_OBJECT_LIST: u32 = four_cc(b"prs#");
+const PROCESS_PID: u32 = four_cc(b"ppid");
+const PROCESS_BUNDLE_ID: u32 = four_cc(b"pbid");
+const PROCESS_IS_RUNNING_INPUT: u32 = four_cc(b"piri");
+
+// CMIOHardware.h
+const CMIO_DEVICES: u32 = four_cc(b"dev#");
+const CMIO_DEVICE_IS_RUNNING_SOMEWHERE: u32 = four_cc(b"gone");
+
+#[repr(C)]
+struct CMIOObjectPropertyAddress {
+    selector: u32,
+    scope: u32,
+    element: u32,
+}
+
+#[link(name = "CoreMediaIO", kind = "framework")]
+extern "C" {
+    fn CMIOObjectGetPropertyDataSize(
+        object_id: u32,
+        address: *const CMIOObjectPropertyAddress,
+        qualifier_data_size: u32,
+        qualifier_data: *const c_void,
+        data_size: *mut u32,
+    ) -> i32;
+
+    fn CMIOObjectGetPropertyData(
+        object_id: u32,
+        address: *const CMIOObjectPropertyAddress,
+        qualifier_data_size: u32,
+        qualifier_data: *const c_void,
+        data_size: u32,
+        data_used: *mut u32,
+        data: *mut c_void,
+    ) -> i32;
+}
+
+/// A process currently capturing from an input device
+#[derive(Debug, Clone)]
+pub struct CaptureClient {
+    pub process_id: u32,
+    /// Bundle identifier, e.g. "us.zoom.xos"; empty for bare executables
+    pub bundle_id: String,
+}
+
+/// Processes running audio input right now
+/// Fails on macOS releases without process objects (before 14.2) so callers can fall back.
+pub fn microphone_clients() -> Result<Vec<CaptureClient>, ValidatorError> {
+    let processes: Vec<AudioObjectID> = unsafe { audio_property_array(SYSTEM_OBJECT, PROCESS_OBJECT_LIST) }
+        .map_err(|e| ValidatorError::BackendUnavailable(format!("Core Audio process objects ({})", e)))?;
+
+    let mut clients = Vec::new();
+    for process in processes {
+        let running_input: u32 = unsafe { audio_property(process, PROCESS_IS_RUNNING_INPUT).unwrap_or(0) };
+        if running_input == 0 {
+            continue;
+        }
+
+        let process_id: i32 = unsafe { audio_property(process, PROCESS_PID).unwrap_or(0) };
+        // CFStringRef returned with +1 retain count
+        let bundle_id = unsafe { audio_property::<usize>(process, PROCESS_BUNDLE_ID) }
+            .ok()
+            .filter(|&string| string != 0)
+            .map(|string| unsafe { CFString::wrap_under_create_rule(string as CFStringRef) }.to_string())
+            .unwrap_or_default();
+
+        clients.push(CaptureClient {
+            process_id: process_id.max(0) as u32,
+            bundle_id,
+        });
+    }
+
+    Ok(clients)
+}
+
+/// Whether any process is capturing from a camera
+pub fn camera_in_use() -> bool {
+    unsafe {
+        let devices = match cmio_property_array(SYSTEM_OBJECT, CMIO_DEVICES) {
+            Ok(devices) => devices,
+            Err(_) => return false,
+        };
+
+        devices.into_iter().any(|device| {
+            let mut running: u32 = 0;
+            let mut used = 0u32;
+            let address = cmio_address(CMIO_DEVICE_IS_RUNNING_SOMEWHERE);
+            CMIOObjectGetPropertyData(
+                device,
+                &address,
+                0,
+                ptr::null(),
+                size_of::<u32>() as u32,
+                &mut used,
+                &mut running as *mut u32 as *mut c_void,
+            ) == 0
+                && running != 0
+        })
+    }
+}
+
+fn audio_address(selector: u32) -> AudioObjectPropertyAddress {
+    AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: SCOPE_GLOBAL,
+        mElement: ELEMENT_MAIN,
+    }
+}
+
+fn cmio_address(selector: u32) -> CMIOObjectPropertyAddress {
+    CMIOObjectPropertyAddress {
+        selector,
+        scope: SCOPE_GLOBAL,
+        element: ELEMENT_MAIN,
+    }
+}
+
+/// Fixed-size property value
+unsafe fn audio_property<T: Copy + Default>(object: AudioObjectID, selector: u32) -> Result<T, String> {
+    let address = audio_address(selector);
+    let mut value = T::default();
+    let mut size = size_of::<T>() as u32;
+    let status = AudioObjectGetPropertyData(object, &address, 0, ptr::null(), &mut size, &mut value as *mut T as *mut c_void);
+    if status != 0 {
+        return Err(format!("AudioObjectGetPropertyData({:#x}) failed: {}", selector, status));
+    }
+    Ok(value)
+}
+
+/// Array-of-object-ids property value
+unsafe fn audio_property_array(object: AudioObjectID, selector: u32) -> Result<Vec<AudioObjectID>, String> {
+    let address = audio_address(selector);
+    let mut size = 0u32;
+    let status = AudioObjectGetPropertyDataSize(object, &address, 0, ptr::null(), &mut size);
+    if status != 0 {
+        return Err(format!("AudioObjectGetPropertyDataSize({:#x}) failed: {}", selector, status));
+    }
+
+    let mut ids = vec![0 as AudioObjectID; size as usize / size_of::<AudioObjectID>()];
+    let status = AudioObjectGetPropertyData(object, &address, 0, ptr::null(), &mut size, ids.as_mut_ptr() as *mut c_void);
+    if status != 0 {
+        return Err(format!("AudioObjectGetPropertyData({:#x}) failed: {}", selector, status));
+    }
+    ids.truncate(size as usize / size_of::<AudioObjectID>());
+    Ok(ids)
+}
+
+unsafe fn cmio_property_array(object: u32, selector: u32) -> Result<Vec<u32>, String> {
+    let address = cmio_address(selector);
+    let mut size = 0u32;
+    let status = CMIOObjectGetPropertyDataSize(object, &address, 0, ptr::null(), &mut size);
+    if status != 0 {
+        return Err(format!("CMIOObjectGetPropertyDataSize({:#x}) failed: {}", selector, status));
+    }
+
+    let mut ids = vec![0u32; size as usize / size_of::<u32>()];
+    let mut used = 0u32;
+    let status = CMIOObjectGetPropertyData(object, &address, 0, ptr::null(), size, &mut used, ids.as_mut_ptr() as *mut c_void);
+    if status != 0 {
+        return Err(format!("CMIOObjectGetPropertyData({:#x}) failed: {}", selector, status));
+    }
+    ids.truncate(used as usize / size_of::<u32>());
+    Ok(ids)
+}
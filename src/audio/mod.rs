@@ -5,21 +5,26 @@
 #[cfg(target_os = "windows")]
 pub mod windows;
 
+#[cfg(target_os = "windows")]
+pub mod windows_consent;
+
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+#[cfg(target_os = "linux")]
+pub mod alsa;
+
 #[cfg(target_os = "macos")]
 pub mod macos;
 
-// Re-export platform-specific implementation as 'platform'
-#[cfg(target_os = "windows")]
-pub use windows as platform;
+#[cfg(target_os = "macos")]
+pub mod macos_capture;
 
-#[cfg(target_os = "linux")]
-pub use linux as platform;
+#[cfg(target_os = "freebsd")]
+pub mod freebsd;
 
-#[cfg(target_os = "macos")]
-pub use macos as platform;
+use crate::error::ValidatorError;
+use serde::{Deserialize, Serialize};
 
 // Shared data structures (platform-agnostic)
 
@@ -37,31 +42,205 @@ pub struct AudioAppSession {
     pub volume: f32,          // Per-app volume 0.0-100.0
     pub is_active: bool,      // Whether session is currently active
     pub peak_level: f32,      // Current audio level 0.0-1.0
+    pub is_muted: bool,       // Session muted in the volume mixer
     pub process_id: u32,      // Process ID
     pub window_title: String, // Window title of the application
+    pub output_device: Option<OutputDevice>, // Device the session renders to, when known
+}
+
+/// An application capturing from a microphone
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MicAppSession {
+    pub name: String,           // Process name (e.g., "Teams.exe"), or bundle/package name
+    pub process_id: u32,        // Capturing process, 0 where the platform does not tell
+    pub device: Option<String>, // Capture device the session records from, when known
+}
+
+impl MicAppSession {
+    /// A session known only by name (consent store, heuristics)
+    pub fn named(name: String) -> Self {
+        MicAppSession { name, ..Default::default() }
+    }
+}
+
+/// A process capturing from a microphone
+#[derive(Debug, Clone)]
+pub struct CaptureSession {
+    pub process_id: u32,
+    pub peak_level: Option<f32>, // Input level of the session 0.0-1.0, None without a meter
+    pub device_level: bool,      // The level is the capture device's, not the session's (Pulse)
+}
+
+/// Physical kind of an output device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormFactor {
+    Speakers,
+    Headset,
+    Headphones,
+    Hdmi,
+    Bluetooth,
+    #[default]
+    Unknown,
+}
+
+impl OutputFormFactor {
+    /// Same spelling as the JSON output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormFactor::Speakers => "speakers",
+            OutputFormFactor::Headset => "headset",
+            OutputFormFactor::Headphones => "headphones",
+            OutputFormFactor::Hdmi => "hdmi",
+            OutputFormFactor::Bluetooth => "bluetooth",
+            OutputFormFactor::Unknown => "unknown",
+        }
+    }
+
+    /// Classify from the words a platform uses for a device: PulseAudio `device.form_factor`,
+    /// `device.bus` and port names, Core Audio transport types and output sources, device names
+    pub fn from_hints(hints: &[&str]) -> Self {
+        let hints = hints.join(" ").to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|word| hints.contains(word));
+
+        // The transport wins: a Bluetooth headset is reported as Bluetooth
+        if has(&["bluetooth", "bluez"]) {
+            OutputFormFactor::Bluetooth
+        } else if has(&["hdmi", "displayport", "display port"]) {
+            OutputFormFactor::Hdmi
+        } else if has(&["headset", "handset"]) {
+            OutputFormFactor::Headset
+        } else if has(&["headphone", "earphone"]) {
+            OutputFormFactor::Headphones
+        } else if has(&["speaker", "built-in", "internal"]) {
+            OutputFormFactor::Speakers
+        } else {
+            OutputFormFactor::Unknown
+        }
+    }
+}
+
+/// Drivers and modules that only pass audio on to other software
+/// (not a bare "virtual": real headsets advertise "Virtual Surround")
+const VIRTUAL_DEVICE_HINTS: &[&str] = &[
+    "vb-audio", "vb-cable", "voicemeeter", "blackhole", "soundflower", "loopback audio",
+    "null output", "dummy output", "null-sink", "null-audio-sink", "obs monitor", "obs-monitor",
+    "virtual cable", "virtual audio device", "virtual device",
+];
+
+/// Whether a device is a virtual sink or loopback (VB-Cable, BlackHole, PulseAudio null
+/// sinks, OBS monitors): audio sent there is usually recorded or streamed, not heard.
+/// `hints` are names, descriptions, drivers and transport types as the platform reports them.
+pub fn is_virtual_device(hints: &[&str]) -> bool {
+    let hints = hints.join(" ").to_lowercase();
+    VIRTUAL_DEVICE_HINTS.iter().any(|hint| hints.contains(hint))
+}
+
+/// An output endpoint and what kind of device it is
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputDevice {
+    pub name: String,
+    pub form_factor: OutputFormFactor,
+    /// Virtual sink or loopback device, see `is_virtual_device`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_virtual: bool,
+}
+
+/// An active endpoint and the apps using it, for per-device breakdowns
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceUsage {
+    pub name: String,
+    pub form_factor: OutputFormFactor,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_virtual: bool,
+    pub is_default: bool,
+    pub apps: Vec<String>,
 }
 
 // Platform audio backend trait
-// All platforms must implement these functions
+// Methods take `&self` so the pipeline can run against any implementation, including
+// the scripted MockBackend used by tests (see mock_backend.rs)
 pub trait AudioBackend {
     /// Get microphone volume and mute status
-    fn get_microphone_volume_and_mute() -> Result<AudioInfo, Box<dyn std::error::Error>>;
+    fn get_microphone_volume_and_mute(&self) -> Result<AudioInfo, ValidatorError>;
 
     /// Get name of default microphone device
-    fn get_microphone_device_name() -> Result<String, Box<dyn std::error::Error>>;
-
-    /// Get list of applications currently using the microphone
-    fn get_apps_using_microphone() -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    fn get_microphone_device_name(&self) -> Result<String, ValidatorError>;
+
+    /// Get the applications currently using the microphone, with their process where known
+    fn get_apps_using_microphone(&self) -> Result<Vec<MicAppSession>, ValidatorError>;
+
+    /// Get every active capture device with the apps capturing from it
+    /// (only the default device where unsupported)
+    fn get_microphone_devices(&self) -> Result<Vec<DeviceUsage>, ValidatorError> {
+        Ok(vec![DeviceUsage {
+            name: self.get_microphone_device_name()?,
+            is_default: true,
+            apps: self.get_apps_using_microphone()?.into_iter().map(|session| session.name).collect(),
+            ..Default::default()
+        }])
+    }
+
+    /// Get active capture sessions with their input level (empty where the platform has no
+    /// per-session capture meter)
+    fn get_microphone_sessions(&self) -> Result<Vec<CaptureSession>, ValidatorError> {
+        Ok(Vec::new())
+    }
+
+    /// Get list of applications currently using the camera (empty where unsupported)
+    fn get_apps_using_camera(&self) -> Result<Vec<String>, ValidatorError> {
+        Ok(Vec::new())
+    }
 
     /// Get audio output (speakers/headphones) volume and mute status
-    fn get_audio_output_volume_and_mute() -> Result<AudioInfo, Box<dyn std::error::Error>>;
+    fn get_audio_output_volume_and_mute(&self) -> Result<AudioInfo, ValidatorError>;
 
     /// Get name of default audio output device
-    fn get_audio_output_device_name() -> Result<String, Box<dyn std::error::Error>>;
+    fn get_audio_output_device_name(&self) -> Result<String, ValidatorError>;
+
+    /// Get default audio output device with its form factor (Unknown where unsupported)
+    fn get_audio_output_device(&self) -> Result<OutputDevice, ValidatorError> {
+        Ok(OutputDevice {
+            name: self.get_audio_output_device_name()?,
+            ..Default::default()
+        })
+    }
 
     /// Get current audio output peak level (0.0 to 1.0)
-    fn get_audio_output_peak_level() -> Result<f32, Box<dyn std::error::Error>>;
+    fn get_audio_output_peak_level(&self) -> Result<f32, ValidatorError>;
 
     /// Get list of applications currently playing audio
-    fn get_apps_playing_audio() -> Result<Vec<AudioAppSession>, Box<dyn std::error::Error>>;
+    fn get_apps_playing_audio(&self) -> Result<Vec<AudioAppSession>, ValidatorError>;
+
+    /// Drop cached state after a poll panicked
+    fn restart(&mut self) {}
+}
+
+/// The audio backend of the platform we were built for
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemAudio;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_factor_from_platform_hints() {
+        let classify = OutputFormFactor::from_hints;
+        assert_eq!(classify(&["headset", "bluetooth", "headset-output"]), OutputFormFactor::Bluetooth);
+        assert_eq!(classify(&["", "pci", "hdmi-output-0"]), OutputFormFactor::Hdmi);
+        assert_eq!(classify(&["Built-in", "External Headphones"]), OutputFormFactor::Headphones);
+        assert_eq!(classify(&["Built-in", "MacBook Pro Speakers"]), OutputFormFactor::Speakers);
+        assert_eq!(classify(&["usb", "analog-output"]), OutputFormFactor::Unknown);
+    }
+
+    #[test]
+    fn test_virtual_devices() {
+        assert!(is_virtual_device(&["CABLE Input (VB-Audio Virtual Cable)"]));
+        assert!(is_virtual_device(&["BlackHole 2ch"]));
+        assert!(is_virtual_device(&["Null Output"]));
+        assert!(!is_virtual_device(&["Razer Kraken 7.1 Virtual Surround"]));
+        assert!(!is_virtual_device(&["Jabra Evolve2 65", "USB"]));
+        assert!(!is_virtual_device(&["MacBook Pro Speakers", "Built-in"]));
+    }
 }
@@ -1,48 +1,73 @@
 // Windows audio backend using WASAPI (Windows Audio Session API)
 // This is a refactored version of wasapi_audio.rs
 
-use super::{AudioAppSession, AudioBackend, AudioInfo};
+use super::{is_virtual_device, AudioAppSession, AudioBackend, AudioInfo, CaptureSession, DeviceUsage, MicAppSession, OutputDevice, OutputFormFactor, SystemAudio};
+use crate::error::ValidatorError;
+use crate::process_cache::{self, PidCache};
 use windows::core::*;
+use windows::Win32::Devices::FunctionDiscovery::{PKEY_Device_EnumeratorName, PKEY_Device_FriendlyName};
 use windows::Win32::Foundation::*;
 use windows::Win32::Media::Audio::Endpoints::*;
 use windows::Win32::Media::Audio::*;
 use windows::Win32::System::Com::*;
+use windows::Win32::UI::Shell::PropertiesSystem::PROPERTYKEY;
 
 // Implement the AudioBackend trait for Windows
-impl AudioBackend for () {
-    fn get_microphone_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+impl AudioBackend for SystemAudio {
+    fn get_microphone_volume_and_mute(&self) -> std::result::Result<AudioInfo, ValidatorError> {
         get_microphone_volume_and_mute_impl()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            .map_err(ValidatorError::from)
     }
 
-    fn get_microphone_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_microphone_device_name(&self) -> std::result::Result<String, ValidatorError> {
         get_microphone_device_name_impl()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            .map_err(ValidatorError::from)
     }
 
-    fn get_apps_using_microphone() -> std::result::Result<Vec<String>, Box<dyn std::error::Error>> {
+    fn get_apps_using_microphone(&self) -> std::result::Result<Vec<MicAppSession>, ValidatorError> {
         get_apps_using_microphone_impl()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            .map_err(ValidatorError::from)
     }
 
-    fn get_audio_output_volume_and_mute() -> std::result::Result<AudioInfo, Box<dyn std::error::Error>> {
+    fn get_microphone_devices(&self) -> std::result::Result<Vec<DeviceUsage>, ValidatorError> {
+        get_microphone_devices_impl()
+            .map_err(ValidatorError::from)
+    }
+
+    fn get_microphone_sessions(&self) -> std::result::Result<Vec<CaptureSession>, ValidatorError> {
+        get_microphone_sessions_impl()
+            .map_err(ValidatorError::from)
+    }
+
+    fn get_audio_output_volume_and_mute(&self) -> std::result::Result<AudioInfo, ValidatorError> {
         get_audio_output_volume_and_mute_impl()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            .map_err(ValidatorError::from)
     }
 
-    fn get_audio_output_device_name() -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_audio_output_device_name(&self) -> std::result::Result<String, ValidatorError> {
         get_audio_output_device_name_impl()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            .map_err(ValidatorError::from)
+    }
+
+    fn get_audio_output_device(&self) -> std::result::Result<OutputDevice, ValidatorError> {
+        get_audio_output_device_impl()
+            .map_err(ValidatorError::from)
     }
 
-    fn get_audio_output_peak_level() -> std::result::Result<f32, Box<dyn std::error::Error>> {
+    fn get_audio_output_peak_level(&self) -> std::result::Result<f32, ValidatorError> {
         get_audio_output_peak_level_impl()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            .map_err(ValidatorError::from)
     }
 
-    fn get_apps_playing_audio() -> std::result::Result<Vec<AudioAppSession>, Box<dyn std::error::Error>> {
+    // Session process names and titles are looked up again
+    fn restart(&mut self) {
+        PROCESS_NAMES.clear();
+        WINDOW_TITLES.clear();
+    }
+
+    fn get_apps_playing_audio(&self) -> std::result::Result<Vec<AudioAppSession>, ValidatorError> {
         get_apps_playing_audio_impl()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            .map_err(ValidatorError::from)
     }
 }
 
@@ -87,56 +112,140 @@ fn get_microphone_device_name_impl() -> Result<String> {
             CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
 
         let device = enumerator.GetDefaultAudioEndpoint(eCapture, eConsole)?;
-
-        // Get device ID as string (simpler than getting friendly name)
-        let id = device.GetId()?;
-        let device_name = id.to_string()?;
+        let device_name = endpoint_device(&device).name;
 
         CoUninitialize();
 
-        // Return a simplified name or ID
         if device_name.is_empty() {
             Ok("Default Microphone".to_string())
         } else {
-            // Extract a readable name from the ID
-            Ok("Microphone".to_string())
+            Ok(device_name)
         }
     }
 }
 
-/// Get list of apps currently using the microphone
-fn get_apps_using_microphone_impl() -> Result<Vec<String>> {
+/// Get the capture sessions of every active capture device, one per process
+fn get_apps_using_microphone_impl() -> Result<Vec<MicAppSession>> {
+    let mut apps: Vec<MicAppSession> = Vec::new();
     unsafe {
         let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
 
         let enumerator: IMMDeviceEnumerator =
             CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let devices = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE)?;
+
+        for d in 0..devices.GetCount()? {
+            let Ok(device) = devices.Item(d) else { continue };
+            let endpoint = endpoint_device(&device);
+            for (name, session) in active_sessions(&device) {
+                if !apps.iter().any(|known| known.process_id == session.process_id) {
+                    apps.push(MicAppSession { name, process_id: session.process_id, device: Some(endpoint.name.clone()) });
+                }
+            }
+        }
 
-        let device = enumerator.GetDefaultAudioEndpoint(eCapture, eConsole)?;
+        CoUninitialize();
+    }
 
-        // Get the audio session manager
-        let session_manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+    // Apps holding the mic through the consent store that have no capture session
+    for app in super::windows_consent::apps_using_capability("microphone") {
+        if !apps.iter().any(|known| known.name.eq_ignore_ascii_case(&app)) {
+            apps.push(MicAppSession::named(app));
+        }
+    }
 
-        // Get the session enumerator
-        let session_enum = session_manager.GetSessionEnumerator()?;
-        let session_count = session_enum.GetCount()?;
+    Ok(apps)
+}
 
-        let mut apps = Vec::new();
+/// Get every active capture device with the apps capturing from it
+fn get_microphone_devices_impl() -> Result<Vec<DeviceUsage>> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
 
-        // Enumerate all audio sessions
-        for i in 0..session_count {
-            if let Ok(session) = session_enum.GetSession(i) {
-                if let Ok(session_control) = session.cast::<IAudioSessionControl2>() {
-                    // Get the process ID
-                    if let Ok(process_id) = session_control.GetProcessId() {
-                        if process_id != 0 {
-                            // Get process name
-                            if let Ok(process_name) = get_process_name(process_id) {
-                                // Check if this session is actively capturing audio
-                                if let Ok(state) = session_control.GetState() {
-                                    if state == AudioSessionStateActive {
-                                        apps.push(process_name);
-                                    }
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+
+        let default_id = enumerator
+            .GetDefaultAudioEndpoint(eCapture, eConsole)
+            .and_then(|device| device_id(&device))
+            .ok();
+
+        // A USB headset can be in use while the built-in mic stays the default
+        let devices = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE)?;
+
+        let mut usage = Vec::new();
+        for d in 0..devices.GetCount()? {
+            let Ok(device) = devices.Item(d) else { continue };
+            let endpoint = endpoint_device(&device);
+            usage.push(DeviceUsage {
+                name: endpoint.name,
+                form_factor: endpoint.form_factor,
+                is_virtual: endpoint.is_virtual,
+                is_default: default_id.is_some() && device_id(&device).ok() == default_id,
+                apps: active_sessions(&device).into_iter().map(|(name, _)| name).collect(),
+            });
+        }
+
+        CoUninitialize();
+
+        Ok(usage)
+    }
+}
+
+/// Get active capture sessions on every capture device, with their input peak
+fn get_microphone_sessions_impl() -> Result<Vec<CaptureSession>> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let devices = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE)?;
+
+        let mut sessions = Vec::new();
+        for d in 0..devices.GetCount()? {
+            let Ok(device) = devices.Item(d) else { continue };
+            sessions.extend(active_sessions(&device).into_iter().map(|(_, session)| session));
+        }
+
+        CoUninitialize();
+
+        Ok(sessions)
+    }
+}
+
+/// Endpoint ID, stable across runs for the same device
+unsafe fn device_id(device: &IMMDevice) -> Result<String> {
+    let id = device.GetId()?;
+    Ok(id.to_string()?)
+}
+
+/// Processes with an active session on `device`, by process name
+unsafe fn active_sessions(device: &IMMDevice) -> Vec<(String, CaptureSession)> {
+    let mut sessions = Vec::new();
+
+    let Ok(session_manager) = device.Activate::<IAudioSessionManager2>(CLSCTX_ALL, None) else { return sessions };
+    let Ok(session_enum) = session_manager.GetSessionEnumerator() else { return sessions };
+    let session_count = session_enum.GetCount().unwrap_or(0);
+
+    // Enumerate all audio sessions
+    for i in 0..session_count {
+        if let Ok(session) = session_enum.GetSession(i) {
+            if let Ok(session_control) = session.cast::<IAudioSessionControl2>() {
+                // Get the process ID
+                if let Ok(process_id) = session_control.GetProcessId() {
+                    if process_id != 0 {
+                        // Get process name
+                        if let Ok(process_name) = get_process_name(process_id) {
+                            // Check if this session is actively capturing audio
+                            if let Ok(state) = session_control.GetState() {
+                                if state == AudioSessionStateActive {
+                                    // Stays at zero while the app is muted in its own UI
+                                    // (see in_app_mute.rs)
+                                    let peak_level = session
+                                        .cast::<IAudioMeterInformation>()
+                                        .and_then(|meter| meter.GetPeakValue())
+                                        .ok();
+                                    sessions.push((process_name, CaptureSession { process_id, peak_level, device_level: false }));
                                 }
                             }
                         }
@@ -144,15 +253,24 @@ fn get_apps_using_microphone_impl() -> Result<Vec<String>> {
                 }
             }
         }
+    }
 
-        CoUninitialize();
+    sessions
+}
 
-        Ok(apps)
-    }
+static PROCESS_NAMES: PidCache<String> = PidCache::new(process_cache::NAME_TTL, process_cache::CAPACITY);
+static WINDOW_TITLES: PidCache<String> = PidCache::new(process_cache::TITLE_TTL, process_cache::CAPACITY);
+
+unsafe fn get_process_name(process_id: u32) -> Result<String> {
+    PROCESS_NAMES.get_or_try_insert_with(process_id, || query_process_name(process_id))
+}
+
+unsafe fn get_window_title_for_process(target_pid: u32) -> String {
+    WINDOW_TITLES.get_or_insert_with(target_pid, || query_window_title(target_pid))
 }
 
 /// Get process name from process ID
-unsafe fn get_process_name(process_id: u32) -> Result<String> {
+unsafe fn query_process_name(process_id: u32) -> Result<String> {
     use windows::Win32::System::Threading::*;
     use windows::core::PWSTR;
 
@@ -180,7 +298,7 @@ unsafe fn get_process_name(process_id: u32) -> Result<String> {
 
 /// Get window title for a given process ID
 /// For multi-process apps like browsers, finds any window from the same executable
-unsafe fn get_window_title_for_process(target_pid: u32) -> String {
+unsafe fn query_window_title(target_pid: u32) -> String {
     use windows::Win32::UI::WindowsAndMessaging::*;
     use std::sync::Mutex;
 
@@ -273,6 +391,16 @@ fn get_audio_output_volume_and_mute_impl() -> Result<AudioInfo> {
 
 /// Get audio output device name
 fn get_audio_output_device_name_impl() -> Result<String> {
+    let device = get_audio_output_device_impl()?;
+    if device.name.is_empty() {
+        Ok("Default Speakers".to_string())
+    } else {
+        Ok(device.name)
+    }
+}
+
+/// Get default audio output device with its form factor
+fn get_audio_output_device_impl() -> Result<OutputDevice> {
     unsafe {
         let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
 
@@ -280,17 +408,11 @@ fn get_audio_output_device_name_impl() -> Result<String> {
             CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
 
         let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
-
-        let id = device.GetId()?;
-        let device_name = id.to_string()?;
+        let output_device = endpoint_device(&device);
 
         CoUninitialize();
 
-        if device_name.is_empty() {
-            Ok("Default Speakers".to_string())
-        } else {
-            Ok("Speakers".to_string())
-        }
+        Ok(output_device)
     }
 }
 
@@ -316,7 +438,7 @@ fn get_audio_output_peak_level_impl() -> Result<f32> {
     }
 }
 
-/// Get list of apps currently playing audio
+/// Get list of apps currently playing audio, on every active output device
 fn get_apps_playing_audio_impl() -> Result<Vec<AudioAppSession>> {
     unsafe {
         let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
@@ -324,55 +446,61 @@ fn get_apps_playing_audio_impl() -> Result<Vec<AudioAppSession>> {
         let enumerator: IMMDeviceEnumerator =
             CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
 
-        // Get default audio RENDER device (speakers)
-        let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
-
-        let session_manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
-        let session_enum = session_manager.GetSessionEnumerator()?;
-        let session_count = session_enum.GetCount()?;
+        // All active RENDER devices: a call can play on a headset while music uses the speakers
+        let devices = enumerator.EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)?;
 
         let mut apps = Vec::new();
 
-        for i in 0..session_count {
-            if let Ok(session) = session_enum.GetSession(i) {
-                if let Ok(session_control) = session.cast::<IAudioSessionControl2>() {
-                    if let Ok(process_id) = session_control.GetProcessId() {
-                        if process_id != 0 {
-                            if let Ok(process_name) = get_process_name(process_id) {
-                                if let Ok(state) = session_control.GetState() {
-                                    let is_active = state == AudioSessionStateActive;
-
-                                    // Get session volume
-                                    let volume = if let Ok(volume_control) = session.cast::<ISimpleAudioVolume>() {
-                                        if let Ok(vol) = volume_control.GetMasterVolume() {
-                                            vol * 100.0
+        for d in 0..devices.GetCount()? {
+            let Ok(device) = devices.Item(d) else { continue };
+            let output_device = endpoint_device(&device);
+
+            let Ok(session_manager) = device.Activate::<IAudioSessionManager2>(CLSCTX_ALL, None) else { continue };
+            let Ok(session_enum) = session_manager.GetSessionEnumerator() else { continue };
+            let session_count = session_enum.GetCount().unwrap_or(0);
+
+            for i in 0..session_count {
+                if let Ok(session) = session_enum.GetSession(i) {
+                    if let Ok(session_control) = session.cast::<IAudioSessionControl2>() {
+                        if let Ok(process_id) = session_control.GetProcessId() {
+                            if process_id != 0 {
+                                if let Ok(process_name) = get_process_name(process_id) {
+                                    if let Ok(state) = session_control.GetState() {
+                                        let is_active = state == AudioSessionStateActive;
+
+                                        // Get session volume and mute
+                                        let (volume, is_muted) = if let Ok(volume_control) = session.cast::<ISimpleAudioVolume>() {
+                                            (
+                                                volume_control.GetMasterVolume().map(|vol| vol * 100.0).unwrap_or(0.0),
+                                                volume_control.GetMute().map(|muted| muted.as_bool()).unwrap_or(false),
+                                            )
+                                        } else {
+                                            (0.0, false)
+                                        };
+
+                                        // Get peak meter for this session
+                                        let peak_level = if let Ok(meter) = session.cast::<IAudioMeterInformation>() {
+                                            meter.GetPeakValue().unwrap_or(0.0)
                                         } else {
                                             0.0
+                                        };
+
+                                        // Only include if the app is actually playing audio or was recently
+                                        if is_active || peak_level > 0.0 {
+                                            // Get window title for this process
+                                            let window_title = get_window_title_for_process(process_id);
+
+                                            apps.push(AudioAppSession {
+                                                name: process_name,
+                                                volume,
+                                                is_active,
+                                                peak_level,
+                                                is_muted,
+                                                process_id,
+                                                window_title,
+                                                output_device: Some(output_device.clone()),
+                                            });
                                         }
-                                    } else {
-                                        0.0
-                                    };
-
-                                    // Get peak meter for this session
-                                    let peak_level = if let Ok(meter) = session.cast::<IAudioMeterInformation>() {
-                                        meter.GetPeakValue().unwrap_or(0.0)
-                                    } else {
-                                        0.0
-                                    };
-
-                                    // Only include if the app is actually playing audio or was recently
-                                    if is_active || peak_level > 0.0 {
-                                        // Get window title for this process
-                                        let window_title = get_window_title_for_process(process_id);
-
-                                        apps.push(AudioAppSession {
-                                            name: process_name,
-                                            volume,
-                                            is_active,
-                                            peak_level,
-                                            process_id,
-                                            window_title,
-                                        });
                                     }
                                 }
                             }
@@ -388,31 +516,36 @@ fn get_apps_playing_audio_impl() -> Result<Vec<AudioAppSession>> {
     }
 }
 
-// Public convenience functions (for backward compatibility if needed)
-pub fn get_microphone_volume_and_mute() -> Result<AudioInfo> {
-    get_microphone_volume_and_mute_impl()
-}
-
-pub fn get_microphone_device_name() -> Result<String> {
-    get_microphone_device_name_impl()
-}
-
-pub fn get_apps_using_microphone() -> Result<Vec<String>> {
-    get_apps_using_microphone_impl()
-}
-
-pub fn get_audio_output_volume_and_mute() -> Result<AudioInfo> {
-    get_audio_output_volume_and_mute_impl()
-}
-
-pub fn get_audio_output_device_name() -> Result<String> {
-    get_audio_output_device_name_impl()
-}
+/// Friendly name and form factor of an output endpoint
+unsafe fn endpoint_device(device: &IMMDevice) -> OutputDevice {
+    let Ok(store) = device.OpenPropertyStore(STGM_READ) else {
+        return OutputDevice::default();
+    };
+    let text = |key: &PROPERTYKEY| store.GetValue(key).map(|value| value.to_string()).unwrap_or_default();
+    let name = text(&PKEY_Device_FriendlyName);
+    let enumerator = text(&PKEY_Device_EnumeratorName).to_uppercase();
+
+    // Bluetooth endpoints also say Headset/Headphones; the bus enumerator (BTHENUM,
+    // BTHHFENUM, BTHLEDevice) tells them apart
+    let form_factor = if enumerator.starts_with("BTH") {
+        OutputFormFactor::Bluetooth
+    } else {
+        let endpoint = store
+            .GetValue(&PKEY_AudioEndpoint_FormFactor)
+            .ok()
+            .and_then(|value| u32::try_from(&value).ok())
+            .map(|value| EndpointFormFactor(value as i32));
+        match endpoint {
+            Some(Headset | Handset) => OutputFormFactor::Headset,
+            Some(Headphones) => OutputFormFactor::Headphones,
+            Some(Speakers) => OutputFormFactor::Speakers,
+            Some(DigitalAudioDisplayDevice) => OutputFormFactor::Hdmi,
+            _ => OutputFormFactor::from_hints(&[&name]),
+        }
+    };
 
-pub fn get_audio_output_peak_level() -> Result<f32> {
-    get_audio_output_peak_level_impl()
-}
+    // Software-only drivers (VB-Cable, Voicemeeter) are root-enumerated
+    let is_virtual = enumerator == "ROOT" || is_virtual_device(&[&name]);
 
-pub fn get_apps_playing_audio() -> Result<Vec<AudioAppSession>> {
-    get_apps_playing_audio_impl()
+    OutputDevice { name, form_factor, is_virtual }
 }
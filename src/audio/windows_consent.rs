@@ -0,0 +1,127 @@
+// Windows capability consent store reader
+// Windows records every app's microphone use under
+//   HKCU\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone
+// as LastUsedTimeStart / LastUsedTimeStop FILETIMEs. An app whose LastUsedTimeStop is 0
+// holds the capability right now, even when WASAPI session enumeration misses it
+// (e.g. exclusive-mode capture or a non-default device).
+
+use windows::core::{HSTRING, PCWSTR, PWSTR};
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::System::Registry::*;
+
+const CONSENT_STORE: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+
+/// Apps currently holding `capability` ("microphone", "webcam", ...)
+/// Desktop apps are reported by executable name (e.g. "Zoom.exe"), packaged apps by
+/// package name (e.g. "MicrosoftTeams").
+pub fn apps_using_capability(capability: &str) -> Vec<String> {
+    let root = format!(r"{}\{}", CONSENT_STORE, capability);
+    let mut apps = Vec::new();
+
+    unsafe {
+        let Some(store) = open_key(HKEY_CURRENT_USER, &root) else {
+            return apps;
+        };
+
+        for subkey in subkeys(store) {
+            if subkey == "NonPackaged" {
+                // Desktop apps: one subkey per executable path with '\' replaced by '#'
+                if let Some(non_packaged) = open_key(store, &subkey) {
+                    for path in subkeys(non_packaged) {
+                        if is_in_use(non_packaged, &path) {
+                            apps.push(path.rsplit('#').next().unwrap_or(&path).to_string());
+                        }
+                    }
+                    let _ = RegCloseKey(non_packaged);
+                }
+            } else if is_in_use(store, &subkey) {
+                // Packaged apps: package family name "Name_publisherhash"
+                apps.push(subkey.split('_').next().unwrap_or(&subkey).to_string());
+            }
+        }
+
+        let _ = RegCloseKey(store);
+    }
+
+    apps
+}
+
+/// The user's global switch for `capability`: "Allow" or "Deny"; None when unreadable
+pub fn capability_setting(capability: &str) -> Option<String> {
+    let root = format!(r"{}\{}", CONSENT_STORE, capability);
+
+    unsafe {
+        let store = open_key(HKEY_CURRENT_USER, &root)?;
+        let value = read_string(store, "Value");
+        let _ = RegCloseKey(store);
+        value
+    }
+}
+
+unsafe fn open_key(parent: HKEY, path: &str) -> Option<HKEY> {
+    let mut key = HKEY::default();
+    let status = RegOpenKeyExW(parent, &HSTRING::from(path), 0, KEY_READ, &mut key);
+    (status == ERROR_SUCCESS).then_some(key)
+}
+
+unsafe fn subkeys(key: HKEY) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut buffer = [0u16; 512];
+
+    for index in 0.. {
+        let mut len = buffer.len() as u32;
+        let status = RegEnumKeyExW(key, index, PWSTR(buffer.as_mut_ptr()), &mut len, None, PWSTR::null(), None, None);
+        if status != ERROR_SUCCESS {
+            break;
+        }
+        names.push(String::from_utf16_lossy(&buffer[..len as usize]));
+    }
+
+    names
+}
+
+/// Started and not stopped: LastUsedTimeStart > 0 and LastUsedTimeStop == 0
+unsafe fn is_in_use(parent: HKEY, subkey: &str) -> bool {
+    let Some(key) = open_key(parent, subkey) else {
+        return false;
+    };
+    let start = read_qword(key, "LastUsedTimeStart").unwrap_or(0);
+    let stop = read_qword(key, "LastUsedTimeStop").unwrap_or(0);
+    let _ = RegCloseKey(key);
+
+    start != 0 && stop == 0
+}
+
+unsafe fn read_qword(key: HKEY, name: &str) -> Option<u64> {
+    let mut value = 0u64;
+    let mut size = std::mem::size_of::<u64>() as u32;
+    let name = HSTRING::from(name);
+    let status = RegQueryValueExW(
+        key,
+        PCWSTR(name.as_ptr()),
+        None,
+        None,
+        Some(&mut value as *mut u64 as *mut u8),
+        Some(&mut size),
+    );
+    (status == ERROR_SUCCESS).then_some(value)
+}
+
+unsafe fn read_string(key: HKEY, name: &str) -> Option<String> {
+    let mut buffer = [0u16; 64];
+    let mut size = std::mem::size_of_val(&buffer) as u32;
+    let name = HSTRING::from(name);
+    let status = RegQueryValueExW(
+        key,
+        PCWSTR(name.as_ptr()),
+        None,
+        None,
+        Some(buffer.as_mut_ptr() as *mut u8),
+        Some(&mut size),
+    );
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+    let len = (size as usize / 2).min(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]).trim_end_matches('\0').to_string())
+}
@@ -0,0 +1,181 @@
+// Render stream markers for recorder synchronization (`capture` feature, --audio-markers)
+// While a call is active the loopback tap (capture.rs) feeds the call app's render stream
+// through a detector that emits `audio_marker` events the companion recorder can align with:
+//   - `speech_start` / `silence_start` where the output crosses between silence and sound
+//   - `hash` every second: 32 bits, bit i set when the energy of 100 ms window i+1 is
+//     above window i, over the last 33 windows (3.3 s). The recorder computes the same
+//     over its own recording and matches by Hamming distance.
+// Each marker carries the capture time of its window. Only these summaries leave the
+// detector; the samples are dropped as soon as their window's energy is taken.
+
+use crate::capture::{self, AudioFrame, CaptureScope, LoopbackStream};
+use crate::events::{AudioMarkerKind, AudioMarkerPayload, MonitorEvent};
+use crate::CallInfo;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Energy windows per second
+const WINDOWS_PER_SEC: u32 = 10;
+/// Windows in one hash: 32 energy comparisons
+const HASH_WINDOWS: usize = 33;
+/// Sound above this level, in dBFS, starts speech
+const SPEECH_DB: f32 = -45.0;
+/// Sound below this level ends it
+const SILENCE_DB: f32 = -50.0;
+/// Windows the level must hold before a boundary is reported
+const SPEECH_WINDOWS: u32 = 2;
+const SILENCE_WINDOWS: u32 = 5;
+
+/// Turns render frames into markers
+#[derive(Debug, Default)]
+pub struct MarkerDetector {
+    /// Sum of squares, sample count and start of the window being filled
+    window: (f64, u32, Option<SystemTime>),
+    /// Level and start of the last HASH_WINDOWS windows
+    levels: VecDeque<(f32, SystemTime)>,
+    is_speech: bool,
+    /// Windows in a row on the other side of the current state
+    streak: u32,
+    windows_since_hash: u32,
+}
+
+impl MarkerDetector {
+    pub fn feed(&mut self, frame: &AudioFrame) -> Vec<(AudioMarkerKind, SystemTime, Option<u32>)> {
+        let window_len = (frame.sample_rate / WINDOWS_PER_SEC).max(1);
+        let mut markers = Vec::new();
+        for (i, sample) in frame.samples.iter().enumerate() {
+            let (sum, count, start) = &mut self.window;
+            if start.is_none() {
+                let offset = Duration::from_secs_f64(i as f64 / frame.sample_rate as f64);
+                *start = Some(frame.captured_at + offset);
+            }
+            *sum += (*sample as f64).powi(2);
+            *count += 1;
+            if *count == window_len {
+                let db = 10.0 * (*sum / *count as f64).max(1e-10).log10() as f32;
+                let at = start.unwrap_or(frame.captured_at);
+                self.window = (0.0, 0, None);
+                markers.extend(self.close_window(db, at));
+            }
+        }
+        markers
+    }
+
+    fn close_window(&mut self, db: f32, at: SystemTime) -> Vec<(AudioMarkerKind, SystemTime, Option<u32>)> {
+        let mut markers = Vec::new();
+        self.levels.push_back((db, at));
+        if self.levels.len() > HASH_WINDOWS {
+            self.levels.pop_front();
+        }
+
+        let crossed = if self.is_speech { db < SILENCE_DB } else { db > SPEECH_DB };
+        self.streak = if crossed { self.streak + 1 } else { 0 };
+        let needed = if self.is_speech { SILENCE_WINDOWS } else { SPEECH_WINDOWS };
+        if self.streak >= needed {
+            self.is_speech = !self.is_speech;
+            self.streak = 0;
+            // The boundary is where the new level began
+            let start = self.levels[self.levels.len() - needed as usize].1;
+            let kind = if self.is_speech { AudioMarkerKind::SpeechStart } else { AudioMarkerKind::SilenceStart };
+            markers.push((kind, start, None));
+        }
+
+        self.windows_since_hash += 1;
+        let is_silent = self.levels.iter().all(|(db, _)| *db < SILENCE_DB);
+        if self.levels.len() == HASH_WINDOWS && self.windows_since_hash >= WINDOWS_PER_SEC && !is_silent {
+            self.windows_since_hash = 0;
+            let hash = self
+                .levels
+                .iter()
+                .zip(self.levels.iter().skip(1))
+                .enumerate()
+                .fold(0u32, |hash, (bit, ((before, _), (after, _)))| if after > before { hash | 1 << bit } else { hash });
+            markers.push((AudioMarkerKind::Hash, at, Some(hash)));
+        }
+        markers
+    }
+}
+
+/// The tap and detector for the active call
+#[derive(Default)]
+pub struct AudioMarkers {
+    /// Call being tapped; None for a call whose tap could not start
+    call: Option<(u32, Option<(LoopbackStream, MarkerDetector)>)>,
+}
+
+impl AudioMarkers {
+    /// Markers for `call` (the active call after this tick) since the last update
+    pub fn update(&mut self, call: Option<&CallInfo>) -> Vec<MonitorEvent> {
+        let Some(call) = call else {
+            self.call = None;
+            return Vec::new();
+        };
+        if self.call.as_ref().map(|(process_id, _)| *process_id) != Some(call.process_id) {
+            let tap = capture::start_loopback_capture(call.process_id)
+                .map_err(|e| log::warn!("--audio-markers: no loopback capture: {}", e))
+                .ok()
+                .map(|tap| {
+                    if tap.scope() == CaptureScope::Output {
+                        log::warn!("--audio-markers: {} has no stream of its own; tapping the whole output", call.app);
+                    }
+                    (tap, MarkerDetector::default())
+                });
+            self.call = Some((call.process_id, tap));
+        }
+
+        let Some((process_id, Some((tap, detector)))) = self.call.as_mut() else {
+            return Vec::new();
+        };
+        let process_id = *process_id;
+        tap.frames()
+            .iter()
+            .flat_map(|frame| detector.feed(frame))
+            .map(|(kind, at, hash)| {
+                MonitorEvent::AudioMarker(AudioMarkerPayload {
+                    process_id,
+                    kind,
+                    at: chrono::DateTime::<chrono::Utc>::from(at).to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                    at_ms: at.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64),
+                    hash: hash.map(|hash| format!("{:08x}", hash)),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(at_secs: u64, secs: u64, amplitude: impl Fn(usize) -> f32) -> AudioFrame {
+        AudioFrame {
+            sample_rate: 1000,
+            samples: (0..secs as usize * 1000).map(|i| amplitude(i) * if i % 2 == 0 { 1.0 } else { -1.0 }).collect(),
+            captured_at: UNIX_EPOCH + Duration::from_secs(at_secs),
+        }
+    }
+
+    #[test]
+    fn test_boundaries_and_hashes_follow_the_render_stream() {
+        let mut detector = MarkerDetector::default();
+        let mut markers = detector.feed(&frame(0, 1, |_| 0.0));
+        // Louder and quieter every 100 ms window
+        markers.extend(detector.feed(&frame(1, 4, |i| if i / 100 % 3 == 0 { 0.5 } else { 0.1 })));
+        markers.extend(detector.feed(&frame(5, 1, |_| 0.0)));
+
+        let at = |secs: f64| UNIX_EPOCH + Duration::from_secs_f64(secs);
+        let boundaries: Vec<_> = markers.iter().filter(|(_, _, hash)| hash.is_none()).collect();
+        assert_eq!(
+            boundaries,
+            vec![&(AudioMarkerKind::SpeechStart, at(1.0), None), &(AudioMarkerKind::SilenceStart, at(5.0), None)]
+        );
+
+        let hashes: Vec<u32> = markers.iter().filter_map(|(_, _, hash)| *hash).collect();
+        assert!(!hashes.is_empty());
+        // The same audio hashes the same
+        let mut again = MarkerDetector::default();
+        let mut repeated = again.feed(&frame(0, 1, |_| 0.0));
+        repeated.extend(again.feed(&frame(1, 4, |i| if i / 100 % 3 == 0 { 0.5 } else { 0.1 })));
+        assert_eq!(repeated.iter().filter_map(|(_, _, hash)| *hash).next(), hashes.first().copied());
+    }
+}
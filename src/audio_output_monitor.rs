@@ -1,5 +1,6 @@
+use crate::audio::{DeviceUsage, OutputDevice, OutputFormFactor};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
+use crate::error::ValidatorError;
 
 /// Complete audio output status report
 #[derive(Debug, Serialize, Deserialize)]
@@ -7,6 +8,9 @@ pub struct AudioOutputReport {
     pub timestamp: String,
     pub output: AudioOutputInfo,
     pub active_apps: Vec<AudioAppInfo>,
+    /// The default output plus every other device an app plays to
+    #[serde(default)]
+    pub devices: Vec<DeviceUsage>,
     pub errors: Vec<String>,
 }
 
@@ -18,6 +22,12 @@ pub struct AudioOutputInfo {
     pub volume_level: f32,
     pub peak_level: f32,
     pub is_active: bool,
+    /// Headset, speakers, HDMI or Bluetooth, for the default device
+    #[serde(default)]
+    pub form_factor: OutputFormFactor,
+    /// The default device is a virtual sink or loopback (VB-Cable, BlackHole, null sink)
+    #[serde(default)]
+    pub is_virtual: bool,
 }
 
 /// Information about an app playing audio
@@ -29,6 +39,9 @@ pub struct AudioAppInfo {
     pub peak_level: f32,
     pub process_id: u32,
     pub window_title: String,
+    /// Device the app renders to (matters on multi-device systems)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_device: Option<OutputDevice>,
 }
 
 /// Main audio output monitor struct
@@ -38,52 +51,56 @@ pub struct AudioOutputMonitor {
 
 impl AudioOutputMonitor {
     /// Create a new audio output monitor instance
-    pub fn new() -> std::result::Result<Self, Box<dyn Error>> {
+    pub fn new() -> std::result::Result<Self, ValidatorError> {
         Ok(AudioOutputMonitor {
             errors: Vec::new(),
         })
     }
 
     /// Build complete JSON status report
-    pub fn build_status_report(&mut self) -> std::result::Result<AudioOutputReport, Box<dyn Error>> {
-        #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    pub fn build_status_report(&mut self) -> std::result::Result<AudioOutputReport, ValidatorError> {
+        #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd"))]
         {
             let output_info = self.get_output_info();
             let active_apps = self.get_active_apps();
+            let devices = device_breakdown(&output_info, &active_apps);
 
             Ok(AudioOutputReport {
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 output: output_info,
                 active_apps,
+                devices,
                 errors: self.errors.clone(),
             })
         }
 
-        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd")))]
         {
-            Err("Audio output monitoring is only supported on Windows, Linux, and macOS".into())
+            Err(ValidatorError::BackendUnavailable("Audio output monitoring outside Windows, Linux, macOS and FreeBSD".to_string()))
         }
     }
 
-    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd"))]
     fn get_output_info(&mut self) -> AudioOutputInfo {
-        use crate::audio::platform;
+        use crate::audio::{AudioBackend, SystemAudio};
 
         // Get default audio output device info
-        let (device_name, volume_level, is_muted) = match platform::get_audio_output_volume_and_mute() {
+        let (device, volume_level, is_muted) = match SystemAudio.get_audio_output_volume_and_mute() {
             Ok(audio_info) => {
-                let name = platform::get_audio_output_device_name()
-                    .unwrap_or_else(|_| "Default Speakers".to_string());
-                (name, audio_info.volume, audio_info.is_muted)
+                let device = SystemAudio.get_audio_output_device().unwrap_or_else(|_| OutputDevice {
+                    name: "Default Speakers".to_string(),
+                    ..Default::default()
+                });
+                (device, audio_info.volume, audio_info.is_muted)
             }
             Err(e) => {
                 self.errors.push(format!("Audio output error: {}", e));
-                ("Default Speakers".to_string(), 50.0, false)
+                (OutputDevice { name: "Default Speakers".to_string(), ..Default::default() }, 50.0, false)
             }
         };
 
         // Get peak level (current audio level)
-        let peak_level = match platform::get_audio_output_peak_level() {
+        let peak_level = match SystemAudio.get_audio_output_peak_level() {
             Ok(level) => level,
             Err(e) => {
                 self.errors.push(format!("Failed to get peak level: {}", e));
@@ -94,19 +111,21 @@ impl AudioOutputMonitor {
         let is_active = peak_level > 0.01; // Audio is playing if peak > 1%
 
         AudioOutputInfo {
-            default_device: device_name,
+            default_device: device.name,
             is_muted,
             volume_level,
             peak_level,
             is_active,
+            form_factor: device.form_factor,
+            is_virtual: device.is_virtual,
         }
     }
 
-    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd"))]
     fn get_active_apps(&mut self) -> Vec<AudioAppInfo> {
-        use crate::audio::platform;
+        use crate::audio::{AudioBackend, SystemAudio};
 
-        match platform::get_apps_playing_audio() {
+        match SystemAudio.get_apps_playing_audio() {
             Ok(apps) => apps.into_iter().map(|app| {
                 AudioAppInfo {
                     name: app.name,
@@ -115,6 +134,7 @@ impl AudioOutputMonitor {
                     peak_level: app.peak_level,
                     process_id: app.process_id,
                     window_title: app.window_title,
+                    output_device: app.output_device,
                 }
             }).collect(),
             Err(e) => {
@@ -124,3 +144,88 @@ impl AudioOutputMonitor {
         }
     }
 }
+
+/// Apps grouped by the device they play to, default device first
+/// Sessions without a known device are counted on the default one.
+fn device_breakdown(output: &AudioOutputInfo, apps: &[AudioAppInfo]) -> Vec<DeviceUsage> {
+    let mut devices = vec![DeviceUsage {
+        name: output.default_device.clone(),
+        form_factor: output.form_factor,
+        is_virtual: output.is_virtual,
+        is_default: true,
+        apps: Vec::new(),
+    }];
+
+    for app in apps {
+        let device = app.output_device.clone().unwrap_or_else(|| OutputDevice {
+            name: output.default_device.clone(),
+            form_factor: output.form_factor,
+            is_virtual: output.is_virtual,
+        });
+        let index = match devices.iter().position(|usage| usage.name == device.name) {
+            Some(index) => index,
+            None => {
+                devices.push(DeviceUsage {
+                    name: device.name,
+                    form_factor: device.form_factor,
+                    is_virtual: device.is_virtual,
+                    is_default: false,
+                    apps: Vec::new(),
+                });
+                devices.len() - 1
+            }
+        };
+        if !devices[index].apps.contains(&app.name) {
+            devices[index].apps.push(app.name.clone());
+        }
+    }
+
+    devices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, device: Option<(&str, OutputFormFactor)>) -> AudioAppInfo {
+        AudioAppInfo {
+            name: name.to_string(),
+            volume: 100.0,
+            is_playing: true,
+            peak_level: 0.5,
+            process_id: 1,
+            window_title: String::new(),
+            output_device: device.map(|(name, form_factor)| OutputDevice {
+                name: name.to_string(),
+                form_factor,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_sessions_grouped_per_device() {
+        let output = AudioOutputInfo {
+            default_device: "Speakers".to_string(),
+            is_muted: false,
+            volume_level: 50.0,
+            peak_level: 0.5,
+            is_active: true,
+            form_factor: OutputFormFactor::Speakers,
+            is_virtual: false,
+        };
+        let apps = [
+            app("Spotify.exe", Some(("Speakers", OutputFormFactor::Speakers))),
+            app("Teams.exe", Some(("Jabra Evolve", OutputFormFactor::Headset))),
+            app("vlc", None),
+        ];
+
+        let devices = device_breakdown(&output, &apps);
+        assert_eq!(devices.len(), 2);
+        assert!(devices[0].is_default);
+        assert_eq!(devices[0].apps, vec!["Spotify.exe", "vlc"]);
+        assert_eq!(devices[1].name, "Jabra Evolve");
+        assert_eq!(devices[1].form_factor, OutputFormFactor::Headset);
+        assert_eq!(devices[1].apps, vec!["Teams.exe"]);
+    }
+}
@@ -0,0 +1,132 @@
+// `bench` subcommand: time full poll cycles of the platform backend
+//
+//   rust-audio-validator bench [--cycles N] [--budget-ms MS]
+//
+// Runs N cycles (default 50) with every subsystem due, the way the first cycle after
+// start-up or an idle wake-up polls, and prints mean, p95 and max time per subsystem.
+// With --budget-ms the exit code is 1 when the mean cycle exceeds it, so a CI job on
+// the target laptops can hold changes to a CPU budget.
+// `cargo bench` (benches/poll_cycle.rs) runs the same cycle under criterion through
+// `platform_cycle` and `mock_cycle`, for tracking regressions between builds.
+
+use crate::audio::{AudioBackend, SystemAudio};
+use crate::call_tracker::{CallTracker, Sample};
+use crate::correlation_engine::CorrelationEngine;
+use crate::mock_backend::MockScenario;
+use crate::network_monitor::NetworkMonitor;
+use crate::process_tree::ProcessTree;
+use crate::profile::{format_ms, CycleProfile};
+use crate::scheduler::Due;
+use crate::slack_huddle::HuddleProbe;
+use crate::validator::{CallValidator, NetworkSource};
+use crate::zoom_probe;
+use std::time::{Duration, SystemTime};
+
+const USAGE: &str = "Usage: rust-audio-validator bench [--cycles N] [--budget-ms MS]";
+
+const DEFAULT_CYCLES: usize = 50;
+
+/// A call next to media playback for `mock_cycle`; pids above pid_max so the process tree
+/// never resolves them to a real process
+const MOCK_FRAME: &str = r#"{
+    "mic_apps": ["zoom.us", "chrome"],
+    "playing": [
+        { "name": "zoom.us", "process_id": 4000000007, "window_title": "Zoom Meeting" },
+        { "name": "chrome", "process_id": 4000000009, "window_title": "Lofi - YouTube" },
+        { "name": "spotify", "process_id": 4000000011, "window_title": "Spotify" }
+    ],
+    "webrtc_pids": [4000000007, 4000000009]
+}"#;
+
+/// One poll cycle of the platform backend per call: `CallValidator::sense` with every
+/// subsystem due, then `CallTracker::update`
+#[allow(dead_code)] // only benches/poll_cycle.rs calls it, through lib.rs
+pub fn platform_cycle() -> impl FnMut() {
+    let mut validator = CallValidator::new(SystemAudio, NetworkMonitor::new());
+    let mut tracker = CallTracker::new(CorrelationEngine::new());
+    move || poll_cycle(&mut validator, &mut tracker)
+}
+
+/// The same cycle over the scripted mock backend, the same on every machine
+#[allow(dead_code)] // only benches/poll_cycle.rs calls it, through lib.rs
+pub fn mock_cycle() -> impl FnMut() {
+    let scenario = MockScenario::from_json(&format!("[{}]", MOCK_FRAME)).expect("the bench scenario parses");
+    let (audio, network) = scenario.backends();
+    let mut validator = CallValidator::new(audio, network);
+    let mut tracker = CallTracker::new(CorrelationEngine::new());
+    move || poll_cycle(&mut validator, &mut tracker)
+}
+
+fn poll_cycle<A: AudioBackend, N: NetworkSource>(validator: &mut CallValidator<A, N>, tracker: &mut CallTracker) {
+    let mut process_tree = ProcessTree::snapshot();
+    let sample = validator.sense(&mut process_tree, Due::all()).to_sample(&mut process_tree);
+    tracker.update(&sample, SystemTime::now());
+}
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1));
+    let cycles = match value("--cycles").map(|s| s.parse::<usize>()) {
+        None => DEFAULT_CYCLES,
+        Some(Ok(cycles)) if cycles > 0 => cycles,
+        Some(_) => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+    let budget = match value("--budget-ms").map(|s| s.parse::<u64>()) {
+        None => None,
+        Some(Ok(ms)) => Some(Duration::from_millis(ms)),
+        Some(Err(_)) => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+
+    let mut validator = CallValidator::new(SystemAudio, NetworkMonitor::new());
+    let mut tracker = CallTracker::new(CorrelationEngine::new());
+    let mut huddle_probe = HuddleProbe::default();
+    let runs: Vec<CycleProfile> = (0..cycles)
+        .map(|_| {
+            let mut profile = CycleProfile::default();
+            let mut process_tree = profile.time("process_tree", ProcessTree::snapshot);
+            let sensed = validator.sense(&mut process_tree, Due::all());
+            profile.extend(validator.profile());
+            let zoom_meeting = profile.time("zoom_probe", zoom_probe::probe);
+            let slack_huddle = profile.time("huddle_probe", || huddle_probe.probe(&mut process_tree));
+            let sample = Sample { zoom_meeting, slack_huddle, ..sensed.to_sample(&mut process_tree) };
+            profile.time("track", || tracker.update(&sample, SystemTime::now()));
+            profile
+        })
+        .collect();
+
+    println!("{} cycles, every subsystem due", cycles);
+    println!("{:<14} {:>9} {:>9} {:>9}", "subsystem", "mean", "p95", "max");
+    let names: Vec<&'static str> = runs[0].spans().iter().map(|(name, _)| *name).collect();
+    for name in names {
+        let times = runs
+            .iter()
+            .map(|run| run.spans().iter().find(|(span, _)| *span == name).map_or(Duration::ZERO, |(_, elapsed)| *elapsed))
+            .collect();
+        print_row(name, times);
+    }
+    let totals: Vec<Duration> = runs.iter().map(CycleProfile::total).collect();
+    let mean = totals.iter().sum::<Duration>() / cycles as u32;
+    print_row("total", totals);
+
+    match budget {
+        Some(budget) if mean > budget => {
+            eprintln!("Mean cycle {} exceeds the {} budget", format_ms(mean), format_ms(budget));
+            1
+        }
+        _ => 0,
+    }
+}
+
+fn print_row(name: &str, mut times: Vec<Duration>) {
+    times.sort();
+    let mean = times.iter().sum::<Duration>() / times.len() as u32;
+    let p95 = times[(times.len() * 95 / 100).min(times.len() - 1)];
+    let max = times[times.len() - 1];
+    println!("{:<14} {:>9} {:>9} {:>9}", name, format_ms(mean), format_ms(p95), format_ms(max));
+}
@@ -0,0 +1,323 @@
+// Localhost endpoint for the companion browser extension
+// The extension POSTs its tab state so browser calls can be attributed to a single tab
+// instead of "any browser is using the mic":
+//
+//   POST /tabs
+//   {"browser":"chrome","tabs":[{"id":12,"url":"https://meet.google.com/abc-defg-hij",
+//     "title":"Meet - abc-defg-hij","audible":true,"capturing_audio":true}]}
+//
+// Each POST replaces that browser's tabs. Reports older than STALE_AFTER are dropped,
+// so a closed browser or uninstalled extension falls back to process-level detection.
+// Any page the user visits can POST to loopback, so a report must come from an extension
+// origin (chrome-extension://, moz-extension://) and carry the pairing token in
+// TOKEN_HEADER. A custom header makes pages' requests preflighted, which nothing answers.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Header carrying the token the extension was paired with
+pub const TOKEN_HEADER: &str = "x-bridge-token";
+/// Shortest pairing token accepted
+pub const MIN_TOKEN_LEN: usize = 16;
+/// Origins of browser extensions, the only senders accepted
+const EXTENSION_ORIGINS: &[&str] = &["chrome-extension://", "moz-extension://"];
+
+/// Tabs not refreshed within this window are ignored
+const STALE_AFTER: Duration = Duration::from_secs(10);
+/// Largest request body accepted from the extension
+const MAX_BODY: usize = 256 * 1024;
+/// Largest request line plus headers, in bytes
+const MAX_HEAD: usize = 8 * 1024;
+/// Most header lines in one request
+const MAX_HEADERS: usize = 64;
+/// Most connections served at once; further ones are closed unanswered
+const MAX_CONNECTIONS: usize = 16;
+
+/// Known call web apps by URL prefix (scheme stripped)
+const CALL_URLS: &[(&str, &str)] = &[
+    ("meet.google.com/", "Google Meet"),
+    ("teams.microsoft.com/", "Microsoft Teams"),
+    ("teams.live.com/", "Microsoft Teams"),
+    ("app.zoom.us/wc/", "Zoom"),
+    ("zoom.us/wc/", "Zoom"),
+    ("app.slack.com/", "Slack"),
+    ("web.whatsapp.com/", "WhatsApp"),
+];
+
+/// State of one browser tab as reported by the extension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserTab {
+    #[serde(default)]
+    pub browser: String,
+    pub id: u64,
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    /// Tab is playing sound
+    #[serde(default)]
+    pub audible: bool,
+    /// Tab holds a live getUserMedia audio track
+    #[serde(default)]
+    pub capturing_audio: bool,
+}
+
+impl BrowserTab {
+    /// Call app served by this tab, if any
+    pub fn call_app(&self) -> Option<&'static str> {
+        url_call_app(&self.url)
+    }
+
+    /// Whether this tab was reported by the browser behind `process_name`
+    pub fn belongs_to(&self, process_name: &str) -> bool {
+        let browser = self.browser.to_lowercase();
+        !browser.is_empty() && process_name.to_lowercase().contains(&browser)
+    }
+}
+
+/// Call app served at `url`, if any
+pub fn url_call_app(url: &str) -> Option<&'static str> {
+    let url = url.to_lowercase();
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(&url)
+        .to_string();
+
+    CALL_URLS
+        .iter()
+        .find(|(prefix, _)| url.starts_with(prefix))
+        .map(|(_, app)| *app)
+}
+
+#[derive(Deserialize)]
+struct TabReport {
+    browser: String,
+    tabs: Vec<BrowserTab>,
+}
+
+type TabTable = Arc<Mutex<HashMap<String, (Instant, Vec<BrowserTab>)>>>;
+
+/// Handle to the extension endpoint
+pub struct BrowserBridge {
+    tabs: TabTable,
+}
+
+impl BrowserBridge {
+    /// Listen on `addr` (must be a loopback address) in a background thread; reports must
+    /// carry `token`
+    pub fn start(addr: SocketAddr, token: &str) -> Result<Self, String> {
+        if !addr.ip().is_loopback() {
+            return Err(format!("refusing to serve the browser bridge on non-loopback address {}", addr));
+        }
+        if token.len() < MIN_TOKEN_LEN {
+            return Err(format!("the pairing token must be at least {} characters", MIN_TOKEN_LEN));
+        }
+
+        let listener = TcpListener::bind(addr).map_err(|e| e.to_string())?;
+        let tabs: TabTable = Arc::new(Mutex::new(HashMap::new()));
+        let table = Arc::clone(&tabs);
+
+        let token = token.to_string();
+        thread::spawn(move || serve(listener, move |stream| handle_connection(stream, &table, &token)));
+
+        Ok(BrowserBridge { tabs })
+    }
+
+    /// All fresh tabs across browsers
+    pub fn tabs(&self) -> Vec<BrowserTab> {
+        let mut table = self.tabs.lock().unwrap();
+        table.retain(|_, (updated, _)| updated.elapsed() < STALE_AFTER);
+        table.values().flat_map(|(_, tabs)| tabs.iter().cloned()).collect()
+    }
+}
+
+fn handle_connection(stream: TcpStream, table: &TabTable, token: &str) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    let status = match read_request(&stream) {
+        Ok(request) if request.method == "POST" && request.path == "/tabs" && !from_extension(&request) => {
+            "403 Forbidden"
+        }
+        Ok(request) if request.method == "POST" && request.path == "/tabs" && !has_token(&request, token) => {
+            "401 Unauthorized"
+        }
+        Ok(request) if request.method == "POST" && request.path == "/tabs" => {
+            match serde_json::from_slice::<TabReport>(&request.body) {
+                Ok(report) => {
+                    let browser = report.browser.to_lowercase();
+                    let tabs = report
+                        .tabs
+                        .into_iter()
+                        .map(|mut tab| {
+                            tab.browser = browser.clone();
+                            tab
+                        })
+                        .collect();
+                    table.lock().unwrap().insert(browser, (Instant::now(), tabs));
+                    "204 No Content"
+                }
+                Err(_) => "400 Bad Request",
+            }
+        }
+        Ok(_) => "404 Not Found",
+        Err(_) => "400 Bad Request",
+    };
+
+    let _ = write!(writer, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+}
+
+/// Whether `request` was sent by a browser extension rather than a web page
+fn from_extension(request: &HttpRequest) -> bool {
+    request
+        .headers
+        .get("origin")
+        .is_some_and(|origin| EXTENSION_ORIGINS.iter().any(|prefix| origin.starts_with(prefix)))
+}
+
+/// Whether `request` carries `token`, compared in constant time
+fn has_token(request: &HttpRequest, token: &str) -> bool {
+    request.headers.get(TOKEN_HEADER).is_some_and(|sent| {
+        sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    })
+}
+
+/// Hand each connection to `handle` on its own thread, at most MAX_CONNECTIONS at once
+/// (also serves companion.rs and aggregate.rs)
+pub fn serve<F>(listener: TcpListener, handle: F)
+where
+    F: Fn(TcpStream) + Send + Sync + 'static,
+{
+    /// Frees its connection slot however the handler returns
+    struct Slot(Arc<AtomicUsize>);
+    impl Drop for Slot {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    let active = Arc::new(AtomicUsize::new(0));
+    let handle = Arc::new(handle);
+    for stream in listener.incoming().flatten() {
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            continue;
+        }
+        let slot = Slot(Arc::clone(&active));
+        let handle = Arc::clone(&handle);
+        thread::spawn(move || {
+            let _slot = slot;
+            handle(stream);
+        });
+    }
+}
+
+/// One request read by `read_request`
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    /// Keyed by lowercase header name
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// Minimal HTTP/1.1 request reader: request line, headers, Content-Length and body
+/// (also serves the mobile companion endpoint, companion.rs). The request line and
+/// headers share MAX_HEAD bytes, so nothing unauthenticated grows without bound.
+pub fn read_request(stream: &TcpStream) -> Result<HttpRequest, String> {
+    let mut reader = BufReader::new(stream);
+    let mut budget = MAX_HEAD;
+
+    let request_line = read_head_line(&mut reader, &mut budget)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let header = read_head_line(&mut reader, &mut budget)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err("too many headers".to_string());
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length: usize = match headers.get("content-length") {
+        Some(value) => value.parse().map_err(|_| "invalid Content-Length".to_string())?,
+        None => 0,
+    };
+    if content_length > MAX_BODY {
+        return Err("body too large".to_string());
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    Ok(HttpRequest { method, path, headers, body })
+}
+
+/// One line of the request head, taken from what is left of `budget`
+fn read_head_line(reader: &mut BufReader<&TcpStream>, budget: &mut usize) -> Result<String, String> {
+    let mut line = Vec::new();
+    let read = reader
+        .by_ref()
+        .take(*budget as u64)
+        .read_until(b'\n', &mut line)
+        .map_err(|e| e.to_string())?;
+    if !line.ends_with(b"\n") {
+        return Err(if read == *budget { "request head too large" } else { "connection closed" }.to_string());
+    }
+    *budget -= read;
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_app_from_url() {
+        let tab = |url: &str| BrowserTab {
+            browser: "chrome".to_string(),
+            id: 1,
+            url: url.to_string(),
+            title: String::new(),
+            audible: true,
+            capturing_audio: false,
+        };
+
+        assert_eq!(tab("https://meet.google.com/abc-defg-hij").call_app(), Some("Google Meet"));
+        assert_eq!(tab("https://www.youtube.com/watch?v=meet").call_app(), None);
+        assert!(tab("x").belongs_to("chrome.exe"));
+        assert!(!tab("x").belongs_to("firefox"));
+
+        let request = |origin: &str, token: &str| HttpRequest {
+            method: "POST".to_string(),
+            path: "/tabs".to_string(),
+            headers: [("origin", origin), (TOKEN_HEADER, token)]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: Vec::new(),
+        };
+        let paired = "0123456789abcdef";
+        assert!(from_extension(&request("chrome-extension://abcdefghijklmnop", paired)));
+        assert!(!from_extension(&request("https://evil.example", paired)));
+        assert!(has_token(&request("moz-extension://1234", paired), paired));
+        assert!(!has_token(&request("moz-extension://1234", "0123456789abcdeX"), paired));
+    }
+}
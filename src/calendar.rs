@@ -0,0 +1,704 @@
+// Calendar events for detection and call labels (`calendar` feature)
+// A call that starts while the user's calendar has a meeting with a conferencing link is
+// far more likely a real call, and the event says what the call is about. Three sources:
+//
+// - An ICS file (`--calendar ~/work.ics`) or a published ICS feed (`--calendar
+//   https://.../basic.ics`, `webcal://...`), read every minute. Daily and weekly
+//   recurrences are expanded; times with a TZID are read as local time, which holds for a
+//   calendar kept in the user's own time zone.
+// - A CalDAV calendar collection (`--calendar https://dav.example.com/calendars/me/work/`),
+//   queried every 5 minutes with `VALIDATOR_CALDAV_USER` / `VALIDATOR_CALDAV_PASSWORD`;
+//   the server expands recurrences.
+// - Microsoft Graph (`--calendar graph`): /me/calendarView every 5 minutes with a
+//   user-provided token (env `VALIDATOR_CALENDAR_GRAPH_TOKEN`, needs Calendars.Read).
+//
+// Only events with a meeting link meeting_id.rs recognizes count; all-day and cancelled
+// events never do. While one is under way, the `calendar` rule adds `calendar_weight` to
+// the calls it may belong to, and its title and organizer are attached to the call.
+
+use crate::meeting_id::{self, ScheduledMeeting};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const GRAPH_CALENDAR_VIEW_URL: &str = "https://graph.microsoft.com/v1.0/me/calendarView";
+/// Graph pages hold up to 50 events; a day never needs more than a few
+const MAX_GRAPH_PAGES: usize = 10;
+const FILE_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const REMOTE_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Events are kept from a day back to a day ahead, so a failed refresh loses nothing
+const WINDOW: Duration = Duration::from_secs(24 * 3600);
+/// People join a few minutes early
+const EARLY_JOIN: Duration = Duration::from_secs(5 * 60);
+
+/// Where calendar events come from
+#[derive(Debug, Clone)]
+pub enum CalendarSource {
+    IcsFile(PathBuf),
+    IcsFeed { url: String, credentials: Option<(String, String)> },
+    CalDav { url: String, credentials: Option<(String, String)> },
+    Graph { token: String },
+}
+
+impl CalendarSource {
+    /// `--calendar` value: `graph`, an ICS feed or CalDAV collection URL, or a file path
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if value == "graph" {
+            return std::env::var("VALIDATOR_CALENDAR_GRAPH_TOKEN")
+                .map(|token| CalendarSource::Graph { token })
+                .map_err(|_| "--calendar graph needs VALIDATOR_CALENDAR_GRAPH_TOKEN".to_string());
+        }
+
+        let Some((scheme, rest)) = value.split_once("://") else {
+            return Ok(CalendarSource::IcsFile(PathBuf::from(value)));
+        };
+        let credentials = std::env::var("VALIDATOR_CALDAV_USER")
+            .ok()
+            .map(|user| (user, std::env::var("VALIDATOR_CALDAV_PASSWORD").unwrap_or_default()));
+        let path = rest.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+
+        match scheme.to_lowercase().as_str() {
+            "webcal" => Ok(CalendarSource::IcsFeed { url: format!("https://{}", rest), credentials }),
+            "http" | "https" if path.ends_with(".ics") => Ok(CalendarSource::IcsFeed { url: value.to_string(), credentials }),
+            "http" | "https" => Ok(CalendarSource::CalDav { url: value.to_string(), credentials }),
+            _ => Err(format!("unsupported calendar URL '{}'", value)),
+        }
+    }
+}
+
+/// One occurrence of a meeting
+#[derive(Debug, Clone)]
+struct Event {
+    start: SystemTime,
+    end: SystemTime,
+    meeting: ScheduledMeeting,
+}
+
+type Shared = Arc<Mutex<Vec<Event>>>;
+
+pub struct Calendar {
+    events: Shared,
+}
+
+impl Calendar {
+    /// Start refreshing events in a background thread
+    pub fn start(source: CalendarSource) -> Self {
+        let events: Shared = Arc::default();
+        let shared = Arc::clone(&events);
+        thread::spawn(move || refresh(&source, &shared));
+        Calendar { events }
+    }
+
+    /// Meetings under way at `now`, the latest to start first
+    pub fn meetings_at(&self, now: SystemTime) -> Vec<ScheduledMeeting> {
+        self.events.lock().map(|events| under_way(&events, now)).unwrap_or_default()
+    }
+}
+
+fn under_way(events: &[Event], now: SystemTime) -> Vec<ScheduledMeeting> {
+    let mut current: Vec<&Event> = events
+        .iter()
+        .filter(|event| event.start <= now + EARLY_JOIN && now < event.end)
+        .collect();
+    // Back-to-back meetings overlap by the early join; the next one is the likelier call
+    current.sort_by_key(|event| std::cmp::Reverse(event.start));
+    current.into_iter().map(|event| event.meeting.clone()).collect()
+}
+
+fn refresh(source: &CalendarSource, events: &Shared) {
+    let interval = match source {
+        CalendarSource::IcsFile(_) => FILE_REFRESH_INTERVAL,
+        _ => REMOTE_REFRESH_INTERVAL,
+    };
+    let mut last_error: Option<String> = None;
+
+    loop {
+        let now = SystemTime::now();
+        match fetch(source, now - WINDOW, now + WINDOW) {
+            Ok(fetched) => {
+                last_error = None;
+                if let Ok(mut events) = events.lock() {
+                    *events = fetched;
+                }
+            }
+            // Report each new failure once and keep the events already known
+            Err(e) => {
+                if last_error.as_deref() != Some(e.as_str()) {
+                    log::warn!("{}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        thread::sleep(interval);
+    }
+}
+
+fn fetch(source: &CalendarSource, from: SystemTime, to: SystemTime) -> Result<Vec<Event>, String> {
+    match source {
+        CalendarSource::IcsFile(path) => fs::read_to_string(path)
+            .map(|text| parse_ics(&text, from, to))
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e)),
+        CalendarSource::IcsFeed { url, credentials } => authorized(ureq::get(url), credentials)
+            .timeout(REQUEST_TIMEOUT)
+            .call()
+            .map_err(|e| format!("Calendar feed request failed: {}", e))
+            .and_then(|response| response.into_string().map_err(|e| e.to_string()))
+            .map(|text| parse_ics(&text, from, to)),
+        CalendarSource::CalDav { url, credentials } => authorized(ureq::request("REPORT", url), credentials)
+            .set("Depth", "1")
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .timeout(REQUEST_TIMEOUT)
+            .send_string(&calendar_query(from, to))
+            .map_err(|e| format!("CalDAV request failed: {}", e))
+            .and_then(|response| response.into_string().map_err(|e| e.to_string()))
+            .map(|multistatus| calendar_data(&multistatus).iter().flat_map(|ics| parse_ics(ics, from, to)).collect()),
+        CalendarSource::Graph { token } => fetch_graph(token, from, to),
+    }
+}
+
+fn authorized(request: ureq::Request, credentials: &Option<(String, String)>) -> ureq::Request {
+    match credentials {
+        Some((user, password)) => {
+            request.set("Authorization", &format!("Basic {}", BASE64.encode(format!("{}:{}", user, password))))
+        }
+        None => request,
+    }
+}
+
+// RFC 4791 calendar-query for the events in `from..to`, recurrences expanded by the server
+fn calendar_query(from: SystemTime, to: SystemTime) -> String {
+    let (from, to) = (caldav_time(from), caldav_time(to));
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data><c:expand start="{from}" end="{to}"/></c:calendar-data></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT">
+    <c:time-range start="{from}" end="{to}"/>
+  </c:comp-filter></c:comp-filter></c:filter>
+</c:calendar-query>"#
+    )
+}
+
+fn caldav_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The ICS text of each `calendar-data` element in a multistatus response
+fn calendar_data(multistatus: &str) -> Vec<String> {
+    let pattern = Regex::new(r"(?s)<(?:[\w-]+:)?calendar-data[^>/]*>(.*?)</(?:[\w-]+:)?calendar-data>")
+        .expect("valid calendar-data pattern");
+    pattern
+        .captures_iter(multistatus)
+        .map(|captures| {
+            let text = captures[1].trim();
+            match text.strip_prefix("<![CDATA[").and_then(|text| text.strip_suffix("]]>")) {
+                Some(text) => text.to_string(),
+                None => text
+                    .replace("&#13;", "")
+                    .replace("&#xD;", "")
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&amp;", "&"),
+            }
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct GraphPage {
+    #[serde(default)]
+    value: Vec<GraphEvent>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GraphEvent {
+    subject: String,
+    organizer: Option<GraphRecipient>,
+    start: Option<GraphTime>,
+    end: Option<GraphTime>,
+    is_cancelled: bool,
+    is_all_day: bool,
+    online_meeting: Option<GraphOnlineMeeting>,
+    location: Option<GraphLocation>,
+    body_preview: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphRecipient {
+    email_address: GraphEmailAddress,
+}
+
+#[derive(Deserialize)]
+struct GraphEmailAddress {
+    name: Option<String>,
+    address: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphTime {
+    date_time: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphOnlineMeeting {
+    join_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphLocation {
+    display_name: Option<String>,
+}
+
+fn fetch_graph(token: &str, from: SystemTime, to: SystemTime) -> Result<Vec<Event>, String> {
+    let iso = |time: SystemTime| DateTime::<Utc>::from(time).format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let mut url = format!(
+        "{}?startDateTime={}&endDateTime={}&$top=50&$select=subject,organizer,start,end,isCancelled,isAllDay,onlineMeeting,location,bodyPreview",
+        GRAPH_CALENDAR_VIEW_URL,
+        iso(from),
+        iso(to)
+    );
+    let mut events = Vec::new();
+
+    for _ in 0..MAX_GRAPH_PAGES {
+        let body = ureq::get(&url)
+            .set("Authorization", &format!("Bearer {}", token))
+            .set("Prefer", "outlook.timezone=\"UTC\"")
+            .timeout(REQUEST_TIMEOUT)
+            .call()
+            .map_err(|e| format!("Graph calendar request failed: {}", e))?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        let page: GraphPage = serde_json::from_str(&body).map_err(|e| format!("Invalid Graph calendar response: {}", e))?;
+        events.extend(page.value.iter().filter_map(graph_event));
+        match page.next_link {
+            Some(next) => url = next,
+            None => break,
+        }
+    }
+    Ok(events)
+}
+
+fn graph_event(event: &GraphEvent) -> Option<Event> {
+    if event.is_cancelled || event.is_all_day {
+        return None;
+    }
+    let meeting_identifier = [
+        event.online_meeting.as_ref().and_then(|meeting| meeting.join_url.as_deref()),
+        event.location.as_ref().and_then(|location| location.display_name.as_deref()),
+        Some(event.body_preview.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(find_meeting)?;
+
+    // UTC, as asked for with the Prefer header: "2026-10-16T09:00:00.0000000"
+    let time = |time: &GraphTime| {
+        NaiveDateTime::parse_from_str(&time.date_time, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|time| SystemTime::from(Utc.from_utc_datetime(&time)))
+    };
+    let organizer = event.organizer.as_ref().map(|organizer| &organizer.email_address);
+
+    Some(Event {
+        start: time(event.start.as_ref()?)?,
+        end: time(event.end.as_ref()?)?,
+        meeting: ScheduledMeeting {
+            title: event.subject.clone(),
+            organizer: organizer.and_then(|email| email.name.clone().or_else(|| email.address.clone())),
+            meeting_identifier,
+        },
+    })
+}
+
+/// The first meeting link in free text (locations, descriptions)
+fn find_meeting(text: &str) -> Option<String> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | '(' | ')' | '[' | ']'))
+        .filter(|word| word.contains("://"))
+        .find_map(meeting_id::from_url)
+}
+
+/// A DATE-TIME as written: UTC, or local time (floating or with a TZID)
+#[derive(Debug, Clone, Copy)]
+struct Stamp {
+    time: NaiveDateTime,
+    utc: bool,
+}
+
+impl Stamp {
+    /// None for all-day DATE values
+    fn parse(value: &str, tzid: Option<&str>) -> Option<Self> {
+        let (text, utc) = match value.strip_suffix('Z') {
+            Some(text) => (text, true),
+            None => (value, tzid.is_some_and(|tzid| matches!(tzid, "UTC" | "Etc/UTC" | "GMT"))),
+        };
+        NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%S").ok().map(|time| Stamp { time, utc })
+    }
+
+    fn system_time(&self) -> Option<SystemTime> {
+        system_time(self.time, self.utc)
+    }
+}
+
+fn system_time(time: NaiveDateTime, utc: bool) -> Option<SystemTime> {
+    if utc {
+        Some(Utc.from_utc_datetime(&time).into())
+    } else {
+        Local.from_local_datetime(&time).earliest().map(SystemTime::from)
+    }
+}
+
+/// The properties of a VEVENT that matter here
+#[derive(Debug, Default)]
+struct VEvent {
+    uid: String,
+    summary: String,
+    organizer: Option<String>,
+    start: Option<Stamp>,
+    end: Option<Stamp>,
+    duration: Option<chrono::Duration>,
+    rrule: Option<String>,
+    exdates: Vec<SystemTime>,
+    /// Set on a moved or cancelled occurrence of a series
+    recurrence_id: Option<SystemTime>,
+    cancelled: bool,
+    meeting_identifier: Option<String>,
+}
+
+impl VEvent {
+    fn set(&mut self, name: &str, params: &[(String, String)], value: &str) {
+        let param = |key: &str| params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        match name {
+            "UID" => self.uid = value.to_string(),
+            "SUMMARY" => self.summary = unescape(value),
+            // ORGANIZER;CN=Jane Doe:mailto:jane@example.com
+            "ORGANIZER" => {
+                let address = value.split_once(':').map_or(value, |(_, address)| address);
+                self.organizer = Some(param("CN").unwrap_or(address).to_string());
+            }
+            "DTSTART" => self.start = Stamp::parse(value, param("TZID")),
+            "DTEND" => self.end = Stamp::parse(value, param("TZID")),
+            "DURATION" => self.duration = parse_duration(value),
+            "RRULE" => self.rrule = Some(value.to_string()),
+            "EXDATE" => self.exdates.extend(
+                value.split(',').filter_map(|date| Stamp::parse(date, param("TZID"))?.system_time()),
+            ),
+            "RECURRENCE-ID" => self.recurrence_id = Stamp::parse(value, param("TZID")).and_then(|stamp| stamp.system_time()),
+            "STATUS" => self.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            "URL" | "LOCATION" | "DESCRIPTION" | "X-GOOGLE-CONFERENCE" | "X-MICROSOFT-SKYPETEAMSMEETINGURL" => {
+                if self.meeting_identifier.is_none() {
+                    self.meeting_identifier = find_meeting(&unescape(value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Start and end of each occurrence overlapping `from..to`
+    fn occurrences(&self, skipped: &[SystemTime], from: SystemTime, to: SystemTime) -> Vec<(SystemTime, SystemTime)> {
+        let Some(start) = self.start else { return Vec::new() };
+        let length = match (self.end, self.duration) {
+            (Some(end), _) => end.time - start.time,
+            (None, Some(duration)) => duration,
+            (None, None) => chrono::Duration::zero(),
+        };
+        let length = length.to_std().unwrap_or_default();
+
+        let starts = match self.rrule.as_deref().and_then(Recurrence::parse) {
+            // A day of slack either side covers any UTC offset
+            Some(rule) => {
+                let day = |time: SystemTime| DateTime::<Utc>::from(time).date_naive();
+                rule.starts(start, day(from) - chrono::Duration::days(1), day(to) + chrono::Duration::days(1))
+            }
+            None => vec![start.time],
+        };
+
+        starts
+            .into_iter()
+            .filter_map(|time| system_time(time, start.utc))
+            .filter(|time| !skipped.contains(time))
+            .map(|time| (time, time + length))
+            .filter(|(start, end)| *start < to && *end > from)
+            .collect()
+    }
+}
+
+/// The parts of an RRULE needed for daily and weekly series
+#[derive(Debug)]
+struct Recurrence {
+    weekly: bool,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<SystemTime>,
+    days: Vec<Weekday>,
+}
+
+impl Recurrence {
+    /// None for other frequencies, which then count with their first occurrence only
+    fn parse(rule: &str) -> Option<Self> {
+        let mut recurrence = Recurrence { weekly: false, interval: 1, count: None, until: None, days: Vec::new() };
+        for (key, value) in rule.split(';').filter_map(|part| part.split_once('=')) {
+            match key.to_uppercase().as_str() {
+                "FREQ" => match value.to_uppercase().as_str() {
+                    "DAILY" => recurrence.weekly = false,
+                    "WEEKLY" => recurrence.weekly = true,
+                    _ => return None,
+                },
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|interval| *interval > 0)?,
+                "COUNT" => recurrence.count = value.parse().ok(),
+                // UNTIL=20261231T235959Z, or a date for the whole of that day
+                "UNTIL" => {
+                    recurrence.until = Stamp::parse(value, None).and_then(|stamp| stamp.system_time()).or_else(|| {
+                        let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+                        system_time(day.and_hms_opt(23, 59, 59)?, false)
+                    })
+                }
+                "BYDAY" => recurrence.days = value.split(',').filter_map(weekday).collect(),
+                _ => {}
+            }
+        }
+        Some(recurrence)
+    }
+
+    /// Occurrence starts on the days `first_day..=last_day`; earlier ones still count
+    /// towards COUNT
+    fn starts(&self, start: Stamp, first_day: NaiveDate, last_day: NaiveDate) -> Vec<NaiveDateTime> {
+        let series_start = start.time.date();
+        let series_week = series_start - chrono::Duration::days(series_start.weekday().num_days_from_monday() as i64);
+        let mut starts = Vec::new();
+        let mut matched = 0;
+        let mut day = series_start;
+
+        while day <= last_day {
+            let in_interval = if self.weekly {
+                (day - series_week).num_days() / 7 % self.interval == 0
+            } else {
+                (day - series_start).num_days() % self.interval == 0
+            };
+            let on_day = match self.days.is_empty() {
+                true => !self.weekly || day.weekday() == series_start.weekday(),
+                false => self.days.contains(&day.weekday()),
+            };
+
+            if in_interval && on_day {
+                let time = day.and_time(start.time.time());
+                let is_over = self.until.is_some_and(|until| system_time(time, start.utc).map_or(true, |time| time > until));
+                if is_over {
+                    break;
+                }
+                if day >= first_day {
+                    starts.push(time);
+                }
+                matched += 1;
+                if self.count.is_some_and(|count| matched >= count) {
+                    break;
+                }
+            }
+
+            match day.succ_opt() {
+                Some(next) => day = next,
+                None => break,
+            }
+        }
+        starts
+    }
+}
+
+// "MO", or "1MO" in monthly rules
+fn weekday(code: &str) -> Option<Weekday> {
+    let code = code.trim();
+    match code.get(code.len().checked_sub(2)?..)?.to_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// "PT1H30M", "P1D", "P1W"
+fn parse_duration(value: &str) -> Option<chrono::Duration> {
+    let value = value.trim().trim_start_matches('+').strip_prefix('P')?;
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let amount: i64 = std::mem::take(&mut number).parse().ok()?;
+                seconds += amount
+                    * match unit {
+                        'W' => 7 * 86400,
+                        'D' => 86400,
+                        'H' => 3600,
+                        'M' => 60,
+                        'S' => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    Some(chrono::Duration::seconds(seconds))
+}
+
+fn parse_ics(text: &str, from: SystemTime, to: SystemTime) -> Vec<Event> {
+    let vevents = vevents(text);
+
+    // A moved or cancelled occurrence of a series is its own VEVENT with the series' UID
+    let mut replaced: HashMap<&str, Vec<SystemTime>> = HashMap::new();
+    for vevent in vevents.iter().filter(|vevent| vevent.rrule.is_none()) {
+        if let Some(recurrence_id) = vevent.recurrence_id {
+            replaced.entry(vevent.uid.as_str()).or_default().push(recurrence_id);
+        }
+    }
+
+    let mut events = Vec::new();
+    for vevent in vevents.iter().filter(|vevent| !vevent.cancelled) {
+        let Some(meeting_identifier) = vevent.meeting_identifier.clone() else { continue };
+        let mut skipped = vevent.exdates.clone();
+        if vevent.rrule.is_some() {
+            skipped.extend(replaced.get(vevent.uid.as_str()).into_iter().flatten());
+        }
+        let meeting = ScheduledMeeting { title: vevent.summary.clone(), organizer: vevent.organizer.clone(), meeting_identifier };
+        for (start, end) in vevent.occurrences(&skipped, from, to) {
+            events.push(Event { start, end, meeting: meeting.clone() });
+        }
+    }
+    events
+}
+
+fn vevents(text: &str) -> Vec<VEvent> {
+    let mut vevents = Vec::new();
+    let mut current: Option<VEvent> = None;
+    // Properties of a VALARM inside the VEVENT are not the event's
+    let mut nested = 0;
+
+    for line in unfold(text) {
+        let Some((name, params, value)) = property(&line) else { continue };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(VEvent::default()),
+            ("END", "VEVENT") => vevents.extend(current.take()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested = (nested - 1).max(0),
+            _ if nested == 0 => {
+                if let Some(vevent) = current.as_mut() {
+                    vevent.set(&name, &params, value);
+                }
+            }
+            _ => {}
+        }
+    }
+    vevents
+}
+
+// Long lines continue on lines starting with a space or tab
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// NAME;PARAM=value;PARAM="quoted:value":VALUE
+fn property(line: &str) -> Option<(String, Vec<(String, String)>, &str)> {
+    let mut in_quotes = false;
+    let (colon, _) = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ':' && !in_quotes
+    })?;
+    let mut parts = line[..colon].split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.to_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, &line[colon + 1..]))
+}
+
+// TEXT values escape commas, semicolons, backslashes and newlines
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recurring_meetings_from_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+SUMMARY:Team standup\r\n\
+ORGANIZER;CN=\"Doe, Jane\":mailto:jane@example.com\r\n\
+DTSTART:20261012T090000Z\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR\r\n\
+EXDATE:20261015T090000Z\r\n\
+DESCRIPTION:Join: https://meet.google.com/abc-defg-hij?authuser=0\\nor dial \r\n \\,+1 555 0100\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:https://zoom.us/j/81234567890\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:lunch\r\n\
+SUMMARY:Lunch\r\n\
+DTSTART:20261016T120000Z\r\n\
+DTEND:20261016T130000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let at = |day: u32, hour: u32, minute: u32| SystemTime::from(Utc.with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap());
+        let events = parse_ics(ics, at(15, 0, 0), at(17, 0, 0));
+
+        let standup = under_way(&events, at(16, 9, 5));
+        assert_eq!(standup.len(), 1);
+        assert_eq!(standup[0].title, "Team standup");
+        assert_eq!(standup[0].organizer.as_deref(), Some("Doe, Jane"));
+        assert_eq!(standup[0].meeting_identifier, "meet.google.com/abc-defg-hij");
+        assert_eq!(under_way(&events, at(16, 8, 57)).len(), 1, "joining early");
+
+        assert!(under_way(&events, at(15, 9, 5)).is_empty(), "excluded date");
+        assert!(under_way(&events, at(16, 9, 20)).is_empty(), "over");
+        assert!(under_way(&events, at(16, 12, 30)).is_empty(), "no meeting link");
+    }
+}
@@ -0,0 +1,269 @@
+// `calibrate` subcommand: fit scoring weights to labeled recordings
+// Takes a directory of --record-signals traces sorted into `call/` and `no-call/`
+// subdirectories, replays every trace under each candidate ScoringConfig on a grid, and
+// prints the config that classifies the most traces correctly, ready for --config.
+//
+//   rust-audio-validator calibrate <dir> [--config base.json]
+//
+// A trace counts as a detected call when replaying it starts a call at any point.
+// Hysteresis, per-app overrides and process lists come from the base config and are
+// not searched.
+
+use crate::call_tracker::CallTracker;
+use crate::config::Config;
+use crate::correlation_engine::{CorrelationEngine, HysteresisConfig, ScoringConfig};
+use crate::events::MonitorEvent;
+use crate::replay::{self, RecordedSample};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const USAGE: &str = "Usage: rust-audio-validator calibrate <dir> [--config base.json]";
+
+/// Weight values tried for each signal
+const WEIGHT_STEPS: &[f32] = &[0.0, 0.1, 0.2, 0.3, 0.4, 0.5];
+/// Threshold values tried
+const THRESHOLD_STEPS: &[f32] = &[0.3, 0.35, 0.4, 0.45, 0.5, 0.55, 0.6, 0.65, 0.7];
+
+/// Subdirectory names per label
+const CALL_DIRS: &[&str] = &["call", "calls"];
+const NO_CALL_DIRS: &[&str] = &["no-call", "not-call", "no_call", "not_call"];
+
+struct Trace {
+    name: String,
+    is_call: bool,
+    samples: Vec<RecordedSample>,
+}
+
+/// Misclassified traces under one config
+#[derive(Debug, Default)]
+struct Errors {
+    false_positives: Vec<String>,
+    false_negatives: Vec<String>,
+}
+
+impl Errors {
+    fn count(&self) -> usize {
+        self.false_positives.len() + self.false_negatives.len()
+    }
+}
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let Some(dir) = args.first().filter(|a| !a.starts_with("--")) else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+
+    let base = match args.iter().position(|a| a == "--config").and_then(|i| args.get(i + 1)) {
+        Some(path) => match Config::load(Path::new(path)) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("[calibrate] {}", e);
+                return 2;
+            }
+        },
+        None => Config::default(),
+    };
+
+    let traces = match load_traces(Path::new(dir)) {
+        Ok(traces) => traces,
+        Err(e) => {
+            eprintln!("[calibrate] {}", e);
+            return 2;
+        }
+    };
+    let calls = traces.iter().filter(|trace| trace.is_call).count();
+    if calls == 0 || calls == traces.len() {
+        eprintln!("[calibrate] Need traces under both call/ and no-call/ in {}", dir);
+        return 2;
+    }
+    eprintln!("[calibrate] {} traces ({} call, {} no-call)", traces.len(), calls, traces.len() - calls);
+
+    let current = evaluate(&traces, &base.scoring, &base.hysteresis);
+    let (best, errors) = search(&traces, &base.scoring, &base.hysteresis);
+
+    eprintln!(
+        "[calibrate] current config: {}/{} correct",
+        traces.len() - current.count(),
+        traces.len()
+    );
+    eprintln!(
+        "[calibrate] recommended:    {}/{} correct",
+        traces.len() - errors.count(),
+        traces.len()
+    );
+    for name in &errors.false_positives {
+        eprintln!("[calibrate]   false positive: {}", name);
+    }
+    for name in &errors.false_negatives {
+        eprintln!("[calibrate]   false negative: {}", name);
+    }
+
+    let recommended = Config {
+        scoring: best,
+        ..base
+    };
+    match serde_json::to_string_pretty(&recommended) {
+        Ok(json) => {
+            println!("{}", json);
+            0
+        }
+        Err(e) => {
+            eprintln!("[calibrate] Failed to serialize config: {}", e);
+            1
+        }
+    }
+}
+
+/// Every `*.jsonl` trace under the label subdirectories of `dir`
+fn load_traces(dir: &Path) -> Result<Vec<Trace>, String> {
+    let mut traces = Vec::new();
+
+    for (names, is_call) in [(CALL_DIRS, true), (NO_CALL_DIRS, false)] {
+        for name in names {
+            let Ok(entries) = fs::read_dir(dir.join(name)) else { continue };
+            let mut paths: Vec<_> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "jsonl"))
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                traces.push(Trace {
+                    name: format!("{}/{}", name, path.file_name().unwrap_or_default().to_string_lossy()),
+                    is_call,
+                    samples: replay::load(&path)?,
+                });
+            }
+        }
+    }
+
+    Ok(traces)
+}
+
+/// Grid search; ties go to the config closest to `base` so weights only move when it helps
+fn search(traces: &[Trace], base: &ScoringConfig, hysteresis: &HysteresisConfig) -> (ScoringConfig, Errors) {
+    let mut best: Option<(ScoringConfig, Errors, f32)> = None;
+
+    for &audio_weight in WEIGHT_STEPS {
+        for &webrtc_weight in WEIGHT_STEPS {
+            for &mic_weight in WEIGHT_STEPS {
+                for &title_weight in WEIGHT_STEPS {
+                    for &threshold in THRESHOLD_STEPS {
+                        // Nothing can clear the threshold; every call would be missed
+                        if audio_weight + webrtc_weight + mic_weight + title_weight < threshold {
+                            continue;
+                        }
+
+                        let candidate = ScoringConfig {
+                            audio_weight,
+                            webrtc_weight,
+                            mic_weight,
+                            title_weight,
+                            threshold,
+                            ..base.clone()
+                        };
+                        let distance = (audio_weight - base.audio_weight).abs()
+                            + (webrtc_weight - base.webrtc_weight).abs()
+                            + (mic_weight - base.mic_weight).abs()
+                            + (title_weight - base.title_weight).abs()
+                            + (threshold - base.threshold).abs();
+
+                        let better = |errors: &Errors| match &best {
+                            Some((_, best_errors, best_distance)) => {
+                                (errors.count(), distance) < (best_errors.count(), *best_distance)
+                            }
+                            None => true,
+                        };
+
+                        let errors = evaluate(traces, &candidate, hysteresis);
+                        if better(&errors) {
+                            best = Some((candidate, errors, distance));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    match best {
+        Some((config, errors, _)) => (config, errors),
+        None => (base.clone(), evaluate(traces, base, hysteresis)),
+    }
+}
+
+fn evaluate(traces: &[Trace], scoring: &ScoringConfig, hysteresis: &HysteresisConfig) -> Errors {
+    let mut errors = Errors::default();
+    for trace in traces {
+        match (trace.is_call, starts_call(&trace.samples, scoring, hysteresis)) {
+            (true, false) => errors.false_negatives.push(trace.name.clone()),
+            (false, true) => errors.false_positives.push(trace.name.clone()),
+            _ => {}
+        }
+    }
+    errors
+}
+
+/// Whether replaying `samples` starts a call
+fn starts_call(samples: &[RecordedSample], scoring: &ScoringConfig, hysteresis: &HysteresisConfig) -> bool {
+    let engine = CorrelationEngine::new()
+        .with_scoring(scoring.clone())
+        .with_hysteresis(hysteresis.clone());
+    let mut tracker = CallTracker::new(engine);
+
+    samples.iter().any(|recorded| {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_millis(recorded.at_ms);
+        tracker
+            .update(&recorded.sample, now)
+            .iter()
+            .any(|event| matches!(event, MonitorEvent::CallStarted(_)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_tracker::Sample;
+    use crate::AudioSource;
+
+    #[test]
+    fn test_search_fits_labeled_traces() {
+        let source = |name: &str, process_id: u32| AudioSource {
+            name: name.to_string(),
+            process_id,
+            window_title: String::new(),
+            detected_app: Some("Zoom".to_string()),
+            private_context: false,
+            is_virtual_device: false,
+            classification: None,
+            session: None,
+        };
+        let trace = |name: &str, is_call: bool, webrtc: bool| {
+            let sample = Sample {
+                audio_sources: vec![source("zoom.us", 5)],
+                mic_sources: vec![source("zoom.us", 0)],
+                webrtc_pids: if webrtc { [5].into_iter().collect() } else { Default::default() },
+                ..Default::default()
+            };
+            Trace {
+                name: name.to_string(),
+                is_call,
+                samples: (0..6)
+                    .map(|tick| RecordedSample { at_ms: tick * 500, sample: sample.clone() })
+                    .collect(),
+            }
+        };
+
+        // Zoom with audio and mic but no WebRTC is the pre-join speaker test here, not a call
+        let traces = vec![trace("call/meeting", true, true), trace("no-call/speaker-test", false, false)];
+        let base = ScoringConfig::default();
+        let hysteresis = HysteresisConfig::default();
+
+        assert_eq!(evaluate(&traces, &base, &hysteresis).false_positives, vec!["no-call/speaker-test"]);
+        let (best, errors) = search(&traces, &base, &hysteresis);
+        assert_eq!(errors.count(), 0);
+        assert!(best.audio_weight + best.mic_weight < best.threshold);
+    }
+}
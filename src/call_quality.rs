@@ -0,0 +1,490 @@
+// Media quality of the active call
+// While a call is active a background thread measures, every QUALITY_INTERVAL:
+//   - round-trip time to the call's remote media address: ICMP echo through `ping` on
+//     Linux, macOS and FreeBSD, IcmpSendEcho on Windows; jitter and loss over the call
+//   - receive drops of the call process's UDP sockets (/proc/net/udp, Linux)
+//   - output device underruns: PipeWire's per-node ERR counter (Linux) and Core Audio
+//     processor overloads on the default output device (macOS)
+// They form the `quality` block of CallInfo and `call_ended`. Counters start at zero when
+// the call starts. A measure the platform does not offer is left out, and so is the RTT of
+// a remote that never answered: relays and peers often drop ICMP, which is not 100% loss.
+
+use crate::call_tracker::Sample;
+use crate::CallInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often the call is measured
+const QUALITY_INTERVAL: Duration = Duration::from_secs(10);
+/// Echo requests per measurement
+const ECHO_COUNT: u32 = 3;
+
+/// `quality` block of a call
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CallQuality {
+    /// Latest round-trip time to the remote media address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rtt_ms: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_rtt_ms: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rtt_ms: Option<f32>,
+    /// Mean difference between consecutive round-trip times
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter_ms: Option<f32>,
+    /// Echo requests left unanswered, once the remote has answered any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packet_loss_pct: Option<f32>,
+    /// Datagrams the call's UDP sockets dropped on receive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub udp_drops: Option<u64>,
+    /// Output device underruns (glitches heard as clicks or gaps)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_underruns: Option<u64>,
+}
+
+/// What the probe measured for one call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QualityReading {
+    pub process_id: u32,
+    pub quality: CallQuality,
+}
+
+/// `quality` for `call` this tick; the last reading stays until a newer one arrives
+pub fn quality_for(call: &CallInfo, sample: &Sample) -> Option<CallQuality> {
+    sample
+        .call_quality
+        .as_ref()
+        .filter(|reading| reading.process_id == call.process_id)
+        .map(|reading| reading.quality.clone())
+        .or_else(|| call.quality.clone())
+}
+
+/// The call being measured and the remote addresses of its media connections
+#[derive(Debug, Clone, PartialEq)]
+struct Target {
+    process_id: u32,
+    remotes: Vec<IpAddr>,
+}
+
+/// Background measurements of the active call
+#[derive(Default)]
+pub struct QualityProbe {
+    target: Arc<Mutex<Option<Target>>>,
+    reading: Arc<Mutex<Option<QualityReading>>>,
+    is_running: bool,
+}
+
+impl QualityProbe {
+    /// Measure `call` (the call active at the start of the tick) against `remotes`, its
+    /// media connections' remote addresses; the latest reading for it
+    pub fn probe(&mut self, call: Option<&CallInfo>, remotes: Vec<IpAddr>) -> Option<QualityReading> {
+        let target = call.map(|call| Target { process_id: call.process_id, remotes });
+        *lock(&self.target) = target.clone();
+        let target = target?;
+        if !self.is_running {
+            self.spawn();
+        }
+        lock(&self.reading).clone().filter(|reading| reading.process_id == target.process_id)
+    }
+
+    fn spawn(&mut self) {
+        self.is_running = true;
+        let target = Arc::clone(&self.target);
+        let reading = Arc::clone(&self.reading);
+        thread::spawn(move || {
+            let mut measure: Option<(u32, Measurement)> = None;
+            loop {
+                match lock(&target).clone() {
+                    Some(target) => {
+                        if measure.as_ref().map(|(process_id, _)| *process_id) != Some(target.process_id) {
+                            measure = Some((target.process_id, Measurement::default()));
+                        }
+                        if let Some((_, measurement)) = measure.as_mut() {
+                            let quality = measurement.run(&target);
+                            *lock(&reading) = Some(QualityReading { process_id: target.process_id, quality });
+                        }
+                    }
+                    None => {
+                        measure = None;
+                        *lock(&reading) = None;
+                    }
+                }
+                thread::sleep(QUALITY_INTERVAL);
+            }
+        });
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Everything measured since the call started
+#[derive(Default)]
+struct Measurement {
+    remote: Option<IpAddr>,
+    rtt: RttStats,
+    udp_drops: DeltaCounter<u64>,
+    underruns: DeltaCounter<String>,
+}
+
+impl Measurement {
+    fn run(&mut self, target: &Target) -> CallQuality {
+        // Stay with one remote while it is connected so the statistics describe one path
+        if !self.remote.is_some_and(|remote| target.remotes.contains(&remote)) {
+            self.remote = pick_remote(&target.remotes);
+        }
+        if let Some(rtts) = self.remote.and_then(ping) {
+            self.rtt.record(ECHO_COUNT, &rtts);
+        }
+
+        let udp_drops = udp_drops(target.process_id).map(|drops| self.udp_drops.update(drops));
+        let audio_underruns = output_underruns().map(|counts| self.underruns.update(counts));
+        CallQuality { udp_drops, audio_underruns, ..self.rtt.quality() }
+    }
+}
+
+/// The remote to ping: IPv4 first, which every platform's echo path supports
+fn pick_remote(remotes: &[IpAddr]) -> Option<IpAddr> {
+    remotes
+        .iter()
+        .find(|ip| ip.is_ipv4())
+        .or_else(|| remotes.first().filter(|_| cfg!(target_os = "linux")))
+        .copied()
+}
+
+#[derive(Debug, Default)]
+struct RttStats {
+    sent: u32,
+    received: u32,
+    last: Option<f32>,
+    sum: f32,
+    max: f32,
+    /// Sum and count of differences between consecutive round-trip times
+    jitter_sum: f32,
+    jitter_count: u32,
+}
+
+impl RttStats {
+    fn record(&mut self, sent: u32, rtts: &[f32]) {
+        self.sent += sent;
+        for &rtt in rtts {
+            if let Some(last) = self.last {
+                self.jitter_sum += (rtt - last).abs();
+                self.jitter_count += 1;
+            }
+            self.received += 1;
+            self.sum += rtt;
+            self.max = self.max.max(rtt);
+            self.last = Some(rtt);
+        }
+    }
+
+    fn quality(&self) -> CallQuality {
+        if self.received == 0 {
+            return CallQuality::default();
+        }
+        let lost = self.sent.saturating_sub(self.received);
+        CallQuality {
+            rtt_ms: self.last,
+            avg_rtt_ms: Some(self.sum / self.received as f32),
+            max_rtt_ms: Some(self.max),
+            jitter_ms: (self.jitter_count > 0).then(|| self.jitter_sum / self.jitter_count as f32),
+            packet_loss_pct: Some(lost as f32 * 100.0 / self.sent as f32),
+            ..Default::default()
+        }
+    }
+}
+
+/// Total growth of per-object counters (sockets, audio nodes) since each was first seen;
+/// counts of objects that went away are kept
+#[derive(Debug)]
+struct DeltaCounter<K> {
+    first: HashMap<K, u64>,
+    latest: HashMap<K, u64>,
+    gone: u64,
+}
+
+impl<K> Default for DeltaCounter<K> {
+    fn default() -> Self {
+        DeltaCounter { first: HashMap::new(), latest: HashMap::new(), gone: 0 }
+    }
+}
+
+impl<K: Eq + Hash + Clone> DeltaCounter<K> {
+    fn update(&mut self, counts: HashMap<K, u64>) -> u64 {
+        for (key, latest) in &self.latest {
+            if !counts.contains_key(key) {
+                self.gone += latest.saturating_sub(self.first[key]);
+            }
+        }
+        self.first.retain(|key, _| counts.contains_key(key));
+        for (key, &count) in &counts {
+            self.first.entry(key.clone()).or_insert(count);
+        }
+        self.latest = counts;
+        self.gone + self.latest.iter().map(|(key, count)| count.saturating_sub(self.first[key])).sum::<u64>()
+    }
+}
+
+/// Round-trip times of the echo requests that were answered
+#[cfg(unix)]
+fn ping(remote: IpAddr) -> Option<Vec<f32>> {
+    use std::process::Command;
+
+    let count = ECHO_COUNT.to_string();
+    let remote = remote.to_string();
+    let mut command = Command::new("ping");
+    // Numeric output, and a deadline so an unanswered remote does not hang the thread
+    #[cfg(target_os = "linux")]
+    command.args(["-n", "-c", &count, "-w", "5", &remote]);
+    #[cfg(not(target_os = "linux"))]
+    command.args(["-n", "-c", &count, "-t", "5", &remote]);
+    let output = crate::subprocess::output(&mut command).ok()?;
+    Some(parse_ping(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// "64 bytes from 142.250.1.1: icmp_seq=1 ttl=117 time=23.4 ms"
+#[cfg_attr(not(unix), allow(dead_code))]
+fn parse_ping(output: &str) -> Vec<f32> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once("time=")?;
+            rest.split(|c: char| c.is_whitespace() || c == 'm').next()?.parse().ok()
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn ping(remote: IpAddr) -> Option<Vec<f32>> {
+    use windows::Win32::NetworkManagement::IpHelper::{IcmpCloseHandle, IcmpCreateFile, IcmpSendEcho, ICMP_ECHO_REPLY};
+
+    // IcmpSendEcho is IPv4 only
+    let IpAddr::V4(remote) = remote else { return None };
+    let data = [0u8; 32];
+    // Room for one reply, its echoed data and an ICMP error
+    let mut reply = vec![0u64; (std::mem::size_of::<ICMP_ECHO_REPLY>() + data.len() + 8).div_ceil(8)];
+    let mut rtts = Vec::new();
+    unsafe {
+        let handle = IcmpCreateFile().ok()?;
+        for _ in 0..ECHO_COUNT {
+            let replies = IcmpSendEcho(
+                handle,
+                u32::from_ne_bytes(remote.octets()),
+                data.as_ptr() as *const _,
+                data.len() as u16,
+                None,
+                reply.as_mut_ptr() as *mut _,
+                (reply.len() * 8) as u32,
+                1000,
+            );
+            let echo = &*(reply.as_ptr() as *const ICMP_ECHO_REPLY);
+            // IP_SUCCESS
+            if replies > 0 && echo.Status == 0 {
+                rtts.push(echo.RoundTripTime as f32);
+            }
+        }
+        let _ = IcmpCloseHandle(handle);
+    }
+    Some(rtts)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn ping(_remote: IpAddr) -> Option<Vec<f32>> {
+    None
+}
+
+/// Receive drops of each UDP socket (by inode) owned by the call's process or its helpers
+#[cfg(target_os = "linux")]
+fn udp_drops(process_id: u32) -> Option<HashMap<u64, u64>> {
+    use crate::process_tree::ProcessTree;
+
+    let mut drops = HashMap::new();
+    for table in ["/proc/net/udp", "/proc/net/udp6"] {
+        if let Ok(text) = std::fs::read_to_string(table) {
+            drops.extend(parse_udp_drops(&text));
+        }
+    }
+    if drops.is_empty() {
+        return None;
+    }
+    let owners = crate::sock_diag::inode_owners(&drops.keys().copied().collect());
+    let mut process_tree = ProcessTree::snapshot();
+    drops.retain(|inode, _| owners.get(inode).is_some_and(|&pid| process_tree.root(pid) == process_id));
+    Some(drops)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn udp_drops(_process_id: u32) -> Option<HashMap<u64, u64>> {
+    None
+}
+
+// sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode ref pointer drops
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_udp_drops(text: &str) -> HashMap<u64, u64> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let inode = fields.get(9)?.parse().ok().filter(|&inode| inode != 0)?;
+            let drops = fields.last()?.parse().ok()?;
+            Some((inode, drops))
+        })
+        .collect()
+}
+
+/// Underrun counter of each output node, from `pw-top`'s ERR column
+#[cfg(target_os = "linux")]
+fn output_underruns() -> Option<HashMap<String, u64>> {
+    use std::process::Command;
+
+    // The first iteration only primes pw-top's statistics
+    let output = crate::subprocess::output(Command::new("pw-top").args(["-b", "-n", "2"])).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_pw_top(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// S ID QUANT RATE WAIT BUSY W/Q B/Q ERR FORMAT NAME
+// Later iterations repeat every node, so the last row of each wins.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_pw_top(output: &str) -> HashMap<String, u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let name = fields.last()?;
+            if !(name.starts_with("alsa_output.") || name.starts_with("bluez_output.")) {
+                return None;
+            }
+            Some((name.to_string(), fields.get(8)?.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Processor overloads of the default output device, counted by a property listener
+#[cfg(target_os = "macos")]
+fn output_underruns() -> Option<HashMap<String, u64>> {
+    macos::overloads().map(|(device, count)| HashMap::from([(device.to_string(), count)]))
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use coreaudio::sys::{
+        AudioObjectAddPropertyListener, AudioObjectGetPropertyData, AudioObjectID, AudioObjectPropertyAddress,
+        AudioObjectRemovePropertyListener, OSStatus,
+    };
+    use std::ffi::c_void;
+    use std::mem::size_of;
+    use std::ptr;
+    use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+    const fn four_cc(code: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*code)
+    }
+
+    const SYSTEM_OBJECT: u32 = 1;
+    const SCOPE_GLOBAL: u32 = four_cc(b"glob");
+    const ELEMENT_MAIN: u32 = 0;
+    const DEFAULT_OUTPUT_DEVICE: u32 = four_cc(b"dOut");
+    const PROCESSOR_OVERLOAD: u32 = four_cc(b"over");
+
+    static LISTENED_DEVICE: AtomicU32 = AtomicU32::new(0);
+    static OVERLOADS: AtomicU64 = AtomicU64::new(0);
+
+    fn address(selector: u32) -> AudioObjectPropertyAddress {
+        AudioObjectPropertyAddress { mSelector: selector, mScope: SCOPE_GLOBAL, mElement: ELEMENT_MAIN }
+    }
+
+    unsafe extern "C" fn on_overload(
+        _object: AudioObjectID,
+        _count: u32,
+        _addresses: *const AudioObjectPropertyAddress,
+        _client_data: *mut c_void,
+    ) -> OSStatus {
+        OVERLOADS.fetch_add(1, Ordering::Relaxed);
+        0
+    }
+
+    /// The default output device and the overloads counted on it since it was first seen;
+    /// a new default device starts a new count
+    pub fn overloads() -> Option<(AudioObjectID, u64)> {
+        unsafe {
+            let mut device: AudioObjectID = 0;
+            let mut size = size_of::<AudioObjectID>() as u32;
+            let status = AudioObjectGetPropertyData(
+                SYSTEM_OBJECT,
+                &address(DEFAULT_OUTPUT_DEVICE),
+                0,
+                ptr::null(),
+                &mut size,
+                &mut device as *mut AudioObjectID as *mut c_void,
+            );
+            if status != 0 || device == 0 {
+                return None;
+            }
+
+            let listened = LISTENED_DEVICE.load(Ordering::Relaxed);
+            if listened != device {
+                let overload = address(PROCESSOR_OVERLOAD);
+                if listened != 0 {
+                    AudioObjectRemovePropertyListener(listened, &overload, Some(on_overload), ptr::null_mut());
+                }
+                if AudioObjectAddPropertyListener(device, &overload, Some(on_overload), ptr::null_mut()) != 0 {
+                    LISTENED_DEVICE.store(0, Ordering::Relaxed);
+                    return None;
+                }
+                LISTENED_DEVICE.store(device, Ordering::Relaxed);
+                OVERLOADS.store(0, Ordering::Relaxed);
+            }
+            Some((device, OVERLOADS.load(Ordering::Relaxed)))
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn output_underruns() -> Option<HashMap<String, u64>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ping_and_socket_counters_are_parsed() {
+        let output = "PING 142.250.1.1 (142.250.1.1) 56(84) bytes of data.\n\
+                      64 bytes from 142.250.1.1: icmp_seq=1 ttl=117 time=23.4 ms\n\
+                      64 bytes from 142.250.1.1: icmp_seq=3 ttl=117 time=27.4 ms\n";
+        let rtts = parse_ping(output);
+        assert_eq!(rtts, vec![23.4, 27.4]);
+
+        let mut stats = RttStats::default();
+        assert_eq!(stats.quality(), CallQuality::default());
+        stats.record(4, &rtts);
+        let quality = stats.quality();
+        assert_eq!(quality.rtt_ms, Some(27.4));
+        assert_eq!(quality.max_rtt_ms, Some(27.4));
+        assert_eq!(quality.packet_loss_pct, Some(50.0));
+        assert!((quality.jitter_ms.unwrap() - 4.0).abs() < 0.01);
+
+        let udp = "   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops\n\
+                   1234: 0100007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 17292 2 0000000000000000 3\n";
+        let drops = parse_udp_drops(udp);
+        assert_eq!(drops, HashMap::from([(17292, 3)]));
+
+        // Counters count from their first sighting; a closed socket keeps its share
+        let mut counter = DeltaCounter::default();
+        assert_eq!(counter.update(drops), 0);
+        assert_eq!(counter.update(HashMap::from([(17292, 5), (17300, 1)])), 2);
+        assert_eq!(counter.update(HashMap::from([(17300, 4)])), 5);
+    }
+}
@@ -0,0 +1,176 @@
+// Active call persistence across restarts (`--state-file`)
+// A crash, an update or a supervisor restart mid-call used to report a fresh
+// `call_started` and lose the call's duration. While a call is active, its CallInfo and
+// start time are written to a small JSON file every few seconds, and the file is removed
+// when the call ends. At start-up a saved call is resumed if it is recent and its pid
+// still runs the same program, so the tracker carries on without a new `call_started`.
+// The saved title is the redacted one; it is marked so, and written as saved after a
+// resume rather than redacted a second time.
+
+use crate::process_tree::ProcessTree;
+use crate::CallInfo;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+/// Older saves are not resumed: the call may have ended while nothing was watching
+const MAX_AGE: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedCall {
+    call: CallInfo,
+    /// Executable name behind `call.process_id`, to tell a reused pid apart
+    process_name: String,
+    /// `call.window_title` is already redacted for output
+    #[serde(default)]
+    title_redacted: bool,
+    /// Unix milliseconds
+    started_ms: u64,
+    saved_ms: u64,
+}
+
+pub struct CallStateFile {
+    path: PathBuf,
+    last_save: Option<Instant>,
+    process_name: Option<(u32, String)>,
+    is_failing: bool,
+}
+
+impl CallStateFile {
+    pub fn new(path: PathBuf) -> Self {
+        CallStateFile { path, last_save: None, process_name: None, is_failing: false }
+    }
+
+    /// The call a previous run saved, if it can be resumed, and whether its title is
+    /// already redacted; anything else is discarded
+    pub fn load(&self, now: SystemTime) -> Option<(CallInfo, bool)> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        let resumed = serde_json::from_str::<SavedCall>(&contents)
+            .map_err(|e| format!("invalid {}: {}", self.path.display(), e))
+            .and_then(|saved| {
+                let title_redacted = saved.title_redacted;
+                resumable(saved, now, |pid| ProcessTree::snapshot().entry(pid).map(|entry| entry.name.clone()))
+                    .map(|call| (call, title_redacted))
+            });
+        match resumed {
+            Ok(resumed) => Some(resumed),
+            Err(reason) => {
+                log::warn!("Not resuming the saved call: {}", reason);
+                fs::remove_file(&self.path).ok();
+                None
+            }
+        }
+    }
+
+    /// Keep the file in step with the active call, whose title `title_redacted` says whether
+    /// it is redacted
+    pub fn update(&mut self, call: Option<&CallInfo>, title_redacted: bool, now: Instant) {
+        let Some(call) = call else {
+            if self.last_save.take().is_some() {
+                fs::remove_file(&self.path).ok();
+            }
+            self.process_name = None;
+            return;
+        };
+
+        let is_same_call = self.process_name.as_ref().is_some_and(|(pid, _)| *pid == call.process_id);
+        if is_same_call && self.last_save.is_some_and(|last| now.duration_since(last) < SAVE_INTERVAL) {
+            return;
+        }
+        if !is_same_call {
+            let name = ProcessTree::snapshot().entry(call.process_id).map(|entry| entry.name.clone()).unwrap_or_default();
+            self.process_name = Some((call.process_id, name));
+        }
+        self.last_save = Some(now);
+
+        let saved = SavedCall {
+            call: call.clone(),
+            process_name: self.process_name.as_ref().map(|(_, name)| name.clone()).unwrap_or_default(),
+            title_redacted,
+            started_ms: unix_ms(call.call_started_system_time),
+            saved_ms: unix_ms(SystemTime::now()),
+        };
+        match self.write(&saved) {
+            Ok(()) => self.is_failing = false,
+            Err(e) if !self.is_failing => {
+                log::warn!("Cannot save the call state to {}: {}", self.path.display(), e);
+                self.is_failing = true;
+            }
+            Err(_) => {}
+        }
+    }
+
+    // Through a temporary file so a crash mid-write leaves the previous save intact
+    fn write(&self, saved: &SavedCall) -> Result<(), String> {
+        let json = serde_json::to_string(saved).map_err(|e| e.to_string())?;
+        let staged = self.path.with_extension("tmp");
+        fs::write(&staged, json).map_err(|e| e.to_string())?;
+        fs::rename(&staged, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or(0)
+}
+
+fn resumable(
+    saved: SavedCall,
+    now: SystemTime,
+    process_name: impl Fn(u32) -> Option<String>,
+) -> Result<CallInfo, String> {
+    let age = Duration::from_millis(unix_ms(now).saturating_sub(saved.saved_ms));
+    if age > MAX_AGE {
+        return Err(format!("saved {}s ago", age.as_secs()));
+    }
+    let mut call = saved.call;
+    match process_name(call.process_id) {
+        None => return Err(format!("process {} has exited", call.process_id)),
+        Some(name) if name != saved.process_name => {
+            return Err(format!("pid {} is now {}, not {}", call.process_id, name, saved.process_name))
+        }
+        Some(_) => {}
+    }
+    // Saves from before started_at_utc, or without --legacy-timestamps, lack some of the dates
+    call.set_started(UNIX_EPOCH + Duration::from_millis(saved.started_ms));
+    call.duration_secs = crate::call_duration_secs(&call, now);
+    Ok(call)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(saved_ago: Duration, now: SystemTime) -> SavedCall {
+        let started = now - Duration::from_secs(600);
+        SavedCall {
+            call: CallInfo {
+                window_title: "Zoom Meeting".to_string(),
+                has_webrtc: false,
+                started_at: "09:00:00".to_string(),
+                call_started_system_time: now,
+                ..crate::test_call("Zoom", 4242)
+            },
+            process_name: "zoom".to_string(),
+            title_redacted: true,
+            started_ms: unix_ms(started),
+            saved_ms: unix_ms(now - saved_ago),
+        }
+    }
+
+    #[test]
+    fn test_saved_call_resumes_only_in_the_same_process() {
+        let now = SystemTime::now();
+        let zoom = |_: u32| Some("zoom".to_string());
+
+        let call = resumable(saved(Duration::from_secs(3), now), now, zoom).expect("resumed");
+        assert_eq!(call.duration_secs, 600);
+        // Dated again from the saved start, which older saves and schema 2 outputs need
+        assert_eq!(call.started_at_utc, crate::output::utc_timestamp(now - Duration::from_secs(600)));
+
+        assert!(resumable(saved(Duration::from_secs(3), now), now, |_| None).is_err());
+        assert!(resumable(saved(Duration::from_secs(3), now), now, |_| Some("bash".to_string())).is_err());
+        assert!(resumable(saved(MAX_AGE * 2, now), now, zoom).is_err());
+    }
+}
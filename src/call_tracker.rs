@@ -0,0 +1,944 @@
+// Call lifecycle tracking
+// Turns one tick of sensed sources into the next MonitorState and the events between
+// them. Owns the previous state, call start times and duration math so the main loop
+// only has to collect sources; the start/end hysteresis lives in the correlation engine.
+
+use crate::audio::OutputDevice;
+use crate::browser_bridge::BrowserTab;
+use crate::call_quality::{self, QualityReading};
+use crate::companion::ExternalCall;
+use crate::correlation_engine::{
+    CallCandidate, CallPhase, CorrelationEngine, DetectionResult, HysteresisConfig, MultiSignal, ScoringConfig,
+    SourceClass,
+};
+use crate::echo_risk::{self, AudioRouting};
+use crate::environment::Environment;
+use crate::events::{self, MonitorEvent};
+use crate::in_app_mute::{self, InAppMute};
+use crate::meeting_id::{self, ScheduledMeeting};
+use crate::mute_timeline::{MuteTimeline, VolumeLevels};
+use crate::participants;
+use crate::recording_probe::{self, RecordingIndicator};
+use crate::role::{CallRole, RoleTracker};
+use crate::process_load::ProcessLoad;
+use crate::signal_providers::SignalContribution;
+use crate::slack_huddle::SlackHuddle;
+use crate::source_sessions::SourceSessions;
+use crate::uia::{self, ControlsReading};
+use crate::zoom_probe::ZoomMeeting;
+use crate::{AudioSource, CallInfo, MonitorState};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+/// Output peak below which a process tree counts as silent
+const SILENT_PEAK: f32 = 0.001;
+
+/// Everything sensed in one tick
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sample {
+    /// Processes currently playing audio
+    pub audio_sources: Vec<AudioSource>,
+    /// Processes currently using the microphone
+    pub mic_sources: Vec<AudioSource>,
+    /// Processes with WebRTC network activity
+    pub webrtc_pids: HashSet<u32>,
+    /// Processes with SIP signalling alongside RTP media
+    pub sip_pids: HashSet<u32>,
+    /// Whether Teams reports a call (local client API or Graph presence), when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teams_in_call: Option<bool>,
+    /// Open Zoom meeting window found by zoom_probe, root process resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zoom_meeting: Option<ZoomMeeting>,
+    /// Huddle evidence found by slack_huddle, root process resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slack_huddle: Option<SlackHuddle>,
+    /// Tabs reported by the companion browser extension (empty without it)
+    pub browser_tabs: Vec<BrowserTab>,
+    /// A browser holds a live PeerConnection (Chromium's event log, Firefox's about:webrtc).
+    /// Browser-wide: event log names carry Chromium's render process host id, not an OS pid
+    pub browser_peer_connection: bool,
+    /// Microphone, output and per-session volume and mute
+    pub levels: VolumeLevels,
+    /// Input peak of each root process's capture sessions, where the platform meters them
+    pub capture_peaks: BTreeMap<u32, f32>,
+    /// Input peak each root process records, including device-level meters (Pulse)
+    pub mic_peaks: BTreeMap<u32, f32>,
+    /// Seconds each audio source has been playing without a break
+    pub audio_active_secs: BTreeMap<u32, f32>,
+    /// Recent output peak of the whole process tree of the call and its candidates, from
+    /// process loopback (Windows 10 2004+); other apps playing don't count
+    pub render_peaks: BTreeMap<u32, f32>,
+    /// CPU and GPU load of the call and its candidates (see process_load.rs)
+    pub process_load: BTreeMap<u32, ProcessLoad>,
+    /// Meeting identifier on each audio source's command line (see meeting_id.rs)
+    pub command_line_meetings: BTreeMap<u32, String>,
+    /// Call app each audio source's app-mode browser window was started for (see meeting_id.rs)
+    pub command_line_apps: BTreeMap<u32, String>,
+    /// Calendar meetings with a conferencing link under way (`calendar` feature)
+    pub scheduled_meetings: Vec<ScheduledMeeting>,
+    /// Peer-to-peer media endpoints of each root process (see network_monitor.rs)
+    pub media_peers: BTreeMap<u32, usize>,
+    /// Devices each root process's render sessions play to, where the backend tells
+    pub output_devices: BTreeMap<u32, Vec<OutputDevice>>,
+    /// Default output and capture devices read for the call active at the start of the tick
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_routing: Option<AudioRouting>,
+    /// Recording probe result for the call active at the start of the tick
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recording: Option<RecordingIndicator>,
+    /// Call controls read through UI Automation for the call active at the start of the tick
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_controls: Option<ControlsReading>,
+    /// Quality measured for the call active at the start of the tick (see call_quality.rs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_quality: Option<QualityReading>,
+    /// Evidence from registered signal providers (see signal_providers.rs)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub contributions: Vec<SignalContribution>,
+    /// Calls on paired phones (see companion.rs)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_calls: Vec<ExternalCall>,
+}
+
+impl Sample {
+    /// This sample without the audio and mic sources and tabs of apps outside
+    /// `tracked_apps`, or anything else sensed for their processes, for `--record-signals`
+    pub fn tracked_only(&self, scoring: &ScoringConfig) -> Sample {
+        let tracked = |src: &AudioSource| scoring.is_tracked(src.detected_app.as_deref(), &src.name, &src.window_title);
+        let untracked: HashSet<u32> = self
+            .audio_sources
+            .iter()
+            .chain(&self.mic_sources)
+            .filter(|src| !tracked(src))
+            .map(|src| src.process_id)
+            .collect();
+        let mut sample = self.clone();
+        if untracked.is_empty() && scoring.tracked_apps.is_empty() {
+            return sample;
+        }
+
+        sample.audio_sources.retain(tracked);
+        sample.mic_sources.retain(tracked);
+        sample.browser_tabs.retain(|tab| scoring.is_tracked(tab.call_app(), &tab.browser, &tab.title));
+        let keep = |process_id: &u32| !untracked.contains(process_id);
+        sample.webrtc_pids.retain(keep);
+        sample.sip_pids.retain(keep);
+        sample.capture_peaks.retain(|process_id, _| keep(process_id));
+        sample.mic_peaks.retain(|process_id, _| keep(process_id));
+        sample.audio_active_secs.retain(|process_id, _| keep(process_id));
+        sample.render_peaks.retain(|process_id, _| keep(process_id));
+        sample.process_load.retain(|process_id, _| keep(process_id));
+        sample.command_line_meetings.retain(|process_id, _| keep(process_id));
+        sample.command_line_apps.retain(|process_id, _| keep(process_id));
+        sample.media_peers.retain(|process_id, _| keep(process_id));
+        sample.output_devices.retain(|process_id, _| keep(process_id));
+        sample
+    }
+}
+
+/// The `tracked_apps` restriction as reported in the state: other apps' audio sources are
+/// counted per classification instead of listed, so they cannot be told apart
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackedApps {
+    pub apps: Vec<String>,
+    pub untracked_audio_sources: BTreeMap<SourceClass, u32>,
+}
+
+pub struct CallTracker {
+    engine: CorrelationEngine,
+    state: MonitorState,
+    detections: Vec<DetectionResult>,
+    mute_timeline: MuteTimeline,
+    in_app_mute: InAppMute,
+    role: RoleTracker,
+    /// When each root process's recording last went silent (see `mic_idle`)
+    mic_silent_since: BTreeMap<u32, SystemTime>,
+    source_sessions: SourceSessions,
+}
+
+impl CallTracker {
+    pub fn new(engine: CorrelationEngine) -> Self {
+        CallTracker {
+            engine,
+            state: MonitorState {
+                active_call: None,
+                other_audio_sources: Vec::new(),
+                environment: Environment::Local,
+                tracked_apps: None,
+                external_calls: Vec::new(),
+            },
+            detections: Vec::new(),
+            mute_timeline: MuteTimeline::default(),
+            in_app_mute: InAppMute::default(),
+            role: RoleTracker::default(),
+            mic_silent_since: BTreeMap::new(),
+            source_sessions: SourceSessions::default(),
+        }
+    }
+
+    /// Report `environment` in every state
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.state.environment = environment;
+        self
+    }
+
+    /// Apply reloaded scoring and hysteresis without ending the active call
+    pub fn reconfigure(&mut self, scoring: ScoringConfig, hysteresis: HysteresisConfig) {
+        self.engine.set_scoring(scoring);
+        self.engine.set_hysteresis(hysteresis);
+    }
+
+    /// Carry on with a call saved by a previous run, without a new `call_started`
+    pub fn resume(&mut self, call: CallInfo) {
+        self.engine.resume(call.process_id, call.call_started_system_time);
+        self.state.active_call = Some(call);
+    }
+
+    /// State after the last update
+    pub fn state(&self) -> &MonitorState {
+        &self.state
+    }
+
+    /// Scoring in effect, including `tracked_apps`
+    pub fn scoring(&self) -> &ScoringConfig {
+        self.engine.scoring()
+    }
+
+    /// Call lifecycle phase after the last update
+    pub fn phase(&self) -> CallPhase {
+        self.engine.phase()
+    }
+
+    /// Every detection evaluated during the last update
+    pub fn detections(&self) -> &[DetectionResult] {
+        &self.detections
+    }
+
+    /// Advance by one tick and return what changed
+    pub fn update(&mut self, sample: &Sample, now: SystemTime) -> Vec<MonitorEvent> {
+        self.detections.clear();
+        self.observe_mic_silence(sample, now);
+
+        let mut active_call = match self.state.active_call.clone() {
+            Some(prev_call) => self.track_active_call(&prev_call, sample, now),
+            None => self.detect_new_call(sample, now),
+        };
+        if let Some(call) = active_call.as_mut() {
+            call.duration_secs = crate::call_duration_secs(call, now);
+            call.controls = uia::controls_for(call, sample.call_controls.as_ref());
+            call.in_app_muted = self.in_app_mute.update(call, sample, now);
+            call.estimated_participants = participants::estimate(call, sample);
+            call.role = self.role.update(call);
+            call.meeting_is_recorded = recording_probe::is_recorded(call, sample);
+            call.quality = call_quality::quality_for(call, sample);
+            call.echo_risk = echo_risk::assess(call, sample);
+        }
+
+        // Everything that is not the active call, with what it is
+        let scoring = self.engine.scoring();
+        let mut untracked_audio_sources = BTreeMap::new();
+        let mut other_audio_sources: Vec<AudioSource> = sample
+            .audio_sources
+            .iter()
+            .filter(|src| active_call.as_ref().map_or(true, |call| src.process_id != call.process_id))
+            .map(|src| {
+                let is_call_app = src.detected_app.is_some() || sample.sip_pids.contains(&src.process_id);
+                AudioSource {
+                    classification: Some(self.engine.classify_source(&src.name, &src.window_title, is_call_app)),
+                    ..src.clone()
+                }
+            })
+            .filter(|src| {
+                let is_tracked = scoring.is_tracked(src.detected_app.as_deref(), &src.name, &src.window_title);
+                if !is_tracked {
+                    *untracked_audio_sources.entry(src.classification.unwrap_or(SourceClass::Unknown)).or_insert(0) += 1;
+                }
+                is_tracked
+            })
+            .collect();
+        self.source_sessions.update(&mut other_audio_sources, now);
+        let tracked_apps = (!scoring.tracked_apps.is_empty())
+            .then(|| TrackedApps { apps: scoring.tracked_apps.clone(), untracked_audio_sources });
+
+        let next = MonitorState {
+            active_call,
+            other_audio_sources,
+            environment: self.state.environment,
+            tracked_apps,
+            external_calls: sample.external_calls.clone(),
+        };
+
+        let mut events = events::diff_states(&self.state, &next, now);
+        for event in events.iter_mut() {
+            if let MonitorEvent::CallEnded(ended) = event {
+                ended.mute_timeline = self.mute_timeline.take();
+            }
+        }
+        if let Some(call) = next.active_call.as_ref() {
+            self.mute_timeline.observe(call, &sample.levels, now);
+        }
+        self.state = next;
+        events
+    }
+
+    /// Re-score the call we already report
+    fn track_active_call(&mut self, prev_call: &CallInfo, sample: &Sample, now: SystemTime) -> Option<CallInfo> {
+        let audio_src = sample
+            .audio_sources
+            .iter()
+            .find(|src| src.process_id == prev_call.process_id);
+        // A reload that dropped the app from `tracked_apps` ends the call without scoring it
+        let process_name = audio_src.map_or(prev_call.app.as_str(), |src| src.name.as_str());
+        if !self.engine.scoring().is_tracked(Some(&prev_call.app), process_name, &prev_call.window_title) {
+            return match self.engine.update_phase(None, now) {
+                CallPhase::Ending { .. } => Some(prev_call.clone()),
+                _ => None,
+            };
+        }
+
+        let tab = audio_src.and_then(|src| browser_tab(sample, &src.name, Some(&prev_call.app)));
+        let has_mic = match tab {
+            Some(tab) => tab.capturing_audio,
+            None => self.mic_sources(sample).any(|src| is_same_app(src, prev_call.process_id, &prev_call.app)),
+        };
+        let render_peak = sample.render_peaks.get(&prev_call.process_id).copied();
+        // Sound from a helper process without a session of its own still counts
+        let has_audio = audio_src.is_some() || render_peak.is_some_and(|peak| peak > SILENT_PEAK);
+        let has_webrtc = sample.webrtc_pids.contains(&prev_call.process_id);
+        let has_sip = sample.sip_pids.contains(&prev_call.process_id);
+
+        let audio_peak_level = render_peak.unwrap_or(if audio_src.is_some() { 0.1 } else { 0.0 }); // Simplified without a meter
+        let window_title = tab
+            .map(|tab| tab.title.clone())
+            .or_else(|| audio_src.map(|src| src.window_title.clone()))
+            .unwrap_or_else(|| prev_call.window_title.clone());
+
+        // A meeting link opened later (a tab reported by the extension) fills it in
+        let meeting_identifier = prev_call.meeting_identifier.clone().or_else(|| {
+            meeting_identifier(sample, tab, prev_call.process_id, &window_title, &prev_call.app, prev_call.private_context)
+        });
+        // The event a call started in stays with it when the call runs over
+        let scheduled_meeting = prev_call.scheduled_meeting.clone().or_else(|| {
+            scheduled_meeting(sample, meeting_identifier.as_deref(), prev_call.private_context)
+        });
+
+        let signal = MultiSignal {
+            process_id: prev_call.process_id,
+            process_name: prev_call.app.clone(),
+            window_title: window_title.clone(),
+            has_mic_active: has_mic,
+            mic_peak_level: mic_peak_level(sample, prev_call.process_id, has_mic),
+            mic_idle: false, // A quiet participant keeps the call's mic
+            process_load: sample.process_load.get(&prev_call.process_id).copied(),
+            has_audio_output: has_audio,
+            audio_peak_level,
+            is_virtual_device: audio_src.is_some_and(|src| src.is_virtual_device),
+            has_webrtc_connection: has_webrtc,
+            webrtc_started_at: None,
+            has_peer_connection: has_peer_connection(
+                sample,
+                audio_src.map_or(prev_call.app.as_str(), |src| src.name.as_str()),
+            ),
+            has_sip_media: has_sip,
+            client_in_call: client_in_call(sample, &prev_call.app),
+            in_scheduled_meeting: scheduled_meeting.is_some(),
+            audio_active_secs: None,
+            contributions: contributions_for(sample, prev_call.process_id, &[&prev_call.app]),
+            detected_app: Some(prev_call.app.clone()),
+            tab_url: tab.map(|tab| tab.url.clone()),
+            duration: now
+                .duration_since(prev_call.call_started_system_time)
+                .unwrap_or(Duration::from_secs(0)),
+        };
+
+        // should_maintain_call handles mic/camera off scenarios
+        let should_continue = self.engine.should_maintain_call(&signal, true);
+        let detection = self.engine.detect_call(&signal);
+        let confidence = detection.confidence;
+        self.detections.push(detection);
+
+        let candidate = if should_continue {
+            Some(CallCandidate { process_id: prev_call.process_id, confidence })
+        } else {
+            None
+        };
+
+        match self.engine.update_phase(candidate, now) {
+            CallPhase::Active { .. } => Some(CallInfo {
+                app: prev_call.app.clone(),
+                process_id: prev_call.process_id,
+                window_title,
+                has_mic,
+                has_audio,
+                has_webrtc,
+                confidence,
+                kind: prev_call.kind,
+                role: prev_call.role,
+                started_at: prev_call.started_at.clone(),
+                started_at_utc: prev_call.started_at_utc.clone(),
+                local_timezone: prev_call.local_timezone.clone(),
+                duration_secs: prev_call.duration_secs,
+                private_context: audio_src.map(|src| src.private_context).unwrap_or(prev_call.private_context),
+                in_app_muted: None,
+                meeting_is_recorded: prev_call.meeting_is_recorded,
+                controls: prev_call.controls.clone(),
+                estimated_participants: prev_call.estimated_participants,
+                meeting_identifier,
+                scheduled_meeting,
+                quality: prev_call.quality.clone(),
+                echo_risk: prev_call.echo_risk,
+                call_started_system_time: prev_call.call_started_system_time,
+            }),
+            // Signals dropped - keep reporting the call until the end grace expires
+            CallPhase::Ending { .. } => Some(prev_call.clone()),
+            _ => None,
+        }
+    }
+
+    /// Look for a new call among the audio sources
+    fn detect_new_call(&mut self, sample: &Sample, now: SystemTime) -> Option<CallInfo> {
+        let mut candidate_call: Option<CallInfo> = None;
+
+        // A Zoom meeting window or a Slack huddle is a candidate even while the app plays nothing
+        let silent_meetings: Vec<AudioSource> = sample
+            .zoom_meeting
+            .as_ref()
+            .map(|meeting| ("Zoom", meeting.process_id, &meeting.window_title))
+            .into_iter()
+            .chain(
+                sample
+                    .slack_huddle
+                    .as_ref()
+                    .filter(|huddle| huddle.confirms(None))
+                    .map(|huddle| ("Slack", huddle.process_id, &huddle.window_title)),
+            )
+            .filter(|(_, process_id, _)| !sample.audio_sources.iter().any(|src| src.process_id == *process_id))
+            .map(|(app, process_id, window_title)| AudioSource {
+                name: app.to_string(),
+                process_id,
+                window_title: window_title.clone(),
+                detected_app: Some(app.to_string()),
+                private_context: false,
+                is_virtual_device: false,
+                classification: None,
+                session: None,
+            })
+            .collect();
+        let candidates = sample
+            .audio_sources
+            .iter()
+            .map(|src| (src, true))
+            .chain(silent_meetings.iter().map(|src| (src, false)));
+
+        for (audio_src, playing) in candidates {
+            // With the browser extension, the tab tells us the call app even when the
+            // browser window is showing a different tab
+            let tab = browser_tab(sample, &audio_src.name, None);
+            let detected = tab
+                .and_then(|tab| tab.call_app())
+                .or(audio_src.detected_app.as_deref())
+                // An installed web app (a Meet PWA) whose window title doesn't name it
+                .or_else(|| sample.command_line_apps.get(&audio_src.process_id).map(String::as_str));
+            let detected = match detected {
+                Some(app) => app.to_string(),
+                // Softphones in a SIP call and unknown apps on the force-track list are
+                // tracked under their process name
+                None if sample.sip_pids.contains(&audio_src.process_id)
+                    || self.engine.scoring().is_force_tracked(&audio_src.name, &audio_src.window_title) =>
+                {
+                    audio_src.name.clone()
+                }
+                None => continue,
+            };
+            // Apps outside `tracked_apps` are never scored, so no detection names them
+            if !self.engine.scoring().is_tracked(Some(&detected), &audio_src.name, &audio_src.window_title) {
+                continue;
+            }
+
+            let has_mic = if let Some(tab) = tab {
+                // Tab-level attribution from the browser extension
+                tab.capturing_audio
+            } else if crate::is_browser_process(&audio_src.name) {
+                // Without the extension, this browser when its pid is known, else ANY browser
+                self.mic_sources(sample).any(|mic_src| match mic_src.process_id {
+                    0 => crate::is_browser_process(&mic_src.name),
+                    process_id => process_id == audio_src.process_id,
+                })
+            } else {
+                // For native apps, require the same app
+                self.mic_sources(sample).any(|mic_src| is_same_app(mic_src, audio_src.process_id, &detected))
+            };
+            // A session left open after the last call (Discord, a forgotten tab) is not a mic in use
+            let mic_idle = has_mic && self.is_mic_idle(audio_src.process_id, now);
+            let has_mic = has_mic && !mic_idle;
+            let has_webrtc = sample.webrtc_pids.contains(&audio_src.process_id);
+            let has_sip = sample.sip_pids.contains(&audio_src.process_id);
+            let window_title = tab.map(|tab| tab.title.clone()).unwrap_or_else(|| audio_src.window_title.clone());
+            let meeting_identifier =
+                meeting_identifier(sample, tab, audio_src.process_id, &window_title, &detected, audio_src.private_context);
+            let scheduled_meeting = scheduled_meeting(sample, meeting_identifier.as_deref(), audio_src.private_context);
+
+            let signal = MultiSignal {
+                process_id: audio_src.process_id,
+                process_name: audio_src.name.clone(),
+                window_title: window_title.clone(),
+                has_mic_active: has_mic,
+                mic_peak_level: mic_peak_level(sample, audio_src.process_id, has_mic),
+                mic_idle,
+                process_load: sample.process_load.get(&audio_src.process_id).copied(),
+                has_audio_output: playing,
+                // An open session that stays silent is not the call's audio
+                audio_peak_level: match sample.render_peaks.get(&audio_src.process_id) {
+                    Some(peak) if playing => *peak,
+                    _ if playing => 0.1, // Simplified without a meter
+                    _ => 0.0,
+                },
+                is_virtual_device: audio_src.is_virtual_device,
+                has_webrtc_connection: has_webrtc,
+                webrtc_started_at: None,
+                has_peer_connection: has_peer_connection(sample, &audio_src.name),
+                has_sip_media: has_sip,
+                client_in_call: client_in_call(sample, &detected),
+                audio_active_secs: sample.audio_active_secs.get(&audio_src.process_id).copied(),
+                in_scheduled_meeting: scheduled_meeting.is_some(),
+                contributions: contributions_for(sample, audio_src.process_id, &[&audio_src.name, &detected]),
+                detected_app: Some(detected.clone()),
+                tab_url: tab.map(|tab| tab.url.clone()),
+                duration: Duration::from_secs(0), // New call
+            };
+
+            // Filters out voice notes, YouTube, and other false positives
+            let detection = self.engine.detect_call(&signal);
+            let is_call = detection.is_call;
+            let confidence = detection.confidence;
+            let kind = detection.kind;
+            self.detections.push(detection);
+
+            if is_call {
+                candidate_call = Some(CallInfo {
+                    app: detected,
+                    process_id: audio_src.process_id,
+                    window_title,
+                    has_mic,
+                    has_audio: playing,
+                    has_webrtc,
+                    confidence,
+                    kind,
+                    role: CallRole::Unknown,
+                    started_at: String::new(),
+                    started_at_utc: String::new(),
+                    local_timezone: String::new(),
+                    duration_secs: 0,
+                    private_context: audio_src.private_context,
+                    in_app_muted: None,
+                    meeting_is_recorded: None,
+                    controls: None,
+                    estimated_participants: None,
+                    meeting_identifier,
+                    scheduled_meeting,
+                    quality: None,
+                    echo_risk: false,
+                    call_started_system_time: now,
+                });
+                break;
+            }
+        }
+
+        let candidate = candidate_call.as_ref().map(|call| CallCandidate {
+            process_id: call.process_id,
+            confidence: call.confidence,
+        });
+
+        match self.engine.update_phase(candidate, now) {
+            // Reported once confirmed, dated from when it was first suspected
+            CallPhase::Active { process_id, since } => candidate_call
+                .filter(|call| call.process_id == process_id)
+                .map(|mut call| {
+                    call.set_started(since);
+                    call
+                }),
+            _ => None,
+        }
+    }
+
+    /// Track how long each metered recording has read silent
+    fn observe_mic_silence(&mut self, sample: &Sample, now: SystemTime) {
+        self.mic_silent_since.retain(|process_id, _| sample.mic_peaks.contains_key(process_id));
+        for (&process_id, &peak) in &sample.mic_peaks {
+            if peak > in_app_mute::SILENCE_FLOOR {
+                self.mic_silent_since.remove(&process_id);
+            } else {
+                self.mic_silent_since.entry(process_id).or_insert(now);
+            }
+        }
+    }
+
+    /// Whether `process_id` has held the mic silent for longer than `mic_idle_secs`
+    fn is_mic_idle(&self, process_id: u32, now: SystemTime) -> bool {
+        let idle_secs = self.engine.scoring().mic_idle_secs;
+        idle_secs > 0.0
+            && self.mic_silent_since.get(&process_id).is_some_and(|since| {
+                now.duration_since(*since).unwrap_or(Duration::ZERO).as_secs_f32() >= idle_secs
+            })
+    }
+
+    /// Mic users, minus processes on the ignore list
+    fn mic_sources<'a>(&'a self, sample: &'a Sample) -> impl Iterator<Item = &'a AudioSource> + 'a {
+        sample
+            .mic_sources
+            .iter()
+            .filter(|src| !self.engine.scoring().is_ignored(&src.name, &src.window_title))
+    }
+}
+
+/// Provider evidence about a candidate, by process id or any of its names
+fn contributions_for(sample: &Sample, process_id: u32, names: &[&str]) -> Vec<SignalContribution> {
+    sample.contributions.iter().filter(|c| c.applies_to(process_id, names)).cloned().collect()
+}
+
+/// Call state reported by the app itself: Teams' API, a Zoom meeting window, or Slack's
+/// huddle traces
+fn client_in_call(sample: &Sample, app: &str) -> Option<bool> {
+    match app {
+        "Microsoft Teams" => sample.teams_in_call,
+        // No meeting window proves nothing (Zoom Phone, window closed to the tray)
+        "Zoom" => sample.zoom_meeting.as_ref().map(|_| true),
+        // Neither does a Slack without huddle traces
+        "Slack" => sample
+            .slack_huddle
+            .as_ref()
+            .filter(|huddle| huddle.confirms(sample.audio_active_secs.get(&huddle.process_id).copied()))
+            .map(|_| true),
+        _ => None,
+    }
+}
+
+/// Whether a mic source belongs to the app `process_id` runs: the same root process when
+/// both are known, else its detected app, or its process name for force-tracked apps
+/// that have no detected app
+pub fn is_same_app(src: &AudioSource, process_id: u32, app: &str) -> bool {
+    if src.process_id != 0 && process_id != 0 {
+        return src.process_id == process_id;
+    }
+    match &src.detected_app {
+        Some(detected) => detected == app,
+        None => src.name.eq_ignore_ascii_case(app),
+    }
+}
+
+/// Input peak of what `process_id` records, when it holds the mic and a meter reads it
+fn mic_peak_level(sample: &Sample, process_id: u32, has_mic: bool) -> Option<f32> {
+    sample.mic_peaks.get(&process_id).copied().filter(|_| has_mic)
+}
+
+/// Whether a live PeerConnection counts for this process
+/// The evidence can't be tied to a process, so any live connection counts for every
+/// browser process and never for anything else.
+fn has_peer_connection(sample: &Sample, process_name: &str) -> bool {
+    sample.browser_peer_connection && crate::is_browser_process(process_name)
+}
+
+// Nothing is kept for calls in private browser windows
+fn meeting_identifier(
+    sample: &Sample,
+    tab: Option<&BrowserTab>,
+    process_id: u32,
+    window_title: &str,
+    app: &str,
+    private_context: bool,
+) -> Option<String> {
+    if private_context {
+        return None;
+    }
+    meeting_id::identify(
+        tab.map(|tab| tab.url.as_str()),
+        sample.command_line_meetings.get(&process_id).map(String::as_str),
+        window_title,
+        app,
+    )
+}
+
+// A call with a different meeting link than every event under way is not one of them
+fn scheduled_meeting(sample: &Sample, meeting_identifier: Option<&str>, private_context: bool) -> Option<ScheduledMeeting> {
+    if private_context {
+        return None;
+    }
+    meeting_id::scheduled_meeting(meeting_identifier, &sample.scheduled_meetings).cloned()
+}
+
+/// The extension-reported tab behind a browser process: the call tab for `app` (or any
+/// call tab), else the first audible tab. None when the extension reports nothing for it.
+fn browser_tab<'a>(sample: &'a Sample, process_name: &str, app: Option<&str>) -> Option<&'a BrowserTab> {
+    let tabs: Vec<&BrowserTab> = sample
+        .browser_tabs
+        .iter()
+        .filter(|tab| tab.belongs_to(process_name))
+        .collect();
+
+    tabs.iter()
+        .find(|tab| match (tab.call_app(), app) {
+            (Some(tab_app), Some(app)) => tab_app == app,
+            (Some(_), None) => tab.audible || tab.capturing_audio,
+            (None, _) => false,
+        })
+        .or_else(|| tabs.iter().find(|tab| tab.audible))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::EventType;
+
+    fn source(name: &str, process_id: u32, detected_app: &str) -> AudioSource {
+        AudioSource {
+            name: name.to_string(),
+            process_id,
+            window_title: String::new(),
+            detected_app: Some(detected_app.to_string()),
+            private_context: false,
+            is_virtual_device: false,
+            classification: None,
+            session: None,
+        }
+    }
+
+    /// Call lifecycle events only, ignoring source added/removed
+    fn call_events(events: &[MonitorEvent]) -> Vec<EventType> {
+        events
+            .iter()
+            .map(|event| event.event_type())
+            .filter(|event_type| matches!(event_type, EventType::CallStarted | EventType::CallEnded))
+            .collect()
+    }
+
+    #[test]
+    fn test_call_starts_after_confirmation_and_ends_after_grace() {
+        let mut tracker = CallTracker::new(CorrelationEngine::new());
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        let in_call = Sample {
+            audio_sources: vec![source("Zoom.exe", 42, "Zoom")],
+            mic_sources: vec![source("Zoom.exe", 0, "Zoom")],
+            webrtc_pids: HashSet::new(),
+            sip_pids: HashSet::new(),
+            teams_in_call: None,
+            zoom_meeting: None,
+            slack_huddle: None,
+            browser_tabs: Vec::new(),
+            browser_peer_connection: false,
+            levels: VolumeLevels::default(),
+            capture_peaks: BTreeMap::new(),
+            mic_peaks: BTreeMap::new(),
+            audio_active_secs: BTreeMap::new(),
+            render_peaks: BTreeMap::new(),
+            process_load: BTreeMap::new(),
+            media_peers: BTreeMap::new(),
+            recording: None,
+            call_controls: None,
+            command_line_meetings: BTreeMap::new(),
+            command_line_apps: BTreeMap::new(),
+            scheduled_meetings: Vec::new(),
+            call_quality: None,
+            output_devices: BTreeMap::new(),
+            audio_routing: None,
+            contributions: Vec::new(),
+            external_calls: Vec::new(),
+        };
+
+        assert!(call_events(&tracker.update(&in_call, at(0))).is_empty());
+        assert!(call_events(&tracker.update(&in_call, at(500))).is_empty());
+        assert_eq!(call_events(&tracker.update(&in_call, at(1000))), vec![EventType::CallStarted]);
+        assert_eq!(tracker.state().active_call.as_ref().map(|call| call.process_id), Some(42));
+
+        let silent = Sample::default();
+        assert!(call_events(&tracker.update(&silent, at(1500))).is_empty());
+        assert!(call_events(&tracker.update(&silent, at(3000))).is_empty());
+        let ended = tracker.update(&silent, at(3500));
+        assert_eq!(call_events(&ended), vec![EventType::CallEnded]);
+        assert!(ended.iter().any(|event| matches!(event, MonitorEvent::CallEnded(payload) if payload.duration_secs == 3)));
+        assert!(tracker.state().active_call.is_none());
+    }
+
+    #[test]
+    fn test_process_lists_override_app_detection() {
+        let scoring: crate::correlation_engine::ScoringConfig = serde_json::from_str(
+            r#"{ "ignore_processes": ["zoom*"], "force_track_processes": ["3CXPhone*"] }"#,
+        )
+        .unwrap();
+        let mut tracker = CallTracker::new(CorrelationEngine::new().with_scoring(scoring));
+        let at = |ms: u64| SystemTime::UNIX_EPOCH + Duration::from_millis(ms);
+
+        let softphone = AudioSource { detected_app: None, ..source("3CXPhone.exe", 7, "") };
+        let sample = Sample {
+            audio_sources: vec![source("zoom.us", 42, "Zoom"), softphone.clone()],
+            mic_sources: vec![source("zoom.us", 0, "Zoom"), AudioSource { process_id: 0, ..softphone }],
+            ..Default::default()
+        };
+
+        for ms in [0, 500, 1000] {
+            tracker.update(&sample, at(ms));
+        }
+        let call = tracker.state().active_call.as_ref().unwrap();
+        assert_eq!((call.process_id, call.app.as_str(), call.has_mic), (7, "3CXPhone.exe", true));
+    }
+
+    #[test]
+    fn test_tracked_apps_restrict_calls_and_aggregate_other_sources() {
+        let scoring: ScoringConfig = serde_json::from_str(r#"{ "tracked_apps": ["Zoom", "Microsoft Teams"] }"#).unwrap();
+        let mut tracker = CallTracker::new(CorrelationEngine::new().with_scoring(scoring));
+        let at = |ms: u64| SystemTime::UNIX_EPOCH + Duration::from_millis(ms);
+
+        let spotify = AudioSource { detected_app: None, ..source("Spotify.exe", 5, "") };
+        let huddle = Sample {
+            audio_sources: vec![source("slack", 11, "Slack"), spotify.clone()],
+            mic_sources: vec![source("slack", 0, "Slack")],
+            ..Default::default()
+        };
+        for ms in [0, 500, 1000] {
+            tracker.update(&huddle, at(ms));
+        }
+        let state = tracker.state();
+        assert!(state.active_call.is_none() && state.other_audio_sources.is_empty());
+        assert!(tracker.detections().is_empty());
+        let recorded = huddle.tracked_only(tracker.scoring());
+        assert!(recorded.audio_sources.is_empty() && recorded.mic_sources.is_empty());
+        let tracked = state.tracked_apps.as_ref().unwrap();
+        assert_eq!(
+            tracked.untracked_audio_sources,
+            BTreeMap::from([(SourceClass::Media, 1), (SourceClass::CallCandidate, 1)])
+        );
+
+        let zoom = Sample {
+            audio_sources: vec![source("Zoom.exe", 42, "Zoom"), source("Teams.exe", 43, "Microsoft Teams"), spotify],
+            mic_sources: vec![source("Zoom.exe", 0, "Zoom")],
+            ..Default::default()
+        };
+        for ms in [1500, 2000, 2500] {
+            tracker.update(&zoom, at(ms));
+        }
+        let state = tracker.state();
+        assert_eq!(state.active_call.as_ref().map(|call| call.process_id), Some(42));
+        assert_eq!(state.other_audio_sources.iter().map(|src| src.process_id).collect::<Vec<_>>(), vec![43]);
+    }
+
+    #[test]
+    fn test_mic_joins_audio_by_process() {
+        let mut tracker = CallTracker::new(CorrelationEngine::new());
+        let at = |ms: u64| SystemTime::UNIX_EPOCH + Duration::from_millis(ms);
+
+        // New Teams captures through its WebView2 helper, rooted at ms-teams.exe
+        let webview = AudioSource { detected_app: None, ..source("msedgewebview2.exe", 43, "") };
+        let sample = Sample {
+            audio_sources: vec![source("ms-teams.exe", 43, "Microsoft Teams"), source("Zoom.exe", 42, "Zoom")],
+            mic_sources: vec![webview],
+            ..Default::default()
+        };
+        for ms in [0, 500, 1000] {
+            tracker.update(&sample, at(ms));
+        }
+        let call = tracker.state().active_call.as_ref().unwrap();
+        assert_eq!((call.process_id, call.has_mic), (43, true));
+
+        // A second Zoom process holding the mic is not the one playing
+        let other_zoom = source("Zoom.exe", 0, "Zoom");
+        assert!(is_same_app(&other_zoom, 42, "Zoom"));
+        assert!(!is_same_app(&AudioSource { process_id: 77, ..other_zoom }, 42, "Zoom"));
+    }
+
+    #[test]
+    fn test_mic_held_silent_turns_idle() {
+        let scoring: ScoringConfig = serde_json::from_str(r#"{ "mic_idle_secs": 60 }"#).unwrap();
+        let mut tracker = CallTracker::new(CorrelationEngine::new().with_scoring(scoring));
+        let at = |secs: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+
+        // Discord keeps its capture session after the call, and a clip plays in a server
+        let stuck = Sample {
+            audio_sources: vec![source("Discord.exe", 12, "Discord")],
+            mic_sources: vec![source("Discord.exe", 12, "Discord")],
+            mic_peaks: BTreeMap::from([(12, 0.0)]),
+            ..Default::default()
+        };
+        let mic = |tracker: &CallTracker| {
+            let signals = &tracker.detections()[0].signals;
+            (signals.has_mic_active, signals.mic_idle)
+        };
+
+        tracker.update(&stuck, at(0));
+        assert_eq!(mic(&tracker), (true, false));
+        tracker.update(&stuck, at(61));
+        assert_eq!(mic(&tracker), (false, true));
+
+        // Speaking again makes it a live mic
+        tracker.update(&Sample { mic_peaks: BTreeMap::from([(12, 0.3)]), ..stuck.clone() }, at(62));
+        assert_eq!(mic(&tracker), (true, false));
+    }
+
+    #[test]
+    fn test_silent_render_meter_withholds_audio_credit() {
+        let sample = Sample {
+            audio_sources: vec![source("zoom.us", 42, "Zoom")],
+            mic_sources: vec![source("zoom.us", 0, "Zoom")],
+            ..Default::default()
+        };
+        let confidence = |sample: &Sample| {
+            let mut tracker = CallTracker::new(CorrelationEngine::new());
+            tracker.update(sample, SystemTime::UNIX_EPOCH);
+            tracker.detections()[0].confidence
+        };
+
+        // Zoom's session is open but the meter heard nothing from its process tree
+        let silent = Sample { render_peaks: BTreeMap::from([(42, 0.0)]), ..sample.clone() };
+        let speaking = Sample { render_peaks: BTreeMap::from([(42, 0.4)]), ..sample.clone() };
+        assert!(confidence(&silent) < confidence(&sample));
+        assert_eq!(confidence(&speaking), confidence(&sample));
+    }
+
+    #[test]
+    fn test_zoom_meeting_window_starts_a_silent_call() {
+        let mut tracker = CallTracker::new(CorrelationEngine::new());
+        let at = |ms: u64| SystemTime::UNIX_EPOCH + Duration::from_millis(ms);
+
+        // Everyone muted: no audio session, only the meeting window
+        let sample = Sample {
+            zoom_meeting: Some(ZoomMeeting { process_id: 9, window_title: "Zoom Meeting".to_string() }),
+            ..Default::default()
+        };
+        for ms in [0, 500, 1000] {
+            tracker.update(&sample, at(ms));
+        }
+        let call = tracker.state().active_call.as_ref().unwrap();
+        assert_eq!((call.process_id, call.app.as_str(), call.has_audio), (9, "Zoom", false));
+
+        tracker.update(&Sample::default(), at(1500));
+        assert!(tracker.state().active_call.is_some(), "ends only after the grace period");
+    }
+
+    #[test]
+    fn test_slack_huddle_audio_only_is_a_huddle() {
+        use crate::correlation_engine::CallKind;
+        use crate::slack_huddle::HuddleSignal;
+
+        let mut tracker = CallTracker::new(CorrelationEngine::new());
+        let at = |ms: u64| SystemTime::UNIX_EPOCH + Duration::from_millis(ms);
+
+        // Listening with the mic off: Slack's huddle session plays, no huddle window
+        let huddle = SlackHuddle { process_id: 7, window_title: String::new(), signals: vec![HuddleSignal::AudioThreads] };
+        let sample = Sample {
+            audio_sources: vec![source("slack", 7, "Slack")],
+            slack_huddle: Some(huddle),
+            audio_active_secs: BTreeMap::from([(7, 30.0)]),
+            ..Default::default()
+        };
+        for ms in [0, 500, 1000] {
+            tracker.update(&sample, at(ms));
+        }
+        let call = tracker.state().active_call.as_ref().unwrap();
+        assert_eq!((call.app.as_str(), call.kind), ("Slack", CallKind::Huddle));
+    }
+}
@@ -0,0 +1,100 @@
+// Data quality the monitor can reach on this machine
+// Missing grants do not stop the monitor, they quietly lower what it sees: without
+// Accessibility on macOS window titles fall back to process names, and without a sound
+// server nothing is attributed to apps at all. The capability level makes that visible
+// in the `health` report (and is logged when it changes):
+//   full                 window titles and per-app audio attribution
+//   titles-unavailable   titles fall back to process names (meeting titles, tab names lost)
+//   process-only         the audio backend is not answering; processes and sockets only
+
+use crate::error::ErrorKind;
+use crate::supervisor::BackendError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CapabilityLevel {
+    #[default]
+    Full,
+    TitlesUnavailable,
+    ProcessOnly,
+}
+
+impl CapabilityLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CapabilityLevel::Full => "full",
+            CapabilityLevel::TitlesUnavailable => "titles-unavailable",
+            CapabilityLevel::ProcessOnly => "process-only",
+        }
+    }
+}
+
+/// The level and why it is below full
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capability {
+    pub level: CapabilityLevel,
+    pub reason: Option<String>,
+}
+
+/// Assess from the audio backend's last error and the platform's title access
+pub fn assess(backend_errors: &BTreeMap<&'static str, BackendError>) -> Capability {
+    let audio_error = backend_errors
+        .get("audio")
+        .filter(|error| matches!(error.kind, ErrorKind::BackendUnavailable | ErrorKind::PermissionDenied));
+    if let Some(error) = audio_error {
+        return Capability { level: CapabilityLevel::ProcessOnly, reason: Some(error.message.clone()) };
+    }
+
+    match titles_unavailable() {
+        Some(reason) => Capability { level: CapabilityLevel::TitlesUnavailable, reason: Some(reason.to_string()) },
+        None => Capability::default(),
+    }
+}
+
+/// Why window titles cannot be read, if they cannot
+#[cfg(target_os = "macos")]
+fn titles_unavailable() -> Option<&'static str> {
+    if !crate::platform::macos::accessibility_trusted() {
+        Some("Accessibility access not granted (check-permissions --prompt asks for it)")
+    } else if crate::subprocess::is_disabled() {
+        Some("window titles need osascript, disabled by --no-subprocess")
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn titles_unavailable() -> Option<&'static str> {
+    (std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none())
+        .then_some("no DISPLAY or WAYLAND_DISPLAY: not running in the desktop session")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn titles_unavailable() -> Option<&'static str> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_sound_server_is_process_only() {
+        let mut backend_errors = BTreeMap::new();
+        backend_errors.insert(
+            "audio",
+            BackendError { kind: ErrorKind::Timeout, message: "PulseAudio sink info timed out".to_string() },
+        );
+        assert_ne!(assess(&backend_errors).level, CapabilityLevel::ProcessOnly, "a slow poll is not a lost backend");
+
+        backend_errors.insert(
+            "audio",
+            BackendError { kind: ErrorKind::BackendUnavailable, message: "PulseAudio is not available".to_string() },
+        );
+        let capability = assess(&backend_errors);
+        assert_eq!(capability.level, CapabilityLevel::ProcessOnly);
+        assert_eq!(capability.reason.as_deref(), Some("PulseAudio is not available"));
+    }
+}
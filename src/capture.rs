@@ -0,0 +1,614 @@
+// Loopback capture tap (`capture` feature)
+// `start_loopback_capture(process_id)` streams what one app plays, downmixed to mono:
+//   - Windows: process loopback of the app's process tree (Windows 10 2004+, see
+//     wasapi_audio.rs), else WASAPI loopback of the default render endpoint
+//   - Linux: the app's PipeWire/PulseAudio playback stream (`parec --monitor-stream`),
+//     else the monitor source of the default sink
+//   - macOS: ScreenCaptureKit audio of the app (macOS 13+, Screen Recording permission),
+//     else of the whole display
+// The fallbacks carry everything the machine plays; `LoopbackStream::scope` tells which
+// one a stream got. Capturing needs explicit consent (`--allow-audio-capture`), and frames
+// only live in memory until the consumer takes them; nothing is written to disk. A consumer
+// that falls behind loses the oldest frames rather than growing the queue without bound.
+
+use crate::error::ValidatorError;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Rate of the per-process streams, enough for speech and energy markers
+const SAMPLE_RATE: u32 = 16_000;
+/// How long the platform may take to open a stream
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+/// Frames kept for a consumer that falls behind (about 5 s of 10 ms packets)
+const QUEUE_FRAMES: usize = 512;
+
+static CONSENT: AtomicBool = AtomicBool::new(false);
+
+/// Allow audio capture for the rest of the process lifetime (`--allow-audio-capture`)
+pub fn grant_consent() {
+    CONSENT.store(true, Ordering::SeqCst);
+}
+
+pub fn has_consent() -> bool {
+    CONSENT.load(Ordering::SeqCst)
+}
+
+/// Mono samples of a render stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFrame {
+    pub sample_rate: u32,
+    /// -1.0 to 1.0
+    pub samples: Vec<f32>,
+    /// When the first sample was captured
+    pub captured_at: SystemTime,
+}
+
+/// What a stream carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureScope {
+    /// Only the requested app (and its helper processes)
+    Process,
+    /// Everything the default output plays
+    Output,
+}
+
+/// A running loopback capture; stops when dropped
+pub struct LoopbackStream {
+    /// Shared with the sink only so that it can drop the oldest frame of a full queue
+    frames: Arc<Mutex<Receiver<AudioFrame>>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+    stop: Arc<AtomicBool>,
+    scope: CaptureScope,
+}
+
+/// Start capturing what `process_id` plays
+pub fn start_loopback_capture(process_id: u32) -> Result<LoopbackStream, ValidatorError> {
+    if !has_consent() {
+        return Err(ValidatorError::PermissionDenied("audio capture needs --allow-audio-capture".to_string()));
+    }
+    let (tx, rx) = mpsc::sync_channel(QUEUE_FRAMES);
+    let rx = Arc::new(Mutex::new(rx));
+    let waker = Arc::new(Mutex::new(None));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let sink = FrameSink { frames: tx, queue: Arc::downgrade(&rx), waker: Arc::clone(&waker) };
+    let thread_stop = Arc::clone(&stop);
+    thread::spawn(move || {
+        if let Err(e) = platform::capture(process_id, &sink, &thread_stop, &ready_tx) {
+            let _ = ready_tx.send(Err(e));
+        }
+    });
+    // The capture thread reports whether a stream opened
+    match ready_rx.recv_timeout(START_TIMEOUT) {
+        Ok(Ok(scope)) => Ok(LoopbackStream { frames: rx, waker, stop, scope }),
+        Ok(Err(e)) => Err(e),
+        Err(_) => {
+            stop.store(true, Ordering::Relaxed);
+            Err(ValidatorError::Timeout("loopback capture start".to_string()))
+        }
+    }
+}
+
+impl LoopbackStream {
+    pub fn scope(&self) -> CaptureScope {
+        self.scope
+    }
+
+    /// Frames captured since the last call, for consumers that poll from a loop
+    pub fn frames(&self) -> Vec<AudioFrame> {
+        self.receiver().try_iter().collect()
+    }
+
+    fn receiver(&self) -> std::sync::MutexGuard<'_, Receiver<AudioFrame>> {
+        self.frames.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Stream for LoopbackStream {
+    type Item = AudioFrame;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<AudioFrame>> {
+        match self.receiver().try_recv() {
+            Ok(frame) => return Poll::Ready(Some(frame)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => {}
+        }
+        *self.waker.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(cx.waker().clone());
+        // A frame sent before the waker was stored would not wake this task
+        match self.receiver().try_recv() {
+            Ok(frame) => Poll::Ready(Some(frame)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for LoopbackStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Sending half of a stream, used by the capture thread
+struct FrameSink {
+    frames: SyncSender<AudioFrame>,
+    /// The stream's end of `frames`, gone once the stream was dropped
+    queue: Weak<Mutex<Receiver<AudioFrame>>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl FrameSink {
+    /// False once the stream was dropped
+    fn send(&self, mut frame: AudioFrame) -> bool {
+        let is_sent = loop {
+            match self.frames.try_send(frame) {
+                Ok(()) => break true,
+                Err(TrySendError::Disconnected(_)) => break false,
+                Err(TrySendError::Full(rejected)) => {
+                    // Make room by dropping the oldest frame
+                    let Some(queue) = self.queue.upgrade() else { break false };
+                    let _ = queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).try_recv();
+                    frame = rejected;
+                }
+            }
+        };
+        if let Some(waker) = self.waker.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() {
+            waker.wake();
+        }
+        is_sent
+    }
+}
+
+type Ready = Sender<Result<CaptureScope, ValidatorError>>;
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{AudioFrame, CaptureScope, FrameSink, Ready, SAMPLE_RATE};
+    use crate::error::ValidatorError;
+    use crate::wasapi_audio::wasapi;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+    use windows::Win32::Media::Audio::*;
+    use windows::Win32::System::Com::*;
+
+    /// Loopback buffer length, in 100 ns units
+    const BUFFER_DURATION: i64 = 2_000_000;
+
+    pub fn capture(process_id: u32, sink: &FrameSink, stop: &AtomicBool, ready: &Ready) -> Result<(), ValidatorError> {
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let result = run(process_id, sink, stop, ready);
+            CoUninitialize();
+            result
+        }
+    }
+
+    unsafe fn run(process_id: u32, sink: &FrameSink, stop: &AtomicBool, ready: &Ready) -> Result<(), ValidatorError> {
+        // Windows 10 2004 and later capture the app's process tree alone
+        let (client, channels, sample_rate, scope) = match wasapi::open_process_loopback(process_id, SAMPLE_RATE) {
+            Ok(client) => (client, 1, SAMPLE_RATE, CaptureScope::Process),
+            Err(_) => {
+                let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+                let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+                let client: IAudioClient = device.Activate(CLSCTX_ALL, None)?;
+                let format = client.GetMixFormat()?;
+                let (channels, sample_rate, bits) =
+                    ((*format).nChannels as usize, (*format).nSamplesPerSec, (*format).wBitsPerSample);
+                // The shared-mode mix format is 32-bit float on every current Windows
+                if bits != 32 {
+                    CoTaskMemFree(Some(format as *const _));
+                    return Err(ValidatorError::Platform(format!("unsupported mix format: {} bits", bits)));
+                }
+                let initialized = client.Initialize(
+                    AUDCLNT_SHAREMODE_SHARED,
+                    AUDCLNT_STREAMFLAGS_LOOPBACK,
+                    BUFFER_DURATION,
+                    0,
+                    format,
+                    None,
+                );
+                CoTaskMemFree(Some(format as *const _));
+                initialized?;
+                (client, channels, sample_rate, CaptureScope::Output)
+            }
+        };
+
+        let capture: IAudioCaptureClient = client.GetService()?;
+        client.Start()?;
+        let _ = ready.send(Ok(scope));
+
+        'capture: while !stop.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(20));
+            while capture.GetNextPacketSize().unwrap_or(0) > 0 {
+                let mut data = std::ptr::null_mut();
+                let mut count = 0u32;
+                let mut flags = 0u32;
+                if capture.GetBuffer(&mut data, &mut count, &mut flags, None, None).is_err() {
+                    break;
+                }
+                let samples = if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 {
+                    vec![0.0; count as usize]
+                } else {
+                    let interleaved = std::slice::from_raw_parts(data as *const f32, count as usize * channels);
+                    interleaved.chunks(channels).map(|frame| frame.iter().sum::<f32>() / channels as f32).collect()
+                };
+                let _ = capture.ReleaseBuffer(count);
+                if !sink.send(AudioFrame { sample_rate, samples, captured_at: SystemTime::now() }) {
+                    break 'capture;
+                }
+            }
+        }
+        let _ = client.Stop();
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{AudioFrame, CaptureScope, FrameSink, Ready, SAMPLE_RATE};
+    use crate::error::ValidatorError;
+    use crate::process_tree::ProcessTree;
+    use libpulse_binding::sample::{Format, Spec};
+    use libpulse_binding::stream::Direction;
+    use libpulse_simple_binding::Simple;
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::SystemTime;
+
+    /// Samples per read: 50 ms
+    const CHUNK: usize = 800;
+
+    pub fn capture(process_id: u32, sink: &FrameSink, stop: &AtomicBool, ready: &Ready) -> Result<(), ValidatorError> {
+        match playback_stream(process_id) {
+            Some(index) => capture_stream(index, sink, stop, ready),
+            None => capture_monitor(sink, stop, ready),
+        }
+    }
+
+    /// The app's own playback stream, recorded by parec
+    fn capture_stream(index: u32, sink: &FrameSink, stop: &AtomicBool, ready: &Ready) -> Result<(), ValidatorError> {
+        let mut child = crate::subprocess::spawn(
+            Command::new("parec")
+                .arg(format!("--monitor-stream={}", index))
+                .args(["--format=float32le", "--channels=1", "--raw", "--latency-msec=50"])
+                .arg(format!("--rate={}", SAMPLE_RATE))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null()),
+        )
+        .map_err(|e| ValidatorError::spawn("parec", e))?;
+        let Some(mut stdout) = child.stdout.take() else {
+            let _ = child.kill();
+            return Err(ValidatorError::Platform("parec has no output".to_string()));
+        };
+        let _ = ready.send(Ok(CaptureScope::Process));
+
+        let mut buffer = vec![0u8; CHUNK * 4];
+        while !stop.load(Ordering::Relaxed) {
+            let captured_at = SystemTime::now();
+            // parec exits when the stream goes away
+            if stdout.read_exact(&mut buffer).is_err() || !sink.send(frame(&buffer, captured_at)) {
+                break;
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+        Ok(())
+    }
+
+    /// Everything the default sink plays
+    fn capture_monitor(sink: &FrameSink, stop: &AtomicBool, ready: &Ready) -> Result<(), ValidatorError> {
+        let spec = Spec { format: Format::F32le, channels: 1, rate: SAMPLE_RATE };
+        let stream = Simple::new(
+            None,
+            "rust-audio-validator",
+            Direction::Record,
+            Some("@DEFAULT_MONITOR@"),
+            "loopback tap",
+            &spec,
+            None,
+            None,
+        )
+        .map_err(|e| ValidatorError::BackendUnavailable(format!("PulseAudio monitor source ({})", e)))?;
+        let _ = ready.send(Ok(CaptureScope::Output));
+
+        let mut buffer = vec![0u8; CHUNK * 4];
+        while !stop.load(Ordering::Relaxed) {
+            let captured_at = SystemTime::now();
+            if stream.read(&mut buffer).is_err() || !sink.send(frame(&buffer, captured_at)) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn frame(buffer: &[u8], captured_at: SystemTime) -> AudioFrame {
+        let samples = buffer.chunks_exact(4).map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect();
+        AudioFrame { sample_rate: SAMPLE_RATE, samples, captured_at }
+    }
+
+    /// Index of a playback stream (sink input) owned by `process_id` or one of its helpers
+    fn playback_stream(process_id: u32) -> Option<u32> {
+        let output = crate::subprocess::output(Command::new("pactl").env("LC_ALL", "C").args(["list", "sink-inputs"])).ok()?;
+        let mut process_tree = ProcessTree::snapshot();
+        parse_sink_inputs(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .find(|(_, pid)| process_tree.root(*pid) == process_id)
+            .map(|(index, _)| index)
+    }
+
+    // "Sink Input #42" blocks with `application.process.id = "4242"`
+    pub(super) fn parse_sink_inputs(text: &str) -> Vec<(u32, u32)> {
+        text.split("Sink Input #")
+            .skip(1)
+            .filter_map(|block| {
+                let index = block.lines().next()?.trim().parse().ok()?;
+                let pid = block.lines().find_map(|line| {
+                    let value = line.trim().strip_prefix("application.process.id = ")?;
+                    value.trim_matches('"').parse().ok()
+                })?;
+                Some((index, pid))
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{AudioFrame, CaptureScope, FrameSink, Ready, SAMPLE_RATE};
+    use crate::error::ValidatorError;
+    use block::ConcreteBlock;
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Class, Object, Protocol, Sel, BOOL, NO, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::{c_char, c_void};
+    use std::ptr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+    use std::sync::Once;
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    /// SCStreamOutputTypeAudio
+    const OUTPUT_TYPE_AUDIO: isize = 1;
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[link(name = "ScreenCaptureKit", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "CoreMedia", kind = "framework")]
+    extern "C" {
+        fn CMSampleBufferGetDataBuffer(buffer: *mut c_void) -> *mut c_void;
+        fn CMBlockBufferGetDataPointer(
+            buffer: *mut c_void,
+            offset: usize,
+            length_at_offset: *mut usize,
+            total_length: *mut usize,
+            data: *mut *mut c_char,
+        ) -> i32;
+    }
+
+    pub fn capture(process_id: u32, sink: &FrameSink, stop: &AtomicBool, ready: &Ready) -> Result<(), ValidatorError> {
+        if Class::get("SCShareableContent").is_none() {
+            return Err(ValidatorError::BackendUnavailable("ScreenCaptureKit (macOS 13 or later)".to_string()));
+        }
+        unsafe {
+            let content = shareable_content()?;
+            let displays: *mut Object = msg_send![content, displays];
+            let display = first(displays).ok_or_else(|| ValidatorError::NotFound("display".to_string()))?;
+            let empty: *mut Object = msg_send![class!(NSArray), array];
+
+            // Audio comes with a display filter; including only the app keeps everyone else out
+            let apps: *mut Object = msg_send![content, applications];
+            let app = objects(apps).into_iter().find(|&app| {
+                let pid: i32 = msg_send![app, processID];
+                pid as u32 == process_id
+            });
+            let filter: *mut Object = msg_send![class!(SCContentFilter), alloc];
+            let (filter, scope): (*mut Object, _) = match app {
+                Some(app) => {
+                    let included: *mut Object = msg_send![class!(NSArray), arrayWithObject: app];
+                    (msg_send![filter, initWithDisplay: display includingApplications: included exceptingWindows: empty], CaptureScope::Process)
+                }
+                None => (msg_send![filter, initWithDisplay: display excludingWindows: empty], CaptureScope::Output),
+            };
+
+            let config: *mut Object = msg_send![class!(SCStreamConfiguration), new];
+            let _: () = msg_send![config, setCapturesAudio: YES];
+            let _: () = msg_send![config, setExcludesCurrentProcessAudio: YES];
+            let _: () = msg_send![config, setSampleRate: SAMPLE_RATE as isize];
+            let _: () = msg_send![config, setChannelCount: 1isize];
+            // Video cannot be turned off; keep its frames tiny
+            let _: () = msg_send![config, setWidth: 2usize];
+            let _: () = msg_send![config, setHeight: 2usize];
+
+            let stream: *mut Object = msg_send![class!(SCStream), alloc];
+            let stream: *mut Object =
+                msg_send![stream, initWithFilter: filter configuration: config delegate: ptr::null_mut::<Object>()];
+            let _: () = msg_send![filter, release];
+            let _: () = msg_send![config, release];
+            let _: () = msg_send![content, release];
+
+            let output: *mut Object = msg_send![output_class(), new];
+            (*output).set_ivar::<usize>("sink", sink as *const FrameSink as usize);
+            let mut error: *mut Object = ptr::null_mut();
+            let added: BOOL = msg_send![stream, addStreamOutput: output type: OUTPUT_TYPE_AUDIO
+                sampleHandlerQueue: ptr::null_mut::<Object>() error: &mut error];
+            if added == NO {
+                let _: () = msg_send![output, release];
+                let _: () = msg_send![stream, release];
+                return Err(ValidatorError::Platform("ScreenCaptureKit refused the audio output".to_string()));
+            }
+
+            let started = call_with_error(|handler| {
+                let _: () = msg_send![stream, startCaptureWithCompletionHandler: handler];
+            });
+            if !started {
+                let _: () = msg_send![output, release];
+                let _: () = msg_send![stream, release];
+                return Err(ValidatorError::PermissionDenied("ScreenCaptureKit (Screen Recording)".to_string()));
+            }
+            let _ = ready.send(Ok(scope));
+
+            while !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(100));
+            }
+            // No sample arrives once the stop completes, so the sink can go away after it
+            call_with_error(|handler| {
+                let _: () = msg_send![stream, stopCaptureWithCompletionHandler: handler];
+            });
+            let _: () = msg_send![output, release];
+            let _: () = msg_send![stream, release];
+        }
+        Ok(())
+    }
+
+    /// SCShareableContent, retained
+    unsafe fn shareable_content() -> Result<*mut Object, ValidatorError> {
+        let (tx, rx) = mpsc::channel::<usize>();
+        let handler = ConcreteBlock::new(move |content: *mut Object, _error: *mut Object| {
+            if !content.is_null() {
+                let _: *mut Object = msg_send![content, retain];
+            }
+            let _ = tx.send(content as usize);
+        })
+        .copy();
+        let _: () = msg_send![class!(SCShareableContent), getShareableContentWithCompletionHandler: &*handler];
+        match rx.recv_timeout(REPLY_TIMEOUT) {
+            Ok(content) if content != 0 => Ok(content as *mut Object),
+            _ => Err(ValidatorError::PermissionDenied("ScreenCaptureKit (Screen Recording)".to_string())),
+        }
+    }
+
+    /// Run a call taking an `NSError` completion handler; true when it reported no error
+    unsafe fn call_with_error(call: impl FnOnce(&block::Block<(*mut Object,), ()>)) -> bool {
+        let (tx, rx) = mpsc::channel::<bool>();
+        let handler = ConcreteBlock::new(move |error: *mut Object| {
+            let _ = tx.send(error.is_null());
+        })
+        .copy();
+        call(&handler);
+        rx.recv_timeout(REPLY_TIMEOUT).unwrap_or(false)
+    }
+
+    unsafe fn objects(array: *mut Object) -> Vec<*mut Object> {
+        if array.is_null() {
+            return Vec::new();
+        }
+        let count: usize = msg_send![array, count];
+        (0..count).map(|i| msg_send![array, objectAtIndex: i]).collect()
+    }
+
+    unsafe fn first(array: *mut Object) -> Option<*mut Object> {
+        objects(array).into_iter().next()
+    }
+
+    /// NSObject subclass implementing SCStreamOutput; its `sink` ivar points at the FrameSink
+    fn output_class() -> &'static Class {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            let Some(mut decl) = ClassDecl::new("RustValidatorAudioOutput", class!(NSObject)) else { return };
+            decl.add_ivar::<usize>("sink");
+            if let Some(protocol) = Protocol::get("SCStreamOutput") {
+                decl.add_protocol(protocol);
+            }
+            unsafe {
+                decl.add_method(
+                    sel!(stream:didOutputSampleBuffer:ofType:),
+                    did_output as extern "C" fn(&Object, Sel, *mut Object, *mut c_void, isize),
+                );
+            }
+            decl.register();
+        });
+        class!(RustValidatorAudioOutput)
+    }
+
+    extern "C" fn did_output(this: &Object, _: Sel, _stream: *mut Object, buffer: *mut c_void, kind: isize) {
+        if kind != OUTPUT_TYPE_AUDIO || buffer.is_null() {
+            return;
+        }
+        unsafe {
+            let sink = *this.get_ivar::<usize>("sink") as *const FrameSink;
+            let block = CMSampleBufferGetDataBuffer(buffer);
+            if sink.is_null() || block.is_null() {
+                return;
+            }
+            let mut length = 0usize;
+            let mut data: *mut c_char = ptr::null_mut();
+            if CMBlockBufferGetDataPointer(block, 0, &mut length, ptr::null_mut(), &mut data) != 0 || data.is_null() {
+                return;
+            }
+            // One channel of 32-bit float, as configured
+            let samples = std::slice::from_raw_parts(data as *const f32, length / 4).to_vec();
+            (*sink).send(AudioFrame { sample_rate: SAMPLE_RATE, samples, captured_at: SystemTime::now() });
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+mod platform {
+    use super::{FrameSink, Ready};
+    use crate::error::ValidatorError;
+    use std::sync::atomic::AtomicBool;
+
+    pub fn capture(_process_id: u32, _sink: &FrameSink, _stop: &AtomicBool, _ready: &Ready) -> Result<(), ValidatorError> {
+        Err(ValidatorError::BackendUnavailable("loopback capture on this platform".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_needs_consent_and_streams_frames() {
+        assert!(matches!(start_loopback_capture(1), Err(ValidatorError::PermissionDenied(_))));
+
+        let (tx, rx) = mpsc::sync_channel(2);
+        let rx = Arc::new(Mutex::new(rx));
+        let waker = Arc::new(Mutex::new(None));
+        let sink = FrameSink { frames: tx, queue: Arc::downgrade(&rx), waker: Arc::clone(&waker) };
+        let mut stream =
+            LoopbackStream { frames: rx, waker, stop: Arc::new(AtomicBool::new(false)), scope: CaptureScope::Process };
+        let frame = AudioFrame { sample_rate: SAMPLE_RATE, samples: vec![0.5; 4], captured_at: SystemTime::UNIX_EPOCH };
+
+        struct Noop;
+        impl std::task::Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Pending);
+        assert!(sink.send(frame.clone()));
+        assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(Some(frame.clone())));
+
+        // A full queue drops its oldest frames
+        for secs in [1, 2, 3] {
+            let captured_at = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+            assert!(sink.send(AudioFrame { captured_at, ..frame.clone() }));
+        }
+        let kept: Vec<u64> = stream
+            .frames()
+            .iter()
+            .map(|frame| frame.captured_at.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs())
+            .collect();
+        assert_eq!(kept, vec![2, 3]);
+        drop(sink);
+        assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(None));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sink_inputs_are_parsed() {
+        let text = "Sink Input #42\n\tDriver: protocol-native.c\n\tProperties:\n\t\tapplication.name = \"Chromium\"\n\t\tapplication.process.id = \"4242\"\n\
+                    Sink Input #43\n\tProperties:\n\t\tapplication.name = \"speech-dispatcher\"\n";
+        assert_eq!(platform::parse_sink_inputs(text), vec![(42, 4242)]);
+    }
+}
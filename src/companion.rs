@@ -0,0 +1,375 @@
+// Mobile companion endpoint
+// The iOS/Android companion apps report when the user is in a call on the phone, so a
+// meeting that moved from the desktop to the phone is reported as `external_calls`
+// instead of simply ending:
+//
+//   POST /calls
+//   X-Companion-Timestamp: 1760612400
+//   X-Companion-Nonce: 6f1c0a2e9b7d4c35
+//   X-Companion-Signature: <hex HMAC-SHA256 of "<timestamp>\n<nonce>\n<body>">
+//   {"device":"Pixel 8","platform":"android","in_call":true,"app":"Google Meet",
+//    "meeting_identifier":"abc-defg-hij"}
+//
+// The phone is on the LAN, not loopback, so every request is authenticated: the
+// signature is keyed with the token both sides were paired with, and the token itself
+// never crosses the network. Timestamps more than MAX_CLOCK_SKEW off and nonces already
+// seen within that window are rejected, so a captured request cannot be replayed.
+// With the `companion-ws` feature the apps may instead open a WebSocket on GET /calls
+// (the upgrade request signed over an empty body) and send one signal per text message,
+// each signed the same way and checked against the same nonces:
+//
+//   {"timestamp":1760612430,"nonce":"9b7d4c356f1c0a2e","signature":"<hex>",
+//    "body":"{\"device\":\"Pixel 8\",\"in_call\":true}"}
+// Each report replaces that device's; devices that stop reporting for STALE_AFTER
+// drop out, like a closed app.
+
+use crate::browser_bridge::{self, HttpRequest};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const TIMESTAMP_HEADER: &str = "x-companion-timestamp";
+pub const NONCE_HEADER: &str = "x-companion-nonce";
+pub const SIGNATURE_HEADER: &str = "x-companion-signature";
+
+/// Shortest pairing token accepted
+pub const MIN_TOKEN_LEN: usize = 16;
+/// Largest difference between the phone's clock and ours
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+/// Devices are expected to report at least this often during a call
+const STALE_AFTER: Duration = Duration::from_secs(60);
+/// Nonce length bounds, in characters
+const NONCE_LEN: std::ops::RangeInclusive<usize> = 16..=64;
+
+/// A call the user is in on another device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalCall {
+    /// Device name as the companion app reports it
+    pub device: String,
+    /// "ios" or "android"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub platform: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meeting_identifier: Option<String>,
+    /// RFC3339 in UTC: the first report of this call
+    pub since_utc: String,
+    /// RFC3339 in UTC: the latest report
+    pub last_seen_utc: String,
+}
+
+/// One report from a companion app
+#[derive(Debug, Deserialize)]
+struct PhoneSignal {
+    device: String,
+    #[serde(default)]
+    platform: String,
+    in_call: bool,
+    #[serde(default)]
+    app: Option<String>,
+    #[serde(default)]
+    meeting_identifier: Option<String>,
+}
+
+/// One signed report over the WebSocket; `body` is a PhoneSignal as JSON text
+#[derive(Debug, Deserialize)]
+#[cfg_attr(not(feature = "companion-ws"), allow(dead_code))]
+struct SignedMessage {
+    timestamp: u64,
+    nonce: String,
+    signature: String,
+    body: String,
+}
+
+#[cfg_attr(not(feature = "companion-ws"), allow(dead_code))]
+impl SignedMessage {
+    /// The message as the POST it stands for, so it is verified like one
+    fn into_request(self) -> HttpRequest {
+        let headers = [
+            (TIMESTAMP_HEADER, self.timestamp.to_string()),
+            (NONCE_HEADER, self.nonce),
+            (SIGNATURE_HEADER, self.signature),
+        ];
+        HttpRequest {
+            method: "POST".to_string(),
+            path: "/calls".to_string(),
+            headers: headers.into_iter().map(|(name, value)| (name.to_string(), value)).collect(),
+            body: self.body.into_bytes(),
+        }
+    }
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AuthError {
+    Missing,
+    Expired,
+    Replayed,
+    BadSignature,
+}
+
+/// Signature and replay checks against the pairing token
+struct Authenticator {
+    token: Vec<u8>,
+    /// Nonces accepted within the last 2 * MAX_CLOCK_SKEW, with their timestamps
+    seen: HashMap<String, u64>,
+}
+
+impl Authenticator {
+    fn new(token: &str) -> Self {
+        Authenticator { token: token.as_bytes().to_vec(), seen: HashMap::new() }
+    }
+
+    fn verify(&mut self, request: &HttpRequest, now: SystemTime) -> Result<(), AuthError> {
+        let header = |name: &str| request.headers.get(name).map(|value| value.as_str());
+        let (Some(timestamp), Some(nonce), Some(signature)) =
+            (header(TIMESTAMP_HEADER), header(NONCE_HEADER), header(SIGNATURE_HEADER))
+        else {
+            return Err(AuthError::Missing);
+        };
+        if !NONCE_LEN.contains(&nonce.len()) {
+            return Err(AuthError::Missing);
+        }
+
+        // The signature first: unsigned requests must not fill the nonce table
+        let signature = hex::decode(signature).map_err(|_| AuthError::BadSignature)?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.token).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}\n{}\n", timestamp, nonce).as_bytes());
+        mac.update(&request.body);
+        mac.verify_slice(&signature).map_err(|_| AuthError::BadSignature)?;
+
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let sent: u64 = timestamp.parse().map_err(|_| AuthError::Expired)?;
+        if now.abs_diff(sent) > MAX_CLOCK_SKEW.as_secs() {
+            return Err(AuthError::Expired);
+        }
+
+        // Anything older than the skew window is refused by its timestamp alone
+        self.seen.retain(|_, at| now.abs_diff(*at) <= 2 * MAX_CLOCK_SKEW.as_secs());
+        if self.seen.insert(nonce.to_string(), sent).is_some() {
+            return Err(AuthError::Replayed);
+        }
+        Ok(())
+    }
+}
+
+type CallTable = Arc<Mutex<HashMap<String, (Instant, ExternalCall)>>>;
+
+/// Handle to the companion endpoint
+pub struct CompanionServer {
+    calls: CallTable,
+}
+
+impl CompanionServer {
+    /// Listen on `addr` in a background thread; requests must be signed with `token`
+    pub fn start(addr: SocketAddr, token: &str) -> Result<Self, String> {
+        if token.len() < MIN_TOKEN_LEN {
+            return Err(format!("the pairing token must be at least {} characters", MIN_TOKEN_LEN));
+        }
+
+        let listener = TcpListener::bind(addr).map_err(|e| e.to_string())?;
+        let calls: CallTable = Arc::new(Mutex::new(HashMap::new()));
+        let table = Arc::clone(&calls);
+        let auth = Arc::new(Mutex::new(Authenticator::new(token)));
+
+        thread::spawn(move || {
+            browser_bridge::serve(listener, move |stream| handle_connection(stream, &table, &auth))
+        });
+
+        Ok(CompanionServer { calls })
+    }
+
+    /// Calls on devices that reported recently
+    pub fn external_calls(&self) -> Vec<ExternalCall> {
+        let mut table = self.calls.lock().unwrap();
+        table.retain(|_, (updated, _)| updated.elapsed() < STALE_AFTER);
+        let mut calls: Vec<ExternalCall> = table.values().map(|(_, call)| call.clone()).collect();
+        calls.sort_by(|a, b| a.device.cmp(&b.device));
+        calls
+    }
+}
+
+fn handle_connection(stream: TcpStream, table: &CallTable, auth: &Mutex<Authenticator>) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    let request = match browser_bridge::read_request(&stream) {
+        Ok(request) if request.path == "/calls" => request,
+        Ok(_) => return respond(&mut writer, "404 Not Found"),
+        Err(_) => return respond(&mut writer, "400 Bad Request"),
+    };
+    let verified = auth.lock().unwrap().verify(&request, SystemTime::now());
+
+    let status = match verified {
+        Err(AuthError::Missing) | Err(AuthError::BadSignature) => "401 Unauthorized",
+        Err(AuthError::Expired) | Err(AuthError::Replayed) => "403 Forbidden",
+        Ok(()) if request.method == "POST" => match serde_json::from_slice::<PhoneSignal>(&request.body) {
+            Ok(signal) => {
+                record(table, signal);
+                "204 No Content"
+            }
+            Err(_) => "400 Bad Request",
+        },
+        Ok(()) if is_websocket_upgrade(&request) => return serve_websocket(stream, writer, &request, table, auth),
+        Ok(()) => "405 Method Not Allowed",
+    };
+    respond(&mut writer, status);
+}
+
+fn respond(writer: &mut TcpStream, status: &str) {
+    let _ = write!(writer, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+}
+
+/// Apply one device's report
+fn record(table: &CallTable, signal: PhoneSignal) {
+    let mut table = table.lock().unwrap();
+    if !signal.in_call {
+        table.remove(&signal.device);
+        return;
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    // Still the same call while the device reports the same app and meeting
+    let since_utc = table
+        .get(&signal.device)
+        .filter(|(_, call)| call.app == signal.app && call.meeting_identifier == signal.meeting_identifier)
+        .map(|(_, call)| call.since_utc.clone())
+        .unwrap_or_else(|| now.clone());
+
+    let call = ExternalCall {
+        device: signal.device.clone(),
+        platform: signal.platform.to_lowercase(),
+        app: signal.app,
+        meeting_identifier: signal.meeting_identifier,
+        since_utc,
+        last_seen_utc: now,
+    };
+    table.insert(signal.device, (Instant::now(), call));
+}
+
+fn is_websocket_upgrade(request: &HttpRequest) -> bool {
+    request.method == "GET"
+        && request.headers.get("upgrade").is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+#[cfg(feature = "companion-ws")]
+fn serve_websocket(
+    stream: TcpStream,
+    mut writer: TcpStream,
+    request: &HttpRequest,
+    table: &CallTable,
+    auth: &Mutex<Authenticator>,
+) {
+    use tungstenite::protocol::Role;
+    use tungstenite::{Message, WebSocket};
+
+    let Some(key) = request.headers.get("sec-websocket-key") else {
+        return respond(&mut writer, "400 Bad Request");
+    };
+    let accept = tungstenite::handshake::derive_accept_key(key.as_bytes());
+    if write!(
+        writer,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    )
+    .is_err()
+    {
+        return;
+    }
+
+    // Phones report at least every STALE_AFTER; a quiet socket is a gone phone
+    let _ = stream.set_read_timeout(Some(STALE_AFTER));
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+    while let Ok(message) = socket.read() {
+        let Message::Text(text) = message else { continue };
+        let Ok(message) = serde_json::from_str::<SignedMessage>(&text) else { continue };
+        let request = message.into_request();
+        // Unsigned, stale or replayed messages end the session like a refused POST
+        if auth.lock().unwrap().verify(&request, SystemTime::now()).is_err() {
+            break;
+        }
+        if let Ok(signal) = serde_json::from_slice::<PhoneSignal>(&request.body) {
+            record(table, signal);
+        }
+    }
+}
+
+#[cfg(not(feature = "companion-ws"))]
+fn serve_websocket(
+    _stream: TcpStream,
+    mut writer: TcpStream,
+    _request: &HttpRequest,
+    _table: &CallTable,
+    _auth: &Mutex<Authenticator>,
+) {
+    respond(&mut writer, "501 Not Implemented");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(token: &str, timestamp: u64, nonce: &str, body: &str) -> HttpRequest {
+        let mut mac = Hmac::<Sha256>::new_from_slice(token.as_bytes()).unwrap();
+        mac.update(format!("{}\n{}\n{}", timestamp, nonce, body).as_bytes());
+        let headers = [
+            (TIMESTAMP_HEADER, timestamp.to_string()),
+            (NONCE_HEADER, nonce.to_string()),
+            (SIGNATURE_HEADER, hex::encode(mac.finalize().into_bytes())),
+        ];
+        HttpRequest {
+            method: "POST".to_string(),
+            path: "/calls".to_string(),
+            headers: headers.into_iter().map(|(name, value)| (name.to_string(), value)).collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_signed_requests_are_accepted_once() {
+        let token = "0123456789abcdef0123";
+        let mut auth = Authenticator::new(token);
+        let now = UNIX_EPOCH + Duration::from_secs(1_760_612_400);
+        let body = r#"{"device":"Pixel 8","in_call":true}"#;
+
+        let request = signed(token, 1_760_612_400, "6f1c0a2e9b7d4c35", body);
+        assert_eq!(auth.verify(&request, now), Ok(()));
+        assert_eq!(auth.verify(&request, now + Duration::from_secs(5)), Err(AuthError::Replayed));
+
+        let late = signed(token, 1_760_612_400, "a0b1c2d3e4f5a6b7", body);
+        assert_eq!(auth.verify(&late, now + Duration::from_secs(120)), Err(AuthError::Expired));
+
+        let forged = signed("fedcba9876543210fedc", 1_760_612_400, "0a1b2c3d4e5f6a7b", body);
+        assert_eq!(auth.verify(&forged, now), Err(AuthError::BadSignature));
+
+        let mut tampered = signed(token, 1_760_612_400, "1a2b3c4d5e6f7a8b", body);
+        tampered.body = br#"{"device":"Pixel 8","in_call":false}"#.to_vec();
+        assert_eq!(auth.verify(&tampered, now), Err(AuthError::BadSignature));
+
+        let mut unsigned = signed(token, 1_760_612_400, "2a3b4c5d6e7f8a9b", body);
+        unsigned.headers.remove(SIGNATURE_HEADER);
+        assert_eq!(auth.verify(&unsigned, now), Err(AuthError::Missing));
+
+        // WebSocket messages carry the same signature and share the nonce table
+        let posted = signed(token, 1_760_612_400, "3a4b5c6d7e8f9a0b", body);
+        let message = |nonce: &str| SignedMessage {
+            timestamp: 1_760_612_400,
+            nonce: nonce.to_string(),
+            signature: posted.headers[SIGNATURE_HEADER].clone(),
+            body: body.to_string(),
+        };
+        assert_eq!(auth.verify(&message("3a4b5c6d7e8f9a0b").into_request(), now), Ok(()));
+        assert_eq!(auth.verify(&message("3a4b5c6d7e8f9a0b").into_request(), now), Err(AuthError::Replayed));
+        assert_eq!(auth.verify(&message("4a5b6c7d8e9f0a1b").into_request(), now), Err(AuthError::BadSignature));
+    }
+}
@@ -0,0 +1,186 @@
+// Optional JSON configuration file (`--config <path>`)
+// Every section is optional; anything missing keeps the built-in defaults.
+//
+// {
+//   "scoring": {
+//     "threshold": 0.45,
+//     "apps": { "google meet": { "require_webrtc": true } },
+//     "ignore_processes": ["obs*"],
+//     "force_track_processes": ["3CXPhone*"],
+//     "tracked_apps": ["Zoom", "Microsoft Teams"],
+//     "title_keywords": { "de": ["besprechung"] }
+//   },
+//   "hysteresis": { "exit_threshold": 0.25, "confirm_ms": 1000, "end_grace_ms": 2000 },
+//   "redaction": { "titles": "hash" },
+//   "polling": { "audio_ms": 250, "mic_ms": 500, "network_ms": 2000, "idle_ms": 3000, "call_ms": 250 },
+//   "outputs": [{ "sink": "file", "path": "logs/calls.log", "verbosity": "calls" }],
+//   "update": { "manifest_url": "https://example.com/releases.json", "check_hours": 24 }
+// }
+//
+// The file is watched while the monitor runs: scoring, hysteresis, redaction and polling
+// changes apply on the next tick without touching the active call. Outputs are opened
+// once at start-up and need a restart. The watch is on the file's directory, so saves that
+// write a temporary file and rename it over the original are picked up too.
+
+use crate::correlation_engine::{HysteresisConfig, ScoringConfig};
+use crate::output_router::SinkConfig;
+use crate::redaction::RedactionConfig;
+use crate::scheduler::PollIntervals;
+use crate::self_update::UpdateConfig;
+use file_watch::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// Quiet time after the last change before the file is read, so a save made in several
+/// writes is read once, complete
+const SETTLE: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub scoring: ScoringConfig,
+    pub hysteresis: HysteresisConfig,
+    pub redaction: RedactionConfig,
+    /// Refresh interval of each sensing subsystem, see scheduler.rs
+    pub polling: PollIntervals,
+    /// Extra output sinks, see output_router.rs
+    pub outputs: Vec<SinkConfig>,
+    /// Release manifest and key for `update` and `--auto-update`, see self_update.rs
+    pub update: UpdateConfig,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("invalid config {}: {}", path.display(), e))
+    }
+
+    /// Top-level sections that differ from `other`
+    pub fn changed_sections(&self, other: &Config) -> Vec<&'static str> {
+        fn json<T: Serialize>(section: &T) -> serde_json::Value {
+            serde_json::to_value(section).unwrap_or_default()
+        }
+        [
+            ("scoring", json(&self.scoring) != json(&other.scoring)),
+            ("hysteresis", json(&self.hysteresis) != json(&other.hysteresis)),
+            ("redaction", json(&self.redaction) != json(&other.redaction)),
+            ("polling", json(&self.polling) != json(&other.polling)),
+            ("outputs", json(&self.outputs) != json(&other.outputs)),
+        ]
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect()
+    }
+}
+
+/// Reloads the `--config` file when it changes on disk
+pub struct ConfigWatcher {
+    path: PathBuf,
+    config: Config,
+    events: Receiver<file_watch::Result<file_watch::Event>>,
+    /// Kept alive so events keep arriving; `None` when the watch could not be set up
+    _watcher: Option<RecommendedWatcher>,
+    /// When a change to the file was last seen, until it is read
+    changed_at: Option<Instant>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf, config: Config) -> Self {
+        let (sender, events) = mpsc::channel();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let watcher = file_watch::recommended_watcher(sender)
+            .and_then(|mut watcher| watcher.watch(&dir, RecursiveMode::NonRecursive).map(|_| watcher))
+            .map_err(|e| log::warn!("Not watching {} for changes: {}", path.display(), e))
+            .ok();
+        ConfigWatcher { path, config, events, _watcher: watcher, changed_at: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The new config and the sections that changed, once the file was rewritten with
+    /// different content; an unreadable or invalid file keeps the previous config
+    pub fn poll(&mut self, now: Instant) -> Option<Result<(Config, Vec<&'static str>), String>> {
+        for event in self.events.try_iter() {
+            let touches_file = match event {
+                Ok(event) => {
+                    !event.kind.is_access()
+                        && event.paths.iter().any(|path| path.file_name() == self.path.file_name())
+                }
+                // Events were dropped, so the file may have changed
+                Err(_) => true,
+            };
+            if touches_file {
+                self.changed_at = Some(now);
+            }
+        }
+        if now.duration_since(self.changed_at?) < SETTLE {
+            return None;
+        }
+        self.changed_at = None;
+
+        let config = match Config::load(&self.path) {
+            Ok(config) => config,
+            Err(e) => return Some(Err(e)),
+        };
+        let changed = config.changed_sections(&self.config);
+        if changed.is_empty() {
+            return None;
+        }
+        self.config = config.clone();
+        Some(Ok((config, changed)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polls until the watcher reports a reload, as the monitor loop would
+    fn next_reload(watcher: &mut ConfigWatcher) -> Option<Result<(Config, Vec<&'static str>), String>> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Some(reload) = watcher.poll(Instant::now()) {
+                return Some(reload);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        None
+    }
+
+    #[test]
+    fn test_watcher_reloads_changed_sections() {
+        let dir = std::env::temp_dir().join(format!("config_watch_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("validator.json");
+        fs::write(&path, r#"{"scoring": {"threshold": 0.45}}"#).unwrap();
+        let mut watcher = ConfigWatcher::new(path.clone(), Config::load(&path).unwrap());
+        assert!(watcher.poll(Instant::now() + SETTLE).is_none());
+
+        fs::write(&path, r#"{"scoring": {"threshold": 0.6}, "polling": {"idle_ms": 5000}}"#).unwrap();
+        let (config, changed) = next_reload(&mut watcher).unwrap().unwrap();
+        assert_eq!(changed, vec!["scoring", "polling"]);
+        assert_eq!(config.scoring.threshold, 0.6);
+
+        // Saved the way editors do: a new file renamed over the old one
+        let temp = dir.join("validator.json.tmp");
+        fs::write(&temp, r#"{"scoring": {"threshold": 0.7}, "polling": {"idle_ms": 5000}}"#).unwrap();
+        fs::rename(&temp, &path).unwrap();
+        let (config, changed) = next_reload(&mut watcher).unwrap().unwrap();
+        assert_eq!(changed, vec!["scoring"]);
+        assert_eq!(config.scoring.threshold, 0.7);
+
+        fs::write(&path, "{ not json").unwrap();
+        assert!(next_reload(&mut watcher).unwrap().is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,85 @@
+// Human-facing console text
+// `--plain` switches to concise single-line sentences with stable phrasing and no
+// box-drawing, arrows or emoji, so screen readers announce updates cleanly.
+// The describe_* helpers are also the canonical text for user-visible notifications.
+
+use crate::CallInfo;
+
+/// How call updates are written to the console
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleStyle {
+    /// Original banner and arrow markers
+    Standard,
+    /// Screen-reader-friendly sentences
+    Plain,
+}
+
+/// One sentence announcing a new call
+pub fn describe_call_started(call: &CallInfo) -> String {
+    format!(
+        "Call started in {}. Confidence {} percent. {}.",
+        call.app,
+        (call.confidence * 100.0).round() as i32,
+        describe_signals(call)
+    )
+}
+
+/// One sentence announcing the end of a call
+pub fn describe_call_ended(call: &CallInfo, duration_secs: u64) -> String {
+    format!(
+        "Call ended in {}. Duration {}.",
+        call.app,
+        spoken_duration(duration_secs)
+    )
+}
+
+/// Which signals back the detection, in words
+fn describe_signals(call: &CallInfo) -> String {
+    let mut signals = Vec::new();
+    if call.has_audio {
+        signals.push("audio output");
+    }
+    if call.has_mic {
+        signals.push(if call.in_app_muted == Some(true) { "microphone (muted in the app)" } else { "microphone" });
+    }
+    if call.has_webrtc {
+        signals.push("network call traffic");
+    }
+
+    match signals.len() {
+        0 => "No active signals".to_string(),
+        1 => format!("Detected from {}", signals[0]),
+        _ => {
+            let last = signals.pop().unwrap_or_default();
+            format!("Detected from {} and {}", signals.join(", "), last)
+        }
+    }
+}
+
+/// "1 hour 2 minutes 3 seconds" rather than "1h 2m 3s"
+pub fn spoken_duration(total_secs: u64) -> String {
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+
+    let mut parts = Vec::new();
+    if hours > 0 {
+        parts.push(plural(hours, "hour"));
+    }
+    if minutes > 0 {
+        parts.push(plural(minutes, "minute"));
+    }
+    if seconds > 0 || parts.is_empty() {
+        parts.push(plural(seconds, "second"));
+    }
+
+    parts.join(" ")
+}
+
+fn plural(value: u64, unit: &str) -> String {
+    if value == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", value, unit)
+    }
+}
@@ -0,0 +1,246 @@
+// Runtime control commands accepted from local consumers
+// Commands are single lines, either a bare word ("status") or JSON ({"command": "status"},
+// or {"cmd": "status"}). They arrive over IPC, gRPC and, in `--stream` mode, stdin.
+// `pause`, `privacy` and `resume` switch the RunMode; on Linux/macOS SIGUSR1 toggles pause.
+
+use crate::capability::CapabilityLevel;
+use crate::environment::Environment;
+use crate::power::PowerProfile;
+use crate::scheduler::Pace;
+use crate::supervisor::BackendError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// A command sent by a consumer to the running monitor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Reply with the current full state snapshot
+    Status,
+    /// Liveness check
+    Ping,
+    /// Reply with the HealthReport: run mode, polling pace, power profile and subsystem errors
+    Health,
+    /// Stop sensing; an active call ends as if its signals went away
+    Pause,
+    /// Keep detecting but emit only whether a call is active
+    Privacy,
+    /// Back to normal monitoring
+    Resume,
+    /// Change the log level (`RUST_LOG`) without a restart
+    SetLogLevel(log::LevelFilter),
+}
+
+/// What the monitor loop is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    Monitoring,
+    Paused,
+    /// No window titles, process names or other audio sources in any output
+    Privacy,
+}
+
+impl RunMode {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            RunMode::Monitoring => "monitoring",
+            RunMode::Paused => "paused",
+            RunMode::Privacy => "privacy mode",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonCommand {
+    #[serde(alias = "cmd")]
+    command: String,
+    /// For `set_log_level`
+    #[serde(default)]
+    level: Option<String>,
+}
+
+impl ControlCommand {
+    /// Parse one line of input
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+
+        let (name, level) = if line.starts_with('{') {
+            let json = serde_json::from_str::<JsonCommand>(line).map_err(|e| format!("Invalid command JSON: {}", e))?;
+            (json.command, json.level)
+        } else {
+            // "set_log_level debug"
+            match line.split_once(char::is_whitespace) {
+                Some((name, level)) => (name.to_string(), Some(level.trim().to_string())),
+                None => (line.to_string(), None),
+            }
+        };
+
+        match name.to_lowercase().as_str() {
+            "status" | "snapshot" => Ok(ControlCommand::Status),
+            "ping" => Ok(ControlCommand::Ping),
+            "health" => Ok(ControlCommand::Health),
+            "pause" => Ok(ControlCommand::Pause),
+            "privacy" => Ok(ControlCommand::Privacy),
+            "resume" => Ok(ControlCommand::Resume),
+            "set_log_level" => {
+                let level = level.ok_or_else(|| "set_log_level needs a level".to_string())?;
+                level
+                    .parse()
+                    .map(ControlCommand::SetLogLevel)
+                    .map_err(|_| format!("Unknown log level '{}' (off, error, warn, info, debug, trace)", level))
+            }
+            "" => Err("Empty command".to_string()),
+            other => Err(format!("Unknown command '{}'", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlCommand::Status => "status",
+            ControlCommand::Ping => "ping",
+            ControlCommand::Health => "health",
+            ControlCommand::Pause => "pause",
+            ControlCommand::Privacy => "privacy",
+            ControlCommand::Resume => "resume",
+            ControlCommand::SetLogLevel(_) => "set_log_level",
+        }
+    }
+
+    /// The mode after this command, for mode-switching commands
+    pub fn run_mode(&self) -> Option<RunMode> {
+        match self {
+            ControlCommand::Pause => Some(RunMode::Paused),
+            ControlCommand::Privacy => Some(RunMode::Privacy),
+            ControlCommand::Resume => Some(RunMode::Monitoring),
+            ControlCommand::Status | ControlCommand::Ping | ControlCommand::Health | ControlCommand::SetLogLevel(_) => None,
+        }
+    }
+}
+
+/// Command lines read from stdin on a background thread
+pub struct StdinCommands {
+    lines: Receiver<String>,
+}
+
+impl StdinCommands {
+    /// `intercept` sees each line first and swallows those it returns true for; with
+    /// `exit_on_close` the monitor stops once stdin is closed
+    pub fn spawn(intercept: impl Fn(&str) -> bool + Send + 'static, exit_on_close: bool) -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if line.trim().is_empty() || intercept(&line) {
+                    continue;
+                }
+                if sender.send(line).is_err() {
+                    return;
+                }
+            }
+            if exit_on_close {
+                log::info!("stdin closed, stopping");
+                std::process::exit(0);
+            }
+        });
+        StdinCommands { lines }
+    }
+
+    /// Lines received since the last call
+    pub fn pending(&self) -> Vec<String> {
+        self.lines.try_iter().collect()
+    }
+}
+
+/// Set from the SIGUSR1 handler, consumed once per tick
+static PAUSE_TOGGLE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Make SIGUSR1 toggle pause (Linux/macOS/FreeBSD; a no-op elsewhere)
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "freebsd"))]
+pub fn install_pause_signal() {
+    extern "C" fn on_sigusr1(_signal: libc::c_int) {
+        PAUSE_TOGGLE_REQUESTED.store(true, Ordering::SeqCst);
+    }
+
+    unsafe {
+        libc::signal(libc::SIGUSR1, on_sigusr1 as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+pub fn install_pause_signal() {}
+
+/// Whether SIGUSR1 arrived since the last call
+pub fn take_pause_toggle() -> bool {
+    PAUSE_TOGGLE_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// How the monitor is running, for `health`
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub run_mode: &'static str,
+    pub pace: Pace,
+    pub power: PowerProfile,
+    /// Polling slowed and socket scans off until a call is suspected
+    pub power_saving: bool,
+    /// Subsystem panics caught since start-up
+    pub errors: u64,
+    /// Subsystems backing off after a panic
+    pub failing: Vec<&'static str>,
+    /// Error each failing backend returned in its last poll, by subsystem
+    pub backend_errors: BTreeMap<&'static str, BackendError>,
+    pub capability_level: CapabilityLevel,
+    /// Why the capability level is below full
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capability_reason: Option<String>,
+    pub environment: Environment,
+    /// Stdout lines dropped because the reader fell behind or went away
+    pub dropped_messages: u64,
+}
+
+/// Payload for `command_result`
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandResult {
+    pub command: String,
+    pub ok: bool,
+    pub message: String,
+}
+
+impl CommandResult {
+    pub fn ok(command: &str, message: &str) -> Self {
+        CommandResult {
+            command: command.to_string(),
+            ok: true,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn error(command: &str, message: &str) -> Self {
+        CommandResult {
+            command: command.to_string(),
+            ok: false,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_parse_from_words_and_json() {
+        assert_eq!(ControlCommand::parse("status"), Ok(ControlCommand::Status));
+        assert_eq!(ControlCommand::parse(r#"{"cmd":"snapshot"}"#), Ok(ControlCommand::Status));
+        assert_eq!(ControlCommand::parse(r#"{"cmd":"pause"}"#), Ok(ControlCommand::Pause));
+        assert_eq!(
+            ControlCommand::parse(r#"{"cmd":"set_log_level","level":"debug"}"#),
+            Ok(ControlCommand::SetLogLevel(log::LevelFilter::Debug))
+        );
+        assert_eq!(ControlCommand::parse("set_log_level TRACE"), Ok(ControlCommand::SetLogLevel(log::LevelFilter::Trace)));
+        assert!(ControlCommand::parse(r#"{"cmd":"set_log_level"}"#).is_err());
+        assert!(ControlCommand::parse(r#"{"cmd":"set_log_level","level":"loud"}"#).is_err());
+    }
+}
@@ -1,5 +1,13 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 use serde::{Deserialize, Serialize};
+use crate::messengers;
+use crate::process_filter::{self, ProcessPattern};
+use crate::process_load::ProcessLoad;
+use crate::rule_script::{RuleScript, ScriptVerdict};
+use crate::signal_providers::SignalContribution;
+use crate::titles::TitleMatcher;
 
 /// All signals collected from different sources
 #[derive(Debug, Clone)]
@@ -10,15 +18,40 @@ pub struct MultiSignal {
 
     // WASAPI signals
     pub has_mic_active: bool,
+    /// Input peak of what the app records, 0.0-1.0; None where no meter reads it
+    pub mic_peak_level: Option<f32>,
+    /// The app holds the mic but its recording has been silent for `mic_idle_secs`;
+    /// `has_mic_active` is then false
+    pub mic_idle: bool,
+    /// CPU and GPU load of the process since the previous tick (see process_load.rs)
+    pub process_load: Option<ProcessLoad>,
     pub has_audio_output: bool,
     pub audio_peak_level: f32,
+    /// The audio only reaches a virtual sink or loopback (VB-Cable, BlackHole, null sink)
+    pub is_virtual_device: bool,
 
     // Network signals
     pub has_webrtc_connection: bool,
     pub webrtc_started_at: Option<SystemTime>,
+    /// Chromium's event log shows a live PeerConnection (see webrtc_event_log.rs)
+    pub has_peer_connection: bool,
+    /// SIP signalling alongside RTP media (softphones such as Bria, Zoiper, 3CX)
+    pub has_sip_media: bool,
+    /// Call state reported by the app itself: Teams' API (teams.rs), or a Zoom meeting
+    /// window (zoom_probe.rs). None when the app reports nothing.
+    pub client_in_call: Option<bool>,
+    /// The user's calendar has a meeting under way that this call may be (see calendar.rs)
+    pub in_scheduled_meeting: bool,
+    /// How long the app's audio has played without a break, for a call not yet reported;
+    /// None for the active call or when unknown
+    pub audio_active_secs: Option<f32>,
+    /// Evidence from registered signal providers about this candidate (see signal_providers.rs)
+    pub contributions: Vec<SignalContribution>,
 
     // Metadata
     pub detected_app: Option<String>,
+    /// URL of the browser tab behind this signal, from the companion extension
+    pub tab_url: Option<String>,
     pub duration: Duration,
 }
 
@@ -28,7 +61,59 @@ pub struct DetectionResult {
     pub is_call: bool,
     pub confidence: f32,
     pub signal_type: SignalType,
+    #[serde(default)]
+    pub kind: CallKind,
     pub reasons: Vec<String>,
+    /// Every rule evaluated, in order (see `--explain`)
+    #[serde(default)]
+    pub rules: Vec<RuleTrace>,
+    /// Raw signal values the rules were evaluated against
+    pub signals: SignalValues,
+}
+
+/// Outcome of a single detection rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTrace {
+    /// Stable snake_case rule name
+    pub rule: String,
+    pub matched: bool,
+    /// Change this rule made to the confidence (negative for penalties, 0 for gates)
+    pub weight: f32,
+}
+
+/// The parts of a `MultiSignal` that feed the rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalValues {
+    pub process_id: u32,
+    pub process_name: String,
+    pub window_title: String,
+    pub detected_app: Option<String>,
+    pub tab_url: Option<String>,
+    pub has_audio_output: bool,
+    pub audio_peak_level: f32,
+    #[serde(default)]
+    pub is_virtual_device: bool,
+    pub has_webrtc_connection: bool,
+    pub has_peer_connection: bool,
+    #[serde(default)]
+    pub has_sip_media: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_in_call: Option<bool>,
+    #[serde(default)]
+    pub in_scheduled_meeting: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_active_secs: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributions: Vec<SignalContribution>,
+    pub has_mic_active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mic_peak_level: Option<f32>,
+    #[serde(default)]
+    pub mic_idle: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_load: Option<ProcessLoad>,
+    pub duration_secs: f64,
+    pub threshold: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,8 +124,296 @@ pub enum SignalType {
     Unknown,
 }
 
+/// What an audio source other than the active call is (`classification`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceClass {
+    /// Music, video or streaming (the media-site list, by title or process name)
+    Media,
+    /// A call app that is not the active call
+    CallCandidate,
+    /// Notification and desktop sounds
+    SystemSound,
+    Unknown,
+}
+
+impl SourceClass {
+    /// Same spelling as the JSON output
+    #[cfg_attr(not(feature = "grpc"), allow(dead_code))] // only gRPC needs it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceClass::Media => "media",
+            SourceClass::CallCandidate => "call_candidate",
+            SourceClass::SystemSound => "system_sound",
+            SourceClass::Unknown => "unknown",
+        }
+    }
+}
+
+/// CPU load (percent of one core) and GPU engine load of a client encoding or decoding a call
+const BUSY_CPU_PERCENT: f32 = 15.0;
+const BUSY_GPU_PERCENT: f32 = 5.0;
+
+/// Input peak above which a recording carries speech rather than room noise
+const SPEECH_PEAK: f32 = 0.02;
+
+/// Lowercase process names that only play notification and desktop sounds
+const SYSTEM_SOUND_PROCESSES: &[&str] = &[
+    "explorer.exe",
+    "shellexperiencehost.exe",
+    "systemsettings.exe",
+    "audiodg.exe",
+    "gnome-shell",
+    "plasmashell",
+    "canberra-gtk-play",
+    "speech-dispatcher",
+    "coreaudiod",
+    "systemuiserver",
+    "notificationcenter",
+    "usernotificationcenter",
+];
+
+/// Whether a process only plays system and notification sounds; never a call
+pub fn is_system_sound_process(process_name: &str) -> bool {
+    SYSTEM_SOUND_PROCESSES.contains(&process_name.to_lowercase().as_str())
+}
+
+/// Which world a call belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    /// Meeting apps and WebRTC (Zoom, Teams, Meet, ...)
+    #[default]
+    Meeting,
+    /// SIP softphone with RTP media and no WebRTC
+    Sip,
+    /// Slack huddle (see slack_huddle.rs)
+    Huddle,
+}
+
+impl CallKind {
+    /// Same spelling as the JSON output
+    #[cfg_attr(not(any(feature = "grpc", feature = "otel")), allow(dead_code))] // only gRPC and OTel need it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallKind::Meeting => "meeting",
+            CallKind::Sip => "sip",
+            CallKind::Huddle => "huddle",
+        }
+    }
+}
+
+impl RuleTrace {
+    /// A rule that only allows or rejects, contributing no weight
+    fn gate(rule: &str, matched: bool) -> Self {
+        RuleTrace { rule: rule.to_string(), matched, weight: 0.0 }
+    }
+
+    /// A scoring rule; `weight` counts only when it matched
+    fn weighted(rule: &str, matched: bool, weight: f32) -> Self {
+        RuleTrace {
+            rule: rule.to_string(),
+            matched,
+            weight: if matched { weight } else { 0.0 },
+        }
+    }
+}
+
+/// Signal weights and call threshold used by `detect_call`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    pub audio_weight: f32,
+    pub webrtc_weight: f32,
+    pub mic_weight: f32,
+    /// Added on top of `mic_weight` while the app's recording carries speech, where the
+    /// platform meters it; an app merely holding the mic open gets only `mic_weight`
+    pub mic_speech_weight: f32,
+    /// Seconds an app's metered recording may stay silent before its mic counts as idle
+    /// (a session left open after a call) and no longer as active; 0 never
+    pub mic_idle_secs: f32,
+    pub title_weight: f32,
+    /// Added while the app's audio is silent but it keeps a core or its GPU busy the way
+    /// encoding and decoding a call does (see process_load.rs)
+    pub load_weight: f32,
+    pub threshold: f32,
+    /// Taken off the audio weight when the audio goes to a virtual device, where
+    /// it is more likely recorded or streamed than heard
+    pub virtual_device_penalty: f32,
+    /// Added while the user's calendar has a meeting with a conferencing link under way
+    /// (`calendar` feature)
+    pub calendar_weight: f32,
+    /// Seconds an app's audio must keep playing before it can start a call without
+    /// network or client evidence; notification sounds (a Slack ping) are shorter
+    pub min_audio_activity_secs: f32,
+    /// Per-app overrides keyed by a lowercase substring of the process name,
+    /// window title or detected app (e.g. "google meet", "zoom")
+    pub apps: BTreeMap<String, AppScoringOverride>,
+    /// Processes never counted as a call (e.g. OBS virtual mic), see process_filter.rs
+    pub ignore_processes: Vec<ProcessPattern>,
+    /// Processes tracked like a known call app (e.g. 3CX, RingCentral softphones)
+    pub force_track_processes: Vec<ProcessPattern>,
+    /// App names calls are detected for (e.g. "Zoom", "Microsoft Teams"); empty tracks every
+    /// known app. Other apps' audio sources are only counted, see call_tracker.rs
+    pub tracked_apps: Vec<String>,
+    /// Rhai script that can boost or veto each detection (`scripting` feature, see rule_script.rs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<PathBuf>,
+    /// Meeting title keywords per language code, replacing the built-in list for that
+    /// language (see titles.rs)
+    pub title_keywords: BTreeMap<String, Vec<String>>,
+}
+
+/// Per-app changes to the default scoring; unset fields keep the defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppScoringOverride {
+    pub audio_weight: Option<f32>,
+    pub webrtc_weight: Option<f32>,
+    pub mic_weight: Option<f32>,
+    pub title_weight: Option<f32>,
+    pub threshold: Option<f32>,
+    /// Never report a call for this app without a WebRTC connection
+    pub require_webrtc: bool,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        // Audio(40%) + Mic(15%) = 55% clears the 45% threshold, matching the old
+        // "mic && audio && call app" logic
+        ScoringConfig {
+            audio_weight: 0.40,
+            webrtc_weight: 0.35,
+            mic_weight: 0.15,
+            mic_speech_weight: 0.10,
+            mic_idle_secs: 120.0,
+            title_weight: 0.10,
+            load_weight: 0.15,
+            threshold: 0.45,
+            virtual_device_penalty: 0.20,
+            calendar_weight: 0.10,
+            min_audio_activity_secs: 3.0,
+            apps: BTreeMap::new(),
+            ignore_processes: Vec::new(),
+            force_track_processes: Vec::new(),
+            tracked_apps: Vec::new(),
+            script: None,
+            title_keywords: BTreeMap::new(),
+        }
+    }
+}
+
+/// Scoring after applying any per-app override
+#[derive(Debug, Clone)]
+struct EffectiveScoring {
+    audio_weight: f32,
+    webrtc_weight: f32,
+    mic_weight: f32,
+    title_weight: f32,
+    threshold: f32,
+    require_webrtc: bool,
+}
+
+impl ScoringConfig {
+    pub fn is_ignored(&self, process_name: &str, window_title: &str) -> bool {
+        process_filter::any_matches(&self.ignore_processes, process_name, window_title)
+    }
+
+    pub fn is_force_tracked(&self, process_name: &str, window_title: &str) -> bool {
+        process_filter::any_matches(&self.force_track_processes, process_name, window_title)
+    }
+
+    /// Allowed by `tracked_apps`: a listed app name, or a force-tracked process
+    pub fn is_tracked(&self, detected_app: Option<&str>, process_name: &str, window_title: &str) -> bool {
+        self.tracked_apps.is_empty()
+            || detected_app.is_some_and(|app| self.tracked_apps.iter().any(|tracked| tracked.eq_ignore_ascii_case(app)))
+            || self.is_force_tracked(process_name, window_title)
+    }
+
+    /// Scoring for a signal; the longest matching app key wins
+    fn resolve(&self, signal: &MultiSignal) -> EffectiveScoring {
+        let combined = format!(
+            "{} {} {}",
+            signal.process_name.to_lowercase(),
+            signal.window_title.to_lowercase(),
+            signal.detected_app.as_ref().map(|s| s.to_lowercase()).unwrap_or_default()
+        );
+
+        let app_override = self
+            .apps
+            .iter()
+            .filter(|(key, _)| combined.contains(key.to_lowercase().as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+
+        EffectiveScoring {
+            audio_weight: app_override.audio_weight.unwrap_or(self.audio_weight),
+            webrtc_weight: app_override.webrtc_weight.unwrap_or(self.webrtc_weight),
+            mic_weight: app_override.mic_weight.unwrap_or(self.mic_weight),
+            title_weight: app_override.title_weight.unwrap_or(self.title_weight),
+            threshold: app_override.threshold.unwrap_or(self.threshold),
+            require_webrtc: app_override.require_webrtc,
+        }
+    }
+}
+
+/// Call lifecycle hysteresis
+/// A call starts once a candidate clears the scoring threshold (the enter threshold)
+/// for `confirm_ms`, and only ends after confidence stays below `exit_threshold`
+/// for `end_grace_ms`, so confidence hovering near the threshold does not churn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HysteresisConfig {
+    /// Confidence below which an active call starts ending
+    pub exit_threshold: f32,
+    /// How long a candidate must keep clearing the enter threshold before the call starts
+    pub confirm_ms: u64,
+    /// How long an ending call may recover before it ends
+    pub end_grace_ms: u64,
+    /// Minimum time a call stays active once started
+    pub min_call_ms: u64,
+}
+
+impl Default for HysteresisConfig {
+    fn default() -> Self {
+        HysteresisConfig {
+            exit_threshold: 0.25,
+            confirm_ms: 1000,
+            end_grace_ms: 2000,
+            min_call_ms: 0,
+        }
+    }
+}
+
+/// Where the tracked call is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallPhase {
+    /// No call and no candidate
+    Idle,
+    /// A candidate cleared the enter threshold but is not confirmed yet (not reported)
+    Suspected { process_id: u32, since: SystemTime },
+    /// Reported call
+    Active { process_id: u32, since: SystemTime },
+    /// Reported call whose confidence dropped below the exit threshold
+    Ending { process_id: u32, since: SystemTime, ending_since: SystemTime },
+}
+
+/// The strongest call candidate seen this tick
+#[derive(Debug, Clone, Copy)]
+pub struct CallCandidate {
+    pub process_id: u32,
+    pub confidence: f32,
+}
+
 /// Correlation engine for multi-signal fusion
 pub struct CorrelationEngine {
+    scoring: ScoringConfig,
+    hysteresis: HysteresisConfig,
+    phase: CallPhase,
+    titles: TitleMatcher,
+    script: Option<RuleScript>,
+
     // Known media sites to filter out
     media_sites: Vec<String>,
 
@@ -51,6 +424,11 @@ pub struct CorrelationEngine {
 impl CorrelationEngine {
     pub fn new() -> Self {
         CorrelationEngine {
+            scoring: ScoringConfig::default(),
+            hysteresis: HysteresisConfig::default(),
+            phase: CallPhase::Idle,
+            titles: TitleMatcher::new(&BTreeMap::new()),
+            script: None,
             media_sites: vec![
                 "youtube".to_string(),
                 "netflix".to_string(),
@@ -72,90 +450,388 @@ impl CorrelationEngine {
         }
     }
 
+    pub fn with_scoring(mut self, scoring: ScoringConfig) -> Self {
+        self.set_scoring(scoring);
+        self
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: HysteresisConfig) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Replace the scoring at runtime; the call phase is kept
+    pub fn set_scoring(&mut self, scoring: ScoringConfig) {
+        self.titles = TitleMatcher::new(&scoring.title_keywords);
+        self.script = RuleScript::from_scoring(&scoring);
+        self.scoring = scoring;
+    }
+
+    pub fn set_hysteresis(&mut self, hysteresis: HysteresisConfig) {
+        self.hysteresis = hysteresis;
+    }
+
+    /// Enter the active phase for a call resumed from a previous run
+    pub fn resume(&mut self, process_id: u32, since: SystemTime) {
+        self.phase = CallPhase::Active { process_id, since };
+    }
+
+    pub fn scoring(&self) -> &ScoringConfig {
+        &self.scoring
+    }
+
+    pub fn phase(&self) -> CallPhase {
+        self.phase
+    }
+
+    /// Advance the call lifecycle by one tick
+    /// While Idle/Suspected, `candidate` is the first source that `detect_call` reports as
+    /// a call; while Active/Ending it is the tracked call's own score, or None if its
+    /// signals are gone.
+    pub fn update_phase(&mut self, candidate: Option<CallCandidate>, now: SystemTime) -> CallPhase {
+        let confirm = Duration::from_millis(self.hysteresis.confirm_ms);
+        let end_grace = Duration::from_millis(self.hysteresis.end_grace_ms);
+        let min_call = Duration::from_millis(self.hysteresis.min_call_ms);
+        let elapsed = |since: SystemTime| now.duration_since(since).unwrap_or(Duration::ZERO);
+
+        self.phase = match (self.phase, candidate) {
+            (CallPhase::Idle, Some(c)) => {
+                if confirm.is_zero() {
+                    CallPhase::Active { process_id: c.process_id, since: now }
+                } else {
+                    CallPhase::Suspected { process_id: c.process_id, since: now }
+                }
+            }
+            (CallPhase::Idle, None) => CallPhase::Idle,
+
+            (CallPhase::Suspected { process_id, since }, Some(c)) if c.process_id == process_id => {
+                if elapsed(since) >= confirm {
+                    CallPhase::Active { process_id, since }
+                } else {
+                    CallPhase::Suspected { process_id, since }
+                }
+            }
+            // A different process took over: start confirming it instead
+            (CallPhase::Suspected { .. }, Some(c)) => CallPhase::Suspected { process_id: c.process_id, since: now },
+            (CallPhase::Suspected { .. }, None) => CallPhase::Idle,
+
+            (CallPhase::Active { process_id, since }, Some(c))
+                if c.process_id == process_id && c.confidence >= self.hysteresis.exit_threshold =>
+            {
+                CallPhase::Active { process_id, since }
+            }
+            (CallPhase::Active { process_id, since }, _) => CallPhase::Ending { process_id, since, ending_since: now },
+
+            (CallPhase::Ending { process_id, since, .. }, Some(c))
+                if c.process_id == process_id && c.confidence >= self.hysteresis.exit_threshold =>
+            {
+                CallPhase::Active { process_id, since }
+            }
+            (CallPhase::Ending { process_id, since, ending_since }, _) => {
+                if elapsed(ending_since) >= end_grace && elapsed(since) >= min_call {
+                    CallPhase::Idle
+                } else {
+                    CallPhase::Ending { process_id, since, ending_since }
+                }
+            }
+        };
+
+        self.phase
+    }
+
     /// Main detection logic with confidence scoring
     pub fn detect_call(&self, signal: &MultiSignal) -> DetectionResult {
+        let scoring = self.scoring.resolve(signal);
         let mut confidence = 0.0;
         let mut reasons = Vec::new();
+        let mut rules = Vec::new();
+
+        let signals = SignalValues {
+            process_id: signal.process_id,
+            process_name: signal.process_name.clone(),
+            window_title: signal.window_title.clone(),
+            detected_app: signal.detected_app.clone(),
+            tab_url: signal.tab_url.clone(),
+            has_audio_output: signal.has_audio_output,
+            audio_peak_level: signal.audio_peak_level,
+            is_virtual_device: signal.is_virtual_device,
+            has_webrtc_connection: signal.has_webrtc_connection,
+            has_peer_connection: signal.has_peer_connection,
+            has_sip_media: signal.has_sip_media,
+            client_in_call: signal.client_in_call,
+            in_scheduled_meeting: signal.in_scheduled_meeting,
+            audio_active_secs: signal.audio_active_secs,
+            contributions: signal.contributions.clone(),
+            has_mic_active: signal.has_mic_active,
+            mic_peak_level: signal.mic_peak_level,
+            mic_idle: signal.mic_idle,
+            process_load: signal.process_load,
+            duration_secs: signal.duration.as_secs_f64(),
+            threshold: scoring.threshold,
+        };
+        let kind = if signal.has_sip_media && !signal.has_webrtc_connection && !signal.has_peer_connection {
+            CallKind::Sip
+        } else if signal.detected_app.as_deref() == Some("Slack") {
+            // Slack's only calls are huddles
+            CallKind::Huddle
+        } else {
+            CallKind::Meeting
+        };
+
+        // RULE 0: Deployment denylist overrides every other signal
+        let is_ignored = self.scoring.is_ignored(&signal.process_name, &signal.window_title);
+        rules.push(RuleTrace::gate("ignored_process", is_ignored));
+        if is_ignored {
+            return DetectionResult {
+                is_call: false,
+                confidence: 0.0,
+                signal_type: SignalType::Unknown,
+                kind,
+                reasons: vec!["Process is on the ignore list".to_string()],
+                rules,
+                signals,
+            };
+        }
+
+        // RULE 0a: Apps outside `tracked_apps` are never scored
+        let is_untracked =
+            !self.scoring.is_tracked(signal.detected_app.as_deref(), &signal.process_name, &signal.window_title);
+        rules.push(RuleTrace::gate("untracked_app", is_untracked));
+        if is_untracked {
+            return DetectionResult {
+                is_call: false,
+                confidence: 0.0,
+                signal_type: SignalType::Unknown,
+                kind,
+                reasons: vec!["App is not in tracked_apps".to_string()],
+                rules,
+                signals,
+            };
+        }
+
+        // RULE 0b: System sounds, and notification blips without network or client evidence
+        let has_evidence = signal.has_webrtc_connection
+            || signal.has_peer_connection
+            || signal.has_sip_media
+            || signal.client_in_call == Some(true);
+        let is_blip = is_system_sound_process(&signal.process_name)
+            || (!has_evidence
+                && signal.audio_active_secs.is_some_and(|secs| secs < self.scoring.min_audio_activity_secs));
+        rules.push(RuleTrace::gate("brief_audio", is_blip));
+        if is_blip {
+            return DetectionResult {
+                is_call: false,
+                confidence: 0.0,
+                signal_type: SignalType::Unknown,
+                kind,
+                reasons: vec!["System sound or audio shorter than min_audio_activity_secs".to_string()],
+                rules,
+                signals,
+            };
+        }
 
-        // RULE 1: Must be a known call app
-        if !self.is_call_app(&signal.process_name, &signal.window_title, &signal.detected_app) {
+        // RULE 1: Must be a known call app (or force-tracked)
+        let is_call_app = self.is_tracked_app(signal);
+        rules.push(RuleTrace::gate("call_app", is_call_app));
+        if !is_call_app {
             return DetectionResult {
                 is_call: false,
                 confidence: 0.0,
                 signal_type: SignalType::Unknown,
+                kind,
                 reasons: vec!["Not a known call app".to_string()],
+                rules,
+                signals,
+            };
+        }
+
+        // RULE 1b: The app's own call state is authoritative when we have it
+        if let Some(in_call) = signal.client_in_call {
+            rules.push(RuleTrace::weighted("client_state", in_call, 1.0));
+            return DetectionResult {
+                is_call: in_call,
+                confidence: if in_call { 1.0 } else { 0.0 },
+                signal_type: if in_call { SignalType::MeetingCall } else { SignalType::Unknown },
+                kind,
+                reasons: vec![if in_call { "App reports a call" } else { "App reports no call" }.to_string()],
+                rules,
+                signals,
             };
         }
 
         // RULE 2: Filter out media playback (YouTube, Netflix, etc.)
-        if self.is_media_site(&signal.window_title) {
+        let is_media = self.is_media_site(&signal.window_title)
+            || signal.tab_url.as_deref().map_or(false, |url| self.is_media_site(url));
+        rules.push(RuleTrace::gate("media_site", is_media));
+        if is_media {
             return DetectionResult {
                 is_call: false,
                 confidence: 0.0,
                 signal_type: SignalType::MediaPlayback,
+                kind,
                 reasons: vec!["Media playback site detected".to_string()],
+                rules,
+                signals,
             };
         }
 
         // RULE 3: Check for voice notes (mic only, no incoming audio, short duration)
-        if self.is_voice_note(signal) {
+        let is_voice_note = self.is_voice_note(signal);
+        rules.push(RuleTrace::gate("voice_note", is_voice_note));
+        if is_voice_note {
             return DetectionResult {
                 is_call: false,
                 confidence: 0.3,
                 signal_type: SignalType::VoiceNote,
+                kind,
                 reasons: vec!["Voice note pattern detected".to_string()],
+                rules,
+                signals,
             };
         }
 
         // SIGNAL SCORING: Multi-source confidence fusion
 
         // Core signal: Audio output (someone speaking to you)
-        if signal.has_audio_output && signal.audio_peak_level > 0.001 {
-            confidence += 0.40;
+        let audio_active = signal.has_audio_output && signal.audio_peak_level > 0.001;
+        rules.push(RuleTrace::weighted("audio_output", audio_active, scoring.audio_weight));
+        if audio_active {
+            confidence += scoring.audio_weight;
             reasons.push("Audio output active".to_string());
         }
 
-        // Strong signal: WebRTC connection (definitive proof of call)
-        if signal.has_webrtc_connection {
-            confidence += 0.35;
+        // Load stands in for audio gone quiet for a moment: a meeting keeps its client busy
+        let is_busy = signal.process_load.is_some_and(|load| {
+            load.cpu_percent >= BUSY_CPU_PERCENT || load.gpu_percent.is_some_and(|gpu| gpu >= BUSY_GPU_PERCENT)
+        });
+        let busy_while_silent = is_busy && !audio_active;
+        rules.push(RuleTrace::weighted("media_load", busy_while_silent, self.scoring.load_weight));
+        if busy_while_silent {
+            confidence += self.scoring.load_weight;
+            reasons.push("Call app busy while its audio is silent".to_string());
+        }
+
+        // Audio routed only to a virtual cable or loopback counts for less
+        let to_virtual = audio_active && signal.is_virtual_device;
+        rules.push(RuleTrace::weighted("virtual_device", to_virtual, -self.scoring.virtual_device_penalty));
+        if to_virtual {
+            confidence -= self.scoring.virtual_device_penalty;
+            reasons.push("Audio output goes to a virtual device".to_string());
+        }
+
+        // Strong signal: WebRTC connection (definitive proof of call); SIP with RTP media
+        // is the softphone equivalent
+        let has_webrtc = signal.has_webrtc_connection || signal.has_peer_connection || signal.has_sip_media;
+        rules.push(RuleTrace::weighted("webrtc", has_webrtc, scoring.webrtc_weight));
+        if signal.has_peer_connection {
+            confidence += scoring.webrtc_weight;
+            reasons.push("Active PeerConnection in browser event log".to_string());
+        } else if signal.has_webrtc_connection {
+            confidence += scoring.webrtc_weight;
             reasons.push("WebRTC connection detected".to_string());
+        } else if signal.has_sip_media {
+            confidence += scoring.webrtc_weight;
+            reasons.push("SIP signalling with RTP media".to_string());
         }
 
         // Supporting signal: Microphone active
+        rules.push(RuleTrace::weighted("microphone", signal.has_mic_active, scoring.mic_weight));
         if signal.has_mic_active {
-            confidence += 0.15;
+            confidence += scoring.mic_weight;
             reasons.push("Microphone active".to_string());
+        } else if signal.mic_idle {
+            reasons.push("Microphone held open but silent".to_string());
         } else {
             // Even without mic, can still be a call if user muted
             // But we need stronger signals
             reasons.push("Microphone muted/off".to_string());
         }
+        rules.push(RuleTrace::gate("mic_idle", signal.mic_idle));
+
+        // Supporting signal: the recording carries speech, not just an open mic
+        let is_speaking = signal.has_mic_active && signal.mic_peak_level.is_some_and(|peak| peak > SPEECH_PEAK);
+        rules.push(RuleTrace::weighted("mic_speech", is_speaking, self.scoring.mic_speech_weight));
+        if is_speaking {
+            confidence += self.scoring.mic_speech_weight;
+            reasons.push("Speech on the microphone".to_string());
+        }
 
-        // Metadata signal: Window title confirms call
-        if self.window_title_confirms_call(&signal.window_title) {
-            confidence += 0.10;
+        // Metadata signal: Window title confirms call, or a messenger's call window is open
+        let title_confirms = self.window_title_confirms_call(&signal.window_title)
+            || messengers::profile_for(&signal.process_name, signal.detected_app.as_deref())
+                .is_some_and(|profile| profile.shows_call(&signal.window_title));
+        rules.push(RuleTrace::weighted("window_title", title_confirms, scoring.title_weight));
+        if title_confirms {
+            confidence += scoring.title_weight;
             reasons.push("Window title confirms meeting".to_string());
         }
 
+        // Metadata signal: a meeting on the user's calendar is under way
+        rules.push(RuleTrace::weighted("calendar", signal.in_scheduled_meeting, self.scoring.calendar_weight));
+        if signal.in_scheduled_meeting {
+            confidence += self.scoring.calendar_weight;
+            reasons.push("Calendar meeting under way".to_string());
+        }
+
+        // Integrator evidence, one rule per contribution
+        for contribution in &signal.contributions {
+            rules.push(RuleTrace::weighted(&format!("provider:{}", contribution.provider), true, contribution.weight));
+            confidence += contribution.weight;
+            reasons.push(format!("{}: {}", contribution.provider, contribution.reason));
+        }
+
         // Time-based validation (only for ongoing calls, not new ones)
         // Don't penalize new calls (duration = 0)
-        if signal.duration > Duration::from_secs(1) && signal.duration < Duration::from_secs(5) {
+        let is_short = signal.duration > Duration::from_secs(1) && signal.duration < Duration::from_secs(5);
+        if is_short {
             // Very short events are likely false positives (but not brand new calls)
-            confidence *= 0.7;
+            let penalized = confidence * 0.7;
+            rules.push(RuleTrace::weighted("short_duration", true, penalized - confidence));
+            confidence = penalized;
             reasons.push("Short duration - reduced confidence".to_string());
+        } else {
+            rules.push(RuleTrace::weighted("short_duration", false, 0.0));
+        }
+
+        // Deployment script: last word on what the rules above could not express
+        let mut is_vetoed = false;
+        if let Some(script) = &self.script {
+            match script.evaluate(&signals, confidence) {
+                ScriptVerdict::Keep => rules.push(RuleTrace::weighted("script", false, 0.0)),
+                ScriptVerdict::Boost(boost) => {
+                    rules.push(RuleTrace::weighted("script", true, boost));
+                    confidence += boost;
+                    reasons.push("Adjusted by the rule script".to_string());
+                }
+                ScriptVerdict::Veto => {
+                    rules.push(RuleTrace::gate("script_veto", true));
+                    is_vetoed = true;
+                    reasons.push("Vetoed by the rule script".to_string());
+                }
+            }
         }
 
-        // Determine if this is a call
-        // Use relaxed threshold to match old logic behavior
-        // Old logic: if (has_mic && has_audio && is_call_app) = detect
-        // New scoring: Audio(40%) + Mic(15%) = 55%, so use 45% threshold
-        let is_call = confidence >= 0.45; // 45% threshold (matches old logic)
+        // Determine if this is a call (default 45% threshold matches old logic)
+        let mut is_call = confidence >= scoring.threshold && !is_vetoed;
+
+        // Per-app rule: e.g. browser-based Meet only counts with a WebRTC connection
+        if scoring.require_webrtc {
+            let blocked = is_call && !has_webrtc;
+            rules.push(RuleTrace::gate("require_webrtc", blocked));
+            if blocked {
+                is_call = false;
+                reasons.push("WebRTC required for this app but not detected".to_string());
+            }
+        }
 
         DetectionResult {
             is_call,
             confidence,
             signal_type: if is_call { SignalType::MeetingCall } else { SignalType::Unknown },
+            kind,
             reasons,
+            rules,
+            signals,
         }
     }
 
@@ -165,29 +841,48 @@ impl CorrelationEngine {
         // 1. Mic is active (recording)
         // 2. NO incoming audio (not listening to others)
         // 3. No WebRTC connection (not a peer-to-peer call)
-        // 4. Usually short duration (<2 minutes)
+        // 4. Usually short (each messenger's longest note, see messengers.rs)
+
+        let messenger = messengers::profile_for(&signal.process_name, signal.detected_app.as_deref());
+        // The messenger's call window is a call, ringing or not
+        if messenger.is_some_and(|profile| profile.shows_call(&signal.window_title)) {
+            return false;
+        }
 
         let has_outgoing_only = signal.has_mic_active && !signal.has_audio_output;
-        let no_webrtc = !signal.has_webrtc_connection;
-        let is_short = signal.duration < Duration::from_secs(120);
+        let no_webrtc = !signal.has_webrtc_connection && !signal.has_peer_connection && !signal.has_sip_media;
 
         // Voice note pattern
         if has_outgoing_only && no_webrtc {
             return true;
         }
 
-        // Also check for very short mic-only sessions in WhatsApp/Slack
-        if is_short && signal.has_mic_active && !signal.has_webrtc_connection {
-            if let Some(app) = &signal.detected_app {
-                if app.to_lowercase().contains("whatsapp") || app.to_lowercase().contains("slack") {
-                    return true;
-                }
+        // Also mic sessions in a messenger no longer than its voice notes
+        if let Some(profile) = messenger {
+            let is_short = signal.duration < Duration::from_secs(profile.max_voice_note_secs);
+            if is_short && signal.has_mic_active && no_webrtc {
+                return true;
             }
         }
 
         false
     }
 
+    /// Classify an audio source that is not the active call
+    /// Media wins over a call app: a browser playing YouTube is media even if Meet is open.
+    pub fn classify_source(&self, process_name: &str, window_title: &str, is_call_app: bool) -> SourceClass {
+        let name = process_name.to_lowercase();
+        if self.is_media_site(window_title) || self.is_media_site(&name) {
+            SourceClass::Media
+        } else if is_call_app {
+            SourceClass::CallCandidate
+        } else if is_system_sound_process(&name) {
+            SourceClass::SystemSound
+        } else {
+            SourceClass::Unknown
+        }
+    }
+
     /// Check if this is a media playback site
     fn is_media_site(&self, window_title: &str) -> bool {
         let lower_title = window_title.to_lowercase();
@@ -201,6 +896,14 @@ impl CorrelationEngine {
         false
     }
 
+    /// Known call app, on the force-track list, or a softphone in a SIP call
+    fn is_tracked_app(&self, signal: &MultiSignal) -> bool {
+        signal.has_sip_media
+            || self.is_call_app(&signal.process_name, &signal.window_title, &signal.detected_app)
+            || messengers::profile_for(&signal.process_name, signal.detected_app.as_deref()).is_some()
+            || self.scoring.is_force_tracked(&signal.process_name, &signal.window_title)
+    }
+
     /// Check if this is a known call app
     fn is_call_app(&self, process_name: &str, window_title: &str, detected_app: &Option<String>) -> bool {
         let combined = format!(
@@ -219,28 +922,9 @@ impl CorrelationEngine {
         false
     }
 
-    /// Check if window title confirms a meeting is happening
+    /// Check if window title confirms a meeting is happening, in any UI language
     fn window_title_confirms_call(&self, window_title: &str) -> bool {
-        let lower_title = window_title.to_lowercase();
-
-        // Meeting-specific keywords in window titles
-        let meeting_keywords = [
-            "meeting",
-            "call with",
-            "video call",
-            "zoom meeting",
-            "teams meeting",
-            " meet ",
-            "conference",
-        ];
-
-        for keyword in &meeting_keywords {
-            if lower_title.contains(keyword) {
-                return true;
-            }
-        }
-
-        false
+        self.titles.confirms_call(window_title)
     }
 
     /// Enhanced call detection that handles mic/camera off scenarios
@@ -255,12 +939,18 @@ impl CorrelationEngine {
         // 3. Microphone still active
 
         // First check: Must still be a known call app
-        if !self.is_call_app(&signal.process_name, &signal.window_title, &signal.detected_app) {
+        if self.scoring.is_ignored(&signal.process_name, &signal.window_title) || !self.is_tracked_app(signal) {
             return false;
         }
 
-        // Strong signal: WebRTC still connected AND (audio or mic active)
-        if signal.has_webrtc_connection && (signal.has_audio_output || signal.has_mic_active) {
+        // The app's own call state overrides the signal heuristics
+        if let Some(in_call) = signal.client_in_call {
+            return in_call;
+        }
+
+        // Strong signal: WebRTC (or SIP media) still connected AND (audio or mic active)
+        let has_webrtc = signal.has_webrtc_connection || signal.has_peer_connection || signal.has_sip_media;
+        if has_webrtc && (signal.has_audio_output || signal.has_mic_active) {
             return true;
         }
 
@@ -283,26 +973,151 @@ impl CorrelationEngine {
 mod tests {
     use super::*;
 
+    /// An app holding the mic and playing audio, with no other evidence
+    fn signal(process_name: &str, window_title: &str, detected_app: &str) -> MultiSignal {
+        MultiSignal {
+            process_id: 42,
+            process_name: process_name.to_string(),
+            window_title: window_title.to_string(),
+            has_mic_active: true,
+            mic_peak_level: None,
+            mic_idle: false,
+            process_load: None,
+            has_audio_output: true,
+            audio_peak_level: 0.5,
+            is_virtual_device: false,
+            has_webrtc_connection: false,
+            webrtc_started_at: None,
+            has_peer_connection: false,
+            has_sip_media: false,
+            client_in_call: None,
+            in_scheduled_meeting: false,
+            audio_active_secs: None,
+            contributions: Vec::new(),
+            detected_app: Some(detected_app.to_string()),
+            tab_url: None,
+            duration: Duration::from_secs(0),
+        }
+    }
+
     #[test]
     fn test_voice_note_detection() {
         let engine = CorrelationEngine::new();
 
         let voice_note_signal = MultiSignal {
-            process_id: 1234,
-            process_name: "WhatsApp.exe".to_string(),
-            window_title: "WhatsApp".to_string(),
-            has_mic_active: true,
             has_audio_output: false,
             audio_peak_level: 0.0,
-            has_webrtc_connection: false,
-            webrtc_started_at: None,
-            detected_app: Some("WhatsApp".to_string()),
             duration: Duration::from_secs(30),
+            ..signal("WhatsApp.exe", "WhatsApp", "WhatsApp")
         };
 
         assert!(engine.is_voice_note(&voice_note_signal));
     }
 
+    #[test]
+    fn test_messenger_call_windows() {
+        let engine = CorrelationEngine::new();
+
+        // A Telegram voice message plays back while recording, yet stays a note...
+        let telegram = MultiSignal {
+            audio_peak_level: 0.2,
+            duration: Duration::from_secs(30),
+            ..signal("Telegram.exe", "Telegram (2)", "Telegram")
+        };
+        assert!(engine.is_voice_note(&telegram));
+        // ...while its group call panel is a call, without any WebRTC evidence
+        let voice_chat = MultiSignal { window_title: "Voice Chat".to_string(), ..telegram };
+        assert!(!engine.is_voice_note(&voice_chat));
+        let result = engine.detect_call(&voice_chat);
+        assert!(result.is_call && result.rules.iter().any(|rule| rule.rule == "window_title" && rule.matched));
+
+        // A browser tab named after Signal is not the Signal app
+        let tab = MultiSignal {
+            process_name: "chrome.exe".to_string(),
+            window_title: "Signal Call - Google Chrome".to_string(),
+            detected_app: None,
+            ..voice_chat
+        };
+        assert!(!engine.detect_call(&tab).is_call);
+    }
+
+    #[test]
+    fn test_app_override_requires_webrtc() {
+        let mut scoring = ScoringConfig::default();
+        scoring.apps.insert(
+            "google meet".to_string(),
+            AppScoringOverride { require_webrtc: true, ..Default::default() },
+        );
+        let engine = CorrelationEngine::new().with_scoring(scoring);
+
+        let mut signal = signal("chrome.exe", "Google Meet - Standup", "Google Meet");
+        assert!(!engine.detect_call(&signal).is_call);
+
+        signal.has_webrtc_connection = true;
+        assert!(engine.detect_call(&signal).is_call);
+    }
+
+    #[test]
+    fn test_client_state_is_authoritative() {
+        let engine = CorrelationEngine::new();
+        let mut signal = MultiSignal {
+            has_webrtc_connection: true,
+            client_in_call: Some(false),
+            ..signal("ms-teams.exe", "Microsoft Teams", "Microsoft Teams")
+        };
+        assert!(!engine.detect_call(&signal).is_call);
+        assert!(!engine.should_maintain_call(&signal, true));
+
+        signal.has_audio_output = false;
+        signal.has_webrtc_connection = false;
+        signal.client_in_call = Some(true);
+        assert!(engine.detect_call(&signal).is_call);
+        assert!(engine.should_maintain_call(&signal, true));
+    }
+
+    #[test]
+    fn test_rule_weights_sum_to_confidence() {
+        let engine = CorrelationEngine::new();
+
+        let mut signal = MultiSignal { duration: Duration::from_secs(3), ..signal("Zoom.exe", "Zoom Meeting", "Zoom") };
+        let result = engine.detect_call(&signal);
+
+        let total: f32 = result.rules.iter().map(|rule| rule.weight).sum();
+        assert!((total - result.confidence).abs() < 1e-6);
+        assert!(result.rules.iter().any(|rule| rule.rule == "short_duration" && rule.matched));
+
+        // Same call played into a virtual cable
+        signal.is_virtual_device = true;
+        let routed = engine.detect_call(&signal);
+        let total: f32 = routed.rules.iter().map(|rule| rule.weight).sum();
+        assert!((total - routed.confidence).abs() < 1e-6);
+        assert!(routed.confidence < result.confidence);
+    }
+
+    #[test]
+    fn test_hysteresis_ignores_flapping_confidence() {
+        let mut engine = CorrelationEngine::new();
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let candidate = |confidence: f32| Some(CallCandidate { process_id: 7, confidence });
+
+        // Needs to be seen for confirm_ms before it is reported
+        assert!(matches!(engine.update_phase(candidate(0.55), at(0)), CallPhase::Suspected { .. }));
+        assert!(matches!(engine.update_phase(candidate(0.55), at(500)), CallPhase::Suspected { .. }));
+        assert!(matches!(engine.update_phase(candidate(0.55), at(1000)), CallPhase::Active { .. }));
+
+        // Dipping below the enter threshold but above the exit threshold keeps the call
+        assert!(matches!(engine.update_phase(candidate(0.40), at(1500)), CallPhase::Active { .. }));
+
+        // Briefly losing the signals only starts ending, and recovering resumes the call
+        assert!(matches!(engine.update_phase(None, at(2000)), CallPhase::Ending { .. }));
+        assert!(matches!(engine.update_phase(candidate(0.55), at(2500)), CallPhase::Active { since, .. } if since == at(0)));
+
+        assert!(matches!(engine.update_phase(None, at(3000)), CallPhase::Ending { .. }));
+        assert!(matches!(engine.update_phase(None, at(4500)), CallPhase::Ending { .. }));
+        assert_eq!(engine.update_phase(None, at(5000)), CallPhase::Idle);
+    }
+
     #[test]
     fn test_youtube_filtering() {
         let engine = CorrelationEngine::new();
@@ -311,4 +1126,66 @@ mod tests {
         assert!(engine.is_media_site("Netflix - Watch TV Shows"));
         assert!(!engine.is_media_site("Google Meet - Meeting"));
     }
+
+    #[test]
+    fn test_notification_blips_never_start_a_call() {
+        let engine = CorrelationEngine::new();
+        let ping = MultiSignal {
+            audio_peak_level: 0.3,
+            audio_active_secs: Some(1.0),
+            ..signal("Zoom.exe", "Zoom Workplace", "Zoom")
+        };
+        assert!(!engine.detect_call(&ping).is_call);
+
+        // Sustained audio, or a media connection, goes through the normal scoring
+        assert!(engine.detect_call(&MultiSignal { audio_active_secs: Some(5.0), ..ping.clone() }).is_call);
+        assert!(engine.detect_call(&MultiSignal { has_webrtc_connection: true, ..ping.clone() }).is_call);
+        assert!(!engine.detect_call(&MultiSignal { process_name: "audiodg.exe".to_string(), ..ping }).is_call);
+    }
+
+    #[test]
+    fn test_speech_on_the_mic_outweighs_an_open_mic() {
+        let engine = CorrelationEngine::new();
+        let holding = MultiSignal {
+            mic_peak_level: Some(0.001),
+            audio_peak_level: 0.3,
+            ..signal("Zoom.exe", "Zoom Workplace", "Zoom")
+        };
+        let speaking = engine.detect_call(&MultiSignal { mic_peak_level: Some(0.2), ..holding.clone() });
+        let holding = engine.detect_call(&holding);
+
+        let speech_rule = |result: &DetectionResult| result.rules.iter().find(|rule| rule.rule == "mic_speech").unwrap().matched;
+        assert!(speech_rule(&speaking) && !speech_rule(&holding));
+        assert!((speaking.confidence - holding.confidence - ScoringConfig::default().mic_speech_weight).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_busy_client_backs_up_silent_audio() {
+        let engine = CorrelationEngine::new();
+        let quiet = MultiSignal {
+            process_load: Some(ProcessLoad { cpu_percent: 38.0, gpu_percent: None }),
+            audio_peak_level: 0.0,
+            ..signal("Zoom.exe", "Zoom Meeting", "Zoom")
+        };
+        let load_rule = |signal: &MultiSignal| {
+            engine.detect_call(signal).rules.into_iter().find(|rule| rule.rule == "media_load").unwrap().matched
+        };
+
+        assert!(load_rule(&quiet));
+        // An idle client window, or one whose audio already counts
+        let idle = Some(ProcessLoad { cpu_percent: 0.4, gpu_percent: Some(0.0) });
+        assert!(!load_rule(&MultiSignal { process_load: idle, ..quiet.clone() }));
+        assert!(!load_rule(&MultiSignal { audio_peak_level: 0.3, ..quiet }));
+    }
+
+    #[test]
+    fn test_source_classification() {
+        let engine = CorrelationEngine::new();
+
+        assert_eq!(engine.classify_source("Spotify.exe", "Daft Punk - Around the World", false), SourceClass::Media);
+        assert_eq!(engine.classify_source("chrome", "YouTube - Google Chrome", true), SourceClass::Media);
+        assert_eq!(engine.classify_source("slack", "Huddle", true), SourceClass::CallCandidate);
+        assert_eq!(engine.classify_source("explorer.exe", "", false), SourceClass::SystemSound);
+        assert_eq!(engine.classify_source("vlc", "movie.mkv", false), SourceClass::Unknown);
+    }
 }
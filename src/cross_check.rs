@@ -0,0 +1,188 @@
+// Cross-check diagnostics
+// Independently derives "calls according to audio" and "calls according to network"
+// each tick and reports where the two sensors disagree
+
+use crate::network_monitor::WebRTCSignal;
+use crate::AudioSource;
+use std::collections::BTreeMap;
+
+/// What a single sensor believes about one process
+#[derive(Debug, Clone)]
+struct SensorView {
+    process_id: u32,
+    detail: String,
+}
+
+/// Which sensor saw a call the other one missed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Disagreement {
+    AudioOnly,
+    NetworkOnly,
+}
+
+/// Tracks sensor disagreements across ticks so each one is reported once
+/// when it appears and once when it resolves
+pub struct CrossCheck {
+    open: BTreeMap<String, (Disagreement, u64)>,
+    tick: u64,
+    agree_ticks: u64,
+    disagree_ticks: u64,
+}
+
+impl CrossCheck {
+    pub fn new() -> Self {
+        CrossCheck {
+            open: BTreeMap::new(),
+            tick: 0,
+            agree_ticks: 0,
+            disagree_ticks: 0,
+        }
+    }
+
+    /// Compare both sensors for this tick and log changes to stderr
+    pub fn run(
+        &mut self,
+        audio_sources: &[AudioSource],
+        mic_sources: &[AudioSource],
+        webrtc_signals: &[WebRTCSignal],
+    ) {
+        self.tick += 1;
+
+        let audio_calls = audio_derived_calls(audio_sources, mic_sources);
+        let network_calls = network_derived_calls(webrtc_signals);
+
+        let mut current = BTreeMap::new();
+        for (key, view) in &audio_calls {
+            if !network_calls.contains_key(key) {
+                current.insert(key.clone(), (Disagreement::AudioOnly, view.clone()));
+            }
+        }
+        for (key, view) in &network_calls {
+            if !audio_calls.contains_key(key) {
+                current.insert(key.clone(), (Disagreement::NetworkOnly, view.clone()));
+            }
+        }
+
+        if current.is_empty() {
+            self.agree_ticks += 1;
+        } else {
+            self.disagree_ticks += 1;
+        }
+
+        // Newly opened disagreements
+        for (key, (kind, view)) in &current {
+            let is_new = match self.open.get(key) {
+                Some((open_kind, _)) => open_kind != kind,
+                None => true,
+            };
+
+            if is_new {
+                let (seen_by, missed_by) = match kind {
+                    Disagreement::AudioOnly => ("audio", "network"),
+                    Disagreement::NetworkOnly => ("network", "audio"),
+                };
+                eprintln!(
+                    "[cross-check] {} sees a call in '{}' (pid {}) that {} does not | {}",
+                    seen_by, key, view.process_id, missed_by, view.detail
+                );
+                self.open.insert(key.clone(), (*kind, self.tick));
+            }
+        }
+
+        // Disagreements that resolved this tick
+        let resolved: Vec<String> = self
+            .open
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned()
+            .collect();
+
+        for key in resolved {
+            if let Some((_, opened_at)) = self.open.remove(&key) {
+                eprintln!(
+                    "[cross-check] '{}' sensors agree again after {} tick(s) | agreement so far: {:.0}%",
+                    key,
+                    self.tick - opened_at,
+                    self.agreement_ratio() * 100.0
+                );
+            }
+        }
+    }
+
+    /// Fraction of ticks where both sensors agreed
+    fn agreement_ratio(&self) -> f32 {
+        let total = self.agree_ticks + self.disagree_ticks;
+        if total == 0 {
+            return 1.0;
+        }
+        self.agree_ticks as f32 / total as f32
+    }
+}
+
+/// Calls according to audio only: a call app playing audio while the same app holds the mic
+fn audio_derived_calls(
+    audio_sources: &[AudioSource],
+    mic_sources: &[AudioSource],
+) -> BTreeMap<String, SensorView> {
+    let mut calls = BTreeMap::new();
+
+    for audio_src in audio_sources {
+        let detected = match &audio_src.detected_app {
+            Some(detected) => detected,
+            None => continue,
+        };
+
+        let is_browser = crate::is_browser_process(&audio_src.name);
+        let has_mic = mic_sources.iter().any(|mic_src| match mic_src.process_id {
+            0 if is_browser => crate::is_browser_process(&mic_src.name),
+            0 => mic_src.detected_app.as_ref() == Some(detected),
+            process_id => process_id == audio_src.process_id,
+        });
+
+        if has_mic {
+            calls.insert(
+                process_key(&audio_src.name),
+                SensorView {
+                    process_id: audio_src.process_id,
+                    detail: format!("app: {} | title: {:?}", detected, audio_src.window_title),
+                },
+            );
+        }
+    }
+
+    calls
+}
+
+/// Calls according to network only: WebRTC activity from a call app or browser process
+fn network_derived_calls(webrtc_signals: &[WebRTCSignal]) -> BTreeMap<String, SensorView> {
+    let mut calls = BTreeMap::new();
+
+    for signal in webrtc_signals {
+        let detected = crate::detect_call_app(&signal.process_name, "");
+        let is_browser = crate::is_browser_process(&signal.process_name);
+
+        if detected.is_none() && !is_browser {
+            continue;
+        }
+
+        calls.insert(
+            process_key(&signal.process_name),
+            SensorView {
+                process_id: signal.process_id,
+                detail: format!(
+                    "app: {} | connections: {}",
+                    detected.unwrap_or_else(|| "browser (unattributed)".to_string()),
+                    signal.connection_count
+                ),
+            },
+        );
+    }
+
+    calls
+}
+
+/// Both sensors report PIDs from different processes of the same app
+/// (e.g. browser audio service vs network service), so compare by executable name
+fn process_key(process_name: &str) -> String {
+    process_name.to_lowercase().trim_end_matches(".exe").to_string()
+}
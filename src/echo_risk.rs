@@ -0,0 +1,187 @@
+// Speaker echo risk
+// `echo_risk` on the active call: the call plays through open speakers while the built-in
+// microphone listens, so the other side hears itself back. The recording app uses it to
+// suggest headphones. Needs both ends of the routing:
+//   - the devices the call app's sessions render to (session sensing, validator.rs), else
+//     the default output device
+//   - the capture device the call app records from, else the default one, and whether it
+//     is a built-in mic (laptop array, MacBook microphone, internal-mic port)
+// Muted anywhere (system mic, in-app, output, the app's session) means nothing to echo.
+// Device lists are slow to read (pactl, system_profiler) so they are probed every few seconds.
+
+use crate::audio::{AudioBackend, DeviceUsage, OutputDevice, OutputFormFactor, SystemAudio};
+use crate::call_tracker::{self, Sample};
+use crate::CallInfo;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How often the device lists are read
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Lowercase device names of microphones built into the machine
+const BUILT_IN_MIC_HINTS: &[&str] = &[
+    "built-in", "internal", "microphone array", "mic array", "digital microphone", "macbook", "imac",
+];
+
+/// Default output and capture devices read for the active call
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioRouting {
+    pub process_id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_output: Option<OutputDevice>,
+    pub mic_devices: Vec<DeviceUsage>,
+}
+
+/// Whether a capture device is the machine's own microphone
+/// A headset plugged into the built-in jack reports the headset port, not the internal mic.
+pub fn is_built_in_mic(device: &DeviceUsage) -> bool {
+    match device.form_factor {
+        OutputFormFactor::Headset | OutputFormFactor::Headphones | OutputFormFactor::Bluetooth => false,
+        // Internal-mic ports and built-in transports classify as the machine's speakers
+        OutputFormFactor::Speakers => !device.is_virtual,
+        OutputFormFactor::Hdmi | OutputFormFactor::Unknown => {
+            let name = device.name.to_lowercase();
+            !device.is_virtual && BUILT_IN_MIC_HINTS.iter().any(|hint| name.contains(hint))
+        }
+    }
+}
+
+/// Whether an output device plays into the room
+/// HDMI and DisplayPort audio comes out of a monitor's or TV's speakers.
+fn is_open_speaker(device: &OutputDevice) -> bool {
+    !device.is_virtual && matches!(device.form_factor, OutputFormFactor::Speakers | OutputFormFactor::Hdmi)
+}
+
+/// `echo_risk` for `call` this tick
+pub fn assess(call: &CallInfo, sample: &Sample) -> bool {
+    let Some(routing) = sample.audio_routing.as_ref().filter(|routing| routing.process_id == call.process_id) else {
+        return false;
+    };
+    let levels = &sample.levels;
+    let is_muted = call.in_app_muted == Some(true)
+        || levels.mic.is_some_and(|mic| mic.is_muted)
+        || levels.output.is_some_and(|output| output.is_muted)
+        || levels.sessions.get(&call.process_id).is_some_and(|session| session.is_muted);
+    if !call.has_mic || !call.has_audio || is_muted {
+        return false;
+    }
+
+    let plays_to_speakers = match sample.output_devices.get(&call.process_id) {
+        Some(devices) if !devices.is_empty() => devices.iter().any(is_open_speaker),
+        _ => routing.default_output.as_ref().is_some_and(is_open_speaker),
+    };
+
+    let mic_users: Vec<&str> = sample
+        .mic_sources
+        .iter()
+        .filter(|src| call_tracker::is_same_app(src, call.process_id, &call.app))
+        .map(|src| src.name.as_str())
+        .collect();
+    let mic = routing
+        .mic_devices
+        .iter()
+        .find(|device| device.apps.iter().any(|app| mic_users.contains(&app.as_str())))
+        .or_else(|| routing.mic_devices.iter().find(|device| device.is_default));
+
+    plays_to_speakers && mic.is_some_and(is_built_in_mic)
+}
+
+/// Throttled device reads for the active call
+#[derive(Debug, Default)]
+pub struct RoutingProbe {
+    last: Option<(Instant, AudioRouting)>,
+}
+
+impl RoutingProbe {
+    /// Read the default output and the capture devices for `call` (the call active at the
+    /// start of the tick)
+    pub fn probe(&mut self, call: Option<&CallInfo>) -> Option<AudioRouting> {
+        let call = call?;
+        if let Some((at, routing)) = &self.last {
+            if routing.process_id == call.process_id && at.elapsed() < PROBE_INTERVAL {
+                return Some(routing.clone());
+            }
+        }
+
+        let routing = AudioRouting {
+            process_id: call.process_id,
+            default_output: SystemAudio.get_audio_output_device().ok(),
+            mic_devices: SystemAudio.get_microphone_devices().unwrap_or_default(),
+        };
+        self.last = Some((Instant::now(), routing.clone()));
+        Some(routing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mute_timeline::Level;
+    use crate::AudioSource;
+    use std::collections::BTreeMap;
+
+    fn mic(name: &str, form_factor: OutputFormFactor, apps: &[&str]) -> DeviceUsage {
+        DeviceUsage {
+            name: name.to_string(),
+            form_factor,
+            is_virtual: false,
+            is_default: apps.is_empty(),
+            apps: apps.iter().map(|app| app.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_speakers_with_built_in_mic_risk_echo() {
+        let call = CallInfo { window_title: "Zoom Meeting".to_string(), ..crate::test_call("Zoom", 7) };
+        let speakers = OutputDevice {
+            name: "MacBook Pro Speakers".to_string(),
+            form_factor: OutputFormFactor::Speakers,
+            is_virtual: false,
+        };
+        let sample = Sample {
+            mic_sources: vec![AudioSource {
+                name: "zoom.us".to_string(),
+                process_id: 0,
+                window_title: String::new(),
+                detected_app: Some("Zoom".to_string()),
+                private_context: false,
+                is_virtual_device: false,
+                classification: None,
+                session: None,
+            }],
+            audio_routing: Some(AudioRouting {
+                process_id: 7,
+                default_output: Some(speakers.clone()),
+                mic_devices: vec![
+                    mic("MacBook Pro Microphone", OutputFormFactor::Unknown, &[]),
+                    mic("Jabra Evolve2 65", OutputFormFactor::Headset, &["zoom.us"]),
+                ],
+            }),
+            ..Default::default()
+        };
+
+        // Zoom records from the headset even though the built-in mic is the default
+        assert!(!assess(&call, &sample));
+
+        let mut built_in = sample.clone();
+        if let Some(routing) = built_in.audio_routing.as_mut() {
+            routing.mic_devices[1].apps.clear();
+        }
+        assert!(assess(&call, &built_in));
+
+        // The session's own device wins over the default output
+        let headphones = OutputDevice { form_factor: OutputFormFactor::Headphones, ..speakers };
+        let to_headphones = Sample { output_devices: BTreeMap::from([(7, vec![headphones])]), ..built_in.clone() };
+        assert!(!assess(&call, &to_headphones));
+
+        let muted = Sample {
+            levels: crate::mute_timeline::VolumeLevels {
+                mic: Some(Level { volume: 80.0, is_muted: true }),
+                ..Default::default()
+            },
+            ..built_in
+        };
+        assert!(!assess(&call, &muted));
+    }
+}
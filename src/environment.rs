@@ -0,0 +1,124 @@
+// Remote and virtualized sessions
+// Inside WSL2 the monitor sees only the distro's processes and WSLg's sound server, not
+// the Windows apps holding the call; inside RDP or Citrix sessions audio goes through
+// redirected endpoints ("Remote Audio", "Citrix HDX Audio") whose capture meters read
+// silence. Either way the usual probes come back empty or wrong, and "no call" would be
+// misleading. The environment is detected once at start-up, logged with what it limits,
+// and reported as `environment` in the state snapshot and `health`. Session-level
+// signals keep working there (per-app sessions on the redirected endpoints, sockets,
+// window titles); capture meters are not used where they cannot be trusted.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    /// A desktop session on the machine itself
+    #[default]
+    Local,
+    /// A Linux distro under WSL2
+    Wsl,
+    /// A Remote Desktop (RDP or xrdp) session
+    Rdp,
+    /// A Citrix ICA/HDX session
+    Citrix,
+}
+
+impl Environment {
+    pub fn is_local(&self) -> bool {
+        *self == Environment::Local
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Environment::Local => "local",
+            Environment::Wsl => "wsl",
+            Environment::Rdp => "rdp",
+            Environment::Citrix => "citrix",
+        }
+    }
+
+    /// Whether capture session meters reflect the microphone (redirected endpoints report
+    /// silence, which would read as muted in the app)
+    pub fn meters_capture(&self) -> bool {
+        matches!(self, Environment::Local | Environment::Wsl)
+    }
+
+    /// What the monitor cannot see here
+    pub fn limitation(&self) -> Option<&'static str> {
+        match self {
+            Environment::Local => None,
+            Environment::Wsl => Some("Windows apps are not visible from WSL; run the Windows build to detect their calls"),
+            Environment::Rdp | Environment::Citrix => {
+                Some("audio goes through redirected devices; capture meters and in-app mute are unavailable")
+            }
+        }
+    }
+}
+
+/// The environment this process runs in
+pub fn detect() -> Environment {
+    platform_detect().unwrap_or_default()
+}
+
+#[cfg(target_os = "windows")]
+fn platform_detect() -> Option<Environment> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_REMOTESESSION};
+
+    if let Some(environment) = std::env::var("SESSIONNAME").ok().and_then(|name| from_session_name(&name)) {
+        return Some(environment);
+    }
+    // SESSIONNAME is missing in services and some shells; the session flag is not
+    (unsafe { GetSystemMetrics(SM_REMOTESESSION) } != 0).then_some(Environment::Rdp)
+}
+
+#[cfg(target_os = "linux")]
+fn platform_detect() -> Option<Environment> {
+    let osrelease = std::fs::read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default();
+    if std::env::var_os("WSL_DISTRO_NAME").is_some() || is_wsl_kernel(&osrelease) {
+        Some(Environment::Wsl)
+    } else if std::env::var_os("XRDP_SESSION").is_some() {
+        Some(Environment::Rdp)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn platform_detect() -> Option<Environment> {
+    None
+}
+
+/// Windows SESSIONNAME: "Console" locally, "RDP-Tcp#3" over RDP, "ICA-CGP#12" under Citrix
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn from_session_name(name: &str) -> Option<Environment> {
+    let name = name.to_ascii_uppercase();
+    if name.starts_with("RDP-") {
+        Some(Environment::Rdp)
+    } else if name.starts_with("ICA-") {
+        Some(Environment::Citrix)
+    } else {
+        None
+    }
+}
+
+/// WSL kernels carry "microsoft" in their release ("5.15.153.1-microsoft-standard-WSL2")
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_wsl_kernel(osrelease: &str) -> bool {
+    osrelease.to_ascii_lowercase().contains("microsoft")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_sessions_are_recognized() {
+        assert_eq!(from_session_name("RDP-Tcp#3"), Some(Environment::Rdp));
+        assert_eq!(from_session_name("ICA-CGP#12"), Some(Environment::Citrix));
+        assert_eq!(from_session_name("Console"), None);
+        assert!(is_wsl_kernel("5.15.153.1-microsoft-standard-WSL2\n"));
+        assert!(!is_wsl_kernel("6.8.0-45-generic\n"));
+        assert!(!Environment::Rdp.meters_capture());
+    }
+}
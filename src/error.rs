@@ -0,0 +1,137 @@
+// Crate-wide error type for the audio and platform backends
+// Callers and the `health` report tell "PulseAudio not running" from "permission denied"
+// by variant (or by ErrorKind once serialized) instead of matching on message strings.
+// Library errors convert with `?`: io::Error, procfs and windows::core::Error are sorted
+// into variants by their error code; bare strings become Platform.
+
+use serde::Serialize;
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum ValidatorError {
+    /// The audio server, display server or OS service is not running or not installed
+    #[error("{0} is not available")]
+    BackendUnavailable(String),
+    /// The OS refused access (TCC, process rights, --no-subprocess)
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    /// A tool or API answered in an unexpected format
+    #[error("could not parse {0}")]
+    ParseFailure(String),
+    #[error("{0} timed out")]
+    Timeout(String),
+    /// No such process, window or device
+    #[error("{0} not found")]
+    NotFound(String),
+    /// Any other OS API failure
+    #[error("{0}")]
+    Platform(String),
+}
+
+/// The variant of a ValidatorError, for reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    BackendUnavailable,
+    PermissionDenied,
+    ParseFailure,
+    Timeout,
+    NotFound,
+    Platform,
+}
+
+impl ValidatorError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ValidatorError::BackendUnavailable(_) => ErrorKind::BackendUnavailable,
+            ValidatorError::PermissionDenied(_) => ErrorKind::PermissionDenied,
+            ValidatorError::ParseFailure(_) => ErrorKind::ParseFailure,
+            ValidatorError::Timeout(_) => ErrorKind::Timeout,
+            ValidatorError::NotFound(_) => ErrorKind::NotFound,
+            ValidatorError::Platform(_) => ErrorKind::Platform,
+        }
+    }
+
+    /// Failure to run an external tool: a missing binary means the backend is unavailable
+    pub fn spawn(program: &str, error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => ValidatorError::BackendUnavailable(program.to_string()),
+            _ => error.into(),
+        }
+    }
+}
+
+impl From<io::Error> for ValidatorError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied => ValidatorError::PermissionDenied(error.to_string()),
+            io::ErrorKind::TimedOut => ValidatorError::Timeout(error.to_string()),
+            io::ErrorKind::NotFound => ValidatorError::NotFound(error.to_string()),
+            io::ErrorKind::InvalidData => ValidatorError::ParseFailure(error.to_string()),
+            _ => ValidatorError::Platform(error.to_string()),
+        }
+    }
+}
+
+impl From<String> for ValidatorError {
+    fn from(message: String) -> Self {
+        ValidatorError::Platform(message)
+    }
+}
+
+impl From<&str> for ValidatorError {
+    fn from(message: &str) -> Self {
+        ValidatorError::Platform(message.to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<procfs::ProcError> for ValidatorError {
+    fn from(error: procfs::ProcError) -> Self {
+        match error {
+            procfs::ProcError::PermissionDenied(_) => ValidatorError::PermissionDenied(error.to_string()),
+            procfs::ProcError::NotFound(_) => ValidatorError::NotFound(error.to_string()),
+            procfs::ProcError::Incomplete(_) => ValidatorError::ParseFailure(error.to_string()),
+            procfs::ProcError::Io(error, _) => error.into(),
+            _ => ValidatorError::Platform(error.to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl From<windows::core::Error> for ValidatorError {
+    fn from(error: windows::core::Error) -> Self {
+        use windows::core::HRESULT;
+        use windows::Win32::Foundation::{E_ACCESSDENIED, ERROR_NOT_FOUND, ERROR_TIMEOUT, RPC_E_TIMEOUT};
+        use windows::Win32::Media::Audio::AUDCLNT_E_SERVICE_NOT_RUNNING;
+
+        let code = error.code();
+        let message = error.to_string();
+        if code == E_ACCESSDENIED {
+            ValidatorError::PermissionDenied(message)
+        } else if code == AUDCLNT_E_SERVICE_NOT_RUNNING {
+            ValidatorError::BackendUnavailable(format!("Windows Audio service ({})", message))
+        } else if code == HRESULT::from_win32(ERROR_NOT_FOUND.0) {
+            ValidatorError::NotFound(message)
+        } else if code == RPC_E_TIMEOUT || code == HRESULT::from_win32(ERROR_TIMEOUT.0) {
+            ValidatorError::Timeout(message)
+        } else {
+            ValidatorError::Platform(message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_errors_keep_their_kind() {
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "'ss' not run: subprocesses are disabled");
+        assert_eq!(ValidatorError::from(denied).kind(), ErrorKind::PermissionDenied);
+        let missing = io::Error::new(io::ErrorKind::NotFound, "No such file or directory");
+        assert_eq!(ValidatorError::from(missing).kind(), ErrorKind::NotFound);
+        assert_eq!(ValidatorError::from("COM call failed").kind(), ErrorKind::Platform);
+    }
+}
@@ -0,0 +1,307 @@
+// Delta events between two monitor states
+// Used by `--stream-mode events` so consumers only see what changed
+
+use crate::call_quality::CallQuality;
+use crate::correlation_engine::SourceClass;
+use crate::mute_timeline::MuteChange;
+use crate::output::{Envelope, EventType};
+use crate::{AudioSource, CallInfo, MonitorState};
+use serde::Serialize;
+use std::time::SystemTime;
+
+/// Minimum confidence change worth reporting (5 percentage points)
+const CONFIDENCE_CHANGE_THRESHOLD: f32 = 0.05;
+
+/// Payload for `call_ended`
+#[derive(Debug, Clone, Serialize)]
+pub struct CallEndedPayload {
+    pub app: String,
+    pub process_id: u32,
+    pub window_title: String,
+    /// Local "HH:MM:SS"; written with --legacy-timestamps only
+    #[serde(skip_serializing_if = "crate::output::omit_legacy_timestamp")]
+    pub started_at: String,
+    /// RFC3339 in UTC
+    pub started_at_utc: String,
+    pub ended_at_utc: String,
+    /// UTC offset of the local time zone when the call started
+    pub local_timezone: String,
+    pub duration: String,
+    pub duration_secs: u64,
+    /// Mute and volume changes during the call (filled in by the tracker)
+    pub mute_timeline: Vec<MuteChange>,
+    /// Media quality over the whole call (see call_quality.rs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<CallQuality>,
+}
+
+/// Payload for `confidence_changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfidenceChangedPayload {
+    pub app: String,
+    pub process_id: u32,
+    pub previous: f32,
+    pub current: f32,
+}
+
+/// Payload for `config_reloaded`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReloadedPayload {
+    pub path: String,
+    /// Top-level sections that changed ("scoring", "polling", ...)
+    pub sections: Vec<String>,
+}
+
+/// Payload for `media_playback_during_call`
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaPlaybackPayload {
+    pub call_app: String,
+    pub call_process_id: u32,
+    /// The source classified as media
+    pub app: String,
+    pub process_id: u32,
+    pub window_title: String,
+    /// Seconds into the call when it started playing (or when the call started over it)
+    pub offset_secs: u64,
+}
+
+/// What an `audio_marker` marks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioMarkerKind {
+    /// Rolling energy hash of the last 3.3 s
+    Hash,
+    SpeechStart,
+    SilenceStart,
+}
+
+impl AudioMarkerKind {
+    /// Same spelling as the JSON output
+    #[cfg_attr(not(feature = "grpc"), allow(dead_code))] // only gRPC needs it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioMarkerKind::Hash => "hash",
+            AudioMarkerKind::SpeechStart => "speech_start",
+            AudioMarkerKind::SilenceStart => "silence_start",
+        }
+    }
+}
+
+/// Payload for `audio_marker` (see audio_markers.rs)
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioMarkerPayload {
+    /// The active call's process
+    pub process_id: u32,
+    pub kind: AudioMarkerKind,
+    /// Capture time of the marked audio, RFC 3339 in UTC with milliseconds
+    pub at: String,
+    pub at_ms: u64,
+    /// Eight hex digits, for `hash`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+/// A single change between two ticks
+#[derive(Debug, Clone)]
+pub enum MonitorEvent {
+    CallStarted(CallInfo),
+    CallEnded(CallEndedPayload),
+    ConfidenceChanged(ConfidenceChangedPayload),
+    SourceAdded(AudioSource),
+    SourceRemoved(AudioSource),
+    ConfigReloaded(ConfigReloadedPayload),
+    MediaPlaybackDuringCall(MediaPlaybackPayload),
+    /// Only produced with the `capture` feature
+    #[cfg_attr(not(feature = "capture"), allow(dead_code))]
+    AudioMarker(AudioMarkerPayload),
+}
+
+impl MonitorEvent {
+    pub fn event_type(&self) -> EventType {
+        match self {
+            MonitorEvent::CallStarted(_) => EventType::CallStarted,
+            MonitorEvent::CallEnded(_) => EventType::CallEnded,
+            MonitorEvent::ConfidenceChanged(_) => EventType::ConfidenceChanged,
+            MonitorEvent::SourceAdded(_) => EventType::SourceAdded,
+            MonitorEvent::SourceRemoved(_) => EventType::SourceRemoved,
+            MonitorEvent::ConfigReloaded(_) => EventType::ConfigReloaded,
+            MonitorEvent::MediaPlaybackDuringCall(_) => EventType::MediaPlaybackDuringCall,
+            MonitorEvent::AudioMarker(_) => EventType::AudioMarker,
+        }
+    }
+
+    /// Serialize inside the versioned envelope as one NDJSON line
+    pub fn to_json_line(&self) -> serde_json::Result<String> {
+        let event_type = self.event_type();
+        match self {
+            MonitorEvent::CallStarted(payload) => Envelope::new(event_type, payload).to_json_line(),
+            MonitorEvent::CallEnded(payload) => Envelope::new(event_type, payload).to_json_line(),
+            MonitorEvent::ConfidenceChanged(payload) => Envelope::new(event_type, payload).to_json_line(),
+            MonitorEvent::SourceAdded(payload) => Envelope::new(event_type, payload).to_json_line(),
+            MonitorEvent::SourceRemoved(payload) => Envelope::new(event_type, payload).to_json_line(),
+            MonitorEvent::ConfigReloaded(payload) => Envelope::new(event_type, payload).to_json_line(),
+            MonitorEvent::MediaPlaybackDuringCall(payload) => Envelope::new(event_type, payload).to_json_line(),
+            MonitorEvent::AudioMarker(payload) => Envelope::new(event_type, payload).to_json_line(),
+        }
+    }
+}
+
+/// Compute the events that turn `previous` into `current` at time `now`
+pub fn diff_states(previous: &MonitorState, current: &MonitorState, now: SystemTime) -> Vec<MonitorEvent> {
+    let mut events = Vec::new();
+
+    match (&previous.active_call, &current.active_call) {
+        (None, Some(call)) => {
+            events.push(MonitorEvent::CallStarted(call.clone()));
+        }
+        (Some(prev_call), None) => {
+            events.push(call_ended(prev_call, now));
+        }
+        (Some(prev_call), Some(call)) => {
+            if prev_call.process_id != call.process_id || prev_call.app != call.app {
+                // A different call replaced the previous one within a single tick
+                events.push(call_ended(prev_call, now));
+                events.push(MonitorEvent::CallStarted(call.clone()));
+            } else if (call.confidence - prev_call.confidence).abs() >= CONFIDENCE_CHANGE_THRESHOLD {
+                events.push(MonitorEvent::ConfidenceChanged(ConfidenceChangedPayload {
+                    app: call.app.clone(),
+                    process_id: call.process_id,
+                    previous: prev_call.confidence,
+                    current: call.confidence,
+                }));
+            }
+        }
+        (None, None) => {}
+    }
+
+    for source in &current.other_audio_sources {
+        if !previous.other_audio_sources.iter().any(|prev| same_source(prev, source)) {
+            events.push(MonitorEvent::SourceAdded(source.clone()));
+        }
+    }
+
+    for source in &previous.other_audio_sources {
+        if !current.other_audio_sources.iter().any(|cur| same_source(cur, source)) {
+            events.push(MonitorEvent::SourceRemoved(source.clone()));
+        }
+    }
+
+    // Media already playing alongside the same call was reported when it started
+    if let Some(call) = &current.active_call {
+        let is_same_call = previous
+            .active_call
+            .as_ref()
+            .is_some_and(|prev| prev.process_id == call.process_id && prev.app == call.app);
+        let is_media = |source: &AudioSource| source.classification == Some(SourceClass::Media);
+        for source in current.other_audio_sources.iter().filter(|source| is_media(source)) {
+            let was_playing = is_same_call
+                && previous.other_audio_sources.iter().any(|prev| same_source(prev, source) && is_media(prev));
+            if !was_playing {
+                events.push(MonitorEvent::MediaPlaybackDuringCall(MediaPlaybackPayload {
+                    call_app: call.app.clone(),
+                    call_process_id: call.process_id,
+                    app: source.name.clone(),
+                    process_id: source.process_id,
+                    window_title: source.window_title.clone(),
+                    offset_secs: crate::call_duration_secs(call, now),
+                }));
+            }
+        }
+    }
+
+    events
+}
+
+fn call_ended(call: &CallInfo, now: SystemTime) -> MonitorEvent {
+    // From the stored start time, so calls spanning midnight or DST changes stay accurate
+    let duration_secs = crate::call_duration_secs(call, now);
+    MonitorEvent::CallEnded(CallEndedPayload {
+        app: call.app.clone(),
+        process_id: call.process_id,
+        window_title: call.window_title.clone(),
+        started_at: call.started_at.clone(),
+        started_at_utc: call.started_at_utc.clone(),
+        ended_at_utc: crate::output::utc_timestamp(now),
+        local_timezone: call.local_timezone.clone(),
+        duration: crate::format_duration(duration_secs),
+        duration_secs,
+        mute_timeline: Vec::new(),
+        quality: call.quality.clone(),
+    })
+}
+
+fn same_source(a: &AudioSource, b: &AudioSource) -> bool {
+    a.process_id == b.process_id && a.name == b.name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(app: &str, process_id: u32, confidence: f32) -> CallInfo {
+        CallInfo {
+            has_webrtc: false,
+            confidence,
+            call_started_system_time: SystemTime::now(),
+            ..crate::test_call(app, process_id)
+        }
+    }
+
+    fn state(active_call: Option<CallInfo>) -> MonitorState {
+        MonitorState {
+            active_call,
+            other_audio_sources: Vec::new(),
+            environment: Default::default(),
+            tracked_apps: None,
+            external_calls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_no_events_when_unchanged() {
+        let previous = state(Some(call("Zoom", 10, 0.55)));
+        let current = state(Some(call("Zoom", 10, 0.57)));
+
+        assert!(diff_states(&previous, &current, SystemTime::now()).is_empty());
+    }
+
+    #[test]
+    fn test_call_replaced_within_tick() {
+        let previous = state(Some(call("Zoom", 10, 0.55)));
+        let current = state(Some(call("Slack", 20, 0.90)));
+
+        let types: Vec<EventType> = diff_states(&previous, &current, SystemTime::now())
+            .iter()
+            .map(|event| event.event_type())
+            .collect();
+
+        assert_eq!(types, vec![EventType::CallEnded, EventType::CallStarted]);
+    }
+
+    #[test]
+    fn test_media_during_call_reported_once() {
+        let spotify = AudioSource {
+            name: "Spotify.exe".to_string(),
+            process_id: 30,
+            window_title: "Daft Punk - Around the World".to_string(),
+            detected_app: None,
+            private_context: false,
+            is_virtual_device: false,
+            classification: Some(SourceClass::Media),
+            session: None,
+        };
+        let with_music = |active_call| MonitorState { other_audio_sources: vec![spotify.clone()], ..state(active_call) };
+        let media_events = |previous: &MonitorState, current: &MonitorState| {
+            diff_states(previous, current, SystemTime::now())
+                .iter()
+                .filter(|event| event.event_type() == EventType::MediaPlaybackDuringCall)
+                .count()
+        };
+
+        // Playing before the call counts once the call starts, then not again
+        assert_eq!(media_events(&with_music(None), &with_music(Some(call("Zoom", 10, 0.9)))), 1);
+        assert_eq!(media_events(&with_music(Some(call("Zoom", 10, 0.9))), &with_music(Some(call("Zoom", 10, 0.9)))), 0);
+        assert_eq!(media_events(&state(Some(call("Zoom", 10, 0.9))), &with_music(Some(call("Zoom", 10, 0.9)))), 1);
+        assert_eq!(media_events(&state(None), &with_music(None)), 0);
+    }
+}
@@ -0,0 +1,300 @@
+// `export` subcommand: call history as flat tables for pandas, duckdb or a spreadsheet
+// Reads the same NDJSON logs as `report` and writes two tables into the output directory:
+//   calls     one row per call session (app, start, end, duration)
+//   snapshots one row per `state` snapshot (call flag, app, pid, confidence, signals)
+//
+//   rust-audio-validator export <log or dir>... --format csv|parquet [--from 2024-05-01] [--to 2024-05-31] [--out dir]
+//
+// Dates are inclusive and compare against the local start time of each call or snapshot.
+// Parquet output needs the `parquet` feature; timestamps are written as local (not UTC
+// adjusted) millisecond timestamps.
+
+use crate::report;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+const USAGE: &str = "Usage: rust-audio-validator export <log or dir>... --format csv|parquet [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--out dir]";
+
+/// Values of one table column; None is a missing value
+enum Column {
+    Timestamp(Vec<NaiveDateTime>),
+    Text(Vec<Option<String>>),
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Bool(Vec<Option<bool>>),
+}
+
+impl Column {
+    fn len(&self) -> usize {
+        match self {
+            Column::Timestamp(values) => values.len(),
+            Column::Text(values) => values.len(),
+            Column::Int(values) => values.len(),
+            Column::Float(values) => values.len(),
+            Column::Bool(values) => values.len(),
+        }
+    }
+
+    fn csv_cell(&self, row: usize) -> String {
+        let text = |value: Option<String>| value.unwrap_or_default();
+        match self {
+            Column::Timestamp(values) => values[row].format("%Y-%m-%dT%H:%M:%S").to_string(),
+            Column::Text(values) => report::csv_field(values[row].as_deref().unwrap_or_default()),
+            Column::Int(values) => text(values[row].map(|v| v.to_string())),
+            Column::Float(values) => text(values[row].map(|v| v.to_string())),
+            Column::Bool(values) => text(values[row].map(|v| v.to_string())),
+        }
+    }
+}
+
+/// Named columns of equal length
+struct Table {
+    name: &'static str,
+    columns: Vec<(&'static str, Column)>,
+}
+
+impl Table {
+    fn rows(&self) -> usize {
+        self.columns.first().map(|(_, column)| column.len()).unwrap_or(0)
+    }
+
+    fn to_csv(&self) -> String {
+        let names: Vec<&str> = self.columns.iter().map(|(name, _)| *name).collect();
+        let mut out = format!("{}\n", names.join(","));
+        for row in 0..self.rows() {
+            let cells: Vec<String> = self.columns.iter().map(|(_, column)| column.csv_cell(row)).collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Inclusive date range from `--from` / `--to`
+#[derive(Debug, Clone, Copy, Default)]
+struct DateRange {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    fn contains(&self, at: NaiveDateTime) -> bool {
+        self.from.is_none_or(|from| at.date() >= from) && self.to.is_none_or(|to| at.date() <= to)
+    }
+}
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+
+    let format = option("--format").map(|s| s.as_str()).unwrap_or("csv");
+    if !matches!(format, "csv" | "parquet") {
+        eprintln!("[export] Unknown --format '{}', expected csv or parquet", format);
+        return 2;
+    }
+    if format == "parquet" && !cfg!(feature = "parquet") {
+        eprintln!("[export] --format parquet needs a build with the `parquet` feature");
+        return 2;
+    }
+
+    let mut range = DateRange::default();
+    for (flag, bound) in [("--from", &mut range.from), ("--to", &mut range.to)] {
+        if let Some(value) = option(flag) {
+            match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                Ok(date) => *bound = Some(date),
+                Err(_) => {
+                    eprintln!("[export] Invalid {} '{}', expected YYYY-MM-DD", flag, value);
+                    return 2;
+                }
+            }
+        }
+    }
+
+    let inputs = report::positional_args(args, &["--format", "--from", "--to", "--out"]);
+    if inputs.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+    let out_dir = Path::new(option("--out").map(|s| s.as_str()).unwrap_or("."));
+
+    let tables = report::load_calls(&inputs).and_then(|calls| {
+        let logs = report::read_logs(&inputs)?;
+        Ok([calls_table(&calls, range), snapshots_table(&logs, range)])
+    });
+    let tables = match tables {
+        Ok(tables) => tables,
+        Err(e) => {
+            eprintln!("[export] {}", e);
+            return 2;
+        }
+    };
+
+    if let Err(e) = fs::create_dir_all(out_dir) {
+        eprintln!("[export] cannot create {}: {}", out_dir.display(), e);
+        return 1;
+    }
+    for table in &tables {
+        let path = out_dir.join(format!("{}.{}", table.name, format));
+        let written = match format {
+            "parquet" => write_parquet(table, &path),
+            _ => fs::write(&path, table.to_csv()).map_err(|e| e.to_string()),
+        };
+        match written {
+            Ok(()) => println!("[export] {} rows -> {}", table.rows(), path.display()),
+            Err(e) => {
+                eprintln!("[export] cannot write {}: {}", path.display(), e);
+                return 1;
+            }
+        }
+    }
+    0
+}
+
+fn calls_table(calls: &[report::CallRecord], range: DateRange) -> Table {
+    let calls: Vec<&report::CallRecord> = calls.iter().filter(|call| range.contains(call.start)).collect();
+    Table {
+        name: "calls",
+        columns: vec![
+            ("app", Column::Text(calls.iter().map(|call| Some(call.app.clone())).collect())),
+            ("started_at", Column::Timestamp(calls.iter().map(|call| call.start).collect())),
+            ("ended_at", Column::Timestamp(calls.iter().map(|call| call.end).collect())),
+            ("duration_secs", Column::Int(calls.iter().map(|call| Some(call.duration_secs())).collect())),
+        ],
+    }
+}
+
+fn snapshots_table(logs: &[String], range: DateRange) -> Table {
+    let mut snapshots: Vec<(NaiveDateTime, Value)> = logs
+        .iter()
+        .flat_map(|contents| report::parse_entries(contents))
+        .filter(|(event_type, at, _)| event_type == "state" && range.contains(*at))
+        .map(|(_, at, payload)| (at, payload))
+        .collect();
+    snapshots.sort_by_key(|(at, _)| *at);
+
+    let call = |field: &str| -> Vec<Value> {
+        snapshots.iter().map(|(_, state)| state["active_call"][field].clone()).collect()
+    };
+    Table {
+        name: "snapshots",
+        columns: vec![
+            ("timestamp", Column::Timestamp(snapshots.iter().map(|(at, _)| *at).collect())),
+            ("in_call", Column::Bool(snapshots.iter().map(|(_, state)| Some(!state["active_call"].is_null())).collect())),
+            ("app", Column::Text(call("app").iter().map(|v| v.as_str().map(String::from)).collect())),
+            ("process_id", Column::Int(call("process_id").iter().map(Value::as_i64).collect())),
+            ("confidence", Column::Float(call("confidence").iter().map(Value::as_f64).collect())),
+            ("has_mic", Column::Bool(call("has_mic").iter().map(Value::as_bool).collect())),
+            ("has_audio", Column::Bool(call("has_audio").iter().map(Value::as_bool).collect())),
+            ("has_webrtc", Column::Bool(call("has_webrtc").iter().map(Value::as_bool).collect())),
+            (
+                "other_sources",
+                Column::Int(
+                    snapshots
+                        .iter()
+                        .map(|(_, state)| Some(state["other_audio_sources"].as_array().map_or(0, |s| s.len() as i64)))
+                        .collect(),
+                ),
+            ),
+        ],
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(table: &Table, path: &Path) -> Result<(), String> {
+    use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    // Missing values are the None entries; definition level 0 marks them
+    fn present<T: Clone>(values: &[Option<T>]) -> (Vec<T>, Vec<i16>) {
+        let levels = values.iter().map(|v| v.is_some() as i16).collect();
+        (values.iter().flatten().cloned().collect(), levels)
+    }
+
+    let fields: Vec<String> = table
+        .columns
+        .iter()
+        .map(|(name, column)| match column {
+            Column::Timestamp(_) => format!("required int64 {} (TIMESTAMP(MILLIS,false));", name),
+            Column::Text(_) => format!("optional binary {} (UTF8);", name),
+            Column::Int(_) => format!("optional int64 {};", name),
+            Column::Float(_) => format!("optional double {};", name),
+            Column::Bool(_) => format!("optional boolean {};", name),
+        })
+        .collect();
+    let schema = parse_message_type(&format!("message {} {{ {} }}", table.name, fields.join(" ")))
+        .map_err(|e| e.to_string())?;
+
+    let file = fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(WriterProperties::builder().build()))
+        .map_err(|e| e.to_string())?;
+    let mut row_group = writer.next_row_group().map_err(|e| e.to_string())?;
+    for (_, column) in &table.columns {
+        let mut out = row_group
+            .next_column()
+            .map_err(|e| e.to_string())?
+            .ok_or("schema has fewer columns than the table")?;
+        let written = match column {
+            Column::Timestamp(values) => {
+                let millis: Vec<i64> = values.iter().map(|at| at.and_utc().timestamp_millis()).collect();
+                out.typed::<Int64Type>().write_batch(&millis, None, None)
+            }
+            Column::Text(values) => {
+                let (values, levels) = present(values);
+                let bytes: Vec<ByteArray> = values.iter().map(|v| ByteArray::from(v.as_str())).collect();
+                out.typed::<ByteArrayType>().write_batch(&bytes, Some(&levels), None)
+            }
+            Column::Int(values) => {
+                let (values, levels) = present(values);
+                out.typed::<Int64Type>().write_batch(&values, Some(&levels), None)
+            }
+            Column::Float(values) => {
+                let (values, levels) = present(values);
+                out.typed::<DoubleType>().write_batch(&values, Some(&levels), None)
+            }
+            Column::Bool(values) => {
+                let (values, levels) = present(values);
+                out.typed::<BoolType>().write_batch(&values, Some(&levels), None)
+            }
+        };
+        written.map_err(|e| e.to_string())?;
+        out.close().map_err(|e| e.to_string())?;
+    }
+    row_group.close().map_err(|e| e.to_string())?;
+    writer.close().map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_table: &Table, _path: &Path) -> Result<(), String> {
+    Err("built without the `parquet` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_table_flattens_state_lines() {
+        let log = [
+            r#"{"event_type":"state","timestamp":"2024-05-02T10:00:00+00:00","payload":{"active_call":null,"other_audio_sources":[]}}"#,
+            r#"{"event_type":"state","timestamp":"2024-05-03T10:00:00+00:00","payload":{"active_call":{"app":"Zoom, Inc","process_id":42,"confidence":0.9,"has_mic":true,"has_audio":true,"has_webrtc":false},"other_audio_sources":[{}]}}"#,
+        ]
+        .join("\n");
+
+        let all = snapshots_table(std::slice::from_ref(&log), DateRange::default());
+        assert_eq!(all.rows(), 2);
+        let csv = all.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp,in_call,app,process_id,confidence,has_mic,has_audio,has_webrtc,other_sources");
+        assert!(lines[1].ends_with(",false,,,,,,,0"));
+        assert!(lines[2].ends_with(",true,\"Zoom, Inc\",42,0.9,true,true,false,1"));
+
+        let from = NaiveDate::from_ymd_opt(2024, 5, 3);
+        let later = snapshots_table(&[log], DateRange { from, to: from });
+        assert_eq!(later.rows(), 1);
+    }
+}
@@ -0,0 +1,325 @@
+// Firefox call attribution
+// Firefox differs from the Chromium browsers in three places:
+//   - processes: content, RDD, socket and utility processes have names of their own
+//     ("Isolated Web Co", "plugin-container" on macOS) and the Linux launcher adds a
+//     "firefox-bin" level, so the process tree roots them at Firefox by name
+//   - window titles: "<page> — Mozilla Firefox" (Nightly, Developer Edition and the
+//     LibreWolf/Floorp/Waterfox forks alike), plus a "Firefox — Sharing Indicator" window
+//     while the mic is shared that names no page. Only the page part is matched, and
+//     the indicator is never taken for Firefox's window.
+//   - tab state, opt-in with `--firefox-remote`: with Firefox started with `--marionette`,
+//     a privileged script lists the tabs sharing the microphone and the PeerConnections
+//     about:webrtc shows. They reach detection as extension tabs (see browser_bridge.rs)
+//     and as live PeerConnections. Tabs of private windows are never read.
+
+use crate::browser_bridge::BrowserTab;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Firefox and its forks, by normalized process name prefix
+const FIREFOXES: &[&str] = &["firefox", "librewolf", "floorp", "waterfox"];
+
+/// Firefox's own helper processes, as truncated by /proc comm
+const HELPERS: &[&str] = &[
+    "web content",
+    "isolated web co",
+    "webextensions",
+    "rdd process",
+    "socket process",
+    "utility process",
+    "privileged cont",
+    "gpu process",
+    "forkserver",
+    "plugin-container",
+];
+
+/// Window title suffixes, lowercase; "mozilla firefox private browsing" starts the same
+const BRANDS: &[&str] = &[
+    "mozilla firefox",
+    "firefox nightly",
+    "firefox developer edition",
+    "librewolf",
+    "floorp",
+    "waterfox",
+];
+
+/// How often the Marionette script runs
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Wait before reconnecting once Firefox is gone or refused the session
+const RECONNECT_AFTER: Duration = Duration::from_secs(10);
+/// Reports not refreshed within this window are ignored
+const STALE_AFTER: Duration = Duration::from_secs(10);
+/// Largest Marionette message accepted
+const MAX_MESSAGE: usize = 4 * 1024 * 1024;
+
+/// Runs in Firefox's chrome context; resolves with the tabs and open PeerConnections
+const STATE_SCRIPT: &str = r#"
+const resolve = arguments[arguments.length - 1];
+const tabs = [];
+for (const win of Services.wm.getEnumerator("navigator:browser")) {
+  const isPrivate = win.document.documentElement.hasAttribute("privatebrowsingmode");
+  for (const tab of win.gBrowser.tabs) {
+    const webrtc = tab._sharingState && tab._sharingState.webRTC;
+    tabs.push({
+      url: isPrivate ? "" : tab.linkedBrowser.currentURI.spec,
+      title: isPrivate ? "" : tab.label,
+      audible: tab.soundPlaying,
+      sharing_mic: !!(webrtc && webrtc.microphone),
+      private: isPrivate,
+    });
+  }
+}
+const done = stats => resolve({
+  tabs,
+  peer_connections: ((stats && stats.reports) || []).filter(report => !report.closed).map(report => report.pcid),
+});
+const pending = WebrtcGlobalInformation.getAllStats(done);
+if (pending && pending.then) pending.then(done, () => done(null));
+"#;
+
+/// Whether `process_name` is Firefox or a fork
+pub fn is_firefox(process_name: &str) -> bool {
+    let name = normalize(process_name);
+    FIREFOXES.iter().any(|firefox| name.starts_with(firefox))
+}
+
+/// Whether `child` is one of the processes of the Firefox running as `parent`
+pub fn same_app(child: &str, parent: &str) -> bool {
+    is_firefox(parent) && (is_firefox(child) || HELPERS.contains(&normalize(child).as_str()))
+}
+
+/// The page part of a Firefox window title; None for the sharing indicator and for
+/// windows showing no page
+pub fn page_title(title: &str) -> Option<&str> {
+    if is_sharing_indicator(title) {
+        return None;
+    }
+    let page = [" — ", " - "]
+        .iter()
+        .filter_map(|separator| title.rfind(separator).map(|at| (at, at + separator.len())))
+        .max_by_key(|(at, _)| *at)
+        .filter(|(_, brand)| BRANDS.iter().any(|known| title[*brand..].to_lowercase().starts_with(known)))
+        .map_or(title, |(at, _)| &title[..at])
+        .trim();
+    let is_brand = BRANDS.iter().any(|known| page.to_lowercase().starts_with(known));
+    (!page.is_empty() && !is_brand).then_some(page)
+}
+
+/// Whether a Firefox window is the indicator shown while the mic or camera is shared
+pub fn is_sharing_indicator(title: &str) -> bool {
+    title.to_lowercase().contains("sharing indicator")
+}
+
+fn normalize(name: &str) -> String {
+    let name = name.to_lowercase();
+    name.strip_suffix(".exe").unwrap_or(&name).to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct ScriptTab {
+    url: String,
+    title: String,
+    audible: bool,
+    sharing_mic: bool,
+    private: bool,
+}
+
+/// What STATE_SCRIPT resolves with
+#[derive(Debug, Deserialize)]
+struct ScriptState {
+    tabs: Vec<ScriptTab>,
+    /// about:webrtc ids: "<hash> (id=<n> url=<page url>)"
+    peer_connections: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Report {
+    at: Instant,
+    tabs: Vec<BrowserTab>,
+    open_peer_connections: bool,
+}
+
+impl Report {
+    fn new(state: ScriptState) -> Self {
+        let peer_urls: Vec<&str> = state.peer_connections.iter().filter_map(|pcid| peer_connection_url(pcid)).collect();
+        let tabs = state
+            .tabs
+            .into_iter()
+            .enumerate()
+            .filter(|(_, tab)| !tab.private)
+            .map(|(index, tab)| {
+                // A call tab that went quiet still holds its PeerConnection
+                let has_peer = peer_urls.iter().any(|url| without_query(url) == without_query(&tab.url));
+                BrowserTab {
+                    browser: "firefox".to_string(),
+                    id: index as u64,
+                    url: tab.url,
+                    title: tab.title,
+                    audible: tab.audible || has_peer,
+                    capturing_audio: tab.sharing_mic,
+                }
+            })
+            .collect();
+        Report {
+            at: Instant::now(),
+            tabs,
+            open_peer_connections: !peer_urls.is_empty(),
+        }
+    }
+}
+
+/// The page URL in an about:webrtc PeerConnection id
+fn peer_connection_url(pcid: &str) -> Option<&str> {
+    let (_, rest) = pcid.split_once("url=")?;
+    Some(rest.trim_end_matches(')').trim())
+}
+
+fn without_query(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+/// Handle to the Marionette poller
+pub struct FirefoxRemote {
+    report: Arc<Mutex<Option<Report>>>,
+}
+
+impl FirefoxRemote {
+    /// Poll Firefox's Marionette server at `addr` (must be a loopback address) in a
+    /// background thread, reconnecting whenever Firefox restarts
+    pub fn start(addr: SocketAddr) -> Result<Self, String> {
+        if !addr.ip().is_loopback() {
+            return Err(format!("refusing to connect to Marionette on non-loopback address {}", addr));
+        }
+
+        let report = Arc::new(Mutex::new(None));
+        let shared = Arc::clone(&report);
+        thread::spawn(move || {
+            let mut last_error = String::new();
+            loop {
+                if let Err(e) = run_session(addr, &shared) {
+                    // Logged once until it changes: Firefox is often simply not running
+                    if e != last_error {
+                        log::warn!("Firefox Marionette on {}: {}", addr, e);
+                        last_error = e;
+                    }
+                }
+                *shared.lock().unwrap() = None;
+                thread::sleep(RECONNECT_AFTER);
+            }
+        });
+
+        Ok(FirefoxRemote { report })
+    }
+
+    /// Firefox's tabs, outside private windows
+    pub fn tabs(&self) -> Vec<BrowserTab> {
+        self.fresh().map(|report| report.tabs).unwrap_or_default()
+    }
+
+    /// Whether any of Firefox's PeerConnections is open
+    pub fn has_peer_connection(&self) -> bool {
+        self.fresh().is_some_and(|report| report.open_peer_connections)
+    }
+
+    fn fresh(&self) -> Option<Report> {
+        self.report.lock().unwrap().clone().filter(|report| report.at.elapsed() < STALE_AFTER)
+    }
+}
+
+/// One Marionette session, polling until the connection fails
+fn run_session(addr: SocketAddr, report: &Mutex<Option<Report>>) -> Result<(), String> {
+    let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(2)).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(Duration::from_secs(10))).map_err(|e| e.to_string())?;
+    let writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut client = Marionette { reader: BufReader::new(stream), writer, next_id: 0 };
+
+    client.read_message()?; // {"applicationType":"gecko","marionetteProtocol":3}
+    client.command("WebDriver:NewSession", json!({}))?;
+    client.command("Marionette:SetContext", json!({ "value": "chrome" }))?;
+    loop {
+        let result = client.command("WebDriver:ExecuteAsyncScript", json!({ "script": STATE_SCRIPT, "args": [] }))?;
+        let state: ScriptState =
+            serde_json::from_value(result["value"].clone()).map_err(|e| format!("unexpected script result: {}", e))?;
+        *report.lock().unwrap() = Some(Report::new(state));
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Marionette's framing: `<length>:<json>`, commands `[0, id, name, params]`, replies
+/// `[1, id, error, result]`
+struct Marionette {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    next_id: u64,
+}
+
+impl Marionette {
+    fn command(&mut self, name: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let body = json!([0, self.next_id, name, params]).to_string();
+        write!(self.writer, "{}:{}", body.len(), body).map_err(|e| e.to_string())?;
+
+        loop {
+            let reply = self.read_message()?;
+            if reply[0] != 1 || reply[1] != self.next_id {
+                continue;
+            }
+            if !reply[2].is_null() {
+                let message = reply[2]["message"].as_str().unwrap_or("command failed");
+                return Err(format!("{}: {}", name, message));
+            }
+            return Ok(reply[3].clone());
+        }
+    }
+
+    fn read_message(&mut self) -> Result<Value, String> {
+        let mut length = Vec::new();
+        self.reader.read_until(b':', &mut length).map_err(|e| e.to_string())?;
+        let length: usize = std::str::from_utf8(&length)
+            .ok()
+            .and_then(|length| length.trim_end_matches(':').parse().ok())
+            .filter(|length| *length <= MAX_MESSAGE)
+            .ok_or("connection closed or not a Marionette server")?;
+        let mut body = vec![0u8; length];
+        self.reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&body).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_firefox_processes_titles_and_tabs() {
+        assert!(same_app("Isolated Web Co", "firefox"));
+        assert!(same_app("firefox-bin", "firefox"));
+        assert!(same_app("plugin-container", "Firefox Nightly"));
+        assert!(!same_app("Isolated Web Co", "chrome"));
+
+        assert_eq!(page_title("Meet – abc-defg-hij — Mozilla Firefox"), Some("Meet – abc-defg-hij"));
+        assert_eq!(page_title("Meet - abc-defg-hij - Mozilla Firefox Private Browsing"), Some("Meet - abc-defg-hij"));
+        assert_eq!(page_title("Slack - Huddle — LibreWolf"), Some("Slack - Huddle"));
+        assert_eq!(page_title("Firefox — Sharing Indicator"), None);
+        assert_eq!(page_title("Mozilla Firefox"), None);
+
+        let state: ScriptState = serde_json::from_value(json!({
+            "tabs": [
+                {"url": "https://meet.google.com/abc-defg-hij?authuser=0", "title": "Meet", "audible": false, "sharing_mic": false, "private": false},
+                {"url": "", "title": "", "audible": true, "sharing_mic": true, "private": true},
+            ],
+            "peer_connections": ["1f2e3d (id=4294967297 url=https://meet.google.com/abc-defg-hij)"],
+        }))
+        .unwrap();
+        let report = Report::new(state);
+        assert!(report.open_peer_connections);
+        assert_eq!(report.tabs.len(), 1, "private tabs are left out");
+        assert!(report.tabs[0].audible, "the open PeerConnection keeps the muted call tab");
+        assert_eq!(report.tabs[0].call_app(), Some("Google Meet"));
+    }
+}
@@ -0,0 +1,327 @@
+// gRPC API over a localhost endpoint (`grpc` feature)
+// Serves the same data as --stream/--ipc with protobuf types from proto/validator.proto.
+// The tonic server runs on its own tokio runtime thread; the main loop publishes
+// into it and answers forwarded control commands, like the IPC transport.
+
+use crate::call_quality::CallQuality;
+use crate::control::{CommandResult, ControlCommand};
+use crate::events::MonitorEvent;
+use crate::mute_timeline::MuteChange;
+use crate::output::SCHEMA_VERSION;
+use crate::state_store::StateReader;
+use crate::uia::CallControls;
+use crate::{AudioSource, CallInfo, MonitorState};
+use std::net::SocketAddr;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use tokio::sync::{broadcast, oneshot};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status};
+
+pub mod pb {
+    tonic::include_proto!("validator.v1");
+}
+
+use pb::call_validator_server::{CallValidator, CallValidatorServer};
+
+/// Number of events a slow subscriber may lag behind before losing events
+const EVENT_BUFFER: usize = 256;
+
+type CommandRequest = (ControlCommand, oneshot::Sender<CommandResult>);
+
+struct Service {
+    state: StateReader,
+    events: broadcast::Sender<pb::CallEvent>,
+    commands: Mutex<mpsc::Sender<CommandRequest>>,
+}
+
+#[tonic::async_trait]
+impl CallValidator for Service {
+    async fn get_state(
+        &self,
+        _request: Request<pb::GetStateRequest>,
+    ) -> Result<Response<pb::MonitorState>, Status> {
+        Ok(Response::new(to_pb_state(&self.state.snapshot().state)))
+    }
+
+    type StreamEventsStream =
+        std::pin::Pin<Box<dyn tokio_stream::Stream<Item = Result<pb::CallEvent, Status>> + Send>>;
+
+    async fn stream_events(
+        &self,
+        _request: Request<pb::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        // Lagged receivers skip the missed events rather than erroring the stream
+        let stream = BroadcastStream::new(self.events.subscribe())
+            .filter_map(|event| event.ok().map(Ok));
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn send_command(
+        &self,
+        request: Request<pb::ControlCommand>,
+    ) -> Result<Response<pb::CommandReply>, Status> {
+        let command = match pb::control_command::Kind::try_from(request.into_inner().kind) {
+            Ok(pb::control_command::Kind::Status) => ControlCommand::Status,
+            Ok(pb::control_command::Kind::Ping) => ControlCommand::Ping,
+            Ok(pb::control_command::Kind::Pause) => ControlCommand::Pause,
+            Ok(pb::control_command::Kind::Privacy) => ControlCommand::Privacy,
+            Ok(pb::control_command::Kind::Resume) => ControlCommand::Resume,
+            Ok(pb::control_command::Kind::Health) => ControlCommand::Health,
+            Err(_) => return Err(Status::invalid_argument("unknown command kind")),
+        };
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.commands
+            .lock()
+            .unwrap()
+            .send((command, reply_tx))
+            .map_err(|_| Status::unavailable("monitor loop stopped"))?;
+
+        let result = reply_rx
+            .await
+            .map_err(|_| Status::unavailable("monitor loop dropped the command"))?;
+
+        Ok(Response::new(pb::CommandReply {
+            command: result.command,
+            ok: result.ok,
+            message: result.message,
+        }))
+    }
+}
+
+/// Handle held by the main loop
+pub struct GrpcServer {
+    events: broadcast::Sender<pb::CallEvent>,
+    commands: mpsc::Receiver<CommandRequest>,
+}
+
+impl GrpcServer {
+    /// Start serving on `addr` (must be a loopback address); GetState reads `state`
+    pub fn start(addr: SocketAddr, state: StateReader) -> Result<Self, String> {
+        if !addr.ip().is_loopback() {
+            return Err(format!("refusing to serve gRPC on non-loopback address {}", addr));
+        }
+
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        let (command_tx, command_rx) = mpsc::channel();
+
+        let service = Service {
+            state,
+            events: events.clone(),
+            commands: Mutex::new(command_tx),
+        };
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| format!("failed to start tokio runtime: {}", e))?;
+
+        thread::spawn(move || {
+            let result = runtime.block_on(
+                tonic::transport::Server::builder()
+                    .add_service(CallValidatorServer::new(service))
+                    .serve(addr),
+            );
+            if let Err(e) = result {
+                log::warn!("gRPC server stopped: {}", e);
+            }
+        });
+
+        Ok(GrpcServer {
+            events,
+            commands: command_rx,
+        })
+    }
+
+    /// Publish this tick's events to subscribers
+    pub fn publish(&self, events: &[MonitorEvent]) {
+        for event in events {
+            // No subscribers is not an error
+            let _ = self.events.send(to_pb_event(event));
+        }
+    }
+
+    /// Answer commands forwarded from gRPC clients
+    pub fn handle_commands<F>(&self, mut handler: F)
+    where
+        F: FnMut(&ControlCommand) -> CommandResult,
+    {
+        for (command, reply) in self.commands.try_iter() {
+            let _ = reply.send(handler(&command));
+        }
+    }
+}
+
+fn to_pb_state(state: &MonitorState) -> pb::MonitorState {
+    pb::MonitorState {
+        active_call: state.active_call.as_ref().map(to_pb_call),
+        other_audio_sources: state.other_audio_sources.iter().map(to_pb_source).collect(),
+        tracked_apps: state.tracked_apps.as_ref().map(|tracked| pb::TrackedApps {
+            apps: tracked.apps.clone(),
+            untracked_audio_sources: tracked
+                .untracked_audio_sources
+                .iter()
+                .map(|(class, count)| (class.as_str().to_string(), *count))
+                .collect(),
+        }),
+        external_calls: state
+            .external_calls
+            .iter()
+            .map(|call| pb::ExternalCall {
+                device: call.device.clone(),
+                platform: call.platform.clone(),
+                app: call.app.clone(),
+                meeting_identifier: call.meeting_identifier.clone(),
+                since_utc: call.since_utc.clone(),
+                last_seen_utc: call.last_seen_utc.clone(),
+            })
+            .collect(),
+        environment: (!state.environment.is_local()).then(|| state.environment.as_str().to_string()),
+    }
+}
+
+fn to_pb_call(call: &CallInfo) -> pb::CallInfo {
+    pb::CallInfo {
+        app: call.app.clone(),
+        process_id: call.process_id,
+        window_title: call.window_title.clone(),
+        has_mic: call.has_mic,
+        has_audio: call.has_audio,
+        has_webrtc: call.has_webrtc,
+        confidence: call.confidence,
+        started_at: call.started_at.clone(),
+        private_context: call.private_context,
+        duration_secs: call.duration_secs,
+        kind: call.kind.as_str().to_string(),
+        role: call.role.as_str().to_string(),
+        in_app_muted: call.in_app_muted,
+        meeting_is_recorded: call.meeting_is_recorded,
+        controls: call.controls.as_ref().map(to_pb_controls),
+        estimated_participants: call.estimated_participants,
+        meeting_identifier: call.meeting_identifier.clone(),
+        scheduled_meeting: call.scheduled_meeting.as_ref().map(|meeting| pb::ScheduledMeeting {
+            title: meeting.title.clone(),
+            organizer: meeting.organizer.clone(),
+            meeting_identifier: meeting.meeting_identifier.clone(),
+        }),
+        quality: call.quality.as_ref().map(to_pb_quality),
+        echo_risk: call.echo_risk,
+        started_at_utc: call.started_at_utc.clone(),
+        local_timezone: call.local_timezone.clone(),
+    }
+}
+
+fn to_pb_quality(quality: &CallQuality) -> pb::CallQuality {
+    pb::CallQuality {
+        rtt_ms: quality.rtt_ms,
+        avg_rtt_ms: quality.avg_rtt_ms,
+        max_rtt_ms: quality.max_rtt_ms,
+        jitter_ms: quality.jitter_ms,
+        packet_loss_pct: quality.packet_loss_pct,
+        udp_drops: quality.udp_drops,
+        audio_underruns: quality.audio_underruns,
+    }
+}
+
+fn to_pb_controls(controls: &CallControls) -> pb::CallControls {
+    pb::CallControls {
+        mic_muted: controls.mic_muted,
+        camera_on: controls.camera_on,
+        participants: controls.participants,
+        call_timer: controls.call_timer.clone(),
+        presenting: controls.presenting,
+        host_controls: controls.host_controls,
+    }
+}
+
+fn to_pb_source(source: &AudioSource) -> pb::AudioSource {
+    pb::AudioSource {
+        name: source.name.clone(),
+        process_id: source.process_id,
+        window_title: source.window_title.clone(),
+        detected_app: source.detected_app.clone(),
+        private_context: source.private_context,
+        is_virtual_device: source.is_virtual_device,
+        classification: source.classification.map(|class| class.as_str().to_string()),
+        session: source.session.as_ref().map(|session| pb::SourceSession {
+            id: session.id,
+            first_seen_utc: session.first_seen_utc.clone(),
+            last_seen_utc: session.last_seen_utc.clone(),
+            duration_secs: session.duration_secs,
+        }),
+    }
+}
+
+fn to_pb_mute_change(change: &MuteChange) -> pb::MuteChange {
+    pb::MuteChange {
+        at: change.at.clone(),
+        offset_secs: change.offset_secs,
+        target: change.target.as_str().to_string(),
+        change: change.change.as_str().to_string(),
+        volume: change.volume,
+    }
+}
+
+fn to_pb_event(event: &MonitorEvent) -> pb::CallEvent {
+    use pb::call_event::Payload;
+
+    let payload = match event {
+        MonitorEvent::CallStarted(call) => Payload::CallStarted(to_pb_call(call)),
+        MonitorEvent::CallEnded(ended) => Payload::CallEnded(pb::CallEnded {
+            app: ended.app.clone(),
+            process_id: ended.process_id,
+            window_title: ended.window_title.clone(),
+            started_at: ended.started_at.clone(),
+            duration: ended.duration.clone(),
+            duration_secs: ended.duration_secs,
+            mute_timeline: ended.mute_timeline.iter().map(to_pb_mute_change).collect(),
+            quality: ended.quality.as_ref().map(to_pb_quality),
+            started_at_utc: ended.started_at_utc.clone(),
+            ended_at_utc: ended.ended_at_utc.clone(),
+            local_timezone: ended.local_timezone.clone(),
+        }),
+        MonitorEvent::ConfidenceChanged(changed) => Payload::ConfidenceChanged(pb::ConfidenceChanged {
+            app: changed.app.clone(),
+            process_id: changed.process_id,
+            previous: changed.previous,
+            current: changed.current,
+        }),
+        MonitorEvent::SourceAdded(source) => Payload::SourceAdded(to_pb_source(source)),
+        MonitorEvent::SourceRemoved(source) => Payload::SourceRemoved(to_pb_source(source)),
+        MonitorEvent::ConfigReloaded(reloaded) => Payload::ConfigReloaded(pb::ConfigReloaded {
+            path: reloaded.path.clone(),
+            sections: reloaded.sections.clone(),
+        }),
+        MonitorEvent::MediaPlaybackDuringCall(media) => Payload::MediaPlaybackDuringCall(pb::MediaPlayback {
+            call_app: media.call_app.clone(),
+            call_process_id: media.call_process_id,
+            app: media.app.clone(),
+            process_id: media.process_id,
+            window_title: media.window_title.clone(),
+            offset_secs: media.offset_secs,
+        }),
+        MonitorEvent::AudioMarker(marker) => Payload::AudioMarker(pb::AudioMarker {
+            process_id: marker.process_id,
+            kind: marker.kind.as_str().to_string(),
+            at: marker.at.clone(),
+            at_ms: marker.at_ms,
+            hash: marker.hash.clone(),
+        }),
+    };
+
+    let event_type = serde_json::to_value(event.event_type())
+        .ok()
+        .and_then(|value| value.as_str().map(|s| s.to_string()))
+        .unwrap_or_default();
+
+    pb::CallEvent {
+        schema_version: SCHEMA_VERSION,
+        event_type,
+        timestamp: crate::output::utc_timestamp(std::time::SystemTime::now()),
+        payload: Some(payload),
+    }
+}
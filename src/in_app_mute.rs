@@ -0,0 +1,105 @@
+// In-app mute detection
+// Muting inside Teams, Zoom or Meet leaves the system microphone open and unmuted, so
+// `has_mic` stays true. Two hints tell the two apart:
+//   - the call app's capture session meter sits at zero while the mic is open and not
+//     muted system-wide (Windows, where WASAPI meters every capture session)
+//   - the window or tab title says so ("(Muted)", 🔇)
+// The client's mute button, when read through UI Automation (uia.rs), overrides both.
+// The result is `in_app_muted` on the active call, None when neither hint applies.
+
+use crate::call_tracker::Sample;
+use crate::CallInfo;
+use std::time::{Duration, SystemTime};
+
+/// Title markers of a muted client, lowercase
+const MUTED_TITLE_HINTS: &[&str] = &[
+    "🔇", "(muted)", "[muted]", "- muted", "· muted", "mic muted", "microphone muted", "mic off",
+    "microphone off", "you are muted", "you're muted",
+];
+
+/// Input peak at or below this is digital silence; a live microphone's noise floor is above it
+pub const SILENCE_FLOOR: f32 = 0.0001;
+
+/// How long the capture meter must stay silent before the call counts as muted in the app
+const SILENT_FOR: Duration = Duration::from_secs(3);
+
+/// Whether a window or tab title shows the client muted
+pub fn title_says_muted(title: &str) -> bool {
+    let title = title.to_lowercase();
+    MUTED_TITLE_HINTS.iter().any(|hint| title.contains(hint))
+}
+
+/// Silence tracking for the active call
+#[derive(Debug, Default)]
+pub struct InAppMute {
+    process_id: Option<u32>,
+    /// When the call app's capture meter last went silent
+    silent_since: Option<SystemTime>,
+}
+
+impl InAppMute {
+    /// `in_app_muted` for `call` this tick
+    pub fn update(&mut self, call: &CallInfo, sample: &Sample, now: SystemTime) -> Option<bool> {
+        if self.process_id != Some(call.process_id) {
+            *self = InAppMute { process_id: Some(call.process_id), silent_since: None };
+        }
+
+        // The client's own mute button is authoritative
+        if let Some(muted) = call.controls.as_ref().and_then(|controls| controls.mic_muted) {
+            return Some(muted);
+        }
+        if title_says_muted(&call.window_title) {
+            return Some(true);
+        }
+
+        // A system-wide mute silences every session alike, and a closed mic has no session
+        let system_muted = sample.levels.mic.is_some_and(|mic| mic.is_muted);
+        let peak = sample.capture_peaks.get(&call.process_id).filter(|_| call.has_mic && !system_muted);
+        let Some(&peak) = peak else {
+            self.silent_since = None;
+            return None;
+        };
+
+        if peak > SILENCE_FLOOR {
+            self.silent_since = None;
+            return Some(false);
+        }
+        let since = *self.silent_since.get_or_insert(now);
+        Some(now.duration_since(since).unwrap_or(Duration::ZERO) >= SILENT_FOR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mute_timeline::Level;
+
+    #[test]
+    fn test_silent_capture_session_means_muted_in_app() {
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let call = CallInfo {
+            window_title: "Weekly sync | Microsoft Teams".to_string(),
+            call_started_system_time: start,
+            ..crate::test_call("Microsoft Teams", 7)
+        };
+        let sample = |peak: f32, system_muted: bool| Sample {
+            capture_peaks: [(7, peak)].into(),
+            levels: crate::mute_timeline::VolumeLevels {
+                mic: Some(Level { volume: 80.0, is_muted: system_muted }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut detector = InAppMute::default();
+        assert_eq!(detector.update(&call, &sample(0.02, false), at(0)), Some(false));
+        assert_eq!(detector.update(&call, &sample(0.0, false), at(500)), Some(false));
+        assert_eq!(detector.update(&call, &sample(0.0, false), at(3500)), Some(true));
+        assert_eq!(detector.update(&call, &sample(0.0, true), at(4000)), None);
+        assert_eq!(detector.update(&call, &Sample::default(), at(4500)), None);
+
+        let muted_tab = CallInfo { window_title: "🔇 Meet - abc-defg-hij".to_string(), ..call };
+        assert_eq!(detector.update(&muted_tab, &Sample::default(), at(5000)), Some(true));
+    }
+}
@@ -0,0 +1,222 @@
+// Meeting-provider media IP ranges
+// Zoom, Microsoft, Google and Webex publish the networks their media relays live in.
+// A dataset is bundled at build time (data/ip_ranges.json); `--update-ip-ranges <path>`
+// refreshes it from the vendors' published endpoints and `--ip-ranges <path>` loads the
+// refreshed copy instead of the bundled one.
+//
+//   { "updated": "2026-10-01", "providers": { "zoom": ["170.114.0.0/16", ...], ... } }
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::Duration;
+
+const BUNDLED: &str = include_str!("../data/ip_ranges.json");
+
+/// Zoom's list of meeting (media and zone controller) networks, one CIDR per line
+const ZOOM_URL: &str = "https://assets.zoom.us/docs/ipranges/ZoomMeetings.txt";
+/// Microsoft 365 endpoint service, limited to the Teams ("Skype") service area
+const MICROSOFT_URL: &str =
+    "https://endpoints.office.com/endpoints/worldwide?ServiceAreas=Skype&clientrequestid=b10c5ed1-bad1-445f-b386-b919946339a7";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Microsoft,
+    Zoom,
+    Google,
+    Webex,
+}
+
+impl Provider {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Provider::Microsoft => "Microsoft Teams",
+            Provider::Zoom => "Zoom",
+            Provider::Google => "Google Meet",
+            Provider::Webex => "Webex",
+        }
+    }
+}
+
+/// The on-disk dataset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpRangeSet {
+    #[serde(default)]
+    pub updated: Option<String>,
+    pub providers: BTreeMap<Provider, Vec<String>>,
+}
+
+/// An IPv4 or IPv6 network in CIDR notation
+#[derive(Debug, Clone, Copy)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn parse(cidr: &str) -> Option<Self> {
+        // Bare addresses are single-host ranges
+        let (network, prefix) = cidr.trim().split_once('/').unwrap_or((cidr.trim(), ""));
+        let network: IpAddr = network.parse().ok()?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix: u8 = if prefix.is_empty() { max } else { prefix.parse().ok()? };
+        (prefix <= max).then_some(IpRange { network, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Parsed ranges, ready for lookups
+#[derive(Debug, Clone)]
+pub struct IpRangeDb {
+    ranges: Vec<(Provider, IpRange)>,
+}
+
+impl IpRangeDb {
+    pub fn bundled() -> Self {
+        let set: IpRangeSet = serde_json::from_str(BUNDLED).expect("bundled data/ip_ranges.json is valid");
+        Self::from_set(&set)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let set: IpRangeSet = serde_json::from_str(&contents)
+            .map_err(|e| format!("invalid IP range dataset {}: {}", path.display(), e))?;
+        Ok(Self::from_set(&set))
+    }
+
+    fn from_set(set: &IpRangeSet) -> Self {
+        let ranges = set
+            .providers
+            .iter()
+            .flat_map(|(&provider, cidrs)| cidrs.iter().filter_map(move |cidr| Some((provider, IpRange::parse(cidr)?))))
+            .collect();
+        IpRangeDb { ranges }
+    }
+
+    /// The provider whose published ranges contain `ip`
+    pub fn classify(&self, ip: IpAddr) -> Option<Provider> {
+        self.ranges.iter().find(|(_, range)| range.contains(ip)).map(|(provider, _)| *provider)
+    }
+}
+
+/// `--update-ip-ranges <path>`: fetch the vendors' lists, merge them over the bundled
+/// dataset and write the result. Providers without a machine-readable list (Google
+/// Meet, Webex) or whose download fails keep their bundled ranges.
+pub fn run_update(path: &Path) -> i32 {
+    let mut set: IpRangeSet = serde_json::from_str(BUNDLED).expect("bundled data/ip_ranges.json is valid");
+    let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+
+    let fetched = [
+        (Provider::Zoom, fetch_zoom(&agent)),
+        (Provider::Microsoft, fetch_microsoft(&agent)),
+    ];
+    for (provider, result) in fetched {
+        match result {
+            Ok(cidrs) if !cidrs.is_empty() => {
+                println!("{}: {} ranges", provider.display_name(), cidrs.len());
+                set.providers.insert(provider, cidrs);
+            }
+            Ok(_) => eprintln!("[rust] {}: empty list, keeping bundled ranges", provider.display_name()),
+            Err(e) => eprintln!("[rust] {}: {}, keeping bundled ranges", provider.display_name(), e),
+        }
+    }
+    set.updated = Some(chrono::Local::now().format("%Y-%m-%d").to_string());
+
+    let json = match serde_json::to_string_pretty(&set) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("[rust] Failed to serialize IP ranges: {}", e);
+            return 1;
+        }
+    };
+    if let Err(e) = fs::write(path, json) {
+        eprintln!("[rust] Failed to write {}: {}", path.display(), e);
+        return 1;
+    }
+    println!("Wrote {}", path.display());
+    0
+}
+
+fn fetch_zoom(agent: &ureq::Agent) -> Result<Vec<String>, String> {
+    let body = agent
+        .get(ZOOM_URL)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+
+    Ok(body
+        .lines()
+        .map(str::trim)
+        .filter(|line| IpRange::parse(line).is_some())
+        .map(str::to_string)
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct MicrosoftEndpointSet {
+    #[serde(default)]
+    ips: Vec<String>,
+    #[serde(default, rename = "udpPorts")]
+    udp_ports: Option<String>,
+}
+
+fn fetch_microsoft(agent: &ureq::Agent) -> Result<Vec<String>, String> {
+    let body = agent
+        .get(MICROSOFT_URL)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let sets: Vec<MicrosoftEndpointSet> = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+
+    // Media relays are the endpoint sets that also take UDP (3478-3481)
+    let mut cidrs: Vec<String> = sets
+        .into_iter()
+        .filter(|set| set.udp_ports.is_some())
+        .flat_map(|set| set.ips)
+        .filter(|cidr| IpRange::parse(cidr).is_some())
+        .collect();
+    cidrs.sort();
+    cidrs.dedup();
+    Ok(cidrs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_bundled_ranges() {
+        let db = IpRangeDb::bundled();
+        let classify = |ip: &str| db.classify(ip.parse().unwrap());
+
+        assert_eq!(classify("52.113.10.4"), Some(Provider::Microsoft));
+        assert_eq!(classify("2603:1063:2::7"), Some(Provider::Microsoft));
+        assert_eq!(classify("170.114.52.2"), Some(Provider::Zoom));
+        assert_eq!(classify("74.125.250.129"), Some(Provider::Google));
+        assert_eq!(classify("170.72.1.1"), Some(Provider::Webex));
+        assert_eq!(classify("93.184.216.34"), None);
+        assert!(IpRange::parse("10.0.0.1").unwrap().contains("10.0.0.1".parse().unwrap()));
+        assert!(IpRange::parse("10.0.0.0/33").is_none());
+    }
+}
@@ -0,0 +1,333 @@
+// Local IPC transport: serves the event stream and accepts control commands
+// Unix domain socket on Linux/macOS, named pipe on Windows
+// Every connected client receives a state snapshot on connect, then delta events.
+// Clients may write one command per line (see control.rs).
+// Output goes through a bounded queue per client, written by the client's own thread, so
+// a client that stops reading cannot stall the monitor; once its queue is full it is
+// dropped.
+
+use crate::output::{Envelope, EventType};
+use crate::state_store::StateReader;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+/// Lines queued for a client before it counts as stalled
+const CLIENT_QUEUE: usize = 256;
+
+struct IpcClient {
+    id: u64,
+    writer: Box<dyn Write + Send>,
+    needs_snapshot: bool,
+}
+
+type ClientList = Arc<Mutex<Vec<IpcClient>>>;
+
+/// Multi-client IPC server
+pub struct IpcServer {
+    clients: ClientList,
+    requests: Receiver<(u64, String)>,
+    /// Where new clients' snapshots come from
+    state: StateReader,
+}
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+impl IpcServer {
+    /// Start listening on `path` in a background thread
+    pub fn bind(path: &str, state: StateReader) -> io::Result<Self> {
+        let clients: ClientList = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel();
+
+        platform::listen(path, Arc::clone(&clients), tx)?;
+
+        Ok(IpcServer {
+            clients,
+            requests: rx,
+            state,
+        })
+    }
+
+    /// Lines received from clients since the last call
+    pub fn pending_requests(&self) -> Vec<(u64, String)> {
+        self.requests.try_iter().collect()
+    }
+
+    /// Send one line to a single client
+    pub fn send_to(&self, client_id: u64, line: &str) {
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain_mut(|client| {
+            if client.id != client_id {
+                return true;
+            }
+            write_line(&mut client.writer, line).is_ok()
+        });
+    }
+
+    /// Send this tick's output to every client
+    /// New clients get the latest snapshot first so they can bootstrap their state
+    pub fn publish(&self, event_lines: &[String]) {
+        let mut clients = self.clients.lock().unwrap();
+        let mut snapshot_line = None;
+        clients.retain_mut(|client| {
+            if client.needs_snapshot {
+                let line = snapshot_line.get_or_insert_with(|| {
+                    Envelope::new(EventType::State, &self.state.snapshot().state).to_json_line().unwrap_or_default()
+                });
+                if write_line(&mut client.writer, line).is_err() {
+                    return false;
+                }
+                client.needs_snapshot = false;
+                return true;
+            }
+
+            event_lines
+                .iter()
+                .all(|line| write_line(&mut client.writer, line).is_ok())
+        });
+    }
+}
+
+fn write_line(writer: &mut Box<dyn Write + Send>, line: &str) -> io::Result<()> {
+    writer.write_all(format!("{}\n", line).as_bytes())?;
+    writer.flush()
+}
+
+/// Queues outgoing lines for the thread that writes to the client; fails once the queue
+/// is full or that thread is gone
+struct QueuedWriter(SyncSender<Vec<u8>>);
+
+impl Write for QueuedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.0.try_send(buf.to_vec()) {
+            Ok(()) => Ok(buf.len()),
+            Err(TrySendError::Full(_)) => Err(io::Error::new(io::ErrorKind::WouldBlock, "IPC client stopped reading")),
+            Err(TrySendError::Disconnected(_)) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "IPC client disconnected"))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A client's output queue and the end its writer thread drains
+fn queued_writer() -> (QueuedWriter, Receiver<Vec<u8>>) {
+    let (tx, rx) = mpsc::sync_channel(CLIENT_QUEUE);
+    (QueuedWriter(tx), rx)
+}
+
+/// Add a client to the broadcast list and return its id
+fn add_client(writer: Box<dyn Write + Send>, clients: &ClientList) -> u64 {
+    let id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+
+    clients.lock().unwrap().push(IpcClient {
+        id,
+        writer,
+        needs_snapshot: true,
+    });
+
+    id
+}
+
+/// Register a connected client and spawn threads writing its output and reading its commands
+#[cfg(unix)]
+fn register_client<R, W>(reader: R, mut writer: W, clients: &ClientList, requests: Sender<(u64, String)>)
+where
+    R: io::Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    use std::io::{BufRead, BufReader};
+
+    let (queue, outgoing) = queued_writer();
+    let id = add_client(Box::new(queue), clients);
+
+    std::thread::spawn(move || {
+        for chunk in outgoing {
+            if writer.write_all(&chunk).and_then(|_| writer.flush()).is_err() {
+                break;
+            }
+        }
+    });
+
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            match line {
+                Ok(line) if !line.trim().is_empty() => {
+                    if requests.send((id, line)).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+#[cfg(unix)]
+mod platform {
+    use super::{register_client, ClientList};
+    use std::io;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+    use std::sync::mpsc::Sender;
+    use std::thread;
+    use std::time::Duration;
+
+    pub fn listen(path: &str, clients: ClientList, requests: Sender<(u64, String)>) -> io::Result<()> {
+        // Remove a stale socket left behind by a previous run, and nothing else
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path),
+                ))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let listener = UnixListener::bind(path)?;
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        // A client that stops reading fails its writer thread instead of holding it
+                        let _ = stream.set_write_timeout(Some(Duration::from_secs(5)));
+                        let reader = match stream.try_clone() {
+                            Ok(reader) => reader,
+                            Err(e) => {
+                                log::warn!("IPC client setup failed: {}", e);
+                                continue;
+                            }
+                        };
+                        register_client(reader, stream, &clients, requests.clone());
+                    }
+                    Err(e) => log::warn!("IPC accept failed: {}", e),
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{add_client, queued_writer, ClientList};
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::windows::io::{AsRawHandle, FromRawHandle};
+    use std::sync::mpsc::{Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::{ERROR_PIPE_CONNECTED, HANDLE};
+    use windows::Win32::Storage::FileSystem::PIPE_ACCESS_DUPLEX;
+    use windows::Win32::System::Pipes::*;
+
+    pub fn listen(path: &str, clients: ClientList, requests: Sender<(u64, String)>) -> io::Result<()> {
+        // Accept bare names as a convenience: "validator" -> \\.\pipe\validator
+        let pipe_name = if path.starts_with(r"\\.\pipe\") {
+            path.to_string()
+        } else {
+            format!(r"\\.\pipe\{}", path)
+        };
+
+        // Create the first instance up front so bind errors surface to the caller.
+        // Instances are held as `File` (which is Send) and only borrowed as HANDLE.
+        let mut pending = create_instance(&pipe_name)?;
+
+        thread::spawn(move || loop {
+            let handle = HANDLE(pending.as_raw_handle() as _);
+            let connected = match unsafe { ConnectNamedPipe(handle, None) } {
+                Ok(()) => true,
+                Err(e) => e.code() == ERROR_PIPE_CONNECTED.to_hresult(),
+            };
+
+            let next = match create_instance(&pipe_name) {
+                Ok(file) => file,
+                Err(e) => {
+                    log::warn!("IPC pipe creation failed: {}", e);
+                    return;
+                }
+            };
+            let pipe = std::mem::replace(&mut pending, next);
+
+            // A blocking ReadFile on a synchronous pipe would stall writes from another
+            // thread, so each pipe is serviced by exactly one thread, draining the queue
+            if connected {
+                let (queue, rx) = queued_writer();
+                let id = add_client(Box::new(queue), &clients);
+                let requests = requests.clone();
+                thread::spawn(move || serve_client(id, pipe, rx, requests));
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Alternate between flushing queued output and reading whatever input is available
+    fn serve_client(id: u64, mut pipe: File, outgoing: Receiver<Vec<u8>>, requests: Sender<(u64, String)>) {
+        let mut pending_input = String::new();
+        let mut buffer = [0u8; 1024];
+
+        loop {
+            for chunk in outgoing.try_iter() {
+                if pipe.write_all(&chunk).is_err() {
+                    return;
+                }
+            }
+
+            let mut available = 0u32;
+            let handle = HANDLE(pipe.as_raw_handle() as _);
+            if unsafe { PeekNamedPipe(handle, None, 0, None, Some(&mut available), None) }.is_err() {
+                return; // Client went away
+            }
+
+            if available > 0 {
+                let read = match pipe.read(&mut buffer) {
+                    Ok(0) | Err(_) => return,
+                    Ok(read) => read,
+                };
+                pending_input.push_str(&String::from_utf8_lossy(&buffer[..read]));
+
+                while let Some(newline) = pending_input.find('\n') {
+                    let line: String = pending_input.drain(..=newline).collect();
+                    let line = line.trim();
+                    if !line.is_empty() && requests.send((id, line.to_string())).is_err() {
+                        return;
+                    }
+                }
+            } else {
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+
+    fn create_instance(pipe_name: &str) -> io::Result<File> {
+        let handle = unsafe {
+            CreateNamedPipeW(
+                &HSTRING::from(pipe_name),
+                PIPE_ACCESS_DUPLEX,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                4096,
+                4096,
+                0,
+                None,
+            )
+        };
+
+        if handle.is_invalid() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(unsafe { File::from_raw_handle(handle.0 as _) })
+    }
+}
@@ -0,0 +1,9 @@
+// Library target for the criterion benchmarks in benches/
+// The monitor is a binary; this compiles the same main.rs as a library so that
+// benches/poll_cycle.rs can run its poll cycle. Nothing but the benchmark entry points in
+// bench.rs is exported, so the rest reads as unused here.
+#![allow(dead_code)]
+
+include!("main.rs");
+
+pub use bench::{mock_cycle, platform_cycle};
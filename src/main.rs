@@ -1,26 +1,119 @@
+mod meeting_id;
+mod messengers;
 mod mic_monitor;
 mod audio_output_monitor;
+mod aggregate;
+#[cfg(feature = "capture")]
+mod audio_markers;
+mod bench;
+mod browser_bridge;
+#[cfg(feature = "calendar")]
+mod calendar;
+mod calibrate;
+mod call_quality;
+mod capability;
+#[cfg(feature = "capture")]
+mod capture;
+mod call_state;
+mod call_tracker;
+mod companion;
+mod config;
+mod console;
+mod control;
 mod network_monitor;
 mod correlation_engine;
+mod cross_check;
+mod echo_risk;
+mod environment;
+mod error;
+mod events;
+mod export;
+mod firefox;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod in_app_mute;
+mod ip_ranges;
+mod ipc;
+mod mock_backend;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod mute_timeline;
+#[cfg(feature = "notify")]
+mod notify;
+#[cfg(feature = "otel")]
+mod otel;
+mod output;
+mod output_router;
+mod participants;
+mod permissions;
+mod power;
+mod privacy;
+mod process_cache;
+mod process_filter;
+mod process_load;
+mod process_tree;
+mod profile;
+mod recording_probe;
+mod redaction;
+mod replay;
+mod report;
+mod role;
+mod rule_script;
+mod safari;
+#[cfg(target_os = "linux")]
+mod sandbox;
+mod scheduler;
+mod sealed_log;
+mod self_update;
+mod sense;
+mod session_events;
+mod signal_providers;
+mod slack_huddle;
+mod source_sessions;
+#[cfg(target_os = "linux")]
+mod sock_diag;
+mod state_store;
+mod stdout_writer;
+mod subprocess;
+mod supervisor;
+#[cfg(feature = "teams")]
+mod teams;
+mod titles;
+mod uia;
+mod validator;
+mod watchdog;
+mod webhook;
+mod webrtc_event_log;
+mod zoom_probe;
 mod audio;      // New platform-agnostic audio module
 mod platform;   // New platform-specific utilities module
 
-// Keep old wasapi_audio for backward compatibility during transition
+// Keep old wasapi_audio for backward compatibility during transition; also home to the
+// process loopback used by render_meters and capture.rs
 #[cfg(target_os = "windows")]
 mod wasapi_audio;
 
-use mic_monitor::MicMonitor;
-use audio_output_monitor::AudioOutputMonitor;
 use network_monitor::NetworkMonitor;
-use correlation_engine::{CorrelationEngine, MultiSignal};
+use audio::SystemAudio;
+use correlation_engine::{CorrelationEngine, HysteresisConfig};
+use browser_bridge::BrowserBridge;
+use call_tracker::{CallTracker, Sample};
+use console::ConsoleStyle;
+use config::{Config, ConfigWatcher};
+use control::{CommandResult, ControlCommand, HealthReport, RunMode};
+use cross_check::CrossCheck;
+use ipc::IpcServer;
+use output::{Envelope, EventType, StreamMode};
+use output_router::{OutputRouter, SinkConfig};
+use privacy::PrivateWindowPolicy;
+use process_tree::ProcessTree;
+use redaction::Redactor;
+use validator::CallValidator;
 use serde::{Deserialize, Serialize};
-use std::fs::OpenOptions;
-use std::io::Write;
 use std::thread;
-use std::time::{Duration, SystemTime};
-use chrono::Timelike;
+use std::time::{Duration, Instant, SystemTime};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AudioSource {
@@ -29,12 +122,33 @@ struct AudioSource {
     window_title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     detected_app: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    private_context: bool,
+    /// Playing only to a virtual sink or loopback (VB-Cable, BlackHole, null sink)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    is_virtual_device: bool,
+    /// Set on `other_audio_sources`: media, call_candidate, system_sound or unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    classification: Option<correlation_engine::SourceClass>,
+    /// Set on `other_audio_sources`: the playback this tick belongs to (see source_sessions.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session: Option<source_sessions::SourceSession>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MonitorState {
     active_call: Option<CallInfo>,
     other_audio_sources: Vec<AudioSource>,
+    /// WSL, RDP or Citrix session the monitor runs in (see environment.rs); absent locally
+    #[serde(default, skip_serializing_if = "environment::Environment::is_local")]
+    environment: environment::Environment,
+    /// Apps calls are restricted to (`tracked_apps` in the scoring config), with other apps'
+    /// audio counted instead of listed; absent when every app is tracked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tracked_apps: Option<call_tracker::TrackedApps>,
+    /// Calls the user is in on a paired phone (see companion.rs); absent when there are none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    external_calls: Vec<companion::ExternalCall>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,9 +160,52 @@ struct CallInfo {
     has_audio: bool,
     has_webrtc: bool,
     confidence: f32,
+    #[serde(default)]
+    kind: correlation_engine::CallKind,
+    /// host, presenter, attendee or unknown (see role.rs)
+    #[serde(default)]
+    role: role::CallRole,
+    /// Local "HH:MM:SS" without a date, for consoles and notifications; written to the
+    /// outputs with --legacy-timestamps only
+    #[serde(default, skip_serializing_if = "output::omit_legacy_timestamp")]
     started_at: String,
-    #[serde(skip, default = "default_system_time")]
-    last_seen: SystemTime,
+    /// RFC3339 in UTC, e.g. "2025-01-01T09:00:00Z"
+    #[serde(default)]
+    started_at_utc: String,
+    /// UTC offset of the local time zone when the call started, e.g. "+01:00"
+    #[serde(default)]
+    local_timezone: String,
+    /// Seconds since the call started, from the system clock (not `started_at`)
+    #[serde(default)]
+    duration_secs: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    private_context: bool,
+    /// Muted inside the call app while the system mic stays open (see in_app_mute.rs);
+    /// None when that cannot be told
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    in_app_muted: Option<bool>,
+    /// A recording indicator was seen during the call (see recording_probe.rs); None when
+    /// nothing could tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    meeting_is_recorded: Option<bool>,
+    /// Call controls read from the client's window (see uia.rs, `uia` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    controls: Option<uia::CallControls>,
+    /// Other people in the call, estimated (see participants.rs); None when nothing tells
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimated_participants: Option<u32>,
+    /// Meeting URL or code without passcodes, e.g. "meet.google.com/abc-defg-hij" (see meeting_id.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    meeting_identifier: Option<String>,
+    /// Calendar event the call belongs to (see calendar.rs, `calendar` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheduled_meeting: Option<meeting_id::ScheduledMeeting>,
+    /// RTT, loss, socket drops and audio underruns measured during the call (see call_quality.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quality: Option<call_quality::CallQuality>,
+    /// Plays through open speakers into the built-in mic (see echo_risk.rs)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    echo_risk: bool,
     #[serde(skip, default = "default_system_time")]
     call_started_system_time: SystemTime,
 }
@@ -57,11 +214,44 @@ fn default_system_time() -> SystemTime {
     SystemTime::now()
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct JsonLogEntry {
-    timestamp: String,
-    active_call: Option<CallInfo>,
-    other_audio: Vec<AudioSource>,
+impl CallInfo {
+    /// Date the call from `since`, in local and UTC time
+    fn set_started(&mut self, since: SystemTime) {
+        self.started_at = chrono::DateTime::<chrono::Local>::from(since).format("%H:%M:%S").to_string();
+        self.started_at_utc = output::utc_timestamp(since);
+        self.local_timezone = output::local_timezone(since);
+        self.call_started_system_time = since;
+    }
+}
+
+/// Call fixture for tests: `app` with mic, audio and WebRTC, nothing else known
+#[cfg(test)]
+fn test_call(app: &str, process_id: u32) -> CallInfo {
+    CallInfo {
+        app: app.to_string(),
+        process_id,
+        window_title: String::new(),
+        has_mic: true,
+        has_audio: true,
+        has_webrtc: true,
+        confidence: 0.9,
+        kind: Default::default(),
+        role: Default::default(),
+        started_at: "10:00:00".to_string(),
+        started_at_utc: String::new(),
+        local_timezone: String::new(),
+        duration_secs: 0,
+        private_context: false,
+        in_app_muted: None,
+        meeting_is_recorded: None,
+        controls: None,
+        estimated_participants: None,
+        meeting_identifier: None,
+        scheduled_meeting: None,
+        quality: None,
+        echo_risk: false,
+        call_started_system_time: SystemTime::UNIX_EPOCH,
+    }
 }
 
 // Communication apps we care about
@@ -74,10 +264,6 @@ const CALL_APPS: &[&str] = &[
     "whatsapp",
 ];
 
-// Grace period before ending call (seconds)
-// Reduced to 2s for faster detection while still preventing false endings
-const CALL_END_GRACE_PERIOD: u64 = 2;
-
 /// OS information structure
 #[derive(Debug)]
 struct OSInfo {
@@ -150,48 +336,15 @@ fn get_platform_specific_info(os: &str) -> String {
         "windows" => get_windows_version(),
         "linux" => get_linux_version(),
         "macos" => get_macos_version(),
+        "freebsd" => get_freebsd_version(),
         _ => format!("{} (version detection not implemented)", os),
     }
 }
 
 #[cfg(target_os = "windows")]
 fn get_windows_version() -> String {
-    use std::process::Command;
-
-    // Try to get Windows version using 'ver' command
-    if let Ok(output) = Command::new("cmd").args(&["/c", "ver"]).output() {
-        if let Ok(version_str) = String::from_utf8(output.stdout) {
-            let version_str = version_str.trim();
-            if !version_str.is_empty() {
-                return version_str.to_string();
-            }
-        }
-    }
-
-    // Fallback: Try systeminfo (slower but more detailed)
-    if let Ok(output) = Command::new("wmic")
-        .args(&["os", "get", "Caption,Version", "/value"])
-        .output()
-    {
-        if let Ok(info) = String::from_utf8(output.stdout) {
-            let mut caption = String::new();
-            let mut version = String::new();
-
-            for line in info.lines() {
-                if line.starts_with("Caption=") {
-                    caption = line.replace("Caption=", "").trim().to_string();
-                } else if line.starts_with("Version=") {
-                    version = line.replace("Version=", "").trim().to_string();
-                }
-            }
-
-            if !caption.is_empty() || !version.is_empty() {
-                return format!("{} (Build {})", caption, version);
-            }
-        }
-    }
-
-    "Windows (version unknown)".to_string()
+    // Native query; spawning cmd.exe/wmic every start trips endpoint protection
+    platform::windows::get_os_version()
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -228,7 +381,7 @@ fn get_linux_version() -> String {
     }
 
     // Fallback: Try uname
-    if let Ok(output) = std::process::Command::new("uname").arg("-a").output() {
+    if let Ok(output) = crate::subprocess::output(std::process::Command::new("uname").arg("-a")) {
         if let Ok(uname_str) = String::from_utf8(output.stdout) {
             return uname_str.trim().to_string();
         }
@@ -247,7 +400,7 @@ fn get_macos_version() -> String {
     use std::process::Command;
 
     // Try to get macOS version using sw_vers
-    if let Ok(output) = Command::new("sw_vers").output() {
+    if let Ok(output) = crate::subprocess::output(Command::new("sw_vers")) {
         if let Ok(version_str) = String::from_utf8(output.stdout) {
             let mut product_name = String::new();
             let mut product_version = String::new();
@@ -274,19 +427,342 @@ fn get_macos_version() -> String {
     "Not running on macOS".to_string()
 }
 
+fn get_freebsd_version() -> String {
+    // freebsd-version reports the userland, which is what gets patched
+    match crate::subprocess::output(&mut std::process::Command::new("freebsd-version")) {
+        Ok(output) if output.status.success() => format!("FreeBSD {}", String::from_utf8_lossy(&output.stdout).trim()),
+        _ => "FreeBSD (version unknown)".to_string(),
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
+    init_logging();
+
+    // Native-API-only mode: any code path that would spawn a program fails cleanly instead
+    if args.contains(&"--no-subprocess".to_string()) {
+        subprocess::set_disabled(true);
+    }
+
+    // One-shot sensor probes: `sense <mic|audio-apps|net> [--json]`
+    if args.get(1).map(|s| s.as_str()) == Some("sense") {
+        std::process::exit(sense::run(&args[2..]));
+    }
+
+    // Time full poll cycles per subsystem: `bench [--cycles N] [--budget-ms MS]`
+    if args.get(1).map(|s| s.as_str()) == Some("bench") {
+        std::process::exit(bench::run(&args[2..]));
+    }
+
+    // What the monitor can access here, for installers: `check-permissions [--json]`
+    if args.get(1).map(|s| s.as_str()) == Some("check-permissions") {
+        std::process::exit(permissions::run(&args[2..]));
+    }
+
+    // Fit scoring weights to labeled recordings: `calibrate <dir> [--config base.json]`
+    if args.get(1).map(|s| s.as_str()) == Some("calibrate") {
+        std::process::exit(calibrate::run(&args[2..]));
+    }
+
+    // Per-day, per-app call summaries from the JSON log: `report <log or dir>... [--format f]`
+    if args.get(1).map(|s| s.as_str()) == Some("report") {
+        std::process::exit(report::run(&args[2..]));
+    }
+
+    // Flat call and snapshot tables from the JSON log: `export <log or dir>... --format csv|parquet`
+    if args.get(1).map(|s| s.as_str()) == Some("export") {
+        std::process::exit(export::run(&args[2..]));
+    }
+
+    // Merge several machines' monitors per user: `aggregate [--listen addr] [--http addr]`
+    if args.get(1).map(|s| s.as_str()) == Some("aggregate") {
+        std::process::exit(aggregate::run(&args[2..]));
+    }
+
+    // Read a --encrypt-logs log: `decrypt-log <file> [--key-file path]` or `decrypt-log --keygen`
+    if args.get(1).map(|s| s.as_str()) == Some("decrypt-log") {
+        std::process::exit(sealed_log::run(&args[2..]));
+    }
+
+    // Install a signed release: `update [--check] [--config path]`, `update --keygen`, `update --sign <binary>`
+    if args.get(1).map(|s| s.as_str()) == Some("update") {
+        std::process::exit(self_update::run(&args[2..]));
+    }
+
+    // Refresh the provider IP range dataset: `--update-ip-ranges <path>`
+    if let Some(i) = args.iter().position(|r| r == "--update-ip-ranges") {
+        match args.get(i + 1) {
+            Some(path) => std::process::exit(ip_ranges::run_update(Path::new(path))),
+            None => {
+                eprintln!("Usage: rust-audio-validator --update-ip-ranges <path>");
+                std::process::exit(2);
+            }
+        }
+    }
+
     let is_stream = args.contains(&"--stream".to_string());
+    let is_cross_check = args.contains(&"--cross-check".to_string());
+    let is_explain = args.contains(&"--explain".to_string());
+    let is_no_redact = args.contains(&"--no-redact".to_string());
+    let is_self_profile = args.contains(&"--self-profile".to_string());
+    let is_auto_update = args.contains(&"--auto-update".to_string());
+    let is_audio_markers = args.contains(&"--audio-markers".to_string());
+    let is_allow_audio_capture = args.contains(&"--allow-audio-capture".to_string());
+    let is_fast_start = args.contains(&"--fast-start".to_string());
+    output::set_legacy_timestamps(args.contains(&"--legacy-timestamps".to_string()));
+
+    // Label every line for an `aggregate` agent merging this user's machines
+    let origin_user = args.iter()
+        .position(|r| r == "--origin-user")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+    if let Some(user) = origin_user {
+        let machine = args.iter()
+            .position(|r| r == "--origin-machine")
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+            .unwrap_or_else(output::host_name);
+        output::set_origin(output::Origin { user, machine });
+    }
+
+    let stream_mode = args.iter()
+        .position(|r| r == "--stream-mode")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| StreamMode::parse(s).unwrap_or_else(|| {
+            log::warn!("Unknown --stream-mode '{}', using snapshots", s);
+            StreamMode::Snapshots
+        }))
+        .unwrap_or(StreamMode::Snapshots);
     
+    // Optional JSON config; a file that was asked for but cannot be used is fatal
+    let config_path = args.iter().position(|r| r == "--config").and_then(|i| args.get(i + 1));
+    let config = match config_path {
+        Some(path) => Config::load(Path::new(path)).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            std::process::exit(2);
+        }),
+        None => Config::default(),
+    };
+
+    // Window titles are redacted in every output unless --no-redact is given explicitly
+    let mut redactor = if is_no_redact {
+        Redactor::disabled()
+    } else {
+        Redactor::new(&config.redaction).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            std::process::exit(2);
+        })
+    };
+
+    // Refreshed provider ranges from --update-ip-ranges; unusable files are fatal like --config
+    let ip_ranges = args.iter().position(|r| r == "--ip-ranges").and_then(|i| args.get(i + 1)).map(|path| {
+        ip_ranges::IpRangeDb::load(Path::new(path)).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            std::process::exit(2);
+        })
+    });
+
+    // Offline replay of a --record-signals capture; never touches the OS sensors
+    if let Some(path) = args.iter().position(|r| r == "--replay").and_then(|i| args.get(i + 1)) {
+        std::process::exit(replay::run(Path::new(path), &config));
+    }
+
+    let record_signals_path = args.iter()
+        .position(|r| r == "--record-signals")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| PathBuf::from(s));
+
     let log_dir = args.iter()
         .position(|r| r == "--log-dir")
         .and_then(|i| args.get(i + 1))
         .map(|s| PathBuf::from(s));
 
-    if !is_stream {
+    // Seal every JSON log entry to this public key; an unusable key is fatal
+    let log_sealer = args.iter()
+        .position(|r| r == "--encrypt-logs")
+        .and_then(|i| args.get(i + 1))
+        .map(|key| sealed_log::LogSealer::new(key).unwrap_or_else(|e| {
+            log::error!("--encrypt-logs: {}", e);
+            std::process::exit(2);
+        }));
+
+    // Unchanged JSON log snapshots are rewritten this often (0: only on changes)
+    let log_keepalive_secs = args.iter()
+        .position(|r| r == "--log-keepalive")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.parse::<u64>().unwrap_or_else(|_| {
+            log::warn!("Invalid --log-keepalive '{}', using {}s", s, output_router::DEFAULT_KEEPALIVE_SECS);
+            output_router::DEFAULT_KEEPALIVE_SECS
+        }))
+        .unwrap_or(output_router::DEFAULT_KEEPALIVE_SECS);
+
+    let ipc_path = args.iter()
+        .position(|r| r == "--ipc")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let grpc_addr = args.iter()
+        .position(|r| r == "--grpc-addr")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let browser_bridge_addr = args.iter()
+        .position(|r| r == "--browser-bridge")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    // Pairing token the extension sends with its reports
+    let browser_bridge_token = env::var("VALIDATOR_BRIDGE_TOKEN").ok().or_else(|| {
+        args.iter()
+            .position(|r| r == "--browser-bridge-token")
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    });
+
+    // Listens on the LAN for the mobile companion apps
+    let companion_addr = args.iter()
+        .position(|r| r == "--companion")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    // Pairing token the companion apps sign with; the environment keeps it out of the process list
+    let companion_token = env::var("VALIDATOR_COMPANION_TOKEN").ok().or_else(|| {
+        args.iter()
+            .position(|r| r == "--companion-token")
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    });
+
+    let firefox_remote_addr = args.iter()
+        .position(|r| r == "--firefox-remote")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let webrtc_log_dir = args.iter()
+        .position(|r| r == "--webrtc-log-dir")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| PathBuf::from(s));
+
+    let webhook_url = args.iter()
+        .position(|r| r == "--webhook-url")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    // Prefer the environment variable so the secret stays out of the process list
+    let webhook_secret = env::var("VALIDATOR_WEBHOOK_SECRET").ok().or_else(|| {
+        args.iter()
+            .position(|r| r == "--webhook-secret")
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    });
+
+    let mqtt_broker = args.iter()
+        .position(|r| r == "--mqtt-broker")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    // OTLP/HTTP collector base URL, e.g. http://localhost:4318
+    let otel_endpoint = args.iter()
+        .position(|r| r == "--otel-endpoint")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let is_notify = args.contains(&"--notify".to_string());
+
+    let notify_call_template = args.iter()
+        .position(|r| r == "--notify-call-template")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let notify_recording_template = args.iter()
+        .position(|r| r == "--notify-recording-template")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    // Teams call state source: `--teams local` or `--teams graph`
+    let teams_mode = args.iter()
+        .position(|r| r == "--teams")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    // Signal provider libraries, one per --plugin
+    let plugin_paths: Vec<PathBuf> = args.iter()
+        .enumerate()
+        .filter(|(_, arg)| *arg == "--plugin")
+        .filter_map(|(i, _)| args.get(i + 1).map(PathBuf::from))
+        .collect();
+
+    // Calendar for scheduled meetings: an ICS file, an ICS feed or CalDAV URL, or `graph`
+    let calendar_source = args.iter()
+        .position(|r| r == "--calendar")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let private_window_policy = args.iter()
+        .position(|r| r == "--private-windows")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| PrivateWindowPolicy::parse(s).unwrap_or_else(|| {
+            log::warn!("Unknown --private-windows '{}', using ignore", s);
+            PrivateWindowPolicy::Ignore
+        }))
+        .unwrap_or(PrivateWindowPolicy::Ignore);
+
+    let console_style = if args.contains(&"--plain".to_string()) {
+        ConsoleStyle::Plain
+    } else {
+        ConsoleStyle::Standard
+    };
+
+    // Every NDJSON sink: the legacy flags plus the `outputs` config section
+    let mut sink_configs = Vec::new();
+    if is_stream {
+        sink_configs.push(SinkConfig::stream(stream_mode));
+    }
+    if let Some(dir) = &log_dir {
+        sink_configs.push(SinkConfig::log_dir(dir, is_explain, log_sealer.is_some(), log_keepalive_secs));
+    }
+    if let Some(url) = webhook_url {
+        sink_configs.push(SinkConfig::webhook(url));
+    }
+    sink_configs.extend(config.outputs.iter().cloned());
+    let has_file_sink = sink_configs.iter().any(|sink| matches!(sink, SinkConfig::File { .. }));
+    // Sinks, command replies and heartbeat pings share one writer, so a stalled reader never blocks the loop
+    let stdout_writer = stdout_writer::StdoutWriter::start();
+    let mut output_router = OutputRouter::new(sink_configs, log_sealer.as_ref(), webhook_secret, &stdout_writer);
+
+    // Exit with the app that launched the monitor; an unusable pid is fatal
+    if let Some(pid) = args.iter().position(|r| r == "--parent-pid").and_then(|i| args.get(i + 1)) {
+        let watched = pid.parse::<u32>().map_err(|_| format!("invalid pid '{}'", pid)).and_then(watchdog::watch_parent);
+        if let Err(e) = watched {
+            log::error!("--parent-pid: {}", e);
+            std::process::exit(2);
+        }
+    }
+    // Ping/pong with the parent over stdin and stdout
+    let mut heartbeat = args.iter()
+        .position(|r| r == "--heartbeat")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.parse::<u64>().unwrap_or_else(|_| {
+            log::warn!("Invalid --heartbeat '{}', using 5s", s);
+            5
+        }))
+        .map(|secs| watchdog::Heartbeat::start(Duration::from_secs(secs.max(1))));
+    // Commands on stdin while streaming; the heartbeat reads them itself
+    let stdin_commands = (is_stream && heartbeat.is_none()).then(|| control::StdinCommands::spawn(|_| false, false));
+
+    // NDJSON on stdout must stay parseable, so banners go away and call updates use stderr
+    let is_stdout_taken = output_router.uses_stdout() || heartbeat.is_some();
+
+    if !is_stdout_taken && console_style == ConsoleStyle::Plain {
+        let os_info = get_os_info();
+        println!(
+            "Call validator started. Tracking Meet, Slack, Zoom, Teams, WhatsApp, Telegram and Signal on {}, {}.",
+            os_info.os_name, os_info.arch
+        );
+    } else if !is_stdout_taken {
         // Only print headers if NOT streaming JSON to stdout
         println!("\n=== Recordio Call Validator (Enhanced) ===");
-        println!("Tracking: Meet, Slack, Zoom, Teams, WhatsApp");
+        println!("Tracking: Meet, Slack, Zoom, Teams, WhatsApp, Telegram, Signal");
         // println!("Features: WebRTC Detection, Voice Note Filtering, YouTube Filtering");
         // println!("Console: Call start/end only");
         // println!("Full logs: audio_monitor_rust.json");
@@ -296,279 +772,736 @@ fn main() {
         println!("\n=== Worker Installed (System Information) ===");
         println!("Operating System: {}", os_info.os_name);
         println!("Architecture: {}", os_info.arch);
+        if subprocess::is_disabled() {
+            println!("Mode: no-subprocess (native APIs only)");
+        }
+        match private_window_policy {
+            PrivateWindowPolicy::Exclude => println!("Private windows: excluded"),
+            PrivateWindowPolicy::Tag => println!("Private windows: tagged"),
+            PrivateWindowPolicy::Ignore => {}
+        }
+        if !redactor.is_enabled() {
+            println!("Window titles: not redacted (--no-redact)");
+        }
         // println!("OS Family: {}", os_info.family);
         // println!("Platform: {}", os_info.platform_details);
         // println!();
     }
 
-    let mut previous_state = MonitorState {
-        active_call: None,
-        other_audio_sources: Vec::new(),
-    };
+    // WSL and remote sessions hide most of what the probes look for; say so up front
+    let environment = environment::detect();
+    if let Some(limitation) = environment.limitation() {
+        log::info!("Running in a {} session: {}", environment.as_str(), limitation);
+    }
 
-    // Initialize network monitor and correlation engine
+    // Initialize network monitor and call tracking
     let mut network_monitor = NetworkMonitor::new();
-    let correlation_engine = CorrelationEngine::new();
+    if let Some(ip_ranges) = ip_ranges {
+        network_monitor = network_monitor.with_ip_ranges(ip_ranges);
+    }
+    let mut validator = CallValidator::new(SystemAudio, network_monitor)
+        .with_private_window_policy(private_window_policy)
+        .with_environment(environment);
+    let correlation_engine = CorrelationEngine::new()
+        .with_scoring(config.scoring.clone())
+        .with_hysteresis(fast_start_hysteresis(config.hysteresis.clone(), is_fast_start));
+    let mut tracker = CallTracker::new(correlation_engine).with_environment(environment);
+
+    // The active call survives crashes and updates: --state-file, or call_state.json in --log-dir.
+    // The file is plaintext, so --encrypt-logs leaves the log directory without one
+    let mut call_state_file = args.iter()
+        .position(|r| r == "--state-file")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from)
+        .or_else(|| log_dir.as_ref().filter(|_| log_sealer.is_none()).map(|dir| dir.join("call_state.json")))
+        .map(call_state::CallStateFile::new);
+    if let Some((call, title_redacted)) = call_state_file.as_ref().and_then(|file| file.load(SystemTime::now())) {
+        log::info!("Resuming the {} call started at {}", call.app, call.started_at);
+        // The saved title is what the previous run wrote out; hashing it again would change it
+        if title_redacted {
+            redactor.keep_redacted(&call.window_title);
+        }
+        tracker.resume(call);
+    }
 
-    loop {
-        let mut current_state = MonitorState {
-            active_call: None,
-            other_audio_sources: Vec::new(),
+    // Latest state and health, read by the servers below
+    let state_store = state_store::StateStore::new(redactor.state(tracker.state()));
+
+    // Local IPC transport shared by multiple consumers
+    let ipc_server = ipc_path.as_ref().and_then(|path| match IpcServer::bind(path, state_store.reader()) {
+        Ok(server) => Some(server),
+        Err(e) => {
+            log::warn!("Failed to start IPC server on {}: {}", path, e);
+            None
+        }
+    });
+
+    #[cfg(feature = "grpc")]
+    let grpc_server = grpc_addr.as_ref().and_then(|addr| {
+        let started = addr
+            .parse()
+            .map_err(|e| format!("invalid address: {}", e))
+            .and_then(|addr| grpc::GrpcServer::start(addr, state_store.reader()));
+        match started {
+            Ok(server) => Some(server),
+            Err(e) => {
+                log::warn!("Failed to start gRPC server on {}: {}", addr, e);
+                None
+            }
+        }
+    });
+
+    #[cfg(not(feature = "grpc"))]
+    if grpc_addr.is_some() {
+        log::warn!("--grpc-addr ignored: built without the `grpc` feature");
+    }
+
+    // Tab-level browser attribution from the companion extension
+    let browser_bridge = browser_bridge_addr.as_ref().and_then(|addr| {
+        let started = match browser_bridge_token.as_deref() {
+            Some(token) => addr
+                .parse()
+                .map_err(|e| format!("invalid address: {}", e))
+                .and_then(|addr| BrowserBridge::start(addr, token)),
+            None => Err("no pairing token (VALIDATOR_BRIDGE_TOKEN or --browser-bridge-token)".to_string()),
+        };
+        match started {
+            Ok(bridge) => Some(bridge),
+            Err(e) => {
+                log::warn!("Failed to start browser bridge on {}: {}", addr, e);
+                None
+            }
+        }
+    });
+
+    // Tabs and PeerConnections of a Firefox started with --marionette
+    let firefox_remote = firefox_remote_addr.as_ref().and_then(|addr| {
+        let started = addr
+            .parse()
+            .map_err(|e| format!("invalid address: {}", e))
+            .and_then(firefox::FirefoxRemote::start);
+        match started {
+            Ok(remote) => Some(remote),
+            Err(e) => {
+                log::warn!("Failed to start the Firefox remote on {}: {}", addr, e);
+                None
+            }
+        }
+    });
+
+    // "In a call on the phone" signals from the mobile companion apps
+    let companion = companion_addr.as_ref().and_then(|addr| {
+        let started = match companion_token.as_deref() {
+            Some(token) => addr
+                .parse()
+                .map_err(|e| format!("invalid address: {}", e))
+                .and_then(|addr| companion::CompanionServer::start(addr, token)),
+            None => Err("no pairing token (VALIDATOR_COMPANION_TOKEN or --companion-token)".to_string()),
         };
+        match started {
+            Ok(server) => Some(server),
+            Err(e) => {
+                log::warn!("Failed to start the companion endpoint on {}: {}", addr, e);
+                None
+            }
+        }
+    });
+
+    // Live PeerConnections from Chromium's WebRTC event log directory
+    let webrtc_event_logs = webrtc_log_dir.as_deref().map(webrtc_event_log::WebRtcEventLogs::new);
+
+    #[cfg(feature = "mqtt")]
+    let mut mqtt_publisher = mqtt_broker.as_ref().and_then(|broker| {
+        let prefix = args.iter()
+            .position(|r| r == "--mqtt-prefix")
+            .and_then(|i| args.get(i + 1))
+            .map(|s| s.as_str())
+            .unwrap_or("");
+        let credentials = env::var("VALIDATOR_MQTT_USERNAME")
+            .ok()
+            .map(|user| (user, env::var("VALIDATOR_MQTT_PASSWORD").unwrap_or_default()));
+        match mqtt::MqttPublisher::connect(broker, prefix, credentials) {
+            Ok(publisher) => Some(publisher),
+            Err(e) => {
+                log::warn!("Failed to start MQTT publisher for {}: {}", broker, e);
+                None
+            }
+        }
+    });
+
+    #[cfg(not(feature = "mqtt"))]
+    if mqtt_broker.is_some() {
+        log::warn!("--mqtt-broker ignored: built without the `mqtt` feature");
+    }
 
-        let mut mic_sources: Vec<AudioSource> = Vec::new();
-        let mut audio_sources: Vec<AudioSource> = Vec::new();
-
-        // Get microphone sources
-        if let Ok(mut monitor) = MicMonitor::new() {
-            if let Ok(report) = monitor.build_status_report() {
-                for app_name in &report.conflicts.apps_using_mic {
-                    mic_sources.push(AudioSource {
-                        name: app_name.clone(),
-                        process_id: 0,
-                        window_title: String::new(),
-                        detected_app: detect_call_app(app_name, ""),
-                    });
+    #[cfg(feature = "otel")]
+    let mut otel_exporter = otel_endpoint.or_else(|| env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()).and_then(|endpoint| {
+        match otel::OtelExporter::new(&endpoint) {
+            Ok(exporter) => Some(exporter),
+            Err(e) => {
+                log::warn!("Failed to start OpenTelemetry export to {}: {}", endpoint, e);
+                None
+            }
+        }
+    });
+
+    #[cfg(not(feature = "otel"))]
+    if otel_endpoint.is_some() {
+        log::warn!("--otel-endpoint ignored: built without the `otel` feature");
+    }
+
+    #[cfg(feature = "notify")]
+    let mut notifier = if is_notify {
+        let mut templates = notify::NotifyTemplates::default();
+        if let Some(template) = notify_call_template {
+            templates.call_started = template;
+        }
+        if let Some(template) = notify_recording_template {
+            templates.recording_without_call = template;
+        }
+        Some(notify::Notifier::new(templates))
+    } else {
+        None
+    };
+
+    #[cfg(not(feature = "notify"))]
+    if is_notify || notify_call_template.is_some() || notify_recording_template.is_some() {
+        log::warn!("--notify ignored: built without the `notify` feature");
+    }
+
+    #[cfg(feature = "teams")]
+    let teams_presence = teams_mode.as_deref().and_then(|mode| {
+        let source = match mode {
+            "local" => teams::TeamsSource::LocalClient {
+                token_file: args.iter()
+                    .position(|r| r == "--teams-token-file")
+                    .and_then(|i| args.get(i + 1))
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| PathBuf::from("teams_token.txt")),
+            },
+            "graph" => match env::var("VALIDATOR_TEAMS_GRAPH_TOKEN") {
+                Ok(token) => teams::TeamsSource::Graph { token },
+                Err(_) => {
+                    log::warn!("--teams graph needs VALIDATOR_TEAMS_GRAPH_TOKEN");
+                    return None;
                 }
+            },
+            _ => {
+                log::warn!("Unknown --teams '{}', expected local or graph", mode);
+                return None;
             }
+        };
+        Some(teams::TeamsPresence::start(source))
+    });
+
+    #[cfg(not(feature = "teams"))]
+    if teams_mode.is_some() {
+        log::warn!("--teams ignored: built without the `teams` feature");
+    }
+
+    #[cfg(feature = "calendar")]
+    let calendar = calendar_source.as_deref().and_then(|value| match calendar::CalendarSource::parse(value) {
+        Ok(source) => Some(calendar::Calendar::start(source)),
+        Err(e) => {
+            log::warn!("{}", e);
+            None
+        }
+    });
+
+    #[cfg(not(feature = "calendar"))]
+    if calendar_source.is_some() {
+        log::warn!("--calendar ignored: built without the `calendar` feature");
+    }
+
+    // Integrator evidence: providers compiled in, then --plugin libraries
+    let mut signal_providers = signal_providers::SignalProviders::default();
+    signal_providers::register_builtin(&mut signal_providers);
+    #[cfg(feature = "plugins")]
+    for path in &plugin_paths {
+        match signal_providers::load_plugin(path) {
+            Ok(provider) => signal_providers.register(provider),
+            Err(e) => log::warn!("--plugin: {}", e),
+        }
+    }
+    #[cfg(not(feature = "plugins"))]
+    if !plugin_paths.is_empty() {
+        log::warn!("--plugin ignored: built without the `plugins` feature");
+    }
+
+    // Audio capture only runs with explicit consent
+    #[cfg(feature = "capture")]
+    if is_allow_audio_capture {
+        capture::grant_consent();
+    }
+
+    // Render stream hashes and speech/silence boundaries for recorder alignment
+    #[cfg(feature = "capture")]
+    let mut audio_markers = match (is_audio_markers, is_allow_audio_capture) {
+        (true, true) => Some(audio_markers::AudioMarkers::default()),
+        (true, false) => {
+            log::warn!("--audio-markers ignored: audio capture needs --allow-audio-capture");
+            None
+        }
+        (false, _) => None,
+    };
+
+    #[cfg(not(feature = "capture"))]
+    for (is_set, flag) in [(is_audio_markers, "--audio-markers"), (is_allow_audio_capture, "--allow-audio-capture")] {
+        if is_set {
+            log::warn!("{} ignored: built without the `capture` feature", flag);
+        }
+    }
+
+    if is_explain && log_dir.is_none() {
+        log::warn!("--explain writes to the JSON log; pass --log-dir to enable it");
+    }
+    if log_sealer.is_some() && !has_file_sink {
+        log::warn!("--encrypt-logs applies to the JSON log; pass --log-dir to enable it");
+    }
+
+    let mut signal_recorder = record_signals_path.as_deref().and_then(|path| match replay::SignalRecorder::open(path) {
+        Ok(recorder) => Some(recorder),
+        Err(e) => {
+            log::warn!("Failed to start signal recording: {}", e);
+            None
         }
+    });
+
+    // Diagnostic: compare audio-derived and network-derived call sets each tick
+    let mut cross_check = if is_cross_check { Some(CrossCheck::new()) } else { None };
+
+    // Recording banners and processes of the active call's app, every few seconds
+    let mut recording_probe = recording_probe::RecordingProbe::default();
+
+    // Ping, socket drops and output underruns of the active call, on a background thread
+    let mut quality_probe = call_quality::QualityProbe::default();
+
+    // Default output and capture devices of the active call, every few seconds
+    let mut routing_probe = echo_risk::RoutingProbe::default();
+
+    // CPU and GPU load of the active call's (or the call candidates') process
+    let mut load_probe = process_load::LoadProbe::default();
+
+    // Output peaks of the active call's (or the call candidates') own process tree
+    #[cfg(target_os = "windows")]
+    let mut render_meters = wasapi_audio::wasapi::RenderMeters::default();
+
+    // Mute, camera, participant and timer controls of the active call's client
+    #[cfg(feature = "uia")]
+    let mut uia_probe = uia::UiaProbe::default();
+
+    // Audio, mic, network and window title refreshes, each on its own interval, slower
+    // while idle and faster during calls
+    let mut scheduler = scheduler::Scheduler::new(config.polling.clone());
+    // --fast-start: new or newly active audio sessions wake the loop instead of the next interval
+    let session_events = if is_fast_start {
+        session_events::SessionEvents::start()
+            .map_err(|e| log::warn!("--fast-start: no session notifications ({}); keeping the regular pace", e))
+            .ok()
+    } else {
+        None
+    };
+    // Signed releases from the `update` config section, installed in the background
+    self_update::clean_up();
+    let auto_updater = if is_auto_update {
+        self_update::AutoUpdater::spawn(config.update.clone())
+            .map_err(|e| log::warn!("--auto-update disabled: {}", e))
+            .ok()
+    } else {
+        None
+    };
+    // Edits to the --config file apply while running
+    let mut config_watcher = config_path.map(|path| ConfigWatcher::new(PathBuf::from(path), config.clone()));
+    // Meeting links and web app URLs on the command lines of the processes playing audio
+    let mut command_lines = meeting_id::CommandLines::default();
+    // Zoom's meeting window is looked up with the window titles
+    let mut zoom_meeting = None;
+    // And Slack's huddle traces, following its log from tick to tick
+    let mut huddle_probe = slack_huddle::HuddleProbe::default();
+    let mut slack_huddle = None;
+    // On battery or in low power mode, idle and skip socket scans outside calls
+    let mut power_monitor = power::PowerMonitor::default();
+    let mut capability_level = capability::CapabilityLevel::Full;
+    // Time spent per subsystem this cycle, printed with --self-profile
+    let mut cycle_profile = profile::CycleProfile::default();
+
+    // Switched by the pause/privacy/resume control commands and SIGUSR1
+    let mut run_mode = RunMode::Monitoring;
+    control::install_pause_signal();
 
-        // Get audio output sources
-        if let Ok(mut monitor) = AudioOutputMonitor::new() {
-            if let Ok(report) = monitor.build_status_report() {
-                for app in report.active_apps {
-                    if app.is_playing || app.peak_level > 0.001 {
-                        audio_sources.push(AudioSource {
-                            name: app.name.clone(),
-                            process_id: app.process_id,
-                            window_title: app.window_title.clone(),
-                            detected_app: detect_call_app(&app.name, &app.window_title),
-                        });
+    loop {
+        #[cfg(feature = "otel")]
+        let cycle_start = std::time::Instant::now();
+        if control::take_pause_toggle() {
+            run_mode = if run_mode == RunMode::Paused { RunMode::Monitoring } else { RunMode::Paused };
+            log::info!("SIGUSR1: {}", run_mode.describe());
+        }
+
+        cycle_profile.clear();
+
+        // A reload keeps the active call: only thresholds, intervals and redaction change
+        let mut reload_event = None;
+        match config_watcher.as_mut().and_then(|watcher| watcher.poll(Instant::now())) {
+            Some(Ok((reloaded, sections))) => {
+                let path = config_watcher.as_ref().map(|watcher| watcher.path().display().to_string()).unwrap_or_default();
+                log::info!("Reloaded {} ({})", path, sections.join(", "));
+                tracker.reconfigure(reloaded.scoring, fast_start_hysteresis(reloaded.hysteresis, is_fast_start));
+                scheduler.set_intervals(reloaded.polling);
+                if !is_no_redact {
+                    match Redactor::new(&reloaded.redaction) {
+                        Ok(reloaded) => redactor = reloaded.keeping_redacted(&redactor),
+                        Err(e) => log::warn!("Keeping the previous redaction: {}", e),
                     }
                 }
+                if sections.contains(&"outputs") {
+                    log::warn!("Output changes apply after a restart");
+                }
+                reload_event = Some(events::MonitorEvent::ConfigReloaded(events::ConfigReloadedPayload {
+                    path,
+                    sections: sections.iter().map(|section| section.to_string()).collect(),
+                }));
             }
+            Some(Err(e)) => log::warn!("Config not reloaded, keeping the previous one: {}", e),
+            None => {}
         }
 
-        // Get WebRTC signals from network monitor (updates internal state)
-        let _webrtc_signals = network_monitor.get_webrtc_signals();
+        let power = power_monitor.profile();
+        scheduler.set_power_saving(power.is_saving());
+        let due = scheduler.due(Instant::now());
+        let sample = if run_mode == RunMode::Paused {
+            // Nothing is sensed; an active call ends through the normal end grace period
+            Sample::default()
+        } else {
+            if scheduler.skips_expensive_probes() {
+                validator.clear_network();
+                zoom_meeting = None;
+                slack_huddle = None;
+            }
+            let mut process_tree = cycle_profile.time("process_tree", ProcessTree::snapshot);
+            let sensed = validator.sense(&mut process_tree, due);
+            cycle_profile.extend(validator.profile());
+            if due.window_titles && !scheduler.skips_expensive_probes() {
+                zoom_meeting = cycle_profile.time("zoom_probe", zoom_probe::probe).map(|meeting| zoom_probe::ZoomMeeting {
+                    process_id: process_tree.root(meeting.process_id),
+                    ..meeting
+                });
+                slack_huddle = cycle_profile.time("huddle_probe", || huddle_probe.probe(&mut process_tree));
+            }
+
+            command_lines.refresh(sensed.audio_sources.iter().map(|src| src.process_id));
 
-        // Check if previous call is still active
-        if let Some(prev_call) = &previous_state.active_call {
-            // Build signal for existing call
-            let audio_src = audio_sources.iter().find(|src| src.process_id == prev_call.process_id);
-            let has_mic = mic_sources.iter().any(|src| {
-                if let Some(detected) = &src.detected_app {
-                    detected == &prev_call.app
-                } else {
-                    false
-                }
-            });
-            let has_audio = audio_src.is_some();
-            let has_webrtc = network_monitor.has_webrtc_activity(prev_call.process_id);
-
-            let audio_peak_level = audio_src.map(|_src| 0.1).unwrap_or(0.0); // Simplified
-            let window_title = audio_src
-                .map(|src| src.window_title.clone())
-                .unwrap_or_else(|| prev_call.window_title.clone());
-
-            // Calculate call duration
-            let call_duration = SystemTime::now()
-                .duration_since(prev_call.call_started_system_time)
-                .unwrap_or(Duration::from_secs(0));
-
-            let signal = MultiSignal {
-                process_id: prev_call.process_id,
-                process_name: prev_call.app.clone(),
-                window_title: window_title.clone(),
-                has_mic_active: has_mic,
-                has_audio_output: has_audio,
-                audio_peak_level,
-                has_webrtc_connection: has_webrtc,
-                webrtc_started_at: None,
-                detected_app: Some(prev_call.app.clone()),
-                duration: call_duration,
+            if let Some(checker) = cross_check.as_mut() {
+                checker.run(&sensed.audio_sources, &sensed.mic_sources, &sensed.webrtc_signals);
+            }
+
+            let active_call = tracker.state().active_call.as_ref();
+            let recording = cycle_profile.time("recording_probe", || recording_probe.probe(active_call));
+            #[cfg(feature = "uia")]
+            let call_controls = cycle_profile.time("uia", || uia_probe.probe(active_call));
+            let media_remotes = active_call.map(|call| sensed.media_remotes(call.process_id, &mut process_tree)).unwrap_or_default();
+            let call_quality = quality_probe.probe(active_call, media_remotes);
+            let audio_routing = cycle_profile.time("routing_probe", || routing_probe.probe(active_call));
+            let process_load = {
+                let probed: Vec<u32> = match active_call {
+                    Some(call) => vec![call.process_id],
+                    None => sensed.audio_sources.iter().filter(|src| src.detected_app.is_some()).map(|src| src.process_id).collect(),
+                };
+                cycle_profile.time("load_probe", || load_probe.probe(probed))
+            };
+            #[cfg(target_os = "windows")]
+            let render_peaks = {
+                let metered: Vec<u32> = match active_call {
+                    Some(call) => vec![call.process_id],
+                    None => sensed.audio_sources.iter().filter(|src| src.detected_app.is_some()).map(|src| src.process_id).collect(),
+                };
+                cycle_profile.time("render_meters", || render_meters.update(metered))
             };
 
-            // Enhanced: Use correlation engine to determine if call should continue
-            // This handles mic/camera off scenarios
-            let should_continue = correlation_engine.should_maintain_call(&signal, true);
-
-            if should_continue {
-                // Call is still active - update it
-                let detection = correlation_engine.detect_call(&signal);
-
-                current_state.active_call = Some(CallInfo {
-                    app: prev_call.app.clone(),
-                    process_id: prev_call.process_id,
-                    window_title,
-                    has_mic,
-                    has_audio,
-                    has_webrtc,
-                    confidence: detection.confidence,
-                    started_at: prev_call.started_at.clone(),
-                    last_seen: SystemTime::now(),
-                    call_started_system_time: prev_call.call_started_system_time,
-                });
-            } else {
-                // Call signals lost - check grace period
-                let elapsed = SystemTime::now()
-                    .duration_since(prev_call.last_seen)
-                    .unwrap_or(Duration::from_secs(0));
-
-                if elapsed.as_secs() < CALL_END_GRACE_PERIOD {
-                    // Still within grace period - keep the call active
-                    current_state.active_call = Some(prev_call.clone());
-                }
-                // else: grace period expired, call will end
+            Sample {
+                browser_tabs: browser_bridge
+                    .as_ref()
+                    .map(|bridge| bridge.tabs())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(firefox_remote.as_ref().map(|remote| remote.tabs()).unwrap_or_default())
+                    .collect(),
+                browser_peer_connection: webrtc_event_logs.as_ref().is_some_and(|logs| logs.any_active())
+                    || firefox_remote.as_ref().is_some_and(|remote| remote.has_peer_connection()),
+                zoom_meeting: zoom_meeting.clone(),
+                slack_huddle: slack_huddle.clone(),
+                command_line_meetings: command_lines.meetings(),
+                command_line_apps: command_lines.apps(),
+                recording,
+                call_quality,
+                audio_routing,
+                process_load,
+                #[cfg(feature = "uia")]
+                call_controls,
+                #[cfg(target_os = "windows")]
+                render_peaks,
+                #[cfg(feature = "teams")]
+                teams_in_call: teams_presence.as_ref().and_then(|presence| presence.in_call()),
+                #[cfg(feature = "calendar")]
+                scheduled_meetings: calendar.as_ref().map(|calendar| calendar.meetings_at(SystemTime::now())).unwrap_or_default(),
+                contributions: cycle_profile.time("signal_providers", || signal_providers.poll()),
+                external_calls: companion.as_ref().map(|server| server.external_calls()).unwrap_or_default(),
+                ..sensed.to_sample(&mut process_tree)
             }
-        } else {
-            // No previous call - detect new calls using enhanced correlation engine
-            for audio_src in &audio_sources {
-                if let Some(detected) = &audio_src.detected_app {
-                    let is_browser = is_browser_process(&audio_src.name);
-
-                    // Check if this app has mic active
-                    let has_mic = if is_browser {
-                        // For browsers, check if ANY browser is using the mic
-                        // (can't correlate specific tabs without browser extension)
-                        mic_sources.iter().any(|mic_src| is_browser_process(&mic_src.name))
-                    } else {
-                        // For native apps, require exact app match
-                        mic_sources.iter().any(|mic_src| {
-                            if let Some(mic_detected) = &mic_src.detected_app {
-                                mic_detected == detected
-                            } else {
-                                false
-                            }
-                        })
-                    };
-
-                    // Check for WebRTC connection
-                    let has_webrtc = network_monitor.has_webrtc_activity(audio_src.process_id);
-
-                    // Build multi-signal for correlation engine
-                    let signal = MultiSignal {
-                        process_id: audio_src.process_id,
-                        process_name: audio_src.name.clone(),
-                        window_title: audio_src.window_title.clone(),
-                        has_mic_active: has_mic,
-                        has_audio_output: true,
-                        audio_peak_level: 0.1, // Simplified
-                        has_webrtc_connection: has_webrtc,
-                        webrtc_started_at: None,
-                        detected_app: Some(detected.clone()),
-                        duration: Duration::from_secs(0), // New call
-                    };
-
-                    // ENHANCED: Use correlation engine to detect call
-                    // This filters out voice notes, YouTube, and other false positives
-                    let detection = correlation_engine.detect_call(&signal);
-
-                    // DEBUG: Show what's being detected
-                    if !is_stream && (detection.confidence > 0.3 || has_mic || has_webrtc) {
-                        eprintln!("[DEBUG] App: {} | Mic: {} | Audio: {} | WebRTC: {} | Confidence: {:.0}% | Call: {}",
-                            detected, has_mic, true, has_webrtc, detection.confidence * 100.0, detection.is_call);
-                        if !detection.reasons.is_empty() {
-                            eprintln!("[DEBUG] Reasons: {:?}", detection.reasons);
-                        }
-                    }
+        };
+
+        let now = SystemTime::now();
+        // Raw samples carry titles and process names, so only normal monitoring is recorded,
+        // and never those of apps outside `tracked_apps`
+        if let Some(recorder) = signal_recorder.as_mut().filter(|_| run_mode == RunMode::Monitoring) {
+            recorder.record(&sample.tracked_only(tracker.scoring()), now);
+        }
 
-                    if detection.is_call {
-                        // High-confidence call detected!
-                        let now = SystemTime::now();
-                        current_state.active_call = Some(CallInfo {
-                            app: detected.clone(),
-                            process_id: audio_src.process_id,
-                            window_title: audio_src.window_title.clone(),
-                            has_mic,
-                            has_audio: true,
-                            has_webrtc,
-                            confidence: detection.confidence,
-                            started_at: chrono::Local::now().format("%H:%M:%S").to_string(),
-                            last_seen: now,
-                            call_started_system_time: now,
-                        });
-                        break;
+        let previous_state = tracker.state().clone();
+        let mut tick_events = cycle_profile.time("track", || tracker.update(&sample, now));
+        tick_events.extend(reload_event);
+        // The loopback tap only runs while a call is monitored, never paused or private
+        #[cfg(feature = "capture")]
+        if let Some(markers) = audio_markers.as_mut() {
+            let call = tracker.state().active_call.as_ref().filter(|_| run_mode == RunMode::Monitoring);
+            tick_events.extend(cycle_profile.time("audio_markers", || markers.update(call)));
+        }
+        // Privacy mode writes nothing about the call to disk and removes this run's save, so a
+        // crash or restart during a private call starts a new call instead of resuming it
+        if let Some(file) = call_state_file.as_mut() {
+            let call = tracker.state().active_call.as_ref()
+                .filter(|_| run_mode != RunMode::Privacy)
+                .map(|call| redactor.call(call));
+            file.update(call.as_ref(), redactor.is_enabled(), Instant::now());
+        }
+        scheduler.set_pace(scheduler::Pace::of(tracker.phase(), &sample));
+        let output_start = Instant::now();
+        let current_state = tracker.state().clone();
+
+        // Privacy mode: every output below only learns whether a call is active
+        let is_private = run_mode == RunMode::Privacy;
+        let (previous_state, current_state, tick_events) = if is_private {
+            (
+                privacy::call_state_only(&previous_state),
+                privacy::call_state_only(&current_state),
+                privacy::call_events_only(&tick_events),
+            )
+        } else {
+            (previous_state, current_state, tick_events)
+        };
+        let (current_state, tick_events) = (redactor.state(&current_state), redactor.events(&tick_events));
+
+        // At debug level, show what's being detected while looking for a new call
+        if !is_private && previous_state.active_call.is_none() && log::log_enabled!(log::Level::Debug) {
+            for detection in tracker.detections() {
+                let signals = &detection.signals;
+                if detection.confidence > 0.3 || signals.has_mic_active || signals.has_webrtc_connection {
+                    log::debug!("App: {} | Mic: {} | Audio: {} | WebRTC: {} | Confidence: {:.0}% | Call: {}",
+                        signals.detected_app.as_deref().unwrap_or(&signals.process_name),
+                        signals.has_mic_active, signals.has_audio_output, signals.has_webrtc_connection,
+                        detection.confidence * 100.0, detection.is_call);
+                    if !detection.reasons.is_empty() {
+                        log::debug!("Reasons: {:?}", detection.reasons);
                     }
-                    // else: Not a call (voice note, YouTube, etc.) - skip
                 }
             }
         }
 
-        // Collect other audio sources (not the active call)
-        for audio_src in &audio_sources {
-            let is_active_call = if let Some(call) = &current_state.active_call {
-                audio_src.process_id == call.process_id
-            } else {
-                false
-            };
+        // stdout, files, sockets and webhooks, each with its own filter
+        let detections = if output_router.wants_detections() && !is_private {
+            redactor.detections(tracker.detections())
+        } else {
+            Vec::new()
+        };
+        output_router.publish(&current_state, &tick_events, &detections);
+
+        // Log when missing grants or a lost sound server lower what the monitor sees
+        let backend_errors = validator.supervisor().backend_errors();
+        let capability = capability::assess(&backend_errors);
+        if capability.level != capability_level {
+            log::info!(
+                "Capability level: {} ({})",
+                capability.level.as_str(),
+                capability.reason.as_deref().unwrap_or("all data sources available")
+            );
+            capability_level = capability.level;
+        }
+
+        // What the `health` control command reports
+        let health = HealthReport {
+            run_mode: run_mode.describe(),
+            pace: scheduler.pace(),
+            power,
+            power_saving: scheduler.skips_expensive_probes(),
+            errors: validator.supervisor().errors(),
+            failing: validator.supervisor().failing(),
+            backend_errors,
+            capability_level: capability.level,
+            capability_reason: capability.reason,
+            environment,
+            dropped_messages: stdout_writer.dropped(),
+        };
+        let snapshot = state_store.publish(current_state.clone(), health);
+
+        // Serve IPC clients and answer their commands
+        if let Some(server) = &ipc_server {
+            let event_lines: Vec<String> = tick_events
+                .iter()
+                .filter_map(|event| event.to_json_line().ok())
+                .collect();
+
+            server.publish(&event_lines);
 
-            if !is_active_call {
-                current_state.other_audio_sources.push(audio_src.clone());
+            for (client_id, request) in server.pending_requests() {
+                let reply = handle_control_request(&request, &snapshot, &mut run_mode);
+                server.send_to(client_id, &reply);
             }
         }
 
-        // Stream to stdout if requested
-        if is_stream {
-            if let Ok(json) = serde_json::to_string(&current_state) {
-                println!("{}", json);
-            }
+        #[cfg(feature = "grpc")]
+        if let Some(server) = &grpc_server {
+            server.publish(&tick_events);
+            server.handle_commands(|command| match command {
+                ControlCommand::Status => {
+                    CommandResult::ok(command.name(), &serde_json::to_string(&snapshot.state).unwrap_or_default())
+                }
+                ControlCommand::Health => {
+                    CommandResult::ok(command.name(), &serde_json::to_string(&snapshot.health).unwrap_or_default())
+                }
+                _ => execute_command(command, &mut run_mode),
+            });
+        }
+
+        #[cfg(feature = "mqtt")]
+        if let Some(publisher) = mqtt_publisher.as_mut() {
+            publisher.publish(&current_state);
+        }
+
+        #[cfg(feature = "otel")]
+        if let Some(exporter) = otel_exporter.as_mut() {
+            exporter.record(&current_state, &tick_events, cycle_start.elapsed());
         }
 
-        // Log to JSON if log_dir is provided
-        if let Some(ref path) = log_dir {
-            log_to_custom_file(&current_state, path);
+        #[cfg(feature = "notify")]
+        if let Some(notifier) = notifier.as_mut() {
+            notifier.update(&tick_events, current_state.active_call.as_ref(), &sample.mic_sources);
         }
 
-        // Log state changes to console (only if not streaming)
-        if !is_stream {
-            log_state_changes(&previous_state, &current_state);
+        // Commands and pings on stdin are answered from this loop, so a stalled loop stops answering
+        let stdin_requests = match (&heartbeat, &stdin_commands) {
+            (Some(heartbeat), _) => heartbeat.requests(),
+            (None, Some(commands)) => commands.pending(),
+            (None, None) => Vec::new(),
+        };
+        for request in stdin_requests {
+            stdout_writer.send(handle_control_request(&request, &snapshot, &mut run_mode));
+        }
+        if let Some(ping) = heartbeat.as_mut().and_then(|heartbeat| heartbeat.ping(Instant::now())) {
+            stdout_writer.send(ping);
+        }
+
+        log_state_changes(console_style, &previous_state, &current_state, is_stdout_taken);
+
+        cycle_profile.add("output", output_start.elapsed());
+        if is_self_profile {
+            eprintln!("[profile] {}", cycle_profile);
         }
 
-        // Update previous state
-        previous_state = current_state;
+        // Between calls, switch to an update installed in the background
+        if let Some(version) = auto_updater.as_ref().and_then(|updater| updater.installed()) {
+            if tracker.state().active_call.is_none() {
+                log::info!("Restarting into {}", version);
+                self_update::restart(&args);
+            }
+        }
 
-        // Sleep before next check
-        thread::sleep(Duration::from_millis(500));
+        // Sleep until the next subsystem is due, or until an audio session starts
+        let sleep = scheduler.until_next(Instant::now());
+        match &session_events {
+            Some(events) => {
+                if events.wait(sleep) {
+                    // Network too: WebRTC evidence lets a fresh stream past the brief-audio gate
+                    for subsystem in [scheduler::Subsystem::Audio, scheduler::Subsystem::Mic, scheduler::Subsystem::Network] {
+                        scheduler.expedite(subsystem);
+                    }
+                }
+            }
+            None => thread::sleep(sleep),
+        }
     }
 }
 
-/// Log current state to specific file
-fn log_to_custom_file(state: &MonitorState, dir: &PathBuf) {
-    // Ensure directory exists
-    if !dir.exists() {
-        if let Err(e) = std::fs::create_dir_all(dir) {
-            eprintln!("[rust] Failed to create log directory {:?}: {}", dir, e);
-            return;
-        }
+/// Under --fast-start a candidate confirms within FAST_CONFIRM_MS
+fn fast_start_hysteresis(mut hysteresis: HysteresisConfig, is_fast_start: bool) -> HysteresisConfig {
+    if is_fast_start {
+        hysteresis.confirm_ms = hysteresis.confirm_ms.min(session_events::FAST_CONFIRM_MS);
     }
+    hysteresis
+}
 
-    let entry = JsonLogEntry {
-        timestamp: chrono::Local::now().to_rfc3339(),
-        active_call: state.active_call.clone(),
-        other_audio: state.other_audio_sources.clone(),
+/// Execute one control command line and build the reply line
+fn handle_control_request(request: &str, snapshot: &state_store::Snapshot, run_mode: &mut RunMode) -> String {
+    let result = match ControlCommand::parse(request) {
+        Ok(ControlCommand::Status) => {
+            return Envelope::new(EventType::State, &snapshot.state).to_json_line().unwrap_or_default();
+        }
+        Ok(ControlCommand::Health) => {
+            CommandResult::ok("health", &serde_json::to_string(&snapshot.health).unwrap_or_default())
+        }
+        Ok(command) => execute_command(&command, run_mode),
+        Err(e) => CommandResult::error(request.trim(), &e),
     };
 
-    let log_path = dir.join("rust_monitor.log");
+    Envelope::new(EventType::CommandResult, &result)
+        .to_json_line()
+        .unwrap_or_default()
+}
+
+/// RUST_LOG takes env_logger directives (info for the monitor, warn for dependencies by
+/// default); the `set_log_level` command changes the global max level later, within what
+/// the directives let through
+fn init_logging() {
+    // Without RUST_LOG our own logs can be raised at runtime while dependencies stay at warn
+    let default_filter = concat!("warn,", module_path!(), "=trace");
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_filter)).init();
+    if env::var_os("RUST_LOG").is_none() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+}
 
-    match OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)
-    {
-        Ok(mut file) => {
-            if let Ok(json) = serde_json::to_string(&entry) {
-                let _ = writeln!(file, "{}", json);
-            }
+/// Commands whose reply does not depend on the transport
+fn execute_command(command: &ControlCommand, run_mode: &mut RunMode) -> CommandResult {
+    if let Some(mode) = command.run_mode() {
+        if *run_mode != mode {
+            log::info!("Control: {}", mode.describe());
         }
-        Err(e) => {
-            eprintln!("[rust] Failed to open log file {:?}: {}", log_path, e);
+        *run_mode = mode;
+        return CommandResult::ok(command.name(), mode.describe());
+    }
+
+    match command {
+        ControlCommand::Ping => CommandResult::ok(command.name(), "pong"),
+        ControlCommand::SetLogLevel(level) => {
+            log::set_max_level(*level);
+            log::info!("Control: log level {}", level);
+            CommandResult::ok(command.name(), &level.to_string().to_lowercase())
         }
+        _ => CommandResult::ok(command.name(), "ok"),
     }
 }
 
 /// Detect which call app this is
 fn detect_call_app(process_name: &str, window_title: &str) -> Option<String> {
+    // Messengers by process name only: "signal" in any title would be too loose
+    if let Some(profile) = messengers::profile_for(process_name, None) {
+        return Some(profile.app.to_string());
+    }
+
+    // Only the page in a Firefox title, not the brand or the sharing indicator
+    let window_title = if firefox::is_firefox(process_name) {
+        firefox::page_title(window_title).unwrap_or_default()
+    } else {
+        window_title
+    };
     let combined = format!("{} {}", process_name.to_lowercase(), window_title.to_lowercase());
 
     for app in CALL_APPS {
@@ -587,55 +1520,54 @@ fn detect_call_app(process_name: &str, window_title: &str) -> Option<String> {
     None
 }
 
-/// Log only call start/end to console (minimal)
-fn log_state_changes(previous: &MonitorState, current: &MonitorState) {
+/// Log only call start/end to console (minimal), on stderr when stdout carries NDJSON
+fn log_state_changes(style: ConsoleStyle, previous: &MonitorState, current: &MonitorState, to_stderr: bool) {
     let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
 
-    // Call started
-    if previous.active_call.is_none() && current.active_call.is_some() {
-        if let Some(call) = &current.active_call {
-            println!("[{}] ======> CALL STARTED - {}", timestamp, call.app);
-        }
-    }
-    // Call ended
-    else if previous.active_call.is_some() && current.active_call.is_none() {
-        if let Some(prev_call) = &previous.active_call {
-            let duration = calculate_duration(&prev_call.started_at);
-            println!("[{}] ======> CALL ENDED - {} (Duration: {})", timestamp, prev_call.app, duration);
+    let line = match (&previous.active_call, &current.active_call) {
+        // Call started
+        (None, Some(call)) => match style {
+            ConsoleStyle::Standard => format!("[{}] ======> CALL STARTED - {}", timestamp, call.app),
+            ConsoleStyle::Plain => format!("{} {}", timestamp, console::describe_call_started(call)),
+        },
+        // Call ended
+        (Some(prev_call), None) => {
+            let duration_secs = call_duration_secs(prev_call, SystemTime::now());
+            match style {
+                ConsoleStyle::Standard => format!("[{}] ======> CALL ENDED - {} (Duration: {})",
+                    timestamp, prev_call.app, format_duration(duration_secs)),
+                ConsoleStyle::Plain => format!("{} {}", timestamp, console::describe_call_ended(prev_call, duration_secs)),
+            }
         }
+        _ => return,
+    };
+
+    if to_stderr {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
     }
 }
 
-/// Calculate call duration
-fn calculate_duration(started_at: &str) -> String {
-    let now = chrono::Local::now();
-
-    // Parse the start time (HH:MM:SS)
-    if let Ok(start_time) = chrono::NaiveTime::parse_from_str(started_at, "%H:%M:%S") {
-        let current_time = now.time();
-
-        let duration_secs = if current_time >= start_time {
-            (current_time - start_time).num_seconds()
-        } else {
-            // Handle day boundary
-            let seconds_to_midnight = (chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap() - start_time).num_seconds();
-            let seconds_from_midnight = current_time.num_seconds_from_midnight() as i64;
-            seconds_to_midnight + seconds_from_midnight + 1
-        };
+/// Seconds a call has been running, measured on the system clock it was started with
+fn call_duration_secs(call: &CallInfo, now: SystemTime) -> u64 {
+    now.duration_since(call.call_started_system_time)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs()
+}
 
-        let hours = duration_secs / 3600;
-        let minutes = (duration_secs % 3600) / 60;
-        let seconds = duration_secs % 60;
+/// Format a call duration as "1h 2m 3s"
+fn format_duration(duration_secs: u64) -> String {
+    let hours = duration_secs / 3600;
+    let minutes = (duration_secs % 3600) / 60;
+    let seconds = duration_secs % 60;
 
-        if hours > 0 {
-            format!("{}h {}m {}s", hours, minutes, seconds)
-        } else if minutes > 0 {
-            format!("{}m {}s", minutes, seconds)
-        } else {
-            format!("{}s", seconds)
-        }
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
     } else {
-        format!("Started at {}", started_at)
+        format!("{}s", seconds)
     }
 }
 
@@ -643,7 +1575,8 @@ fn calculate_duration(started_at: &str) -> String {
 fn is_browser_process(process_name: &str) -> bool {
     let lower = process_name.to_lowercase();
     lower.contains("chrome") ||
-    lower.contains("firefox") ||
+    firefox::is_firefox(process_name) ||
+    safari::is_safari(process_name) ||
     lower.contains("edge") ||
     lower.contains("msedge") ||
     lower.contains("brave")
@@ -0,0 +1,347 @@
+// Meeting identifiers for correlating calls with calendar events
+// The meeting URL of a call is found in the companion extension's tab, in the command
+// line of the call's process (Chrome/Edge `--app=` shortcuts, Zoom's `--url=zoommtg://`),
+// or, for Meet and Zoom, in the window title. Only the part naming the meeting is kept:
+// no scheme, query string or fragment, so passcodes (`pwd=`, `p=`) never reach an output.
+//
+//   https://meet.google.com/abc-defg-hij?authuser=0     -> meet.google.com/abc-defg-hij
+//   https://us02web.zoom.us/j/81234567890?pwd=...       -> zoom.us/j/81234567890
+//   zoommtg://zoom.us/join?action=join&confno=812...    -> zoom.us/j/812...
+//   https://teams.microsoft.com/l/meetup-join/19%3ameeting_X%40thread.v2/0?context=...
+//                                                       -> teams.microsoft.com/l/meetup-join/19:meeting_X@thread.v2
+//   "Meet - abc-defg-hij"                               -> meet.google.com/abc-defg-hij
+//
+// The same command lines name the call app of an installed web app (a Meet PWA runs as
+// `chrome --app-id=…`, an Edge app as `msedge --app-url=https://teams.microsoft.com/…`),
+// whose window title often doesn't.
+
+use crate::browser_bridge;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Meeting identifier from a meeting URL
+pub fn from_url(url: &str) -> Option<String> {
+    // Hosts and schemes compare case-insensitively; Teams thread ids and Webex names do not
+    let url = url.trim();
+    let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+    let scheme = scheme.to_lowercase();
+    let (location, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let location = location.split('#').next().unwrap_or(location);
+    let (host, path) = location.split_once('/').unwrap_or((location, ""));
+    let host = host.to_lowercase();
+    let host = host.as_str();
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+
+    if scheme == "zoommtg" || scheme == "zoomus" {
+        return query_value(query, "confno").filter(|id| is_zoom_id(id)).map(|id| format!("zoom.us/j/{}", id));
+    }
+
+    if host == "meet.google.com" {
+        let code = segments.first().map(|code| code.to_lowercase()).filter(|code| is_meet_code(code))?;
+        return Some(format!("meet.google.com/{}", code));
+    }
+
+    if host == "zoom.us" || host.ends_with(".zoom.us") {
+        // /j/<id>, /s/<id>, /w/<id>, /wc/<id>/join, /wc/join/<id>
+        let id = segments.iter().skip(1).find(|segment| is_zoom_id(segment))?;
+        let kind = segments.first().map(|kind| kind.to_lowercase());
+        return matches!(kind.as_deref(), Some("j" | "s" | "w" | "wc")).then(|| format!("zoom.us/j/{}", id));
+    }
+
+    if host == "teams.microsoft.com" || host == "teams.live.com" {
+        return match segments.as_slice() {
+            ["l", "meetup-join", thread, ..] => {
+                Some(format!("{}/l/meetup-join/{}", host, percent_decode(thread)))
+            }
+            ["meet", id, ..] => Some(format!("{}/meet/{}", host, id)),
+            _ => None,
+        };
+    }
+
+    if host.ends_with(".webex.com") {
+        return match segments.as_slice() {
+            ["meet" | "join", name, ..] => Some(format!("{}/meet/{}", host, name)),
+            [page] if page.to_lowercase().ends_with(".php") => {
+                query_value(query, "mtid").map(|mtid| format!("{}/j.php?MTID={}", host, mtid))
+            }
+            _ => None,
+        };
+    }
+
+    if host == "app.slack.com" {
+        return match segments.as_slice() {
+            ["huddle", team, channel, ..] => Some(format!("app.slack.com/huddle/{}/{}", team, channel)),
+            _ => None,
+        };
+    }
+
+    None
+}
+
+/// Meeting identifier from the call's window title, for apps that show it there
+pub fn from_title(title: &str, app: &str) -> Option<String> {
+    let title = title.to_lowercase();
+    match app {
+        "Google Meet" => title
+            .split(|c: char| !c.is_ascii_lowercase() && c != '-')
+            .find(|word| is_meet_code(word))
+            .map(|code| format!("meet.google.com/{}", code)),
+        // "Zoom Meeting ID: 812 3456 7890"
+        "Zoom" => {
+            let (_, rest) = title.split_once("meeting id")?;
+            let id: String = rest
+                .trim_start_matches([':', ' '])
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == ' ' || *c == '-')
+                .filter(char::is_ascii_digit)
+                .collect();
+            is_zoom_id(&id).then(|| format!("zoom.us/j/{}", id))
+        }
+        _ => None,
+    }
+}
+
+/// The first meeting URL among a process's command-line arguments
+pub fn from_command_line(args: &[String]) -> Option<String> {
+    args.iter().find_map(|arg| {
+        let value = arg.split_once('=').filter(|(flag, _)| flag.starts_with("--")).map_or(arg.as_str(), |(_, value)| value);
+        from_url(value)
+    })
+}
+
+/// Installed web apps by Chrome/Edge app id, for windows started with `--app-id=` alone
+const APP_IDS: &[(&str, &str)] = &[
+    // Google Meet, preinstalled with Chrome
+    ("kjgfgldnnfoeklkmfkjfagphfepbbdan", "Google Meet"),
+];
+
+/// Call app an app-mode browser window was started for: its `--app=` or `--app-url=` URL,
+/// else a known `--app-id=`
+pub fn app_from_command_line(args: &[String]) -> Option<&'static str> {
+    let value = |flag: &str| args.iter().find_map(|arg| arg.strip_prefix(flag));
+    value("--app=").or_else(|| value("--app-url=")).and_then(browser_bridge::url_call_app).or_else(|| {
+        let id = value("--app-id=")?;
+        APP_IDS.iter().find(|(known, _)| *known == id).map(|(_, app)| *app)
+    })
+}
+
+/// Meeting identifier of a call: the extension's tab, then the command line, then the title
+pub fn identify(tab_url: Option<&str>, command_line: Option<&str>, title: &str, app: &str) -> Option<String> {
+    tab_url
+        .and_then(from_url)
+        .or_else(|| command_line.map(str::to_string))
+        .or_else(|| from_title(title, app))
+}
+
+/// A calendar event with a conferencing link, under way (see calendar.rs)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledMeeting {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organizer: Option<String>,
+    /// The event's conferencing link, reduced like a call's `meeting_identifier`
+    pub meeting_identifier: String,
+}
+
+/// The scheduled meeting a call belongs to: the one with the call's meeting identifier,
+/// or the first one under way when the call's is unknown
+pub fn scheduled_meeting<'a>(
+    meeting_identifier: Option<&str>,
+    meetings: &'a [ScheduledMeeting],
+) -> Option<&'a ScheduledMeeting> {
+    match meeting_identifier {
+        Some(identifier) => meetings.iter().find(|meeting| meeting.meeting_identifier == identifier),
+        None => meetings.first(),
+    }
+}
+
+/// "abc-defg-hij"
+fn is_meet_code(code: &str) -> bool {
+    let parts: Vec<&str> = code.split('-').collect();
+    parts.len() == 3
+        && [3, 4, 3].iter().zip(&parts).all(|(len, part)| part.len() == *len && part.chars().all(|c| c.is_ascii_lowercase()))
+}
+
+/// Zoom meeting ids are 9 to 11 digits; personal link names are not ids
+fn is_zoom_id(id: &str) -> bool {
+    (9..=11).contains(&id.len()) && id.chars().all(|c| c.is_ascii_digit())
+}
+
+fn query_value<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .find_map(|pair| pair.split_once('=').filter(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, value)| value))
+}
+
+// Teams thread ids arrive escaped: 19%3ameeting_X%40thread.v2
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Command lines of the audio sources' processes, read once per process
+#[derive(Default)]
+pub struct CommandLines {
+    cache: HashMap<u32, Option<Vec<String>>>,
+}
+
+impl CommandLines {
+    /// Read the command lines of `pids` not seen before
+    pub fn refresh(&mut self, pids: impl IntoIterator<Item = u32>) {
+        let pids: Vec<u32> = pids.into_iter().collect();
+        // Forget exited processes so a reused pid is read again
+        self.cache.retain(|pid, _| pids.contains(pid));
+        for pid in pids {
+            self.cache.entry(pid).or_insert_with(|| command_line(pid));
+        }
+    }
+
+    /// The meeting identifier on each command line, where there is one
+    pub fn meetings(&self) -> BTreeMap<u32, String> {
+        self.parsed(from_command_line)
+    }
+
+    /// The call app each app-mode browser window was started for
+    pub fn apps(&self) -> BTreeMap<u32, String> {
+        self.parsed(|args| app_from_command_line(args).map(str::to_string))
+    }
+
+    fn parsed(&self, parse: impl Fn(&[String]) -> Option<String>) -> BTreeMap<u32, String> {
+        self.cache
+            .iter()
+            .filter_map(|(pid, args)| Some((*pid, parse(args.as_deref()?)?)))
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    Some(
+        cmdline
+            .split(|&byte| byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect(),
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    use windows::Wdk::System::Threading::{NtQueryInformationProcess, ProcessCommandLineInformation};
+    use windows::Win32::Foundation::{CloseHandle, UNICODE_STRING};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        // A UNICODE_STRING followed by its buffer; asked for once to learn the size
+        let mut size = 0u32;
+        let _ = NtQueryInformationProcess(handle, ProcessCommandLineInformation, std::ptr::null_mut(), 0, &mut size);
+        // u64 elements keep the UNICODE_STRING at the start aligned
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8).max(2)];
+        let status = NtQueryInformationProcess(
+            handle,
+            ProcessCommandLineInformation,
+            buffer.as_mut_ptr() as *mut _,
+            (buffer.len() * 8) as u32,
+            &mut size,
+        );
+        let _ = CloseHandle(handle);
+        if status.is_err() {
+            return None;
+        }
+        let unicode = &*(buffer.as_ptr() as *const UNICODE_STRING);
+        let text = std::slice::from_raw_parts(unicode.Buffer.0, unicode.Length as usize / 2);
+        Some(split_windows_command_line(&String::from_utf16_lossy(text)))
+    }
+}
+
+// Quotes group arguments; backslash escapes do not matter for URLs
+#[cfg(target_os = "windows")]
+fn split_windows_command_line(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in line.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ' ' | '\t' if !in_quotes => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+// `ps` prints the arguments joined by spaces; URLs have none
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    let output = crate::subprocess::output(std::process::Command::new("ps").args(["-ww", "-o", "command=", "-p", &pid.to_string()])).ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).split_whitespace().map(str::to_string).collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos", target_os = "freebsd")))]
+fn command_line(_pid: u32) -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meeting_urls_are_reduced_to_their_identifier() {
+        assert_eq!(from_url("https://meet.google.com/abc-defg-hij?authuser=0").as_deref(), Some("meet.google.com/abc-defg-hij"));
+        assert_eq!(from_url("https://meet.google.com/landing"), None);
+        assert_eq!(from_url("https://us02web.zoom.us/j/81234567890?pwd=secret").as_deref(), Some("zoom.us/j/81234567890"));
+        assert_eq!(
+            from_url("zoommtg://zoom.us/join?action=join&confno=81234567890&pwd=secret").as_deref(),
+            Some("zoom.us/j/81234567890")
+        );
+        assert_eq!(
+            from_url("https://teams.microsoft.com/l/meetup-join/19%3ameeting_NzQ%40thread.v2/0?context=%7b%7d").as_deref(),
+            Some("teams.microsoft.com/l/meetup-join/19:meeting_NzQ@thread.v2")
+        );
+        assert_eq!(from_url("https://www.youtube.com/watch?v=abc-defg-hij"), None);
+
+        let args = ["chrome".to_string(), "--app=https://meet.google.com/abc-defg-hij".to_string()];
+        assert_eq!(from_command_line(&args).as_deref(), Some("meet.google.com/abc-defg-hij"));
+        assert_eq!(app_from_command_line(&args), Some("Google Meet"));
+        let split = |line: &str| line.split(' ').map(str::to_string).collect::<Vec<_>>();
+        let pwa = split("chrome_proxy.exe --profile-directory=Default --app-id=kjgfgldnnfoeklkmfkjfagphfepbbdan");
+        assert_eq!(app_from_command_line(&pwa), Some("Google Meet"));
+        let edge = split("msedge --app-id=abcdef --app-url=https://teams.microsoft.com/v2/");
+        assert_eq!(app_from_command_line(&edge), Some("Microsoft Teams"));
+        assert_eq!(app_from_command_line(&split("chrome --app=https://www.youtube.com/")), None);
+
+        assert_eq!(from_title("Meet - abc-defg-hij", "Google Meet").as_deref(), Some("meet.google.com/abc-defg-hij"));
+        assert_eq!(from_title("Zoom Meeting ID: 812 3456 7890", "Zoom").as_deref(), Some("zoom.us/j/81234567890"));
+        assert_eq!(identify(None, None, "Zoom Meeting", "Zoom"), None);
+    }
+}
@@ -0,0 +1,99 @@
+// Desktop messenger profiles (WhatsApp, Telegram, Signal, Slack)
+// Messengers open the microphone for voice notes as often as for calls, so a mic-only
+// session there is a voice note unless the app shows its call UI. Each profile knows
+// the app's process names and the window titles only its call UI uses:
+//   - WhatsApp: the separate "WhatsApp Call" window (voice, video and group calls)
+//   - Telegram: its call window and the group call ("voice chat" / "video chat") panel
+//   - Signal: the call window it opens next to the main "Signal" window
+//   - Slack: the huddle window
+// Profiles match on the process name, or on the app a web client was detected as
+// (`detected_app`: WhatsApp Web, Slack in a tab). Titles alone never name Signal or
+// Telegram, so a browser tab that merely mentions "Signal" is not taken for the app.
+// A call title lifts the voice-note filter and confirms the call like a meeting title does.
+
+use crate::titles;
+
+/// One messenger's call indicators
+#[derive(Debug)]
+pub struct MessengerProfile {
+    /// Reported as `app`
+    pub app: &'static str,
+    /// Process name prefixes, lowercase ("telegram" covers "Telegram.exe" and "telegram-desktop")
+    process_prefixes: &'static [&'static str],
+    /// Normalized title fragments shown only during a call
+    call_titles: &'static [&'static str],
+    /// Longest voice note the app records; mic-only sessions up to this long are notes
+    pub max_voice_note_secs: u64,
+}
+
+const PROFILES: &[MessengerProfile] = &[
+    MessengerProfile {
+        app: "WhatsApp",
+        process_prefixes: &["whatsapp"],
+        call_titles: &["whatsapp call", "whatsapp voice call", "whatsapp video call", "whatsapp group call"],
+        max_voice_note_secs: 120,
+    },
+    MessengerProfile {
+        app: "Telegram",
+        process_prefixes: &["telegram"],
+        call_titles: &["telegram call", "voice chat", "video chat", "group call"],
+        max_voice_note_secs: 120,
+    },
+    MessengerProfile {
+        app: "Signal",
+        process_prefixes: &["signal"],
+        call_titles: &["signal call", "signal - call", "signal – call", "group call"],
+        max_voice_note_secs: 120,
+    },
+    MessengerProfile {
+        app: "Slack",
+        process_prefixes: &["slack"],
+        call_titles: &["huddle"],
+        // Slack clips run up to 5 minutes
+        max_voice_note_secs: 300,
+    },
+];
+
+/// The messenger running as `process_name`, or reported as `detected_app`
+pub fn profile_for(process_name: &str, detected_app: Option<&str>) -> Option<&'static MessengerProfile> {
+    let name = process_name.to_lowercase();
+    PROFILES.iter().find(|profile| {
+        profile.process_prefixes.iter().any(|prefix| name.starts_with(prefix))
+            || detected_app.is_some_and(|app| app.eq_ignore_ascii_case(profile.app))
+    })
+}
+
+/// Whether `window_title` is the call UI of the messenger running as `process_name`
+pub fn title_shows_call(process_name: &str, window_title: &str) -> bool {
+    profile_for(process_name, None).is_some_and(|profile| profile.shows_call(window_title))
+}
+
+impl MessengerProfile {
+    pub fn shows_call(&self, window_title: &str) -> bool {
+        let title = titles::normalize(window_title);
+        self.call_titles.iter().any(|fragment| title.contains(fragment))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_by_process_and_call_window() {
+        let whatsapp = profile_for("WhatsApp.exe", None).unwrap();
+        assert_eq!(whatsapp.app, "WhatsApp");
+        assert!(whatsapp.shows_call("WhatsApp Call"));
+        assert!(!whatsapp.shows_call("WhatsApp"));
+
+        assert!(title_shows_call("telegram-desktop", "Voice Chat"));
+        assert!(!title_shows_call("telegram-desktop", "Telegram (3)"));
+        assert!(title_shows_call("Signal.exe", "Signal Call"));
+        assert_eq!(profile_for("Slack.exe", None).unwrap().max_voice_note_secs, 300);
+
+        // A tab about Signal is not the Signal app
+        assert!(profile_for("chrome.exe", None).is_none());
+        assert!(!title_shows_call("chrome.exe", "Signal Call - Google Chrome"));
+        assert_eq!(profile_for("chrome.exe", Some("Telegram")).map(|profile| profile.app), Some("Telegram"));
+    }
+}
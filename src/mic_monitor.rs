@@ -1,5 +1,6 @@
+use crate::audio::DeviceUsage;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
+use crate::error::ValidatorError;
 use std::time::Duration;
 
 /// Complete microphone status report
@@ -10,6 +11,9 @@ pub struct MicStatusReport {
     pub permissions: PermissionsInfo,
     pub conflicts: ConflictsInfo,
     pub driver_status: DriverInfo,
+    /// Every active capture device with the apps recording from it
+    #[serde(default)]
+    pub devices: Vec<DeviceUsage>,
     pub errors: Vec<String>,
 }
 
@@ -36,6 +40,9 @@ pub struct PermissionsInfo {
 pub struct ConflictsInfo {
     pub exclusive_lock: bool,
     pub apps_using_mic: Vec<String>,
+    /// Apps using the camera (macOS only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub apps_using_camera: Vec<String>,
 }
 
 /// Audio driver information
@@ -53,19 +60,20 @@ pub struct MicMonitor {
 
 impl MicMonitor {
     /// Create a new microphone monitor instance
-    pub fn new() -> std::result::Result<Self, Box<dyn Error>> {
+    pub fn new() -> std::result::Result<Self, ValidatorError> {
         Ok(MicMonitor {
             errors: Vec::new(),
         })
     }
 
     /// Build complete JSON status report
-    pub fn build_status_report(&mut self) -> std::result::Result<MicStatusReport, Box<dyn Error>> {
-        #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    pub fn build_status_report(&mut self) -> std::result::Result<MicStatusReport, ValidatorError> {
+        #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd"))]
         {
             // Get mic info from platform audio backend
             let mic_info = self.get_mic_info();
             let conflicts = self.get_conflicts_info();
+            let devices = self.get_devices();
 
             let permissions = PermissionsInfo {
                 global: true,
@@ -81,7 +89,7 @@ impl MicMonitor {
 
             #[cfg(target_os = "linux")]
             let driver_info = DriverInfo {
-                name: "PulseAudio".to_string(),
+                name: if crate::audio::alsa::is_fallback() { "ALSA" } else { "PulseAudio" }.to_string(),
                 version: "Built-in".to_string(),
                 status: "OK".to_string(),
             };
@@ -93,31 +101,39 @@ impl MicMonitor {
                 status: "OK".to_string(),
             };
 
+            #[cfg(target_os = "freebsd")]
+            let driver_info = DriverInfo {
+                name: "OSS".to_string(),
+                version: "Built-in".to_string(),
+                status: "OK".to_string(),
+            };
+
             Ok(MicStatusReport {
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 mic: mic_info,
                 permissions,
                 conflicts,
                 driver_status: driver_info,
+                devices,
                 errors: self.errors.clone(),
             })
         }
 
-        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd")))]
         {
-            Err("Microphone monitoring is only supported on Windows, Linux, and macOS".into())
+            Err(ValidatorError::BackendUnavailable("Microphone monitoring outside Windows, Linux, macOS and FreeBSD".to_string()))
         }
     }
 
 
-    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd"))]
     fn get_mic_info(&mut self) -> MicInfo {
         // Use platform audio backend to get REAL microphone data
-        use crate::audio::platform;
+        use crate::audio::{AudioBackend, SystemAudio};
 
-        let (device_name, volume_level, is_muted) = match platform::get_microphone_volume_and_mute() {
+        let (device_name, volume_level, is_muted) = match SystemAudio.get_microphone_volume_and_mute() {
             Ok(audio_info) => {
-                let name = platform::get_microphone_device_name()
+                let name = SystemAudio.get_microphone_device_name()
                     .unwrap_or_else(|_| "Default Microphone".to_string());
                 (name, audio_info.volume, audio_info.is_muted)
             }
@@ -128,8 +144,8 @@ impl MicMonitor {
         };
 
         // Get REAL apps using microphone via audio backend
-        let apps_using_mic = match platform::get_apps_using_microphone() {
-            Ok(apps) => apps,
+        let apps_using_mic: Vec<String> = match SystemAudio.get_apps_using_microphone() {
+            Ok(sessions) => sessions.into_iter().map(|session| session.name).collect(),
             Err(e) => {
                 self.errors.push(format!("Failed to get mic apps: {}", e));
                 Vec::new()
@@ -166,13 +182,26 @@ impl MicMonitor {
     }
 
 
-    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd"))]
+    fn get_devices(&mut self) -> Vec<DeviceUsage> {
+        use crate::audio::{AudioBackend, SystemAudio};
+
+        match SystemAudio.get_microphone_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                self.errors.push(format!("Failed to enumerate capture devices: {}", e));
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd"))]
     fn get_conflicts_info(&mut self) -> ConflictsInfo {
-        use crate::audio::platform;
+        use crate::audio::{AudioBackend, SystemAudio};
 
         // Get REAL apps using microphone via audio backend
-        let apps_using_mic = match platform::get_apps_using_microphone() {
-            Ok(apps) => apps,
+        let apps_using_mic: Vec<String> = match SystemAudio.get_apps_using_microphone() {
+            Ok(sessions) => sessions.into_iter().map(|session| session.name).collect(),
             Err(e) => {
                 self.errors.push(format!("Failed to enumerate mic sessions: {}", e));
                 Vec::new()
@@ -181,9 +210,12 @@ impl MicMonitor {
 
         let exclusive_lock = apps_using_mic.len() == 1;
 
+        let apps_using_camera = SystemAudio.get_apps_using_camera().unwrap_or_default();
+
         ConflictsInfo {
             exclusive_lock,
             apps_using_mic,
+            apps_using_camera,
         }
     }
 
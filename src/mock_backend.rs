@@ -0,0 +1,257 @@
+// Scripted audio and network backends for tests and benches/poll_cycle.rs
+// A MockScenario is a list of frames, one per tick, each saying which apps use the mic,
+// which play audio and which have WebRTC activity. Its MockBackend and MockNetwork plug
+// into CallValidator in place of the platform backends, so the whole pipeline
+// (sensing -> CallTracker -> events) runs the same on every OS.
+//
+//   [
+//     { "mic_apps": ["zoom.us"], "playing": [{ "name": "zoom.us", "process_id": 7 }], "webrtc_pids": [7] },
+//     {}
+//   ]
+
+use crate::audio::{AudioAppSession, AudioBackend, AudioInfo, MicAppSession};
+use crate::error::ValidatorError;
+use crate::network_monitor::WebRTCSignal;
+use crate::validator::NetworkSource;
+use serde::Deserialize;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+/// One tick of a scenario; anything missing is idle
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MockFrame {
+    pub mic_apps: Vec<String>,
+    /// System microphone mute
+    pub mic_muted: bool,
+    pub playing: Vec<MockApp>,
+    pub webrtc_pids: Vec<u32>,
+}
+
+/// An app playing audio
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockApp {
+    pub name: String,
+    pub process_id: u32,
+    #[serde(default)]
+    pub window_title: String,
+    #[serde(default = "default_peak_level")]
+    pub peak_level: f32,
+    #[serde(default)]
+    pub muted: bool,
+}
+
+fn default_peak_level() -> f32 {
+    0.2
+}
+
+/// Frames plus the shared tick both backends read from
+pub struct MockScenario {
+    frames: Rc<Vec<MockFrame>>,
+    tick: Rc<Cell<usize>>,
+}
+
+impl MockScenario {
+    pub fn new(frames: Vec<MockFrame>) -> Self {
+        MockScenario {
+            frames: Rc::new(frames),
+            tick: Rc::new(Cell::new(0)),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json)
+            .map(Self::new)
+            .map_err(|e| format!("invalid mock scenario: {}", e))
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))] // only tests walk a script to its end
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Backends reading the current frame
+    pub fn backends(&self) -> (MockBackend, MockNetwork) {
+        let frames = MockFrames {
+            frames: self.frames.clone(),
+            tick: self.tick.clone(),
+        };
+        (MockBackend { frames: frames.clone() }, MockNetwork { frames })
+    }
+
+    /// Move to the next frame; the last frame repeats once the script runs out
+    #[cfg_attr(not(test), allow(dead_code))] // the bench scenario is a single frame
+    pub fn advance(&self) {
+        self.tick.set(self.tick.get() + 1);
+    }
+}
+
+#[derive(Clone)]
+struct MockFrames {
+    frames: Rc<Vec<MockFrame>>,
+    tick: Rc<Cell<usize>>,
+}
+
+impl MockFrames {
+    fn current(&self) -> MockFrame {
+        let index = self.tick.get().min(self.frames.len().saturating_sub(1));
+        self.frames.get(index).cloned().unwrap_or_default()
+    }
+}
+
+pub struct MockBackend {
+    frames: MockFrames,
+}
+
+impl AudioBackend for MockBackend {
+    fn get_microphone_volume_and_mute(&self) -> Result<AudioInfo, ValidatorError> {
+        Ok(AudioInfo { volume: 100.0, is_muted: self.frames.current().mic_muted })
+    }
+
+    fn get_microphone_device_name(&self) -> Result<String, ValidatorError> {
+        Ok("Mock Microphone".to_string())
+    }
+
+    fn get_apps_using_microphone(&self) -> Result<Vec<MicAppSession>, ValidatorError> {
+        Ok(self.frames.current().mic_apps.into_iter().map(MicAppSession::named).collect())
+    }
+
+    fn get_audio_output_volume_and_mute(&self) -> Result<AudioInfo, ValidatorError> {
+        Ok(AudioInfo { volume: 100.0, is_muted: false })
+    }
+
+    fn get_audio_output_device_name(&self) -> Result<String, ValidatorError> {
+        Ok("Mock Speakers".to_string())
+    }
+
+    fn get_audio_output_peak_level(&self) -> Result<f32, ValidatorError> {
+        let frame = self.frames.current();
+        Ok(frame.playing.iter().map(|app| app.peak_level).fold(0.0, f32::max))
+    }
+
+    fn get_apps_playing_audio(&self) -> Result<Vec<AudioAppSession>, ValidatorError> {
+        Ok(self
+            .frames
+            .current()
+            .playing
+            .into_iter()
+            .map(|app| AudioAppSession {
+                name: app.name,
+                volume: 100.0,
+                is_active: app.peak_level > 0.0,
+                peak_level: app.peak_level,
+                is_muted: app.muted,
+                process_id: app.process_id,
+                window_title: app.window_title,
+                output_device: None,
+            })
+            .collect())
+    }
+}
+
+pub struct MockNetwork {
+    frames: MockFrames,
+}
+
+impl NetworkSource for MockNetwork {
+    fn webrtc_signals(&mut self) -> Vec<WebRTCSignal> {
+        let now = SystemTime::now();
+        self.frames
+            .current()
+            .webrtc_pids
+            .into_iter()
+            .map(|pid| WebRTCSignal {
+                process_id: pid,
+                process_name: format!("Process_{}", pid),
+                remote_ips: Vec::new(),
+                providers: Vec::new(),
+                has_stun_traffic: true,
+                has_media_traffic: true,
+                connection_count: 1,
+                has_sip_signaling: false,
+                media_peers: 0,
+                media_remotes: Vec::new(),
+                last_seen: now,
+                started_at: now,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_tracker::CallTracker;
+    use crate::correlation_engine::CorrelationEngine;
+    use crate::output::EventType;
+    use crate::process_tree::ProcessTree;
+    use crate::scheduler::Due;
+    use crate::validator::CallValidator;
+    use std::time::Duration;
+
+    #[test]
+    fn test_scripted_call_runs_through_the_pipeline() {
+        // Pids above pid_max so the process tree never resolves them to a real process
+        let in_call = r#"{
+            "mic_apps": ["zoom.us"],
+            "playing": [{ "name": "zoom.us", "process_id": 4000000007, "window_title": "Zoom Meeting" }],
+            "webrtc_pids": [4000000007]
+        }"#;
+        let youtube = r#"{ "playing": [{ "name": "chrome", "process_id": 4000000009, "window_title": "Lofi - YouTube" }] }"#;
+        let json = format!("[{0}, {0}, {0}, {0}, {1}, {1}, {1}, {1}, {1}, {1}, {1}]", in_call, youtube);
+        let scenario = MockScenario::from_json(&json).unwrap();
+
+        let (audio, network) = scenario.backends();
+        let mut validator = CallValidator::new(audio, network);
+        let mut tracker = CallTracker::new(CorrelationEngine::new());
+        let start = SystemTime::UNIX_EPOCH;
+
+        let mut call_events = Vec::new();
+        for tick in 0..scenario.len() {
+            let mut process_tree = ProcessTree::snapshot();
+            let sample = validator.sense(&mut process_tree, Due::all()).to_sample(&mut process_tree);
+            let now = start + Duration::from_millis(500 * tick as u64);
+            for event in tracker.update(&sample, now) {
+                if matches!(event.event_type(), EventType::CallStarted | EventType::CallEnded) {
+                    call_events.push((tick, event.event_type()));
+                }
+            }
+            scenario.advance();
+        }
+
+        assert_eq!(call_events, vec![(2, EventType::CallStarted), (8, EventType::CallEnded)]);
+    }
+
+    /// Mean time the platform-independent part of a cycle (sensing logic, tracking) may take
+    /// in a debug build. The platform calls come on top; `bench` measures those.
+    const PIPELINE_BUDGET: Duration = Duration::from_micros(500);
+
+    #[test]
+    fn test_pipeline_cycle_stays_within_cpu_budget() {
+        let frame = r#"{
+            "mic_apps": ["zoom.us", "chrome"],
+            "playing": [
+                { "name": "zoom.us", "process_id": 4000000007, "window_title": "Zoom Meeting" },
+                { "name": "chrome", "process_id": 4000000009, "window_title": "Lofi - YouTube" },
+                { "name": "spotify", "process_id": 4000000011, "window_title": "Spotify" }
+            ],
+            "webrtc_pids": [4000000007, 4000000009]
+        }"#;
+        let scenario = MockScenario::from_json(&format!("[{}]", frame)).unwrap();
+        let (audio, network) = scenario.backends();
+        let mut validator = CallValidator::new(audio, network);
+        let mut tracker = CallTracker::new(CorrelationEngine::new());
+        let mut process_tree = ProcessTree::snapshot();
+
+        let cycles = 200;
+        let start = std::time::Instant::now();
+        for tick in 0..cycles {
+            let sample = validator.sense(&mut process_tree, Due::all()).to_sample(&mut process_tree);
+            tracker.update(&sample, SystemTime::UNIX_EPOCH + Duration::from_millis(250 * tick));
+        }
+        let mean = start.elapsed() / cycles as u32;
+
+        assert!(mean < PIPELINE_BUDGET, "mean cycle {:?} over the {:?} budget", mean, PIPELINE_BUDGET);
+    }
+}
@@ -0,0 +1,140 @@
+// MQTT publisher (`mqtt` feature) for home-automation integrations
+// Publishes retained `call/active`, `call/app` and `call/confidence` topics whenever they
+// change. The last will sets `call/active` to "false" so an "on air" light turns off
+// if the monitor dies without disconnecting.
+
+use crate::MonitorState;
+use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_PORT: u16 = 1883;
+const KEEP_ALIVE: Duration = Duration::from_secs(10);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Values last sent to the broker
+#[derive(Debug, Clone, PartialEq)]
+struct Published {
+    active: bool,
+    app: String,
+    confidence: String,
+}
+
+pub struct MqttPublisher {
+    client: Client,
+    prefix: String,
+    last: Option<Published>,
+    /// Set by the connection thread on (re)connect so retained topics are refreshed
+    reconnected: Arc<AtomicBool>,
+}
+
+impl MqttPublisher {
+    /// Connect to `broker` (`host` or `host:port`, optional `mqtt://` scheme)
+    /// Topics are `<prefix>call/...`; pass an empty prefix for bare `call/...`
+    pub fn connect(broker: &str, prefix: &str, credentials: Option<(String, String)>) -> Result<Self, String> {
+        let (host, port) = parse_broker(broker)?;
+        let prefix = normalize_prefix(prefix);
+
+        let client_id = format!("call-validator-{}", std::process::id());
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(KEEP_ALIVE);
+        options.set_last_will(LastWill::new(
+            format!("{}call/active", prefix),
+            "false",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some((username, password)) = credentials {
+            options.set_credentials(username, password);
+        }
+
+        let (client, mut connection) = Client::new(options, 16);
+        let reconnected = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&reconnected);
+
+        // The connection must be polled for anything to be sent; it reconnects on its own
+        thread::spawn(move || {
+            for notification in connection.iter() {
+                match notification {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => flag.store(true, Ordering::SeqCst),
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::warn!("Connection error: {} (retrying in {}s)", e, RECONNECT_DELAY.as_secs());
+                        thread::sleep(RECONNECT_DELAY);
+                    }
+                }
+            }
+        });
+
+        Ok(MqttPublisher {
+            client,
+            prefix,
+            last: None,
+            reconnected,
+        })
+    }
+
+    /// Publish the call topics if they changed since the last tick
+    pub fn publish(&mut self, state: &MonitorState) {
+        let current = match &state.active_call {
+            Some(call) => Published {
+                active: true,
+                app: call.app.clone(),
+                confidence: format!("{:.2}", call.confidence),
+            },
+            None => Published {
+                active: false,
+                app: String::new(),
+                confidence: "0.00".to_string(),
+            },
+        };
+
+        let refresh = self.reconnected.swap(false, Ordering::SeqCst);
+        if !refresh && self.last.as_ref() == Some(&current) {
+            return;
+        }
+
+        let sent = self.send("call/active", if current.active { "true" } else { "false" })
+            && self.send("call/app", &current.app)
+            && self.send("call/confidence", &current.confidence);
+
+        // Leave `last` stale on failure so the next tick retries
+        if sent {
+            self.last = Some(current);
+        }
+    }
+
+    fn send(&self, topic: &str, payload: &str) -> bool {
+        let topic = format!("{}{}", self.prefix, topic);
+        match self.client.try_publish(topic.as_str(), QoS::AtLeastOnce, true, payload.as_bytes().to_vec()) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Failed to publish {}: {}", topic, e);
+                false
+            }
+        }
+    }
+}
+
+fn parse_broker(broker: &str) -> Result<(String, u16), String> {
+    let address = broker.strip_prefix("mqtt://").unwrap_or(broker);
+    match address.rsplit_once(':') {
+        Some((host, port)) => port
+            .parse()
+            .map(|port| (host.to_string(), port))
+            .map_err(|_| format!("invalid port in '{}'", broker)),
+        None if !address.is_empty() => Ok((address.to_string(), DEFAULT_PORT)),
+        None => Err("empty broker address".to_string()),
+    }
+}
+
+fn normalize_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", trimmed)
+    }
+}
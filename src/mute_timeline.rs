@@ -0,0 +1,201 @@
+// Mute and volume changes over the life of a call
+// Every tick the validator reads the microphone, the default output and each render
+// session's volume and mute; while a call is active the tracker compares them with the
+// previous tick and keeps the transitions ("mic muted at 00:03:12, unmuted at 00:05:40").
+// The list goes out as `mute_timeline` on `call_ended`.
+
+use crate::audio::AudioInfo;
+use crate::CallInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+/// Smallest volume change recorded, in percentage points (slider jitter stays out)
+const VOLUME_CHANGE_THRESHOLD: f32 = 5.0;
+
+/// Volume (0-100) and mute of an endpoint or session
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub volume: f32,
+    pub is_muted: bool,
+}
+
+impl From<AudioInfo> for Level {
+    fn from(info: AudioInfo) -> Self {
+        Level { volume: info.volume, is_muted: info.is_muted }
+    }
+}
+
+/// Levels read in one tick; None where the backend could not read them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeLevels {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mic: Option<Level>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Level>,
+    /// Render sessions by root process id
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sessions: BTreeMap<u32, Level>,
+}
+
+/// What changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MuteTarget {
+    /// Default microphone (system-wide mute)
+    Mic,
+    /// Default output device
+    Output,
+    /// The call app's own render session (the volume mixer entry)
+    Session,
+}
+
+impl MuteTarget {
+    /// Same spelling as the JSON output
+    #[cfg_attr(not(feature = "grpc"), allow(dead_code))] // only gRPC needs it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MuteTarget::Mic => "mic",
+            MuteTarget::Output => "output",
+            MuteTarget::Session => "session",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MuteChangeKind {
+    Muted,
+    Unmuted,
+    Volume,
+}
+
+impl MuteChangeKind {
+    /// Same spelling as the JSON output
+    #[cfg_attr(not(feature = "grpc"), allow(dead_code))] // only gRPC needs it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MuteChangeKind::Muted => "muted",
+            MuteChangeKind::Unmuted => "unmuted",
+            MuteChangeKind::Volume => "volume",
+        }
+    }
+}
+
+/// One transition during a call
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MuteChange {
+    /// Offset from the call start, "HH:MM:SS"
+    pub at: String,
+    pub offset_secs: u64,
+    pub target: MuteTarget,
+    pub change: MuteChangeKind,
+    /// Volume after the change
+    pub volume: f32,
+}
+
+/// Transitions of the active call so far
+#[derive(Debug, Default)]
+pub struct MuteTimeline {
+    /// Call the changes belong to
+    process_id: Option<u32>,
+    /// Last level per target (mic, output, session), for volume the last recorded one
+    last: [Option<Level>; 3],
+    changes: Vec<MuteChange>,
+}
+
+impl MuteTimeline {
+    /// Compare this tick's `levels` with the previous tick of `call`
+    /// Anything already muted when the call starts gets a `muted` entry at the first tick.
+    pub fn observe(&mut self, call: &CallInfo, levels: &VolumeLevels, now: SystemTime) {
+        if self.process_id != Some(call.process_id) {
+            *self = MuteTimeline { process_id: Some(call.process_id), ..Default::default() };
+        }
+
+        let offset_secs = crate::call_duration_secs(call, now);
+        let current = [
+            (MuteTarget::Mic, levels.mic),
+            (MuteTarget::Output, levels.output),
+            (MuteTarget::Session, levels.sessions.get(&call.process_id).copied()),
+        ];
+
+        for (index, (target, level)) in current.into_iter().enumerate() {
+            // Not read this tick (session idle, backend error): keep the last level
+            let Some(level) = level else { continue };
+            let change = match self.last[index] {
+                None if level.is_muted => Some(MuteChangeKind::Muted),
+                None => None,
+                Some(last) if last.is_muted != level.is_muted => {
+                    Some(if level.is_muted { MuteChangeKind::Muted } else { MuteChangeKind::Unmuted })
+                }
+                Some(last) if (last.volume - level.volume).abs() >= VOLUME_CHANGE_THRESHOLD => {
+                    Some(MuteChangeKind::Volume)
+                }
+                // Small drifts are measured against the last recorded volume
+                Some(_) => continue,
+            };
+
+            if let Some(change) = change {
+                self.changes.push(MuteChange {
+                    at: format_offset(offset_secs),
+                    offset_secs,
+                    target,
+                    change,
+                    volume: level.volume,
+                });
+            }
+            self.last[index] = Some(level);
+        }
+    }
+
+    /// The changes of the call that just ended; the next call starts a new timeline
+    pub fn take(&mut self) -> Vec<MuteChange> {
+        std::mem::take(self).changes
+    }
+}
+
+fn format_offset(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_transitions_during_call() {
+        let start = SystemTime::UNIX_EPOCH;
+        let call = CallInfo { call_started_system_time: start, ..crate::test_call("Zoom", 7) };
+        let levels = |mic_muted: bool, session_volume: f32| VolumeLevels {
+            mic: Some(Level { volume: 80.0, is_muted: mic_muted }),
+            output: Some(Level { volume: 50.0, is_muted: false }),
+            sessions: BTreeMap::from([(7, Level { volume: session_volume, is_muted: false })]),
+        };
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let mut timeline = MuteTimeline::default();
+        timeline.observe(&call, &levels(false, 100.0), at(0));
+        timeline.observe(&call, &levels(false, 98.0), at(60));
+        timeline.observe(&call, &levels(true, 94.0), at(192));
+        timeline.observe(&call, &levels(false, 60.0), at(340));
+
+        let changes: Vec<(&str, MuteTarget, MuteChangeKind)> = timeline
+            .changes
+            .iter()
+            .map(|change| (change.at.as_str(), change.target, change.change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("00:03:12", MuteTarget::Mic, MuteChangeKind::Muted),
+                ("00:03:12", MuteTarget::Session, MuteChangeKind::Volume),
+                ("00:05:40", MuteTarget::Mic, MuteChangeKind::Unmuted),
+                ("00:05:40", MuteTarget::Session, MuteChangeKind::Volume),
+            ]
+        );
+        assert_eq!(timeline.take().len(), 4);
+        assert!(timeline.take().is_empty());
+    }
+}
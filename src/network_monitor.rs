@@ -1,5 +1,7 @@
+use crate::ip_ranges::{IpRangeDb, Provider};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
 use std::time::{SystemTime, Duration};
 
 /// Network signal indicating WebRTC activity
@@ -8,16 +10,52 @@ pub struct WebRTCSignal {
     pub process_id: u32,
     pub process_name: String,
     pub remote_ips: Vec<String>,
+    /// Meeting providers whose published ranges contain one of `remote_ips`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<Provider>,
     pub has_stun_traffic: bool,
     pub has_media_traffic: bool,
     pub connection_count: usize,
+    /// The process also has SIP signalling open: a softphone call, not WebRTC
+    #[serde(default)]
+    pub has_sip_signaling: bool,
+    /// Distinct remote ends of connected media sockets outside provider ranges, this scan
+    /// A peer-to-peer call connects one per participant; relayed meetings show none.
+    #[serde(default)]
+    pub media_peers: usize,
+    /// Remote ends of connected media sockets this scan, relays included (see call_quality.rs)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media_remotes: Vec<IpAddr>,
     pub last_seen: SystemTime,
     pub started_at: SystemTime,
 }
 
+/// TURN over TCP (3478) and TURN over TLS (5349), used when UDP is blocked
+const TURN_TCP_PORTS: &[u16] = &[3478, 5349];
+
+/// SIP over UDP/TCP (5060) and over TLS (5061)
+const SIP_PORTS: &[u16] = &[5060, 5061];
+
+/// Port corporate firewalls leave open, where Teams/Zoom/Meet/Webex fall back to TURN over TLS
+const HTTPS_PORT: u16 = 443;
+
+/// How long a 443 connection to a relay must stay up before it counts as media.
+/// Signalling and chat also use these hosts, but only in short-lived requests.
+const RELAY_SUSTAIN: Duration = Duration::from_secs(5);
+
 /// Network monitor for WebRTC detection
 pub struct NetworkMonitor {
     active_connections: HashMap<u32, WebRTCSignal>,
+    /// When each (pid, relay) connection on 443 was first seen, for RELAY_SUSTAIN
+    relay_first_seen: HashMap<(u32, SocketAddr), SystemTime>,
+    /// Processes with a SIP socket / an RTP-style media socket in the current scan
+    sip_pids: HashSet<u32>,
+    rtp_pids: HashSet<u32>,
+    /// Peer media endpoints of each process in the current scan
+    media_peers: HashMap<u32, HashSet<SocketAddr>>,
+    /// Remote addresses of each process's connected media sockets in the current scan
+    media_remotes: HashMap<u32, HashSet<IpAddr>>,
+    ip_ranges: IpRangeDb,
     #[allow(dead_code)]
     known_stun_servers: HashSet<String>,
 }
@@ -39,14 +77,46 @@ impl NetworkMonitor {
 
         NetworkMonitor {
             active_connections: HashMap::new(),
+            relay_first_seen: HashMap::new(),
+            sip_pids: HashSet::new(),
+            rtp_pids: HashSet::new(),
+            media_peers: HashMap::new(),
+            media_remotes: HashMap::new(),
+            ip_ranges: IpRangeDb::bundled(),
             known_stun_servers,
         }
     }
 
+    /// Use a refreshed provider range dataset instead of the bundled one
+    pub fn with_ip_ranges(mut self, ip_ranges: IpRangeDb) -> Self {
+        self.ip_ranges = ip_ranges;
+        self
+    }
+
+    /// Forget all connection state, keeping the provider ranges
+    pub fn restart(&mut self) {
+        self.active_connections.clear();
+        self.relay_first_seen.clear();
+        self.sip_pids.clear();
+        self.rtp_pids.clear();
+        self.media_peers.clear();
+        self.media_remotes.clear();
+    }
+
+    /// Which meeting provider's media network `ip` belongs to, if any
+    pub fn classify_remote(&self, ip: IpAddr) -> Option<Provider> {
+        self.ip_ranges.classify(ip)
+    }
+
     /// Get WebRTC signals for active connections
     /// This is a simplified implementation that uses platform-specific commands
     /// For production, you'd use pcap, but this works without driver installation
     pub fn get_webrtc_signals(&mut self) -> Vec<WebRTCSignal> {
+        self.sip_pids.clear();
+        self.rtp_pids.clear();
+        self.media_peers.clear();
+        self.media_remotes.clear();
+
         #[cfg(target_os = "windows")]
         {
             self.scan_network_connections();
@@ -62,6 +132,14 @@ impl NetworkMonitor {
             self.scan_network_connections();
         }
 
+        #[cfg(target_os = "freebsd")]
+        {
+            self.scan_network_connections();
+        }
+
+        self.flag_sip_calls();
+        self.count_media_peers();
+
         // Clean up stale connections (no activity for 10 seconds)
         let now = SystemTime::now();
         self.active_connections.retain(|_, signal| {
@@ -73,66 +151,84 @@ impl NetworkMonitor {
 
     #[cfg(target_os = "windows")]
     fn scan_network_connections(&mut self) {
-        use std::process::Command;
-
-        // Use netstat to get active connections with process IDs
-        // netstat -ano gives us: Proto, Local Address, Foreign Address, State, PID
-        let output = match Command::new("netstat")
-            .args(&["-ano", "-p", "UDP"])
-            .output()
-        {
-            Ok(output) => output,
-            Err(_) => return,
-        };
+        // IP Helper tables carry the owning PID directly - no netstat child
+        // process and no locale-dependent text to parse
+        for (pid, port) in windows_udp_endpoints() {
+            if pid != 0 {
+                self.track_udp_socket(pid, port, None);
+            }
+        }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
+        let connections = windows_tcp_connections()
+            .into_iter()
+            .filter(|&(pid, remote_ip, remote_port)| pid != 0 && self.is_tcp_candidate(SocketAddr::new(remote_ip, remote_port)))
+            .map(|(pid, remote_ip, remote_port)| (pid, SocketAddr::new(remote_ip, remote_port)))
+            .collect();
+        self.track_tcp_connections(connections);
+    }
 
-        for line in output_str.lines().skip(4) {
-            self.parse_netstat_line(line);
+    #[cfg(target_os = "linux")]
+    fn scan_network_connections(&mut self) {
+        // Netlink sock_diag first; `ss` only when netlink is unavailable
+        // (e.g. seccomp-restricted containers)
+        if self.scan_sock_diag().is_err() {
+            self.scan_ss();
         }
     }
 
-    #[cfg(target_os = "windows")]
-    fn parse_netstat_line(&mut self, line: &str) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-
-        // UDP format: UDP  0.0.0.0:PORT  *:*  PID
-        if parts.len() >= 4 && parts[0] == "UDP" {
-            if let Some(pid_str) = parts.last() {
-                if let Ok(pid) = pid_str.parse::<u32>() {
-                    if pid == 0 {
-                        return; // Skip system process
-                    }
-
-                    // Check if this is a WebRTC-related port
-                    let local_addr = parts[1];
-
-                    // WebRTC typically uses high UDP ports (>10000)
-                    // STUN uses port 3478, 19302
-                    if self.is_webrtc_port(local_addr) {
-                        self.update_or_create_signal(pid);
-                    }
+    #[cfg(target_os = "linux")]
+    fn scan_sock_diag(&mut self) -> Result<(), String> {
+        use crate::sock_diag::{self, Protocol};
+
+        let udp: Vec<_> = sock_diag::sockets(Protocol::Udp)?
+            .into_iter()
+            .filter(|socket| {
+                Self::is_webrtc_port_number(socket.local.port())
+                    || is_sip_port(socket.local.port())
+                    || is_sip_port(socket.remote.port())
+            })
+            .collect();
+        let tcp: Vec<_> = sock_diag::sockets(Protocol::Tcp)?
+            .into_iter()
+            .filter(|socket| socket.established && self.is_tcp_candidate(socket.remote))
+            .collect();
+
+        let inodes = udp.iter().chain(&tcp).map(|socket| socket.inode).collect();
+        let owners = sock_diag::inode_owners(&inodes);
+
+        for socket in &udp {
+            if let Some(&pid) = owners.get(&socket.inode) {
+                // Unconnected sockets report port 0 as the remote
+                let remote_port = Some(socket.remote.port()).filter(|&port| port != 0);
+                self.track_udp_socket(pid, socket.local.port(), remote_port);
+                if remote_port.is_some() {
+                    self.track_media_peer(pid, socket.local.port(), socket.remote);
                 }
             }
         }
+        let connections = tcp
+            .iter()
+            .filter_map(|socket| owners.get(&socket.inode).map(|&pid| (pid, socket.remote)))
+            .collect();
+        self.track_tcp_connections(connections);
+
+        Ok(())
     }
 
     #[cfg(target_os = "linux")]
-    fn scan_network_connections(&mut self) {
+    fn scan_ss(&mut self) {
         use std::process::Command;
 
         // Use 'ss' command (modern replacement for netstat)
         // Format: ss -uapn (UDP, all, process, numeric)
-        let output = match Command::new("ss")
-            .args(&["-uapn"])
-            .output()
+        let output = match crate::subprocess::output(Command::new("ss")
+            .args(&["-uapn"]))
         {
             Ok(output) => output,
             Err(_) => {
                 // Fallback to netstat if ss is not available
-                match Command::new("netstat")
-                    .args(&["-anup"])
-                    .output()
+                match crate::subprocess::output(Command::new("netstat")
+                    .args(&["-anup"]))
                 {
                     Ok(output) => output,
                     Err(_) => return,
@@ -162,12 +258,11 @@ impl NetworkMonitor {
             return;
         }
 
-        let local_addr = parts[4];
-
-        // Check if this is a WebRTC port
-        if !self.is_webrtc_port(local_addr) {
+        let Some(local_port) = port_of(parts[4]) else {
             return;
-        }
+        };
+        let peer_port = parts.get(5).and_then(|peer| port_of(peer));
+        let peer = parts.get(5).and_then(|peer| peer.parse::<SocketAddr>().ok());
 
         // Extract PID from users:((processname,pid=1234,fd=56))
         if let Some(users_part) = line.split("users:").nth(1) {
@@ -175,7 +270,10 @@ impl NetworkMonitor {
                 if let Some(pid_str) = pid_part.split(',').next() {
                     if let Ok(pid) = pid_str.trim().parse::<u32>() {
                         if pid > 0 {
-                            self.update_or_create_signal(pid);
+                            self.track_udp_socket(pid, local_port, peer_port);
+                            if let Some(peer) = peer {
+                                self.track_media_peer(pid, local_port, peer);
+                            }
                         }
                     }
                 }
@@ -188,9 +286,8 @@ impl NetworkMonitor {
         use std::process::Command;
 
         // Use lsof to get UDP connections with process information
-        let output = match Command::new("lsof")
-            .args(&["-i", "UDP", "-n", "-P"])
-            .output()
+        let output = match crate::subprocess::output(Command::new("lsof")
+            .args(&["-i", "UDP", "-n", "-P"]))
         {
             Ok(output) => output,
             Err(_) => return,
@@ -201,6 +298,19 @@ impl NetworkMonitor {
         for line in output_str.lines().skip(1) {
             self.parse_lsof_line(line);
         }
+
+        let connections = match crate::subprocess::output(Command::new("lsof")
+            .args(&["-iTCP", "-sTCP:ESTABLISHED", "-n", "-P"]))
+        {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .skip(1)
+                .filter_map(parse_lsof_tcp_line)
+                .filter(|&(_, remote)| self.is_tcp_candidate(remote))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        self.track_tcp_connections(connections);
     }
 
     #[cfg(target_os = "macos")]
@@ -219,37 +329,159 @@ impl NetworkMonitor {
                 return;
             }
 
-            // Get the connection info (last column typically contains address:port)
+            // Connection info is the last column: "*:12345" or "local:port->remote:port"
             if let Some(addr_info) = parts.last() {
-                // Check if this is a WebRTC-related port
-                if self.is_webrtc_port(addr_info) {
-                    self.update_or_create_signal(pid);
+                let (local, remote) = match addr_info.split_once("->") {
+                    Some((local, remote)) => (local, Some(remote)),
+                    None => (*addr_info, None),
+                };
+                if let Some(local_port) = port_of(local) {
+                    self.track_udp_socket(pid, local_port, remote.and_then(port_of));
+                    if let Some(peer) = remote.and_then(|remote| remote.parse::<SocketAddr>().ok()) {
+                        self.track_media_peer(pid, local_port, peer);
+                    }
                 }
             }
         }
     }
 
-    fn is_webrtc_port(&self, addr: &str) -> bool {
-        if let Some(port_str) = addr.split(':').last() {
-            if let Ok(port) = port_str.parse::<u16>() {
-                // STUN/TURN standard ports
-                if port == 3478 || port == 19302 || port == 5349 {
-                    return true;
-                }
+    #[cfg(target_os = "freebsd")]
+    fn scan_network_connections(&mut self) {
+        use std::process::Command;
 
-                // WebRTC media ports (typically >10000)
-                if port >= 10000 && port <= 65535 {
-                    return true;
-                }
+        // sockstat columns: USER COMMAND PID FD PROTO LOCAL-ADDRESS FOREIGN-ADDRESS
+        let output = match crate::subprocess::output(Command::new("sockstat")
+            .args(&["-4", "-6", "-P", "udp"]))
+        {
+            Ok(output) => output,
+            Err(_) => return,
+        };
+
+        for (pid, local, remote) in String::from_utf8_lossy(&output.stdout).lines().skip(1).filter_map(parse_sockstat_line) {
+            self.track_udp_socket(pid, local.port(), remote.map(|remote| remote.port()));
+            if let Some(remote) = remote {
+                self.track_media_peer(pid, local.port(), remote);
             }
         }
-        false
+
+        let connections = match crate::subprocess::output(Command::new("sockstat")
+            .args(&["-4", "-6", "-c", "-P", "tcp"]))
+        {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .skip(1)
+                .filter_map(parse_sockstat_line)
+                .filter_map(|(pid, _, remote)| Some((pid, remote?)))
+                .filter(|&(_, remote)| self.is_tcp_candidate(remote))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        self.track_tcp_connections(connections);
+    }
+
+    fn is_webrtc_port_number(port: u16) -> bool {
+        // STUN/TURN standard ports
+        if port == 3478 || port == 19302 || port == 5349 {
+            return true;
+        }
+
+        // WebRTC media ports (typically >10000)
+        port >= 10000
+    }
+
+    /// Classify one UDP socket: SIP signalling, or WebRTC/RTP media
+    fn track_udp_socket(&mut self, pid: u32, local_port: u16, remote_port: Option<u16>) {
+        if is_sip_port(local_port) || remote_port.map_or(false, is_sip_port) {
+            self.sip_pids.insert(pid);
+            return;
+        }
+
+        if Self::is_webrtc_port_number(local_port) {
+            if is_rtp_port(local_port) {
+                self.rtp_pids.insert(pid);
+            }
+            self.update_or_create_signal(pid, None);
+        }
     }
 
-    fn update_or_create_signal(&mut self, pid: u32) {
+    /// A registered softphone keeps its SIP socket open all day; only SIP plus RTP media
+    /// from the same process is a call
+    fn flag_sip_calls(&mut self) {
+        for (pid, signal) in self.active_connections.iter_mut() {
+            signal.has_sip_signaling = self.sip_pids.contains(pid) && self.rtp_pids.contains(pid);
+        }
+    }
+
+    /// Remember the remote end of a connected media socket, and count it when it is a peer,
+    /// not a relay
+    /// Windows' UDP tables carry no remote address, so peers are only seen on Linux and macOS.
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    fn track_media_peer(&mut self, pid: u32, local_port: u16, remote: SocketAddr) {
+        let ip = remote.ip();
+        if !Self::is_webrtc_port_number(local_port) || is_sip_port(remote.port()) || ip.is_unspecified() || ip.is_loopback() {
+            return;
+        }
+        self.media_remotes.entry(pid).or_default().insert(ip);
+        if self.classify_remote(ip).is_none() {
+            self.media_peers.entry(pid).or_default().insert(remote);
+        }
+    }
+
+    fn count_media_peers(&mut self) {
+        for (pid, signal) in self.active_connections.iter_mut() {
+            signal.media_peers = self.media_peers.get(pid).map_or(0, HashSet::len);
+            signal.media_remotes = self.media_remotes.get(pid).map(|ips| ips.iter().copied().collect()).unwrap_or_default();
+        }
+    }
+
+    /// Established TCP connections worth tracking: SIP signalling or relayed media
+    fn is_tcp_candidate(&self, remote: SocketAddr) -> bool {
+        is_sip_port(remote.port()) || self.is_relay_candidate(remote)
+    }
+
+    /// TCP connections that may carry relayed media: TURN ports, or 443 to a provider relay
+    fn is_relay_candidate(&self, remote: SocketAddr) -> bool {
+        TURN_TCP_PORTS.contains(&remote.port()) || (remote.port() == HTTPS_PORT && self.classify_remote(remote.ip()).is_some())
+    }
+
+    /// Count SIP signalling and relayed media from established TCP connections (pid, remote)
+    /// TURN ports count immediately. 443 only counts for call apps and browsers, once the
+    /// connection has been up for RELAY_SUSTAIN.
+    fn track_tcp_connections(&mut self, connections: Vec<(u32, SocketAddr)>) {
+        let now = SystemTime::now();
+        let mut seen = HashSet::new();
+
+        for (pid, remote) in connections {
+            if is_sip_port(remote.port()) {
+                self.sip_pids.insert(pid);
+                continue;
+            }
+
+            if TURN_TCP_PORTS.contains(&remote.port()) {
+                self.update_or_create_signal(pid, Some(remote.ip()));
+                continue;
+            }
+
+            let process_name = get_process_name_from_pid(pid);
+            if crate::detect_call_app(&process_name, "").is_none() && !crate::is_browser_process(&process_name) {
+                continue;
+            }
+
+            seen.insert((pid, remote));
+            let first_seen = *self.relay_first_seen.entry((pid, remote)).or_insert(now);
+            if now.duration_since(first_seen).unwrap_or_default() >= RELAY_SUSTAIN {
+                self.update_or_create_signal(pid, Some(remote.ip()));
+            }
+        }
+
+        // A connection that dropped has to sustain again from scratch
+        self.relay_first_seen.retain(|key, _| seen.contains(key));
+    }
+
+    fn update_or_create_signal(&mut self, pid: u32, remote_ip: Option<IpAddr>) {
         let now = SystemTime::now();
 
-        self.active_connections.entry(pid)
+        let signal = self.active_connections.entry(pid)
             .and_modify(|signal| {
                 signal.last_seen = now;
                 signal.connection_count += 1;
@@ -260,18 +492,30 @@ impl NetworkMonitor {
                     process_id: pid,
                     process_name,
                     remote_ips: Vec::new(),
+                    providers: Vec::new(),
                     has_stun_traffic: true,
                     has_media_traffic: true,
                     connection_count: 1,
+                    has_sip_signaling: false,
+                    media_peers: 0,
+                    media_remotes: Vec::new(),
                     last_seen: now,
                     started_at: now,
                 }
             });
-    }
 
-    /// Check if a specific process has WebRTC activity
-    pub fn has_webrtc_activity(&self, process_id: u32) -> bool {
-        self.active_connections.contains_key(&process_id)
+        if let Some(remote_ip) = remote_ip {
+            let provider = self.ip_ranges.classify(remote_ip);
+            let remote_ip = remote_ip.to_string();
+            if !signal.remote_ips.contains(&remote_ip) {
+                signal.remote_ips.push(remote_ip);
+            }
+            if let Some(provider) = provider {
+                if !signal.providers.contains(&provider) {
+                    signal.providers.push(provider);
+                }
+            }
+        }
     }
 
     /// Get WebRTC signal for specific process
@@ -280,27 +524,150 @@ impl NetworkMonitor {
     }
 }
 
+fn is_sip_port(port: u16) -> bool {
+    SIP_PORTS.contains(&port)
+}
+
+/// RTP media goes on an even port by convention (RTCP takes the odd one above it)
+fn is_rtp_port(port: u16) -> bool {
+    port >= 10000 && port % 2 == 0
+}
+
+/// Port of an "address:port" column (`*:5060`, `10.0.0.2:5060`, `[::1]:5060`)
+#[cfg(not(target_os = "windows"))]
+fn port_of(addr: &str) -> Option<u16> {
+    addr.rsplit(':').next()?.parse().ok()
+}
+
+/// (owning pid, local port) of every IPv4 and IPv6 UDP endpoint
 #[cfg(target_os = "windows")]
-fn get_process_name_from_pid(pid: u32) -> String {
-    use std::process::Command;
+fn windows_udp_endpoints() -> Vec<(u32, u16)> {
+    use windows::Win32::NetworkManagement::IpHelper::*;
+    use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+    let mut endpoints = Vec::new();
+
+    unsafe {
+        if let Some(buffer) = read_ip_table(|table, size| {
+            GetExtendedUdpTable(table, size, false, AF_INET.0 as u32, UDP_TABLE_OWNER_PID, 0)
+        }) {
+            let table = &*(buffer.as_ptr() as *const MIB_UDPTABLE_OWNER_PID);
+            let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+            endpoints.extend(rows.iter().map(|row| (row.dwOwningPid, u16::from_be(row.dwLocalPort as u16))));
+        }
 
-    // Use tasklist to get process name
-    let output = Command::new("tasklist")
-        .args(&["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
-        .output();
+        if let Some(buffer) = read_ip_table(|table, size| {
+            GetExtendedUdpTable(table, size, false, AF_INET6.0 as u32, UDP_TABLE_OWNER_PID, 0)
+        }) {
+            let table = &*(buffer.as_ptr() as *const MIB_UDP6TABLE_OWNER_PID);
+            let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+            endpoints.extend(rows.iter().map(|row| (row.dwOwningPid, u16::from_be(row.dwLocalPort as u16))));
+        }
+    }
 
-    if let Ok(output) = output {
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        if let Some(first_line) = output_str.lines().next() {
-            // CSV format: "processname.exe","PID","Session","Memory"
-            let parts: Vec<&str> = first_line.split(',').collect();
-            if let Some(name) = parts.first() {
-                return name.trim_matches('"').to_string();
-            }
+    endpoints
+}
+
+/// (owning pid, remote address, remote port) of every established IPv4 and IPv6 TCP connection
+#[cfg(target_os = "windows")]
+fn windows_tcp_connections() -> Vec<(u32, std::net::IpAddr, u16)> {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use windows::Win32::NetworkManagement::IpHelper::*;
+    use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+    let established = MIB_TCP_STATE_ESTAB.0 as u32;
+    let mut connections = Vec::new();
+
+    unsafe {
+        if let Some(buffer) = read_ip_table(|table, size| {
+            GetExtendedTcpTable(table, size, false, AF_INET.0 as u32, TCP_TABLE_OWNER_PID_CONNECTIONS, 0)
+        }) {
+            let table = &*(buffer.as_ptr() as *const MIB_TCPTABLE_OWNER_PID);
+            let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+            connections.extend(rows.iter().filter(|row| row.dwState == established).map(|row| {
+                // Addresses and ports are in network byte order
+                let remote_ip = IpAddr::V4(Ipv4Addr::from(row.dwRemoteAddr.to_ne_bytes()));
+                (row.dwOwningPid, remote_ip, u16::from_be(row.dwRemotePort as u16))
+            }));
+        }
+
+        if let Some(buffer) = read_ip_table(|table, size| {
+            GetExtendedTcpTable(table, size, false, AF_INET6.0 as u32, TCP_TABLE_OWNER_PID_CONNECTIONS, 0)
+        }) {
+            let table = &*(buffer.as_ptr() as *const MIB_TCP6TABLE_OWNER_PID);
+            let rows = std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize);
+            connections.extend(rows.iter().filter(|row| row.dwState == established).map(|row| {
+                let remote_ip = IpAddr::V6(Ipv6Addr::from(row.ucRemoteAddr));
+                (row.dwOwningPid, remote_ip, u16::from_be(row.dwRemotePort as u16))
+            }));
         }
     }
 
-    format!("Process_{}", pid)
+    connections
+}
+
+/// Run an IP Helper table query, growing the buffer until the table fits
+/// The buffer is u32-backed so the row structs are correctly aligned.
+#[cfg(target_os = "windows")]
+unsafe fn read_ip_table(query: impl Fn(Option<*mut std::ffi::c_void>, *mut u32) -> u32) -> Option<Vec<u32>> {
+    const NO_ERROR: u32 = 0;
+    const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+
+    let mut size = 0u32;
+    query(None, &mut size);
+
+    // The table can grow between the size query and the read
+    for _ in 0..3 {
+        let mut buffer = vec![0u32; size as usize / 4 + 1];
+        match query(Some(buffer.as_mut_ptr() as *mut _), &mut size) {
+            NO_ERROR => return Some(buffer),
+            ERROR_INSUFFICIENT_BUFFER => continue,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// (pid, remote address) from an lsof TCP line
+/// Example: Teams  1234  user  56u  IPv4  0x123456  0t0  TCP 10.0.0.2:50000->52.112.4.10:443 (ESTABLISHED)
+#[cfg(target_os = "macos")]
+fn parse_lsof_tcp_line(line: &str) -> Option<(u32, SocketAddr)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let pid = parts.get(1)?.parse::<u32>().ok().filter(|&pid| pid != 0)?;
+    let (_, remote) = parts.iter().find(|part| part.contains("->"))?.split_once("->")?;
+
+    // IPv6 addresses are bracketed ("[2603:1063::1]:443"), which SocketAddr parses as is
+    Some((pid, remote.parse().ok()?))
+}
+
+/// (pid, local, remote) of a sockstat line; unconnected sockets have no remote
+#[cfg(target_os = "freebsd")]
+fn parse_sockstat_line(line: &str) -> Option<(u32, SocketAddr, Option<SocketAddr>)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let pid = parts.get(2)?.parse::<u32>().ok().filter(|&pid| pid != 0)?;
+    let local = parse_sockstat_addr(parts.get(5)?)?;
+    Some((pid, local, parts.get(6).and_then(|remote| parse_sockstat_addr(remote))))
+}
+
+/// "192.0.2.1:3478", "*:5353", "fe80::1%em0:123"; "*:*" is no address
+#[cfg(target_os = "freebsd")]
+fn parse_sockstat_addr(addr: &str) -> Option<SocketAddr> {
+    let (ip, port) = addr.rsplit_once(':')?;
+    let port = port.parse::<u16>().ok()?;
+    let ip = match ip.split('%').next()? {
+        "*" => IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+        ip => ip.trim_start_matches('[').trim_end_matches(']').parse().ok()?,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+#[cfg(target_os = "windows")]
+fn get_process_name_from_pid(pid: u32) -> String {
+    // Native lookup (QueryFullProcessImageNameW) instead of spawning tasklist
+    match crate::platform::windows::get_process_name(pid) {
+        Ok(name) => name,
+        Err(_) => format!("Process_{}", pid),
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -314,7 +681,7 @@ fn get_process_name_from_pid(pid: u32) -> String {
     }
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
 fn get_process_name_from_pid(pid: u32) -> String {
     use crate::platform::PlatformUtils;
 
@@ -325,7 +692,44 @@ fn get_process_name_from_pid(pid: u32) -> String {
     }
 }
 
-#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd")))]
 fn get_process_name_from_pid(_pid: u32) -> String {
     String::from("Unknown")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_candidates() {
+        let monitor = NetworkMonitor::new();
+        let candidate = |addr: &str| monitor.is_relay_candidate(addr.parse().unwrap());
+
+        assert!(candidate("52.113.10.4:443"));
+        assert!(candidate("[2603:1063:2::7]:443"));
+        assert!(candidate("170.114.52.2:443"));
+        assert!(candidate("198.51.100.7:3478"));
+        assert!(!candidate("52.113.10.4:80"));
+        assert!(!candidate("93.184.216.34:443"));
+    }
+
+    #[test]
+    fn test_sip_needs_rtp_media() {
+        let mut monitor = NetworkMonitor::new();
+
+        // Registered softphone, no call: SIP socket only
+        monitor.track_udp_socket(4000000001, 5060, None);
+        // Softphone in a call: SIP to the registrar plus an RTP socket
+        monitor.track_udp_socket(4000000002, 40112, Some(5060));
+        monitor.track_udp_socket(4000000002, 16384, None);
+        // Browser WebRTC on an odd port, no SIP
+        monitor.track_udp_socket(4000000003, 51001, None);
+        monitor.flag_sip_calls();
+
+        let sip = |pid: u32| monitor.active_connections.get(&pid).map(|signal| signal.has_sip_signaling);
+        assert_eq!(sip(4000000001), None);
+        assert_eq!(sip(4000000002), Some(true));
+        assert_eq!(sip(4000000003), Some(false));
+    }
+}
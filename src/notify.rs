@@ -0,0 +1,80 @@
+// Native desktop notifications (`notify` feature)
+// Windows toast, libnotify on Linux and Notification Center on macOS via notify-rust.
+// Raised when a call is detected and when the microphone is recording with no call active.
+
+use crate::events::MonitorEvent;
+use crate::{AudioSource, CallInfo};
+use notify_rust::Notification;
+
+const APP_NAME: &str = "Call Validator";
+
+/// Notification text templates
+/// Call placeholders: {app}, {confidence}, {window_title}, {started_at}
+/// Recording placeholders: {apps}
+#[derive(Debug, Clone)]
+pub struct NotifyTemplates {
+    pub call_started: String,
+    pub recording_without_call: String,
+}
+
+impl Default for NotifyTemplates {
+    fn default() -> Self {
+        NotifyTemplates {
+            call_started: "Call started in {app}. Confidence {confidence} percent.".to_string(),
+            recording_without_call: "Microphone in use by {apps} with no call detected.".to_string(),
+        }
+    }
+}
+
+/// Raises notifications on state transitions only, never on every tick
+pub struct Notifier {
+    templates: NotifyTemplates,
+    recording_apps: Vec<String>,
+}
+
+impl Notifier {
+    pub fn new(templates: NotifyTemplates) -> Self {
+        Notifier {
+            templates,
+            recording_apps: Vec::new(),
+        }
+    }
+
+    /// Inspect this tick and raise any notifications it warrants
+    pub fn update(&mut self, events: &[MonitorEvent], active_call: Option<&CallInfo>, mic_sources: &[AudioSource]) {
+        for event in events {
+            if let MonitorEvent::CallStarted(call) = event {
+                show(&render_call(&self.templates.call_started, call));
+            }
+        }
+
+        // Recording with no call: notify once per distinct set of recording apps
+        let mut apps: Vec<String> = if active_call.is_none() {
+            mic_sources.iter().map(|src| src.name.clone()).collect()
+        } else {
+            Vec::new()
+        };
+        apps.sort();
+        apps.dedup();
+
+        if !apps.is_empty() && apps != self.recording_apps {
+            let body = self.templates.recording_without_call.replace("{apps}", &apps.join(", "));
+            show(&body);
+        }
+        self.recording_apps = apps;
+    }
+}
+
+fn render_call(template: &str, call: &CallInfo) -> String {
+    template
+        .replace("{app}", &call.app)
+        .replace("{confidence}", &((call.confidence * 100.0).round() as i32).to_string())
+        .replace("{window_title}", &call.window_title)
+        .replace("{started_at}", &call.started_at)
+}
+
+fn show(body: &str) {
+    if let Err(e) = Notification::new().appname(APP_NAME).summary(APP_NAME).body(body).show() {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
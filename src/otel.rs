@@ -0,0 +1,154 @@
+// OpenTelemetry export (`otel` feature) over OTLP/HTTP
+// Every detected call becomes one `call` span from call start to call end, with the app,
+// kind, confidence and signals as attributes, so it lands in the same traces as the
+// recorder app. Each poll cycle also records metrics:
+//   validator.poll.cycles       counter
+//   validator.poll.duration     histogram, ms spent sensing and correlating
+//   validator.call.active       gauge, 1 while a call is active
+//   validator.call.confidence   gauge, confidence of the active call (0 without one)
+//
+// The standard OTEL_* variables (OTEL_SERVICE_NAME, OTEL_RESOURCE_ATTRIBUTES, headers)
+// apply; the endpoint is the collector base URL, e.g. http://localhost:4318.
+
+use crate::events::MonitorEvent;
+use crate::{CallInfo, MonitorState};
+use opentelemetry::metrics::{Counter, Gauge, Histogram, MeterProvider};
+use opentelemetry::trace::{Span, SpanKind, Tracer, TracerProvider};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use std::time::{Duration, SystemTime};
+
+const SCOPE: &str = "rust-audio-validator";
+const METRIC_INTERVAL: Duration = Duration::from_secs(15);
+
+pub struct OtelExporter {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+    tracer: SdkTracer,
+    /// Span of the active call, ended on `call_ended`
+    call_span: Option<opentelemetry_sdk::trace::Span>,
+    poll_cycles: Counter<u64>,
+    poll_duration: Histogram<f64>,
+    call_active: Gauge<u64>,
+    call_confidence: Gauge<f64>,
+}
+
+impl OtelExporter {
+    /// Export to the OTLP/HTTP collector at `endpoint` (base URL, without `/v1/traces`)
+    pub fn new(endpoint: &str) -> Result<Self, String> {
+        let endpoint = endpoint.trim_end_matches('/');
+        // OTEL_SERVICE_NAME, when set, names the service instead
+        let resource = match std::env::var("OTEL_SERVICE_NAME") {
+            Ok(_) => Resource::builder().build(),
+            Err(_) => Resource::builder().with_service_name(SCOPE).build(),
+        };
+
+        let span_exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_resource(resource.clone())
+            .build();
+
+        let metric_exporter = MetricExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/metrics", endpoint))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(metric_exporter).with_interval(METRIC_INTERVAL).build())
+            .with_resource(resource)
+            .build();
+
+        let meter = meter_provider.meter(SCOPE);
+        Ok(OtelExporter {
+            tracer: tracer_provider.tracer(SCOPE),
+            poll_cycles: meter.u64_counter("validator.poll.cycles").build(),
+            poll_duration: meter.f64_histogram("validator.poll.duration").with_unit("ms").build(),
+            call_active: meter.u64_gauge("validator.call.active").build(),
+            call_confidence: meter.f64_gauge("validator.call.confidence").build(),
+            tracer_provider,
+            meter_provider,
+            call_span: None,
+        })
+    }
+
+    /// Record one poll cycle that took `elapsed` and produced `state` and `events`
+    pub fn record(&mut self, state: &MonitorState, events: &[MonitorEvent], elapsed: Duration) {
+        self.poll_cycles.add(1, &[]);
+        self.poll_duration.record(elapsed.as_secs_f64() * 1000.0, &[]);
+
+        let call = state.active_call.as_ref();
+        let app = call.map(|call| call.app.clone()).unwrap_or_default();
+        self.call_active.record(call.is_some() as u64, &[KeyValue::new("app", app.clone())]);
+        self.call_confidence.record(
+            call.map(|call| call.confidence as f64).unwrap_or(0.0),
+            &[KeyValue::new("app", app)],
+        );
+
+        for event in events {
+            match event {
+                MonitorEvent::CallStarted(call) => {
+                    // A call that replaces another without an end event still closes its span
+                    if let Some(mut span) = self.call_span.take() {
+                        span.end();
+                    }
+                    self.call_span = Some(self.start_span(call));
+                }
+                MonitorEvent::CallEnded(ended) => {
+                    if let Some(mut span) = self.call_span.take() {
+                        span.set_attribute(KeyValue::new("call.duration_secs", ended.duration_secs as i64));
+                        span.end_with_timestamp(SystemTime::now());
+                    }
+                }
+                MonitorEvent::ConfidenceChanged(changed) => {
+                    if let Some(span) = self.call_span.as_mut() {
+                        span.set_attribute(KeyValue::new("call.confidence", changed.current as f64));
+                    }
+                }
+                MonitorEvent::MediaPlaybackDuringCall(_) => {
+                    if let Some(span) = self.call_span.as_mut() {
+                        span.set_attribute(KeyValue::new("call.media_playback", true));
+                    }
+                }
+                MonitorEvent::SourceAdded(_)
+                | MonitorEvent::SourceRemoved(_)
+                | MonitorEvent::ConfigReloaded(_)
+                | MonitorEvent::AudioMarker(_) => {}
+            }
+        }
+    }
+
+    fn start_span(&self, call: &CallInfo) -> opentelemetry_sdk::trace::Span {
+        self.tracer
+            .span_builder("call")
+            .with_kind(SpanKind::Internal)
+            .with_start_time(call.call_started_system_time)
+            .with_attributes(vec![
+                KeyValue::new("call.app", call.app.clone()),
+                KeyValue::new("call.kind", call.kind.as_str()),
+                KeyValue::new("call.process_id", call.process_id as i64),
+                KeyValue::new("call.confidence", call.confidence as f64),
+                KeyValue::new("call.has_mic", call.has_mic),
+                KeyValue::new("call.has_audio", call.has_audio),
+                KeyValue::new("call.has_webrtc", call.has_webrtc),
+            ])
+            .start(&self.tracer)
+    }
+}
+
+impl Drop for OtelExporter {
+    fn drop(&mut self) {
+        if let Some(mut span) = self.call_span.take() {
+            span.end();
+        }
+        let _ = self.tracer_provider.shutdown();
+        let _ = self.meter_provider.shutdown();
+    }
+}
@@ -0,0 +1,180 @@
+// Versioned envelope for everything written to --stream and the JSON log file
+// Consumers should dispatch on `event_type` and check `schema_version` before
+// reading `payload`, so new fields never break existing parsers
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+/// Version of the streamed/logged JSON schema
+/// Bump this whenever a payload field is removed, renamed or changes type.
+/// Adding optional fields is not a breaking change.
+/// 2: calls are dated by `started_at_utc` and `local_timezone`; `started_at` needs --legacy-timestamps
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// `--legacy-timestamps`: keep writing the local "HH:MM:SS" `started_at` of schema 1
+static LEGACY_TIMESTAMPS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_legacy_timestamps(enabled: bool) {
+    LEGACY_TIMESTAMPS.store(enabled, Ordering::Relaxed);
+}
+
+/// serde `skip_serializing_if` for the legacy `started_at`
+#[allow(clippy::ptr_arg)] // serde passes the field as it is
+pub fn omit_legacy_timestamp(_: &String) -> bool {
+    !LEGACY_TIMESTAMPS.load(Ordering::Relaxed)
+}
+
+/// Who wrote a line, so `aggregate` can merge several machines per user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Origin {
+    pub user: String,
+    pub machine: String,
+}
+
+/// `--origin-user` / `--origin-machine`: stamped on every line; unset by default
+static ORIGIN: OnceLock<Origin> = OnceLock::new();
+
+pub fn set_origin(origin: Origin) {
+    let _ = ORIGIN.set(origin);
+}
+
+/// This machine's host name, the default `--origin-machine`
+pub fn host_name() -> String {
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "freebsd"))]
+    {
+        let mut name = [0u8; 256];
+        if unsafe { libc::gethostname(name.as_mut_ptr() as *mut libc::c_char, name.len()) } == 0 {
+            let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            let name = String::from_utf8_lossy(&name[..end]).to_string();
+            if !name.is_empty() {
+                return name;
+            }
+        }
+    }
+
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// RFC3339 in UTC, e.g. "2025-01-01T09:00:00Z"
+pub fn utc_timestamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// UTC offset of the local time zone at `time`, e.g. "+01:00" (DST included)
+pub fn local_timezone(time: SystemTime) -> String {
+    DateTime::<Local>::from(time).format("%:z").to_string()
+}
+
+/// Stable event type names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    /// Full monitor state snapshot
+    State,
+    CallStarted,
+    CallEnded,
+    ConfidenceChanged,
+    SourceAdded,
+    SourceRemoved,
+    /// The `--config` file changed and was applied
+    ConfigReloaded,
+    /// A media source started playing during the active call
+    MediaPlaybackDuringCall,
+    /// One-shot samples from the `sense` subcommand
+    MicSample,
+    AudioAppsSample,
+    NetSample,
+    /// Reply to a control command
+    CommandResult,
+    /// Per-candidate detection trace (`--explain`)
+    Detection,
+    /// One tick of sensed inputs (`--record-signals`)
+    SignalSample,
+    /// Access checks from the `check-permissions` subcommand
+    PermissionsReport,
+    /// Heartbeat to the parent process (`--heartbeat`)
+    Ping,
+    /// Render stream hash or speech/silence boundary (`--audio-markers`)
+    AudioMarker,
+    /// Combined per-user state from the `aggregate` subcommand
+    AggregateState,
+}
+
+/// What `--stream` writes each tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamMode {
+    /// Full state every tick (default, backwards compatible)
+    Snapshots,
+    /// Only deltas between ticks
+    Events,
+}
+
+impl StreamMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "snapshots" | "snapshot" | "state" => Some(StreamMode::Snapshots),
+            "events" | "event" => Some(StreamMode::Events),
+            _ => None,
+        }
+    }
+}
+
+/// Top-level wrapper around every JSON line we emit
+#[derive(Debug, Serialize)]
+pub struct Envelope<T: Serialize> {
+    pub schema_version: u32,
+    pub event_type: EventType,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Origin>,
+    pub payload: T,
+}
+
+impl<T: Serialize> Envelope<T> {
+    pub fn new(event_type: EventType, payload: T) -> Self {
+        Envelope {
+            schema_version: SCHEMA_VERSION,
+            event_type,
+            timestamp: utc_timestamp(SystemTime::now()),
+            origin: ORIGIN.get().cloned(),
+            payload,
+        }
+    }
+
+    /// Serialize as a single NDJSON line (without trailing newline)
+    pub fn to_json_line(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_utc_timestamps_and_offsets() {
+        let at = UNIX_EPOCH + Duration::from_secs(1_735_722_000);
+        assert_eq!(utc_timestamp(at), "2025-01-01T09:00:00Z");
+
+        // The offset is the local wall clock's distance from UTC at that moment
+        let offset = local_timezone(at);
+        let local = DateTime::<Local>::from(at);
+        assert_eq!(offset, local.offset().to_string());
+        assert!(offset.starts_with('+') || offset.starts_with('-'));
+
+        assert!(omit_legacy_timestamp(&String::new()));
+    }
+
+    #[test]
+    fn test_envelope_timestamp_is_utc() {
+        let envelope = Envelope::new(EventType::Ping, ());
+        assert!(envelope.timestamp.ends_with('Z'), "{}", envelope.timestamp);
+        assert!(DateTime::parse_from_rfc3339(&envelope.timestamp).is_ok());
+    }
+}
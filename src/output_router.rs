@@ -0,0 +1,461 @@
+// Output router: fans every tick out to any number of sinks at once
+// Each sink has its own verbosity and event filter, so stdout can carry snapshots for a
+// parent process while a rotating file keeps only call start/end and a collector socket
+// gets every delta. Sinks come from the `outputs` config section:
+//
+//   "outputs": [
+//     { "sink": "stdout", "verbosity": "events" },
+//     { "sink": "file", "path": "logs/calls.log", "max_bytes": 10485760, "keep": 5, "verbosity": "calls" },
+//     { "sink": "socket", "address": "127.0.0.1:9000", "events": ["call_started", "call_ended", "confidence_changed"] },
+//     { "sink": "webhook", "url": "https://example.com/hooks/calls" }
+//   ]
+//
+// plus the legacy flags: --stream (stdout), --log-dir (file) and --webhook-url (webhook).
+// File sinks dedupe snapshots: the state is only written when something changed (any delta
+// event this tick) or `keepalive_secs` (default 60) passed since the last one written.
+
+use crate::correlation_engine::DetectionResult;
+use crate::events::MonitorEvent;
+use crate::output::{Envelope, EventType, StreamMode};
+use crate::sealed_log::LogSealer;
+use crate::stdout_writer::StdoutWriter;
+use crate::webhook::WebhookSink;
+use crate::MonitorState;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_KEEP: usize = 5;
+const SOCKET_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+const SOCKET_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+pub const DEFAULT_KEEPALIVE_SECS: u64 = 60;
+
+/// How much of each tick a sink receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verbosity {
+    /// `call_started` and `call_ended` only
+    Calls,
+    /// Every delta event
+    Events,
+    /// The full state every tick
+    Snapshots,
+    /// Snapshots plus per-candidate detection traces
+    Explain,
+}
+
+/// Which lines a sink keeps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputFilter {
+    /// Defaults to snapshots, or calls for webhooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verbosity: Option<Verbosity>,
+    /// Only these event types; empty keeps everything the verbosity selects
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventType>,
+    /// Skip unchanged snapshots; defaults to on for file sinks only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedupe: Option<bool>,
+    /// With dedupe, rewrite an unchanged snapshot after this long (0: only on changes)
+    pub keepalive_secs: u64,
+}
+
+impl Default for OutputFilter {
+    fn default() -> Self {
+        OutputFilter {
+            verbosity: None,
+            events: Vec::new(),
+            dedupe: None,
+            keepalive_secs: DEFAULT_KEEPALIVE_SECS,
+        }
+    }
+}
+
+/// One configured sink
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "sink", rename_all = "snake_case")]
+pub enum SinkConfig {
+    Stdout {
+        #[serde(flatten)]
+        filter: OutputFilter,
+    },
+    /// NDJSON file rotated to `<path>.1` .. `<path>.<keep>` past `max_bytes` (0 never rotates)
+    File {
+        path: PathBuf,
+        #[serde(default = "default_max_bytes")]
+        max_bytes: u64,
+        #[serde(default = "default_keep")]
+        keep: usize,
+        #[serde(flatten)]
+        filter: OutputFilter,
+    },
+    /// NDJSON over TCP to a collector, reconnecting in the background
+    Socket {
+        address: String,
+        #[serde(flatten)]
+        filter: OutputFilter,
+    },
+    /// POST per line, signed with VALIDATOR_WEBHOOK_SECRET when set
+    Webhook {
+        url: String,
+        #[serde(flatten)]
+        filter: OutputFilter,
+    },
+}
+
+fn default_max_bytes() -> u64 {
+    DEFAULT_MAX_BYTES
+}
+
+fn default_keep() -> usize {
+    DEFAULT_KEEP
+}
+
+impl SinkConfig {
+    /// `--stream` with `--stream-mode`
+    pub fn stream(mode: StreamMode) -> Self {
+        let verbosity = match mode {
+            StreamMode::Snapshots => Verbosity::Snapshots,
+            StreamMode::Events => Verbosity::Events,
+        };
+        SinkConfig::Stdout {
+            filter: OutputFilter { verbosity: Some(verbosity), ..Default::default() },
+        }
+    }
+
+    /// `--log-dir`, never rotated; `--explain` adds detection traces
+    pub fn log_dir(dir: &Path, explain: bool, sealed: bool, keepalive_secs: u64) -> Self {
+        SinkConfig::File {
+            path: dir.join(if sealed { "rust_monitor.sealed.log" } else { "rust_monitor.log" }),
+            max_bytes: 0,
+            keep: 0,
+            filter: OutputFilter {
+                verbosity: Some(if explain { Verbosity::Explain } else { Verbosity::Snapshots }),
+                keepalive_secs,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// `--webhook-url`
+    pub fn webhook(url: String) -> Self {
+        SinkConfig::Webhook { url, filter: OutputFilter::default() }
+    }
+}
+
+/// One serialized output line
+struct Line {
+    event_type: EventType,
+    json: String,
+}
+
+enum Target {
+    Stdout(StdoutWriter),
+    File { path: PathBuf, max_bytes: u64, keep: usize, sealer: Option<LogSealer> },
+    Socket(Sender<String>),
+    Webhook(WebhookSink),
+}
+
+struct Sink {
+    target: Target,
+    verbosity: Verbosity,
+    events: Vec<EventType>,
+    /// Some(keepalive) when snapshots are deduped
+    dedupe: Option<Duration>,
+    last_snapshot: Option<Instant>,
+}
+
+impl Sink {
+    /// Whether this tick's snapshot is written; `changed` when the tick had delta events
+    fn snapshot_due(&mut self, changed: bool, now: Instant) -> bool {
+        let due = match (self.dedupe, self.last_snapshot) {
+            (Some(keepalive), Some(last)) => {
+                changed || (!keepalive.is_zero() && now.duration_since(last) >= keepalive)
+            }
+            _ => true,
+        };
+        if due {
+            self.last_snapshot = Some(now);
+        }
+        due
+    }
+
+    fn accepts(&self, line: &Line) -> bool {
+        let selected = match self.verbosity {
+            Verbosity::Calls => matches!(line.event_type, EventType::CallStarted | EventType::CallEnded),
+            Verbosity::Events => !matches!(line.event_type, EventType::State | EventType::Detection),
+            Verbosity::Snapshots => line.event_type == EventType::State,
+            Verbosity::Explain => matches!(line.event_type, EventType::State | EventType::Detection),
+        };
+        selected && (self.events.is_empty() || self.events.contains(&line.event_type))
+    }
+
+    fn write(&mut self, lines: &[&Line]) {
+        match &mut self.target {
+            Target::Stdout(stdout) => {
+                for line in lines {
+                    match line.event_type {
+                        EventType::State | EventType::Detection => stdout.send_snapshot(line.json.clone()),
+                        _ => stdout.send(line.json.clone()),
+                    }
+                }
+            }
+            Target::File { path, max_bytes, keep, sealer } => write_file(path, *max_bytes, *keep, sealer.as_ref(), lines),
+            Target::Socket(queue) => {
+                for line in lines {
+                    let _ = queue.send(line.json.clone());
+                }
+            }
+            Target::Webhook(webhook) => {
+                for line in lines {
+                    webhook.send(line.event_type, line.json.clone());
+                }
+            }
+        }
+    }
+}
+
+pub struct OutputRouter {
+    sinks: Vec<Sink>,
+}
+
+impl OutputRouter {
+    /// Start every sink; file sinks are sealed with `sealer` (`--encrypt-logs`) when given
+    pub fn new(
+        configs: Vec<SinkConfig>,
+        sealer: Option<&LogSealer>,
+        webhook_secret: Option<String>,
+        stdout: &StdoutWriter,
+    ) -> Self {
+        let sinks = configs
+            .into_iter()
+            .map(|config| {
+                let is_file = matches!(config, SinkConfig::File { .. });
+                let (target, filter, default_verbosity) = match config {
+                    SinkConfig::Stdout { filter } => (Target::Stdout(stdout.clone()), filter, Verbosity::Snapshots),
+                    SinkConfig::File { path, max_bytes, keep, filter } => (
+                        Target::File { path, max_bytes, keep, sealer: sealer.cloned() },
+                        filter,
+                        Verbosity::Snapshots,
+                    ),
+                    SinkConfig::Socket { address, filter } => {
+                        (Target::Socket(start_socket(address)), filter, Verbosity::Snapshots)
+                    }
+                    SinkConfig::Webhook { url, filter } => (
+                        Target::Webhook(WebhookSink::start(url, webhook_secret.clone())),
+                        filter,
+                        Verbosity::Calls,
+                    ),
+                };
+                Sink {
+                    target,
+                    verbosity: filter.verbosity.unwrap_or(default_verbosity),
+                    events: filter.events,
+                    dedupe: filter
+                        .dedupe
+                        .unwrap_or(is_file)
+                        .then(|| Duration::from_secs(filter.keepalive_secs)),
+                    last_snapshot: None,
+                }
+            })
+            .collect();
+
+        OutputRouter { sinks }
+    }
+
+    /// Whether a sink writes NDJSON to stdout, which then belongs to it alone
+    pub fn uses_stdout(&self) -> bool {
+        self.sinks.iter().any(|sink| matches!(sink.target, Target::Stdout(_)))
+    }
+
+    /// Whether any sink wants detection traces, so they are only built when needed
+    pub fn wants_detections(&self) -> bool {
+        self.sinks.iter().any(|sink| sink.verbosity == Verbosity::Explain)
+    }
+
+    /// Write one tick to every sink that wants it
+    pub fn publish(&mut self, state: &MonitorState, events: &[MonitorEvent], detections: &[DetectionResult]) {
+        if self.sinks.is_empty() {
+            return;
+        }
+
+        let lines: Vec<Line> = Envelope::new(EventType::State, state)
+            .to_json_line()
+            .map(|json| Line { event_type: EventType::State, json })
+            .into_iter()
+            .chain(events.iter().filter_map(|event| {
+                event.to_json_line().ok().map(|json| Line { event_type: event.event_type(), json })
+            }))
+            .chain(detections.iter().filter_map(|detection| {
+                Envelope::new(EventType::Detection, detection)
+                    .to_json_line()
+                    .ok()
+                    .map(|json| Line { event_type: EventType::Detection, json })
+            }))
+            .collect();
+
+        let changed = !events.is_empty();
+        let now = Instant::now();
+        for sink in &mut self.sinks {
+            let mut accepted: Vec<&Line> = lines.iter().filter(|line| sink.accepts(line)).collect();
+            if accepted.iter().any(|line| line.event_type == EventType::State) && !sink.snapshot_due(changed, now) {
+                accepted.retain(|line| line.event_type != EventType::State);
+            }
+            if !accepted.is_empty() {
+                sink.write(&accepted);
+            }
+        }
+    }
+}
+
+fn write_file(path: &Path, max_bytes: u64, keep: usize, sealer: Option<&LogSealer>, lines: &[&Line]) {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty() && !dir.exists()) {
+        if let Err(e) = fs::create_dir_all(dir) {
+            log::warn!("Failed to create log directory {:?}: {}", dir, e);
+            return;
+        }
+    }
+
+    if max_bytes > 0 && fs::metadata(path).map_or(false, |meta| meta.len() >= max_bytes) {
+        rotate(path, keep);
+    }
+
+    let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            log::warn!("Failed to open log file {:?}: {}", path, e);
+            return;
+        }
+    };
+
+    for line in lines {
+        let text = match sealer {
+            Some(sealer) => match sealer.seal(&line.json) {
+                Ok(sealed) => sealed,
+                Err(e) => {
+                    log::warn!("Failed to encrypt log entry: {}", e);
+                    continue;
+                }
+            },
+            None => line.json.clone(),
+        };
+        let _ = writeln!(file, "{}", text);
+    }
+}
+
+/// `<path>` -> `<path>.1` -> ... -> `<path>.<keep>`; the oldest is dropped
+fn rotate(path: &Path, keep: usize) {
+    let numbered = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+
+    if keep == 0 {
+        let _ = fs::remove_file(path);
+        return;
+    }
+    let _ = fs::remove_file(numbered(keep));
+    for n in (1..keep).rev() {
+        let _ = fs::rename(numbered(n), numbered(n + 1));
+    }
+    let _ = fs::rename(path, numbered(1));
+}
+
+/// Background writer for a socket sink; lines are dropped while disconnected
+fn start_socket(address: String) -> Sender<String> {
+    let (queue, lines) = mpsc::channel::<String>();
+
+    thread::spawn(move || {
+        let mut stream: Option<TcpStream> = None;
+        let mut last_attempt: Option<Instant> = None;
+        let mut reported_error = false;
+
+        for line in lines {
+            if stream.is_none() && last_attempt.map_or(true, |at| at.elapsed() >= SOCKET_RETRY_INTERVAL) {
+                last_attempt = Some(Instant::now());
+                let connected = address
+                    .parse()
+                    .map_err(|e| format!("invalid address: {}", e))
+                    .and_then(|addr| {
+                        TcpStream::connect_timeout(&addr, SOCKET_CONNECT_TIMEOUT).map_err(|e| e.to_string())
+                    });
+                match connected {
+                    Ok(connected) => {
+                        reported_error = false;
+                        stream = Some(connected);
+                    }
+                    Err(e) => {
+                        if !reported_error {
+                            log::warn!("Output socket {} unavailable: {}", address, e);
+                            reported_error = true;
+                        }
+                    }
+                }
+            }
+
+            if let Some(connected) = stream.as_mut() {
+                if writeln!(connected, "{}", line).is_err() {
+                    stream = None;
+                }
+            }
+        }
+    });
+
+    queue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sinks_filter_independently() {
+        let configs: Vec<SinkConfig> = serde_json::from_str(
+            r#"[
+                { "sink": "file", "path": "calls.log", "verbosity": "calls" },
+                { "sink": "socket", "address": "127.0.0.1:9", "events": ["state", "call_ended"] },
+                { "sink": "webhook", "url": "http://localhost/hook" }
+            ]"#,
+        )
+        .unwrap();
+        let router = OutputRouter::new(configs, None, None, &StdoutWriter::start());
+        let line = |event_type| Line { event_type, json: String::new() };
+
+        let accepted = |sink: &Sink| {
+            [EventType::State, EventType::CallStarted, EventType::CallEnded, EventType::SourceAdded, EventType::Detection]
+                .into_iter()
+                .filter(|&event_type| sink.accepts(&line(event_type)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(accepted(&router.sinks[0]), vec![EventType::CallStarted, EventType::CallEnded]);
+        assert_eq!(accepted(&router.sinks[1]), vec![EventType::State]);
+        assert_eq!(accepted(&router.sinks[2]), vec![EventType::CallStarted, EventType::CallEnded]);
+        assert!(!router.uses_stdout() && !router.wants_detections());
+    }
+
+    #[test]
+    fn test_file_snapshots_only_on_change_or_keepalive() {
+        let mut router = OutputRouter::new(
+            vec![SinkConfig::log_dir(Path::new("logs"), false, false, 60), SinkConfig::stream(StreamMode::Snapshots)],
+            None,
+            None,
+            &StdoutWriter::start(),
+        );
+        let start = Instant::now();
+        let (file, stdout) = router.sinks.split_at_mut(1);
+        let file = &mut file[0];
+        let stdout = &mut stdout[0];
+
+        assert!(file.snapshot_due(false, start));
+        assert!(!file.snapshot_due(false, start + Duration::from_secs(30)));
+        assert!(file.snapshot_due(true, start + Duration::from_secs(31)));
+        assert!(!file.snapshot_due(false, start + Duration::from_secs(90)));
+        assert!(file.snapshot_due(false, start + Duration::from_secs(91)));
+        // stdout keeps a snapshot every tick
+        assert!(stdout.snapshot_due(false, start) && stdout.snapshot_due(false, start));
+    }
+}
@@ -0,0 +1,71 @@
+// Participant count estimation
+// `estimated_participants` on the active call: how many others are in it, so a 1:1 call
+// can be told from a large meeting. From the most to the least direct source:
+//   - the participants button read through UI Automation (uia.rs), which counts you too
+//   - a count in the window or tab title ("Weekly sync (5 participants)", "3 people")
+//   - distinct peer-to-peer media endpoints of the call app (Linux and macOS, where
+//     connected UDP sockets show their remote end); meetings relayed by the provider's
+//     servers show none
+// The last estimate is kept while no source has one.
+
+use crate::call_tracker::Sample;
+use crate::CallInfo;
+
+/// Title words following a head count, lowercase
+const COUNT_WORDS: &[&str] = &["participant", "participants", "people", "attendees", "members"];
+
+/// Everyone counted in a window or tab title, you included
+pub fn count_in_title(title: &str) -> Option<u32> {
+    let words: Vec<String> = title
+        .split(|c: char| c.is_whitespace() || "()[]|,·-".contains(c))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    words
+        .windows(2)
+        .find(|pair| COUNT_WORDS.contains(&pair[1].as_str()))
+        .and_then(|pair| pair[0].parse().ok())
+}
+
+/// `estimated_participants` for `call` this tick
+pub fn estimate(call: &CallInfo, sample: &Sample) -> Option<u32> {
+    let in_call = call
+        .controls
+        .as_ref()
+        .and_then(|controls| controls.participants)
+        .or_else(|| count_in_title(&call.window_title));
+
+    // Head counts include you; peers are the others already
+    in_call
+        .map(|count| count.saturating_sub(1))
+        .or_else(|| sample.media_peers.get(&call.process_id).map(|&peers| peers as u32))
+        .or(call.estimated_participants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uia::CallControls;
+
+    #[test]
+    fn test_participant_sources_in_order() {
+        let call = CallInfo { window_title: "Meet - abc-defg-hij".to_string(), ..crate::test_call("Google Meet", 7) };
+        let peers = Sample { media_peers: [(7, 1)].into(), ..Default::default() };
+
+        assert_eq!(estimate(&call, &Sample::default()), None);
+        assert_eq!(estimate(&call, &peers), Some(1));
+
+        let titled = CallInfo { window_title: "Meet - Planning (6 participants)".to_string(), ..call.clone() };
+        assert_eq!(estimate(&titled, &peers), Some(5));
+
+        let controls = CallControls { participants: Some(12), ..Default::default() };
+        let read = CallInfo { controls: Some(controls), ..titled };
+        assert_eq!(estimate(&read, &peers), Some(11));
+
+        let known = CallInfo { estimated_participants: Some(3), ..call };
+        assert_eq!(estimate(&known, &Sample::default()), Some(3));
+        assert_eq!(count_in_title("Team sync | 3 people | Microsoft Teams"), Some(3));
+        assert_eq!(count_in_title("People Ops review - Zoom"), None);
+    }
+}
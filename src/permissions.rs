@@ -0,0 +1,419 @@
+// `check-permissions` subcommand: what the monitor can actually access on this machine
+//
+//   rust-audio-validator check-permissions [--json] [--prompt]
+//
+// Checks whether the audio backend answers, then the access each platform gates: TCC
+// grants on macOS (microphone, accessibility, screen recording), the PulseAudio socket,
+// sound devices, /proc visibility, netlink socket listing and Flatpak/Snap confinement
+// on Linux, process-query rights and the microphone privacy switch on Windows. Every
+// failed check comes with a remediation hint, and the resulting capability level
+// (capability.rs) is printed last.
+// --prompt first asks for grants the OS can prompt for (Accessibility on macOS).
+// Exit codes, for installers: 0 everything granted, 1 a required check failed (no call
+// can be detected), 3 only optional checks failed (less detail), 2 usage.
+
+use crate::audio::{AudioAppSession, AudioBackend, SystemAudio};
+use crate::capability::{self, CapabilityLevel};
+use crate::error::{ErrorKind, ValidatorError};
+use crate::output::{Envelope, EventType};
+use crate::supervisor::BackendError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const USAGE: &str = "Usage: rust-audio-validator check-permissions [--json] [--prompt]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Granted,
+    Missing,
+    /// Could not be determined (no such device, not applicable here)
+    Unknown,
+}
+
+/// One access check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    /// Without it no call can be detected
+    pub required: bool,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a missing grant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remedy: Option<&'static str>,
+}
+
+impl Check {
+    fn granted(name: &'static str, required: bool, detail: impl Into<String>) -> Self {
+        Check { name, required, status: Status::Granted, detail: detail.into(), remedy: None }
+    }
+
+    fn missing(name: &'static str, required: bool, detail: impl Into<String>, remedy: Option<&'static str>) -> Self {
+        Check { name, required, status: Status::Missing, detail: detail.into(), remedy }
+    }
+
+    fn unknown(name: &'static str, required: bool, detail: impl Into<String>) -> Self {
+        Check { name, required, status: Status::Unknown, detail: detail.into(), remedy: None }
+    }
+}
+
+/// Payload of the `permissions_report` envelope
+#[derive(Debug, Serialize)]
+struct PermissionsReport {
+    checks: Vec<Check>,
+    capability_level: CapabilityLevel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capability_reason: Option<String>,
+}
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let as_json = args.iter().any(|a| a == "--json");
+    if let Some(other) = args.iter().find(|a| *a != "--json" && *a != "--prompt") {
+        eprintln!("Unknown argument '{}'\n{}", other, USAGE);
+        return 2;
+    }
+    if args.iter().any(|a| a == "--prompt") {
+        prompt();
+    }
+
+    let audio = SystemAudio.get_apps_playing_audio();
+    let mut backend_errors = BTreeMap::new();
+    if let Err(error) = &audio {
+        backend_errors.insert("audio", BackendError { kind: error.kind(), message: error.to_string() });
+    }
+    let capability = capability::assess(&backend_errors);
+    let mut checks = vec![audio_backend(audio)];
+    checks.extend(platform_checks());
+    let code = exit_code(&checks);
+
+    let report = PermissionsReport { checks, capability_level: capability.level, capability_reason: capability.reason };
+    if as_json {
+        match Envelope::new(EventType::PermissionsReport, &report).to_json_line() {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("[permissions] Failed to serialize report: {}", e);
+                return 1;
+            }
+        }
+    } else {
+        for check in &report.checks {
+            let status = match check.status {
+                Status::Granted => "ok",
+                Status::Missing if check.required => "MISSING",
+                Status::Missing => "missing",
+                Status::Unknown => "unknown",
+            };
+            println!("[{:<7}] {:<18} {}", status, check.name, check.detail);
+            if let Some(remedy) = check.remedy {
+                println!("{:<29}-> {}", "", remedy);
+            }
+        }
+        match &report.capability_reason {
+            Some(reason) => println!("Capability level: {} ({})", report.capability_level.as_str(), reason),
+            None => println!("Capability level: {}", report.capability_level.as_str()),
+        }
+    }
+    code
+}
+
+/// Trigger the OS prompts for grants that have one
+#[cfg(target_os = "macos")]
+fn prompt() {
+    if !crate::platform::macos::request_accessibility() {
+        eprintln!("[permissions] Asked for Accessibility access; run the check again after answering the prompt");
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn prompt() {
+    eprintln!("[permissions] Nothing to prompt for on this platform");
+}
+
+fn exit_code(checks: &[Check]) -> i32 {
+    let missing = |required: bool| checks.iter().any(|c| c.status == Status::Missing && c.required == required);
+    if missing(true) {
+        1
+    } else if missing(false) {
+        3
+    } else {
+        0
+    }
+}
+
+/// Whether audio sessions can be listed at all
+fn audio_backend(audio: Result<Vec<AudioAppSession>, ValidatorError>) -> Check {
+    let unavailable = if cfg!(target_os = "windows") {
+        "Start the Windows Audio service: sc start Audiosrv"
+    } else if cfg!(target_os = "macos") {
+        "Restart Core Audio: sudo killall coreaudiod"
+    } else if cfg!(target_os = "freebsd") {
+        "Load the sound driver: kldload snd_driver, and set hw.snd.verbose=2 so /dev/sndstat lists channel owners"
+    } else {
+        "Start the sound server: systemctl --user start pipewire-pulse (or pulseaudio)"
+    };
+
+    match audio {
+        Ok(apps) => Check::granted("audio_backend", true, format!("audio sessions readable ({} playing)", apps.len())),
+        Err(error) => {
+            let remedy = match error.kind() {
+                ErrorKind::BackendUnavailable => Some(unavailable),
+                ErrorKind::PermissionDenied => Some("Run the monitor as the signed-in desktop user"),
+                _ => None,
+            };
+            Check::missing("audio_backend", true, error.to_string(), remedy)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn platform_checks() -> Vec<Check> {
+    use crate::platform::macos::{self, Authorization};
+
+    let microphone = match macos::microphone_authorization() {
+        Authorization::Authorized => Check::granted("microphone", false, "microphone access granted"),
+        Authorization::NotDetermined => Check::unknown("microphone", false, "not asked yet; macOS prompts on first capture"),
+        status => Check::missing(
+            "microphone",
+            false,
+            format!("microphone access {:?}", status).to_lowercase(),
+            Some("System Settings > Privacy & Security > Microphone: enable the terminal or rust-audio-validator"),
+        ),
+    };
+    let accessibility = if macos::accessibility_trusted() {
+        Check::granted("accessibility", false, "window titles readable")
+    } else {
+        Check::missing(
+            "accessibility",
+            false,
+            "window titles fall back to process names",
+            Some("System Settings > Privacy & Security > Accessibility: add the terminal or rust-audio-validator"),
+        )
+    };
+    let screen_recording = if macos::screen_capture_allowed() {
+        Check::granted("screen_recording", false, "other apps' window names visible")
+    } else {
+        Check::missing(
+            "screen_recording",
+            false,
+            "other apps' window names hidden from the window list",
+            Some("System Settings > Privacy & Security > Screen Recording: add the terminal or rust-audio-validator"),
+        )
+    };
+    vec![microphone, accessibility, screen_recording]
+}
+
+#[cfg(target_os = "linux")]
+fn platform_checks() -> Vec<Check> {
+    let mut pulse = pulse_socket();
+    let mut devices = sound_devices();
+    // Without a sound server the backend reads ALSA directly (audio/alsa.rs), through /dev/snd
+    if pulse.status == Status::Missing && crate::audio::alsa::is_fallback() {
+        pulse.required = false;
+        pulse.detail.push_str("; using the ALSA fallback (no per-app volume)");
+        devices.required = true;
+    }
+    vec![pulse, devices, proc_visibility(), socket_listing(), display(), sandbox()]
+}
+
+/// The PulseAudio (or pipewire-pulse) native socket accepts connections
+#[cfg(target_os = "linux")]
+fn pulse_socket() -> Check {
+    use std::io::ErrorKind;
+    use std::os::unix::net::UnixStream;
+
+    let path = match std::env::var("PULSE_SERVER") {
+        Ok(server) => server.trim_start_matches("unix:").to_string(),
+        Err(_) => match std::env::var("XDG_RUNTIME_DIR") {
+            Ok(runtime_dir) => format!("{}/pulse/native", runtime_dir),
+            Err(_) => {
+                return Check::missing(
+                    "pulse_socket",
+                    true,
+                    "XDG_RUNTIME_DIR is not set, so the sound server cannot be found",
+                    Some("Run the monitor inside the user's desktop session"),
+                )
+            }
+        },
+    };
+
+    match UnixStream::connect(&path) {
+        Ok(_) => Check::granted("pulse_socket", true, format!("connected to {}", path)),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => Check::missing(
+            "pulse_socket",
+            true,
+            format!("{}: {}", path, e),
+            Some("Run as the desktop user; with a system-wide PulseAudio add the user to the pulse-access group"),
+        ),
+        Err(e) => Check::missing(
+            "pulse_socket",
+            true,
+            format!("{}: {}", path, e),
+            Some("Start the sound server: systemctl --user start pipewire-pulse (or pulseaudio)"),
+        ),
+    }
+}
+
+/// ALSA device nodes are opened through logind ACLs or the audio group
+#[cfg(target_os = "linux")]
+fn sound_devices() -> Check {
+    if !std::path::Path::new("/dev/snd/controlC0").exists() {
+        return Check::unknown("sound_devices", false, "no ALSA sound card");
+    }
+    match std::fs::File::open("/dev/snd/controlC0") {
+        Ok(_) => Check::granted("sound_devices", false, "/dev/snd accessible"),
+        Err(e) => Check::missing(
+            "sound_devices",
+            false,
+            format!("/dev/snd/controlC0: {}", e),
+            Some("Add the user to the audio group: sudo usermod -aG audio $USER, then log in again"),
+        ),
+    }
+}
+
+/// /proc mounted with hidepid hides other users' processes from attribution
+#[cfg(target_os = "linux")]
+fn proc_visibility() -> Check {
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let hidepid = mounts
+        .lines()
+        .filter(|line| line.split_whitespace().nth(1) == Some("/proc"))
+        .flat_map(|line| line.split_whitespace().nth(3).unwrap_or("").split(','))
+        .find_map(|option| option.strip_prefix("hidepid="))
+        .filter(|value| !matches!(*value, "0" | "off"));
+
+    match hidepid {
+        None => Check::granted("proc_visibility", false, "all processes visible in /proc"),
+        Some(value) => Check::missing(
+            "proc_visibility",
+            false,
+            format!("/proc is mounted with hidepid={}", value),
+            Some("Add the user to the group named by gid= in the /proc mount options, or remount /proc without hidepid"),
+        ),
+    }
+}
+
+/// Netlink sock_diag, the fast path for socket scans
+#[cfg(target_os = "linux")]
+fn socket_listing() -> Check {
+    match crate::sock_diag::sockets(crate::sock_diag::Protocol::Udp) {
+        Ok(sockets) => Check::granted("socket_listing", false, format!("netlink sock_diag ({} UDP sockets)", sockets.len())),
+        Err(e) => Check::missing(
+            "socket_listing",
+            false,
+            format!("netlink sock_diag failed: {}", e),
+            Some("Socket scans fall back to ss; install iproute2 if it is missing"),
+        ),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn display() -> Check {
+    match (std::env::var_os("WAYLAND_DISPLAY"), std::env::var_os("DISPLAY")) {
+        (Some(_), _) => Check::granted("display", false, "Wayland session"),
+        (None, Some(_)) => Check::granted("display", false, "X11 session"),
+        (None, None) => Check::missing(
+            "display",
+            false,
+            "no DISPLAY or WAYLAND_DISPLAY; window titles come from command lines only",
+            Some("Run the monitor inside the desktop session, or export DISPLAY / WAYLAND_DISPLAY"),
+        ),
+    }
+}
+
+/// Inside a Flatpak or Snap sandbox /proc shows only the monitor's own processes
+#[cfg(target_os = "linux")]
+fn sandbox() -> Check {
+    use crate::sandbox::Sandbox;
+
+    match crate::sandbox::current() {
+        None => Check::granted("sandbox", false, "not sandboxed"),
+        Some(Sandbox::Flatpak) => Check::missing(
+            "sandbox",
+            false,
+            "running inside Flatpak; other apps' processes are not visible",
+            Some("Flatpak cannot expose host processes; install the monitor from the native package instead"),
+        ),
+        Some(Sandbox::Snap) => Check::missing(
+            "sandbox",
+            false,
+            "running as a strictly confined snap; other apps' processes are not visible",
+            Some("Connect the interface: sudo snap connect <snap>:system-observe"),
+        ),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn platform_checks() -> Vec<Check> {
+    vec![process_query(), microphone_privacy()]
+}
+
+/// Names of other processes, needed to attribute their audio sessions and sockets
+#[cfg(target_os = "windows")]
+fn process_query() -> Check {
+    use crate::platform::PlatformUtils;
+
+    // 0 and 4 are the idle and System pseudo-processes
+    let pids: Vec<u32> = crate::process_tree::ProcessTree::snapshot().pids().into_iter().filter(|&pid| pid > 4).collect();
+    let denied = pids
+        .iter()
+        .filter(|&&pid| {
+            <() as PlatformUtils>::get_process_name(pid).is_err_and(|e| e.kind() == ErrorKind::PermissionDenied)
+        })
+        .count();
+
+    if denied == 0 {
+        Check::granted("process_query", false, format!("all {} processes queryable", pids.len()))
+    } else {
+        Check::missing(
+            "process_query",
+            false,
+            format!("{} of {} processes cannot be queried", denied, pids.len()),
+            Some("Run elevated (as administrator) to attribute audio from elevated and other users' processes"),
+        )
+    }
+}
+
+/// Settings > Privacy > Microphone; with it off no app can open the microphone
+#[cfg(target_os = "windows")]
+fn microphone_privacy() -> Check {
+    match crate::audio::windows_consent::capability_setting("microphone").as_deref() {
+        Some("Allow") => Check::granted("microphone_privacy", false, "microphone access is on"),
+        Some(value) => Check::missing(
+            "microphone_privacy",
+            false,
+            format!("microphone access is set to {}", value),
+            Some("Settings > Privacy & security > Microphone: turn on Microphone access"),
+        ),
+        None => Check::missing(
+            "microphone_privacy",
+            false,
+            "microphone consent store unreadable; mic users come from audio sessions only",
+            Some("Run the monitor as the signed-in user so it can read HKCU"),
+        ),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn platform_checks() -> Vec<Check> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_separates_required_from_optional() {
+        let mut checks = vec![
+            Check::granted("audio_backend", true, "ok"),
+            Check::unknown("sound_devices", false, "no ALSA sound card"),
+        ];
+        assert_eq!(exit_code(&checks), 0);
+        checks.push(Check::missing("accessibility", false, "titles fall back", None));
+        assert_eq!(exit_code(&checks), 3);
+        checks[0] = Check::missing("audio_backend", true, "PulseAudio is not available", None);
+        assert_eq!(exit_code(&checks), 1);
+    }
+}
@@ -0,0 +1,82 @@
+// FreeBSD platform utilities for process and window information
+
+use super::PlatformUtils;
+use crate::error::ValidatorError;
+use crate::process_cache::{self, PidCache};
+use std::ffi::CStr;
+use std::process::Command;
+
+// Implement PlatformUtils trait for FreeBSD
+impl PlatformUtils for () {
+    fn get_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
+        get_process_name_impl(pid)
+    }
+
+    fn get_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
+        get_window_title(pid)
+    }
+}
+
+static PROCESS_NAMES: PidCache<String> = PidCache::new(process_cache::NAME_TTL, process_cache::CAPACITY);
+static WINDOW_TITLES: PidCache<String> = PidCache::new(process_cache::TITLE_TTL, process_cache::CAPACITY);
+
+fn get_process_name_impl(pid: u32) -> std::result::Result<String, ValidatorError> {
+    PROCESS_NAMES.get_or_try_insert_with(pid, || {
+        process_info(pid)
+            .map(|(_, name)| name)
+            .ok_or_else(|| ValidatorError::NotFound(format!("process {}", pid)))
+    })
+}
+
+pub fn get_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
+    WINDOW_TITLES.get_or_try_insert_with(pid, || query_window_title(pid))
+}
+
+/// Parent pid and command name from the kern.proc.pid sysctl
+pub fn process_info(pid: u32) -> Option<(u32, String)> {
+    let info = kinfo_proc(pid)?;
+    let name = unsafe { CStr::from_ptr(info.ki_comm.as_ptr()) }.to_string_lossy().to_string();
+    Some((info.ki_ppid.max(0) as u32, name))
+}
+
+/// User and system run time of `pid`
+pub fn cpu_time(pid: u32) -> Option<std::time::Duration> {
+    Some(std::time::Duration::from_micros(kinfo_proc(pid)?.ki_runtime))
+}
+
+fn kinfo_proc(pid: u32) -> Option<libc::kinfo_proc> {
+    let mut mib = [libc::CTL_KERN, libc::KERN_PROC, libc::KERN_PROC_PID, pid as libc::c_int];
+    unsafe {
+        let mut info: libc::kinfo_proc = std::mem::zeroed();
+        let mut size = std::mem::size_of::<libc::kinfo_proc>();
+        let result = libc::sysctl(
+            mib.as_mut_ptr(),
+            mib.len() as libc::c_uint,
+            &mut info as *mut _ as *mut libc::c_void,
+            &mut size,
+            std::ptr::null(),
+            0,
+        );
+        // An exited pid answers with no data rather than an error
+        if result != 0 || size == 0 {
+            return None;
+        }
+        Some(info)
+    }
+}
+
+/// Window title from wmctrl under X11, else the process name
+fn query_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
+    if let Ok(output) = crate::subprocess::output(Command::new("wmctrl").args(["-l", "-p"])) {
+        let windows = String::from_utf8_lossy(&output.stdout);
+        for line in windows.lines() {
+            // wmctrl format: window_id desktop pid machine window_title
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() >= 5 && parts[2].parse::<u32>() == Ok(pid) {
+                return Ok(parts[4..].join(" "));
+            }
+        }
+    }
+
+    get_process_name_impl(pid)
+}
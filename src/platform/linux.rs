@@ -1,44 +1,66 @@
 // Linux platform utilities for process and window information
 
 use super::PlatformUtils;
+use crate::error::ValidatorError;
+use crate::process_cache::{self, PidCache};
 use procfs::process::Process;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::ffi::CStr;
-use std::os::raw::{c_char, c_int, c_long, c_ulong};
+use std::os::raw::{c_char, c_ulong};
 use std::process::Command;
+use std::ptr;
 
 // Implement PlatformUtils trait for Linux
 impl PlatformUtils for () {
-    fn get_process_name(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
         get_process_name_impl(pid)
     }
 
-    fn get_window_title(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
         get_window_title_impl(pid)
     }
 }
 
-/// Get process name from /proc filesystem
-fn get_process_name_impl(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    let process = Process::new(pid as i32)
-        .map_err(|e| format!("Failed to read process {}: {}", pid, e))?;
+static PROCESS_NAMES: PidCache<String> = PidCache::new(process_cache::NAME_TTL, process_cache::CAPACITY);
+static WINDOW_TITLES: PidCache<String> = PidCache::new(process_cache::TITLE_TTL, process_cache::CAPACITY);
+
+fn get_process_name_impl(pid: u32) -> std::result::Result<String, ValidatorError> {
+    PROCESS_NAMES.get_or_try_insert_with(pid, || query_process_name(pid))
+}
+
+fn get_window_title_impl(pid: u32) -> std::result::Result<String, ValidatorError> {
+    WINDOW_TITLES.get_or_try_insert_with(pid, || query_window_title(pid))
+}
 
-    let stat = process.stat()
-        .map_err(|e| format!("Failed to read process stat: {}", e))?;
+/// Get process name from /proc filesystem
+fn query_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
+    let process = Process::new(pid as i32)?;
+    let stat = process.stat()?;
 
-    Ok(stat.comm)
+    Ok(crate::sandbox::resolve_name(pid, stat.comm))
 }
 
 /// Get window title for a process using X11, Wayland, or fallbacks
 /// Tries multiple methods to ensure window titles are found
-fn get_window_title_impl(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    // Method 1: Try X11 window title first
-    if let Ok(title) = get_window_title_x11(pid) {
-        if !title.is_empty() && title != "Window not found for PID" {
-            return Ok(title);
+fn query_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
+    // Method 1: Try X11 window titles first, preferring a messenger's call window, then
+    // one that names a call app
+    if let Ok(mut titles) = get_window_titles_x11(pid) {
+        let process_name = get_process_name_impl(pid).unwrap_or_default();
+        // Firefox's sharing indicator and empty windows name no page
+        if crate::firefox::is_firefox(&process_name) && titles.iter().any(|title| crate::firefox::page_title(title).is_some()) {
+            titles.retain(|title| crate::firefox::page_title(title).is_some());
         }
+        let title = titles
+            .iter()
+            .find(|title| crate::messengers::title_shows_call(&process_name, title))
+            .or_else(|| titles.iter().find(|title| crate::detect_call_app(&process_name, title).is_some()))
+            .unwrap_or(&titles[0]);
+        return Ok(title.clone());
     }
 
-    // Method 2: Try Wayland via /proc/pid/environ and desktop files
+    // Method 2: Try Wayland (wlr-foreign-toplevel or GNOME Shell introspection)
     if let Ok(title) = get_window_title_wayland(pid) {
         if !title.is_empty() {
             return Ok(title);
@@ -63,61 +85,43 @@ fn get_window_title_impl(pid: u32) -> std::result::Result<String, Box<dyn std::e
     get_process_name_impl(pid)
 }
 
-/// Get window title on Wayland using proc and environment
-fn get_window_title_wayland(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    use std::fs;
-
-    // Read environment variables to detect the display protocol
-    let environ_path = format!("/proc/{}/environ", pid);
-    if let Ok(environ_data) = fs::read(&environ_path) {
-        let environ_str = String::from_utf8_lossy(&environ_data);
-
-        // Check if running under Wayland
-        if environ_str.contains("WAYLAND_DISPLAY") {
-            // Try to extract GDK_BACKEND or other app identifiers
-            for entry in environ_str.split('\0') {
-                if entry.starts_with("GDK_BACKEND") || entry.starts_with("WAYLAND_DISPLAY") {
-                    // Detected Wayland, now try to get app name via cmdline
-                    return get_title_from_cmdline(pid);
-                }
-            }
-        }
-    }
+/// Get window title on Wayland from the compositor's toplevel list
+fn get_window_title_wayland(pid: u32) -> std::result::Result<String, ValidatorError> {
+    let process_name = get_process_name_impl(pid)?;
 
-    Err("Not running under Wayland or info unavailable".into())
+    super::wayland::window_title(&process_name)
+        .ok_or_else(|| ValidatorError::NotFound(format!("Wayland toplevel of process {}", pid)))
 }
 
 /// Get window title using wmctrl command
-fn get_window_title_wmctrl(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    let output = Command::new("wmctrl")
-        .args(&["-l", "-p"])
-        .output();
-
-    if let Ok(output) = output {
-        if output.status.success() {
-            let wmctrl_str = String::from_utf8_lossy(&output.stdout);
-
-            for line in wmctrl_str.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                // wmctrl format: window_id desktop pid machine window_title
-                if parts.len() >= 5 {
-                    if let Ok(window_pid) = parts[2].parse::<u32>() {
-                        if window_pid == pid {
-                            // Join remaining parts as window title
-                            let title = parts[4..].join(" ");
-                            return Ok(title);
-                        }
+fn get_window_title_wmctrl(pid: u32) -> std::result::Result<String, ValidatorError> {
+    let output = crate::subprocess::output(Command::new("wmctrl")
+        .args(&["-l", "-p"]))
+        .map_err(|e| ValidatorError::spawn("wmctrl", e))?;
+
+    if output.status.success() {
+        let wmctrl_str = String::from_utf8_lossy(&output.stdout);
+
+        for line in wmctrl_str.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            // wmctrl format: window_id desktop pid machine window_title
+            if parts.len() >= 5 {
+                if let Ok(window_pid) = parts[2].parse::<u32>() {
+                    if window_pid == pid {
+                        // Join remaining parts as window title
+                        let title = parts[4..].join(" ");
+                        return Ok(title);
                     }
                 }
             }
         }
     }
 
-    Err("wmctrl not available or window not found".into())
+    Err(ValidatorError::NotFound(format!("wmctrl window of process {}", pid)))
 }
 
 /// Extract meaningful title from command line arguments
-fn get_title_from_cmdline(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+fn get_title_from_cmdline(pid: u32) -> std::result::Result<String, ValidatorError> {
     use std::fs;
 
     let cmdline_path = format!("/proc/{}/cmdline", pid);
@@ -157,7 +161,7 @@ fn get_title_from_cmdline(pid: u32) -> std::result::Result<String, Box<dyn std::
         }
     }
 
-    Err("Could not extract title from cmdline".into())
+    Err(ValidatorError::NotFound(format!("title in the command line of process {}", pid)))
 }
 
 /// Extract domain from URL
@@ -173,167 +177,197 @@ fn extract_domain(url: &str) -> String {
     url.to_string()
 }
 
-/// Get window title using X11
-#[cfg(feature = "x11")]
-fn get_window_title_x11(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    use x11::xlib::*;
-    use std::ptr;
-
-    unsafe {
-        // Open display
-        let display = XOpenDisplay(ptr::null());
-        if display.is_null() {
-            return Err("Failed to open X11 display".into());
+/// Persistent X11 connection with the atoms we query, reopened only after a failure
+struct X11Connection {
+    display: *mut x11::xlib::Display,
+    net_client_list: x11::xlib::Atom,
+    net_wm_pid: x11::xlib::Atom,
+    net_wm_name: x11::xlib::Atom,
+    utf8_string: x11::xlib::Atom,
+}
+
+impl X11Connection {
+    fn open() -> std::result::Result<Self, ValidatorError> {
+        use x11::xlib::*;
+
+        unsafe {
+            let display = XOpenDisplay(ptr::null());
+            if display.is_null() {
+                return Err(ValidatorError::BackendUnavailable("X11 display".to_string()));
+            }
+
+            let atom = |name: &[u8]| XInternAtom(display, name.as_ptr() as *const c_char, 0);
+            Ok(X11Connection {
+                display,
+                net_client_list: atom(b"_NET_CLIENT_LIST\0"),
+                net_wm_pid: atom(b"_NET_WM_PID\0"),
+                net_wm_name: atom(b"_NET_WM_NAME\0"),
+                utf8_string: atom(b"UTF8_STRING\0"),
+            })
         }
+    }
 
-        let root = XDefaultRootWindow(display);
+    /// Titles of all managed windows owned by any of `pids`
+    fn window_titles(&self, pids: &HashSet<u32>) -> Vec<String> {
+        use x11::xlib::*;
+
+        unsafe {
+            let root = XDefaultRootWindow(self.display);
+            let windows = match self.property(root, self.net_client_list, XA_WINDOW) {
+                Some((data, count)) => std::slice::from_raw_parts(data as *const c_ulong, count).to_vec(),
+                None => return Vec::new(),
+            };
+
+            let mut titles = Vec::new();
+            for window in windows {
+                let window_pid = match self.property(window, self.net_wm_pid, XA_CARDINAL) {
+                    Some((data, count)) if count > 0 => {
+                        let window_pid = *(data as *const c_ulong) as u32;
+                        XFree(data as *mut _);
+                        window_pid
+                    }
+                    Some((data, _)) => {
+                        XFree(data as *mut _);
+                        continue;
+                    }
+                    None => continue,
+                };
+                if !pids.contains(&window_pid) {
+                    continue;
+                }
 
-        // Get the _NET_CLIENT_LIST atom
-        let net_client_list = XInternAtom(
-            display,
-            b"_NET_CLIENT_LIST\0".as_ptr() as *const c_char,
-            0,
-        );
+                if let Some(title) = self.title(window) {
+                    if !title.is_empty() {
+                        titles.push(title);
+                    }
+                }
+            }
+            titles
+        }
+    }
 
-        let net_wm_pid = XInternAtom(
-            display,
-            b"_NET_WM_PID\0".as_ptr() as *const c_char,
-            0,
-        );
+    /// _NET_WM_NAME (UTF-8), falling back to the legacy WM_NAME
+    unsafe fn title(&self, window: x11::xlib::Window) -> Option<String> {
+        use x11::xlib::*;
 
-        let net_wm_name = XInternAtom(
-            display,
-            b"_NET_WM_NAME\0".as_ptr() as *const c_char,
-            0,
-        );
+        if let Some((data, count)) = self.property(window, self.net_wm_name, self.utf8_string) {
+            let title = String::from_utf8_lossy(std::slice::from_raw_parts(data, count)).to_string();
+            XFree(data as *mut _);
+            return Some(title);
+        }
 
-        let utf8_string = XInternAtom(
-            display,
-            b"UTF8_STRING\0".as_ptr() as *const c_char,
-            0,
-        );
+        let mut name: *mut c_char = ptr::null_mut();
+        if XFetchName(self.display, window, &mut name) != 0 && !name.is_null() {
+            let title = CStr::from_ptr(name).to_string_lossy().to_string();
+            XFree(name as *mut _);
+            return Some(title);
+        }
+        None
+    }
 
-        // Get window list
-        let mut actual_type_return = 0;
-        let mut actual_format_return = 0;
-        let mut nitems_return = 0;
-        let mut bytes_after_return = 0;
-        let mut prop_return: *mut u8 = ptr::null_mut();
+    /// Raw property data and item count; the caller frees the data with XFree
+    unsafe fn property(
+        &self,
+        window: x11::xlib::Window,
+        property: x11::xlib::Atom,
+        property_type: x11::xlib::Atom,
+    ) -> Option<(*mut u8, usize)> {
+        use x11::xlib::*;
+
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut nitems = 0;
+        let mut bytes_after = 0;
+        let mut data: *mut u8 = ptr::null_mut();
 
         let status = XGetWindowProperty(
-            display,
-            root,
-            net_client_list,
+            self.display,
+            window,
+            property,
             0,
-            1024,
+            65536,
             0,
-            XA_WINDOW,
-            &mut actual_type_return,
-            &mut actual_format_return,
-            &mut nitems_return,
-            &mut bytes_after_return,
-            &mut prop_return,
+            property_type,
+            &mut actual_type,
+            &mut actual_format,
+            &mut nitems,
+            &mut bytes_after,
+            &mut data,
         );
 
-        if status != 0 || prop_return.is_null() {
-            XCloseDisplay(display);
-            return Err("Failed to get window list".into());
+        if status != 0 || data.is_null() {
+            return None;
         }
+        Some((data, nitems as usize))
+    }
+}
 
-        let windows = std::slice::from_raw_parts(
-            prop_return as *const c_ulong,
-            nitems_return as usize,
-        );
-
-        // Search for window with matching PID
-        for &window in windows {
-            // Get window PID
-            let mut window_pid_type = 0;
-            let mut window_pid_format = 0;
-            let mut window_pid_nitems = 0;
-            let mut window_pid_bytes_after = 0;
-            let mut window_pid_prop: *mut u8 = ptr::null_mut();
+impl Drop for X11Connection {
+    fn drop(&mut self) {
+        unsafe {
+            x11::xlib::XCloseDisplay(self.display);
+        }
+    }
+}
 
-            let pid_status = XGetWindowProperty(
-                display,
-                window,
-                net_wm_pid,
-                0,
-                1,
-                0,
-                XA_CARDINAL,
-                &mut window_pid_type,
-                &mut window_pid_format,
-                &mut window_pid_nitems,
-                &mut window_pid_bytes_after,
-                &mut window_pid_prop,
-            );
-
-            if pid_status == 0 && !window_pid_prop.is_null() {
-                let window_pid = *(window_pid_prop as *const c_ulong) as u32;
-
-                if window_pid == pid {
-                    // Found matching window, get title
-                    let mut title_type = 0;
-                    let mut title_format = 0;
-                    let mut title_nitems = 0;
-                    let mut title_bytes_after = 0;
-                    let mut title_prop: *mut u8 = ptr::null_mut();
-
-                    let title_status = XGetWindowProperty(
-                        display,
-                        window,
-                        net_wm_name,
-                        0,
-                        1024,
-                        0,
-                        utf8_string,
-                        &mut title_type,
-                        &mut title_format,
-                        &mut title_nitems,
-                        &mut title_bytes_after,
-                        &mut title_prop,
-                    );
-
-                    if title_status == 0 && !title_prop.is_null() {
-                        let title_cstr = CStr::from_ptr(title_prop as *const c_char);
-                        let title = title_cstr.to_string_lossy().to_string();
-
-                        XFree(title_prop as *mut _);
-                        XFree(window_pid_prop as *mut _);
-                        XFree(prop_return as *mut _);
-                        XCloseDisplay(display);
+thread_local! {
+    static X11_CONNECTION: RefCell<Option<X11Connection>> = const { RefCell::new(None) };
+}
 
-                        return Ok(title);
-                    }
+/// Get all window titles for a process using X11
+/// Browsers play audio from a renderer or audio-service child while the window belongs to
+/// the browser process, so windows of the root application process and its descendants
+/// count too.
+fn get_window_titles_x11(pid: u32) -> std::result::Result<Vec<String>, ValidatorError> {
+    if std::env::var_os("DISPLAY").is_none() {
+        return Err(ValidatorError::BackendUnavailable("X11 display".to_string()));
+    }
 
-                    if !title_prop.is_null() {
-                        XFree(title_prop as *mut _);
-                    }
-                }
+    let pids = process_family(pid);
+    X11_CONNECTION.with(|cell| {
+        let mut connection = cell.borrow_mut();
+        if connection.is_none() {
+            *connection = Some(X11Connection::open()?);
+        }
 
-                XFree(window_pid_prop as *mut _);
-            }
+        let titles = connection.as_ref().unwrap().window_titles(&pids);
+        if titles.is_empty() {
+            return Err(ValidatorError::NotFound(format!("X11 window of process {}", pid)));
         }
+        Ok(titles)
+    })
+}
 
-        XFree(prop_return as *mut _);
-        XCloseDisplay(display);
+/// `pid`, its root application process, and all of the root's descendants
+fn process_family(pid: u32) -> HashSet<u32> {
+    let root = crate::process_tree::ProcessTree::snapshot().root(pid);
 
-        Err("Window not found for PID".into())
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    if let Ok(all) = procfs::process::all_processes() {
+        for process in all.flatten() {
+            if let Ok(stat) = process.stat() {
+                children.entry(stat.ppid as u32).or_default().push(stat.pid as u32);
+            }
+        }
     }
-}
 
-/// Fallback when X11 is not available (Wayland or headless)
-#[cfg(not(feature = "x11"))]
-fn get_window_title_x11(_pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    Err("X11 support not compiled".into())
+    let mut family = HashSet::from([pid, root]);
+    let mut frontier = vec![root];
+    while let Some(current) = frontier.pop() {
+        for &child in children.get(&current).into_iter().flatten() {
+            if family.insert(child) {
+                frontier.push(child);
+            }
+        }
+    }
+    family
 }
 
 // Public convenience functions
-pub fn get_process_name(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+pub fn get_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
     get_process_name_impl(pid)
 }
 
-pub fn get_window_title(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+pub fn get_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
     get_window_title_impl(pid)
 }
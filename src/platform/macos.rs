@@ -1,25 +1,36 @@
 // macOS platform utilities for process and window information
 
 use super::PlatformUtils;
+use crate::error::ValidatorError;
+use crate::process_cache::{self, PidCache};
 use std::process::Command;
 
 // Implement PlatformUtils trait for macOS
 impl PlatformUtils for () {
-    fn get_process_name(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
         get_process_name_impl(pid)
     }
 
-    fn get_window_title(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
         get_window_title_impl(pid)
     }
 }
 
+static PROCESS_NAMES: PidCache<String> = PidCache::new(process_cache::NAME_TTL, process_cache::CAPACITY);
+static WINDOW_TITLES: PidCache<String> = PidCache::new(process_cache::TITLE_TTL, process_cache::CAPACITY);
+
+fn get_process_name_impl(pid: u32) -> std::result::Result<String, ValidatorError> {
+    PROCESS_NAMES.get_or_try_insert_with(pid, || query_process_name(pid))
+}
+
+fn get_window_title_impl(pid: u32) -> std::result::Result<String, ValidatorError> {
+    WINDOW_TITLES.get_or_try_insert_with(pid, || query_window_title(pid))
+}
+
 /// Get process name from process ID using ps command
-fn get_process_name_impl(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    let output = Command::new("ps")
-        .args(&["-p", &pid.to_string(), "-o", "comm="])
-        .output()
-        .map_err(|e| format!("Failed to execute ps: {}", e))?;
+fn query_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
+    let output = crate::subprocess::output(Command::new("ps")
+        .args(&["-p", &pid.to_string(), "-o", "comm="]))?;
 
     if output.status.success() {
         let name = String::from_utf8_lossy(&output.stdout)
@@ -35,20 +46,29 @@ fn get_process_name_impl(pid: u32) -> std::result::Result<String, Box<dyn std::e
         }
     }
 
-    Err(format!("Process {} not found", pid).into())
+    Err(ValidatorError::NotFound(format!("process {}", pid)))
 }
 
 /// Get window title for a process using AppleScript
-/// This requires Accessibility permissions on macOS
-fn get_window_title_impl(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
-    // Method 1: Try to get window title via AppleScript
-    // This requires Accessibility permissions
-
+/// This requires Accessibility permissions on macOS; without them the process name stands
+/// in for the title and the capability level (capability.rs) says so.
+fn query_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
     // First, get the process name to identify the app
     let process_name = get_process_name_impl(pid)?;
 
+    // Safari answers through its own dictionary, naming a call tab even when it isn't in front
+    if crate::safari::is_safari(&process_name) {
+        if let Some(title) = crate::safari::window_title() {
+            return Ok(title);
+        }
+    }
+
+    // System Events would only answer with the app name
+    if !accessibility_trusted() {
+        return Ok(process_name);
+    }
+
     // Try using osascript to get window title
-    // Note: This may fail if Accessibility permissions are not granted
     let script = format!(
         r#"
         tell application "System Events"
@@ -70,10 +90,9 @@ fn get_window_title_impl(pid: u32) -> std::result::Result<String, Box<dyn std::e
         pid, process_name
     );
 
-    let output = Command::new("osascript")
+    let output = crate::subprocess::output(Command::new("osascript")
         .arg("-e")
-        .arg(&script)
-        .output();
+        .arg(&script));
 
     match output {
         Ok(output) if output.status.success() => {
@@ -93,10 +112,77 @@ fn get_window_title_impl(pid: u32) -> std::result::Result<String, Box<dyn std::e
 }
 
 // Public convenience functions
-pub fn get_process_name(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+pub fn get_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
     get_process_name_impl(pid)
 }
 
-pub fn get_window_title(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+pub fn get_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
     get_window_title_impl(pid)
 }
+
+// Privacy (TCC) grants, for check-permissions and the capability level
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+    fn AXIsProcessTrustedWithOptions(options: core_foundation::dictionary::CFDictionaryRef) -> bool;
+    static kAXTrustedCheckOptionPrompt: core_foundation::string::CFStringRef;
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+}
+
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {
+    static AVMediaTypeAudio: *mut objc::runtime::Object;
+}
+
+/// AVAuthorizationStatus of a capture device type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authorization {
+    NotDetermined,
+    Restricted,
+    Denied,
+    Authorized,
+}
+
+/// Whether this process may read other apps' windows through the Accessibility API
+pub fn accessibility_trusted() -> bool {
+    unsafe { AXIsProcessTrusted() }
+}
+
+/// Ask for Accessibility access: macOS shows its prompt pointing to System Settings
+/// unless access is already granted (or was denied before, in which case nothing shows)
+/// Returns whether access is granted now; the user's answer only applies afterwards.
+pub fn request_accessibility() -> bool {
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::CFDictionary;
+    use core_foundation::string::CFString;
+
+    unsafe {
+        let prompt = CFString::wrap_under_get_rule(kAXTrustedCheckOptionPrompt);
+        let options = CFDictionary::from_CFType_pairs(&[(prompt.as_CFType(), CFBoolean::true_value().as_CFType())]);
+        AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef())
+    }
+}
+
+/// Whether this process may read other apps' window titles through CGWindowList (10.15+)
+pub fn screen_capture_allowed() -> bool {
+    unsafe { CGPreflightScreenCaptureAccess() }
+}
+
+/// Microphone access of this process, without prompting
+pub fn microphone_authorization() -> Authorization {
+    use objc::{class, msg_send, sel, sel_impl};
+
+    let status: i64 = unsafe { msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio] };
+    match status {
+        1 => Authorization::Restricted,
+        2 => Authorization::Denied,
+        3 => Authorization::Authorized,
+        _ => Authorization::NotDetermined,
+    }
+}
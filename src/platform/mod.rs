@@ -8,14 +8,22 @@ pub mod windows;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+#[cfg(target_os = "linux")]
+pub mod wayland;
+
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+#[cfg(target_os = "freebsd")]
+pub mod freebsd;
+
+use crate::error::ValidatorError;
+
 // Common trait for platform utilities
 pub trait PlatformUtils {
     /// Get process name from process ID
-    fn get_process_name(pid: u32) -> Result<String, Box<dyn std::error::Error>>;
+    fn get_process_name(pid: u32) -> Result<String, ValidatorError>;
 
     /// Get window title from process ID
-    fn get_window_title(pid: u32) -> Result<String, Box<dyn std::error::Error>>;
+    fn get_window_title(pid: u32) -> Result<String, ValidatorError>;
 }
@@ -0,0 +1,251 @@
+// Wayland window titles
+// Wayland gives clients no way to read other clients' window titles, so they come from
+// compositor-specific interfaces instead:
+//   - wlr-foreign-toplevel-management (Sway, Hyprland, river, labwc and other wlroots compositors)
+//   - org.gnome.Shell.Introspect GetWindows over D-Bus (GNOME Shell)
+// Neither reports a pid, so windows are matched to processes by app id.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wayland_client::backend::ObjectId;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_registry::WlRegistry;
+use wayland_client::{event_created_child, Connection, Dispatch, EventQueue, Proxy, QueueHandle};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+    self, ZwlrForeignToplevelHandleV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+    self, ZwlrForeignToplevelManagerV1,
+};
+use zbus::zvariant::OwnedValue;
+
+/// A top-level window as reported by the compositor
+#[derive(Debug, Clone, Default)]
+pub struct Toplevel {
+    pub app_id: String,
+    pub title: String,
+    pub activated: bool,
+}
+
+/// Title of the window belonging to the process named `process_name`, preferring the
+/// focused one when the app has several
+pub fn window_title(process_name: &str) -> Option<String> {
+    let toplevels = toplevels();
+    let mut matching: Vec<&Toplevel> = toplevels
+        .iter()
+        .filter(|toplevel| !toplevel.title.is_empty() && app_id_matches(&toplevel.app_id, process_name))
+        .collect();
+    matching.sort_by_key(|toplevel| !toplevel.activated);
+    matching.first().map(|toplevel| toplevel.title.clone())
+}
+
+/// All windows from whichever compositor interface is available
+pub fn toplevels() -> Vec<Toplevel> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Vec::new();
+    }
+
+    match wlr_toplevels() {
+        Ok(toplevels) => toplevels,
+        Err(_) => gnome_shell_windows().unwrap_or_default(),
+    }
+}
+
+/// App ids are reverse-DNS ("org.mozilla.firefox"), desktop file names ("google-chrome")
+/// or bare names ("Slack"); process names are truncated executable names
+fn app_id_matches(app_id: &str, process_name: &str) -> bool {
+    let app_id = app_id.to_lowercase();
+    let app_id = app_id.strip_suffix(".desktop").unwrap_or(&app_id);
+    let short_id = app_id.rsplit('.').next().unwrap_or(app_id);
+    let process = process_name.to_lowercase();
+    let process = process.strip_suffix("-bin").unwrap_or(&process);
+
+    if short_id.len() < 3 || process.len() < 3 {
+        return short_id == process;
+    }
+    short_id.contains(process) || process.contains(short_id)
+}
+
+// ---- wlr-foreign-toplevel-management ----
+
+#[derive(Default)]
+struct WlrState {
+    pending: HashMap<ObjectId, Toplevel>,
+    toplevels: HashMap<ObjectId, Toplevel>,
+}
+
+struct WlrClient {
+    queue: EventQueue<WlrState>,
+    state: WlrState,
+    _manager: ZwlrForeignToplevelManagerV1,
+    _connection: Connection,
+}
+
+thread_local! {
+    // Kept open across polls: the compositor pushes title changes and we only
+    // dispatch what arrived since the last call
+    static WLR_CLIENT: RefCell<Option<WlrClient>> = const { RefCell::new(None) };
+}
+
+fn wlr_toplevels() -> Result<Vec<Toplevel>, String> {
+    WLR_CLIENT.with(|cell| {
+        let mut client = cell.borrow_mut();
+        if client.is_none() {
+            *client = Some(wlr_connect()?);
+        }
+
+        let active = client.as_mut().unwrap();
+        if let Err(e) = active.queue.roundtrip(&mut active.state) {
+            // Compositor restarted; reconnect on the next poll
+            *client = None;
+            return Err(e.to_string());
+        }
+        Ok(active.state.toplevels.values().cloned().collect())
+    })
+}
+
+fn wlr_connect() -> Result<WlrClient, String> {
+    let connection = Connection::connect_to_env().map_err(|e| e.to_string())?;
+    let (globals, mut queue) = registry_queue_init::<WlrState>(&connection).map_err(|e| e.to_string())?;
+    let manager: ZwlrForeignToplevelManagerV1 = globals
+        .bind(&queue.handle(), 1..=3, ())
+        .map_err(|e| format!("zwlr_foreign_toplevel_manager_v1 not offered: {}", e))?;
+
+    // First roundtrip announces the toplevels, the second delivers their properties
+    let mut state = WlrState::default();
+    queue.roundtrip(&mut state).map_err(|e| e.to_string())?;
+    queue.roundtrip(&mut state).map_err(|e| e.to_string())?;
+
+    Ok(WlrClient {
+        queue,
+        state,
+        _manager: manager,
+        _connection: connection,
+    })
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for WlrState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for WlrState {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        _: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // New handles arrive through Dispatch<ZwlrForeignToplevelHandleV1>
+    }
+
+    event_created_child!(WlrState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        use zwlr_foreign_toplevel_handle_v1::Event;
+
+        let id = handle.id();
+        match event {
+            // Property changes are double-buffered until `done`
+            Event::Title { title } => pending(state, &id).title = title,
+            Event::AppId { app_id } => pending(state, &id).app_id = app_id,
+            Event::State { state: flags } => {
+                pending(state, &id).activated = flags
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .any(|flag| flag == zwlr_foreign_toplevel_handle_v1::State::Activated as u32);
+            }
+            Event::Done => {
+                if let Some(toplevel) = state.pending.remove(&id) {
+                    state.toplevels.insert(id, toplevel);
+                }
+            }
+            Event::Closed => {
+                state.pending.remove(&id);
+                state.toplevels.remove(&id);
+                handle.destroy();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Pending properties for a handle, seeded from its last committed state
+fn pending<'a>(state: &'a mut WlrState, id: &ObjectId) -> &'a mut Toplevel {
+    let current = state.toplevels.get(id).cloned().unwrap_or_default();
+    state.pending.entry(id.clone()).or_insert(current)
+}
+
+// ---- GNOME Shell ----
+
+/// Windows from org.gnome.Shell.Introspect
+/// GNOME Shell only answers callers it trusts (unsafe mode, or portal-backed apps);
+/// for everyone else the call fails with AccessDenied and we fall back.
+fn gnome_shell_windows() -> Result<Vec<Toplevel>, String> {
+    let connection = zbus::blocking::Connection::session().map_err(|e| e.to_string())?;
+    let reply = connection
+        .call_method(
+            Some("org.gnome.Shell"),
+            "/org/gnome/Shell/Introspect",
+            Some("org.gnome.Shell.Introspect"),
+            "GetWindows",
+            &(),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let windows: HashMap<u64, HashMap<String, OwnedValue>> =
+        reply.body().deserialize().map_err(|e| e.to_string())?;
+
+    let string = |props: &HashMap<String, OwnedValue>, key: &str| {
+        props
+            .get(key)
+            .and_then(|value| <&str>::try_from(value).ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    Ok(windows
+        .values()
+        .map(|props| Toplevel {
+            app_id: string(props, "app-id"),
+            title: string(props, "title"),
+            activated: props
+                .get("has-focus")
+                .and_then(|value| bool::try_from(value).ok())
+                .unwrap_or(false),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_id_matches_process_name() {
+        assert!(app_id_matches("org.mozilla.firefox", "firefox-bin"));
+        assert!(app_id_matches("google-chrome", "chrome"));
+        assert!(app_id_matches("Slack", "slack"));
+        assert!(!app_id_matches("org.gnome.Nautilus", "zoom"));
+    }
+}
@@ -1,29 +1,43 @@
 // Windows platform utilities for process and window information
 
 use super::PlatformUtils;
+use crate::error::ValidatorError;
+use crate::process_cache::{self, PidCache};
 use windows::core::*;
 use windows::Win32::Foundation::*;
 use windows::Win32::System::Threading::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 
 // Implement PlatformUtils trait for Windows
 impl PlatformUtils for () {
-    fn get_process_name(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_process_name(pid: u32) -> std::result::Result<String, ValidatorError> {
         unsafe {
-            get_process_name_impl(pid).map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            get_process_name_impl(pid).map_err(ValidatorError::from)
         }
     }
 
-    fn get_window_title(pid: u32) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    fn get_window_title(pid: u32) -> std::result::Result<String, ValidatorError> {
         unsafe {
             Ok(get_window_title_impl(pid))
         }
     }
 }
 
-/// Get process name from process ID
+static PROCESS_NAMES: PidCache<String> = PidCache::new(process_cache::NAME_TTL, process_cache::CAPACITY);
+static WINDOW_TITLES: PidCache<String> = PidCache::new(process_cache::TITLE_TTL, process_cache::CAPACITY);
+
 unsafe fn get_process_name_impl(process_id: u32) -> Result<String> {
+    PROCESS_NAMES.get_or_try_insert_with(process_id, || query_process_name(process_id))
+}
+
+unsafe fn get_window_title_impl(target_pid: u32) -> String {
+    WINDOW_TITLES.get_or_insert_with(target_pid, || query_window_title(target_pid))
+}
+
+/// Get process name from process ID
+unsafe fn query_process_name(process_id: u32) -> Result<String> {
     let process_handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id)?;
 
     let mut buffer = vec![0u16; 260]; // MAX_PATH
@@ -53,13 +67,17 @@ unsafe fn get_process_name_impl(process_id: u32) -> Result<String> {
 
 /// Get window title for a given process ID
 /// For multi-process apps like browsers, finds any window from the same executable
-unsafe fn get_window_title_impl(target_pid: u32) -> String {
+unsafe fn query_window_title(target_pid: u32) -> String {
     // Store found window title in a static mutex
     static WINDOW_TITLE: Mutex<Option<String>> = Mutex::new(None);
     static PROCESS_NAME: Mutex<Option<String>> = Mutex::new(None);
 
+    // Set once a window of the process itself was found; it beats same-name windows
+    static OWN_WINDOW: AtomicBool = AtomicBool::new(false);
+
     // Reset state
     *WINDOW_TITLE.lock().unwrap() = None;
+    OWN_WINDOW.store(false, Ordering::SeqCst);
 
     // Get the process name for fallback searching
     let _target_process_name = if let Ok(name) = get_process_name_impl(target_pid) {
@@ -84,11 +102,23 @@ unsafe fn get_window_title_impl(target_pid: u32) -> String {
 
             if length > 0 {
                 let title = String::from_utf16_lossy(&buffer[..length as usize]);
-                if !title.trim().is_empty() {
-                    // Priority 1: Exact PID match
+                let name = PROCESS_NAME.lock().unwrap().clone().unwrap_or_default();
+                // Firefox's sharing indicator is topmost while the mic is shared, and names no page
+                let is_firefox_indicator = crate::firefox::is_firefox(&name) && crate::firefox::is_sharing_indicator(&title);
+                if !title.trim().is_empty() && !is_firefox_indicator {
+                    // Priority 1: Exact PID match; a messenger's call window (Signal opens it
+                    // next to the main window) beats its other windows
                     if window_pid == target_pid {
-                        *WINDOW_TITLE.lock().unwrap() = Some(title);
-                        return BOOL(0); // Stop enumeration
+                        if crate::messengers::profile_for(&name, None).is_none()
+                            || crate::messengers::title_shows_call(&name, &title)
+                        {
+                            *WINDOW_TITLE.lock().unwrap() = Some(title);
+                            return BOOL(0); // Stop enumeration
+                        }
+                        if !OWN_WINDOW.swap(true, Ordering::SeqCst) {
+                            *WINDOW_TITLE.lock().unwrap() = Some(title);
+                        }
+                        return BOOL(1);
                     }
 
                     // Priority 2: Same process name (for multi-process apps like browsers)
@@ -116,6 +146,82 @@ unsafe fn get_window_title_impl(target_pid: u32) -> String {
     WINDOW_TITLE.lock().unwrap().clone().unwrap_or_default()
 }
 
+const CURRENT_VERSION_KEY: &str = r"SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+
+/// OS version from RtlGetVersion and the CurrentVersion registry key,
+/// e.g. "Windows 11 Pro 23H2 (Build 10.0.22631.3155)"
+/// RtlGetVersion is not subject to the manifest-based version lie of GetVersionEx.
+unsafe fn get_os_version_impl() -> String {
+    use windows::Wdk::System::SystemServices::RtlGetVersion;
+    use windows::Win32::System::SystemInformation::OSVERSIONINFOW;
+
+    let mut info = OSVERSIONINFOW {
+        dwOSVersionInfoSize: std::mem::size_of::<OSVERSIONINFOW>() as u32,
+        ..Default::default()
+    };
+    if RtlGetVersion(&mut info).is_err() {
+        return "Windows (version unknown)".to_string();
+    }
+
+    // ProductName still says "Windows 10" on Windows 11; the build number tells them apart
+    let mut product = read_registry_string(CURRENT_VERSION_KEY, "ProductName").unwrap_or_else(|| "Windows".to_string());
+    if info.dwBuildNumber >= 22000 {
+        product = product.replace("Windows 10", "Windows 11");
+    }
+
+    let mut version = product;
+    if let Some(display_version) = read_registry_string(CURRENT_VERSION_KEY, "DisplayVersion") {
+        version = format!("{} {}", version, display_version);
+    }
+
+    let mut build = format!("{}.{}.{}", info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber);
+    if let Some(ubr) = read_registry_dword(CURRENT_VERSION_KEY, "UBR") {
+        build = format!("{}.{}", build, ubr);
+    }
+
+    format!("{} (Build {})", version, build)
+}
+
+unsafe fn read_registry_string(key: &str, value: &str) -> Option<String> {
+    use windows::Win32::System::Registry::*;
+
+    let mut buffer = [0u16; 256];
+    let mut size = (buffer.len() * 2) as u32;
+    let status = RegGetValueW(
+        HKEY_LOCAL_MACHINE,
+        &HSTRING::from(key),
+        &HSTRING::from(value),
+        RRF_RT_REG_SZ,
+        None,
+        Some(buffer.as_mut_ptr() as *mut _),
+        Some(&mut size),
+    );
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+
+    // size includes the terminating NUL
+    let len = (size as usize / 2).saturating_sub(1);
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+unsafe fn read_registry_dword(key: &str, value: &str) -> Option<u32> {
+    use windows::Win32::System::Registry::*;
+
+    let mut data = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = RegGetValueW(
+        HKEY_LOCAL_MACHINE,
+        &HSTRING::from(key),
+        &HSTRING::from(value),
+        RRF_RT_REG_DWORD,
+        None,
+        Some(&mut data as *mut u32 as *mut _),
+        Some(&mut size),
+    );
+    (status == ERROR_SUCCESS).then_some(data)
+}
+
 // Public convenience functions
 pub fn get_process_name(pid: u32) -> Result<String> {
     unsafe { get_process_name_impl(pid) }
@@ -124,3 +230,7 @@ pub fn get_process_name(pid: u32) -> Result<String> {
 pub fn get_window_title(pid: u32) -> String {
     unsafe { get_window_title_impl(pid) }
 }
+
+pub fn get_os_version() -> String {
+    unsafe { get_os_version_impl() }
+}
@@ -0,0 +1,129 @@
+// Power source detection
+// On battery, or with Windows battery saver / macOS Low Power Mode / a Linux low-power
+// platform profile on, the monitor saves power while no call is active: polling slows to
+// the idle pace and the expensive probes (socket scans, Zoom window enumeration) are
+// skipped (see scheduler.rs). Once a call is suspected everything runs as usual.
+// The profile is read every 30 seconds and reported by the `health` control command.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How often the power source is re-read
+const READ_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    /// No battery reported (desktops, VMs) or the platform could not tell
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PowerProfile {
+    pub source: PowerSource,
+    /// Battery saver, Low Power Mode or a low-power platform profile is on
+    pub low_power: bool,
+}
+
+impl PowerProfile {
+    /// Whether the monitor should save power outside calls
+    pub fn is_saving(&self) -> bool {
+        self.source == PowerSource::Battery || self.low_power
+    }
+}
+
+/// The power profile, re-read every READ_INTERVAL
+#[derive(Debug, Default)]
+pub struct PowerMonitor {
+    last: Option<(Instant, PowerProfile)>,
+}
+
+impl PowerMonitor {
+    pub fn profile(&mut self) -> PowerProfile {
+        match self.last {
+            Some((at, profile)) if at.elapsed() < READ_INTERVAL => profile,
+            _ => {
+                let profile = read_profile();
+                self.last = Some((Instant::now(), profile));
+                profile
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_profile() -> PowerProfile {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    if unsafe { GetSystemPowerStatus(&mut status) }.is_err() {
+        return PowerProfile::default();
+    }
+
+    // ACLineStatus: 0 offline, 1 online, 255 unknown; SystemStatusFlag 1: battery saver on
+    let source = match status.ACLineStatus {
+        0 => PowerSource::Battery,
+        1 => PowerSource::Ac,
+        _ => PowerSource::Unknown,
+    };
+    PowerProfile { source, low_power: status.SystemStatusFlag == 1 }
+}
+
+#[cfg(target_os = "linux")]
+fn read_profile() -> PowerProfile {
+    use std::fs;
+
+    let read = |path: std::path::PathBuf| fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default();
+
+    let mut has_battery = false;
+    let mut on_mains = false;
+    if let Ok(supplies) = fs::read_dir("/sys/class/power_supply") {
+        for supply in supplies.flatten() {
+            match read(supply.path().join("type")).as_str() {
+                "Mains" | "USB" => on_mains |= read(supply.path().join("online")) == "1",
+                "Battery" => has_battery |= read(supply.path().join("scope")) != "Device",
+                _ => {}
+            }
+        }
+    }
+
+    let source = match (has_battery, on_mains) {
+        (_, true) => PowerSource::Ac,
+        (true, false) => PowerSource::Battery,
+        (false, false) => PowerSource::Unknown,
+    };
+    PowerProfile { source, low_power: read("/sys/firmware/acpi/platform_profile".into()) == "low-power" }
+}
+
+#[cfg(target_os = "macos")]
+fn read_profile() -> PowerProfile {
+    use std::process::Command;
+
+    let pmset = |args: &[&str]| {
+        crate::subprocess::output(Command::new("pmset").args(args))
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    };
+
+    // "Now drawing from 'Battery Power'"
+    let batt = pmset(&["-g", "batt"]);
+    let source = if batt.contains("'AC Power'") {
+        PowerSource::Ac
+    } else if batt.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else {
+        PowerSource::Unknown
+    };
+    let low_power = pmset(&["-g"])
+        .lines()
+        .any(|line| line.split_whitespace().collect::<Vec<_>>() == ["lowpowermode", "1"]);
+    PowerProfile { source, low_power }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn read_profile() -> PowerProfile {
+    PowerProfile::default()
+}
@@ -0,0 +1,160 @@
+// Private/incognito browser window policy and privacy mode output
+// Gives deployments a concrete control for personal-use boundaries on work machines:
+// private windows can be excluded from monitoring entirely or tagged in the output, and
+// privacy mode (`privacy` control command) strips everything but the call state.
+
+use crate::call_tracker::TrackedApps;
+use crate::companion::ExternalCall;
+use crate::events::{CallEndedPayload, ConfidenceChangedPayload, MonitorEvent};
+use crate::{CallInfo, MonitorState};
+
+/// What to do with audio coming from a private browser window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateWindowPolicy {
+    /// Treat private windows like any other window (default)
+    Ignore,
+    /// Drop private windows before detection; they never appear in any output
+    Exclude,
+    /// Keep monitoring but mark sources and calls with `private_context: true`
+    Tag,
+}
+
+impl PrivateWindowPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "ignore" | "off" => Some(PrivateWindowPolicy::Ignore),
+            "exclude" => Some(PrivateWindowPolicy::Exclude),
+            "tag" => Some(PrivateWindowPolicy::Tag),
+            _ => None,
+        }
+    }
+}
+
+/// Title markers each browser adds to private windows (lowercase)
+const PRIVATE_TITLE_MARKERS: &[(&str, &[&str])] = &[
+    ("chrome", &["(incognito)", "incognito"]),
+    ("msedge", &["inprivate"]),
+    ("edge", &["inprivate"]),
+    ("firefox", &["private browsing"]),
+    ("brave", &["(private)", "private window", "private browsing"]),
+    ("opera", &["(private)", "private browsing"]),
+    ("vivaldi", &["(private)", "private browsing"]),
+    ("safari", &["private browsing"]),
+];
+
+/// Check whether a window belongs to a private/incognito browsing session
+pub fn is_private_window(process_name: &str, window_title: &str) -> bool {
+    let process = process_name.to_lowercase();
+    let title = window_title.to_lowercase();
+
+    PRIVATE_TITLE_MARKERS
+        .iter()
+        .filter(|(browser, _)| process.contains(browser))
+        .any(|(_, markers)| markers.iter().any(|marker| title.contains(marker)))
+}
+
+/// `state` as privacy mode reports it: whether a call is active and its timing, without
+/// the app, process, window title or other audio sources
+pub fn call_state_only(state: &MonitorState) -> MonitorState {
+    MonitorState {
+        active_call: state.active_call.as_ref().map(anonymous_call),
+        other_audio_sources: Vec::new(),
+        environment: state.environment,
+        // The restriction stays visible; the counts of other apps' audio do not
+        tracked_apps: state.tracked_apps.as_ref().map(|tracked| TrackedApps { apps: tracked.apps.clone(), ..Default::default() }),
+        // That the user is on a call on the phone, not which device, app or meeting
+        external_calls: state.external_calls.iter().map(anonymous_external_call).collect(),
+    }
+}
+
+/// `events` as privacy mode reports them; source changes are dropped
+pub fn call_events_only(events: &[MonitorEvent]) -> Vec<MonitorEvent> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            MonitorEvent::CallStarted(call) => Some(MonitorEvent::CallStarted(anonymous_call(call))),
+            MonitorEvent::CallEnded(ended) => Some(MonitorEvent::CallEnded(CallEndedPayload {
+                app: String::new(),
+                process_id: 0,
+                window_title: String::new(),
+                mute_timeline: Vec::new(),
+                quality: None,
+                ..ended.clone()
+            })),
+            MonitorEvent::ConfidenceChanged(changed) => Some(MonitorEvent::ConfidenceChanged(ConfidenceChangedPayload {
+                app: String::new(),
+                process_id: 0,
+                ..changed.clone()
+            })),
+            // Markers are derived from what the call plays
+            MonitorEvent::SourceAdded(_)
+            | MonitorEvent::SourceRemoved(_)
+            | MonitorEvent::ConfigReloaded(_)
+            | MonitorEvent::MediaPlaybackDuringCall(_)
+            | MonitorEvent::AudioMarker(_) => None,
+        })
+        .collect()
+}
+
+fn anonymous_external_call(call: &ExternalCall) -> ExternalCall {
+    ExternalCall {
+        device: String::new(),
+        app: None,
+        meeting_identifier: None,
+        ..call.clone()
+    }
+}
+
+fn anonymous_call(call: &CallInfo) -> CallInfo {
+    CallInfo {
+        app: String::new(),
+        process_id: 0,
+        window_title: String::new(),
+        in_app_muted: None,
+        meeting_is_recorded: None,
+        controls: None,
+        estimated_participants: None,
+        meeting_identifier: None,
+        scheduled_meeting: None,
+        quality: None,
+        echo_risk: false,
+        role: Default::default(),
+        ..call.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::diff_states;
+    use crate::AudioSource;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_privacy_mode_keeps_only_call_state() {
+        let source = AudioSource {
+            name: "chrome".to_string(),
+            process_id: 7,
+            window_title: "1:1 with Jane Doe - Google Chrome".to_string(),
+            detected_app: None,
+            private_context: false,
+            is_virtual_device: false,
+            classification: None,
+            session: None,
+        };
+        let call =
+            CallInfo { window_title: "Zoom Meeting - Salary review".to_string(), ..crate::test_call("Zoom", 10) };
+        let idle = MonitorState { active_call: None, other_audio_sources: Vec::new(), environment: Default::default(), tracked_apps: None, external_calls: Vec::new() };
+        let in_call = MonitorState { active_call: Some(call), other_audio_sources: vec![source], environment: Default::default(), tracked_apps: None, external_calls: Vec::new() };
+
+        let state = call_state_only(&in_call);
+        let reported = state.active_call.expect("call state is kept");
+        assert!(reported.app.is_empty() && reported.window_title.is_empty() && reported.process_id == 0);
+        assert!(reported.has_mic && state.other_audio_sources.is_empty());
+
+        let events = call_events_only(&diff_states(&idle, &in_call, SystemTime::now()));
+        assert_eq!(events.len(), 1);
+        let line = events[0].to_json_line().unwrap();
+        assert!(!line.contains("Salary") && !line.contains("Zoom") && !line.contains("Jane"));
+    }
+}
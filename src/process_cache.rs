@@ -0,0 +1,169 @@
+// Per-PID cache for process names and window titles
+// Window title lookups enumerate every top-level window (and on Windows open each
+// window's process), once per audio session per poll. Caching by PID with a short TTL
+// keeps that cost flat as the number of open windows grows. Entries are dropped as soon
+// as their process exits so a reused PID never inherits a stale name.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A live PID keeps its executable name; the TTL only bounds memory
+pub const NAME_TTL: Duration = Duration::from_secs(30);
+/// Titles follow tab switches and meeting renames within a few polls
+pub const TITLE_TTL: Duration = Duration::from_secs(2);
+/// PIDs kept per cache
+pub const CAPACITY: usize = 256;
+
+struct Entry<V> {
+    value: V,
+    inserted: Instant,
+    last_used: Instant,
+}
+
+/// TTL + LRU cache keyed by PID, usable as a `static`
+pub struct PidCache<V> {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<Option<HashMap<u32, Entry<V>>>>,
+}
+
+impl<V: Clone> PidCache<V> {
+    pub const fn new(ttl: Duration, capacity: usize) -> Self {
+        PidCache {
+            ttl,
+            capacity,
+            entries: Mutex::new(None),
+        }
+    }
+
+    /// Forget every entry, also after a panic poisoned the cache
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))] // only the Windows audio backend restarts its caches
+    pub fn clear(&self) {
+        self.entries.clear_poison();
+        *self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+
+    /// Cached value for `pid`, or the result of `lookup` (cached only on success)
+    pub fn get_or_try_insert_with<E>(&self, pid: u32, lookup: impl FnOnce() -> Result<V, E>) -> Result<V, E> {
+        if let Some(value) = self.get(pid) {
+            return Ok(value);
+        }
+
+        let value = lookup()?;
+        self.insert(pid, value.clone());
+        Ok(value)
+    }
+
+    /// Cached value for `pid`, or the result of `lookup`
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))] // infallible lookups are Windows-only
+    pub fn get_or_insert_with(&self, pid: u32, lookup: impl FnOnce() -> V) -> V {
+        match self.get_or_try_insert_with(pid, || Ok::<V, Infallible>(lookup())) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    fn get(&self, pid: u32) -> Option<V> {
+        let mut guard = self.entries.lock().unwrap();
+        let entries = guard.get_or_insert_with(HashMap::new);
+        let now = Instant::now();
+
+        let fresh = match entries.get(&pid) {
+            Some(entry) => now.duration_since(entry.inserted) < self.ttl && process_alive(pid),
+            None => return None,
+        };
+        if !fresh {
+            entries.remove(&pid);
+            return None;
+        }
+
+        let entry = entries.get_mut(&pid)?;
+        entry.last_used = now;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&self, pid: u32, value: V) {
+        let mut guard = self.entries.lock().unwrap();
+        let entries = guard.get_or_insert_with(HashMap::new);
+        let now = Instant::now();
+
+        entries.retain(|_, entry| now.duration_since(entry.inserted) < self.ttl);
+        if entries.len() >= self.capacity {
+            // Evict the least recently used entry
+            if let Some(&oldest) = entries.iter().min_by_key(|(_, entry)| entry.last_used).map(|(pid, _)| pid) {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(
+            pid,
+            Entry {
+                value,
+                inserted: now,
+                last_used: now,
+            },
+        );
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn process_alive(pid: u32) -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::*;
+
+    // STILL_ACTIVE: exit code reported while the process is running
+    const STILL_ACTIVE: u32 = 259;
+
+    unsafe {
+        let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return false;
+        };
+        let mut exit_code = 0u32;
+        let running = GetExitCodeProcess(handle, &mut exit_code).is_ok() && exit_code == STILL_ACTIVE;
+        let _ = CloseHandle(handle);
+        running
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_alive(pid: u32) -> bool {
+    std::path::Path::new(&format!("/proc/{}", pid)).exists()
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the pid exists; EPERM still means it is running
+    unsafe {
+        libc::kill(pid as libc::pid_t, 0) == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_with_ttl_and_process_exit() {
+        let cache: PidCache<String> = PidCache::new(Duration::from_secs(60), 2);
+        let pid = std::process::id();
+
+        assert_eq!(cache.get_or_insert_with(pid, || "first".to_string()), "first");
+        assert_eq!(cache.get_or_insert_with(pid, || "second".to_string()), "first");
+        assert!(cache.get_or_try_insert_with(pid + 1, || Err::<String, _>("gone")).is_err());
+
+        // A PID that does not exist is never served from the cache
+        cache.get_or_insert_with(u32::MAX, || "dead".to_string());
+        assert_eq!(cache.get_or_insert_with(u32::MAX, || "fresh".to_string()), "fresh");
+
+        let expired: PidCache<String> = PidCache::new(Duration::ZERO, 2);
+        expired.get_or_insert_with(pid, || "old".to_string());
+        assert_eq!(expired.get_or_insert_with(pid, || "new".to_string()), "new");
+    }
+}
@@ -0,0 +1,89 @@
+// Per-deployment process allowlist/denylist (`ignore_processes` / `force_track_processes`)
+// Entries are case-insensitive globs (`*` and `?`). A plain string matches the process
+// name; an object can match the window title too, and every glob it gives must match:
+//
+//   "ignore_processes": ["obs*", { "window_title": "*virtual mic*" }],
+//   "force_track_processes": ["3CXPhone*", { "name": "RingCentral*", "window_title": "*call*" }]
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ProcessPattern {
+    /// Glob on the process name
+    Name(String),
+    /// Globs on the process name and/or window title
+    Match {
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        window_title: Option<String>,
+    },
+}
+
+impl ProcessPattern {
+    pub fn matches(&self, process_name: &str, window_title: &str) -> bool {
+        match self {
+            ProcessPattern::Name(name) => glob_match(name, process_name),
+            // An empty pattern would match every process
+            ProcessPattern::Match { name: None, window_title: None } => false,
+            ProcessPattern::Match { name, window_title: title } => {
+                name.as_deref().map_or(true, |glob| glob_match(glob, process_name))
+                    && title.as_deref().map_or(true, |glob| glob_match(glob, window_title))
+            }
+        }
+    }
+}
+
+/// Whether any pattern in `patterns` matches
+pub fn any_matches(patterns: &[ProcessPattern], process_name: &str, window_title: &str) -> bool {
+    patterns.iter().any(|pattern| pattern.matches(process_name, window_title))
+}
+
+/// Case-insensitive glob match of the whole `text`
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Iterative matcher; backtracks to the last `*` on a mismatch
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_match_name_and_title_globs() {
+        let patterns: Vec<ProcessPattern> = serde_json::from_str(
+            r#"["3CX*", { "name": "obs?4*", "window_title": "*virtual*" }, {}]"#,
+        )
+        .unwrap();
+
+        assert!(patterns[0].matches("3CXPhone.exe", ""));
+        assert!(patterns[0].matches("3cxphone", "anything"));
+        assert!(!patterns[0].matches("Zoom.exe", "3CX"));
+        assert!(patterns[1].matches("OBS64.exe", "OBS Virtual Camera"));
+        assert!(!patterns[1].matches("OBS64.exe", "Studio"));
+        assert!(!patterns[2].matches("zoom.us", "Zoom Meeting"));
+        assert!(glob_match("*a*b", "xxaxxb"));
+        assert!(!glob_match("*a*b", "xxaxxbc"));
+    }
+}
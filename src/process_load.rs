@@ -0,0 +1,274 @@
+// CPU and GPU load of call apps
+// A video call keeps the call process busy encoding and decoding, while an idle client
+// window barely runs. When the call's audio goes quiet for a moment and the socket scan
+// misses a tick, that load is what still tells a meeting from an app left open:
+//   - CPU: process times between two probes, in percent of one core (/proc/<pid>/stat on
+//     Linux, GetProcessTimes on Windows, proc_pidinfo on macOS, kern.proc.pid on FreeBSD)
+//   - GPU: busiest engine of the process from the "GPU Engine" performance counters
+//     (Windows only; the video encoder shows up there on hardware-encoding clients)
+// Only the active call or the call candidates are probed, and a process has a reading
+// from its second probe on.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+/// At most this many processes are probed per tick
+const MAX_PROBED: usize = 8;
+
+/// Load of one root process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProcessLoad {
+    /// CPU time since the previous probe, in percent of one core
+    pub cpu_percent: f32,
+    /// Utilization of its busiest GPU engine, where the platform reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_percent: Option<f32>,
+}
+
+/// CPU times of the processes probed on the previous tick
+#[derive(Default)]
+pub struct LoadProbe {
+    previous: HashMap<u32, (Instant, Duration)>,
+    #[cfg(target_os = "windows")]
+    gpu: Option<gpu::GpuCounters>,
+}
+
+impl LoadProbe {
+    /// Load of each of `process_ids` (root processes) since the previous probe
+    pub fn probe(&mut self, process_ids: impl IntoIterator<Item = u32>) -> BTreeMap<u32, ProcessLoad> {
+        let wanted: Vec<u32> = process_ids.into_iter().take(MAX_PROBED).collect();
+        self.previous.retain(|process_id, _| wanted.contains(process_id));
+        if wanted.is_empty() {
+            return BTreeMap::new();
+        }
+
+        #[cfg(target_os = "windows")]
+        let gpu = self.gpu.get_or_insert_with(gpu::GpuCounters::open).utilization();
+        #[cfg(not(target_os = "windows"))]
+        let gpu: HashMap<u32, f32> = HashMap::new();
+
+        let now = Instant::now();
+        let mut loads = BTreeMap::new();
+        for process_id in wanted {
+            let Some(cpu_time) = platform::cpu_time(process_id) else {
+                self.previous.remove(&process_id);
+                continue;
+            };
+            if let Some((at, previous)) = self.previous.insert(process_id, (now, cpu_time)) {
+                let elapsed = now.duration_since(at).as_secs_f32();
+                if elapsed > 0.0 {
+                    let busy = cpu_time.saturating_sub(previous).as_secs_f32();
+                    loads.insert(
+                        process_id,
+                        ProcessLoad { cpu_percent: busy / elapsed * 100.0, gpu_percent: gpu.get(&process_id).copied() },
+                    );
+                }
+            }
+        }
+        loads
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::time::Duration;
+
+    /// User and system time of `pid`
+    pub fn cpu_time(pid: u32) -> Option<Duration> {
+        let stat = procfs::process::Process::new(pid as i32).ok()?.stat().ok()?;
+        let ticks = stat.utime + stat.stime;
+        Some(Duration::from_secs_f64(ticks as f64 / procfs::ticks_per_second() as f64))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::time::Duration;
+    use windows::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows::Win32::System::Threading::{GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    /// User and kernel time of `pid`
+    pub fn cpu_time(pid: u32) -> Option<Duration> {
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let (mut created, mut exited, mut kernel, mut user) =
+                (FILETIME::default(), FILETIME::default(), FILETIME::default(), FILETIME::default());
+            let result = GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user);
+            let _ = CloseHandle(handle);
+            result.ok()?;
+            // FILETIME counts 100 ns intervals
+            let hundred_ns = |time: FILETIME| ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+            Some(Duration::from_nanos((hundred_ns(kernel) + hundred_ns(user)) * 100))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+
+    extern "C" {
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> libc::c_int;
+    }
+
+    /// Task times are in Mach absolute time units, which are not nanoseconds on Apple silicon
+    fn nanos_per_tick() -> f64 {
+        static RATIO: OnceLock<f64> = OnceLock::new();
+        *RATIO.get_or_init(|| {
+            let mut info = MachTimebaseInfo::default();
+            if unsafe { mach_timebase_info(&mut info) } != 0 || info.denom == 0 {
+                return 1.0;
+            }
+            info.numer as f64 / info.denom as f64
+        })
+    }
+
+    /// User and system time of `pid`
+    pub fn cpu_time(pid: u32) -> Option<Duration> {
+        unsafe {
+            let mut info: libc::proc_taskinfo = std::mem::zeroed();
+            let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+            let read = libc::proc_pidinfo(
+                pid as libc::c_int,
+                libc::PROC_PIDTASKINFO,
+                0,
+                &mut info as *mut _ as *mut libc::c_void,
+                size,
+            );
+            if read != size {
+                return None;
+            }
+            let ticks = info.pti_total_user + info.pti_total_system;
+            Some(Duration::from_nanos((ticks as f64 * nanos_per_tick()) as u64))
+        }
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+mod platform {
+    use std::time::Duration;
+
+    /// Run time of `pid`
+    pub fn cpu_time(pid: u32) -> Option<Duration> {
+        crate::platform::freebsd::cpu_time(pid)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos", target_os = "freebsd")))]
+mod platform {
+    use std::time::Duration;
+
+    pub fn cpu_time(_pid: u32) -> Option<Duration> {
+        None
+    }
+}
+
+/// Per-process GPU engine utilization from PDH
+#[cfg(target_os = "windows")]
+mod gpu {
+    use std::collections::HashMap;
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::System::Performance::*;
+
+    /// An open "GPU Engine" query; utilization is measured between two collections
+    pub struct GpuCounters {
+        query: Option<(PDH_HQUERY, PDH_HCOUNTER)>,
+    }
+
+    impl GpuCounters {
+        /// Machines without a WDDM 2.x driver have no such counters; the query then stays closed
+        pub fn open() -> Self {
+            unsafe {
+                let mut query = PDH_HQUERY::default();
+                if PdhOpenQueryW(PCWSTR::null(), 0, &mut query) != 0 {
+                    return GpuCounters { query: None };
+                }
+                let mut counter = PDH_HCOUNTER::default();
+                if PdhAddEnglishCounterW(query, w!("\\GPU Engine(*)\\Utilization Percentage"), 0, &mut counter) != 0 {
+                    let _ = PdhCloseQuery(query);
+                    return GpuCounters { query: None };
+                }
+                let _ = PdhCollectQueryData(query);
+                GpuCounters { query: Some((query, counter)) }
+            }
+        }
+
+        /// Busiest engine of each process since the previous call
+        pub fn utilization(&mut self) -> HashMap<u32, f32> {
+            let mut busiest = HashMap::new();
+            let Some((query, counter)) = self.query else { return busiest };
+            unsafe {
+                if PdhCollectQueryData(query) != 0 {
+                    return busiest;
+                }
+                let (mut size, mut count) = (0u32, 0u32);
+                let _ = PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, None);
+                if size == 0 {
+                    return busiest;
+                }
+                // Item structs followed by the instance names they point into
+                let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+                let items = buffer.as_mut_ptr() as *mut PDH_FMT_COUNTERVALUE_ITEM_W;
+                if PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, Some(items)) != 0 {
+                    return busiest;
+                }
+                for item in std::slice::from_raw_parts(items, count as usize) {
+                    let Some(pid) = item.szName.to_string().ok().and_then(|name| instance_pid(&name)) else { continue };
+                    let value = item.FmtValue.Anonymous.doubleValue as f32;
+                    let engine = busiest.entry(pid).or_insert(0.0f32);
+                    *engine = engine.max(value);
+                }
+            }
+            busiest
+        }
+    }
+
+    impl Drop for GpuCounters {
+        fn drop(&mut self) {
+            if let Some((query, _)) = self.query.take() {
+                unsafe {
+                    let _ = PdhCloseQuery(query);
+                }
+            }
+        }
+    }
+
+    /// Pid of an instance such as "pid_1234_luid_0x00000000_0x0000D1B2_phys_0_eng_3_engtype_VideoEncode"
+    pub(super) fn instance_pid(name: &str) -> Option<u32> {
+        name.strip_prefix("pid_")?.split('_').next()?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_needs_two_probes_of_a_live_process() {
+        let mut probe = LoadProbe::default();
+        let me = std::process::id();
+
+        assert!(probe.probe([me]).is_empty());
+        // Burn a little CPU so the second probe has something to measure
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(20) {
+            std::hint::black_box(start.elapsed());
+        }
+        let loads = probe.probe([me, u32::MAX]);
+        #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos", target_os = "freebsd"))]
+        assert!(loads.get(&me).is_some_and(|load| load.cpu_percent >= 0.0));
+        assert!(!loads.contains_key(&u32::MAX));
+
+        #[cfg(target_os = "windows")]
+        assert_eq!(gpu::instance_pid("pid_1234_luid_0x00000000_0x0000D1B2_phys_0_eng_3_engtype_VideoEncode"), Some(1234));
+    }
+}
@@ -0,0 +1,217 @@
+// Process tree resolution for multi-process apps
+// Browsers and Electron apps (Teams, Slack, Discord) play audio and open sockets from
+// helper processes while the top-level process owns the window. Resolving every PID to
+// its root application process lets audio sessions, window titles and WebRTC sockets
+// from different helpers line up on one PID.
+
+use std::collections::HashMap;
+
+/// Helper processes that belong to whichever app launched them, whatever their name
+/// (WebView2 hosts; Firefox's own helpers are in firefox.rs)
+const EMBEDDED_HELPERS: &[&str] = &["msedgewebview2"];
+
+/// Deepest helper nesting we follow (Chrome: browser -> zygote -> zygote -> renderer)
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ProcessEntry {
+    pub parent_pid: u32,
+    pub name: String,
+}
+
+/// Parent/name lookups for one tick
+/// On Windows this is a single Toolhelp snapshot; elsewhere entries are read on demand.
+pub struct ProcessTree {
+    entries: HashMap<u32, Option<ProcessEntry>>,
+}
+
+impl ProcessTree {
+    pub fn snapshot() -> Self {
+        ProcessTree { entries: platform_snapshot() }
+    }
+
+    /// Parent pid and executable name of `pid`
+    pub fn entry(&mut self, pid: u32) -> Option<&ProcessEntry> {
+        self.entries.entry(pid).or_insert_with(|| platform_entry(pid)).as_ref()
+    }
+
+    /// Every process in the snapshot
+    #[cfg(target_os = "windows")]
+    pub fn pids(&self) -> Vec<u32> {
+        self.entries.keys().copied().collect()
+    }
+
+    /// The top-most ancestor that is still part of the same application
+    pub fn root(&mut self, pid: u32) -> u32 {
+        let mut current = pid;
+
+        for _ in 0..MAX_DEPTH {
+            let Some(entry) = self.entry(current).cloned() else { break };
+            if entry.parent_pid == 0 || entry.parent_pid == current {
+                break;
+            }
+            let Some(parent) = self.entry(entry.parent_pid).cloned() else { break };
+            if !same_app(&entry.name, &parent.name) {
+                break;
+            }
+            current = entry.parent_pid;
+        }
+
+        current
+    }
+}
+
+/// Whether `child` is a helper of `parent`: same executable ("chrome.exe" under
+/// "chrome.exe"), a named helper ("Slack Helper (Renderer)" under "Slack"), an embedded
+/// runtime, one of Firefox's processes, or a WebKit service under its responsible app
+fn same_app(child: &str, parent: &str) -> bool {
+    if crate::firefox::same_app(child, parent) || crate::safari::same_app(child, parent) {
+        return true;
+    }
+    let child = normalize(child);
+    let parent = normalize(parent);
+
+    child == parent
+        || (parent.len() >= 3 && child.starts_with(&format!("{} helper", parent)))
+        || EMBEDDED_HELPERS.iter().any(|helper| child == *helper)
+}
+
+fn normalize(name: &str) -> String {
+    let name = name.to_lowercase();
+    name.strip_suffix(".exe").unwrap_or(&name).to_string()
+}
+
+#[cfg(target_os = "windows")]
+fn platform_snapshot() -> HashMap<u32, Option<ProcessEntry>> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::*;
+
+    let mut entries = HashMap::new();
+
+    unsafe {
+        let snapshot = match CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
+            Ok(snapshot) => snapshot,
+            Err(_) => return entries,
+        };
+
+        let mut process = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+
+        let mut found = Process32FirstW(snapshot, &mut process).is_ok();
+        while found {
+            let len = process.szExeFile.iter().position(|&c| c == 0).unwrap_or(process.szExeFile.len());
+            entries.insert(
+                process.th32ProcessID,
+                Some(ProcessEntry {
+                    parent_pid: process.th32ParentProcessID,
+                    name: String::from_utf16_lossy(&process.szExeFile[..len]),
+                }),
+            );
+            found = Process32NextW(snapshot, &mut process).is_ok();
+        }
+
+        let _ = CloseHandle(snapshot);
+    }
+
+    entries
+}
+
+/// Not in the snapshot means it exited (or started) since
+#[cfg(target_os = "windows")]
+fn platform_entry(_pid: u32) -> Option<ProcessEntry> {
+    None
+}
+
+#[cfg(not(target_os = "windows"))]
+fn platform_snapshot() -> HashMap<u32, Option<ProcessEntry>> {
+    HashMap::new()
+}
+
+#[cfg(target_os = "linux")]
+fn platform_entry(pid: u32) -> Option<ProcessEntry> {
+    let stat = procfs::process::Process::new(pid as i32).ok()?.stat().ok()?;
+    Some(ProcessEntry {
+        parent_pid: stat.ppid.max(0) as u32,
+        name: crate::sandbox::resolve_name(pid, stat.comm),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn platform_entry(pid: u32) -> Option<ProcessEntry> {
+    use std::ffi::CStr;
+
+    unsafe {
+        let mut info: libc::proc_bsdinfo = std::mem::zeroed();
+        let size = std::mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+        let written = libc::proc_pidinfo(
+            pid as libc::c_int,
+            libc::PROC_PIDTBSDINFO,
+            0,
+            &mut info as *mut _ as *mut libc::c_void,
+            size,
+        );
+        if written != size {
+            return None;
+        }
+
+        // pbi_name is the longer bundle-derived name; pbi_comm is truncated to 16 bytes
+        let name = CStr::from_ptr(info.pbi_name.as_ptr()).to_string_lossy().to_string();
+        let name = if name.is_empty() {
+            CStr::from_ptr(info.pbi_comm.as_ptr()).to_string_lossy().to_string()
+        } else {
+            name
+        };
+
+        // WebKit services are launchd's children; the app they work for is the responsible one
+        let parent_pid = if crate::safari::is_webkit_helper(&name) {
+            crate::safari::responsible_pid(pid).unwrap_or(info.pbi_ppid)
+        } else {
+            info.pbi_ppid
+        };
+
+        Some(ProcessEntry { parent_pid, name })
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+fn platform_entry(pid: u32) -> Option<ProcessEntry> {
+    let (parent_pid, name) = crate::platform::freebsd::process_info(pid)?;
+    Some(ProcessEntry { parent_pid, name })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+fn platform_entry(_pid: u32) -> Option<ProcessEntry> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_stops_at_a_different_app() {
+        let entry = |parent_pid: u32, name: &str| {
+            Some(ProcessEntry {
+                parent_pid,
+                name: name.to_string(),
+            })
+        };
+        let mut tree = ProcessTree {
+            entries: HashMap::from([
+                (1, entry(0, "explorer.exe")),
+                (10, entry(1, "ms-teams.exe")),
+                (11, entry(10, "msedgewebview2.exe")),
+                (12, entry(11, "msedgewebview2.exe")),
+                (20, entry(1, "Slack")),
+                (21, entry(20, "Slack Helper (Renderer)")),
+            ]),
+        };
+
+        assert_eq!(tree.root(12), 10);
+        assert_eq!(tree.root(21), 20);
+        assert_eq!(tree.root(10), 10);
+        assert_eq!(tree.root(11), 10);
+    }
+}
@@ -0,0 +1,63 @@
+// Per-cycle self profiling
+// Each poll cycle records how long every subsystem took. `--self-profile` prints that to
+// stderr once per cycle:
+//   [profile] process_tree 1.8ms audio 0.9ms mic 0.3ms network 4.2ms track 0.1ms total 7.3ms
+// and the `bench` subcommand (bench.rs) aggregates it over many cycles.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Time spent per subsystem in one poll cycle, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct CycleProfile {
+    spans: Vec<(&'static str, Duration)>,
+}
+
+impl CycleProfile {
+    /// Run `f`, adding its time to `name`
+    pub fn time<T>(&mut self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.add(name, start.elapsed());
+        result
+    }
+
+    pub fn add(&mut self, name: &'static str, elapsed: Duration) {
+        match self.spans.iter_mut().find(|(span, _)| *span == name) {
+            Some((_, total)) => *total += elapsed,
+            None => self.spans.push((name, elapsed)),
+        }
+    }
+
+    pub fn extend(&mut self, other: &CycleProfile) {
+        for &(name, elapsed) in &other.spans {
+            self.add(name, elapsed);
+        }
+    }
+
+    pub fn spans(&self) -> &[(&'static str, Duration)] {
+        &self.spans
+    }
+
+    pub fn total(&self) -> Duration {
+        self.spans.iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.spans.clear();
+    }
+}
+
+impl fmt::Display for CycleProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, elapsed) in &self.spans {
+            write!(f, "{} {} ", name, format_ms(*elapsed))?;
+        }
+        write!(f, "total {}", format_ms(self.total()))
+    }
+}
+
+/// "4.2ms"
+pub fn format_ms(elapsed: Duration) -> String {
+    format!("{:.1}ms", elapsed.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,193 @@
+// Meeting recording indicators
+// Tells whether the active meeting is being recorded, including by another participant:
+//   - window or tab titles that announce it ("● REC", "This meeting is being recorded")
+//   - Zoom's recording encoder (zTscoder) running while a local recording is written
+//   - Teams' recording banner, read through UI Automation (Windows)
+// `meeting_is_recorded` on CallInfo is Some(true) once any of these shows a recording,
+// Some(false) when Teams could be checked and shows none, None otherwise. The app probes
+// are slow (UI Automation walks the whole Teams window) so they run every few seconds.
+
+use crate::call_tracker::Sample;
+use crate::CallInfo;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Lowercase phrases of recording banners and titles (Teams, Zoom, Meet, Webex)
+const RECORDING_HINTS: &[&str] = &[
+    "is being recorded",
+    "recording has started",
+    "recording started",
+    "started recording",
+    "recording and transcription",
+    "recording in progress",
+    "this meeting is recorded",
+    "● rec",
+    "🔴 rec",
+    "(recording)",
+    "[recording]",
+];
+
+/// Zoom's local recording encoder
+const ZOOM_RECORDER: &str = "ztscoder";
+
+/// How often the app probes run
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What a probe found for one call
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecordingIndicator {
+    pub process_id: u32,
+    pub is_recorded: bool,
+}
+
+/// Whether a title or banner text announces a recording
+pub fn says_recording(text: &str) -> bool {
+    let text = text.to_lowercase();
+    RECORDING_HINTS.iter().any(|hint| text.contains(hint))
+}
+
+/// `meeting_is_recorded` for `call` this tick
+/// Once seen, a recording counts for the rest of the call: banners get dismissed.
+pub fn is_recorded(call: &CallInfo, sample: &Sample) -> Option<bool> {
+    let seen = if says_recording(&call.window_title) {
+        Some(true)
+    } else {
+        sample
+            .recording
+            .filter(|indicator| indicator.process_id == call.process_id)
+            .map(|indicator| indicator.is_recorded)
+    };
+
+    match call.meeting_is_recorded {
+        Some(true) => Some(true),
+        previous => seen.or(previous),
+    }
+}
+
+/// Throttled app probes for the active call
+#[derive(Debug, Default)]
+pub struct RecordingProbe {
+    /// When the last probe ran, for which call, and what it found
+    last: Option<(Instant, u32, Option<RecordingIndicator>)>,
+}
+
+impl RecordingProbe {
+    /// Probe the app of `call` (the call active at the start of the tick)
+    pub fn probe(&mut self, call: Option<&CallInfo>) -> Option<RecordingIndicator> {
+        let call = call?;
+        if let Some((at, process_id, found)) = self.last {
+            if process_id == call.process_id && at.elapsed() < PROBE_INTERVAL {
+                return found;
+            }
+        }
+
+        let app = call.app.to_lowercase();
+        let is_recorded = if app.contains("zoom") {
+            // No encoder proves nothing: cloud recordings run on Zoom's servers
+            zoom_recorder_running().then_some(true)
+        } else if app.contains("teams") {
+            teams_banner_shown(call.process_id)
+        } else {
+            None
+        };
+
+        let found = is_recorded.map(|is_recorded| RecordingIndicator { process_id: call.process_id, is_recorded });
+        self.last = Some((Instant::now(), call.process_id, found));
+        found
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn zoom_recorder_running() -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::*;
+
+    let mut running = false;
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) else { return false };
+
+        let mut process = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+
+        let mut found = Process32FirstW(snapshot, &mut process).is_ok();
+        while found && !running {
+            let len = process.szExeFile.iter().position(|&c| c == 0).unwrap_or(process.szExeFile.len());
+            let name = String::from_utf16_lossy(&process.szExeFile[..len]).to_lowercase();
+            running = name.trim_end_matches(".exe") == ZOOM_RECORDER;
+            found = Process32NextW(snapshot, &mut process).is_ok();
+        }
+
+        let _ = CloseHandle(snapshot);
+    }
+    running
+}
+
+#[cfg(target_os = "linux")]
+fn zoom_recorder_running() -> bool {
+    procfs::process::all_processes().is_ok_and(|processes| {
+        processes
+            .flatten()
+            .any(|process| process.stat().is_ok_and(|stat| stat.comm.to_lowercase() == ZOOM_RECORDER))
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn zoom_recorder_running() -> bool {
+    use std::process::Command;
+
+    crate::subprocess::output(Command::new("pgrep").args(["-ix", ZOOM_RECORDER]))
+        .is_ok_and(|output| output.status.success())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn zoom_recorder_running() -> bool {
+    false
+}
+
+/// Whether a visible window of `process_id` shows a recording banner
+/// None when the process has no window or UI Automation fails.
+#[cfg(target_os = "windows")]
+fn teams_banner_shown(process_id: u32) -> Option<bool> {
+    let elements = crate::uia::element_names(process_id)?;
+    Some(
+        elements
+            .iter()
+            .any(|(kind, name)| *kind == crate::uia::ElementKind::Text && says_recording(name)),
+    )
+}
+
+#[cfg(not(target_os = "windows"))]
+fn teams_banner_shown(_process_id: u32) -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_is_sticky_for_the_call() {
+        let call = CallInfo {
+            window_title: "Weekly sync | Microsoft Teams".to_string(),
+            ..crate::test_call("Microsoft Teams", 7)
+        };
+        let banner = |process_id: u32, is_recorded: bool| Sample {
+            recording: Some(RecordingIndicator { process_id, is_recorded }),
+            ..Default::default()
+        };
+
+        assert_eq!(is_recorded(&call, &Sample::default()), None);
+        assert_eq!(is_recorded(&call, &banner(7, false)), Some(false));
+        assert_eq!(is_recorded(&call, &banner(8, true)), None, "another process");
+        assert_eq!(is_recorded(&call, &banner(7, true)), Some(true));
+
+        let recorded = CallInfo { meeting_is_recorded: Some(true), ..call.clone() };
+        assert_eq!(is_recorded(&recorded, &banner(7, false)), Some(true));
+
+        let titled = CallInfo { window_title: "● REC Standup - Zoom".to_string(), ..call };
+        assert_eq!(is_recorded(&titled, &Sample::default()), Some(true));
+        assert!(!says_recording("Recording studio tour - YouTube"));
+    }
+}
@@ -0,0 +1,229 @@
+// Window title redaction for everything the monitor writes out (`redaction` config section)
+// Titles often carry meeting subjects and attendee names, so by default they are replaced
+// by a short keyed hash: the same window stays recognizable across lines without being
+// readable. Raw titles are only written with `--no-redact`.
+//
+//   "redaction": { "titles": "scrub", "patterns": ["[\\w.+-]+@[\\w-]+\\.[\\w.]+", "(?i)with .*"] }
+
+use crate::companion::ExternalCall;
+use crate::correlation_engine::DetectionResult;
+use crate::events::{CallEndedPayload, MediaPlaybackPayload, MonitorEvent};
+use crate::meeting_id::ScheduledMeeting;
+use crate::{AudioSource, CallInfo, MonitorState};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Replacement for each `patterns` match in scrub mode
+const SCRUBBED: &str = "[redacted]";
+
+/// How window titles are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleRedaction {
+    /// `title:<12 hex digits>` of a keyed SHA-256
+    #[default]
+    Hash,
+    /// The app name only ("Zoom"), or nothing for unknown apps
+    AppOnly,
+    /// The title with every `patterns` match replaced by `[redacted]`
+    Scrub,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub titles: TitleRedaction,
+    /// Regexes scrubbed from titles in `scrub` mode
+    pub patterns: Vec<String>,
+    /// Secret mixed into title hashes so they cannot be matched against guessed titles
+    pub hash_key: String,
+}
+
+pub struct Redactor {
+    /// None with `--no-redact`
+    mode: Option<TitleRedaction>,
+    patterns: Vec<Regex>,
+    hash_key: String,
+    /// A title written redacted before (a call resumed from the state file), kept as it is
+    redacted_title: Option<String>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Result<Self, String> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern).map_err(|e| format!("invalid redaction pattern '{}': {}", pattern, e)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Redactor {
+            mode: Some(config.titles),
+            patterns,
+            hash_key: config.hash_key.clone(),
+            redacted_title: None,
+        })
+    }
+
+    /// Writes raw titles (`--no-redact`)
+    pub fn disabled() -> Self {
+        Redactor {
+            mode: None,
+            patterns: Vec::new(),
+            hash_key: String::new(),
+            redacted_title: None,
+        }
+    }
+
+    /// Write `title`, already redacted by an earlier run, as it is from now on
+    pub fn keep_redacted(&mut self, title: &str) {
+        self.redacted_title = Some(title.to_string()).filter(|title| !title.is_empty());
+    }
+
+    /// This redactor with `previous`'s already redacted title, after a reload
+    pub fn keeping_redacted(mut self, previous: &Redactor) -> Self {
+        self.redacted_title = previous.redacted_title.clone();
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.mode.is_some()
+    }
+
+    /// `title` as it may be written out; `app` is the detected app, if any
+    pub fn title(&self, title: &str, app: Option<&str>) -> String {
+        let Some(mode) = self.mode else { return title.to_string() };
+        if title.is_empty() {
+            return String::new();
+        }
+        if self.redacted_title.as_deref() == Some(title) {
+            return title.to_string();
+        }
+
+        match mode {
+            TitleRedaction::Hash => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(self.hash_key.as_bytes())
+                    .expect("HMAC accepts keys of any length");
+                mac.update(title.as_bytes());
+                format!("title:{}", &hex::encode(mac.finalize().into_bytes())[..12])
+            }
+            TitleRedaction::AppOnly => app.unwrap_or_default().to_string(),
+            TitleRedaction::Scrub => self
+                .patterns
+                .iter()
+                .fold(title.to_string(), |title, pattern| pattern.replace_all(&title, SCRUBBED).into_owned()),
+        }
+    }
+
+    pub fn state(&self, state: &MonitorState) -> MonitorState {
+        MonitorState {
+            active_call: state.active_call.as_ref().map(|call| self.call(call)),
+            other_audio_sources: state.other_audio_sources.iter().map(|source| self.source(source)).collect(),
+            environment: state.environment,
+            tracked_apps: state.tracked_apps.clone(),
+            // Device names name their owners like window titles do
+            external_calls: state
+                .external_calls
+                .iter()
+                .map(|call| ExternalCall { device: self.title(&call.device, None), ..call.clone() })
+                .collect(),
+        }
+    }
+
+    pub fn events(&self, events: &[MonitorEvent]) -> Vec<MonitorEvent> {
+        events
+            .iter()
+            .map(|event| match event {
+                MonitorEvent::CallStarted(call) => MonitorEvent::CallStarted(self.call(call)),
+                MonitorEvent::CallEnded(ended) => MonitorEvent::CallEnded(CallEndedPayload {
+                    window_title: self.title(&ended.window_title, Some(&ended.app)),
+                    ..ended.clone()
+                }),
+                MonitorEvent::SourceAdded(source) => MonitorEvent::SourceAdded(self.source(source)),
+                MonitorEvent::SourceRemoved(source) => MonitorEvent::SourceRemoved(self.source(source)),
+                MonitorEvent::MediaPlaybackDuringCall(media) => MonitorEvent::MediaPlaybackDuringCall(MediaPlaybackPayload {
+                    window_title: self.title(&media.window_title, Some(&media.app)),
+                    ..media.clone()
+                }),
+                MonitorEvent::ConfidenceChanged(_) | MonitorEvent::ConfigReloaded(_) | MonitorEvent::AudioMarker(_) => {
+                    event.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// `--explain` traces for the JSON log
+    pub fn detections(&self, detections: &[DetectionResult]) -> Vec<DetectionResult> {
+        detections
+            .iter()
+            .map(|detection| {
+                let mut detection = detection.clone();
+                let signals = &mut detection.signals;
+                signals.window_title = self.title(&signals.window_title, signals.detected_app.as_deref());
+                detection
+            })
+            .collect()
+    }
+
+    /// A call with its window title and calendar event redacted
+    pub fn call(&self, call: &CallInfo) -> CallInfo {
+        CallInfo {
+            window_title: self.title(&call.window_title, Some(&call.app)),
+            // Event titles and organizers name people and subjects just like window titles
+            scheduled_meeting: call.scheduled_meeting.as_ref().map(|meeting| ScheduledMeeting {
+                title: self.title(&meeting.title, Some(&call.app)),
+                organizer: meeting
+                    .organizer
+                    .as_ref()
+                    .map(|organizer| self.title(organizer, None))
+                    .filter(|organizer| !organizer.is_empty()),
+                ..meeting.clone()
+            }),
+            ..call.clone()
+        }
+    }
+
+    fn source(&self, source: &AudioSource) -> AudioSource {
+        AudioSource {
+            window_title: self.title(&source.window_title, source.detected_app.as_deref()),
+            ..source.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_redaction_modes() {
+        let title = "Zoom Meeting - 1:1 with jane.doe@example.com";
+        let redactor = |config: RedactionConfig| Redactor::new(&config).unwrap();
+
+        let hashed = redactor(RedactionConfig::default()).title(title, Some("Zoom"));
+        assert!(hashed.starts_with("title:") && hashed.len() == 18);
+        assert_eq!(hashed, redactor(RedactionConfig::default()).title(title, None));
+        let keyed = redactor(RedactionConfig { hash_key: "k".to_string(), ..Default::default() });
+        assert_ne!(keyed.title(title, None), hashed);
+
+        // A resumed call's saved title is written as it was, not hashed again
+        let mut resumed = redactor(RedactionConfig::default());
+        resumed.keep_redacted(&hashed);
+        assert_eq!(resumed.title(&hashed, Some("Zoom")), hashed);
+        assert_eq!(resumed.title(title, None), hashed);
+
+        let app_only = redactor(RedactionConfig { titles: TitleRedaction::AppOnly, ..Default::default() });
+        assert_eq!(app_only.title(title, Some("Zoom")), "Zoom");
+
+        let scrub = redactor(RedactionConfig {
+            titles: TitleRedaction::Scrub,
+            patterns: vec![r"[\w.+-]+@[\w-]+\.[\w.]+".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(scrub.title(title, None), "Zoom Meeting - 1:1 with [redacted]");
+
+        assert_eq!(Redactor::disabled().title(title, None), title);
+        assert!(Redactor::new(&RedactionConfig { patterns: vec!["(".to_string()], ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+// `report` subcommand: per-day, per-app call summaries from the JSON log
+// Reads NDJSON logs (files, or directories holding rust_monitor.log and rotated files)
+// and prints call count, total and longest minutes and time inside working hours.
+//
+//   rust-audio-validator report <log or dir>... [--format markdown|json|csv] [--work-hours 09:00-17:00]
+//
+// Calls come from `call_ended` events when the log has them (event sinks), otherwise they
+// are rebuilt from consecutive `state` snapshots. Times are the machine's local time as
+// logged. A call is counted on the day it started; working hours are Monday to Friday.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str =
+    "Usage: rust-audio-validator report <log or dir>... [--format markdown|json|csv] [--work-hours 09:00-17:00]";
+
+/// One call reconstructed from the log
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallRecord {
+    pub app: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl CallRecord {
+    pub fn duration_secs(&self) -> i64 {
+        (self.end - self.start).num_seconds().max(0)
+    }
+}
+
+/// Summary row for one app on one day
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct DaySummary {
+    pub date: String,
+    pub app: String,
+    pub calls: usize,
+    pub total_minutes: f64,
+    pub longest_minutes: f64,
+    pub working_hours_minutes: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WorkingHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl WorkingHours {
+    fn parse(value: &str) -> Option<Self> {
+        let (start, end) = value.split_once('-')?;
+        let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+        let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+        (start < end).then_some(WorkingHours { start, end })
+    }
+
+    /// Seconds of `call` inside working hours on weekdays
+    fn overlap_secs(&self, call: &CallRecord) -> i64 {
+        let mut total = 0;
+        let mut day = call.start.date();
+        while day <= call.end.date() {
+            if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                let from = call.start.max(day.and_time(self.start));
+                let to = call.end.min(day.and_time(self.end));
+                total += (to - from).num_seconds().max(0);
+            }
+            day += Duration::days(1);
+        }
+        total
+    }
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        WorkingHours {
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+        }
+    }
+}
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+
+    let format = option("--format").map(|s| s.as_str()).unwrap_or("markdown");
+    if !matches!(format, "markdown" | "json" | "csv") {
+        eprintln!("[report] Unknown --format '{}', expected markdown, json or csv", format);
+        return 2;
+    }
+    let hours = match option("--work-hours") {
+        Some(value) => match WorkingHours::parse(value) {
+            Some(hours) => hours,
+            None => {
+                eprintln!("[report] Invalid --work-hours '{}', expected HH:MM-HH:MM", value);
+                return 2;
+            }
+        },
+        None => WorkingHours::default(),
+    };
+
+    let inputs = positional_args(args, &["--format", "--work-hours"]);
+    if inputs.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+
+    let calls = match load_calls(&inputs) {
+        Ok(calls) => calls,
+        Err(e) => {
+            eprintln!("[report] {}", e);
+            return 2;
+        }
+    };
+    let summaries = summarize(&calls, hours);
+
+    match format {
+        "json" => match serde_json::to_string_pretty(&summaries) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("[report] Failed to serialize report: {}", e);
+                return 1;
+            }
+        },
+        "csv" => print!("{}", to_csv(&summaries)),
+        _ => print!("{}", to_markdown(&summaries)),
+    }
+    0
+}
+
+/// Arguments that are neither flags nor flag values
+pub fn positional_args<'a>(args: &'a [String], flags_with_values: &[&str]) -> Vec<&'a str> {
+    let mut positional = Vec::new();
+    let mut skip_next = false;
+    for arg in args {
+        if skip_next {
+            skip_next = false;
+        } else if flags_with_values.contains(&arg.as_str()) {
+            skip_next = true;
+        } else if !arg.starts_with("--") {
+            positional.push(arg.as_str());
+        }
+    }
+    positional
+}
+
+/// Every call in the given log files and directories, sorted by start
+pub fn load_calls(inputs: &[&str]) -> Result<Vec<CallRecord>, String> {
+    let mut calls = Vec::new();
+    for contents in read_logs(inputs)? {
+        calls.extend(parse_log(&contents));
+    }
+    calls.sort_by_key(|call| call.start);
+    calls.dedup();
+    Ok(calls)
+}
+
+/// Contents of every log file among `inputs`
+pub fn read_logs(inputs: &[&str]) -> Result<Vec<String>, String> {
+    log_files(inputs)?
+        .iter()
+        .map(|path| fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e)))
+        .collect()
+}
+
+/// Files named directly plus the plain (not sealed) logs inside named directories
+fn log_files(inputs: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if !path.is_dir() {
+            files.push(path.to_path_buf());
+            continue;
+        }
+        let entries = fs::read_dir(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let mut found: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|file| {
+                let name = file.file_name().unwrap_or_default().to_string_lossy();
+                name.contains(".log") && !name.contains(".sealed")
+            })
+            .collect();
+        found.sort();
+        files.extend(found);
+    }
+    Ok(files)
+}
+
+/// (event type, local time, payload) of every envelope in one NDJSON log
+pub fn parse_entries(contents: &str) -> Vec<(String, NaiveDateTime, Value)> {
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|entry| {
+            let event_type = entry.get("event_type")?.as_str()?.to_string();
+            let at = DateTime::parse_from_rfc3339(entry.get("timestamp")?.as_str()?).ok()?.naive_local();
+            Some((event_type, at, entry.get("payload")?.clone()))
+        })
+        .collect()
+}
+
+/// Calls in one NDJSON log
+fn parse_log(contents: &str) -> Vec<CallRecord> {
+    let entries = parse_entries(contents);
+
+    // call_ended carries everything needed
+    let ended: Vec<CallRecord> = entries
+        .iter()
+        .filter(|(event_type, _, _)| event_type == "call_ended")
+        .map(|(_, at, payload)| CallRecord {
+            app: app_name(payload),
+            start: *at - Duration::seconds(payload["duration_secs"].as_i64().unwrap_or(0)),
+            end: *at,
+        })
+        .collect();
+    if !ended.is_empty() {
+        return ended;
+    }
+
+    // Otherwise follow the active call through the snapshots
+    let mut calls = Vec::new();
+    let mut current: Option<(Value, CallRecord)> = None;
+    for (event_type, at, payload) in &entries {
+        if event_type != "state" {
+            continue;
+        }
+        let active = payload.get("active_call").filter(|call| !call.is_null());
+        let same = match (&current, active) {
+            (Some((key, _)), Some(call)) => *key == call_key(call),
+            _ => false,
+        };
+
+        if same {
+            if let Some((_, record)) = current.as_mut() {
+                record.end = *at;
+            }
+            continue;
+        }
+        if let Some((_, mut record)) = current.take() {
+            // The first snapshot without the call is the closest we get to its end
+            record.end = *at;
+            calls.push(record);
+        }
+        if let Some(call) = active {
+            let start = *at - Duration::seconds(call["duration_secs"].as_i64().unwrap_or(0));
+            current = Some((call_key(call), CallRecord { app: app_name(call), start, end: *at }));
+        }
+    }
+    calls.extend(current.map(|(_, record)| record));
+    calls
+}
+
+/// What identifies one call across snapshots; schema 1 logs only have the local `started_at`
+fn call_key(call: &Value) -> Value {
+    serde_json::json!([call["app"], call["process_id"], call["started_at_utc"], call["started_at"]])
+}
+
+fn app_name(payload: &Value) -> String {
+    match payload["app"].as_str() {
+        Some(app) if !app.is_empty() => app.to_string(),
+        // Privacy mode blanks the app
+        _ => "(unknown)".to_string(),
+    }
+}
+
+fn summarize(calls: &[CallRecord], hours: WorkingHours) -> Vec<DaySummary> {
+    let mut rows: BTreeMap<(NaiveDate, String), DaySummary> = BTreeMap::new();
+    for call in calls {
+        let date = call.start.date();
+        let row = rows.entry((date, call.app.clone())).or_insert_with(|| DaySummary {
+            date: date.to_string(),
+            app: call.app.clone(),
+            ..Default::default()
+        });
+        let minutes = call.duration_secs() as f64 / 60.0;
+        row.calls += 1;
+        row.total_minutes += minutes;
+        row.longest_minutes = row.longest_minutes.max(minutes);
+        row.working_hours_minutes += hours.overlap_secs(call) as f64 / 60.0;
+    }
+
+    rows.into_values()
+        .map(|row| DaySummary {
+            total_minutes: round(row.total_minutes),
+            longest_minutes: round(row.longest_minutes),
+            working_hours_minutes: round(row.working_hours_minutes),
+            ..row
+        })
+        .collect()
+}
+
+fn round(minutes: f64) -> f64 {
+    (minutes * 10.0).round() / 10.0
+}
+
+fn to_csv(rows: &[DaySummary]) -> String {
+    let mut out = String::from("date,app,calls,total_minutes,longest_minutes,working_hours_minutes\n");
+    for row in rows {
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.date,
+            csv_field(&row.app),
+            row.calls,
+            row.total_minutes,
+            row.longest_minutes,
+            row.working_hours_minutes
+        ));
+    }
+    out
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_markdown(rows: &[DaySummary]) -> String {
+    let mut out = String::from(
+        "| Date | App | Calls | Total min | Longest min | Working-hours min |\n|---|---|---:|---:|---:|---:|\n",
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "| {} | {} | {} | {:.1} | {:.1} | {:.1} |\n",
+            row.date,
+            row.app.replace('|', "\\|"),
+            row.calls,
+            row.total_minutes,
+            row.longest_minutes,
+            row.working_hours_minutes
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calls_from_snapshots_and_summary() {
+        let state = |at: &str, call: &str| {
+            format!(r#"{{"schema_version":1,"event_type":"state","timestamp":"{}","payload":{{"active_call":{},"other_audio_sources":[]}}}}"#, at, call)
+        };
+        let zoom = |duration: u32| {
+            format!(r#"{{"app":"Zoom","process_id":10,"started_at":"16:50:00","duration_secs":{}}}"#, duration)
+        };
+        // Friday 2024-03-01, 16:50 to 17:20
+        let log = [
+            state("2024-03-01T16:49:59+01:00", "null"),
+            state("2024-03-01T16:50:30+01:00", &zoom(30)),
+            state("2024-03-01T17:10:00+01:00", &zoom(1200)),
+            state("2024-03-01T17:20:00+01:00", "null"),
+        ]
+        .join("\n");
+
+        let calls = parse_log(&log);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].duration_secs(), 30 * 60);
+
+        let summary = summarize(&calls, WorkingHours::default());
+        assert_eq!(summary.len(), 1);
+        assert_eq!((summary[0].calls, summary[0].total_minutes, summary[0].working_hours_minutes), (1, 30.0, 10.0));
+        assert_eq!(summary[0].date, "2024-03-01");
+    }
+}
@@ -0,0 +1,146 @@
+// Call role: host, presenter or attendee (best effort)
+// `role` on the active call, for analytics. The evidence, from the most to the least direct:
+//   - the client's own buttons read through UI Automation (uia.rs): a "Stop presenting" /
+//     "Stop Share" toggle, or controls only hosts get ("Security", "Host tools", "End
+//     meeting for all", Teams' "Meeting options")
+//   - the window or tab title: "You're presenting", Chrome's "... is sharing your screen",
+//     Zoom's "You are the host"
+//   - who was in the call first: the side that opened it waits alone until the others
+//     connect (a P2P caller until the callee answers, an organizer until attendees join),
+//     while whoever joins finds the others already there
+// Which side initiated the WebRTC connection is not observable here: network_monitor.rs
+// reads socket tables, which show a UDP media socket's remote end but not who sent the
+// first packet (both ICE agents send checks), and relayed meetings only show the relay.
+// Who was in the call first, from the participant count, stands in for it.
+// Presenting is what the user does now, so it wins while it lasts; host evidence is kept
+// for the rest of the call. Without any evidence the role is `unknown`.
+
+use crate::titles;
+use crate::CallInfo;
+use serde::{Deserialize, Serialize};
+
+/// Title fragments while the user shares their screen, normalized
+const PRESENTING_TITLES: &[&str] = &[
+    "you're presenting",
+    "you are presenting",
+    "you're sharing",
+    "you are sharing",
+    "you are screen sharing",
+    "is sharing your screen",
+    "is sharing a window",
+    "is sharing this tab",
+];
+
+/// Title fragments shown to the host, normalized
+const HOST_TITLES: &[&str] = &["you are the host", "you're the host", "(host, me)", "(me, host)"];
+
+/// The user's part in the active call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallRole {
+    /// Organizer or host, or the side that opened a 1:1 call
+    Host,
+    /// Sharing their screen right now
+    Presenter,
+    Attendee,
+    #[default]
+    Unknown,
+}
+
+impl CallRole {
+    /// Same spelling as the JSON output
+    #[cfg_attr(not(feature = "grpc"), allow(dead_code))] // only gRPC needs it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallRole::Host => "host",
+            CallRole::Presenter => "presenter",
+            CallRole::Attendee => "attendee",
+            CallRole::Unknown => "unknown",
+        }
+    }
+}
+
+/// Role evidence gathered over the active call
+#[derive(Debug, Default)]
+pub struct RoleTracker {
+    process_id: Option<u32>,
+    /// Others in the call when their number was first known
+    others_at_start: Option<u32>,
+    /// The user was alone first and others joined later
+    opened: bool,
+    host_seen: bool,
+}
+
+impl RoleTracker {
+    /// `role` for `call` this tick; read after `controls` and `estimated_participants`
+    pub fn update(&mut self, call: &CallInfo) -> CallRole {
+        if self.process_id != Some(call.process_id) {
+            *self = RoleTracker { process_id: Some(call.process_id), ..Default::default() };
+        }
+
+        let title = titles::normalize(&call.window_title).replace('\u{2019}', "'");
+        let controls = call.controls.as_ref();
+
+        if let Some(others) = call.estimated_participants {
+            match self.others_at_start {
+                None => self.others_at_start = Some(others),
+                Some(0) if others > 0 => self.opened = true,
+                _ => {}
+            }
+        }
+        if controls.is_some_and(|controls| controls.host_controls) || HOST_TITLES.iter().any(|hint| title.contains(hint)) {
+            self.host_seen = true;
+        }
+
+        let presenting = controls.and_then(|controls| controls.presenting).unwrap_or(false)
+            || PRESENTING_TITLES.iter().any(|hint| title.contains(hint));
+
+        if presenting {
+            CallRole::Presenter
+        } else if self.host_seen || self.opened {
+            CallRole::Host
+        } else if controls.is_some() || self.others_at_start.is_some_and(|others| others > 0) {
+            // The client's controls were read without host tools, or the others were there first
+            CallRole::Attendee
+        } else {
+            CallRole::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uia::CallControls;
+
+    fn call(window_title: &str, estimated_participants: Option<u32>, controls: Option<CallControls>) -> CallInfo {
+        CallInfo {
+            window_title: window_title.to_string(),
+            controls,
+            estimated_participants,
+            ..crate::test_call("Zoom", 7)
+        }
+    }
+
+    #[test]
+    fn test_role_from_controls_titles_and_who_came_first() {
+        // Waited alone, then the other side connected: the user opened the call
+        let mut caller = RoleTracker::default();
+        assert_eq!(caller.update(&call("Zoom Meeting", None, None)), CallRole::Unknown);
+        assert_eq!(caller.update(&call("Zoom Meeting", Some(0), None)), CallRole::Unknown);
+        assert_eq!(caller.update(&call("Zoom Meeting", Some(1), None)), CallRole::Host);
+        assert_eq!(caller.update(&call("You’re presenting - Zoom", Some(1), None)), CallRole::Presenter);
+        assert_eq!(caller.update(&call("Zoom Meeting", Some(1), None)), CallRole::Host);
+
+        // The others were there first
+        let mut joiner = RoleTracker::default();
+        assert_eq!(joiner.update(&call("Zoom Meeting", Some(4), None)), CallRole::Attendee);
+
+        // Host tools outrank arrival order; controls without them make an attendee
+        let host = CallControls { host_controls: true, ..Default::default() };
+        assert_eq!(joiner.update(&call("Zoom Meeting", Some(4), Some(host))), CallRole::Host);
+        let mut reader = RoleTracker::default();
+        let plain = CallControls { presenting: Some(false), ..Default::default() };
+        assert_eq!(reader.update(&call("Zoom Meeting", None, Some(plain))), CallRole::Attendee);
+    }
+}
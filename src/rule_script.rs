@@ -0,0 +1,171 @@
+// Deployment rule script (`scripting` feature, `scoring.script` in the config)
+// For clients whose behavior static config cannot express, a Rhai script gets the last
+// word on each detection that passed the gates. It defines
+//
+//   fn score(signal, confidence) {
+//       if signal.process_name == "acmevoip.exe" && !signal.has_mic_active { return false; }
+//       if signal.window_title.contains("On call") { return 0.3; }
+//   }
+//
+// `signal` has the fields of the `--explain` signals and `confidence` is the score so
+// far. A number is added to the confidence (negative to lower it), `false` vetoes the
+// call, and anything else keeps the result. The script is re-read with the config file;
+// a script that fails to load or run is skipped with a warning, never a crash.
+
+#[cfg(not(feature = "scripting"))]
+use crate::correlation_engine::{ScoringConfig, SignalValues};
+
+/// What the script decided for one detection
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(feature = "scripting"), allow(dead_code))] // only a loaded script decides
+pub enum ScriptVerdict {
+    Keep,
+    Boost(f32),
+    Veto,
+}
+
+#[cfg(feature = "scripting")]
+pub use script::RuleScript;
+
+#[cfg(feature = "scripting")]
+mod script {
+    use super::ScriptVerdict;
+    use crate::correlation_engine::{ScoringConfig, SignalValues};
+    use crate::error::ValidatorError;
+    use rhai::{Dynamic, Engine, Scope, AST};
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    /// Bounds a runaway script to a few milliseconds per detection
+    const MAX_OPERATIONS: u64 = 100_000;
+
+    pub struct RuleScript {
+        engine: Engine,
+        ast: AST,
+        /// Last error reported, so a broken script warns once rather than every tick
+        last_error: Mutex<Option<String>>,
+    }
+
+    impl RuleScript {
+        pub fn load(path: &Path) -> Result<Self, ValidatorError> {
+            let mut engine = Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS);
+            let ast = engine
+                .compile_file(path.to_path_buf())
+                .map_err(|e| ValidatorError::ParseFailure(format!("rule script {} ({})", path.display(), e)))?;
+            Ok(RuleScript { engine, ast, last_error: Mutex::new(None) })
+        }
+
+        /// The script for `scoring.script`, if any loads
+        pub fn from_scoring(scoring: &ScoringConfig) -> Option<Self> {
+            let path = scoring.script.as_ref()?;
+            RuleScript::load(path).map_err(|e| log::warn!("Skipping {}", e)).ok()
+        }
+
+        pub fn evaluate(&self, signals: &SignalValues, confidence: f32) -> ScriptVerdict {
+            let result = rhai::serde::to_dynamic(signals).and_then(|signal| {
+                self.engine.call_fn::<Dynamic>(&mut Scope::new(), &self.ast, "score", (signal, confidence as f64))
+            });
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    let message = e.to_string();
+                    let mut last_error = self.last_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    if last_error.as_deref() != Some(message.as_str()) {
+                        log::warn!("Rule script failed: {}", message);
+                        *last_error = Some(message);
+                    }
+                    return ScriptVerdict::Keep;
+                }
+            };
+
+            if result.as_bool() == Ok(false) {
+                ScriptVerdict::Veto
+            } else if let Ok(boost) = result.as_float() {
+                ScriptVerdict::Boost(boost as f32)
+            } else if let Ok(boost) = result.as_int() {
+                ScriptVerdict::Boost(boost as f32)
+            } else {
+                ScriptVerdict::Keep
+            }
+        }
+    }
+}
+
+/// Without the `scripting` feature no script ever loads
+#[cfg(not(feature = "scripting"))]
+pub enum RuleScript {}
+
+#[cfg(not(feature = "scripting"))]
+impl RuleScript {
+    pub fn from_scoring(scoring: &ScoringConfig) -> Option<Self> {
+        if scoring.script.is_some() {
+            log::warn!("scoring.script ignored: built without the `scripting` feature");
+        }
+        None
+    }
+
+    pub fn evaluate(&self, _signals: &SignalValues, _confidence: f32) -> ScriptVerdict {
+        match *self {}
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use crate::correlation_engine::{CorrelationEngine, MultiSignal, ScoringConfig};
+    use std::time::Duration;
+
+    #[test]
+    fn test_script_boosts_and_vetoes() {
+        let path = std::env::temp_dir().join(format!("rule-script-{}.rhai", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+            fn score(signal, confidence) {
+                if signal.process_name != "acmevoip.exe" { return; }
+                if signal.has_mic_active { 0.5 } else { false }
+            }
+            "#,
+        )
+        .unwrap();
+        let scoring: ScoringConfig = serde_json::from_value(serde_json::json!({
+            "force_track_processes": ["acmevoip.exe"],
+            "script": path,
+        }))
+        .unwrap();
+        let engine = CorrelationEngine::new().with_scoring(scoring);
+
+        // The in-house client only plays audio and opens the mic: 55% without the script
+        let signal = MultiSignal {
+            process_id: 31,
+            process_name: "acmevoip.exe".to_string(),
+            window_title: String::new(),
+            has_mic_active: true,
+            mic_peak_level: None,
+            mic_idle: false,
+            process_load: None,
+            has_audio_output: true,
+            audio_peak_level: 0.2,
+            is_virtual_device: false,
+            has_webrtc_connection: false,
+            webrtc_started_at: None,
+            has_peer_connection: false,
+            has_sip_media: false,
+            client_in_call: None,
+            in_scheduled_meeting: false,
+            audio_active_secs: None,
+            contributions: Vec::new(),
+            detected_app: None,
+            tab_url: None,
+            duration: Duration::from_secs(0),
+        };
+        let boosted = engine.detect_call(&signal);
+        assert!(boosted.is_call && boosted.confidence > 1.0);
+        assert!(boosted.rules.iter().any(|rule| rule.rule == "script" && rule.weight == 0.5));
+
+        // Audio without the mic is its hold music
+        let vetoed = engine.detect_call(&MultiSignal { has_mic_active: false, has_webrtc_connection: true, ..signal });
+        assert!(!vetoed.is_call);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,110 @@
+// Safari call attribution (macOS)
+// Safari plays, captures and connects from WebKit XPC services (com.apple.WebKit.GPU,
+// .Networking, .WebContent) that launchd starts, so their parent is pid 1 and the
+// process tree never reaches Safari by parent. macOS records the app responsible for
+// each XPC service instead, and the process tree follows that for WebKit helpers; the
+// same holds for any app embedding a WKWebView.
+// The window title System Events reports is the front tab's only. Safari's own scripting
+// dictionary lists every tab with its URL, so a call tab in another tab or window still
+// names the call; without one, the front tab's title is used as before.
+
+use crate::browser_bridge;
+
+/// Safari builds, by lowercase process name
+const SAFARIS: &[&str] = &["safari", "safari technology preview"];
+
+/// Prefix of the WebKit XPC services, lowercase
+const WEBKIT_HELPER_PREFIX: &str = "com.apple.webkit.";
+
+/// Lists "<url>\t<title>" per tab, then "front\t<title>" for the front tab
+#[cfg(target_os = "macos")]
+const TABS_SCRIPT: &str = r#"
+tell application "Safari"
+    set separator to character id 9
+    set output to ""
+    repeat with w in windows
+        repeat with t in tabs of w
+            try
+                set output to output & (URL of t) & separator & (name of t) & linefeed
+            end try
+        end repeat
+    end repeat
+    try
+        set output to output & "front" & separator & (name of current tab of front window)
+    end try
+    return output
+end tell
+"#;
+
+/// Whether `process_name` is Safari
+pub fn is_safari(process_name: &str) -> bool {
+    SAFARIS.contains(&process_name.to_lowercase().as_str())
+}
+
+/// Whether `process_name` is a WebKit XPC service, which works for the app responsible for it
+pub fn is_webkit_helper(process_name: &str) -> bool {
+    process_name.to_lowercase().starts_with(WEBKIT_HELPER_PREFIX)
+}
+
+/// Whether `child` works for `parent`: a WebKit service under the app responsible for it.
+/// launchd is only the parent when the responsible app is unknown.
+pub fn same_app(child: &str, parent: &str) -> bool {
+    is_webkit_helper(child) && !parent.eq_ignore_ascii_case("launchd")
+}
+
+/// The app macOS holds responsible for `pid` (Safari for its WebKit services), when
+/// that is another process
+#[cfg(target_os = "macos")]
+pub fn responsible_pid(pid: u32) -> Option<u32> {
+    extern "C" {
+        fn responsibility_get_pid_responsible_for_pid(pid: libc::pid_t) -> libc::pid_t;
+    }
+
+    let responsible = unsafe { responsibility_get_pid_responsible_for_pid(pid as libc::pid_t) };
+    (responsible > 0 && responsible as u32 != pid).then_some(responsible as u32)
+}
+
+/// Title of Safari's call tab, else of its front tab; None when Safari can't be asked
+#[cfg(target_os = "macos")]
+pub fn window_title() -> Option<String> {
+    use std::process::Command;
+
+    let output = crate::subprocess::output(Command::new("osascript").arg("-e").arg(TABS_SCRIPT)).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    title_from_tabs(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The title of the first tab on a call app's URL, else the front tab's
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn title_from_tabs(listing: &str) -> Option<String> {
+    let tabs: Vec<(&str, &str)> = listing.lines().filter_map(|line| line.split_once('\t')).collect();
+    tabs.iter()
+        .find(|(url, _)| browser_bridge::url_call_app(url).is_some())
+        .or_else(|| tabs.iter().find(|(url, _)| *url == "front"))
+        .map(|(_, title)| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helpers_and_call_tab_titles() {
+        assert!(is_safari("Safari"));
+        assert!(is_webkit_helper("com.apple.WebKit.GPU"));
+        assert!(is_webkit_helper("com.apple.WebKit.WebContent"));
+        assert!(!is_webkit_helper("com.apple.Safari.SafeBrowsing"));
+        assert!(same_app("com.apple.WebKit.GPU", "Safari"));
+        assert!(!same_app("com.apple.WebKit.GPU", "launchd"));
+
+        let listing = "https://news.ycombinator.com/\tHacker News\n\
+                       https://meet.google.com/abc-defg-hij\tMeet - abc-defg-hij\n\
+                       front\tHacker News";
+        assert_eq!(title_from_tabs(listing).as_deref(), Some("Meet - abc-defg-hij"));
+        assert_eq!(title_from_tabs("https://example.com/\tExample\nfront\tExample").as_deref(), Some("Example"));
+        assert_eq!(title_from_tabs(""), None);
+    }
+}
@@ -0,0 +1,127 @@
+// Flatpak and Snap sandboxes on Linux
+// Sandboxed apps run under a bwrap (Flatpak) or snap-confine wrapper, so the wrapper is
+// what /proc shows at the top of the app's process tree, and with Flatpak's own pid
+// namespace the pid a client reports to the sound server is only valid inside the
+// sandbox. Processes are mapped back to their app through /proc/<pid>/root/.flatpak-info
+// or the snap.<name> cgroup; audio clients through the PipeWire portal properties
+// (pipewire.access.portal.app_id and the host pipewire.sec.pid, see audio/linux.rs).
+// App IDs become the process names the rest of the pipeline knows ("chrome", "zoom").
+
+use std::fs;
+
+/// Wrappers that stand in for a sandboxed app in /proc
+const WRAPPERS: &[&str] = &["bwrap", "snap-confine", "snap"];
+
+/// Process name for Flatpak app IDs and snap names
+const KNOWN_APPS: &[(&str, &str)] = &[
+    ("com.google.Chrome", "chrome"),
+    ("org.chromium.Chromium", "chromium"),
+    ("com.brave.Browser", "brave"),
+    ("com.microsoft.Edge", "msedge"),
+    ("org.mozilla.firefox", "firefox"),
+    ("us.zoom.Zoom", "zoom"),
+    ("com.slack.Slack", "slack"),
+    ("com.discordapp.Discord", "discord"),
+    ("com.skype.Client", "skype"),
+    ("com.github.IsmaelMartinez.teams_for_linux", "teams"),
+    ("org.signal.Signal", "signal"),
+    ("zoom-client", "zoom"),
+    ("teams-for-linux", "teams"),
+    ("signal-desktop", "signal"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sandbox {
+    Flatpak,
+    Snap,
+}
+
+/// A process's sandbox and the app ID it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxedApp {
+    pub sandbox: Sandbox,
+    /// Flatpak app ID ("com.google.Chrome") or snap name ("zoom-client")
+    pub app_id: String,
+}
+
+/// The sandboxed app `pid` belongs to, if any
+pub fn of_process(pid: u32) -> Option<SandboxedApp> {
+    if let Ok(info) = fs::read_to_string(format!("/proc/{}/root/.flatpak-info", pid)) {
+        return flatpak_app_id(&info).map(|app_id| SandboxedApp { sandbox: Sandbox::Flatpak, app_id });
+    }
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    snap_name(&cgroup).map(|app_id| SandboxedApp { sandbox: Sandbox::Snap, app_id })
+}
+
+/// The sandbox the monitor itself runs in; inside one /proc shows only its own processes
+pub fn current() -> Option<Sandbox> {
+    if fs::metadata("/.flatpak-info").is_ok() {
+        Some(Sandbox::Flatpak)
+    } else if std::env::var_os("SNAP").is_some() {
+        Some(Sandbox::Snap)
+    } else {
+        None
+    }
+}
+
+/// `comm` of `pid`, with sandbox wrappers replaced by the app they run
+pub fn resolve_name(pid: u32, comm: String) -> String {
+    if !WRAPPERS.contains(&comm.as_str()) {
+        return comm;
+    }
+    of_process(pid).map_or(comm, |app| app_name(&app.app_id))
+}
+
+/// Process name for an app ID: the known name, else the ID's last component in lowercase
+pub fn app_name(app_id: &str) -> String {
+    KNOWN_APPS
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(app_id))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| app_id.rsplit('.').next().unwrap_or(app_id).to_lowercase())
+}
+
+/// `name=` in the [Application] group of .flatpak-info
+fn flatpak_app_id(info: &str) -> Option<String> {
+    let mut in_application = false;
+    for line in info.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_application = line == "[Application]";
+        } else if in_application {
+            if let Some(name) = line.strip_prefix("name=") {
+                return Some(name.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// The snap of a "snap.<name>.<app>" scope or service in /proc/<pid>/cgroup
+fn snap_name(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .flat_map(|line| line.rsplit(':').next().unwrap_or("").split('/'))
+        .find_map(|unit| unit.strip_prefix("snap."))
+        .and_then(|unit| unit.split('.').next())
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandboxed_apps_resolve_to_known_names() {
+        let info = "[Application]\nname=com.google.Chrome\nruntime=runtime/org.freedesktop.Platform/x86_64/23.08\n\n[Instance]\ninstance-id=1234\n";
+        assert_eq!(flatpak_app_id(info).as_deref(), Some("com.google.Chrome"));
+        assert_eq!(app_name("com.google.Chrome"), "chrome");
+
+        let cgroup = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/snap.zoom-client.zoom-client-4f1c.scope\n";
+        assert_eq!(snap_name(cgroup).as_deref(), Some("zoom-client"));
+        assert_eq!(app_name("zoom-client"), "zoom");
+        assert_eq!(snap_name("0::/user.slice/user-1000.slice/session-2.scope\n"), None);
+
+        assert_eq!(app_name("org.jitsi.jitsi-meet"), "jitsi-meet");
+    }
+}
@@ -0,0 +1,277 @@
+// Poll scheduling for the monitor loop
+// Each sensing subsystem refreshes on its own interval (`polling` config section): audio
+// sessions are cheap and drive call-end detection, socket scans are expensive and change
+// slowly. The loop wakes when the next subsystem is due; the others reuse their last
+// reading (see validator.rs).
+// The pace adapts to what is going on: with no audio and no microphone user everything
+// slows to `idle_ms`, and while a call is suspected or active audio and mic speed up to
+// `call_ms` so starts and ends are caught quickly. While saving power (power.rs) and no
+// call is suspected, audio and microphone use no longer speed polling up and socket
+// scans stop altogether.
+
+use crate::call_tracker::Sample;
+use crate::correlation_engine::CallPhase;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Shortest interval accepted from the config, to keep a typo from spinning the loop
+const MIN_INTERVAL_MS: u64 = 50;
+
+/// Independently refreshed parts of a poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// Audio sessions, output volume and per-session levels
+    Audio,
+    /// Microphone users, capture peaks and mic volume
+    Mic,
+    /// Socket scans for WebRTC, SIP and relay traffic
+    Network,
+    /// Window titles of the audio sources
+    WindowTitles,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 4] = [Subsystem::Audio, Subsystem::Mic, Subsystem::Network, Subsystem::WindowTitles];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Audio => "audio",
+            Subsystem::Mic => "mic",
+            Subsystem::Network => "network",
+            Subsystem::WindowTitles => "window_titles",
+        }
+    }
+}
+
+/// Refresh interval of each subsystem, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PollIntervals {
+    pub audio_ms: u64,
+    pub mic_ms: u64,
+    pub network_ms: u64,
+    pub window_title_ms: u64,
+    /// Every subsystem while nothing plays audio or uses the microphone
+    pub idle_ms: u64,
+    /// Audio and mic while a call is suspected or active
+    pub call_ms: u64,
+}
+
+impl Default for PollIntervals {
+    fn default() -> Self {
+        PollIntervals {
+            audio_ms: 250,
+            mic_ms: 500,
+            network_ms: 2000,
+            window_title_ms: 1000,
+            idle_ms: 3000,
+            call_ms: 250,
+        }
+    }
+}
+
+impl PollIntervals {
+    pub fn get(&self, subsystem: Subsystem, pace: Pace) -> Duration {
+        let ms = match subsystem {
+            Subsystem::Audio => self.audio_ms,
+            Subsystem::Mic => self.mic_ms,
+            Subsystem::Network => self.network_ms,
+            Subsystem::WindowTitles => self.window_title_ms,
+        };
+        let ms = match (pace, subsystem) {
+            (Pace::Idle, _) => ms.max(self.idle_ms),
+            (Pace::Call, Subsystem::Audio | Subsystem::Mic) => ms.min(self.call_ms),
+            _ => ms,
+        };
+        Duration::from_millis(ms.max(MIN_INTERVAL_MS))
+    }
+}
+
+/// How hard the loop polls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pace {
+    /// Nothing plays audio or uses the microphone
+    Idle,
+    /// Audio or microphone in use, no call candidate
+    #[default]
+    Normal,
+    /// A call is suspected, active or ending, or a call app has the microphone open
+    Call,
+}
+
+impl Pace {
+    /// The pace after a tick that saw `sample` and left the call lifecycle in `phase`
+    pub fn of(phase: CallPhase, sample: &Sample) -> Pace {
+        if phase != CallPhase::Idle || sample.mic_sources.iter().any(|src| src.detected_app.is_some()) {
+            Pace::Call
+        } else if sample.audio_sources.is_empty() && sample.mic_sources.is_empty() {
+            Pace::Idle
+        } else {
+            Pace::Normal
+        }
+    }
+}
+
+/// Which subsystems a poll refreshes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Due {
+    pub audio: bool,
+    pub mic: bool,
+    pub network: bool,
+    pub window_titles: bool,
+}
+
+impl Due {
+    pub fn all() -> Self {
+        Due { audio: true, mic: true, network: true, window_titles: true }
+    }
+
+    fn set(&mut self, subsystem: Subsystem) {
+        match subsystem {
+            Subsystem::Audio => self.audio = true,
+            Subsystem::Mic => self.mic = true,
+            Subsystem::Network => self.network = true,
+            Subsystem::WindowTitles => self.window_titles = true,
+        }
+    }
+}
+
+/// When each subsystem last ran, and at what pace the next runs come
+pub struct Scheduler {
+    intervals: PollIntervals,
+    pace: Pace,
+    power_saving: bool,
+    last: [Option<Instant>; 4],
+}
+
+impl Scheduler {
+    /// Everything is due on the first poll
+    pub fn new(intervals: PollIntervals) -> Self {
+        Scheduler { intervals, pace: Pace::default(), power_saving: false, last: [None; 4] }
+    }
+
+    /// Apply reloaded intervals; each subsystem keeps its last run time
+    pub fn set_intervals(&mut self, intervals: PollIntervals) {
+        self.intervals = intervals;
+    }
+
+    /// The pace polling runs at: a saving scheduler outside calls idles
+    pub fn pace(&self) -> Pace {
+        match self.pace {
+            Pace::Normal if self.power_saving => Pace::Idle,
+            pace => pace,
+        }
+    }
+
+    /// Change pace; a faster pace makes overdue subsystems due right away
+    pub fn set_pace(&mut self, pace: Pace) {
+        self.pace = pace;
+    }
+
+    pub fn set_power_saving(&mut self, power_saving: bool) {
+        self.power_saving = power_saving;
+    }
+
+    /// Whether the expensive probes (socket scans, window enumeration) are off
+    pub fn skips_expensive_probes(&self) -> bool {
+        self.power_saving && self.pace != Pace::Call
+    }
+
+    /// Make `subsystem` due on the next poll, out of its interval (session wakeups)
+    pub fn expedite(&mut self, subsystem: Subsystem) {
+        if let Some(i) = Subsystem::ALL.iter().position(|&s| s == subsystem) {
+            self.last[i] = None;
+        }
+    }
+
+    /// The subsystems due at `now`
+    pub fn due(&mut self, now: Instant) -> Due {
+        let mut due = Due::default();
+        for (i, subsystem) in Subsystem::ALL.into_iter().enumerate() {
+            if subsystem == Subsystem::Network && self.skips_expensive_probes() {
+                continue;
+            }
+            if self.next(i, subsystem).is_none_or(|next| next <= now) {
+                due.set(subsystem);
+                self.last[i] = Some(now);
+            }
+        }
+        due
+    }
+
+    /// How long the loop may sleep before something is due
+    pub fn until_next(&self, now: Instant) -> Duration {
+        Subsystem::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, subsystem)| subsystem != Subsystem::Network || !self.skips_expensive_probes())
+            .map(|(i, subsystem)| self.next(i, subsystem).map_or(Duration::ZERO, |next| next.saturating_duration_since(now)))
+            .min()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    fn next(&self, i: usize, subsystem: Subsystem) -> Option<Instant> {
+        self.last[i].map(|last| last + self.intervals.get(subsystem, self.pace()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsystems_refresh_on_their_own_intervals() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut scheduler = Scheduler::new(PollIntervals {
+            audio_ms: 250,
+            mic_ms: 500,
+            network_ms: 2000,
+            window_title_ms: 0,
+            ..Default::default()
+        });
+
+        assert_eq!(scheduler.due(at(0)), Due::all());
+        assert_eq!(scheduler.until_next(at(0)), Duration::from_millis(MIN_INTERVAL_MS));
+        assert_eq!(scheduler.due(at(50)), Due { window_titles: true, ..Default::default() });
+
+        let due = scheduler.due(at(500));
+        assert!(due.audio && due.mic && due.window_titles && !due.network);
+        assert_eq!(scheduler.due(at(2000)), Due::all());
+    }
+
+    #[test]
+    fn test_pace_follows_activity() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut scheduler = Scheduler::new(PollIntervals::default());
+        scheduler.due(at(0));
+
+        assert_eq!(Pace::of(CallPhase::Idle, &Sample::default()), Pace::Idle);
+        scheduler.set_pace(Pace::Idle);
+        assert_eq!(scheduler.until_next(at(0)), Duration::from_secs(3));
+        assert_eq!(scheduler.due(at(2000)), Due::default());
+
+        // Ramps up at once: audio is overdue at the call pace
+        let suspected = CallPhase::Suspected { process_id: 7, since: std::time::SystemTime::UNIX_EPOCH };
+        scheduler.set_pace(Pace::of(suspected, &Sample::default()));
+        let due = scheduler.due(at(2000));
+        assert!(due.audio && due.mic && due.window_titles && due.network);
+        assert_eq!(scheduler.until_next(at(2000)), Duration::from_millis(250));
+
+        // On battery, audio playing without a call idles and skips socket scans
+        scheduler.set_power_saving(true);
+        scheduler.set_pace(Pace::Normal);
+        assert_eq!(scheduler.pace(), Pace::Idle);
+        let due = scheduler.due(at(6000));
+        assert!(due.audio && !due.network);
+        scheduler.set_pace(Pace::Call);
+        assert!(scheduler.due(at(6000)).network);
+
+        // A session wakeup senses right away
+        scheduler.expedite(Subsystem::Mic);
+        assert_eq!(scheduler.until_next(at(6010)), Duration::ZERO);
+        assert_eq!(scheduler.due(at(6010)), Due { mic: true, ..Default::default() });
+    }
+}
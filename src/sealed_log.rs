@@ -0,0 +1,135 @@
+// Encrypted JSON log (`--encrypt-logs <pubkey>`) and the `decrypt-log` subcommand
+// Every log line is sealed to the collection server's X25519 public key (libsodium
+// `crypto_box_seal`: ephemeral key, XSalsa20-Poly1305) and written base64-encoded, one
+// entry per line, to rust_monitor.sealed.log. The machine that writes the log cannot
+// read it back; only the holder of the private key can.
+//
+//   rust-audio-validator decrypt-log --keygen
+//   VALIDATOR_LOG_SECRET_KEY=<secret> rust-audio-validator decrypt-log <file>
+//   rust-audio-validator decrypt-log <file> --key-file secret.key
+//
+// Keys are 32 bytes, base64.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use crypto_box::aead::OsRng;
+use crypto_box::{PublicKey, SecretKey};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+const USAGE: &str = "Usage: rust-audio-validator decrypt-log <file> [--key-file <path>] | decrypt-log --keygen";
+
+/// Seals log lines to a public key
+#[derive(Clone)]
+pub struct LogSealer {
+    public_key: PublicKey,
+}
+
+impl LogSealer {
+    /// `public_key` is the base64 key printed by `decrypt-log --keygen`
+    pub fn new(public_key: &str) -> Result<Self, String> {
+        Ok(LogSealer {
+            public_key: PublicKey::from(decode_key(public_key)?),
+        })
+    }
+
+    /// One sealed, base64-encoded log line
+    pub fn seal(&self, line: &str) -> Result<String, String> {
+        self.public_key
+            .seal(&mut OsRng, line.as_bytes())
+            .map(|sealed| BASE64.encode(sealed))
+            .map_err(|_| "sealing failed".to_string())
+    }
+}
+
+fn decode_key(key: &str) -> Result<[u8; 32], String> {
+    BASE64
+        .decode(key.trim())
+        .map_err(|e| format!("invalid key: {}", e))?
+        .try_into()
+        .map_err(|_| "invalid key: expected 32 bytes".to_string())
+}
+
+fn unseal(secret_key: &SecretKey, line: &str) -> Result<String, String> {
+    let sealed = BASE64.decode(line.trim()).map_err(|e| format!("not a sealed entry: {}", e))?;
+    let plain = secret_key
+        .unseal(&sealed)
+        .map_err(|_| "cannot decrypt (wrong key or corrupted entry)".to_string())?;
+    String::from_utf8(plain).map_err(|e| format!("decrypted entry is not UTF-8: {}", e))
+}
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "--keygen") {
+        let secret_key = SecretKey::generate(&mut OsRng);
+        println!("public: {}", BASE64.encode(secret_key.public_key().as_bytes()));
+        println!("secret: {}", BASE64.encode(secret_key.to_bytes()));
+        return 0;
+    }
+
+    let Some(path) = args.first().filter(|a| !a.starts_with("--")) else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+
+    // Prefer the environment variable or a file so the key stays out of the process list
+    let key = match args.iter().position(|a| a == "--key-file").and_then(|i| args.get(i + 1)) {
+        Some(key_file) => fs::read_to_string(key_file).map_err(|e| format!("cannot read {}: {}", key_file, e)),
+        None => std::env::var("VALIDATOR_LOG_SECRET_KEY")
+            .map_err(|_| "set VALIDATOR_LOG_SECRET_KEY or pass --key-file".to_string()),
+    };
+    let secret_key = match key.and_then(|key| decode_key(&key)) {
+        Ok(bytes) => SecretKey::from(bytes),
+        Err(e) => {
+            eprintln!("[decrypt-log] {}", e);
+            return 2;
+        }
+    };
+
+    let file = match fs::File::open(Path::new(path)) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("[decrypt-log] cannot open {}: {}", path, e);
+            return 2;
+        }
+    };
+
+    // Keep going past bad lines so one corrupted entry does not hide the rest
+    let mut failures = 0;
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match unseal(&secret_key, &line) {
+            Ok(entry) => println!("{}", entry),
+            Err(e) => {
+                eprintln!("[decrypt-log] line {}: {}", number + 1, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sealed_entries_need_the_secret_key() {
+        let secret_key = SecretKey::generate(&mut OsRng);
+        let public_key = BASE64.encode(secret_key.public_key().as_bytes());
+        let sealer = LogSealer::new(&public_key).unwrap();
+
+        let entry = r#"{"event_type":"state","payload":{"active_call":null}}"#;
+        let sealed = sealer.seal(entry).unwrap();
+        assert!(!sealed.contains("active_call"));
+        assert_eq!(unseal(&secret_key, &sealed).unwrap(), entry);
+
+        assert!(unseal(&SecretKey::generate(&mut OsRng), &sealed).is_err());
+        assert!(LogSealer::new("c2hvcnQ=").is_err());
+    }
+}
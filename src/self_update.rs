@@ -0,0 +1,398 @@
+// Signed self-update: the `update` subcommand and `--auto-update`
+// The `update.manifest_url` of the config names a release manifest with one binary per
+// platform and its Ed25519 signature. The signature covers the version, the platform and
+// the binary, so an old release cannot be replayed as a new one. The download is staged
+// next to the running executable, verified against the pinned public key and only then
+// renamed over it; an interrupted or tampered download never replaces a working binary.
+// `--auto-update` checks in the background and restarts into the new binary with the same
+// arguments once no call is active.
+//
+//   {
+//     "version": "1.2.0",
+//     "assets": {
+//       "linux-x86_64": { "url": "https://example.com/1.2.0/rust-audio-validator", "signature": "<base64>" }
+//     }
+//   }
+//
+//   rust-audio-validator update --keygen
+//   VALIDATOR_UPDATE_SECRET_KEY=<secret> rust-audio-validator update --sign <binary> --version 1.2.0
+//   rust-audio-validator update [--check] [--config validator.json]
+//
+// The public key comes from `update.public_key`, or is built in with the
+// UPDATE_PUBLIC_KEY environment variable at compile time.
+
+use crate::config::Config;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: rust-audio-validator update [--check] [--config <path>] | update --keygen | update --sign <binary> --version <version> [--platform <os-arch>] [--key-file <path>]";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// Larger downloads are refused rather than buffered
+const MAX_BINARY_BYTES: u64 = 256 * 1024 * 1024;
+
+/// `update` section of the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    pub manifest_url: Option<String>,
+    /// Base64 Ed25519 public key; overrides the built-in one
+    pub public_key: Option<String>,
+    /// Hours between `--auto-update` checks
+    pub check_hours: u64,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        UpdateConfig { manifest_url: None, public_key: None, check_hours: 24 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    version: String,
+    assets: HashMap<String, Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    url: String,
+    signature: String,
+}
+
+/// "linux-x86_64", "windows-x86_64", "macos-aarch64"
+fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+// What a release signature covers
+fn signed_message(version: &str, platform: &str, binary: &[u8]) -> Vec<u8> {
+    let mut message = format!("rust-audio-validator:{}:{}:", version, platform).into_bytes();
+    message.extend_from_slice(binary);
+    message
+}
+
+fn verify(public_key: &[u8], version: &str, platform: &str, binary: &[u8], signature: &str) -> Result<(), String> {
+    let signature = BASE64.decode(signature.trim()).map_err(|e| format!("invalid signature: {}", e))?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(&signed_message(version, platform, binary), &signature)
+        .map_err(|_| "signature does not match the public key".to_string())
+}
+
+fn public_key(config: &UpdateConfig) -> Result<Vec<u8>, String> {
+    let key = config
+        .public_key
+        .as_deref()
+        .or(option_env!("UPDATE_PUBLIC_KEY"))
+        .ok_or_else(|| "no update public key: set update.public_key or build with UPDATE_PUBLIC_KEY".to_string())?;
+    let key = BASE64.decode(key.trim()).map_err(|e| format!("invalid update public key: {}", e))?;
+    if key.len() != 32 {
+        return Err("invalid update public key: expected 32 bytes".to_string());
+    }
+    Ok(key)
+}
+
+/// Dotted numeric versions; "1.10.0" is newer than "1.9.2"
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(candidate) > parse(current)
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout_connect(CONNECT_TIMEOUT).timeout_read(READ_TIMEOUT).build()
+}
+
+fn fetch_manifest(agent: &ureq::Agent, url: &str) -> Result<Manifest, String> {
+    let body = agent
+        .get(url)
+        .call()
+        .map_err(|e| format!("cannot fetch {}: {}", url, e))?
+        .into_string()
+        .map_err(|e| format!("cannot fetch {}: {}", url, e))?;
+    serde_json::from_str(&body).map_err(|e| format!("invalid manifest {}: {}", url, e))
+}
+
+fn download(agent: &ureq::Agent, url: &str) -> Result<Vec<u8>, String> {
+    let response = agent.get(url).call().map_err(|e| format!("cannot download {}: {}", url, e))?;
+    let mut binary = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_BINARY_BYTES + 1)
+        .read_to_end(&mut binary)
+        .map_err(|e| format!("cannot download {}: {}", url, e))?;
+    if binary.len() as u64 > MAX_BINARY_BYTES {
+        return Err(format!("{} is larger than {} bytes", url, MAX_BINARY_BYTES));
+    }
+    Ok(binary)
+}
+
+/// A newer release than this build for this platform, if the manifest has one
+fn available(agent: &ureq::Agent, config: &UpdateConfig) -> Result<Option<(String, Asset)>, String> {
+    let url = config.manifest_url.as_deref().ok_or_else(|| "no update.manifest_url configured".to_string())?;
+    let mut manifest = fetch_manifest(agent, url)?;
+    if !is_newer(&manifest.version, env!("CARGO_PKG_VERSION")) {
+        return Ok(None);
+    }
+    let asset = manifest
+        .assets
+        .remove(&platform())
+        .ok_or_else(|| format!("release {} has no {} binary", manifest.version, platform()))?;
+    Ok(Some((manifest.version, asset)))
+}
+
+/// Download, verify and install the newest release; its version if one was installed
+pub fn update(config: &UpdateConfig) -> Result<Option<String>, String> {
+    let key = public_key(config)?;
+    let agent = agent();
+    let Some((version, asset)) = available(&agent, config)? else { return Ok(None) };
+    let binary = download(&agent, &asset.url)?;
+    verify(&key, &version, &platform(), &binary, &asset.signature)?;
+    install(&binary)?;
+    Ok(Some(version))
+}
+
+fn sibling(exe: &Path, suffix: &str) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    exe.with_file_name(name)
+}
+
+// Stage next to the executable (same filesystem, so the rename is atomic), then swap
+fn install(binary: &[u8]) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("cannot locate the executable: {}", e))?;
+    let staged = sibling(&exe, ".new");
+    let write = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&staged)?;
+        file.write_all(binary)?;
+        file.sync_all()?;
+        fs::set_permissions(&staged, fs::metadata(&exe)?.permissions())
+    };
+    if let Err(e) = write() {
+        fs::remove_file(&staged).ok();
+        return Err(format!("cannot write {}: {}", staged.display(), e));
+    }
+
+    // Windows cannot replace a running executable but can rename it out of the way
+    #[cfg(windows)]
+    {
+        let previous = sibling(&exe, ".old");
+        fs::remove_file(&previous).ok();
+        fs::rename(&exe, &previous).map_err(|e| format!("cannot move {} aside: {}", exe.display(), e))?;
+        if let Err(e) = fs::rename(&staged, &exe) {
+            fs::rename(&previous, &exe).ok();
+            return Err(format!("cannot replace {}: {}", exe.display(), e));
+        }
+    }
+    #[cfg(not(windows))]
+    fs::rename(&staged, &exe).map_err(|e| format!("cannot replace {}: {}", exe.display(), e))?;
+    Ok(())
+}
+
+/// Remove what a previous update left behind (the replaced Windows executable)
+pub fn clean_up() {
+    if let Ok(exe) = std::env::current_exe() {
+        fs::remove_file(sibling(&exe, ".old")).ok();
+        fs::remove_file(sibling(&exe, ".new")).ok();
+    }
+}
+
+/// Start the installed binary with the same arguments in place of this process
+pub fn restart(args: &[String]) -> ! {
+    let exe = std::env::current_exe().unwrap_or_else(|_| PathBuf::from(&args[0]));
+    let mut command = std::process::Command::new(&exe);
+    command.args(&args[1..]);
+
+    // exec only returns on failure
+    #[cfg(unix)]
+    let error = std::os::unix::process::CommandExt::exec(&mut command);
+    #[cfg(not(unix))]
+    let error = match command.spawn() {
+        Ok(_) => std::process::exit(0),
+        Err(e) => e,
+    };
+    log::error!("Cannot restart {}: {}", exe.display(), error);
+    std::process::exit(1)
+}
+
+/// `--auto-update`: checks every `check_hours` on a background thread
+pub struct AutoUpdater {
+    installed: Arc<Mutex<Option<String>>>,
+}
+
+impl AutoUpdater {
+    pub fn spawn(config: UpdateConfig) -> Result<Self, String> {
+        public_key(&config)?;
+        if config.manifest_url.is_none() {
+            return Err("no update.manifest_url configured".to_string());
+        }
+        let installed = Arc::new(Mutex::new(None));
+        let slot = Arc::clone(&installed);
+        let interval = Duration::from_secs(config.check_hours.max(1) * 3600);
+        thread::spawn(move || loop {
+            match update(&config) {
+                Ok(Some(version)) => {
+                    log::info!("Installed update {}", version);
+                    *slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(version);
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Update check failed: {}", e),
+            }
+            thread::sleep(interval);
+        });
+        Ok(AutoUpdater { installed })
+    }
+
+    /// The version installed on disk and waiting for a restart
+    pub fn installed(&self) -> Option<String> {
+        self.installed.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+}
+
+/// `update` subcommand
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "--keygen") {
+        return keygen();
+    }
+    if let Some(binary) = args.iter().position(|a| a == "--sign").and_then(|i| args.get(i + 1)) {
+        return sign(binary, args);
+    }
+
+    let config = match args.iter().position(|a| a == "--config").and_then(|i| args.get(i + 1)) {
+        Some(path) => match Config::load(Path::new(path)) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("[update] {}", e);
+                return 2;
+            }
+        },
+        None => Config::default(),
+    };
+
+    if args.iter().any(|a| a == "--check") {
+        return match available(&agent(), &config.update) {
+            Ok(Some((version, _))) => {
+                println!("{} is available (running {})", version, env!("CARGO_PKG_VERSION"));
+                0
+            }
+            Ok(None) => {
+                println!("Up to date ({})", env!("CARGO_PKG_VERSION"));
+                0
+            }
+            Err(e) => {
+                eprintln!("[update] {}", e);
+                1
+            }
+        };
+    }
+
+    match update(&config.update) {
+        Ok(Some(version)) => {
+            println!("Updated {} -> {}; restart the monitor to run it", env!("CARGO_PKG_VERSION"), version);
+            0
+        }
+        Ok(None) => {
+            println!("Up to date ({})", env!("CARGO_PKG_VERSION"));
+            0
+        }
+        Err(e) => {
+            eprintln!("[update] {}", e);
+            1
+        }
+    }
+}
+
+fn keygen() -> i32 {
+    let rng = SystemRandom::new();
+    let pkcs8 = match Ed25519KeyPair::generate_pkcs8(&rng) {
+        Ok(pkcs8) => pkcs8,
+        Err(_) => {
+            eprintln!("[update] cannot generate a key pair");
+            return 1;
+        }
+    };
+    let Ok(key_pair) = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()) else {
+        eprintln!("[update] cannot generate a key pair");
+        return 1;
+    };
+    println!("public: {}", BASE64.encode(key_pair.public_key().as_ref()));
+    println!("secret: {}", BASE64.encode(pkcs8.as_ref()));
+    0
+}
+
+fn sign(binary: &str, args: &[String]) -> i32 {
+    let arg = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1)).cloned();
+    let Some(version) = arg("--version") else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let platform = arg("--platform").unwrap_or_else(platform);
+
+    // Prefer the environment variable or a file so the key stays out of the process list
+    let secret = match arg("--key-file") {
+        Some(key_file) => fs::read_to_string(&key_file).map_err(|e| format!("cannot read {}: {}", key_file, e)),
+        None => std::env::var("VALIDATOR_UPDATE_SECRET_KEY")
+            .map_err(|_| "set VALIDATOR_UPDATE_SECRET_KEY or pass --key-file".to_string()),
+    };
+    let key_pair = secret
+        .and_then(|secret| BASE64.decode(secret.trim()).map_err(|e| format!("invalid secret key: {}", e)))
+        .and_then(|pkcs8| Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|_| "invalid secret key".to_string()));
+    let key_pair = match key_pair {
+        Ok(key_pair) => key_pair,
+        Err(e) => {
+            eprintln!("[update] {}", e);
+            return 2;
+        }
+    };
+
+    let contents = match fs::read(binary) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("[update] cannot read {}: {}", binary, e);
+            return 2;
+        }
+    };
+    let signature = key_pair.sign(&signed_message(&version, &platform, &contents));
+    println!("{}", BASE64.encode(signature.as_ref()));
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_signatures_bind_version_and_platform() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = key_pair.public_key().as_ref();
+        let binary = b"\x7fELF release";
+        let signature = BASE64.encode(key_pair.sign(&signed_message("1.2.0", "linux-x86_64", binary)).as_ref());
+
+        assert!(verify(public_key, "1.2.0", "linux-x86_64", binary, &signature).is_ok());
+        assert!(verify(public_key, "1.2.0", "linux-x86_64", b"\x7fELF tampered", &signature).is_err());
+        assert!(verify(public_key, "1.3.0", "linux-x86_64", binary, &signature).is_err());
+        assert!(verify(public_key, "1.2.0", "windows-x86_64", binary, &signature).is_err());
+
+        assert!(is_newer("1.10.0", "1.9.2"));
+        assert!(is_newer("v2.0.0", "1.0.0"));
+        assert!(!is_newer("1.0.0", "1.0.0"));
+    }
+}
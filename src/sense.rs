@@ -0,0 +1,156 @@
+// `sense` subcommand: run a single sensor once, print one sample and exit
+// Lets scripts and other agents reuse the platform probing code without the correlation loop
+//
+//   rust-audio-validator sense mic [--json]
+//   rust-audio-validator sense audio-apps [--json]
+//   rust-audio-validator sense net [--json]
+
+use crate::audio::DeviceUsage;
+use crate::audio_output_monitor::AudioOutputMonitor;
+use crate::mic_monitor::MicMonitor;
+use crate::network_monitor::NetworkMonitor;
+use crate::output::{Envelope, EventType};
+use serde::Serialize;
+
+const USAGE: &str = "Usage: rust-audio-validator sense <mic|audio-apps|net> [--json]";
+
+/// Run the subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let as_json = args.iter().any(|a| a == "--json");
+    let sensor = args.iter().find(|a| !a.starts_with("--")).map(|s| s.as_str());
+
+    match sensor {
+        Some("mic") => sense_mic(as_json),
+        Some("audio-apps") | Some("audio") => sense_audio_apps(as_json),
+        Some("net") | Some("network") => sense_net(as_json),
+        Some(other) => {
+            eprintln!("Unknown sensor '{}'\n{}", other, USAGE);
+            2
+        }
+        None => {
+            eprintln!("{}", USAGE);
+            2
+        }
+    }
+}
+
+fn sense_mic(as_json: bool) -> i32 {
+    let report = match MicMonitor::new().and_then(|mut monitor| monitor.build_status_report()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("[sense] Microphone probe failed: {}", e);
+            return 1;
+        }
+    };
+
+    if as_json {
+        return print_json(EventType::MicSample, &report);
+    }
+
+    println!("Device:   {}", report.mic.default_device);
+    println!("Muted:    {}", report.mic.is_muted);
+    println!("Volume:   {:.0}%", report.mic.volume_level);
+    println!("In use:   {}", report.mic.is_in_use);
+    for app in &report.conflicts.apps_using_mic {
+        println!("  - {}", app);
+    }
+    for app in &report.conflicts.apps_using_camera {
+        println!("  - {} (camera)", app);
+    }
+    print_devices(&report.devices);
+    for error in &report.errors {
+        eprintln!("[sense] {}", error);
+    }
+    0
+}
+
+fn sense_audio_apps(as_json: bool) -> i32 {
+    let report = match AudioOutputMonitor::new().and_then(|mut monitor| monitor.build_status_report()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("[sense] Audio output probe failed: {}", e);
+            return 1;
+        }
+    };
+
+    if as_json {
+        return print_json(EventType::AudioAppsSample, &report);
+    }
+
+    println!("Device:   {} ({})", report.output.default_device, report.output.form_factor.as_str());
+    println!("Muted:    {}", report.output.is_muted);
+    println!("Peak:     {:.3}", report.output.peak_level);
+    for app in &report.active_apps {
+        println!(
+            "  - {} (pid {}) playing={} peak={:.3} title={:?}",
+            app.name, app.process_id, app.is_playing, app.peak_level, app.window_title
+        );
+        if let Some(device) = &app.output_device {
+            println!("      device: {} ({})", device.name, device.form_factor.as_str());
+        }
+    }
+    print_devices(&report.devices);
+    for error in &report.errors {
+        eprintln!("[sense] {}", error);
+    }
+    0
+}
+
+/// Per-device breakdown, only worth printing when more than one device is involved
+fn print_devices(devices: &[DeviceUsage]) {
+    if devices.len() < 2 {
+        return;
+    }
+    println!("Devices:");
+    for device in devices {
+        println!(
+            "  {} {} ({}): {}",
+            if device.is_default { "*" } else { "-" },
+            device.name,
+            device.form_factor.as_str(),
+            device.apps.join(", ")
+        );
+    }
+}
+
+fn sense_net(as_json: bool) -> i32 {
+    let mut monitor = NetworkMonitor::new();
+    let signals = monitor.get_webrtc_signals();
+
+    if as_json {
+        return print_json(EventType::NetSample, &signals);
+    }
+
+    if signals.is_empty() {
+        println!("No WebRTC-like UDP activity");
+    }
+    for signal in &signals {
+        println!(
+            "  - {} (pid {}) connections={}",
+            signal.process_name, signal.process_id, signal.connection_count
+        );
+        for provider in &signal.providers {
+            println!("      relay: {}", provider.display_name());
+        }
+        if signal.has_sip_signaling {
+            println!("      SIP call (RTP media)");
+        }
+        if signal.media_peers > 0 {
+            println!("      peer media endpoints: {}", signal.media_peers);
+        }
+    }
+    0
+}
+
+fn print_json<T: Serialize>(event_type: EventType, payload: &T) -> i32 {
+    match Envelope::new(event_type, payload).to_json_line() {
+        Ok(json) => {
+            println!("{}", json);
+            0
+        }
+        Err(e) => {
+            eprintln!("[sense] Failed to serialize sample: {}", e);
+            1
+        }
+    }
+}
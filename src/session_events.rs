@@ -0,0 +1,240 @@
+// Audio session wakeups for the fast start path (--fast-start)
+// The monitor loop normally sleeps until the next subsystem is due, so a call starts
+// 1.5-3 s after its audio does. With --fast-start the loop sleeps on these wakeups
+// instead and, when one arrives, senses audio, microphone and sockets right away:
+//   - Windows: a render or capture session is created (IAudioSessionNotification), or an
+//     existing one turns active (IAudioSessionEvents::OnStateChanged)
+//   - Linux: a playback stream or recording stream appears (`pactl subscribe`)
+//   - macOS and others: not available; the loop keeps its regular pace
+// The candidate is then scored like on any tick; --fast-start also shortens the
+// confirmation window so the call is reported within a second.
+
+use crate::error::ValidatorError;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+/// Confirmation window under --fast-start, in milliseconds (see HysteresisConfig)
+pub const FAST_CONFIRM_MS: u64 = 250;
+
+/// Session creation and activation notifications from the audio stack
+pub struct SessionEvents {
+    wakeups: Receiver<()>,
+}
+
+impl SessionEvents {
+    pub fn start() -> Result<Self, ValidatorError> {
+        let (tx, rx) = mpsc::channel();
+        platform::watch(tx)?;
+        Ok(SessionEvents { wakeups: rx })
+    }
+
+    /// Sleep up to `timeout`; true when a session event cut the sleep short
+    pub fn wait(&self, timeout: Duration) -> bool {
+        match self.wakeups.recv_timeout(timeout) {
+            Ok(()) => {
+                // One wakeup per burst: a call opens render and capture streams together
+                while self.wakeups.try_recv().is_ok() {}
+                true
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => {
+                std::thread::sleep(timeout);
+                false
+            }
+        }
+    }
+}
+
+type Wakeups = Sender<()>;
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::Wakeups;
+    use crate::error::ValidatorError;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use windows::core::{implement, Result, GUID, PCWSTR};
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::Media::Audio::*;
+    use windows::Win32::System::Com::*;
+
+    /// Session controls with a state listener; kept so the registrations stay alive
+    type Watched = Arc<Mutex<Vec<IAudioSessionControl>>>;
+
+    pub fn watch(wakeups: Wakeups) -> std::result::Result<(), ValidatorError> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        thread::spawn(move || unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            match register(&wakeups) {
+                Ok(managers) => {
+                    let _ = ready_tx.send(Ok(()));
+                    // Notifications arrive on COM worker threads while the managers live
+                    let _managers = managers;
+                    loop {
+                        thread::park();
+                    }
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(ValidatorError::from(e)));
+                    CoUninitialize();
+                }
+            }
+        });
+        ready_rx
+            .recv()
+            .unwrap_or_else(|_| Err(ValidatorError::Platform("session watcher stopped".to_string())))
+    }
+
+    unsafe fn register(wakeups: &Wakeups) -> Result<Vec<(IAudioSessionManager2, IAudioSessionNotification)>> {
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let watched: Watched = Arc::new(Mutex::new(Vec::new()));
+        let mut managers = Vec::new();
+        // Render sessions carry the call's audio; capture sessions are the mic being acquired
+        for flow in [eRender, eCapture] {
+            let device = enumerator.GetDefaultAudioEndpoint(flow, eConsole)?;
+            let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+            let notification: IAudioSessionNotification =
+                SessionCreated { wakeups: wakeups.clone(), watched: Arc::clone(&watched) }.into();
+            manager.RegisterSessionNotification(&notification)?;
+            // Enumerating once is what starts creation notifications; existing sessions
+            // get a state listener for apps that reuse a session between calls
+            let sessions = manager.GetSessionEnumerator()?;
+            for i in 0..sessions.GetCount()? {
+                if let Ok(session) = sessions.GetSession(i) {
+                    watch_state(&session, wakeups, &watched);
+                }
+            }
+            managers.push((manager, notification));
+        }
+        Ok(managers)
+    }
+
+    fn watch_state(session: &IAudioSessionControl, wakeups: &Wakeups, watched: &Watched) {
+        let events: IAudioSessionEvents = StateChanged { wakeups: wakeups.clone() }.into();
+        if unsafe { session.RegisterAudioSessionNotification(&events) }.is_ok() {
+            watched.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(session.clone());
+        }
+    }
+
+    #[implement(IAudioSessionNotification)]
+    struct SessionCreated {
+        wakeups: Wakeups,
+        watched: Watched,
+    }
+
+    impl IAudioSessionNotification_Impl for SessionCreated_Impl {
+        fn OnSessionCreated(&self, newsession: Option<&IAudioSessionControl>) -> Result<()> {
+            if let Some(session) = newsession {
+                watch_state(session, &self.wakeups, &self.watched);
+            }
+            let _ = self.wakeups.send(());
+            Ok(())
+        }
+    }
+
+    #[implement(IAudioSessionEvents)]
+    struct StateChanged {
+        wakeups: Wakeups,
+    }
+
+    impl IAudioSessionEvents_Impl for StateChanged_Impl {
+        fn OnDisplayNameChanged(&self, _name: &PCWSTR, _context: *const GUID) -> Result<()> {
+            Ok(())
+        }
+
+        fn OnIconPathChanged(&self, _path: &PCWSTR, _context: *const GUID) -> Result<()> {
+            Ok(())
+        }
+
+        fn OnSimpleVolumeChanged(&self, _volume: f32, _mute: BOOL, _context: *const GUID) -> Result<()> {
+            Ok(())
+        }
+
+        fn OnChannelVolumeChanged(&self, _count: u32, _volumes: *const f32, _changed: u32, _context: *const GUID) -> Result<()> {
+            Ok(())
+        }
+
+        fn OnGroupingParamChanged(&self, _param: *const GUID, _context: *const GUID) -> Result<()> {
+            Ok(())
+        }
+
+        fn OnStateChanged(&self, state: AudioSessionState) -> Result<()> {
+            if state == AudioSessionStateActive {
+                let _ = self.wakeups.send(());
+            }
+            Ok(())
+        }
+
+        fn OnSessionDisconnected(&self, _reason: AudioSessionDisconnectReason) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::Wakeups;
+    use crate::error::ValidatorError;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::thread;
+
+    pub fn watch(wakeups: Wakeups) -> Result<(), ValidatorError> {
+        let mut child = crate::subprocess::spawn(
+            Command::new("pactl").env("LC_ALL", "C").arg("subscribe").stdout(Stdio::piped()).stderr(Stdio::null()),
+        )
+        .map_err(|e| ValidatorError::spawn("pactl", e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| ValidatorError::Platform("pactl subscribe has no output".to_string()))?;
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if is_new_stream(&line) && wakeups.send(()).is_err() {
+                    break;
+                }
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+
+    // "Event 'new' on sink-input #42": playback; "source-output": recording (the mic)
+    pub(super) fn is_new_stream(line: &str) -> bool {
+        line.starts_with("Event 'new' on sink-input #") || line.starts_with("Event 'new' on source-output #")
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::Wakeups;
+    use crate::error::ValidatorError;
+
+    pub fn watch(_wakeups: Wakeups) -> Result<(), ValidatorError> {
+        Err(ValidatorError::BackendUnavailable("audio session notifications on this platform".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wakeups_are_coalesced() {
+        let (tx, rx) = mpsc::channel();
+        let events = SessionEvents { wakeups: rx };
+        for _ in 0..3 {
+            tx.send(()).unwrap();
+        }
+        assert!(events.wait(Duration::from_millis(10)));
+        assert!(!events.wait(Duration::from_millis(10)));
+
+        #[cfg(target_os = "linux")]
+        {
+            assert!(platform::is_new_stream("Event 'new' on source-output #118"));
+            assert!(!platform::is_new_stream("Event 'change' on sink-input #42"));
+            assert!(!platform::is_new_stream("Event 'new' on client #9"));
+        }
+    }
+}
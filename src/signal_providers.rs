@@ -0,0 +1,229 @@
+// Custom signal providers
+// Integrators add their own evidence (a CTI connector that knows the desk phone is off
+// hook, a badge reader that knows the user left the room) without touching the rules.
+// A provider is polled once per tick and returns contributions: a weight added to the
+// confidence of the process or app it names, or of every candidate when it names neither.
+// Contributions only move the score; the call-app gate and the threshold still apply, and
+// `--explain` lists each as a `provider:<name>` rule.
+//
+// In-tree providers are registered in `register_builtin`. With the `plugins` feature,
+// `--plugin <library>` loads one from a shared library over a small C ABI (see `dylib`).
+
+use serde::{Deserialize, Serialize};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Largest weight a single contribution may add or take away
+const MAX_WEIGHT: f32 = 1.0;
+
+/// A source of call evidence outside the built-in sensors
+pub trait SignalProvider: Send {
+    /// Short stable name, used in `--explain` rule names
+    fn name(&self) -> &str;
+    /// Evidence as of now; called once per tick, so it must not block
+    fn poll(&mut self) -> Vec<SignalContribution>;
+}
+
+/// One piece of evidence from a provider
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SignalContribution {
+    /// Process the evidence is about (the root process of the app)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_id: Option<u32>,
+    /// App name it is about, as reported in `app` (e.g. "Zoom"), or a process name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    /// Added to the confidence; negative weights count against a call
+    pub weight: f32,
+    pub reason: String,
+    /// Set from the provider's name when polled
+    pub provider: String,
+}
+
+impl SignalContribution {
+    /// Whether this contribution is about the candidate with these names
+    pub fn applies_to(&self, process_id: u32, names: &[&str]) -> bool {
+        match (self.process_id, self.app.as_deref()) {
+            (None, None) => true,
+            (pid, app) => {
+                pid == Some(process_id) || app.is_some_and(|app| names.iter().any(|name| name.eq_ignore_ascii_case(app)))
+            }
+        }
+    }
+}
+
+/// Every registered provider
+#[derive(Default)]
+pub struct SignalProviders {
+    providers: Vec<Box<dyn SignalProvider>>,
+}
+
+impl SignalProviders {
+    pub fn register(&mut self, provider: Box<dyn SignalProvider>) {
+        log::info!("Signal provider: {}", provider.name());
+        self.providers.push(provider);
+    }
+
+    /// Contributions of every provider for this tick; a provider that panics is dropped
+    pub fn poll(&mut self) -> Vec<SignalContribution> {
+        let mut contributions = Vec::new();
+        self.providers.retain_mut(|provider| {
+            match panic::catch_unwind(AssertUnwindSafe(|| provider.poll())) {
+                Ok(polled) => {
+                    let name = provider.name().to_string();
+                    contributions.extend(polled.into_iter().filter(|c| c.weight.is_finite()).map(|c| SignalContribution {
+                        weight: c.weight.clamp(-MAX_WEIGHT, MAX_WEIGHT),
+                        provider: name.clone(),
+                        ..c
+                    }));
+                    true
+                }
+                Err(_) => {
+                    log::warn!("Signal provider {} panicked; removed", provider.name());
+                    false
+                }
+            }
+        });
+        contributions
+    }
+}
+
+/// Providers compiled into this build; add integrations here
+pub fn register_builtin(_providers: &mut SignalProviders) {}
+
+/// Load a provider from a shared library (`plugins` feature)
+#[cfg(feature = "plugins")]
+pub fn load_plugin(path: &std::path::Path) -> Result<Box<dyn SignalProvider>, crate::error::ValidatorError> {
+    Ok(Box::new(dylib::DylibProvider::load(path)?))
+}
+
+/// Shared library plugins. A plugin exports, with C linkage:
+///   - `uint32_t validator_plugin_abi(void)`: PLUGIN_ABI
+///   - `const char *validator_plugin_name(void)`: a static, NUL-terminated name
+///   - `char *validator_plugin_poll(void)`: a JSON array of contributions
+///     (`[{"app": "Zoom", "weight": 0.2, "reason": "desk phone off hook"}]`), or NULL for none
+///   - `void validator_plugin_free(char *)`: frees what poll returned
+#[cfg(feature = "plugins")]
+pub mod dylib {
+    use super::{SignalContribution, SignalProvider};
+    use crate::error::ValidatorError;
+    use libloading::{Library, Symbol};
+    use std::ffi::{c_char, CStr};
+    use std::path::Path;
+
+    /// Version of the exports above
+    pub const PLUGIN_ABI: u32 = 1;
+
+    type AbiFn = unsafe extern "C" fn() -> u32;
+    type NameFn = unsafe extern "C" fn() -> *const c_char;
+    type PollFn = unsafe extern "C" fn() -> *mut c_char;
+    type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+    pub struct DylibProvider {
+        name: String,
+        poll: PollFn,
+        free: FreeFn,
+        /// Keeps the functions above loaded
+        _library: Library,
+    }
+
+    impl DylibProvider {
+        pub fn load(path: &Path) -> Result<Self, ValidatorError> {
+            let display = path.display().to_string();
+            // Loading runs the library's initializers, like any in-process plugin
+            let library = unsafe { Library::new(path) }
+                .map_err(|e| ValidatorError::NotFound(format!("plugin {} ({})", display, e)))?;
+            let missing = |name: &str| ValidatorError::ParseFailure(format!("plugin {}: no {}", display, name));
+            // The symbols borrow the library; copy the function pointers out before storing it
+            let (name, poll, free) = unsafe {
+                let abi: Symbol<AbiFn> = library.get(b"validator_plugin_abi\0").map_err(|_| missing("validator_plugin_abi"))?;
+                let version = abi();
+                if version != PLUGIN_ABI {
+                    return Err(ValidatorError::ParseFailure(format!(
+                        "plugin {}: ABI {} (expected {})",
+                        display, version, PLUGIN_ABI
+                    )));
+                }
+                let name: Symbol<NameFn> = library.get(b"validator_plugin_name\0").map_err(|_| missing("validator_plugin_name"))?;
+                let poll: Symbol<PollFn> = library.get(b"validator_plugin_poll\0").map_err(|_| missing("validator_plugin_poll"))?;
+                let free: Symbol<FreeFn> = library.get(b"validator_plugin_free\0").map_err(|_| missing("validator_plugin_free"))?;
+                let name = match name() {
+                    name if name.is_null() => display.clone(),
+                    name => CStr::from_ptr(name).to_string_lossy().into_owned(),
+                };
+                (name, *poll, *free)
+            };
+            Ok(DylibProvider { name, poll, free, _library: library })
+        }
+    }
+
+    impl SignalProvider for DylibProvider {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn poll(&mut self) -> Vec<SignalContribution> {
+            unsafe {
+                let json = (self.poll)();
+                if json.is_null() {
+                    return Vec::new();
+                }
+                let contributions = serde_json::from_slice(CStr::from_ptr(json).to_bytes()).unwrap_or_else(|e| {
+                    log::warn!("Plugin {}: invalid contributions ({})", self.name, e);
+                    Vec::new()
+                });
+                (self.free)(json);
+                contributions
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(Vec<SignalContribution>);
+
+    impl SignalProvider for Fixed {
+        fn name(&self) -> &str {
+            "cti"
+        }
+
+        fn poll(&mut self) -> Vec<SignalContribution> {
+            self.0.clone()
+        }
+    }
+
+    struct Broken;
+
+    impl SignalProvider for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        fn poll(&mut self) -> Vec<SignalContribution> {
+            panic!("connector lost")
+        }
+    }
+
+    #[test]
+    fn test_contributions_are_named_clamped_and_isolated() {
+        let mut providers = SignalProviders::default();
+        providers.register(Box::new(Fixed(vec![
+            SignalContribution { app: Some("Zoom".to_string()), weight: 5.0, ..Default::default() },
+            SignalContribution { weight: f32::NAN, ..Default::default() },
+        ])));
+        providers.register(Box::new(Broken));
+
+        let polled = providers.poll();
+        assert_eq!(polled.len(), 1);
+        assert_eq!((polled[0].provider.as_str(), polled[0].weight), ("cti", MAX_WEIGHT));
+        assert!(polled[0].applies_to(42, &["zoom.us", "zoom"]));
+        assert!(!polled[0].applies_to(42, &["slack"]));
+        assert!(SignalContribution::default().applies_to(7, &[]));
+
+        // The panicking provider is gone, the other keeps reporting
+        assert_eq!(providers.poll().len(), 1);
+    }
+}
@@ -0,0 +1,359 @@
+// Slack huddle probe
+// Huddles don't always open a window of their own (the huddle bar docks into the main
+// window) and a listener with the mic off is huddle audio only, which the generic rules
+// take for a notification or a clip. Slack's own traces are read instead:
+//   - the huddle window, when there is one ("Huddle with …" or "… - Huddle")
+//   - the WebRTC and audio capture threads Slack's helpers run only while connected
+//     (/proc/<pid>/task/*/comm on Linux, thread descriptions on Windows)
+//   - the latest huddle join/leave line in Slack's local logs
+// A window or a join line shows a huddle on its own. The threads also run while a clip
+// is recorded, so they count only with the audio session a huddle keeps open for its
+// whole length: Slack playing without a break for HUDDLE_SESSION_SECS.
+// Slack calls are all huddles, and are reported with `kind: "huddle"`.
+
+use crate::messengers;
+use crate::platform::PlatformUtils;
+use crate::process_tree::ProcessTree;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// Thread name prefixes, lowercase and cut to Linux's 15 characters
+const HUDDLE_THREADS: &[&str] = &["audioinputdevi", "webrtc_signali", "webrtc_worker", "webrtc_network"];
+
+/// Log lines that end a huddle, lowercase; checked before the joins
+const LEAVE_MARKERS: &[&str] = &[
+    "left huddle",
+    "leave huddle",
+    "leaving huddle",
+    "huddle left",
+    "huddle ended",
+    "huddle_leave",
+    "huddle_end",
+];
+
+/// Log lines that start a huddle, lowercase
+const JOIN_MARKERS: &[&str] = &[
+    "joined huddle",
+    "join huddle",
+    "joining huddle",
+    "huddle joined",
+    "huddle started",
+    "huddle_join",
+];
+
+/// Uninterrupted playback that is a huddle's audio session rather than a sound or a clip
+const HUDDLE_SESSION_SECS: f32 = 10.0;
+
+/// Log bytes read at most per probe; a new log is read from this far before its end
+const MAX_LOG_READ: u64 = 1 << 20;
+
+/// One piece of huddle evidence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HuddleSignal {
+    /// A Slack window shows the huddle
+    Window,
+    /// Slack's log has joined a huddle and not left it
+    Log,
+    /// Slack runs WebRTC or audio capture threads
+    AudioThreads,
+}
+
+/// Huddle evidence found in the running Slack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlackHuddle {
+    /// Slack's root process
+    pub process_id: u32,
+    /// The huddle window's title, empty without one
+    pub window_title: String,
+    pub signals: Vec<HuddleSignal>,
+}
+
+impl SlackHuddle {
+    /// Whether the evidence shows a huddle, given how long Slack has been playing
+    pub fn confirms(&self, audio_active_secs: Option<f32>) -> bool {
+        self.signals.iter().any(|signal| *signal != HuddleSignal::AudioThreads)
+            || audio_active_secs.is_some_and(|secs| secs >= HUDDLE_SESSION_SECS)
+    }
+}
+
+/// Probes Slack each window-title tick; keeps its place in the log between ticks
+#[derive(Debug, Default)]
+pub struct HuddleProbe {
+    log: LogTail,
+}
+
+impl HuddleProbe {
+    /// Huddle evidence of the running Slack, if it is running and shows any
+    pub fn probe(&mut self, process_tree: &mut ProcessTree) -> Option<SlackHuddle> {
+        let pids = slack_pids(process_tree);
+        if pids.is_empty() {
+            // A restarted Slack starts a new log
+            self.log = LogTail::default();
+            return None;
+        }
+
+        let mut roots: Vec<u32> = pids.iter().map(|&pid| process_tree.root(pid)).collect();
+        roots.sort_unstable();
+        roots.dedup();
+
+        let mut signals = Vec::new();
+        let window = roots.iter().find_map(|&process_id| {
+            let title = <() as PlatformUtils>::get_window_title(process_id).ok()?;
+            messengers::title_shows_call("slack", &title).then_some((process_id, title))
+        });
+        if window.is_some() {
+            signals.push(HuddleSignal::Window);
+        }
+        if self.log.poll(&log_dirs()) {
+            signals.push(HuddleSignal::Log);
+        }
+        if pids.iter().any(|&pid| runs_huddle_threads(pid)) {
+            signals.push(HuddleSignal::AudioThreads);
+        }
+        if signals.is_empty() {
+            return None;
+        }
+
+        let (process_id, window_title) = window.unwrap_or((roots[0], String::new()));
+        Some(SlackHuddle { process_id, window_title, signals })
+    }
+}
+
+/// Whether a log line joins (true) or leaves (false) a huddle
+fn huddle_marker(line: &str) -> Option<bool> {
+    let line = line.to_lowercase();
+    if !line.contains("huddle") {
+        None
+    } else if LEAVE_MARKERS.iter().any(|marker| line.contains(marker)) {
+        Some(false)
+    } else if JOIN_MARKERS.iter().any(|marker| line.contains(marker)) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Whether a thread is one Slack runs only while in a huddle
+fn is_huddle_thread(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    HUDDLE_THREADS.iter().any(|prefix| name.starts_with(prefix))
+}
+
+/// The newest log Slack writes, followed across ticks
+#[derive(Debug, Default)]
+struct LogTail {
+    path: Option<PathBuf>,
+    /// Bytes already read, up to the last complete line
+    offset: u64,
+    joined: bool,
+}
+
+impl LogTail {
+    /// Read what was logged since the last poll; returns whether a huddle is joined
+    fn poll(&mut self, dirs: &[PathBuf]) -> bool {
+        let Some(newest) = newest_log(dirs) else {
+            *self = LogTail::default();
+            return false;
+        };
+        if self.path.as_ref() != Some(&newest) {
+            *self = LogTail { path: Some(newest.clone()), ..LogTail::default() };
+        }
+
+        let Ok(mut file) = File::open(&newest) else { return self.joined };
+        let len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if len < self.offset {
+            // Truncated and rewritten
+            self.offset = 0;
+            self.joined = false;
+        }
+        let start = self.offset.max(len.saturating_sub(MAX_LOG_READ));
+        let mut text = Vec::new();
+        if file.seek(SeekFrom::Start(start)).is_err() || file.by_ref().take(len - start).read_to_end(&mut text).is_err() {
+            return self.joined;
+        }
+
+        // A line still being written is read again next time
+        let Some(complete) = text.iter().rposition(|&byte| byte == b'\n') else { return self.joined };
+        self.offset = start + complete as u64 + 1;
+        if let Some(joined) = String::from_utf8_lossy(&text[..complete]).lines().rev().find_map(huddle_marker) {
+            self.joined = joined;
+        }
+        self.joined
+    }
+}
+
+/// The most recently written `.log` in `dirs` or their `default` profile folders
+fn newest_log(dirs: &[PathBuf]) -> Option<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| [dir.clone(), dir.join("default")])
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Slack's log folders: the direct download first, then the store and sandboxed builds
+#[cfg(target_os = "windows")]
+fn log_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(appdata) = std::env::var_os("APPDATA") {
+        dirs.push(PathBuf::from(appdata).join("Slack").join("logs"));
+    }
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        dirs.push(PathBuf::from(local).join(r"Packages\91750D7E.Slack_8she8kybcnzg4\LocalCache\Roaming\Slack\logs"));
+    }
+    dirs
+}
+
+#[cfg(target_os = "linux")]
+fn log_dirs() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else { return Vec::new() };
+    vec![
+        home.join(".config/Slack/logs"),
+        home.join("snap/slack/current/.config/Slack/logs"),
+        home.join(".var/app/com.slack.Slack/config/Slack/logs"),
+    ]
+}
+
+#[cfg(target_os = "macos")]
+fn log_dirs() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else { return Vec::new() };
+    vec![
+        home.join("Library/Application Support/Slack/logs"),
+        home.join("Library/Containers/com.tinyspeck.slackmacgap/Data/Library/Application Support/Slack/logs"),
+    ]
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn log_dirs() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Slack and its helper processes
+#[cfg(target_os = "windows")]
+fn slack_pids(process_tree: &mut ProcessTree) -> Vec<u32> {
+    process_tree
+        .pids()
+        .into_iter()
+        .filter(|&pid| process_tree.entry(pid).is_some_and(|entry| entry.name.to_lowercase().starts_with("slack")))
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn slack_pids(_process_tree: &mut ProcessTree) -> Vec<u32> {
+    let Ok(processes) = procfs::process::all_processes() else { return Vec::new() };
+    processes
+        .flatten()
+        .filter(|process| process.stat().is_ok_and(|stat| stat.comm.to_lowercase().starts_with("slack")))
+        .map(|process| process.pid as u32)
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn slack_pids(_process_tree: &mut ProcessTree) -> Vec<u32> {
+    use std::process::Command;
+
+    let Ok(output) = crate::subprocess::output(Command::new("pgrep").args(["-i", "^slack"])) else { return Vec::new() };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn slack_pids(_process_tree: &mut ProcessTree) -> Vec<u32> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn runs_huddle_threads(pid: u32) -> bool {
+    let Ok(tasks) = std::fs::read_dir(format!("/proc/{}/task", pid)) else { return false };
+    tasks
+        .flatten()
+        .any(|task| std::fs::read_to_string(task.path().join("comm")).is_ok_and(|name| is_huddle_thread(&name)))
+}
+
+#[cfg(target_os = "windows")]
+fn runs_huddle_threads(pid: u32) -> bool {
+    use windows::Win32::Foundation::{CloseHandle, LocalFree, HLOCAL};
+    use windows::Win32::System::Diagnostics::ToolHelp::*;
+    use windows::Win32::System::Threading::{GetThreadDescription, OpenThread, THREAD_QUERY_LIMITED_INFORMATION};
+
+    let mut found = false;
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) else { return false };
+
+        let mut thread = THREADENTRY32 {
+            dwSize: std::mem::size_of::<THREADENTRY32>() as u32,
+            ..Default::default()
+        };
+
+        let mut more = Thread32First(snapshot, &mut thread).is_ok();
+        while more && !found {
+            if thread.th32OwnerProcessID == pid {
+                if let Ok(handle) = OpenThread(THREAD_QUERY_LIMITED_INFORMATION, false, thread.th32ThreadID) {
+                    if let Ok(description) = GetThreadDescription(handle) {
+                        found = description.to_string().is_ok_and(|name| is_huddle_thread(&name));
+                        let _ = LocalFree(HLOCAL(description.0 as *mut _));
+                    }
+                    let _ = CloseHandle(handle);
+                }
+            }
+            more = Thread32Next(snapshot, &mut thread).is_ok();
+        }
+
+        let _ = CloseHandle(snapshot);
+    }
+    found
+}
+
+/// Thread names are not read on other platforms
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn runs_huddle_threads(_pid: u32) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_huddle_evidence_from_threads_and_logs() {
+        assert!(is_huddle_thread("AudioInputDevi\n"));
+        assert!(is_huddle_thread("WebRTC_Signaling"));
+        assert!(!is_huddle_thread("AudioOutputDevi"));
+
+        // Threads alone may be a clip being recorded
+        let threads = SlackHuddle { process_id: 7, window_title: String::new(), signals: vec![HuddleSignal::AudioThreads] };
+        assert!(!threads.confirms(None));
+        assert!(!threads.confirms(Some(2.0)));
+        assert!(threads.confirms(Some(45.0)));
+        assert!(SlackHuddle { signals: vec![HuddleSignal::Log], ..threads }.confirms(None));
+
+        let dir = std::env::temp_dir().join(format!("slack-huddle-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("default")).unwrap();
+        let path = dir.join("default").join("webapp-console.log");
+        let mut log = File::create(&path).unwrap();
+        let mut tail = LogTail::default();
+        assert!(!tail.poll(&[dir.clone()]));
+
+        writeln!(log, "[INFO] Huddles: joined huddle in C0123").unwrap();
+        write!(log, "[INFO] Huddles: left hud").unwrap();
+        assert!(tail.poll(&[dir.clone()]));
+
+        // The leave line is read once complete
+        writeln!(log, "dle").unwrap();
+        assert!(!tail.poll(&[dir.clone()]));
+        writeln!(log, "[INFO] unrelated line").unwrap();
+        assert!(!tail.poll(&[dir.clone()]));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
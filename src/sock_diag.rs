@@ -0,0 +1,277 @@
+// Netlink sock_diag (INET_DIAG) socket listing for Linux
+// Asks the kernel for its UDP/TCP socket tables directly instead of parsing `ss`,
+// whose output changes between iproute2 versions and which may not be installed.
+// The kernel reports each socket's inode; owners are found by matching inodes
+// against the socket links in /proc/<pid>/fd.
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+const TCP_ESTABLISHED: u8 = 1;
+const ALL_STATES: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Udp,
+    Tcp,
+}
+
+/// One kernel socket as reported by INET_DIAG
+#[derive(Debug, Clone)]
+pub struct Socket {
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    pub established: bool,
+    pub inode: u64,
+}
+
+// linux/inet_diag.h
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct InetDiagSockId {
+    sport: u16,
+    dport: u16,
+    src: [u32; 4],
+    dst: [u32; 4],
+    interface: u32,
+    cookie: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct InetDiagReqV2 {
+    family: u8,
+    protocol: u8,
+    ext: u8,
+    pad: u8,
+    states: u32,
+    id: InetDiagSockId,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct InetDiagMsg {
+    family: u8,
+    state: u8,
+    timer: u8,
+    retrans: u8,
+    id: InetDiagSockId,
+    expires: u32,
+    rqueue: u32,
+    wqueue: u32,
+    uid: u32,
+    inode: u32,
+}
+
+#[repr(C)]
+struct Request {
+    header: libc::nlmsghdr,
+    body: InetDiagReqV2,
+}
+
+/// All IPv4 and IPv6 sockets of `protocol`; TCP is limited to established connections
+pub fn sockets(protocol: Protocol) -> Result<Vec<Socket>, String> {
+    let mut sockets = Vec::new();
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        dump(family as u8, protocol, &mut sockets)?;
+    }
+    Ok(sockets)
+}
+
+/// Owning pid of each socket inode in `inodes`
+/// A socket shared across fork belongs to whichever process is found first.
+pub fn inode_owners(inodes: &HashSet<u64>) -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    if inodes.is_empty() {
+        return owners;
+    }
+
+    let Ok(processes) = procfs::process::all_processes() else {
+        return owners;
+    };
+    for process in processes.flatten() {
+        // Other users' fds are unreadable without privileges; skip them
+        let Ok(fds) = process.fd() else { continue };
+        for fd in fds.flatten() {
+            if let procfs::process::FDTarget::Socket(inode) = fd.target {
+                if inodes.contains(&inode) {
+                    owners.entry(inode).or_insert(process.pid as u32);
+                }
+            }
+        }
+        if owners.len() == inodes.len() {
+            break;
+        }
+    }
+    owners
+}
+
+fn dump(family: u8, protocol: Protocol, sockets: &mut Vec<Socket>) -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_SOCK_DIAG) };
+    if fd < 0 {
+        return Err(format!("netlink socket: {}", std::io::Error::last_os_error()));
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let request = Request {
+        header: libc::nlmsghdr {
+            nlmsg_len: std::mem::size_of::<Request>() as u32,
+            nlmsg_type: SOCK_DIAG_BY_FAMILY,
+            nlmsg_flags: (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16,
+            nlmsg_seq: 1,
+            nlmsg_pid: 0,
+        },
+        body: InetDiagReqV2 {
+            family,
+            protocol: match protocol {
+                Protocol::Udp => libc::IPPROTO_UDP as u8,
+                Protocol::Tcp => libc::IPPROTO_TCP as u8,
+            },
+            states: match protocol {
+                Protocol::Udp => ALL_STATES,
+                Protocol::Tcp => 1 << TCP_ESTABLISHED,
+            },
+            ..Default::default()
+        },
+    };
+
+    let sent = unsafe {
+        libc::send(
+            fd.as_raw_fd(),
+            &request as *const Request as *const libc::c_void,
+            std::mem::size_of::<Request>(),
+            0,
+        )
+    };
+    if sent < 0 {
+        return Err(format!("netlink send: {}", std::io::Error::last_os_error()));
+    }
+
+    let mut buffer = vec![0u8; 32 * 1024];
+    loop {
+        let received = unsafe { libc::recv(fd.as_raw_fd(), buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), 0) };
+        if received < 0 {
+            return Err(format!("netlink recv: {}", std::io::Error::last_os_error()));
+        }
+        if parse_messages(&buffer[..received as usize], sockets)? {
+            return Ok(());
+        }
+    }
+}
+
+/// Parse one datagram of netlink messages; true once the dump is complete
+fn parse_messages(mut data: &[u8], sockets: &mut Vec<Socket>) -> Result<bool, String> {
+    let header_len = std::mem::size_of::<libc::nlmsghdr>();
+
+    while data.len() >= header_len {
+        let header: libc::nlmsghdr = unsafe { std::ptr::read_unaligned(data.as_ptr() as *const _) };
+        let len = header.nlmsg_len as usize;
+        if len < header_len || len > data.len() {
+            return Err("Truncated netlink message".to_string());
+        }
+
+        match header.nlmsg_type as i32 {
+            libc::NLMSG_DONE => return Ok(true),
+            libc::NLMSG_ERROR => {
+                let errno = data
+                    .get(header_len..header_len + 4)
+                    .map(|bytes| i32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    .unwrap_or(0);
+                return Err(format!("sock_diag: {}", std::io::Error::from_raw_os_error(-errno)));
+            }
+            _ if len >= header_len + std::mem::size_of::<InetDiagMsg>() => {
+                let message: InetDiagMsg = unsafe { std::ptr::read_unaligned(data[header_len..].as_ptr() as *const _) };
+                if let Some(socket) = socket_from(&message) {
+                    sockets.push(socket);
+                }
+            }
+            _ => {}
+        }
+
+        // Messages are padded to 4-byte boundaries
+        let aligned = (len + 3) & !3;
+        data = data.get(aligned..).unwrap_or_default();
+    }
+
+    Ok(false)
+}
+
+fn socket_from(message: &InetDiagMsg) -> Option<Socket> {
+    // Addresses and ports are in network byte order
+    let address = |words: [u32; 4]| -> Option<IpAddr> {
+        match message.family as i32 {
+            libc::AF_INET => Some(IpAddr::V4(Ipv4Addr::from(words[0].to_ne_bytes()))),
+            libc::AF_INET6 => {
+                let mut octets = [0u8; 16];
+                for (chunk, word) in octets.chunks_exact_mut(4).zip(words) {
+                    chunk.copy_from_slice(&word.to_ne_bytes());
+                }
+                Some(IpAddr::V6(Ipv6Addr::from(octets)))
+            }
+            _ => None,
+        }
+    };
+
+    Some(Socket {
+        local: SocketAddr::new(address(message.id.src)?, u16::from_be(message.id.sport)),
+        remote: SocketAddr::new(address(message.id.dst)?, u16::from_be(message.id.dport)),
+        established: message.state == TCP_ESTABLISHED,
+        inode: message.inode as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_messages_reads_diag_and_done() {
+        let header_len = std::mem::size_of::<libc::nlmsghdr>();
+        let message_len = header_len + std::mem::size_of::<InetDiagMsg>();
+
+        let mut message = InetDiagMsg {
+            family: libc::AF_INET as u8,
+            state: TCP_ESTABLISHED,
+            timer: 0,
+            retrans: 0,
+            id: InetDiagSockId::default(),
+            expires: 0,
+            rqueue: 0,
+            wqueue: 0,
+            uid: 0,
+            inode: 4242,
+        };
+        message.id.sport = 50000u16.to_be();
+        message.id.dport = 3478u16.to_be();
+        message.id.dst[0] = u32::from_ne_bytes([10, 0, 0, 7]);
+
+        let header = |nlmsg_type: u16, len: usize| libc::nlmsghdr {
+            nlmsg_len: len as u32,
+            nlmsg_type,
+            nlmsg_flags: 0,
+            nlmsg_seq: 1,
+            nlmsg_pid: 0,
+        };
+        fn bytes<T>(value: &T) -> Vec<u8> {
+            unsafe { std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>()).to_vec() }
+        }
+
+        let mut data = bytes(&header(SOCK_DIAG_BY_FAMILY, message_len));
+        data.extend(bytes(&message));
+        data.resize((data.len() + 3) & !3, 0);
+
+        let mut sockets = Vec::new();
+        assert!(!parse_messages(&data, &mut sockets).unwrap());
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].local.port(), 50000);
+        assert_eq!(sockets[0].remote, "10.0.0.7:3478".parse().unwrap());
+        assert!(sockets[0].established);
+        assert_eq!(sockets[0].inode, 4242);
+
+        let mut done = bytes(&header(libc::NLMSG_DONE as u16, header_len + 4));
+        done.extend([0u8; 4]);
+        assert!(parse_messages(&done, &mut sockets).unwrap());
+    }
+}
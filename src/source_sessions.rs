@@ -0,0 +1,107 @@
+// Playback sessions of the audio sources beside the call
+// `other_audio_sources` is a per-tick list, so telling how long Spotify played meant
+// stitching ticks back together. Each source there carries a `session` instead: an id
+// that stays the same while the source keeps playing, and when it was first and last
+// seen. A source gone for less than SESSION_GAP (a track change, a paused video) carries
+// on with the same session; after that it comes back with a new id.
+
+use crate::output;
+use crate::AudioSource;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// Silence a source may have before its session ends
+const SESSION_GAP: Duration = Duration::from_secs(30);
+
+/// One source's playback, as reported on `other_audio_sources`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceSession {
+    /// Unique for the life of the monitor
+    pub id: u64,
+    /// RFC3339 in UTC
+    pub first_seen_utc: String,
+    pub last_seen_utc: String,
+    /// From first to last seen, gaps included
+    pub duration_secs: u64,
+}
+
+struct Open {
+    id: u64,
+    first_seen: SystemTime,
+    last_seen: SystemTime,
+}
+
+/// Sessions of the sources seen within the last SESSION_GAP, by process and name
+#[derive(Default)]
+pub struct SourceSessions {
+    open: HashMap<(u32, String), Open>,
+    last_id: u64,
+}
+
+impl SourceSessions {
+    /// Attach its session to each of this tick's `sources`
+    pub fn update(&mut self, sources: &mut [AudioSource], now: SystemTime) {
+        self.open
+            .retain(|_, open| now.duration_since(open.last_seen).unwrap_or_default() < SESSION_GAP);
+        let last_id = &mut self.last_id;
+        for source in sources.iter_mut() {
+            let open = self.open.entry((source.process_id, source.name.clone())).or_insert_with(|| {
+                *last_id += 1;
+                Open { id: *last_id, first_seen: now, last_seen: now }
+            });
+            open.last_seen = now;
+            source.session = Some(SourceSession {
+                id: open.id,
+                first_seen_utc: output::utc_timestamp(open.first_seen),
+                last_seen_utc: output::utc_timestamp(now),
+                duration_secs: now.duration_since(open.first_seen).unwrap_or_default().as_secs(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(name: &str, process_id: u32) -> AudioSource {
+        AudioSource {
+            name: name.to_string(),
+            process_id,
+            window_title: String::new(),
+            detected_app: None,
+            private_context: false,
+            is_virtual_device: false,
+            classification: None,
+            session: None,
+        }
+    }
+
+    #[test]
+    fn test_sessions_span_ticks_and_short_gaps() {
+        let mut sessions = SourceSessions::default();
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let tick = |sessions: &mut SourceSessions, names: &[(&str, u32)], now| {
+            let mut sources: Vec<AudioSource> = names.iter().map(|&(name, pid)| source(name, pid)).collect();
+            sessions.update(&mut sources, now);
+            sources.into_iter().map(|source| source.session.unwrap()).collect::<Vec<_>>()
+        };
+
+        let first = tick(&mut sessions, &[("Spotify.exe", 5), ("chrome", 9)], at(0));
+        assert_ne!(first[0].id, first[1].id);
+
+        // Spotify skips a few ticks between tracks and keeps its session
+        tick(&mut sessions, &[("chrome", 9)], at(10));
+        let later = tick(&mut sessions, &[("Spotify.exe", 5)], at(25));
+        assert_eq!(later[0].id, first[0].id);
+        assert_eq!(later[0].duration_secs, 25);
+        assert_eq!(later[0].first_seen_utc, output::utc_timestamp(at(0)));
+
+        // Chrome was gone for longer than the gap and starts over
+        let again = tick(&mut sessions, &[("chrome", 9)], at(2701));
+        assert!(again[0].id > later[0].id);
+        assert_eq!(again[0].duration_secs, 0);
+    }
+}
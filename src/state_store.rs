@@ -0,0 +1,107 @@
+// Shared state store
+// The monitor loop publishes each tick's state (already privacy-filtered and redacted)
+// and health here once; the IPC server, the gRPC API, stdin commands and any exporter
+// read the latest snapshot through a StateReader instead of keeping their own copy.
+// A publish swaps in a new Arc, so a reader never blocks the loop for longer than a
+// pointer copy and always sees a state and health from the same tick.
+
+use crate::control::HealthReport;
+use crate::MonitorState;
+use std::sync::{Arc, RwLock};
+
+/// Everything one tick published
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub state: MonitorState,
+    /// None before the first tick
+    pub health: Option<HealthReport>,
+}
+
+type Shared = Arc<RwLock<Arc<Snapshot>>>;
+
+/// Written by the monitor loop only
+pub struct StateStore {
+    shared: Shared,
+}
+
+/// Cheap to clone and safe to send to server threads
+#[derive(Clone)]
+pub struct StateReader {
+    shared: Shared,
+}
+
+impl StateStore {
+    /// Starts out with `initial` (a resumed call, or nothing)
+    pub fn new(initial: MonitorState) -> Self {
+        let snapshot = Snapshot { state: initial, health: None };
+        StateStore { shared: Arc::new(RwLock::new(Arc::new(snapshot))) }
+    }
+
+    /// Replace the snapshot; returns the one just published
+    pub fn publish(&self, state: MonitorState, health: HealthReport) -> Arc<Snapshot> {
+        let snapshot = Arc::new(Snapshot { state, health: Some(health) });
+        *self.shared.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::clone(&snapshot);
+        snapshot
+    }
+
+    pub fn reader(&self) -> StateReader {
+        StateReader { shared: Arc::clone(&self.shared) }
+    }
+}
+
+impl StateReader {
+    /// The latest published snapshot
+    pub fn snapshot(&self) -> Arc<Snapshot> {
+        Arc::clone(&self.shared.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capability::CapabilityLevel;
+    use crate::environment::Environment;
+    use crate::power::PowerProfile;
+    use crate::scheduler::Pace;
+    use std::thread;
+
+    fn idle() -> MonitorState {
+        MonitorState {
+            active_call: None,
+            other_audio_sources: Vec::new(),
+            environment: Environment::Local,
+            tracked_apps: None,
+            external_calls: Vec::new(),
+        }
+    }
+
+    fn health() -> HealthReport {
+        HealthReport {
+            run_mode: "monitoring",
+            pace: Pace::Idle,
+            power: PowerProfile::default(),
+            power_saving: false,
+            errors: 0,
+            failing: Vec::new(),
+            backend_errors: Default::default(),
+            capability_level: CapabilityLevel::Full,
+            capability_reason: None,
+            environment: Environment::Local,
+            dropped_messages: 0,
+        }
+    }
+
+    #[test]
+    fn test_readers_see_the_latest_tick() {
+        let store = StateStore::new(idle());
+        let reader = store.reader();
+        let held = reader.snapshot();
+        assert!(held.health.is_none());
+
+        store.publish(idle(), health());
+        // A snapshot already taken stays as it was
+        assert!(held.health.is_none());
+        let published = thread::spawn(move || reader.snapshot().health.is_some()).join().unwrap();
+        assert!(published);
+    }
+}
@@ -0,0 +1,154 @@
+// Non-blocking stdout for NDJSON
+// A parent that stops reading (an Electron main process busy rendering) fills the pipe,
+// and a plain println! then blocks the monitor loop until it drains. Every stdout line
+// goes through a bounded queue instead, written out by its own thread. When the queue is
+// full the oldest snapshot (a `state` or `detection` line) gives way, since the next one
+// supersedes it; events, command replies and pings are never dropped. Dropped lines are
+// counted in `health` as `dropped_messages`.
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// Lines waiting for the writer before snapshots start being dropped
+const CAPACITY: usize = 256;
+
+struct Queued {
+    line: String,
+    /// Superseded by the next snapshot, so it may be dropped
+    snapshot: bool,
+}
+
+/// Lines not yet written, bounded for snapshots only
+struct Queue {
+    lines: VecDeque<Queued>,
+    capacity: usize,
+    /// Set once stdout is gone; later lines are dropped
+    closed: bool,
+}
+
+impl Queue {
+    /// Queue `line`; returns how many lines were dropped to make room
+    fn push(&mut self, line: String, snapshot: bool) -> u64 {
+        if self.closed {
+            return 1;
+        }
+        let mut dropped = 0;
+        if self.lines.len() >= self.capacity {
+            match self.lines.iter().position(|queued| queued.snapshot) {
+                Some(oldest) => {
+                    self.lines.remove(oldest);
+                    dropped = 1;
+                }
+                // Only events are waiting: the new snapshot gives way, events still queue
+                None if snapshot => return 1,
+                None => {}
+            }
+        }
+        self.lines.push_back(Queued { line, snapshot });
+        dropped
+    }
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    ready: Condvar,
+    dropped: AtomicU64,
+}
+
+/// Handle to the writer thread; clones share it
+#[derive(Clone)]
+pub struct StdoutWriter {
+    shared: Arc<Shared>,
+}
+
+impl StdoutWriter {
+    pub fn start() -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue { lines: VecDeque::new(), capacity: CAPACITY, closed: false }),
+            ready: Condvar::new(),
+            dropped: AtomicU64::new(0),
+        });
+
+        let writer = Arc::clone(&shared);
+        thread::spawn(move || {
+            let stdout = io::stdout();
+            loop {
+                let batch: Vec<Queued> = {
+                    let mut queue = writer.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    while queue.lines.is_empty() {
+                        queue = writer.ready.wait(queue).unwrap_or_else(|poisoned| poisoned.into_inner());
+                    }
+                    queue.lines.drain(..).collect()
+                };
+
+                let mut out = stdout.lock();
+                let written = batch.iter().try_for_each(|queued| writeln!(out, "{}", queued.line)).and_then(|_| out.flush());
+                if let Err(e) = written {
+                    // The reader is gone; nothing written from here on would arrive
+                    log::warn!("stdout closed ({}), dropping output", e);
+                    writer.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).closed = true;
+                    writer.dropped.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                    return;
+                }
+            }
+        });
+
+        StdoutWriter { shared }
+    }
+
+    /// Write an event, command reply or ping; never dropped while stdout is open
+    pub fn send(&self, line: String) {
+        self.push(line, false);
+    }
+
+    /// Write a snapshot, which may be dropped for a newer one when the reader falls behind
+    pub fn send_snapshot(&self, line: String) {
+        self.push(line, true);
+    }
+
+    /// Lines dropped since start-up
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    fn push(&self, line: String, snapshot: bool) {
+        let dropped = self.shared.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(line, snapshot);
+        if dropped > 0 {
+            self.shared.dropped.fetch_add(dropped, Ordering::Relaxed);
+        }
+        self.shared.ready.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_queue_drops_oldest_snapshot_only() {
+        let mut queue = Queue { lines: VecDeque::new(), capacity: 3, closed: false };
+        assert_eq!(queue.push("state 1".to_string(), true), 0);
+        assert_eq!(queue.push("call_started".to_string(), false), 0);
+        assert_eq!(queue.push("state 2".to_string(), true), 0);
+
+        // The oldest snapshot makes room, even for another snapshot
+        assert_eq!(queue.push("state 3".to_string(), true), 1);
+        assert_eq!(queue.push("call_ended".to_string(), false), 1);
+        let lines: Vec<&str> = queue.lines.iter().map(|queued| queued.line.as_str()).collect();
+        assert_eq!(lines, ["call_started", "state 3", "call_ended"]);
+
+        // With only events left, snapshots give way and events still queue
+        assert_eq!(queue.push("state 4".to_string(), true), 1);
+        assert_eq!(queue.push("pong".to_string(), false), 1);
+        assert_eq!(queue.push("state 5".to_string(), true), 1);
+        assert_eq!(queue.push("source_added".to_string(), false), 0);
+        let lines: Vec<&str> = queue.lines.iter().map(|queued| queued.line.as_str()).collect();
+        assert_eq!(lines, ["call_started", "call_ended", "pong", "source_added"]);
+
+        queue.closed = true;
+        assert_eq!(queue.push("call_started".to_string(), false), 1);
+    }
+}
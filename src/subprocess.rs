@@ -0,0 +1,58 @@
+// Central gate for spawning external programs
+// With --no-subprocess every data collection path must use native APIs only;
+// anything that would shell out fails with PermissionDenied instead, so security
+// teams get a clear behavioral contract for the worker.
+
+use std::collections::HashSet;
+use std::io;
+use std::process::{Child, Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static SUBPROCESS_DISABLED: AtomicBool = AtomicBool::new(false);
+static REPORTED_PROGRAMS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Forbid (or allow) spawning external programs for the rest of the process lifetime
+pub fn set_disabled(disabled: bool) {
+    SUBPROCESS_DISABLED.store(disabled, Ordering::SeqCst);
+}
+
+pub fn is_disabled() -> bool {
+    SUBPROCESS_DISABLED.load(Ordering::SeqCst)
+}
+
+/// Drop-in replacement for `Command::output()` that honours --no-subprocess
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    check_allowed(command)?;
+    command.output()
+}
+
+/// Drop-in replacement for `Command::spawn()` that honours --no-subprocess
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn spawn(command: &mut Command) -> io::Result<Child> {
+    check_allowed(command)?;
+    command.spawn()
+}
+
+fn check_allowed(command: &Command) -> io::Result<()> {
+    if is_disabled() {
+        let program = command.get_program().to_string_lossy().to_string();
+        report_blocked(&program);
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("'{}' not run: subprocesses are disabled (--no-subprocess)", program),
+        ));
+    }
+    Ok(())
+}
+
+/// Log each blocked program once so the degraded capability is visible without flooding
+fn report_blocked(program: &str) {
+    let mut reported = REPORTED_PROGRAMS.lock().unwrap();
+    if reported.get_or_insert_with(HashSet::new).insert(program.to_string()) {
+        log::warn!(
+            "--no-subprocess: capability backed by '{}' is unavailable",
+            program
+        );
+    }
+}
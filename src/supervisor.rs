@@ -0,0 +1,140 @@
+// Subsystem isolation
+// A panic inside one backend (malformed netstat output, a platform API returning
+// something unexpected) must not take the whole monitor down. validator.rs runs every
+// subsystem poll under catch_unwind: a panic counts as an error, the subsystem reports
+// nothing and its backend is restarted (AudioBackend::restart for the mic and audio polls,
+// NetworkSource::restart for the network one), then it is retried after an exponential
+// backoff (1s, 2s, 4s ... up to a minute). A poll that succeeds resets the backoff.
+// Error counts, the subsystems backing off and the last error each backend returned
+// (error.rs) are part of the `health` report.
+
+use crate::error::{ErrorKind, ValidatorError};
+use crate::scheduler::Subsystem;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Why a supervised poll produced nothing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// The poll panicked just now
+    Panicked,
+    /// Still waiting out the backoff after an earlier panic
+    BackingOff,
+}
+
+/// The last error a backend returned, for `health`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackendError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SubsystemState {
+    errors: u64,
+    failures_in_row: u32,
+    retry_at: Option<Instant>,
+    last_error: Option<ValidatorError>,
+}
+
+/// Panic counts and backoff of each subsystem
+#[derive(Debug, Default)]
+pub struct Supervisor {
+    states: [SubsystemState; 4],
+}
+
+impl Supervisor {
+    /// Run one poll of `subsystem`, unless it is backing off
+    pub fn run<T>(&mut self, subsystem: Subsystem, now: Instant, poll: impl FnOnce() -> T) -> Result<T, Failure> {
+        let state = &mut self.states[subsystem as usize];
+        if state.retry_at.is_some_and(|retry_at| now < retry_at) {
+            return Err(Failure::BackingOff);
+        }
+
+        match panic::catch_unwind(AssertUnwindSafe(poll)) {
+            Ok(result) => {
+                state.failures_in_row = 0;
+                state.retry_at = None;
+                Ok(result)
+            }
+            Err(_) => {
+                state.errors += 1;
+                state.failures_in_row += 1;
+                let backoff = BACKOFF_BASE
+                    .saturating_mul(2u32.saturating_pow(state.failures_in_row - 1))
+                    .min(BACKOFF_MAX);
+                state.retry_at = Some(now + backoff);
+                log::warn!(
+                    "{} subsystem panicked; restarting it in {}s",
+                    subsystem.name(),
+                    backoff.as_secs()
+                );
+                Err(Failure::Panicked)
+            }
+        }
+    }
+
+    /// Keep the error a backend returned in a poll that did not panic; None clears it
+    pub fn record(&mut self, subsystem: Subsystem, error: Option<ValidatorError>) {
+        self.states[subsystem as usize].last_error = error;
+    }
+
+    /// Panics caught across all subsystems
+    pub fn errors(&self) -> u64 {
+        self.states.iter().map(|state| state.errors).sum()
+    }
+
+    /// Subsystems waiting out a backoff
+    pub fn failing(&self) -> Vec<&'static str> {
+        Subsystem::ALL
+            .into_iter()
+            .filter(|&subsystem| self.states[subsystem as usize].failures_in_row > 0)
+            .map(Subsystem::name)
+            .collect()
+    }
+
+    /// Subsystems whose backend returned an error in its last poll
+    pub fn backend_errors(&self) -> BTreeMap<&'static str, BackendError> {
+        Subsystem::ALL
+            .into_iter()
+            .filter_map(|subsystem| {
+                let error = self.states[subsystem as usize].last_error.as_ref()?;
+                Some((subsystem.name(), BackendError { kind: error.kind(), message: error.to_string() }))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panics_back_off_and_recover() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut supervisor = Supervisor::default();
+        let malformed = || -> u32 { panic!("malformed netstat line") };
+
+        assert_eq!(supervisor.run(Subsystem::Network, at(0), malformed), Err(Failure::Panicked));
+        assert_eq!(supervisor.run(Subsystem::Audio, at(0), || 1), Ok(1), "other subsystems keep running");
+        assert_eq!(supervisor.run(Subsystem::Network, at(0), || 2), Err(Failure::BackingOff));
+        assert_eq!(supervisor.run(Subsystem::Network, at(1), malformed), Err(Failure::Panicked));
+        assert_eq!(supervisor.run(Subsystem::Network, at(2), || 3), Err(Failure::BackingOff), "backoff doubled");
+        assert_eq!(supervisor.failing(), vec!["network"]);
+
+        assert_eq!(supervisor.run(Subsystem::Network, at(3), || 4), Ok(4));
+        assert!(supervisor.failing().is_empty());
+        assert_eq!(supervisor.errors(), 2);
+
+        supervisor.record(Subsystem::Audio, Some(ValidatorError::BackendUnavailable("PulseAudio".to_string())));
+        assert_eq!(supervisor.backend_errors()["audio"].kind, ErrorKind::BackendUnavailable);
+        supervisor.record(Subsystem::Audio, None);
+        assert!(supervisor.backend_errors().is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+// Microsoft Teams call state (`teams` feature)
+// Teams often plays call audio from a child process and relays media over paths the UDP
+// heuristic misses, so Teams itself is asked instead. Two sources:
+//
+// - Local client API (`--teams local`): the desktop client's third-party device API on
+//   ws://127.0.0.1:8124 pushes `meetingUpdate` messages with `isInMeeting`. It has to be
+//   enabled under Settings > Privacy > Manage API; the pairing token Teams hands out is
+//   kept in `--teams-token-file` so later runs connect without pairing again.
+// - Graph presence (`--teams graph`): polls /me/presence with a user-provided token
+//   (env `VALIDATOR_TEAMS_GRAPH_TOKEN`, needs Presence.Read); `InACall` and
+//   `InAConferenceCall` count as in a call.
+//
+// The result feeds the engine as `teams_in_call`, which overrides scoring for Teams.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const LOCAL_API_URL: &str = "ws://127.0.0.1:8124";
+const GRAPH_PRESENCE_URL: &str = "https://graph.microsoft.com/v1.0/me/presence";
+const GRAPH_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// A Graph answer older than this no longer says anything
+const GRAPH_STALE_AFTER: Duration = Duration::from_secs(30);
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Where the Teams call state comes from
+#[derive(Debug, Clone)]
+pub enum TeamsSource {
+    LocalClient { token_file: PathBuf },
+    Graph { token: String },
+}
+
+/// Latest known state; None when Teams is not reachable
+type Shared = Arc<Mutex<Option<(bool, Instant)>>>;
+
+pub struct TeamsPresence {
+    state: Shared,
+    stale_after: Option<Duration>,
+}
+
+impl TeamsPresence {
+    /// Start following Teams in a background thread
+    pub fn start(source: TeamsSource) -> Self {
+        let state: Shared = Arc::new(Mutex::new(None));
+        let shared = Arc::clone(&state);
+
+        let stale_after = match source {
+            TeamsSource::LocalClient { token_file } => {
+                thread::spawn(move || follow_local_client(&token_file, &shared));
+                // The socket pushes every change and clears the state when it drops
+                None
+            }
+            TeamsSource::Graph { token } => {
+                thread::spawn(move || poll_graph(&token, &shared));
+                Some(GRAPH_STALE_AFTER)
+            }
+        };
+
+        TeamsPresence { state, stale_after }
+    }
+
+    /// Whether Teams says it is in a call; None when unknown
+    pub fn in_call(&self) -> Option<bool> {
+        let state = *self.state.lock().ok()?;
+        state
+            .filter(|(_, at)| self.stale_after.map_or(true, |stale_after| at.elapsed() < stale_after))
+            .map(|(in_call, _)| in_call)
+    }
+}
+
+fn set(state: &Shared, value: Option<bool>) {
+    if let Ok(mut state) = state.lock() {
+        *state = value.map(|in_call| (in_call, Instant::now()));
+    }
+}
+
+fn follow_local_client(token_file: &Path, state: &Shared) {
+    let mut reported_error = false;
+
+    loop {
+        let token = fs::read_to_string(token_file).unwrap_or_default();
+        let url = format!(
+            "{}?token={}&protocol-version=2.0.0&manufacturer=rust-audio-validator&device=rust-audio-validator&app=rust-audio-validator&app-version={}",
+            LOCAL_API_URL,
+            token.trim(),
+            env!("CARGO_PKG_VERSION")
+        );
+
+        match tungstenite::connect(url.as_str()) {
+            Ok((mut socket, _)) => {
+                log::info!("Connected to the local Teams client API");
+                reported_error = false;
+
+                while let Ok(message) = socket.read() {
+                    let tungstenite::Message::Text(text) = message else { continue };
+                    let update = parse_local_message(&text);
+                    if let Some(token) = update.token_refresh {
+                        if let Err(e) = fs::write(token_file, token) {
+                            log::warn!("Cannot save pairing token to {}: {}", token_file.display(), e);
+                        }
+                    }
+                    if let Some(in_meeting) = update.in_meeting {
+                        set(state, Some(in_meeting));
+                    }
+                }
+                log::info!("Local Teams client API disconnected");
+            }
+            Err(e) => {
+                if !reported_error {
+                    log::warn!("Local Teams client API unavailable: {}", e);
+                    reported_error = true;
+                }
+            }
+        }
+
+        set(state, None);
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+fn poll_graph(token: &str, state: &Shared) {
+    let mut last_error: Option<String> = None;
+
+    loop {
+        let result = ureq::get(GRAPH_PRESENCE_URL)
+            .set("Authorization", &format!("Bearer {}", token))
+            .timeout(Duration::from_secs(10))
+            .call()
+            .map_err(|e| e.to_string())
+            .and_then(|response| response.into_string().map_err(|e| e.to_string()))
+            .and_then(|body| parse_graph_presence(&body));
+
+        match result {
+            Ok(in_call) => {
+                last_error = None;
+                set(state, Some(in_call));
+            }
+            // Report each new failure once; an expired token fails every poll
+            Err(e) => {
+                if last_error.as_deref() != Some(e.as_str()) {
+                    log::warn!("Graph presence request failed: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        thread::sleep(GRAPH_POLL_INTERVAL);
+    }
+}
+
+/// What one local API message tells us
+#[derive(Debug, Default, PartialEq)]
+struct LocalUpdate {
+    token_refresh: Option<String>,
+    in_meeting: Option<bool>,
+}
+
+fn parse_local_message(text: &str) -> LocalUpdate {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Message {
+        token_refresh: Option<String>,
+        meeting_update: Option<MeetingUpdate>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct MeetingUpdate {
+        meeting_state: Option<MeetingState>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct MeetingState {
+        is_in_meeting: bool,
+    }
+
+    match serde_json::from_str::<Message>(text) {
+        Ok(message) => LocalUpdate {
+            token_refresh: message.token_refresh,
+            in_meeting: message
+                .meeting_update
+                .and_then(|update| update.meeting_state)
+                .map(|state| state.is_in_meeting),
+        },
+        Err(_) => LocalUpdate::default(),
+    }
+}
+
+fn parse_graph_presence(body: &str) -> Result<bool, String> {
+    #[derive(Deserialize)]
+    struct Presence {
+        activity: String,
+    }
+
+    let presence: Presence = serde_json::from_str(body).map_err(|e| format!("invalid presence: {}", e))?;
+    Ok(matches!(presence.activity.as_str(), "InACall" | "InAConferenceCall"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_teams_messages() {
+        let update = parse_local_message(
+            r#"{"meetingUpdate":{"meetingState":{"isMuted":false,"isInMeeting":true,"isVideoOn":false}}}"#,
+        );
+        assert_eq!(update, LocalUpdate { token_refresh: None, in_meeting: Some(true) });
+        assert_eq!(
+            parse_local_message(r#"{"tokenRefresh":"abc-123"}"#).token_refresh.as_deref(),
+            Some("abc-123")
+        );
+        assert_eq!(parse_local_message("not json"), LocalUpdate::default());
+
+        assert_eq!(parse_graph_presence(r#"{"availability":"Busy","activity":"InAConferenceCall"}"#), Ok(true));
+        assert_eq!(parse_graph_presence(r#"{"availability":"Busy","activity":"InAMeeting"}"#), Ok(false));
+    }
+}
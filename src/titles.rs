@@ -0,0 +1,132 @@
+// Locale-independent window title matching for the `window_title` rule
+// Titles are NFKC-normalized and lowercased (full-width letters, decomposed accents and
+// `｜` all compare equal to their plain forms), then confirmed either by app-specific
+// title structure, which holds in every UI language, or by a per-language keyword table.
+// `scoring.title_keywords` replaces a language's built-in keywords or adds a language:
+//
+//   "title_keywords": { "de": ["besprechung", "telefonkonferenz"], "sv": ["möte"] }
+
+use std::collections::BTreeMap;
+use unicode_normalization::UnicodeNormalization;
+
+/// Built-in meeting keywords per language, already normalized
+const KEYWORDS: &[(&str, &[&str])] = &[
+    ("en", &["meeting", "call with", "video call", "conference", " meet "]),
+    ("de", &["besprechung", "anruf mit", "videoanruf", "konferenz"]),
+    ("fr", &["réunion", "appel avec", "appel vidéo", "conférence"]),
+    ("es", &["reunión", "llamada con", "videollamada", "conferencia"]),
+    ("pt", &["reunião", "chamada com", "videochamada", "conferência"]),
+    ("it", &["riunione", "chiamata con", "videochiamata", "conferenza"]),
+    ("nl", &["vergadering", "videogesprek"]),
+    ("pl", &["spotkanie", "połączenie wideo"]),
+    ("ru", &["собрание", "встреча", "звонок", "конференция"]),
+    ("ja", &["会議", "ミーティング", "通話中"]),
+    ("zh", &["会议", "會議", "通话", "通話"]),
+    ("ko", &["회의", "통화"]),
+];
+
+/// First title segments of the Teams main window (navigation), which are not calls
+const TEAMS_SECTIONS: &[&str] = &[
+    "activity", "chat", "teams", "calendar", "calls", "files", "apps", "onedrive",
+    "aktivität", "kalender", "anrufe", "dateien",
+    "activité", "conversation", "calendrier", "appels", "fichiers",
+    "actividad", "calendario", "llamadas", "archivos",
+    "atividade", "chamadas", "arquivos",
+    "attività", "chiamate",
+    "アクティビティ", "チャット", "チーム", "カレンダー", "通話", "ファイル",
+];
+
+/// NFKC plus lowercase, the form every comparison here uses
+pub fn normalize(title: &str) -> String {
+    title.nfkc().collect::<String>().to_lowercase()
+}
+
+#[derive(Debug, Clone)]
+pub struct TitleMatcher {
+    keywords: Vec<String>,
+}
+
+impl TitleMatcher {
+    /// Built-in table with `overrides` applied per language
+    pub fn new(overrides: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut table: BTreeMap<String, Vec<String>> = KEYWORDS
+            .iter()
+            .map(|(language, keywords)| (language.to_string(), keywords.iter().map(|k| k.to_string()).collect()))
+            .collect();
+        for (language, keywords) in overrides {
+            table.insert(language.to_lowercase(), keywords.clone());
+        }
+
+        TitleMatcher {
+            keywords: table
+                .into_values()
+                .flatten()
+                .map(|keyword| normalize(&keyword))
+                .filter(|keyword| !keyword.trim().is_empty())
+                .collect(),
+        }
+    }
+
+    /// Whether the window title says a meeting is in progress
+    pub fn confirms_call(&self, window_title: &str) -> bool {
+        let title = normalize(window_title);
+
+        // App title structure decides first, whatever the UI language
+        if let Some(is_call) = teams_window_is_call(&title) {
+            return is_call;
+        }
+        if has_meet_code(&title) || crate::zoom_probe::is_meeting_title(&title) {
+            return true;
+        }
+
+        self.keywords.iter().any(|keyword| title.contains(keyword.as_str()))
+    }
+}
+
+/// "<meeting> | Microsoft Teams" is a meeting window; "Chat | ... | Microsoft Teams" is
+/// the main window. None for anything that is not a Teams window.
+fn teams_window_is_call(title: &str) -> Option<bool> {
+    let segments: Vec<&str> = title.split('|').map(str::trim).collect();
+    let (last, rest) = segments.split_last()?;
+    if rest.is_empty() || !last.starts_with("microsoft teams") {
+        return None;
+    }
+    Some(!TEAMS_SECTIONS.contains(&rest[0]))
+}
+
+/// Google Meet meeting codes ("abc-defg-hij") appear in the tab title in every language
+fn has_meet_code(title: &str) -> bool {
+    title
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .any(|word| {
+            let parts: Vec<&str> = word.split('-').collect();
+            parts.len() == 3
+                && parts.iter().map(|part| part.len()).eq([3, 4, 3])
+                && parts.iter().all(|part| part.chars().all(|c| c.is_ascii_lowercase()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_titles_confirm_calls_in_any_language() {
+        let matcher = TitleMatcher::new(&BTreeMap::new());
+
+        assert!(matcher.confirms_call("Besprechung mit Vertrieb"));
+        assert!(matcher.confirms_call("定例会議"));
+        // Decomposed accent and full-width letters
+        assert!(matcher.confirms_call("Re\u{301}union d'équipe"));
+        assert!(matcher.confirms_call("ＭＥＥＴＩＮＧ"));
+
+        assert!(matcher.confirms_call("Wochenabstimmung | Microsoft Teams"));
+        assert!(!matcher.confirms_call("Chat | Anna Schmidt | Microsoft Teams"));
+        assert!(!matcher.confirms_call("通話 | Microsoft Teams"));
+        assert!(matcher.confirms_call("Meet - abc-defg-hij - Google Chrome"));
+        assert!(!matcher.confirms_call("Inbox - Outlook"));
+
+        let overrides = BTreeMap::from([("sv".to_string(), vec!["Möte".to_string()])]);
+        assert!(TitleMatcher::new(&overrides).confirms_call("Möte med kund"));
+    }
+}
@@ -0,0 +1,282 @@
+// UI Automation reads of call app windows (Windows)
+// `element_names` walks the visible windows of a process and returns the names of its
+// buttons and text elements. recording_probe.rs looks for Teams' recording banner in them;
+// with the `uia` feature the call controls of Zoom, Teams and Meet are read as well:
+//   - the mute toggle ("Unmute my audio" means muted)
+//   - the camera toggle ("Start video" / "Turn camera on" means the camera is off)
+//   - the participant count on the participants/people button
+//   - the call timer ("12:34")
+//   - the share toggle ("Stop presenting" / "Stop Share" means you are presenting) and
+//     buttons only hosts get ("Security", "Host tools", "End meeting for all"), for role.rs
+// That is the client's own view of the call, which audio and network heuristics can only
+// guess. A full tree walk takes tens of milliseconds, so it runs every few seconds.
+
+use crate::CallInfo;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "uia")]
+use std::time::{Duration, Instant};
+
+/// How often the call controls are read
+#[cfg(feature = "uia")]
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Button name prefixes, lowercase: (prefix, state the button shows)
+/// "Unmute" is offered while muted, "Turn camera on" while the camera is off.
+const MUTE_BUTTONS: &[(&str, bool)] = &[
+    ("unmute", true),
+    ("turn on microphone", true),
+    ("mute", false),
+    ("turn off microphone", false),
+];
+const CAMERA_BUTTONS: &[(&str, bool)] = &[
+    ("start video", false),
+    ("start my video", false),
+    ("turn camera on", false),
+    ("turn on camera", false),
+    ("stop video", true),
+    ("stop my video", true),
+    ("turn camera off", true),
+    ("turn off camera", true),
+];
+
+const PRESENT_BUTTONS: &[(&str, bool)] = &[
+    ("stop presenting", true),
+    ("stop sharing", true),
+    ("stop share", true),
+    ("present now", false),
+    ("start presenting", false),
+    ("share screen", false),
+    ("share content", false),
+];
+/// Button name prefixes shown to hosts, organizers and co-hosts only, lowercase
+const HOST_BUTTONS: &[&str] = &[
+    "security",
+    "host tools",
+    "host controls",
+    "end meeting for all",
+    "mute all",
+    "meeting options",
+    "lower all hands",
+];
+
+/// Kind of UI element read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))] // only element_names reads windows
+pub enum ElementKind {
+    Button,
+    Text,
+}
+
+/// Call controls as the client shows them; None for whatever was not found
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CallControls {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mic_muted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_on: Option<bool>,
+    /// Everyone in the call, as the participants button counts them (you included)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participants: Option<u32>,
+    /// Elapsed time shown by the client, as displayed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_timer: Option<String>,
+    /// Sharing your screen or a window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presenting: Option<bool>,
+    /// A control only hosts get was found; absence proves nothing
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub host_controls: bool,
+}
+
+/// Controls read from one process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlsReading {
+    pub process_id: u32,
+    pub controls: CallControls,
+}
+
+/// The call controls among a window's element names
+#[cfg_attr(not(feature = "uia"), allow(dead_code))] // only the `uia` probe and tests read controls
+pub fn read_controls(elements: &[(ElementKind, String)]) -> CallControls {
+    let mut controls = CallControls::default();
+    let toggle = |table: &[(&str, bool)], name: &str| {
+        table.iter().find(|(prefix, _)| name.starts_with(prefix)).map(|(_, state)| *state)
+    };
+
+    for (kind, name) in elements {
+        let name = name.trim().to_lowercase();
+        match kind {
+            ElementKind::Button => {
+                controls.mic_muted = controls.mic_muted.or_else(|| toggle(MUTE_BUTTONS, &name));
+                controls.camera_on = controls.camera_on.or_else(|| toggle(CAMERA_BUTTONS, &name));
+                controls.presenting = controls.presenting.or_else(|| toggle(PRESENT_BUTTONS, &name));
+                controls.host_controls |= HOST_BUTTONS.iter().any(|prefix| name.starts_with(prefix));
+                if name.contains("participant") || name.contains("people") {
+                    controls.participants = controls.participants.or_else(|| first_number(&name));
+                }
+            }
+            ElementKind::Text => {
+                if controls.call_timer.is_none() && is_timer(&name) {
+                    controls.call_timer = Some(name);
+                }
+            }
+        }
+    }
+
+    controls
+}
+
+/// "Participants, 5" -> 5
+fn first_number(text: &str) -> Option<u32> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .find(|part| !part.is_empty())
+        .and_then(|digits| digits.parse().ok())
+}
+
+/// "4:05", "12:34" or "1:02:03"
+fn is_timer(text: &str) -> bool {
+    let parts: Vec<&str> = text.split(':').collect();
+    (2..=3).contains(&parts.len())
+        && parts.iter().all(|part| !part.is_empty() && part.len() <= 2 && part.chars().all(|c| c.is_ascii_digit()))
+        && parts[1..].iter().all(|part| part.len() == 2)
+}
+
+/// Throttled reads of the active call's controls (`uia` feature)
+#[cfg(feature = "uia")]
+#[derive(Debug, Default)]
+pub struct UiaProbe {
+    /// When the last read ran, for which call, and what it found
+    last: Option<(Instant, u32, Option<ControlsReading>)>,
+}
+
+#[cfg(feature = "uia")]
+impl UiaProbe {
+    /// Read the controls of `call` (the call active at the start of the tick)
+    pub fn probe(&mut self, call: Option<&CallInfo>) -> Option<ControlsReading> {
+        let call = call?;
+        if let Some((at, process_id, found)) = &self.last {
+            if *process_id == call.process_id && at.elapsed() < PROBE_INTERVAL {
+                return found.clone();
+            }
+        }
+
+        let app = call.app.to_lowercase();
+        let known = ["zoom", "teams", "meet"].iter().any(|name| app.contains(name));
+        let found = known
+            .then(|| element_names(call.process_id))
+            .flatten()
+            .map(|elements| ControlsReading { process_id: call.process_id, controls: read_controls(&elements) })
+            .filter(|reading| reading.controls != CallControls::default());
+
+        self.last = Some((Instant::now(), call.process_id, found.clone()));
+        found
+    }
+}
+
+/// Controls for `call` this tick: a fresh reading of its process, else the last one
+pub fn controls_for(call: &CallInfo, reading: Option<&ControlsReading>) -> Option<CallControls> {
+    reading
+        .filter(|reading| reading.process_id == call.process_id)
+        .map(|reading| reading.controls.clone())
+        .or_else(|| call.controls.clone())
+}
+
+/// Names of the buttons and text elements in the visible windows of `process_id`
+/// None when the process has no window or UI Automation fails.
+#[cfg(target_os = "windows")]
+pub fn element_names(process_id: u32) -> Option<Vec<(ElementKind, String)>> {
+    use windows::core::VARIANT;
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::System::Com::*;
+    use windows::Win32::UI::Accessibility::*;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    unsafe extern "system" fn enum_window_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let (target, found) = &mut *(lparam.0 as *mut (u32, Vec<HWND>));
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id as *mut u32));
+        if process_id == *target && IsWindowVisible(hwnd).as_bool() {
+            found.push(hwnd);
+        }
+        BOOL(1)
+    }
+
+    unsafe {
+        let mut windows = (process_id, Vec::new());
+        let _ = EnumWindows(Some(enum_window_callback), LPARAM(&mut windows as *mut _ as isize));
+        if windows.1.is_empty() {
+            return None;
+        }
+
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let names = (|| -> windows::core::Result<Vec<(ElementKind, String)>> {
+            let automation: IUIAutomation = CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER)?;
+            let condition = automation.CreateOrCondition(
+                &automation.CreatePropertyCondition(UIA_ControlTypePropertyId, &VARIANT::from(UIA_ButtonControlTypeId.0))?,
+                &automation.CreatePropertyCondition(UIA_ControlTypePropertyId, &VARIANT::from(UIA_TextControlTypeId.0))?,
+            )?;
+
+            let mut names = Vec::new();
+            for hwnd in windows.1 {
+                let elements = automation.ElementFromHandle(hwnd)?.FindAll(TreeScope_Descendants, &condition)?;
+                for i in 0..elements.Length()? {
+                    let element = elements.GetElement(i)?;
+                    let kind = if element.CurrentControlType()? == UIA_ButtonControlTypeId {
+                        ElementKind::Button
+                    } else {
+                        ElementKind::Text
+                    };
+                    names.push((kind, element.CurrentName()?.to_string()));
+                }
+            }
+            Ok(names)
+        })();
+        CoUninitialize();
+
+        names.ok()
+    }
+}
+
+#[cfg(all(feature = "uia", not(target_os = "windows")))]
+fn element_names(_process_id: u32) -> Option<Vec<(ElementKind, String)>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_call_controls() {
+        let button = |name: &str| (ElementKind::Button, name.to_string());
+        let text = |name: &str| (ElementKind::Text, name.to_string());
+
+        let zoom = read_controls(&[
+            text("Zoom Meeting"),
+            button("Unmute my audio"),
+            button("Start Video"),
+            button("Participants, 5"),
+            text("12:34"),
+        ]);
+        assert_eq!(
+            zoom,
+            CallControls {
+                mic_muted: Some(true),
+                camera_on: Some(false),
+                participants: Some(5),
+                call_timer: Some("12:34".to_string()),
+                presenting: None,
+                host_controls: false,
+            }
+        );
+
+        let host = read_controls(&[button("Security"), button("Stop Share"), button("Participants, 5")]);
+        assert_eq!((host.presenting, host.host_controls), (Some(true), true));
+
+        let teams = read_controls(&[button("Mute mic (Ctrl+Shift+M)"), button("Turn camera off (Ctrl+Shift+O)"), text("1:02:03")]);
+        assert_eq!((teams.mic_muted, teams.camera_on), (Some(false), Some(true)));
+        assert_eq!(teams.call_timer.as_deref(), Some("1:02:03"));
+        assert!(!is_timer("10:5") && !is_timer("Meet - 10:30 AM"));
+    }
+}
@@ -0,0 +1,405 @@
+// Sensing half of the monitor loop
+// CallValidator turns one poll of an audio backend and a network source into the
+// audio/mic sources, WebRTC signals and volume levels of a tick. Both are type parameters so the real
+// platform backends and the scripted MockBackend (mock_backend.rs) run the same code.
+// Each poll refreshes only the subsystems the scheduler says are due (scheduler.rs) and
+// reuses the last reading of the others. Every subsystem poll runs under the supervisor
+// (supervisor.rs), so a panicking backend blanks its own reading and nothing else.
+
+use crate::audio::{AudioBackend, OutputDevice};
+use crate::call_tracker::Sample;
+use crate::environment::Environment;
+use crate::error::ValidatorError;
+use crate::mute_timeline::{Level, VolumeLevels};
+use crate::network_monitor::{NetworkMonitor, WebRTCSignal};
+use crate::privacy::{self, PrivateWindowPolicy};
+use crate::process_tree::ProcessTree;
+use crate::profile::CycleProfile;
+use crate::scheduler::{Due, Subsystem};
+use crate::supervisor::{Failure, Supervisor};
+use crate::AudioSource;
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::time::Instant;
+
+/// Source of WebRTC network activity
+pub trait NetworkSource {
+    fn webrtc_signals(&mut self) -> Vec<WebRTCSignal>;
+
+    /// Drop internal state after a scan panicked
+    fn restart(&mut self) {}
+}
+
+impl NetworkSource for NetworkMonitor {
+    fn webrtc_signals(&mut self) -> Vec<WebRTCSignal> {
+        self.get_webrtc_signals()
+    }
+
+    fn restart(&mut self) {
+        NetworkMonitor::restart(self);
+    }
+}
+
+/// What one poll of the backends found
+#[derive(Debug, Clone, Default)]
+pub struct Sensed {
+    /// Processes playing audio, resolved to their root application process
+    pub audio_sources: Vec<AudioSource>,
+    /// Processes using the microphone
+    pub mic_sources: Vec<AudioSource>,
+    pub webrtc_signals: Vec<WebRTCSignal>,
+    pub levels: VolumeLevels,
+    /// Capture session input peaks by root process
+    pub capture_peaks: BTreeMap<u32, f32>,
+    /// Input peak each root process records, per session or per capture device
+    pub mic_peaks: BTreeMap<u32, f32>,
+    /// Devices the render sessions of each root process play to, where the backend tells
+    pub output_devices: BTreeMap<u32, Vec<OutputDevice>>,
+    /// Seconds each audio source has been playing without a break
+    pub audio_active_secs: BTreeMap<u32, f32>,
+}
+
+impl Sensed {
+    /// The tracker input for these sources, without browser-side signals
+    pub fn to_sample(&self, process_tree: &mut ProcessTree) -> Sample {
+        // Helper processes of one app may each hold media sockets; the busiest one counts
+        let mut media_peers: BTreeMap<u32, usize> = BTreeMap::new();
+        for signal in self.webrtc_signals.iter().filter(|signal| signal.media_peers > 0) {
+            let peers = media_peers.entry(process_tree.root(signal.process_id)).or_insert(0);
+            *peers = (*peers).max(signal.media_peers);
+        }
+
+        Sample {
+            audio_sources: self.audio_sources.clone(),
+            mic_sources: self.mic_sources.clone(),
+            levels: self.levels.clone(),
+            capture_peaks: self.capture_peaks.clone(),
+            mic_peaks: self.mic_peaks.clone(),
+            webrtc_pids: self.webrtc_signals.iter().map(|signal| process_tree.root(signal.process_id)).collect(),
+            sip_pids: self
+                .webrtc_signals
+                .iter()
+                .filter(|signal| signal.has_sip_signaling)
+                .map(|signal| process_tree.root(signal.process_id))
+                .collect(),
+            media_peers,
+            output_devices: self.output_devices.clone(),
+            audio_active_secs: self.audio_active_secs.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Remote addresses of the media connections held by `root_pid` or its helper processes
+    pub fn media_remotes(&self, root_pid: u32, process_tree: &mut ProcessTree) -> Vec<IpAddr> {
+        let mut remotes: Vec<IpAddr> = Vec::new();
+        for signal in self.webrtc_signals.iter().filter(|signal| process_tree.root(signal.process_id) == root_pid) {
+            for ip in &signal.media_remotes {
+                if !remotes.contains(ip) {
+                    remotes.push(*ip);
+                }
+            }
+        }
+        remotes
+    }
+}
+
+pub struct CallValidator<A: AudioBackend, N: NetworkSource> {
+    audio: A,
+    network: N,
+    private_window_policy: PrivateWindowPolicy,
+    /// Last reading of every subsystem
+    last: Sensed,
+    /// Window title of each root process, kept between title refreshes
+    titles: HashMap<u32, String>,
+    /// When each audio source started playing, for telling notification blips from calls
+    active_since: HashMap<u32, Instant>,
+    /// Time each subsystem took in the last poll
+    profile: CycleProfile,
+    supervisor: Supervisor,
+    environment: Environment,
+}
+
+impl<A: AudioBackend, N: NetworkSource> CallValidator<A, N> {
+    pub fn new(audio: A, network: N) -> Self {
+        CallValidator {
+            audio,
+            network,
+            private_window_policy: PrivateWindowPolicy::Ignore,
+            last: Sensed::default(),
+            titles: HashMap::new(),
+            active_since: HashMap::new(),
+            profile: CycleProfile::default(),
+            supervisor: Supervisor::default(),
+            environment: Environment::Local,
+        }
+    }
+
+    pub fn with_private_window_policy(mut self, policy: PrivateWindowPolicy) -> Self {
+        self.private_window_policy = policy;
+        self
+    }
+
+    /// Remote sessions skip the capture meters of redirected microphones
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Poll the backends for the subsystems in `due`
+    /// Helper processes (browser renderers, Electron helpers) are attributed to the root
+    /// application process that owns the window.
+    pub fn sense(&mut self, process_tree: &mut ProcessTree, due: Due) -> Sensed {
+        self.profile.clear();
+        let mut supervisor = std::mem::take(&mut self.supervisor);
+        let now = Instant::now();
+        if due.mic {
+            let start = Instant::now();
+            match supervisor.run(Subsystem::Mic, now, || self.sense_mic(process_tree)) {
+                Ok(result) => supervisor.record(Subsystem::Mic, result.err()),
+                Err(failure) => {
+                    self.last.mic_sources.clear();
+                    self.last.capture_peaks.clear();
+                    self.last.mic_peaks.clear();
+                    self.last.levels.mic = None;
+                    if failure == Failure::Panicked {
+                        self.audio.restart();
+                    }
+                }
+            }
+            self.profile.add("mic", start.elapsed());
+        }
+        if due.audio {
+            let start = Instant::now();
+            match supervisor.run(Subsystem::Audio, now, || self.sense_audio(process_tree, due.window_titles)) {
+                Ok(result) => supervisor.record(Subsystem::Audio, result.err()),
+                Err(failure) => {
+                    self.last.audio_sources.clear();
+                    self.last.levels.output = None;
+                    self.last.levels.sessions.clear();
+                    self.last.output_devices.clear();
+                    self.titles.clear();
+                    if failure == Failure::Panicked {
+                        self.audio.restart();
+                    }
+                }
+            }
+            self.profile.add("audio", start.elapsed());
+        } else if due.window_titles {
+            let start = Instant::now();
+            // Titles are only re-read here; the sources are kept if that panics
+            let audio_sources = self.last.audio_sources.clone();
+            if supervisor.run(Subsystem::WindowTitles, now, || self.refresh_titles()).is_err() {
+                self.last.audio_sources = audio_sources;
+            }
+            self.profile.add("window_titles", start.elapsed());
+        }
+        // A source that drops out starts over, so repeated pings never add up
+        self.active_since.retain(|process_id, _| self.last.audio_sources.iter().any(|src| src.process_id == *process_id));
+        for src in &self.last.audio_sources {
+            self.active_since.entry(src.process_id).or_insert(now);
+        }
+        self.last.audio_active_secs = self
+            .active_since
+            .iter()
+            .map(|(process_id, since)| (*process_id, now.duration_since(*since).as_secs_f32()))
+            .collect();
+        if due.network {
+            let start = Instant::now();
+            match supervisor.run(Subsystem::Network, now, || self.network.webrtc_signals()) {
+                Ok(signals) => self.last.webrtc_signals = signals,
+                Err(failure) => {
+                    self.last.webrtc_signals.clear();
+                    if failure == Failure::Panicked {
+                        self.network.restart();
+                    }
+                }
+            }
+            self.profile.add("network", start.elapsed());
+        }
+        self.supervisor = supervisor;
+        self.last.clone()
+    }
+
+    /// Panic counts, backoff and backend errors of the subsystems
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.supervisor
+    }
+
+    /// Time each subsystem took in the last `sense`
+    pub fn profile(&self) -> &CycleProfile {
+        &self.profile
+    }
+
+    /// Drop the last network reading, for when socket scans stop
+    pub fn clear_network(&mut self) {
+        self.last.webrtc_signals.clear();
+    }
+
+    /// Refresh the mic readings; an error of the session query is returned after blanking them
+    fn sense_mic(&mut self, process_tree: &mut ProcessTree) -> Result<(), ValidatorError> {
+        let mut result = Ok(());
+        let mic_sessions = self.audio.get_apps_using_microphone().unwrap_or_else(|error| {
+            result = Err(error);
+            Vec::new()
+        });
+        // Keyed like the audio sources: by the root process of the app holding the mic
+        let mut mic_sources: Vec<AudioSource> = Vec::new();
+        for session in mic_sessions {
+            let process_id = if session.process_id != 0 { process_tree.root(session.process_id) } else { 0 };
+            if process_id != 0 && mic_sources.iter().any(|src| src.process_id == process_id) {
+                continue;
+            }
+            // A helper's name ("Teams Helper") may not be recognized where its root's is
+            let detected_app = crate::detect_call_app(&session.name, "").or_else(|| {
+                let root = process_tree.entry(process_id)?;
+                crate::detect_call_app(&root.name, "")
+            });
+            mic_sources.push(AudioSource {
+                detected_app,
+                name: session.name,
+                process_id,
+                window_title: String::new(),
+                private_context: false,
+                is_virtual_device: false,
+                classification: None,
+                session: None,
+            });
+        }
+        self.last.mic_sources = mic_sources;
+
+        let mut capture_peaks: BTreeMap<u32, f32> = BTreeMap::new();
+        let mut mic_peaks: BTreeMap<u32, f32> = BTreeMap::new();
+        let sessions = if self.environment.meters_capture() {
+            self.audio.get_microphone_sessions().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        for session in sessions {
+            let Some(peak_level) = session.peak_level else { continue };
+            let root = process_tree.root(session.process_id);
+            let peak = mic_peaks.entry(root).or_insert(0.0);
+            *peak = peak.max(peak_level);
+            // A device meter hears the room even when the app mutes itself
+            if !session.device_level {
+                let peak = capture_peaks.entry(root).or_insert(0.0);
+                *peak = peak.max(peak_level);
+            }
+        }
+        self.last.capture_peaks = capture_peaks;
+        self.last.mic_peaks = mic_peaks;
+        self.last.levels.mic = self.audio.get_microphone_volume_and_mute().ok().map(Level::from);
+        result
+    }
+
+    /// Refresh the audio readings; an error of the session query is returned after blanking them
+    fn sense_audio(&mut self, process_tree: &mut ProcessTree, refresh_titles: bool) -> Result<(), ValidatorError> {
+        let mut levels = VolumeLevels {
+            mic: self.last.levels.mic,
+            output: self.audio.get_audio_output_volume_and_mute().ok().map(Level::from),
+            sessions: BTreeMap::new(),
+        };
+
+        let mut result = Ok(());
+        let apps = self.audio.get_apps_playing_audio().unwrap_or_else(|error| {
+            result = Err(error);
+            Vec::new()
+        });
+        let mut audio_sources: Vec<AudioSource> = Vec::new();
+        let mut output_devices: BTreeMap<u32, Vec<OutputDevice>> = BTreeMap::new();
+        for app in apps {
+            if !app.is_active && app.peak_level <= 0.001 {
+                continue;
+            }
+
+            let process_id = process_tree.root(app.process_id);
+            // Muted only while every session of the app is
+            let level = Level { volume: app.volume, is_muted: app.is_muted };
+            levels
+                .sessions
+                .entry(process_id)
+                .and_modify(|merged| {
+                    merged.volume = merged.volume.max(level.volume);
+                    merged.is_muted &= level.is_muted;
+                })
+                .or_insert(level);
+            let is_virtual_device = app.output_device.as_ref().is_some_and(|device| device.is_virtual);
+            if let Some(device) = &app.output_device {
+                let devices = output_devices.entry(process_id).or_default();
+                if !devices.contains(device) {
+                    devices.push(device.clone());
+                }
+            }
+            // Virtual only while none of the app's sessions reaches a real device
+            if let Some(src) = audio_sources.iter_mut().find(|src| src.process_id == process_id) {
+                src.is_virtual_device &= is_virtual_device;
+                continue;
+            }
+
+            let name = if process_id == app.process_id {
+                app.name.clone()
+            } else {
+                process_tree
+                    .entry(process_id)
+                    .map(|entry| entry.name.clone())
+                    .unwrap_or_else(|| app.name.clone())
+            };
+            let window_title = match self.titles.get(&process_id).filter(|_| !refresh_titles) {
+                Some(title) => title.clone(),
+                None if process_id == app.process_id => app.window_title.clone(),
+                // The helper has no window of its own
+                None if app.window_title.is_empty() || app.window_title == app.name => {
+                    <() as crate::platform::PlatformUtils>::get_window_title(process_id)
+                        .unwrap_or_else(|_| app.window_title.clone())
+                }
+                None => app.window_title.clone(),
+            };
+            self.titles.insert(process_id, window_title.clone());
+
+            audio_sources.push(AudioSource {
+                detected_app: crate::detect_call_app(&name, &window_title),
+                private_context: privacy::is_private_window(&name, &window_title),
+                name,
+                process_id,
+                window_title,
+                is_virtual_device,
+                classification: None,
+                session: None,
+            });
+        }
+
+        self.titles.retain(|process_id, _| audio_sources.iter().any(|src| src.process_id == *process_id));
+        self.apply_private_window_policy(&mut audio_sources);
+        self.last.audio_sources = audio_sources;
+        self.last.output_devices = output_devices;
+        self.last.levels = levels;
+        result
+    }
+
+    /// Re-read the titles of the last audio sources between audio polls
+    fn refresh_titles(&mut self) {
+        let mut audio_sources = std::mem::take(&mut self.last.audio_sources);
+        for src in audio_sources.iter_mut() {
+            let Ok(window_title) = <() as crate::platform::PlatformUtils>::get_window_title(src.process_id) else {
+                continue;
+            };
+            if window_title.is_empty() || window_title == src.window_title {
+                continue;
+            }
+            src.detected_app = crate::detect_call_app(&src.name, &window_title);
+            src.private_context = privacy::is_private_window(&src.name, &window_title);
+            src.window_title = window_title.clone();
+            self.titles.insert(src.process_id, window_title);
+        }
+        self.apply_private_window_policy(&mut audio_sources);
+        self.last.audio_sources = audio_sources;
+    }
+
+    /// Apply private window policy before any detection sees the sources
+    fn apply_private_window_policy(&self, audio_sources: &mut Vec<AudioSource>) {
+        match self.private_window_policy {
+            PrivateWindowPolicy::Exclude => audio_sources.retain(|src| !src.private_context),
+            PrivateWindowPolicy::Ignore => audio_sources.iter_mut().for_each(|src| src.private_context = false),
+            PrivateWindowPolicy::Tag => {}
+        }
+    }
+}
@@ -1,5 +1,10 @@
 #[cfg(target_os = "windows")]
 pub mod wasapi {
+    use std::collections::{BTreeMap, VecDeque};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
     use windows::core::*;
     use windows::Win32::Foundation::*;
     use windows::Win32::Media::Audio::Endpoints::*;
@@ -360,4 +365,185 @@ pub mod wasapi {
             Ok(apps)
         }
     }
+
+    /// Activate a loopback client on the render streams of `process_id` and its child
+    /// processes, initialized for mono 32-bit float at `sample_rate`
+    /// Needs Windows 10 2004 or later and COM initialized on the calling thread.
+    pub fn open_process_loopback(process_id: u32, sample_rate: u32) -> Result<IAudioClient> {
+        unsafe {
+            let params = AUDIOCLIENT_ACTIVATION_PARAMS {
+                ActivationType: AUDIOCLIENT_ACTIVATION_TYPE_PROCESS_LOOPBACK,
+                Anonymous: AUDIOCLIENT_ACTIVATION_PARAMS_0 {
+                    ProcessLoopbackParams: AUDIOCLIENT_PROCESS_LOOPBACK_PARAMS {
+                        TargetProcessId: process_id,
+                        ProcessLoopbackMode: PROCESS_LOOPBACK_MODE_INCLUDE_TARGET_PROCESS_TREE,
+                    },
+                },
+            };
+            let blob = BlobVariant {
+                vt: VT_BLOB,
+                reserved: [0; 3],
+                size: std::mem::size_of::<AUDIOCLIENT_ACTIVATION_PARAMS>() as u32,
+                data: &params as *const AUDIOCLIENT_ACTIVATION_PARAMS as *const u8,
+            };
+
+            let (tx, rx) = mpsc::channel();
+            let handler: IActivateAudioInterfaceCompletionHandler = Activation(tx).into();
+            let operation = ActivateAudioInterfaceAsync(
+                VIRTUAL_AUDIO_DEVICE_PROCESS_LOOPBACK,
+                &IAudioClient::IID,
+                Some(&blob as *const BlobVariant as *const PROPVARIANT),
+                &handler,
+            )?;
+            if rx.recv_timeout(Duration::from_secs(5)).is_err() {
+                return Err(Error::from(ERROR_TIMEOUT.to_hresult()));
+            }
+
+            let mut result = HRESULT(0);
+            let mut activated: Option<IUnknown> = None;
+            operation.GetActivateResult(&mut result, &mut activated)?;
+            result.ok()?;
+            let client: IAudioClient = activated.ok_or_else(|| Error::from(E_NOINTERFACE))?.cast()?;
+
+            // Process loopback has no mix format of its own and converts to any PCM one
+            let format = WAVEFORMATEX {
+                wFormatTag: WAVE_FORMAT_IEEE_FLOAT,
+                nChannels: 1,
+                nSamplesPerSec: sample_rate,
+                nAvgBytesPerSec: sample_rate * 4,
+                nBlockAlign: 4,
+                wBitsPerSample: 32,
+                cbSize: 0,
+            };
+            client.Initialize(
+                AUDCLNT_SHAREMODE_SHARED,
+                AUDCLNT_STREAMFLAGS_LOOPBACK | AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM,
+                LOOPBACK_BUFFER_DURATION,
+                0,
+                &format,
+                None,
+            )?;
+            Ok(client)
+        }
+    }
+
+    /// Loopback buffer length, in 100 ns units
+    const LOOPBACK_BUFFER_DURATION: i64 = 2_000_000;
+    const VT_BLOB: u16 = 65;
+    const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+    /// PROPVARIANT holding a VT_BLOB, the layout ActivateAudioInterfaceAsync reads
+    #[repr(C)]
+    struct BlobVariant {
+        vt: u16,
+        reserved: [u16; 3],
+        size: u32,
+        data: *const u8,
+    }
+
+    #[implement(IActivateAudioInterfaceCompletionHandler, IAgileObject)]
+    struct Activation(mpsc::Sender<()>);
+
+    impl IActivateAudioInterfaceCompletionHandler_Impl for Activation_Impl {
+        fn ActivateCompleted(&self, _operation: Option<&IActivateAudioInterfaceAsyncOperation>) -> Result<()> {
+            let _ = self.0.send(());
+            Ok(())
+        }
+    }
+
+    /// Meter rate; peaks need no more
+    const METER_SAMPLE_RATE: u32 = 8_000;
+    /// How long a peak counts, so pauses between sentences don't read as silence
+    const METER_HOLD: Duration = Duration::from_secs(10);
+    /// Process trees metered at once
+    const MAX_METERS: usize = 4;
+
+    /// Peak level of one process tree's own render streams, even while other apps play
+    /// Session meters only see the process that owns a session; browsers play from a helper.
+    pub struct ProcessLoopbackMeter {
+        /// Loudest sample of each 100 ms read; None until the stream opened
+        peaks: Arc<Mutex<Option<VecDeque<(Instant, f32)>>>>,
+        stop: Arc<AtomicBool>,
+    }
+
+    impl ProcessLoopbackMeter {
+        pub fn start(process_id: u32) -> Self {
+            let peaks = Arc::new(Mutex::new(None));
+            let stop = Arc::new(AtomicBool::new(false));
+            let (thread_peaks, thread_stop) = (Arc::clone(&peaks), Arc::clone(&stop));
+            thread::spawn(move || unsafe {
+                let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+                if let Err(e) = run_meter(process_id, &thread_peaks, &thread_stop) {
+                    log::debug!("process loopback meter for {} stopped: {}", process_id, e);
+                }
+                *thread_peaks.lock().unwrap() = None;
+                CoUninitialize();
+            });
+            ProcessLoopbackMeter { peaks, stop }
+        }
+
+        /// Loudest peak (0.0 to 1.0) over the last METER_HOLD; None without process loopback
+        pub fn peak_level(&self) -> Option<f32> {
+            let peaks = self.peaks.lock().unwrap();
+            peaks.as_ref().map(|peaks| peaks.iter().map(|(_, peak)| *peak).fold(0.0, f32::max))
+        }
+    }
+
+    impl Drop for ProcessLoopbackMeter {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    unsafe fn run_meter(process_id: u32, peaks: &Mutex<Option<VecDeque<(Instant, f32)>>>, stop: &AtomicBool) -> Result<()> {
+        let client = open_process_loopback(process_id, METER_SAMPLE_RATE)?;
+        let capture: IAudioCaptureClient = client.GetService()?;
+        client.Start()?;
+        *peaks.lock().unwrap() = Some(VecDeque::new());
+
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+            let mut peak = 0.0f32;
+            while capture.GetNextPacketSize().unwrap_or(0) > 0 {
+                let mut data = std::ptr::null_mut();
+                let mut count = 0u32;
+                let mut flags = 0u32;
+                capture.GetBuffer(&mut data, &mut count, &mut flags, None, None)?;
+                if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 == 0 {
+                    let samples = std::slice::from_raw_parts(data as *const f32, count as usize);
+                    peak = samples.iter().fold(peak, |peak, sample| peak.max(sample.abs()));
+                }
+                capture.ReleaseBuffer(count)?;
+            }
+            if let Some(peaks) = peaks.lock().unwrap().as_mut() {
+                let now = Instant::now();
+                peaks.push_back((now, peak.min(1.0)));
+                peaks.retain(|(at, _)| now.duration_since(*at) < METER_HOLD);
+            }
+        }
+        let _ = client.Stop();
+        Ok(())
+    }
+
+    /// Process loopback meters that follow the processes attribution asks about
+    #[derive(Default)]
+    pub struct RenderMeters {
+        meters: BTreeMap<u32, ProcessLoopbackMeter>,
+    }
+
+    impl RenderMeters {
+        /// Render peak of each of `process_ids` (root processes) that could be metered
+        /// Meters start on a process's first update, so it has a peak from the next tick on.
+        pub fn update(&mut self, process_ids: impl IntoIterator<Item = u32>) -> BTreeMap<u32, f32> {
+            let wanted: Vec<u32> = process_ids.into_iter().take(MAX_METERS).collect();
+            self.meters.retain(|process_id, _| wanted.contains(process_id));
+            for process_id in &wanted {
+                self.meters.entry(*process_id).or_insert_with(|| ProcessLoopbackMeter::start(*process_id));
+            }
+            self.meters
+                .iter()
+                .filter_map(|(process_id, meter)| Some((*process_id, meter.peak_level()?)))
+                .collect()
+        }
+    }
 }
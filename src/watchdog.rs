@@ -0,0 +1,232 @@
+// Supervision by an embedding parent process (`--parent-pid`, `--heartbeat`)
+// `--parent-pid <pid>` exits the monitor once that process is gone, so an app that
+// crashes or is killed does not leave an orphaned monitor behind. `--heartbeat <secs>`
+// adds a ping/pong over the pipes: the monitor writes a `ping` envelope to stdout every
+// interval and expects a `pong` line on stdin before the next few are due, and it
+// answers a `ping` line on stdin with a `pong` reply from the monitor loop. Each side
+// can tell the other has hung when its pings go unanswered. Both checks run on their
+// own threads, so a stalled monitor loop or a full stdout pipe does not hide a dead
+// parent.
+
+use crate::control::StdinCommands;
+use crate::output::{Envelope, EventType};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the parent's liveness is checked
+const PARENT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Pings the parent may leave unanswered before it counts as hung
+const MISSED_PONGS: u32 = 3;
+
+/// Exit once `pid` has exited
+pub fn watch_parent(pid: u32) -> Result<(), String> {
+    let parent = Parent::open(pid)?;
+    thread::spawn(move || loop {
+        if !parent.is_alive() {
+            log::info!("Parent process {} exited, stopping", pid);
+            std::process::exit(0);
+        }
+        thread::sleep(PARENT_CHECK_INTERVAL);
+    });
+    Ok(())
+}
+
+/// How the parent is watched, from the most to the least exact
+#[cfg(unix)]
+enum Parent {
+    /// Our own parent: once it exits we are reparented, whatever process reuses its pid
+    Own(libc::pid_t),
+    /// A pidfd refers to the process itself, so a reused pid cannot pass for it (Linux 5.3+)
+    #[cfg(target_os = "linux")]
+    PidFd(libc::c_int),
+    /// Any other process on older kernels and other systems: a reused pid passes for it
+    Pid(libc::pid_t),
+}
+
+#[cfg(unix)]
+impl Parent {
+    fn open(pid: u32) -> Result<Self, String> {
+        let pid = pid as libc::pid_t;
+        if unsafe { libc::getppid() } == pid {
+            return Ok(Parent::Own(pid));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+            if fd >= 0 {
+                return Ok(Parent::PidFd(fd as libc::c_int));
+            }
+        }
+        let parent = Parent::Pid(pid);
+        if !parent.is_alive() {
+            return Err(format!("no process {}", pid));
+        }
+        Ok(parent)
+    }
+
+    fn is_alive(&self) -> bool {
+        match *self {
+            Parent::Own(pid) => unsafe { libc::getppid() } == pid,
+            // A pidfd turns readable once its process exits
+            #[cfg(target_os = "linux")]
+            Parent::PidFd(fd) => {
+                let mut exited = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+                unsafe { libc::poll(&mut exited, 1, 0) == 0 }
+            }
+            // Signal 0 only checks that the pid exists; EPERM means it exists under another user
+            Parent::Pid(pid) => unsafe {
+                libc::kill(pid, 0) == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+            },
+        }
+    }
+}
+
+// The handle keeps the process object, so a reused pid cannot pass for the parent
+#[cfg(windows)]
+struct Parent(windows::Win32::Foundation::HANDLE);
+
+#[cfg(windows)]
+unsafe impl Send for Parent {}
+
+#[cfg(windows)]
+impl Parent {
+    fn open(pid: u32) -> Result<Self, String> {
+        use windows::Win32::System::Threading::{OpenProcess, PROCESS_SYNCHRONIZE};
+
+        unsafe { OpenProcess(PROCESS_SYNCHRONIZE, false, pid) }
+            .map(Parent)
+            .map_err(|e| format!("cannot open process {}: {}", pid, e))
+    }
+
+    fn is_alive(&self) -> bool {
+        use windows::Win32::Foundation::WAIT_TIMEOUT;
+        use windows::Win32::System::Threading::WaitForSingleObject;
+
+        unsafe { WaitForSingleObject(self.0, 0) == WAIT_TIMEOUT }
+    }
+}
+
+#[derive(Serialize)]
+struct PingPayload {
+    seq: u64,
+}
+
+/// Last ping sent and last pong received, in milliseconds on the monotonic clock, so a
+/// wall clock step does not read as a hung parent
+struct Exchange {
+    started: Instant,
+    last_ping: AtomicU64,
+    last_pong: AtomicU64,
+}
+
+impl Exchange {
+    fn new() -> Self {
+        Exchange { started: Instant::now(), last_ping: AtomicU64::new(0), last_pong: AtomicU64::new(0) }
+    }
+
+    /// Milliseconds since start, from 1 so that 0 stays "never"
+    fn now_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64 + 1
+    }
+}
+
+/// `--heartbeat`: pings on stdout, pongs and pings from the parent on stdin
+pub struct Heartbeat {
+    interval: Duration,
+    exchange: Arc<Exchange>,
+    requests: StdinCommands,
+    next_ping: Instant,
+    seq: u64,
+}
+
+impl Heartbeat {
+    pub fn start(interval: Duration) -> Self {
+        let exchange = Arc::new(Exchange::new());
+
+        // Pongs are taken on the reader thread, so a busy monitor loop does not miss them;
+        // a parent that closes its end of the pipe is gone
+        let reader = Arc::clone(&exchange);
+        let requests = StdinCommands::spawn(
+            move |line| {
+                let is_pong = is_pong(line);
+                if is_pong {
+                    reader.last_pong.store(reader.now_ms(), Ordering::Relaxed);
+                }
+                is_pong
+            },
+            true,
+        );
+
+        let timeout = interval * MISSED_PONGS;
+        let checker = Arc::clone(&exchange);
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            let (ping, pong) = (checker.last_ping.load(Ordering::Relaxed), checker.last_pong.load(Ordering::Relaxed));
+            if is_unanswered(ping, pong, checker.now_ms(), timeout) {
+                log::info!("Parent did not answer pings for {}s, stopping", timeout.as_secs());
+                std::process::exit(1);
+            }
+        });
+
+        Heartbeat { interval, exchange, requests, next_ping: Instant::now(), seq: 0 }
+    }
+
+    /// The ping line to write to stdout, when one is due
+    pub fn ping(&mut self, now: Instant) -> Option<String> {
+        if now < self.next_ping {
+            return None;
+        }
+        self.next_ping = now + self.interval;
+        self.seq += 1;
+        // An unanswered ping keeps its time, so the timeout runs from the oldest one
+        let pong = self.exchange.last_pong.load(Ordering::Relaxed);
+        if self.exchange.last_ping.load(Ordering::Relaxed) <= pong {
+            self.exchange.last_ping.store(self.exchange.now_ms(), Ordering::Relaxed);
+        }
+        Envelope::new(EventType::Ping, PingPayload { seq: self.seq }).to_json_line().ok()
+    }
+
+    /// Lines from the parent other than pongs, to be answered by the monitor loop
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.pending()
+    }
+}
+
+// "pong", {"command": "pong"} or {"cmd": "pong"}
+fn is_pong(line: &str) -> bool {
+    let line = line.trim();
+    line.eq_ignore_ascii_case("pong")
+        || serde_json::from_str::<serde_json::Value>(line)
+            .ok()
+            .and_then(|value| {
+                let command = value.get("command").or_else(|| value.get("cmd"))?.as_str()?;
+                Some(command.eq_ignore_ascii_case("pong"))
+            })
+            .unwrap_or(false)
+}
+
+fn is_unanswered(last_ping: u64, last_pong: u64, now: u64, timeout: Duration) -> bool {
+    last_ping > last_pong && now.saturating_sub(last_ping) > timeout.as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pongs_and_unanswered_pings() {
+        assert!(is_pong("pong\n"));
+        assert!(is_pong(r#"{"command": "PONG"}"#));
+        assert!(is_pong(r#"{"cmd": "pong"}"#));
+        assert!(!is_pong("ping"));
+        assert!(!is_pong(r#"{"command": "status"}"#));
+
+        let timeout = Duration::from_secs(15);
+        assert!(!is_unanswered(10_000, 12_000, 60_000, timeout));
+        assert!(!is_unanswered(10_000, 0, 20_000, timeout));
+        assert!(is_unanswered(10_000, 0, 30_000, timeout));
+    }
+}
@@ -0,0 +1,129 @@
+// Webhook notifications for call start/end
+// POSTs the enveloped `call_started` / `call_ended` event to `--webhook-url` so
+// services can receive calls without tailing the log. Deliveries run on a
+// background thread with exponential backoff; the monitor loop never waits on HTTP.
+// Which events are sent is up to the output router (calls only by default).
+
+use crate::output::EventType;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Header carrying `sha256=<hex HMAC of the body>` when a secret is configured
+pub const SIGNATURE_HEADER: &str = "X-Validator-Signature";
+/// Header carrying the event type (`call_started` / `call_ended`)
+pub const EVENT_HEADER: &str = "X-Validator-Event";
+
+/// Attempts per event before giving up
+const MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first retry; doubles on each further attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Delivery {
+    event_type: String,
+    body: String,
+}
+
+/// Queues call events for delivery to a webhook endpoint
+pub struct WebhookSink {
+    queue: Sender<Delivery>,
+}
+
+impl WebhookSink {
+    /// Start the delivery thread for `url`, signing bodies with `secret` if given
+    pub fn start(url: String, secret: Option<String>) -> Self {
+        let (queue, deliveries) = mpsc::channel::<Delivery>();
+
+        thread::spawn(move || {
+            let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+            for delivery in deliveries {
+                deliver(&agent, &url, secret.as_deref(), &delivery);
+            }
+        });
+
+        WebhookSink { queue }
+    }
+
+    /// Queue one enveloped line for delivery
+    pub fn send(&self, event_type: EventType, body: String) {
+        let event_type = serde_json::to_value(event_type)
+            .ok()
+            .and_then(|value| value.as_str().map(|s| s.to_string()))
+            .unwrap_or_default();
+
+        let _ = self.queue.send(Delivery { event_type, body });
+    }
+}
+
+fn deliver(agent: &ureq::Agent, url: &str, secret: Option<&str>, delivery: &Delivery) {
+    let mut backoff = INITIAL_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = agent
+            .post(url)
+            .set("Content-Type", "application/json")
+            .set(EVENT_HEADER, &delivery.event_type);
+        if let Some(secret) = secret {
+            request = request.set(SIGNATURE_HEADER, &format!("sha256={}", sign(secret, &delivery.body)));
+        }
+
+        match request.send_string(&delivery.body) {
+            Ok(_) => return,
+            // Client errors will not succeed on retry (except rate limiting)
+            Err(ureq::Error::Status(code, _)) if (400..500).contains(&code) && code != 429 => {
+                log::warn!("{} rejected with HTTP {}, dropping", delivery.event_type, code);
+                return;
+            }
+            Err(e) => {
+                if attempt == MAX_ATTEMPTS {
+                    log::warn!("{} failed after {} attempts: {}", delivery.event_type, attempt, e);
+                    return;
+                }
+                log::warn!("{} attempt {} failed: {} (retrying in {}s)",
+                    delivery.event_type, attempt, e, backoff.as_secs());
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Whether `header` is the `sha256=<hex>` signature of `body` under `secret`
+pub fn verify(secret: &str, body: &[u8], header: &str) -> bool {
+    let Some(signature) = header.strip_prefix("sha256=").and_then(|digest| hex::decode(digest).ok()) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_rfc4231() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let header = format!("sha256={}", sign("Jefe", "what do ya want for nothing?"));
+        assert!(verify("Jefe", b"what do ya want for nothing?", &header));
+        assert!(!verify("Jefe", b"what do ya want for something?", &header));
+        assert!(!verify("Jefe", b"what do ya want for nothing?", "5bdcc146"));
+    }
+}
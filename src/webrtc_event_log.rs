@@ -0,0 +1,66 @@
+// Chromium WebRTC event log reader (`--webrtc-log-dir`)
+// When event log recording is enabled (chrome://webrtc-internals "Enable diagnostic
+// event recording", or the WebRtcEventLogCollectionAllowed policy with a local output
+// path), Chromium writes one file per PeerConnection named
+// `<base>.<render process id>_<peer connection id>[.log]` and appends to it while the
+// connection is alive. A recently written file is direct evidence of a live
+// PeerConnection, unlike the UDP port heuristic in network_monitor.rs. The render process
+// id is Chromium's own render process host id, not an OS pid, so a live log only says
+// that the browser holds a PeerConnection somewhere.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// A log not written for this long belongs to a closed PeerConnection
+const ACTIVE_WINDOW: Duration = Duration::from_secs(15);
+
+pub struct WebRtcEventLogs {
+    dir: PathBuf,
+}
+
+impl WebRtcEventLogs {
+    pub fn new(dir: &Path) -> Self {
+        WebRtcEventLogs { dir: dir.to_path_buf() }
+    }
+
+    /// Whether a PeerConnection log was written within the active window
+    pub fn any_active(&self) -> bool {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return false,
+        };
+
+        let now = SystemTime::now();
+        entries
+            .flatten()
+            .filter(|entry| parse_log_name(&entry.file_name().to_string_lossy()).is_some())
+            .any(|entry| {
+                entry
+                    .metadata()
+                    .and_then(|meta| meta.modified())
+                    .map(|modified| now.duration_since(modified).unwrap_or(Duration::ZERO) < ACTIVE_WINDOW)
+                    .unwrap_or(false)
+            })
+    }
+}
+
+/// Extract `(render process id, peer connection id)` from a log file name
+fn parse_log_name(name: &str) -> Option<(u32, u32)> {
+    let stem = name.strip_suffix(".log").unwrap_or(name);
+    let suffix = stem.rsplit('.').next()?;
+    let (render, peer) = suffix.split_once('_')?;
+    Some((render.parse().ok()?, peer.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_name() {
+        assert_eq!(parse_log_name("event_log.4242_3.log"), Some((4242, 3)));
+        assert_eq!(parse_log_name("webrtc.17_1"), Some((17, 1)));
+        assert_eq!(parse_log_name("notes.txt"), None);
+    }
+}
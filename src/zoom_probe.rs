@@ -0,0 +1,131 @@
+// Zoom meeting probe
+// Confirms a Zoom meeting from Zoom's own UI instead of its audio sessions, which go
+// quiet when everyone is muted. On Windows the meeting window has a dedicated window
+// class; elsewhere the meeting window title is matched against its localized variants.
+// Only a found meeting counts: no meeting window proves nothing (Zoom Phone calls,
+// meeting window closed to the tray).
+
+use serde::{Deserialize, Serialize};
+
+/// Window classes of the Windows meeting window (classic and Workplace clients)
+#[cfg(target_os = "windows")]
+const MEETING_WINDOW_CLASSES: &[&str] = &["ZPContentViewWndClass", "ConfMultiTabContentWndClass"];
+
+/// Meeting window titles of localized clients, lowercase
+const MEETING_TITLES: &[&str] = &[
+    "zoom meeting",
+    "zoom webinar",
+    "zoom-meeting",      // de
+    "réunion zoom",      // fr
+    "reunión de zoom",   // es
+    "reunião zoom",      // pt
+    "riunione zoom",     // it
+    "zoom ミーティング", // ja
+    "zoom 会议",         // zh-CN
+    "zoom 會議",         // zh-TW
+    "zoom 회의",         // ko
+];
+
+/// A Zoom meeting window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZoomMeeting {
+    pub process_id: u32,
+    pub window_title: String,
+}
+
+/// Whether `title` is a Zoom meeting window title in any supported language
+pub fn is_meeting_title(title: &str) -> bool {
+    let title = crate::titles::normalize(title);
+    MEETING_TITLES.iter().any(|variant| title.contains(variant))
+}
+
+/// The open Zoom meeting window, if any
+#[cfg(target_os = "windows")]
+pub fn probe() -> Option<ZoomMeeting> {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    unsafe extern "system" fn enum_window_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let found = &mut *(lparam.0 as *mut Option<ZoomMeeting>);
+
+        let mut class = [0u16; 256];
+        let length = GetClassNameW(hwnd, &mut class);
+        if length <= 0 || !IsWindowVisible(hwnd).as_bool() {
+            return BOOL(1);
+        }
+        let class = String::from_utf16_lossy(&class[..length as usize]);
+        if !MEETING_WINDOW_CLASSES.contains(&class.as_str()) {
+            return BOOL(1);
+        }
+
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id as *mut u32));
+        let mut title = [0u16; 512];
+        let length = GetWindowTextW(hwnd, &mut title).max(0) as usize;
+        *found = Some(ZoomMeeting {
+            process_id,
+            window_title: String::from_utf16_lossy(&title[..length]),
+        });
+        BOOL(0) // Stop enumeration
+    }
+
+    let mut found: Option<ZoomMeeting> = None;
+    unsafe {
+        let _ = EnumWindows(Some(enum_window_callback), LPARAM(&mut found as *mut _ as isize));
+    }
+    found
+}
+
+/// The open Zoom meeting window, if any
+#[cfg(target_os = "linux")]
+pub fn probe() -> Option<ZoomMeeting> {
+    let processes = procfs::process::all_processes().ok()?;
+    let zoom_pids = processes
+        .flatten()
+        .filter(|process| process.stat().map_or(false, |stat| stat.comm.starts_with("zoom")))
+        .map(|process| process.pid as u32);
+    find_meeting_window(zoom_pids)
+}
+
+/// The open Zoom meeting window, if any
+#[cfg(target_os = "macos")]
+pub fn probe() -> Option<ZoomMeeting> {
+    use std::process::Command;
+
+    let output = crate::subprocess::output(Command::new("pgrep").args(["-x", "zoom.us"])).ok()?;
+    let zoom_pids = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+        .collect::<Vec<_>>();
+    find_meeting_window(zoom_pids)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+pub fn probe() -> Option<ZoomMeeting> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn find_meeting_window(zoom_pids: impl IntoIterator<Item = u32>) -> Option<ZoomMeeting> {
+    use crate::platform::PlatformUtils;
+
+    zoom_pids.into_iter().find_map(|process_id| {
+        let window_title = <() as PlatformUtils>::get_window_title(process_id).ok()?;
+        is_meeting_title(&window_title).then_some(ZoomMeeting { process_id, window_title })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meeting_titles_across_locales() {
+        assert!(is_meeting_title("Zoom Meeting"));
+        assert!(is_meeting_title("Zoom-Meeting 40-Minuten"));
+        assert!(is_meeting_title("Zoom ミーティング"));
+        assert!(is_meeting_title("Réunion Zoom"));
+        assert!(!is_meeting_title("Zoom Workplace"));
+        assert!(!is_meeting_title("Zoom"));
+    }
+}